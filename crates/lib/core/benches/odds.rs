@@ -0,0 +1,49 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! Times the simulation of the odds of each pool of play against the budget of an interactive odds display: 10 000
+//! rolls in under 5ms.
+//!
+//! Run with `cargo bench -p darkforge --bench odds`.
+
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use darkforge::odds::{MAX_TABLED_POOL, RollSpec, simulate};
+
+/// The number of rolls simulated for each estimate.
+const ITERATIONS: u32 = 10_000;
+/// How many times each pool is simulated.
+const RUNS: u32 = 50;
+/// The longest a simulation may take to be shown as the pool changes.
+const BUDGET: Duration = Duration::from_millis(5);
+
+fn main() {
+    println!("{:<8} {:>12} {:>12}", "pool", "mean (ms)", "budget (ms)");
+    for pool in 0..=MAX_TABLED_POOL {
+        let spec = RollSpec::new(pool);
+        let started = Instant::now();
+        for _ in 0..RUNS {
+            black_box(simulate(black_box(spec), ITERATIONS));
+        }
+        let mean = started.elapsed() / RUNS;
+
+        println!(
+            "{pool:<8} {:>12.3} {:>12.3}{}",
+            mean.as_secs_f64() * 1_000.0,
+            BUDGET.as_secs_f64() * 1_000.0,
+            if mean > BUDGET { "  over budget" } else { "" }
+        );
+    }
+}
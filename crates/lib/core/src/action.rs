@@ -0,0 +1,662 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! Action rolls and the resolution of their consequences.
+//!
+//! [`Campaign::resolve_action`] takes the result of an action roll, the consequences the GM proposes and the
+//! resistance the player chooses for each of them. It rolls the resistance, then applies the resulting stress, harm
+//! and clock ticks in a single transaction.
+
+use std::fmt::{self, Display, Formatter};
+
+#[cfg(feature = "store")]
+use darkforge_data::store::World;
+use darkforge_rng::dice::Dice;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[cfg(feature = "store")]
+use crate::{
+    campaign::{Campaign, CampaignError, Result},
+    changeset::{Access, Changeset},
+    clock::Clock,
+    permission::Actor,
+};
+use crate::{
+    character::{Harm, Stress},
+    consequence::HarmConsequence,
+    context::RollContext,
+    effect::Effect,
+};
+
+/// The outcome of an action roll, from worst to best.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Outcome {
+    /// No die above 3: things go badly.
+    Failure,
+    /// Highest die is 4 or 5: success with a consequence.
+    Partial,
+    /// Highest die is 6: full success.
+    Success,
+    /// Several 6s: success with increased effect.
+    Critical,
+}
+
+impl Outcome {
+    /// Reads the outcome of the kept dice of a roll.
+    #[must_use]
+    #[expect(clippy::naive_bytecount, reason = "Dice pools hold a handful of dice")]
+    pub fn from_dice(dice: &[u8]) -> Outcome {
+        match (dice.iter().filter(|&&die| die == 6).count(), dice.iter().max()) {
+            (2.., _) => Outcome::Critical,
+            (1, _) => Outcome::Success,
+            (_, Some(4 | 5)) => Outcome::Partial,
+            _ => Outcome::Failure,
+        }
+    }
+}
+
+/// A rule keeping only some of the dice rolled for a pool, for hacks that e.g. "roll an extra die and drop the
+/// highest".
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case", tag = "kind", content = "dice")]
+pub enum KeepDrop {
+    /// Roll that many extra dice and drop as many of the highest, a disadvantage.
+    DropHighest(u8),
+    /// Roll that many extra dice and drop as many of the lowest, an advantage.
+    DropLowest(u8),
+    /// Keep only that many of the highest dice of the pool.
+    KeepHighest(u8),
+    /// Keep only that many of the lowest dice of the pool.
+    KeepLowest(u8),
+}
+
+impl KeepDrop {
+    /// Returns the number of extra dice rolled for the rule.
+    #[must_use]
+    pub fn extra(self) -> u8 {
+        match self {
+            KeepDrop::DropHighest(dice) | KeepDrop::DropLowest(dice) => dice,
+            KeepDrop::KeepHighest(_) | KeepDrop::KeepLowest(_) => 0,
+        }
+    }
+
+    /// Returns the dice kept by the rule, in the order they were rolled.
+    #[must_use]
+    pub fn select(self, dice: &[u8]) -> Vec<u8> {
+        let (kept, highest) = match self {
+            KeepDrop::DropHighest(dropped) => (dice.len().saturating_sub(usize::from(dropped)), false),
+            KeepDrop::DropLowest(dropped) => (dice.len().saturating_sub(usize::from(dropped)), true),
+            KeepDrop::KeepHighest(kept) => (usize::from(kept), true),
+            KeepDrop::KeepLowest(kept) => (usize::from(kept), false),
+        };
+
+        let mut order = (0..dice.len()).collect::<Vec<_>>();
+        order.sort_by_key(|&index| dice[index]);
+        if highest {
+            order.reverse();
+        }
+        order.truncate(kept);
+        order.sort_unstable();
+
+        order.into_iter().map(|index| dice[index]).collect()
+    }
+}
+
+impl Display for KeepDrop {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            KeepDrop::DropHighest(dice) => write!(f, "+{dice}d, drop highest"),
+            KeepDrop::DropLowest(dice) => write!(f, "+{dice}d, drop lowest"),
+            KeepDrop::KeepHighest(dice) => write!(f, "keep highest {dice}"),
+            KeepDrop::KeepLowest(dice) => write!(f, "keep lowest {dice}"),
+        }
+    }
+}
+
+/// Rolls a pool of d6 for the given rating and returns the dice kept. A rating of zero rolls two dice and keeps the
+/// lowest.
+pub fn roll_pool(dice: &impl Dice, rating: u8) -> Vec<u8> {
+    roll_pool_with(dice, rating, &[])
+}
+
+/// Rolls a pool of d6 for the given rating along with the extra dice of the keep and drop rules, then applies the rules
+/// in order and returns the dice kept. A rating of zero keeps the lowest of two dice once the rules are applied.
+pub fn roll_pool_with(dice: &impl Dice, rating: u8, keep_drop: &[KeepDrop]) -> Vec<u8> {
+    let zero = (rating == 0).then_some(KeepDrop::KeepLowest(1));
+
+    keep_drop
+        .iter()
+        .chain(&zero)
+        .fold(dice.roll_pool(pool_size(rating, keep_drop)), |kept, rule| rule.select(&kept))
+}
+
+/// Returns the number of dice rolled for the given rating along with the extra dice of the keep and drop rules, before
+/// any are kept, see [`roll_pool_with`].
+#[must_use]
+pub fn pool_size(rating: u8, keep_drop: &[KeepDrop]) -> usize {
+    let pool = if rating == 0 { 2 } else { usize::from(rating) };
+
+    pool + keep_drop.iter().map(|rule| usize::from(rule.extra())).sum::<usize>()
+}
+
+/// The result of a fortune roll, made to see how things turn out when no character's action is at stake.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct FortuneRoll {
+    /// The dice kept.
+    pub dice: Vec<u8>,
+    /// The outcome of the roll.
+    pub outcome: Outcome,
+}
+
+impl FortuneRoll {
+    /// Rolls a fortune roll for the given rating.
+    pub fn roll(dice: &impl Dice, rating: u8) -> FortuneRoll {
+        let dice = roll_pool(dice, rating);
+        let outcome = Outcome::from_dice(&dice);
+
+        FortuneRoll { dice, outcome }
+    }
+}
+
+/// The result of an action roll.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ActionRoll {
+    /// The dice kept.
+    pub dice: Vec<u8>,
+    /// The effect level of the action.
+    pub effect: Effect,
+    /// The stress paid before rolling, e.g. for pushing yourself, taken along with the stress of resistance.
+    #[serde(default)]
+    pub stress: u8,
+    /// The fictional context of the roll, if given, recorded along with its resolution.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<RollContext>,
+    /// The dice added to or taken from the rating to make up the pool, and why.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub modifiers: Vec<Modifier>,
+}
+
+/// A die added to or taken from the pool of an action roll, shown in the roll's breakdown.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case", tag = "kind")]
+pub enum Modifier {
+    /// The character pushed themselves.
+    Pushed,
+    /// The player accepted a devil's bargain.
+    Bargained,
+    /// A teammate helped the character, as they must when suffering from severe harm.
+    Assisted,
+    /// The character suffers from moderate harm and did not push through it.
+    Harm {
+        /// The harm, e.g. "Deep cut".
+        description: String,
+    },
+}
+
+impl Modifier {
+    /// Returns the number of dice added to the pool, negative if taken from it.
+    #[must_use]
+    pub fn dice(&self) -> i8 {
+        match self {
+            Modifier::Pushed | Modifier::Bargained | Modifier::Assisted => 1,
+            Modifier::Harm { .. } => -1,
+        }
+    }
+}
+
+impl Display for Modifier {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Modifier::Pushed => write!(f, "pushed: +1d"),
+            Modifier::Bargained => write!(f, "devil's bargain: +1d"),
+            Modifier::Assisted => write!(f, "assisted: +1d"),
+            Modifier::Harm { description } => write!(f, "{description}: -1d"),
+        }
+    }
+}
+
+impl ActionRoll {
+    /// Returns the outcome of the roll.
+    #[must_use]
+    pub fn outcome(&self) -> Outcome {
+        Outcome::from_dice(&self.dice)
+    }
+}
+
+/// A consequence the GM inflicts on a partial success or a failure.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Consequence {
+    /// The character suffers harm.
+    Harm(HarmConsequence),
+    /// A clock ticks, e.g. the alarm being raised.
+    Complication {
+        /// The clock entity.
+        clock: Uuid,
+        /// The number of segments to fill.
+        ticks: u8,
+    },
+    /// The action's effect is one level lower.
+    ReducedEffect,
+}
+
+impl Consequence {
+    /// Returns the consequence reduced by one level after being resisted, or `None` if it is avoided entirely.
+    #[must_use]
+    pub fn reduced(self) -> Option<Consequence> {
+        match self {
+            Consequence::Harm(HarmConsequence { level, description }) if level > 1 => Some(Consequence::Harm(HarmConsequence {
+                level: level - 1,
+                description,
+            })),
+            Consequence::Complication { clock, ticks } if ticks > 1 => Some(Consequence::Complication { clock, ticks: ticks - 1 }),
+            _ => None,
+        }
+    }
+
+    /// Returns the consequence worsened by the given number of levels, or lessened if negative, see
+    /// [`Factors::severity`](crate::effect::Factors::severity). Harm stays between lesser and fatal, and complications
+    /// tick at least one segment.
+    #[must_use]
+    pub fn scaled(self, levels: i8) -> Consequence {
+        let scale = |value: u8, max: u8| value.saturating_add_signed(levels).clamp(1, max);
+        match self {
+            Consequence::Harm(HarmConsequence { level, description }) => Consequence::Harm(HarmConsequence {
+                level: scale(level, Harm::FATAL),
+                description,
+            }),
+            Consequence::Complication { clock, ticks } => Consequence::Complication {
+                clock,
+                ticks: scale(ticks, u8::MAX),
+            },
+            Consequence::ReducedEffect => Consequence::ReducedEffect,
+        }
+    }
+}
+
+/// A proposed consequence along with the player's choice to resist it.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Proposal {
+    /// The consequence.
+    pub consequence: Consequence,
+    /// The rating of the attribute the player resists with, if they choose to resist.
+    pub resist: Option<u8>,
+}
+
+/// A resistance roll and the stress it cost.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Resistance {
+    /// The dice kept.
+    pub dice: Vec<u8>,
+    /// The stress taken, 6 minus the highest die, or -1 on a critical.
+    pub stress: i8,
+}
+
+impl Resistance {
+    /// Reads the stress cost of the kept dice of a resistance roll.
+    #[must_use]
+    pub fn from_dice(dice: Vec<u8>) -> Resistance {
+        let stress = match Outcome::from_dice(&dice) {
+            Outcome::Critical => -1,
+            _ => 6 - i8::try_from(dice.iter().max().copied().unwrap_or(1)).unwrap_or(1),
+        };
+
+        Resistance { dice, stress }
+    }
+}
+
+/// Changes applied by resolving an action.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Delta {
+    /// The outcome of the action roll.
+    pub outcome: Outcome,
+    /// The effect of the action, after consequences.
+    pub effect: Effect,
+    /// The resistance rolls made, in the order of the proposals.
+    pub resistances: Vec<Resistance>,
+    /// The stress paid before rolling, e.g. for pushing yourself.
+    #[serde(default)]
+    pub paid: u8,
+    /// The character's stress after resistance.
+    pub stress: Stress,
+    /// True if the character took more stress than they could and suffered trauma, clearing their stress.
+    pub trauma: bool,
+    /// The levels harm was marked at, see [`Harm::suffer`].
+    pub harm: Vec<u8>,
+    /// The clocks ticked along with the segments filled.
+    pub clocks: Vec<(Uuid, u8)>,
+    /// The fictional context of the action roll, if given.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<RollContext>,
+}
+
+#[cfg(feature = "store")]
+impl Campaign {
+    /// Resolves an action roll made by the character.
+    ///
+    /// Consequences only apply on a partial success or a failure. Each consequence the player resists is rolled for
+    /// with `dice` and reduced by one level, or avoided entirely if it cannot be reduced further. The stress paid
+    /// before rolling and for resistance, the harm and the clock ticks are then applied along with a journal entry in a
+    /// single transaction.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::Permission`] if the actor does not own the character, [`CampaignError::MissingClock`]
+    /// if a complication ticks a clock that does not exist, or another [`CampaignError`] if the store cannot be
+    /// written, in which case nothing is applied.
+    pub async fn resolve_action(
+        &mut self, actor: Actor, character: Uuid, roll: &ActionRoll, proposals: Vec<Proposal>, dice: &impl Dice,
+    ) -> Result<Delta> {
+        self.authorize(actor, character).await?;
+
+        let outcome = roll.outcome();
+        let store = self.store();
+        let mut delta = Delta {
+            outcome,
+            effect: if outcome == Outcome::Critical {
+                roll.effect.shift(1)
+            } else {
+                roll.effect
+            },
+            resistances: Vec::new(),
+            paid: roll.stress,
+            stress: store.get::<Stress>(character).await?.unwrap_or_default(),
+            trauma: false,
+            harm: Vec::new(),
+            clocks: Vec::new(),
+            context: roll.context.clone(),
+        };
+
+        let mut stress = i16::from(delta.stress.value()) + i16::from(roll.stress);
+        let mut sheet = None;
+        let mut clocks = Vec::<(Uuid, Clock)>::new();
+        let proposals = if outcome >= Outcome::Success { Vec::new() } else { proposals };
+
+        for Proposal { consequence, resist } in proposals {
+            let consequence = match resist {
+                Some(rating) => {
+                    let resistance = Resistance::from_dice(roll_pool(dice, rating));
+                    stress = (stress + i16::from(resistance.stress)).max(0);
+                    delta.resistances.push(resistance);
+                    consequence.reduced()
+                }
+                None => Some(consequence),
+            };
+
+            match consequence {
+                Some(Consequence::Harm(harm)) => {
+                    let sheet = match &mut sheet {
+                        Some(sheet) => sheet,
+                        None => sheet.insert(store.get::<Harm>(character).await?.unwrap_or_default()),
+                    };
+                    delta.harm.push(sheet.suffer(harm.level, harm.description));
+                }
+                Some(Consequence::Complication { clock, ticks }) => {
+                    let index = if let Some(index) = clocks.iter().position(|(entity, _)| *entity == clock) {
+                        index
+                    } else {
+                        let loaded = store.get::<Clock>(clock).await?.ok_or(CampaignError::MissingClock(clock))?;
+                        clocks.push((clock, loaded));
+                        clocks.len() - 1
+                    };
+                    let ticked = clocks[index].1.tick(ticks);
+                    delta.clocks.push((clock, ticked));
+                }
+                Some(Consequence::ReducedEffect) => delta.effect = delta.effect.shift(-1),
+                None => {}
+            }
+        }
+
+        if stress > i16::from(Stress::MAX) {
+            delta.trauma = true;
+            stress = 0;
+        }
+        delta.stress = Stress::new(u8::try_from(stress).unwrap_or(Stress::MAX));
+
+        let mut changeset = Changeset::new(actor, Access::Owner(character)).insert(character, &delta.stress)?;
+        if let Some(sheet) = &sheet {
+            changeset = changeset.insert(character, sheet)?;
+        }
+        for (entity, clock) in &clocks {
+            changeset = changeset.insert(*entity, clock)?;
+        }
+
+        self.apply(changeset.record(Some(character), "action.resolved", &delta)?).await?;
+
+        Ok(delta)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::{cell::RefCell, collections::VecDeque};
+
+    use rstest::rstest;
+
+    use super::*;
+    #[cfg(feature = "store")]
+    use crate::campaign::tests::bootstrap;
+
+    /// Dice returning predetermined results.
+    pub(crate) struct Loaded(RefCell<VecDeque<u8>>);
+
+    impl Loaded {
+        pub(crate) fn new(results: &[u8]) -> Loaded {
+            Loaded(RefCell::new(results.iter().copied().collect()))
+        }
+    }
+
+    impl Dice for Loaded {
+        fn roll(&self) -> u8 {
+            self.0.borrow_mut().pop_front().unwrap_or(1)
+        }
+
+        fn roll_pool(&self, pool: usize) -> Vec<u8> {
+            (0..pool).map(|_| self.roll()).collect()
+        }
+
+        fn sides(&self) -> u8 {
+            6
+        }
+    }
+
+    fn harm(level: u8) -> Consequence {
+        Consequence::Harm(HarmConsequence {
+            level,
+            description: "Stabbed".into(),
+        })
+    }
+
+    #[rstest]
+    #[case::failure(&[1, 3, 2], Outcome::Failure)]
+    #[case::partial(&[5, 3], Outcome::Partial)]
+    #[case::success(&[6, 4], Outcome::Success)]
+    #[case::critical(&[6, 6, 1], Outcome::Critical)]
+    #[case::empty(&[], Outcome::Failure)]
+    fn should_read_outcome_from_dice(#[case] dice: &[u8], #[case] expect: Outcome) {
+        assert_eq!(expect, Outcome::from_dice(dice));
+    }
+
+    #[rstest]
+    #[case::zero(0, &[5, 2], vec![2])]
+    #[case::pool(2, &[5, 2], vec![5, 2])]
+    fn should_roll_pool_keeping_lowest_for_zero_rating(#[case] rating: u8, #[case] results: &[u8], #[case] expect: Vec<u8>) {
+        assert_eq!(expect, roll_pool(&Loaded::new(results), rating));
+    }
+
+    #[rstest]
+    #[case::disadvantage(2, &[KeepDrop::DropHighest(1)], &[6, 3, 5], vec![3, 5])]
+    #[case::advantage(1, &[KeepDrop::DropLowest(1)], &[2, 4], vec![4])]
+    #[case::keep_highest(3, &[KeepDrop::KeepHighest(2)], &[1, 6, 4], vec![6, 4])]
+    #[case::keep_lowest(3, &[KeepDrop::KeepLowest(1)], &[5, 2, 4], vec![2])]
+    #[case::zero_with_advantage(0, &[KeepDrop::DropLowest(1)], &[1, 5, 4], vec![4])]
+    #[case::rules_in_order(2, &[KeepDrop::DropLowest(1), KeepDrop::KeepHighest(1)], &[3, 6, 2], vec![6])]
+    #[case::drop_everything(1, &[KeepDrop::KeepHighest(0)], &[6], vec![])]
+    fn should_roll_pool_with_keep_drop_rules(#[case] rating: u8, #[case] keep_drop: &[KeepDrop], #[case] results: &[u8], #[case] expect: Vec<u8>) {
+        assert_eq!(expect, roll_pool_with(&Loaded::new(results), rating, keep_drop));
+    }
+
+    #[rstest]
+    #[case::six(vec![6, 2], 0)]
+    #[case::four(vec![4], 2)]
+    #[case::one(vec![1, 1], 5)]
+    #[case::critical(vec![6, 6], -1)]
+    fn should_cost_stress_from_highest_resistance_die(#[case] dice: Vec<u8>, #[case] expect: i8) {
+        assert_eq!(expect, Resistance::from_dice(dice).stress);
+    }
+
+    #[rstest]
+    #[case::worse_harm(harm(2), 1, harm(3))]
+    #[case::fatal_harm(harm(3), 3, harm(4))]
+    #[case::lesser_harm(harm(2), -3, harm(1))]
+    #[case::more_ticks(Consequence::Complication { clock: Uuid::nil(), ticks: 2 }, 2, Consequence::Complication { clock: Uuid::nil(), ticks: 4 })]
+    #[case::one_tick(Consequence::Complication { clock: Uuid::nil(), ticks: 2 }, -2, Consequence::Complication { clock: Uuid::nil(), ticks: 1 })]
+    #[case::reduced_effect(Consequence::ReducedEffect, 1, Consequence::ReducedEffect)]
+    fn should_scale_consequence_severity(#[case] consequence: Consequence, #[case] levels: i8, #[case] expect: Consequence) {
+        assert_eq!(expect, consequence.scaled(levels));
+    }
+
+    #[cfg(feature = "store")]
+    #[tokio::test]
+    async fn should_resolve_action_with_resistance() {
+        let mut campaign = bootstrap().await;
+        let store = campaign.store();
+        let pc = store.spawn().await.expect("should have spawned character");
+        store.insert(pc, &Stress::new(2)).await.expect("should have inserted stress");
+        let alarm = store.spawn().await.expect("should have spawned clock");
+        store
+            .insert(alarm, &Clock::new("Alarm", 4).expect("should have created clock"))
+            .await
+            .expect("should have inserted clock");
+
+        let roll = ActionRoll {
+            dice: vec![4, 2],
+            effect: Effect::Standard,
+            stress: 0,
+            context: None,
+            modifiers: Vec::new(),
+        };
+        let proposals = vec![
+            Proposal {
+                consequence: harm(2),
+                resist: Some(2),
+            },
+            Proposal {
+                consequence: Consequence::Complication { clock: alarm, ticks: 2 },
+                resist: None,
+            },
+            Proposal {
+                consequence: Consequence::ReducedEffect,
+                resist: Some(1),
+            },
+        ];
+
+        let delta = campaign
+            .resolve_action(Actor::Gm, pc, &roll, proposals, &Loaded::new(&[3, 5, 6]))
+            .await
+            .expect("should have resolved action");
+
+        assert_eq!(
+            Delta {
+                outcome: Outcome::Partial,
+                effect: Effect::Standard,
+                resistances: vec![Resistance { dice: vec![3, 5], stress: 1 }, Resistance { dice: vec![6], stress: 0 },],
+                paid: 0,
+                stress: Stress::new(3),
+                trauma: false,
+                harm: vec![1],
+                clocks: vec![(alarm, 2)],
+                context: None,
+            },
+            delta
+        );
+
+        let store = campaign.store();
+        assert_eq!(Some(Stress::new(3)), store.get(pc).await.expect("should have read stress"));
+        let clock = store
+            .get::<Clock>(alarm)
+            .await
+            .expect("should have read clock")
+            .expect("should have a clock");
+        assert_eq!(2, clock.filled());
+    }
+
+    #[cfg(feature = "store")]
+    #[tokio::test]
+    async fn should_suffer_trauma_when_stress_overflows() {
+        let mut campaign = bootstrap().await;
+        let store = campaign.store();
+        let pc = store.spawn().await.expect("should have spawned character");
+        store.insert(pc, &Stress::new(8)).await.expect("should have inserted stress");
+
+        let roll = ActionRoll {
+            dice: vec![1],
+            effect: Effect::Standard,
+            stress: 0,
+            context: None,
+            modifiers: Vec::new(),
+        };
+        let proposals = vec![Proposal {
+            consequence: harm(3),
+            resist: Some(1),
+        }];
+
+        let delta = campaign
+            .resolve_action(Actor::Gm, pc, &roll, proposals, &Loaded::new(&[2]))
+            .await
+            .expect("should have resolved action");
+
+        assert!(delta.trauma);
+        assert_eq!(Stress::default(), delta.stress);
+        assert_eq!(vec![2], delta.harm);
+    }
+
+    #[cfg(feature = "store")]
+    #[tokio::test]
+    async fn should_apply_nothing_given_missing_clock() {
+        let mut campaign = bootstrap().await;
+        let store = campaign.store();
+        let pc = store.spawn().await.expect("should have spawned character");
+
+        let roll = ActionRoll {
+            dice: vec![2],
+            effect: Effect::Standard,
+            stress: 0,
+            context: None,
+            modifiers: Vec::new(),
+        };
+        let missing = Uuid::new_v4();
+        let proposals = vec![
+            Proposal {
+                consequence: harm(1),
+                resist: None,
+            },
+            Proposal {
+                consequence: Consequence::Complication { clock: missing, ticks: 1 },
+                resist: None,
+            },
+        ];
+
+        let err = campaign
+            .resolve_action(Actor::Gm, pc, &roll, proposals, &Loaded::new(&[]))
+            .await
+            .expect_err("should have failed to tick missing clock");
+
+        assert!(
+            matches!(err, CampaignError::MissingClock(clock) if clock == missing),
+            "unexpected error: {err}"
+        );
+        assert_eq!(None, campaign.store().get::<Harm>(pc).await.expect("should have read harm"));
+    }
+}
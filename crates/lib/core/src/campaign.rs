@@ -0,0 +1,1302 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! A campaign is a game world saved in a store, bootstrapped from a content pack.
+
+#[cfg(feature = "store")]
+use std::path::Path;
+
+#[cfg(feature = "store")]
+use darkforge_data::store::sql::sqlite::{MIGRATIONS, SqliteError, SqliteStore, StoreMetrics};
+use darkforge_data::{
+    CodecError, Component, Persisted,
+    markup::{MarkupError, Target},
+    pack::{Changed, PackError},
+    store::reference::ReferenceViolation,
+};
+#[cfg(feature = "store")]
+use darkforge_data::{
+    collation::Collation,
+    markup::Markup,
+    pack::{EntryChange, Pack, Slug},
+    store::{
+        Content, Migrator, Replicate, World,
+        delta::Delta,
+        diff::Snapshot,
+        reference::{Deletion, ReferencePolicy},
+        schema::UpgradeComponents,
+    },
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::{
+    changeset::ChangesetError,
+    clock::ClockError,
+    crew::CrewError,
+    item::ArmorKind,
+    permission::{Actor, PermissionError},
+    statblock::BlockError,
+    template::TemplateError,
+};
+#[cfg(feature = "store")]
+use crate::{
+    changeset::{Access, Changeset},
+    clock::Clock,
+    dashboard::GmDashboard,
+    faction::{self, Faction, FactionTemplate},
+    permission::Owner,
+};
+
+/// Kind of the journal entries recorded for each content entry changed by reloading the pack.
+pub const CONTENT_CHANGED: &str = "content.changed";
+
+/// Relationship linking a campaign to its factions.
+pub const FACTION: &str = "faction";
+/// Relationship linking an entity to its clocks.
+pub const CLOCK: &str = "clock";
+
+/// Error type for campaign operations.
+#[derive(Error, Debug)]
+pub enum CampaignError {
+    /// The store could not be read or written.
+    #[cfg(feature = "store")]
+    #[error(transparent)]
+    Store(#[from] SqliteError),
+    /// The content pack could not be read.
+    #[error(transparent)]
+    Pack(#[from] PackError),
+    /// The content pack defines an invalid clock.
+    #[error("invalid clock for faction {faction}: {source}")]
+    InvalidClock {
+        /// The label of the faction defining the clock.
+        faction: String,
+        /// The reason the clock is invalid.
+        source: ClockError,
+    },
+    /// The store already holds a campaign.
+    #[error("store already holds campaign {0}")]
+    AlreadyExists(Uuid),
+    /// The store does not hold a campaign.
+    #[error("store does not hold a campaign")]
+    NotFound,
+    /// The store does not hold a campaign with that id.
+    #[error("campaign {0} does not exist")]
+    MissingCampaign(Uuid),
+    /// The campaign cannot be deleted while it is open.
+    #[error("campaign {0} is open")]
+    CampaignOpen(Uuid),
+    /// A character bets more coin than their stash holds.
+    #[error("cannot stake {stake} coin out of a stash of {stash}")]
+    Overstaked {
+        /// The coin staked.
+        stake: u8,
+        /// The coin in the character's stash.
+        stash: u8,
+    },
+    /// The store holds a component that cannot be decoded.
+    #[error("invalid component: {0}")]
+    Codec(#[from] CodecError),
+    /// The chosen armor cannot be used to mitigate harm.
+    #[error("{0:?} armor is not available")]
+    ArmorUnavailable(ArmorKind),
+    /// A consequence ticks a clock that does not exist.
+    #[error("clock {0} does not exist")]
+    MissingClock(Uuid),
+    /// A faction does not exist or does not take part in a contest.
+    #[error("faction {0} does not exist")]
+    MissingFaction(Uuid),
+    /// A one-shot cannot be generated from a pack without factions to oppose the crew.
+    #[error("pack holds no faction to oppose the crew")]
+    NoFaction,
+    /// A contest does not exist.
+    #[error("contest {0} does not exist")]
+    MissingContest(Uuid),
+    /// A contest has already been won.
+    #[error("contest {0} is already resolved")]
+    ContestResolved(Uuid),
+    /// A crew breaks the crew creation rules.
+    #[error(transparent)]
+    Crew(#[from] CrewError),
+    /// An entity is not a crew.
+    #[error("crew {0} does not exist")]
+    MissingCrew(Uuid),
+    /// An entity is not a character.
+    #[error("character {0} does not exist")]
+    MissingCharacter(Uuid),
+    /// A trauma condition does not exist in the content pack.
+    #[error("trauma {0} does not exist")]
+    MissingTrauma(Uuid),
+    /// A special ability does not exist in the content pack.
+    #[error("ability {0} does not exist")]
+    MissingAbility(Uuid),
+    /// A character has marked too many traumas to go on and must retire.
+    #[error("character {0} has too much trauma to go on")]
+    Retired(Uuid),
+    /// A character suffering from severe harm cannot act without help.
+    #[error("character {0} cannot act without help")]
+    NeedsHelp(Uuid),
+    /// No session was started with that number.
+    #[error("session {0} does not exist")]
+    MissingSession(usize),
+    /// No score was started with that number.
+    #[error("score {0} does not exist")]
+    MissingScore(usize),
+    /// A score is in progress and must end before another starts.
+    #[error("score {0} is still in progress")]
+    ScoreInProgress(usize),
+    /// No score is in progress.
+    #[error("no score is in progress")]
+    NoScore,
+    /// A journal entry is not a roll.
+    #[error("journal entry {0} is not a roll")]
+    MissingRoll(i64),
+    /// A chosen NPC or NPC template does not exist.
+    #[error("npc {0} does not exist")]
+    MissingNpc(Uuid),
+    /// A pack other than the campaign's was given to reload.
+    #[error("campaign uses pack {expected}, not {found}")]
+    PackMismatch {
+        /// The name of the campaign's pack.
+        expected: String,
+        /// The name of the pack given.
+        found: String,
+    },
+    /// A text template of the content pack is invalid or names a placeholder that is not bound.
+    #[error(transparent)]
+    Template(#[from] TemplateError),
+    /// Stat blocks to import cannot be parsed.
+    #[error(transparent)]
+    Block(#[from] BlockError),
+    /// The results of physical dice cannot make a roll.
+    #[error(transparent)]
+    ManualRoll(#[from] crate::roll::ManualRollError),
+    /// A house rule cannot be parsed or is not in play.
+    #[error(transparent)]
+    Setup(#[from] crate::setup::SetupError),
+    /// A changeset breaks a mutation rule.
+    #[error(transparent)]
+    Changeset(#[from] ChangesetError),
+    /// An entity cannot be deleted while other entities refer to it.
+    #[error(transparent)]
+    Reference(#[from] ReferenceViolation),
+    /// The actor is not allowed to perform the mutation.
+    #[error(transparent)]
+    Permission(#[from] PermissionError),
+    /// A description is not valid markup.
+    #[error(transparent)]
+    Markup(#[from] MarkupError),
+    /// Links of a description point to nothing the campaign holds.
+    #[error("links to missing targets: {}", .0.iter().map(ToString::to_string).collect::<Vec<_>>().join(", "))]
+    DanglingLinks(Vec<Target>),
+    /// A content pack script does not exist or failed.
+    #[cfg(feature = "scripting")]
+    #[error(transparent)]
+    Script(#[from] crate::script::ScriptError),
+}
+
+/// Result type for campaign operations.
+pub type Result<T> = std::result::Result<T, CampaignError>;
+
+/// Information about a campaign, attached to the campaign entity.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct CampaignInfo {
+    /// The name of the campaign.
+    pub name: String,
+    /// The name of the content pack the campaign was bootstrapped from.
+    pub pack: String,
+    /// Whether the campaign was archived, hiding it from [`Campaign::open`] while keeping it in the store.
+    #[serde(default)]
+    pub archived: bool,
+}
+
+impl Component for CampaignInfo {}
+
+/// Journal entry recorded about a content entry changed by reloading the pack.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ContentChanged {
+    /// The unique id of the entry.
+    pub id: Uuid,
+    /// The category of the entry.
+    pub category: String,
+    /// How the entry changed.
+    pub change: Changed,
+}
+
+impl Persisted for CampaignInfo {
+    const KIND: &'static str = "campaign";
+}
+
+/// A journal entry recording who performed the event.
+#[derive(Serialize)]
+pub(crate) struct Event<'a, T: Serialize> {
+    pub(crate) actor: Actor,
+    pub(crate) data: &'a T,
+}
+
+/// A journal entry recorded by [`Campaign::record`], read back from the journal.
+#[cfg(feature = "store")]
+#[derive(Deserialize)]
+pub(crate) struct Logged<T> {
+    pub(crate) actor: Actor,
+    pub(crate) data: T,
+}
+
+/// Handle on a campaign saved in a store.
+#[cfg(feature = "store")]
+pub struct Campaign {
+    id: Uuid,
+    info: CampaignInfo,
+    store: SqliteStore,
+}
+
+#[cfg(feature = "store")]
+impl Campaign {
+    /// Initializes a fresh campaign in the store from the given content pack.
+    ///
+    /// Runs the migrations, loads the pack's static data, then creates the pack's factions along with their starting
+    /// status and clocks.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::AlreadyExists`] if the store already holds a campaign, or another [`CampaignError`] if
+    /// the pack is invalid or the store cannot be written.
+    pub async fn bootstrap(store: SqliteStore, name: impl Into<String>, pack: &Pack) -> Result<Campaign> {
+        let templates = pack.load::<FactionTemplate>(faction::CATEGORY)?;
+        let (campaign, _) = Campaign::found(store, name, pack, templates).await?;
+
+        Ok(campaign)
+    }
+
+    /// Initializes another campaign in the store from the given content pack, next to the campaigns it already holds, and
+    /// switches the store to it, e.g. to fill a new save slot.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CampaignError`] if the pack is invalid or the store cannot be written.
+    pub async fn create(store: SqliteStore, name: impl Into<String>, pack: &Pack) -> Result<Campaign> {
+        let templates = pack.load::<FactionTemplate>(faction::CATEGORY)?;
+        let (campaign, _) = Campaign::settle(store, name, pack, templates).await?;
+
+        Ok(campaign)
+    }
+
+    /// Initializes a fresh campaign in the store from the given content pack with the given factions, returning the
+    /// entity created for each faction along with the id of its template.
+    pub(crate) async fn found(
+        mut store: SqliteStore, name: impl Into<String>, pack: &Pack, templates: Vec<FactionTemplate>,
+    ) -> Result<(Campaign, Vec<(Uuid, Uuid)>)> {
+        store.apply(MIGRATIONS).await?;
+        if let Some((id, _)) = store.across::<CampaignInfo>().await?.first() {
+            return Err(CampaignError::AlreadyExists(*id));
+        }
+
+        Campaign::settle(store, name, pack, templates).await
+    }
+
+    /// Initializes a campaign in the store from the given content pack with the given factions, whatever campaigns the
+    /// store already holds. The campaign entity, its factions and their clocks are created in a single changeset, so
+    /// nothing is left behind if it fails.
+    pub(crate) async fn settle(
+        mut store: SqliteStore, name: impl Into<String>, pack: &Pack, templates: Vec<FactionTemplate>,
+    ) -> Result<(Campaign, Vec<(Uuid, Uuid)>)> {
+        let factions = templates
+            .into_iter()
+            .map(|template| {
+                let clocks = template
+                    .clocks
+                    .iter()
+                    .map(|clock| Clock::with_progress(clock.label.clone(), clock.segments, clock.filled))
+                    .collect::<std::result::Result<Vec<_>, _>>()
+                    .map_err(|source| CampaignError::InvalidClock {
+                        faction: template.descriptor.label().into(),
+                        source,
+                    })?;
+
+                Ok((template, clocks))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Campaign::upgrade(&mut store).await?;
+        store.load_pack(pack).await?;
+
+        let info = CampaignInfo {
+            name: name.into(),
+            pack: pack.name().into(),
+            archived: false,
+        };
+        let id = Uuid::new_v4();
+        store.scope_to(id);
+        let mut changeset = Changeset::new(Actor::Gm, Access::Gm).spawn(id).insert(id, &info)?;
+
+        let mut entities = Vec::with_capacity(factions.len());
+        for (template, clocks) in factions {
+            let entity = Uuid::new_v4();
+            changeset = changeset
+                .spawn(entity)
+                .insert(entity, &template.faction())?
+                .insert(entity, &template.status)?
+                .relate(id, entity, FACTION);
+
+            for clock in clocks {
+                let clock_entity = Uuid::new_v4();
+                changeset = changeset
+                    .spawn(clock_entity)
+                    .insert(clock_entity, &clock)?
+                    .relate(entity, clock_entity, CLOCK);
+            }
+            entities.push((template.id, entity));
+        }
+
+        let changeset = changeset.record(Some(id), "campaign.created", &info)?;
+        let mut campaign = Campaign { id, info, store };
+        campaign.apply(changeset).await?;
+
+        Ok((campaign, entities))
+    }
+
+    /// Opens the first campaign held in the store that was not archived, migrating the store and upgrading the
+    /// components it holds first.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::NotFound`] if the store does not hold such a campaign, or [`CampaignError::Store`] if it
+    /// cannot be migrated, upgraded or read.
+    pub async fn open(mut store: SqliteStore) -> Result<Campaign> {
+        Campaign::upgrade(&mut store).await?;
+        let (id, info) = store
+            .across::<CampaignInfo>()
+            .await?
+            .into_iter()
+            .find(|(_, info)| !info.archived)
+            .ok_or(CampaignError::NotFound)?;
+        store.scope_to(id);
+
+        Ok(Campaign { id, info, store })
+    }
+
+    /// Opens the campaign with the given id held in the store, archived or not, migrating the store and upgrading the
+    /// components it holds first.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::MissingCampaign`] if the store does not hold it, or [`CampaignError::Store`] if it
+    /// cannot be migrated, upgraded or read.
+    pub async fn open_id(mut store: SqliteStore, id: Uuid) -> Result<Campaign> {
+        Campaign::upgrade(&mut store).await?;
+        let (id, info) = store
+            .across::<CampaignInfo>()
+            .await?
+            .into_iter()
+            .find(|(campaign, _)| *campaign == id)
+            .ok_or(CampaignError::MissingCampaign(id))?;
+        store.scope_to(id);
+
+        Ok(Campaign { id, info, store })
+    }
+
+    /// Lists the campaigns held in the store, archived ones included, in the order they were created.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::Store`] if the store cannot be migrated or read.
+    pub async fn list(store: &mut SqliteStore) -> Result<Vec<(Uuid, CampaignInfo)>> {
+        Campaign::upgrade(store).await?;
+
+        Ok(store.across::<CampaignInfo>().await?)
+    }
+
+    /// Migrates the store, then upgrades the components it holds to the current shape of their types, see
+    /// [`UpgradeComponents`], so campaigns saved by older versions of the game load as the types change.
+    async fn upgrade(store: &mut SqliteStore) -> Result<()> {
+        store.apply(MIGRATIONS).await?;
+        store.upgrade_components(&crate::schema::registry()).await?;
+
+        Ok(())
+    }
+
+    /// Lists the campaigns held in the same store as this one, see [`Campaign::list`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::Store`] if the store cannot be read.
+    pub async fn campaigns(&mut self) -> Result<Vec<(Uuid, CampaignInfo)>> {
+        Ok(self.store.across::<CampaignInfo>().await?)
+    }
+
+    /// Switches to another campaign held in the same store, see [`Campaign::open_id`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::MissingCampaign`] if the store does not hold it, or [`CampaignError::Store`] if it
+    /// cannot be read.
+    pub async fn switch(self, id: Uuid) -> Result<Campaign> {
+        Campaign::open_id(self.store, id).await
+    }
+
+    /// Archives a campaign held in the same store, this one included, so [`Campaign::open`] skips it. Its entities and
+    /// journal are kept and it can still be opened by id.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::Permission`] if the actor is not the GM, [`CampaignError::MissingCampaign`] if the store
+    /// does not hold the campaign, or [`CampaignError::Store`] if it cannot be written.
+    pub async fn archive(&mut self, actor: Actor, campaign: Uuid) -> Result<i64> {
+        actor.require_gm()?;
+        let (_, mut info) = self
+            .campaigns()
+            .await?
+            .into_iter()
+            .find(|(id, _)| *id == campaign)
+            .ok_or(CampaignError::MissingCampaign(campaign))?;
+        info.archived = true;
+
+        let seq = self
+            .apply(
+                Changeset::new(actor, Access::Gm)
+                    .insert(campaign, &info)?
+                    .record(Some(campaign), "campaign.archived", &info)?,
+            )
+            .await?;
+        if campaign == self.id {
+            self.info = info;
+        }
+
+        Ok(seq)
+    }
+
+    /// Exports the rows of the campaign written and the journal entries recorded after the given journal entry, so
+    /// another device holding the campaign up to that entry can catch up with [`Campaign::sync`]. A checkpoint of `0`
+    /// exports the whole campaign.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::Store`] if the store cannot be read.
+    pub async fn delta(&mut self, since: i64) -> Result<Delta> {
+        Ok(self.store.delta(since).await?)
+    }
+
+    /// Applies a delta exported by [`Campaign::delta`] on another device to the store, loading the campaign's content
+    /// pack first, and opens the campaign it holds.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::Store`] if the store recorded entries after the delta's checkpoint that the delta does
+    /// not hold or cannot be written, [`CampaignError::MissingCampaign`] if neither the store nor the delta hold the
+    /// campaign, or [`CampaignError::Pack`] if the pack cannot be read.
+    pub async fn sync(mut store: SqliteStore, pack: &Pack, delta: &Delta) -> Result<Campaign> {
+        store.apply(MIGRATIONS).await?;
+        store.load_pack(pack).await?;
+        store.apply_delta(delta).await?;
+
+        Campaign::open_id(store, delta.campaign).await
+    }
+
+    /// Takes a snapshot of the campaign's world, to diff it against another save with
+    /// [`WorldDiff::between`](darkforge_data::store::diff::WorldDiff::between) or to reconcile two diverging saves with
+    /// [`merge`](darkforge_data::store::diff::merge).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::Store`] if the store cannot be read.
+    pub async fn snapshot(&mut self) -> Result<Snapshot> {
+        Ok(Snapshot::from(&self.delta(0).await?))
+    }
+
+    /// Reloads the campaign's content pack from the given directory, applying only the entries that changed since it
+    /// was loaded and recording a [`CONTENT_CHANGED`] event for each of them in the same transaction, so editors can
+    /// refresh in place.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::Permission`] if the actor is not the GM, [`CampaignError::PackMismatch`] if the
+    /// directory holds another pack, or another [`CampaignError`] if the pack is invalid or the store cannot be written.
+    pub async fn reload_pack(&mut self, actor: Actor, path: impl AsRef<Path>) -> Result<Vec<EntryChange>> {
+        actor.require_gm()?;
+        let pack = Pack::open(path)?;
+        if pack.name() != self.info.pack {
+            return Err(CampaignError::PackMismatch {
+                expected: self.info.pack.clone(),
+                found: pack.name().into(),
+            });
+        }
+
+        let changes = self
+            .store
+            .reload_pack(&pack, |changes| {
+                let mut events = Vec::new();
+                for change in changes {
+                    let data = ContentChanged {
+                        id: change.id,
+                        category: change.category.clone(),
+                        change: change.change,
+                    };
+                    events.extend(Changeset::new(actor, Access::Gm).record(None, CONTENT_CHANGED, &data)?.into_changes());
+                }
+                Ok(events)
+            })
+            .await?;
+
+        Ok(changes)
+    }
+
+    /// Returns the id of the content entry the slug refers to, if the store holds it. Slugs keep resolving after the pack
+    /// is reloaded with fresh ids, so references across packs should be kept as slugs rather than ids.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::Store`] if the store cannot be read.
+    pub async fn resolve(&mut self, slug: &Slug) -> Result<Option<Uuid>> {
+        Ok(self.store.resolve(slug).await?)
+    }
+
+    /// Closes the campaign's store, leaving no write-ahead log behind, see [`SqliteStore::close`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::Store`] if the store cannot be closed cleanly.
+    pub async fn close(self) -> Result<()> {
+        Ok(self.store.close().await?)
+    }
+
+    /// Returns the id of the campaign entity.
+    #[must_use]
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+
+    /// Returns the campaign information.
+    #[must_use]
+    pub fn info(&self) -> &CampaignInfo {
+        &self.info
+    }
+
+    /// Returns the query metrics of the campaign's store, if it collects any, see [`SqliteStore::metrics`].
+    #[must_use]
+    pub fn metrics(&self) -> Option<StoreMetrics> {
+        self.store.metrics()
+    }
+
+    /// Drops the query metrics collected so far by the campaign's store, see [`SqliteStore::reset_metrics`].
+    pub fn reset_metrics(&self) {
+        self.store.reset_metrics();
+    }
+
+    /// Gives up the campaign for the store holding it, e.g. to settle another campaign in it.
+    pub(crate) fn into_store(self) -> SqliteStore {
+        self.store
+    }
+
+    /// Returns the store holding the campaign. Mutations go through [`Campaign::apply`] instead.
+    pub(crate) fn store(&mut self) -> &mut SqliteStore {
+        &mut self.store
+    }
+
+    /// Returns the component of the entity, if it has one.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CampaignError`] if the store cannot be read or holds an invalid component.
+    pub async fn get<C: Persisted>(&mut self, entity: Uuid) -> Result<Option<C>> {
+        Ok(self.store.get::<C>(entity).await?)
+    }
+
+    /// Returns every component of the kind along with its entity, leaving out archived entities.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CampaignError`] if the store cannot be read or holds an invalid component.
+    pub async fn all<C: Persisted>(&mut self) -> Result<Vec<(Uuid, C)>> {
+        Ok(self.store.all::<C>().await?)
+    }
+
+    /// Returns every component of the kind along with its entity, including archived entities only if
+    /// `include_archived` is set.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CampaignError`] if the store cannot be read or holds an invalid component.
+    pub async fn all_with<C: Persisted>(&mut self, include_archived: bool) -> Result<Vec<(Uuid, C)>> {
+        Ok(self.store.all_with::<C>(include_archived).await?)
+    }
+
+    /// Returns every component of the kind along with its entity, ordered by the text `key` picks out of it, e.g. its
+    /// name, per the collation of the campaign, leaving out archived entities.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CampaignError`] if the store cannot be read or holds an invalid component.
+    pub async fn sorted<C: Persisted>(&mut self, key: impl Fn(&C) -> &str + Send) -> Result<Vec<(Uuid, C)>> {
+        Ok(self.store.sorted::<C>(key).await?)
+    }
+
+    /// Returns the collation the lists of labels and names of the campaign are sorted by.
+    #[must_use]
+    pub fn collation(&self) -> &Collation {
+        self.store.collation()
+    }
+
+    /// Sorts the lists of labels and names of the campaign by the collation from now on, e.g. that of the language the
+    /// player switched to.
+    pub fn collate_with(&mut self, collation: Collation) {
+        self.store.collate_with(collation);
+    }
+
+    /// Parses the marked up text, e.g. a lore entry the GM wrote, checking that its links point to entities of the
+    /// campaign or to loaded content entries.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::Markup`] if the text is not valid markup, [`CampaignError::DanglingLinks`] if links
+    /// point to nothing, or [`CampaignError::Store`] if the store cannot be read.
+    pub async fn markup(&mut self, text: &str) -> Result<Markup> {
+        let markup = Markup::parse(text)?;
+        let dangling = self.dangling_links(&markup).await?;
+        if !dangling.is_empty() {
+            return Err(CampaignError::DanglingLinks(dangling));
+        }
+
+        Ok(markup)
+    }
+
+    /// Returns the targets of the links of the markup that point to nothing the campaign holds, e.g. an NPC since
+    /// deleted.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::Store`] if the store cannot be read.
+    pub async fn dangling_links(&mut self, markup: &Markup) -> Result<Vec<Target>> {
+        Ok(markup.dangling(&mut self.store).await?)
+    }
+
+    /// Returns the campaign's factions along with their entity ids.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::Store`] if the store cannot be read.
+    pub async fn factions(&mut self) -> Result<Vec<(Uuid, Faction)>> {
+        let mut factions = Vec::new();
+        for entity in self.store.related(self.id, FACTION).await? {
+            if let Some(faction) = self.store.get::<Faction>(entity).await? {
+                factions.push((entity, faction));
+            }
+        }
+
+        Ok(factions)
+    }
+
+    /// Loads everything the GM screen needs.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CampaignError`] if the store cannot be read or holds invalid components.
+    pub async fn dashboard(&mut self) -> Result<GmDashboard> {
+        GmDashboard::load(&mut self.store).await
+    }
+
+    /// Gives ownership of the entity to the player. Only the GM can assign entities.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::Permission`] if the actor is not the GM, or [`CampaignError::Store`] if the store cannot
+    /// be written.
+    pub async fn assign(&mut self, actor: Actor, entity: Uuid, player: Uuid) -> Result<i64> {
+        let owner = Owner(player);
+        let changeset = Changeset::new(actor, Access::Gm)
+            .insert(entity, &owner)?
+            .record(Some(entity), "owner.assigned", &owner)?;
+
+        self.apply(changeset).await
+    }
+
+    /// Archives the entity on behalf of the actor, e.g. a dead NPC, leaving it out of the listings of the campaign while
+    /// keeping it in its history and exports, see [`Archived`](darkforge_data::store::Archived).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::Permission`] if the actor cannot mutate the entity, or [`CampaignError::Store`] if the
+    /// store cannot be written.
+    pub async fn archive_entity(&mut self, actor: Actor, entity: Uuid) -> Result<i64> {
+        let changeset = Changeset::new(actor, Access::Owner(entity))
+            .archive(entity)
+            .record(Some(entity), "entity.archived", &())?;
+
+        self.apply(changeset).await
+    }
+
+    /// Restores the archived entity on behalf of the actor, listing it again.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::Permission`] if the actor cannot mutate the entity, or [`CampaignError::Store`] if the
+    /// store cannot be written.
+    pub async fn restore_entity(&mut self, actor: Actor, entity: Uuid) -> Result<i64> {
+        let changeset = Changeset::new(actor, Access::Owner(entity))
+            .restore(entity)
+            .record(Some(entity), "entity.restored", &())?;
+
+        self.apply(changeset).await
+    }
+
+    /// Deletes the entity on behalf of the GM, applying the policy to its relationships: the entities it cascades to are
+    /// deleted along with it, in one changeset. Returns the entities deleted, the one asked for first. The journal
+    /// entries about them are kept.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::Reference`] listing the relationships the policy blocks, [`CampaignError::Permission`]
+    /// if the actor is not the GM, or [`CampaignError::Store`] if the store cannot be read or written, in which case
+    /// nothing is deleted.
+    pub async fn delete_entity(&mut self, actor: Actor, entity: Uuid, policy: &ReferencePolicy) -> Result<Vec<Uuid>> {
+        actor.require_gm()?;
+        let mut deletion = Deletion::new(entity);
+        while let Some(next) = deletion.unvisited() {
+            let links = self.store.links(next).await?;
+            deletion.visit(next, &links, policy);
+        }
+        let deletion = deletion.finish()?;
+
+        let changeset = deletion
+            .entities
+            .iter()
+            .fold(Changeset::new(actor, Access::Gm), |changeset, entity| changeset.despawn(*entity))
+            .record(Some(entity), "entity.deleted", &deletion.entities)?;
+        self.apply(changeset).await?;
+
+        Ok(deletion.entities)
+    }
+
+    /// Attaches the component to the entity on behalf of the actor, and records the change in the journal.
+    ///
+    /// Players can only update the entities they own and cannot change ownership themselves.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::Permission`] if the actor cannot mutate the entity, or [`CampaignError::Store`] if the
+    /// store cannot be written.
+    pub async fn update<C: Persisted>(&mut self, actor: Actor, entity: Uuid, component: &C) -> Result<i64> {
+        let access = if C::KIND == Owner::KIND { Access::Gm } else { Access::Owner(entity) };
+        let changeset = Changeset::new(actor, access)
+            .insert(entity, component)?
+            .record(Some(entity), &format!("{}.updated", C::KIND), component)?;
+
+        self.apply(changeset).await
+    }
+
+    /// Appends an event to the journal on behalf of the actor. Events about an entity can only be recorded by the GM or
+    /// the player owning it, while events about the campaign as a whole are reserved to the GM.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::Permission`] if the actor cannot record the event, or [`CampaignError::Store`] if the
+    /// store cannot be written.
+    pub async fn record(&mut self, actor: Actor, entity: Option<Uuid>, kind: &str, data: &impl Serialize) -> Result<i64> {
+        let access = entity.map_or(Access::Gm, Access::Owner);
+
+        self.apply(Changeset::new(actor, access).record(entity, kind, data)?).await
+    }
+
+    /// Checks whether the actor may mutate the entity.
+    pub(crate) async fn authorize(&mut self, actor: Actor, entity: Uuid) -> Result<()> {
+        if actor == Actor::Gm {
+            return Ok(());
+        }
+
+        let owner = self.store.get::<Owner>(entity).await?;
+        Ok(actor.authorize(entity, owner)?)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    #[cfg(feature = "store")]
+    use std::{collections::BTreeSet, env, fs, path::PathBuf};
+
+    #[cfg(feature = "store")]
+    use darkforge_data::store::{
+        Archived,
+        diff::{ComponentChange, WorldDiff},
+        reference::OnDelete,
+    };
+
+    #[cfg(feature = "store")]
+    use super::*;
+    #[cfg(feature = "store")]
+    use crate::faction::Status;
+
+    /// Path to the default content pack.
+    pub(crate) const DEFAULTS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../../../data/defaults");
+
+    /// Returns the path to a fresh database file.
+    #[cfg(feature = "store")]
+    pub(crate) fn db() -> PathBuf {
+        env::temp_dir().join(format!("darkforge-{}.db", Uuid::new_v4()))
+    }
+
+    /// Bootstraps a campaign from the default pack in a fresh in-memory database.
+    #[cfg(feature = "store")]
+    pub(crate) async fn bootstrap() -> Campaign {
+        let pack = Pack::open(DEFAULTS).expect("should have opened pack");
+        let store = SqliteStore::memory().await.expect("should have opened store");
+
+        Campaign::bootstrap(store, "Crow's Foot", &pack)
+            .await
+            .expect("should have bootstrapped campaign")
+    }
+
+    #[cfg(feature = "store")]
+    #[tokio::test]
+    async fn should_bootstrap_campaign_from_default_pack() {
+        let pack = Pack::open(DEFAULTS).expect("should have opened pack");
+        let store = SqliteStore::open(db()).await.expect("should have opened store");
+
+        let mut campaign = Campaign::bootstrap(store, "Crow's Foot", &pack)
+            .await
+            .expect("should have bootstrapped campaign");
+
+        assert_eq!(
+            &CampaignInfo {
+                name: "Crow's Foot".into(),
+                pack: "defaults".into(),
+                archived: false
+            },
+            campaign.info()
+        );
+
+        let factions = campaign.factions().await.expect("should have listed factions");
+        assert_eq!(pack.entries(faction::CATEGORY).len(), factions.len());
+
+        let (lampblacks, _) = factions
+            .iter()
+            .find(|(_, faction)| faction.descriptor().label() == "Lampblacks")
+            .expect("should have created the Lampblacks");
+        let clocks = campaign.store().related(*lampblacks, CLOCK).await.expect("should have listed clocks");
+        let clock = campaign
+            .store()
+            .get::<Clock>(clocks[0])
+            .await
+            .expect("should have read clock")
+            .expect("should have a clock");
+        assert_eq!("Destroy the Red Sashes", clock.label());
+        assert_eq!(2, clock.filled());
+
+        let status = campaign.store().get::<Status>(*lampblacks).await.expect("should have read status");
+        assert_eq!(Some(Status::default()), status);
+    }
+
+    #[cfg(feature = "store")]
+    #[tokio::test]
+    async fn should_only_let_players_update_what_they_own() {
+        const ALICE: Uuid = uuid::uuid!("0b1c0b45-7d1f-4c55-9c2f-5e6e5f1b3a10");
+        const BOB: Uuid = uuid::uuid!("9a8d6c3e-2f4b-4b1a-8e5d-7c6b5a4f3e2d");
+
+        let mut campaign = bootstrap().await;
+
+        let sheet = campaign.store().spawn().await.expect("should have spawned sheet");
+        let err = campaign
+            .assign(Actor::Player(ALICE), sheet, ALICE)
+            .await
+            .expect_err("should have refused assignment");
+        assert!(
+            matches!(err, CampaignError::Permission(PermissionError::GmOnly(ALICE))),
+            "unexpected error: {err}"
+        );
+        campaign.assign(Actor::Gm, sheet, ALICE).await.expect("should have assigned sheet");
+
+        let clock = Clock::new("Heal", 4).expect("should have created clock");
+        campaign
+            .update(Actor::Player(ALICE), sheet, &clock)
+            .await
+            .expect("should have let owner update");
+        campaign.update(Actor::Gm, sheet, &clock).await.expect("should have let GM update");
+
+        let err = campaign
+            .update(Actor::Player(BOB), sheet, &clock)
+            .await
+            .expect_err("should have refused update");
+        assert!(
+            matches!(err, CampaignError::Permission(PermissionError::NotOwner { player: BOB, entity }) if entity == sheet),
+            "unexpected error: {err}"
+        );
+
+        let err = campaign
+            .update(Actor::Player(ALICE), sheet, &Owner(BOB))
+            .await
+            .expect_err("should have refused ownership change");
+        assert!(
+            matches!(err, CampaignError::Permission(PermissionError::GmOnly(ALICE))),
+            "unexpected error: {err}"
+        );
+
+        let err = campaign
+            .record(Actor::Player(ALICE), None, "session.ended", &())
+            .await
+            .expect_err("should have refused campaign event");
+        assert!(
+            matches!(err, CampaignError::Permission(PermissionError::GmOnly(ALICE))),
+            "unexpected error: {err}"
+        );
+    }
+
+    #[cfg(feature = "store")]
+    #[tokio::test]
+    async fn should_archive_entities_out_of_listings() {
+        const ALICE: Uuid = uuid::uuid!("0b1c0b45-7d1f-4c55-9c2f-5e6e5f1b3a10");
+
+        let mut campaign = bootstrap().await;
+        let factions = campaign.all::<Faction>().await.expect("should have listed factions");
+        let (lampblacks, _) = factions
+            .iter()
+            .find(|(_, faction)| faction.descriptor().label() == "Lampblacks")
+            .expect("should have created the Lampblacks");
+        let since = campaign.delta(0).await.expect("should have exported campaign").until;
+
+        let err = campaign
+            .archive_entity(Actor::Player(ALICE), *lampblacks)
+            .await
+            .expect_err("should have refused archival");
+        assert!(matches!(err, CampaignError::Permission(_)), "unexpected error: {err}");
+        campaign
+            .archive_entity(Actor::Gm, *lampblacks)
+            .await
+            .expect("should have archived faction");
+
+        let listed = campaign.all::<Faction>().await.expect("should have listed factions");
+        assert_eq!(factions.len() - 1, listed.len());
+        assert!(listed.iter().all(|(entity, _)| entity != lampblacks));
+        assert_eq!(
+            factions.len(),
+            campaign.all_with::<Faction>(true).await.expect("should have listed factions").len()
+        );
+        let dashboard = campaign.dashboard().await.expect("should have loaded dashboard");
+        assert!(dashboard.factions.iter().all(|standing| standing.entity != *lampblacks));
+
+        let delta = campaign.delta(since).await.expect("should have exported delta");
+        assert_eq!(
+            vec!["entity.archived"],
+            delta.journal.iter().map(|entry| entry.kind.as_str()).collect::<Vec<_>>()
+        );
+        assert!(
+            delta
+                .components
+                .iter()
+                .any(|component| component.entity == *lampblacks && component.kind == Archived::KIND)
+        );
+
+        campaign
+            .restore_entity(Actor::Gm, *lampblacks)
+            .await
+            .expect("should have restored faction");
+        assert_eq!(factions, campaign.all::<Faction>().await.expect("should have listed factions"));
+    }
+
+    #[cfg(feature = "store")]
+    #[tokio::test]
+    async fn should_delete_entity_per_reference_policy() {
+        let mut campaign = bootstrap().await;
+        let (lampblacks, _) = campaign
+            .factions()
+            .await
+            .expect("should have listed factions")
+            .into_iter()
+            .find(|(_, faction)| faction.descriptor().label() == "Lampblacks")
+            .expect("should have created the Lampblacks");
+        let clocks = campaign.store().related(lampblacks, CLOCK).await.expect("should have listed clocks");
+
+        let err = campaign
+            .delete_entity(Actor::Gm, lampblacks, &ReferencePolicy::default())
+            .await
+            .expect_err("should have blocked deletion");
+        let CampaignError::Reference(violation) = err else {
+            panic!("unexpected error: {err}");
+        };
+        assert_eq!(lampblacks, violation.entity);
+        assert!(
+            violation
+                .referrers
+                .iter()
+                .any(|referrer| referrer.source == campaign.id() && referrer.kind == FACTION)
+        );
+        assert!(campaign.get::<Faction>(lampblacks).await.expect("should have read faction").is_some());
+
+        let policy = ReferencePolicy::new(OnDelete::Nullify).with(CLOCK, OnDelete::Cascade);
+        let deleted = campaign
+            .delete_entity(Actor::Gm, lampblacks, &policy)
+            .await
+            .expect("should have deleted faction");
+
+        assert_eq!([vec![lampblacks], clocks.clone()].concat(), deleted);
+        assert_eq!(None, campaign.get::<Faction>(lampblacks).await.expect("should have read faction"));
+        assert_eq!(None, campaign.get::<Clock>(clocks[0]).await.expect("should have read clock"));
+        assert!(
+            campaign
+                .factions()
+                .await
+                .expect("should have listed factions")
+                .iter()
+                .all(|(entity, _)| *entity != lampblacks)
+        );
+        assert_eq!(
+            Some(lampblacks),
+            campaign
+                .store()
+                .journal(None, "entity.deleted", 1)
+                .await
+                .expect("should have read journal")[0]
+                .entity
+        );
+    }
+
+    #[cfg(feature = "store")]
+    #[tokio::test]
+    async fn should_resolve_slugs_given_default_pack() {
+        let pack = Pack::open(DEFAULTS).expect("should have opened pack");
+        let mut campaign = bootstrap().await;
+
+        for category in pack.categories() {
+            for entry in pack.entries(category) {
+                let slug = pack.slug(category, entry.id).expect("should have a slug");
+                assert_eq!(
+                    Some(entry.id),
+                    campaign.resolve(&slug).await.expect("should have resolved slug"),
+                    "{slug}"
+                );
+            }
+        }
+        assert_eq!(
+            Some(uuid::uuid!("24c28e86-8187-4f2c-a2e8-1d35841328c3")),
+            campaign
+                .resolve(&"defaults:npc:bazso-baz".parse().expect("should have parsed slug"))
+                .await
+                .expect("should have resolved slug")
+        );
+    }
+
+    #[cfg(feature = "store")]
+    #[tokio::test]
+    async fn should_check_links_of_markup_against_campaign() {
+        let mut campaign = bootstrap().await;
+        let (faction, _) = campaign.factions().await.expect("should have listed factions")[0].clone();
+
+        let markup = campaign
+            .markup(&format!("*Rivals* of [Bazso](defaults:npc:bazso-baz) and [them]({faction})"))
+            .await
+            .expect("should have parsed markup");
+        assert_eq!("Rivals of Bazso and them", markup.to_plain());
+
+        let ghost = Uuid::new_v4();
+        let err = campaign
+            .markup(&format!("[Nobody]({ghost}) of [nowhere](defaults:npc:nobody)"))
+            .await
+            .expect_err("should have refused dangling links");
+        assert!(
+            matches!(&err, CampaignError::DanglingLinks(targets) if targets.len() == 2 && targets[0] == Target::Entity(ghost)),
+            "unexpected error: {err}"
+        );
+        let err = campaign.markup("**unclosed").await.expect_err("should have refused invalid markup");
+        assert!(matches!(err, CampaignError::Markup(_)), "unexpected error: {err}");
+    }
+
+    #[cfg(feature = "store")]
+    #[tokio::test]
+    async fn should_reload_changed_pack_entries() {
+        let dir = env::temp_dir().join(format!("darkforge-{}", Uuid::new_v4())).join("defaults");
+        fs::create_dir_all(&dir).expect("should have created pack directory");
+        for file in fs::read_dir(DEFAULTS).expect("should have read default pack") {
+            let file = file.expect("should have read pack file").path();
+            fs::copy(&file, dir.join(file.file_name().expect("should have a file name"))).expect("should have copied pack file");
+        }
+        let pack = Pack::open(&dir).expect("should have opened pack");
+        let mut campaign = Campaign::bootstrap(SqliteStore::open(db()).await.expect("should have opened store"), "Crow's Foot", &pack)
+            .await
+            .expect("should have bootstrapped campaign");
+
+        let removed = pack.entries(crate::npc::CATEGORY).iter().map(|entry| entry.id).collect::<Vec<_>>();
+        let added = Uuid::new_v4();
+        fs::remove_file(dir.join("npc.jsonc")).expect("should have removed npcs");
+        fs::write(dir.join("note.json"), format!(r#"[{{ "id": "{added}", "text": "Mind the bells" }}]"#)).expect("should have written notes");
+
+        let err = campaign
+            .reload_pack(Actor::Player(Uuid::new_v4()), &dir)
+            .await
+            .expect_err("should have refused player");
+        assert!(
+            matches!(err, CampaignError::Permission(PermissionError::GmOnly(_))),
+            "unexpected error: {err}"
+        );
+
+        let changes = campaign.reload_pack(Actor::Gm, &dir).await.expect("should have reloaded pack");
+
+        assert_eq!(removed.len() + 1, changes.len());
+        assert_eq!(
+            ("note", added, Changed::Added),
+            (changes[0].category.as_str(), changes[0].id, changes[0].change)
+        );
+        assert!(
+            changes[1..]
+                .iter()
+                .all(|change| change.change == Changed::Removed && removed.contains(&change.id))
+        );
+        let recorded = campaign
+            .store()
+            .journal(None, CONTENT_CHANGED, u32::MAX)
+            .await
+            .expect("should have read journal");
+        assert_eq!(changes.len(), recorded.len());
+
+        let other = dir.with_file_name("other");
+        fs::create_dir_all(&other).expect("should have created pack directory");
+        let err = campaign
+            .reload_pack(Actor::Gm, &other)
+            .await
+            .expect_err("should have refused another pack");
+        assert!(
+            matches!(&err, CampaignError::PackMismatch { expected, found } if expected == "defaults" && found == "other"),
+            "unexpected error: {err}"
+        );
+    }
+
+    #[cfg(feature = "store")]
+    #[tokio::test]
+    async fn should_reopen_bootstrapped_campaign() {
+        let pack = Pack::open(DEFAULTS).expect("should have opened pack");
+        let path = db();
+
+        let store = SqliteStore::open(&path).await.expect("should have opened store");
+        let id = Campaign::bootstrap(store, "Crow's Foot", &pack)
+            .await
+            .expect("should have bootstrapped campaign")
+            .id();
+
+        let store = SqliteStore::open(&path).await.expect("should have opened store");
+        let campaign = Campaign::open(store).await.expect("should have opened campaign");
+        assert_eq!(id, campaign.id());
+
+        let store = SqliteStore::open(&path).await.expect("should have opened store");
+        let err = Campaign::bootstrap(store, "Again", &pack)
+            .await
+            .err()
+            .expect("should have refused to bootstrap twice");
+        assert!(
+            matches!(err, CampaignError::AlreadyExists(existing) if existing == id),
+            "unexpected error: {err}"
+        );
+    }
+
+    #[cfg(feature = "store")]
+    #[tokio::test]
+    async fn should_reopen_closed_campaign() {
+        let pack = Pack::open(DEFAULTS).expect("should have opened pack");
+        let path = db();
+        let store = SqliteStore::open(&path).await.expect("should have opened store");
+        let campaign = Campaign::bootstrap(store, "Crow's Foot", &pack)
+            .await
+            .expect("should have bootstrapped campaign");
+        let id = campaign.id();
+
+        campaign.close().await.expect("should have closed campaign");
+
+        let store = SqliteStore::open(&path).await.expect("should have opened store");
+        let campaign = Campaign::open(store).await.expect("should have opened campaign");
+        assert_eq!(id, campaign.id());
+    }
+
+    #[cfg(feature = "store")]
+    #[tokio::test]
+    async fn should_keep_several_campaigns_apart_in_one_store() {
+        let pack = Pack::open(DEFAULTS).expect("should have opened pack");
+        let path = db();
+        let store = SqliteStore::open(&path).await.expect("should have opened store");
+        let mut first = Campaign::bootstrap(store, "Crow's Foot", &pack)
+            .await
+            .expect("should have bootstrapped campaign");
+        let (first_id, factions) = (first.id(), first.factions().await.expect("should have listed factions").len());
+        first.close().await.expect("should have closed campaign");
+
+        let store = SqliteStore::open(&path).await.expect("should have opened store");
+        let mut second = Campaign::create(store, "Six Towers", &pack).await.expect("should have created campaign");
+        assert_eq!(factions, second.factions().await.expect("should have listed factions").len());
+        assert_eq!(
+            vec!["Crow's Foot", "Six Towers"],
+            second
+                .campaigns()
+                .await
+                .expect("should have listed campaigns")
+                .iter()
+                .map(|(_, info)| info.name.as_str())
+                .collect::<Vec<_>>()
+        );
+
+        second
+            .archive(Actor::Player(Uuid::new_v4()), first_id)
+            .await
+            .expect_err("should have refused to archive as a player");
+        second.archive(Actor::Gm, first_id).await.expect("should have archived campaign");
+        let second_id = second.id();
+        let first = second.switch(first_id).await.expect("should have switched campaign");
+        assert!(first.info().archived);
+        first.close().await.expect("should have closed campaign");
+
+        let store = SqliteStore::open(&path).await.expect("should have opened store");
+        let campaign = Campaign::open(store).await.expect("should have opened campaign");
+        assert_eq!(second_id, campaign.id());
+    }
+
+    #[cfg(feature = "store")]
+    #[tokio::test]
+    async fn should_sync_campaign_to_another_store_through_deltas() {
+        let pack = Pack::open(DEFAULTS).expect("should have opened pack");
+        let mut laptop = bootstrap().await;
+        let full = laptop.delta(0).await.expect("should have exported campaign");
+
+        let path = db();
+        let store = SqliteStore::open(&path).await.expect("should have opened store");
+        let mut phone = Campaign::sync(store, &pack, &full).await.expect("should have synced campaign");
+        assert_eq!((laptop.id(), laptop.info()), (phone.id(), phone.info()));
+        assert_eq!(
+            laptop.factions().await.expect("should have listed factions").len(),
+            phone.factions().await.expect("should have listed factions").len()
+        );
+        phone.close().await.expect("should have closed campaign");
+
+        laptop.archive(Actor::Gm, laptop.id()).await.expect("should have archived campaign");
+        let delta = laptop.delta(full.until).await.expect("should have exported delta");
+        assert_eq!(1, delta.journal.len());
+
+        let store = SqliteStore::open(&path).await.expect("should have opened store");
+        let phone = Campaign::sync(store, &pack, &delta).await.expect("should have synced delta");
+        assert!(phone.info().archived);
+    }
+
+    #[cfg(feature = "store")]
+    #[tokio::test]
+    async fn should_diff_snapshots_of_campaign() {
+        let mut campaign = bootstrap().await;
+        let before = campaign.snapshot().await.expect("should have taken snapshot");
+        assert!(WorldDiff::between(&before, &before).is_empty());
+
+        campaign.archive(Actor::Gm, campaign.id()).await.expect("should have archived campaign");
+        let after = campaign.snapshot().await.expect("should have taken snapshot");
+
+        let diff = WorldDiff::between(&before, &after);
+        assert!(diff.spawned.is_empty() && diff.despawned.is_empty());
+        let changed = diff
+            .components
+            .iter()
+            .map(|change| match change {
+                ComponentChange::Added { entity, .. } | ComponentChange::Removed { entity, .. } | ComponentChange::Changed { entity, .. } => *entity,
+            })
+            .collect::<BTreeSet<_>>();
+        assert_eq!(BTreeSet::from([campaign.id()]), changed);
+    }
+}
@@ -0,0 +1,436 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! What changed in a campaign's world between two snapshots, laid out for review, e.g. by a GM going over a session
+//! in an editor dock.
+//!
+//! [`WorldChanges::between`] groups the [`WorldDiff`] of two snapshots by the kind of the entities changed, and names
+//! each entity by its label, or name, rather than by its id.
+
+use std::{collections::BTreeMap, fmt};
+
+use darkforge_data::{
+    JSONDeserialize, Persisted,
+    collation::Collation,
+    store::diff::{ComponentChange, Components, Relationship, Snapshot, WorldDiff},
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[cfg(feature = "store")]
+use crate::campaign::{Campaign, Result};
+use crate::{campaign::CampaignInfo, character::Character, clock::Clock, contest::Contest, crew::Crew, faction::Faction, item::Item, npc::Npc};
+
+/// The kind of an entity, told by the component it is made of.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum EntityKind {
+    /// The campaign itself.
+    Campaign,
+    /// The crew.
+    Crew,
+    /// A player character.
+    Character,
+    /// A non-player character.
+    Npc,
+    /// A faction.
+    Faction,
+    /// A progress clock.
+    Clock,
+    /// An item.
+    Item,
+    /// A contest between faction clocks.
+    Contest,
+    /// An entity of none of the kinds above.
+    Other,
+}
+
+impl EntityKind {
+    /// Every kind, in the order changes are grouped by.
+    pub const ALL: [EntityKind; 9] = [
+        EntityKind::Campaign,
+        EntityKind::Crew,
+        EntityKind::Character,
+        EntityKind::Npc,
+        EntityKind::Faction,
+        EntityKind::Clock,
+        EntityKind::Item,
+        EntityKind::Contest,
+        EntityKind::Other,
+    ];
+
+    /// Returns the kind of component an entity of this kind is made of, or `None` for [`EntityKind::Other`].
+    #[must_use]
+    pub fn component(self) -> Option<&'static str> {
+        match self {
+            EntityKind::Campaign => Some(CampaignInfo::KIND),
+            EntityKind::Crew => Some(Crew::KIND),
+            EntityKind::Character => Some(Character::KIND),
+            EntityKind::Npc => Some(Npc::KIND),
+            EntityKind::Faction => Some(Faction::KIND),
+            EntityKind::Clock => Some(Clock::KIND),
+            EntityKind::Item => Some(Item::KIND),
+            EntityKind::Contest => Some(Contest::KIND),
+            EntityKind::Other => None,
+        }
+    }
+
+    /// Returns the kind of the entity with the given components.
+    #[must_use]
+    pub fn of(components: &Components) -> EntityKind {
+        EntityKind::ALL
+            .into_iter()
+            .find(|kind| kind.component().is_some_and(|component| components.contains_key(component)))
+            .unwrap_or(EntityKind::Other)
+    }
+}
+
+impl fmt::Display for EntityKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            EntityKind::Campaign => "campaign",
+            EntityKind::Crew => "crew",
+            EntityKind::Character => "character",
+            EntityKind::Npc => "npc",
+            EntityKind::Faction => "faction",
+            EntityKind::Clock => "clock",
+            EntityKind::Item => "item",
+            EntityKind::Contest => "contest",
+            EntityKind::Other => "other",
+        })
+    }
+}
+
+/// How an entity changed.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum EntityChange {
+    /// The entity is new.
+    Spawned,
+    /// The entity is gone.
+    Despawned,
+    /// Some components of the entity changed.
+    Changed,
+}
+
+/// A component that changed, its serialized value before and after.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ComponentEdit {
+    /// The kind of component.
+    pub kind: String,
+    /// The serialized component as it was, or `None` if it was added.
+    pub before: Option<String>,
+    /// The serialized component as it is, or `None` if it was removed.
+    pub after: Option<String>,
+}
+
+/// An entity that changed, along with its components that did.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct EntityChanges {
+    /// The entity.
+    pub entity: Uuid,
+    /// The label or name of the entity, else its id.
+    pub label: String,
+    /// How the entity changed.
+    pub change: EntityChange,
+    /// The components that changed, ordered by kind.
+    pub components: Vec<ComponentEdit>,
+}
+
+/// The entities of a kind that changed.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct KindChanges {
+    /// The kind of the entities.
+    pub kind: EntityKind,
+    /// The entities that changed, ordered by label.
+    pub entities: Vec<EntityChanges>,
+}
+
+/// A relationship created or removed.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct RelationshipChange {
+    /// The source of the relationship.
+    pub source: Uuid,
+    /// The label or name of the source, else its id.
+    pub source_label: String,
+    /// The target of the relationship.
+    pub target: Uuid,
+    /// The label or name of the target, else its id.
+    pub target_label: String,
+    /// The kind of relationship.
+    pub kind: String,
+    /// Whether the relationship was created, rather than removed.
+    pub related: bool,
+}
+
+/// What changed from a snapshot to another, grouped by the kind of the entities that did.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct WorldChanges {
+    /// The entities that changed, by kind in the order of [`EntityKind::ALL`], leaving out kinds without changes.
+    pub kinds: Vec<KindChanges>,
+    /// The relationships created, then those removed.
+    pub relationships: Vec<RelationshipChange>,
+}
+
+impl WorldChanges {
+    /// Lists what changed from the `before` snapshot to the `after` one, ordering the entities of each kind by their
+    /// label in the collation.
+    #[must_use]
+    pub fn between(before: &Snapshot, after: &Snapshot, collation: &Collation) -> WorldChanges {
+        let diff = WorldDiff::between(before, after);
+        let mut entities = BTreeMap::<Uuid, EntityChanges>::new();
+        for change in &diff.components {
+            let (entity, edit) = match change {
+                ComponentChange::Added { entity, kind, data } => (entity, edit(kind, None, Some(data))),
+                ComponentChange::Removed { entity, kind, data } => (entity, edit(kind, Some(data), None)),
+                ComponentChange::Changed { entity, kind, before, after } => (entity, edit(kind, Some(before), Some(after))),
+            };
+            entities
+                .entry(*entity)
+                .or_insert_with(|| changes(*entity, EntityChange::Changed, before, after))
+                .components
+                .push(edit);
+        }
+        for (ids, change) in [(&diff.spawned, EntityChange::Spawned), (&diff.despawned, EntityChange::Despawned)] {
+            for entity in ids {
+                entities.entry(*entity).or_insert_with(|| changes(*entity, change, before, after)).change = change;
+            }
+        }
+
+        let mut kinds = BTreeMap::<EntityKind, Vec<EntityChanges>>::new();
+        for (entity, changes) in entities {
+            let components = after.entities.get(&entity).or_else(|| before.entities.get(&entity));
+            kinds
+                .entry(components.map_or(EntityKind::Other, EntityKind::of))
+                .or_default()
+                .push(changes);
+        }
+        let kinds = kinds
+            .into_iter()
+            .map(|(kind, mut entities)| {
+                collation.sort_by_key(&mut entities, |changes| changes.label.as_str());
+                KindChanges { kind, entities }
+            })
+            .collect();
+
+        let relationships = [(&diff.related, true), (&diff.unrelated, false)]
+            .into_iter()
+            .flat_map(|(relationships, related)| {
+                relationships.iter().map(move |Relationship { source, target, kind }| RelationshipChange {
+                    source: *source,
+                    source_label: label(*source, before, after),
+                    target: *target,
+                    target_label: label(*target, before, after),
+                    kind: kind.clone(),
+                    related,
+                })
+            })
+            .collect();
+
+        WorldChanges { kinds, relationships }
+    }
+
+    /// Returns true if nothing changed.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.kinds.is_empty() && self.relationships.is_empty()
+    }
+}
+
+/// Returns the edit of a component of the given kind.
+fn edit(kind: &str, before: Option<&String>, after: Option<&String>) -> ComponentEdit {
+    ComponentEdit {
+        kind: kind.into(),
+        before: before.cloned(),
+        after: after.cloned(),
+    }
+}
+
+/// Returns the changes of an entity, without any component yet.
+fn changes(entity: Uuid, change: EntityChange, before: &Snapshot, after: &Snapshot) -> EntityChanges {
+    EntityChanges {
+        entity,
+        label: label(entity, before, after),
+        change,
+        components: Vec::new(),
+    }
+}
+
+/// Returns the label of the entity as it is, or as it was if it is gone: the `descriptor.label`, `label` or `name` of
+/// the component its kind is made of, else of any of its components, else its id.
+fn label(entity: Uuid, before: &Snapshot, after: &Snapshot) -> String {
+    [after, before]
+        .into_iter()
+        .filter_map(|snapshot| snapshot.entities.get(&entity))
+        .find_map(|components| {
+            let main = EntityKind::of(components).component().and_then(|kind| components.get(kind));
+            main.into_iter()
+                .chain(components.values())
+                .find_map(|data| Labelled::from_json(data.as_bytes()).ok()?.label())
+        })
+        .unwrap_or_else(|| entity.to_string())
+}
+
+/// The fields of a serialized component that may name its entity.
+#[derive(Deserialize)]
+struct Labelled {
+    descriptor: Option<Label>,
+    label: Option<String>,
+    name: Option<String>,
+}
+
+/// The label of a descriptor.
+#[derive(Deserialize)]
+struct Label {
+    label: String,
+}
+
+impl Labelled {
+    fn label(self) -> Option<String> {
+        self.descriptor.map(|descriptor| descriptor.label).or(self.label).or(self.name)
+    }
+}
+
+#[cfg(feature = "store")]
+impl Campaign {
+    /// Lists what changed in the campaign's world since the snapshot was taken with [`Campaign::snapshot`], e.g. at the
+    /// start of the session, the entities of each kind ordered by the campaign's collation.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::Store`](crate::campaign::CampaignError::Store) if the store cannot be read.
+    pub async fn changes_since(&mut self, before: &Snapshot) -> Result<WorldChanges> {
+        let after = self.snapshot().await?;
+        Ok(WorldChanges::between(before, &after, self.collation()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use darkforge_data::store::diff::Relationship;
+    use uuid::uuid;
+
+    use super::*;
+    #[cfg(feature = "store")]
+    use crate::{campaign::tests::bootstrap, permission::Actor};
+
+    const BLUECOATS: Uuid = uuid!("45a83a2c-066b-4676-9179-c3109025f4d7");
+    const ALICE: Uuid = uuid!("F9168C5E-FEB2-4FAA-B6BF-329BF39FA1E4");
+    const CLOCK: Uuid = uuid!("5B0C3F0E-58E2-4E55-9A4C-8D0F1A3E9C21");
+
+    fn snapshot(entities: &[(Uuid, &[(&str, &str)])], relationships: &[(Uuid, Uuid)]) -> Snapshot {
+        Snapshot {
+            entities: entities
+                .iter()
+                .map(|(entity, components)| {
+                    let components = components.iter().map(|(kind, data)| ((*kind).to_owned(), (*data).to_owned())).collect();
+                    (*entity, components)
+                })
+                .collect(),
+            relationships: relationships
+                .iter()
+                .map(|(source, target)| Relationship {
+                    source: *source,
+                    target: *target,
+                    kind: "owner".into(),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn should_group_changes_by_kind_with_labels() {
+        let faction = r#"{"descriptor":{"id":"d0a9b7b4-d84a-4765-baf1-b3f0f5297d51","label":"Bluecoats","description":""},"tier":3,"hold":"strong"}"#;
+        let before = snapshot(
+            &[
+                (BLUECOATS, &[("faction", faction), ("status", "0")]),
+                (ALICE, &[("character", r#"{"name":"Alice"}"#)]),
+            ],
+            &[],
+        );
+        let after = snapshot(
+            &[
+                (BLUECOATS, &[("faction", faction), ("status", "-1")]),
+                (CLOCK, &[("clock", r#"{"label":"Crackdown","segments":4,"filled":0}"#)]),
+            ],
+            &[(CLOCK, BLUECOATS)],
+        );
+
+        let changes = WorldChanges::between(&before, &after, &Collation::root());
+
+        assert_eq!(
+            vec![
+                KindChanges {
+                    kind: EntityKind::Character,
+                    entities: vec![EntityChanges {
+                        entity: ALICE,
+                        label: "Alice".into(),
+                        change: EntityChange::Despawned,
+                        components: vec![edit("character", Some(&r#"{"name":"Alice"}"#.into()), None)],
+                    }],
+                },
+                KindChanges {
+                    kind: EntityKind::Faction,
+                    entities: vec![EntityChanges {
+                        entity: BLUECOATS,
+                        label: "Bluecoats".into(),
+                        change: EntityChange::Changed,
+                        components: vec![edit("status", Some(&"0".into()), Some(&"-1".into()))],
+                    }],
+                },
+                KindChanges {
+                    kind: EntityKind::Clock,
+                    entities: vec![EntityChanges {
+                        entity: CLOCK,
+                        label: "Crackdown".into(),
+                        change: EntityChange::Spawned,
+                        components: vec![edit("clock", None, Some(&r#"{"label":"Crackdown","segments":4,"filled":0}"#.into()))],
+                    }],
+                },
+            ],
+            changes.kinds
+        );
+        assert_eq!(
+            vec![RelationshipChange {
+                source: CLOCK,
+                source_label: "Crackdown".into(),
+                target: BLUECOATS,
+                target_label: "Bluecoats".into(),
+                kind: "owner".into(),
+                related: true,
+            }],
+            changes.relationships
+        );
+    }
+
+    #[cfg(feature = "store")]
+    #[tokio::test]
+    async fn should_list_changes_since_snapshot() {
+        let mut campaign = bootstrap().await;
+        let before = campaign.snapshot().await.expect("should have taken snapshot");
+        assert!(campaign.changes_since(&before).await.expect("should have listed changes").is_empty());
+
+        let clock = Clock::new("Crackdown", 4).expect("should have created clock");
+        let id = campaign.add_clock(Actor::Gm, None, &clock).await.expect("should have added clock");
+        let changes = campaign.changes_since(&before).await.expect("should have listed changes");
+
+        assert_eq!(vec![EntityKind::Clock], changes.kinds.iter().map(|kind| kind.kind).collect::<Vec<_>>());
+        assert_eq!(
+            vec![(id, "Crackdown", EntityChange::Spawned)],
+            changes.kinds[0]
+                .entities
+                .iter()
+                .map(|entity| (entity.entity, entity.label.as_str(), entity.change))
+                .collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,407 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! Changesets are the only way to mutate a campaign.
+//!
+//! A [`Changeset`] batches component mutations and relationships together with the single journal entry describing
+//! them. Only the methods of [`Campaign`] build changesets, each declaring the access its changes call for, then
+//! `Campaign::apply` checks that the actor has it and that the changeset follows the mutation rules, and commits it
+//! atomically, so every change to the world is authorized, journaled and all-or-nothing.
+//!
+//! Callers outside the crate go through those methods, as they cannot build a changeset of their own:
+//!
+//! ```compile_fail,E0603
+//! use darkforge::{changeset::{Access, Changeset}, permission::Actor};
+//!
+//! let changeset = Changeset::new(Actor::Player(uuid::Uuid::new_v4()), Access::Anyone);
+//! ```
+
+use std::collections::HashSet;
+
+use darkforge_data::{
+    CodecError, Persisted,
+    store::{Archived, Change},
+};
+#[cfg(feature = "store")]
+use darkforge_data::{JSONDeserialize as _, store::World as _};
+use serde::Serialize;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::{campaign::Event, permission::Actor};
+#[cfg(feature = "store")]
+use crate::{
+    campaign::{Campaign, Result},
+    permission::Owner,
+};
+
+/// Error type for changesets breaking the mutation rules.
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum ChangesetError {
+    /// The changeset has no journal entry.
+    #[error("changeset has no journal entry")]
+    Unrecorded,
+    /// The changeset sets the same component of an entity twice, one of them being lost.
+    #[error("{kind} changeset sets {component} of entity {entity} twice")]
+    Conflict {
+        /// The kind of the changeset's journal entry.
+        kind: String,
+        /// The entity set twice.
+        entity: Uuid,
+        /// The kind of component set twice.
+        component: String,
+    },
+    /// The changeset relates an entity to itself.
+    #[error("{kind} changeset relates entity {entity} to itself")]
+    SelfRelation {
+        /// The kind of the changeset's journal entry.
+        kind: String,
+        /// The entity related to itself.
+        entity: Uuid,
+    },
+}
+
+/// Who may apply a changeset. Only the methods of [`Campaign`] build changesets, each declaring the access its
+/// changes call for, so callers cannot widen it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(not(feature = "store"), allow(dead_code, reason = "Only campaigns build changesets, with the store"))]
+pub(crate) enum Access {
+    /// Anyone at the table.
+    Anyone,
+    /// Only the GM.
+    Gm,
+    /// The GM or the player owning the entity.
+    Owner(Uuid),
+}
+
+/// A batch of mutations along with the journal entry describing them, built and applied atomically by the methods of
+/// [`Campaign`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Changeset {
+    actor: Actor,
+    access: Access,
+    changes: Vec<Change>,
+    entry: Option<Change>,
+}
+
+impl Changeset {
+    /// Creates an empty changeset made by the actor, who must have the access to apply it.
+    #[must_use]
+    #[cfg_attr(not(feature = "store"), allow(dead_code, reason = "Only campaigns build changesets, with the store"))]
+    pub(crate) fn new(actor: Actor, access: Access) -> Changeset {
+        Changeset {
+            actor,
+            access,
+            changes: Vec::new(),
+            entry: None,
+        }
+    }
+
+    /// Returns the actor making the changes.
+    #[must_use]
+    pub fn actor(&self) -> Actor {
+        self.actor
+    }
+
+    /// Creates the entity along with the rest of the changeset, ahead of its other changes so they can refer to it. An
+    /// entity is only created if the whole changeset is applied.
+    #[must_use]
+    pub fn spawn(mut self, entity: Uuid) -> Changeset {
+        let spawned = self.changes.iter().take_while(|change| matches!(change, Change::Spawn { .. })).count();
+        self.changes.insert(spawned, Change::spawn(entity));
+
+        self
+    }
+
+    /// Attaches the component to the entity, replacing any component of the same kind.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CodecError::Serialize`] if the component cannot be serialized.
+    pub fn insert<C: Persisted>(mut self, entity: Uuid, component: &C) -> std::result::Result<Changeset, CodecError> {
+        self.changes.push(Change::insert(entity, component)?);
+
+        Ok(self)
+    }
+
+    /// Links the source entity to the target entity.
+    #[must_use]
+    pub fn relate(mut self, source: Uuid, target: Uuid, kind: &str) -> Changeset {
+        self.changes.push(Change::relate(source, target, kind));
+
+        self
+    }
+
+    /// Archives the entity, leaving it out of the listings of the world, see [`Archived`].
+    #[must_use]
+    pub fn archive(mut self, entity: Uuid) -> Changeset {
+        self.changes.push(Change::archive(entity));
+
+        self
+    }
+
+    /// Restores the archived entity, listing it again.
+    #[must_use]
+    pub fn restore(mut self, entity: Uuid) -> Changeset {
+        self.changes.push(Change::restore(entity));
+
+        self
+    }
+
+    /// Removes the components of the entity and the relationships from and to it, see [`Campaign::delete_entity`].
+    #[must_use]
+    pub fn despawn(mut self, entity: Uuid) -> Changeset {
+        self.changes.push(Change::despawn(entity));
+
+        self
+    }
+
+    /// Removes the journal entries of the given kind recorded before the entry `before`, see [`Campaign::prune`].
+    #[must_use]
+    pub fn prune(mut self, kind: &str, before: i64) -> Changeset {
+        self.changes.push(Change::prune(kind, before));
+
+        self
+    }
+
+    /// Sets the journal entry of the changeset, recording the data on behalf of the actor.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CodecError::Serialize`] if the data cannot be serialized.
+    pub fn record(mut self, entity: Option<Uuid>, kind: &str, data: &impl Serialize) -> std::result::Result<Changeset, CodecError> {
+        let actor = self.actor;
+        self.entry = Some(Change::record(entity, kind, &Event { actor, data })?);
+
+        Ok(self)
+    }
+
+    /// Checks the changeset against the mutation rules: it has a journal entry, sets each component of an entity at most
+    /// once and relates no entity to itself.
+    ///
+    /// # Errors
+    ///
+    /// Returns the [`ChangesetError`] of the first rule broken.
+    pub fn validate(&self) -> std::result::Result<(), ChangesetError> {
+        let Some(Change::Record { kind, .. }) = &self.entry else {
+            return Err(ChangesetError::Unrecorded);
+        };
+
+        let mut set = HashSet::new();
+        for change in &self.changes {
+            match change {
+                Change::Insert { entity, kind: component, .. } if !set.insert((*entity, component.as_str())) => {
+                    return Err(ChangesetError::Conflict {
+                        kind: kind.clone(),
+                        entity: *entity,
+                        component: component.clone(),
+                    });
+                }
+                Change::Archive { entity, .. } if !set.insert((*entity, Archived::KIND)) => {
+                    return Err(ChangesetError::Conflict {
+                        kind: kind.clone(),
+                        entity: *entity,
+                        component: Archived::KIND.into(),
+                    });
+                }
+                Change::Relate { source, target, .. } if source == target => {
+                    return Err(ChangesetError::SelfRelation {
+                        kind: kind.clone(),
+                        entity: *source,
+                    });
+                }
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the players the changeset gives ownership of an entity to.
+    #[cfg(feature = "store")]
+    fn owners(&self) -> Vec<Uuid> {
+        self.changes
+            .iter()
+            .filter_map(|change| match change {
+                Change::Insert { kind, data, .. } if kind == Owner::KIND => Owner::from_json(data.as_bytes()).ok(),
+                _ => None,
+            })
+            .map(|Owner(player)| player)
+            .collect()
+    }
+
+    /// Returns the changes to commit, the journal entry last.
+    #[cfg(feature = "store")]
+    pub(crate) fn into_changes(self) -> Vec<Change> {
+        let mut changes = self.changes;
+        changes.extend(self.entry);
+
+        changes
+    }
+}
+
+#[cfg(feature = "store")]
+impl Campaign {
+    /// Applies the changeset atomically, returning the sequence number of its journal entry.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::Permission`](crate::campaign::CampaignError::Permission) if the actor does not have the
+    /// changeset's access or gives ownership to another player without being the GM, [`CampaignError::Changeset`](
+    /// crate::campaign::CampaignError::Changeset) if it breaks a mutation rule, or another
+    /// [`CampaignError`](crate::campaign::CampaignError) if the store cannot be written, in which case nothing is
+    /// applied.
+    pub(crate) async fn apply(&mut self, changeset: Changeset) -> Result<i64> {
+        let applied = self.apply_all(vec![changeset]).await?;
+
+        Ok(applied.into_iter().next().unwrap_or_default())
+    }
+
+    /// Applies every changeset in a single transaction, returning the sequence numbers of their journal entries in
+    /// order. Either all of them are applied or none are.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CampaignError`](crate::campaign::CampaignError) as [`Campaign::apply`] does for any of them.
+    pub(crate) async fn apply_all(&mut self, changesets: Vec<Changeset>) -> Result<Vec<i64>> {
+        for changeset in &changesets {
+            changeset.validate()?;
+            match changeset.access {
+                Access::Anyone => {}
+                Access::Gm => changeset.actor.require_gm()?,
+                Access::Owner(entity) => self.authorize(changeset.actor, entity).await?,
+            }
+            for player in changeset.owners() {
+                changeset.actor.act_for(player)?;
+            }
+        }
+
+        let changes = changesets.into_iter().flat_map(Changeset::into_changes).collect::<Vec<_>>();
+
+        Ok(self.store().commit(&changes).await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::Clock;
+    #[cfg(feature = "store")]
+    use crate::{
+        campaign::{CampaignError, tests::bootstrap},
+        character::{Character, Stress},
+    };
+
+    #[cfg(feature = "store")]
+    const ALICE: Uuid = uuid::uuid!("0b1c0b45-7d1f-4c55-9c2f-5e6e5f1b3a10");
+
+    fn clock() -> Clock {
+        Clock::new("Alarm", 4).expect("should have created clock")
+    }
+
+    #[test]
+    fn should_reject_changeset_without_journal_entry() {
+        let changeset = Changeset::new(Actor::Gm, Access::Gm)
+            .insert(Uuid::new_v4(), &clock())
+            .expect("should have inserted clock");
+
+        assert_eq!(Err(ChangesetError::Unrecorded), changeset.validate());
+    }
+
+    #[test]
+    fn should_reject_changeset_setting_component_twice() {
+        let entity = Uuid::new_v4();
+        let changeset = Changeset::new(Actor::Gm, Access::Gm)
+            .insert(entity, &clock())
+            .and_then(|changeset| changeset.insert(entity, &clock()))
+            .and_then(|changeset| changeset.record(Some(entity), "clock.added", &()))
+            .expect("should have built changeset");
+
+        assert_eq!(
+            Err(ChangesetError::Conflict {
+                kind: "clock.added".into(),
+                entity,
+                component: Clock::KIND.into()
+            }),
+            changeset.validate()
+        );
+    }
+
+    #[test]
+    fn should_reject_changeset_relating_entity_to_itself() {
+        let entity = Uuid::new_v4();
+        let changeset = Changeset::new(Actor::Gm, Access::Gm)
+            .relate(entity, entity, "clock")
+            .record(None, "clock.related", &())
+            .expect("should have built changeset");
+
+        assert_eq!(
+            Err(ChangesetError::SelfRelation {
+                kind: "clock.related".into(),
+                entity
+            }),
+            changeset.validate()
+        );
+    }
+
+    #[cfg(feature = "store")]
+    #[tokio::test]
+    async fn should_apply_changeset_with_single_journal_entry() {
+        let mut campaign = bootstrap().await;
+        let entity = campaign.store().spawn().await.expect("should have spawned entity");
+
+        let changeset = Changeset::new(Actor::Gm, Access::Gm)
+            .insert(entity, &clock())
+            .and_then(|changeset| changeset.insert(entity, &Stress::new(2)))
+            .and_then(|changeset| changeset.record(Some(entity), "test.applied", &"both"))
+            .expect("should have built changeset");
+        let seq = campaign.apply(changeset).await.expect("should have applied changeset");
+
+        assert_eq!(
+            Some(clock()),
+            campaign.store().get::<Clock>(entity).await.expect("should have read clock")
+        );
+        let journal = campaign
+            .store()
+            .journal(Some(entity), "test.applied", 10)
+            .await
+            .expect("should have read journal");
+        assert_eq!(vec![seq], journal.iter().map(|entry| entry.seq).collect::<Vec<_>>());
+    }
+
+    #[cfg(feature = "store")]
+    #[tokio::test]
+    async fn should_apply_nothing_when_one_changeset_is_not_allowed() {
+        let mut campaign = bootstrap().await;
+        let pc = campaign
+            .enlist(Actor::Gm, ALICE, &Character::new("Arcy"))
+            .await
+            .expect("should have enlisted character");
+        let player = Actor::Player(ALICE);
+
+        let allowed = Changeset::new(player, Access::Owner(pc))
+            .insert(pc, &Stress::new(3))
+            .and_then(|changeset| changeset.record(Some(pc), "stress.updated", &3))
+            .expect("should have built changeset");
+        let assigning = Changeset::new(player, Access::Owner(pc))
+            .insert(pc, &Owner(Uuid::new_v4()))
+            .and_then(|changeset| changeset.record(Some(pc), "owner.assigned", &()))
+            .expect("should have built changeset");
+        let err = campaign
+            .apply_all(vec![allowed, assigning])
+            .await
+            .expect_err("should have failed to apply changesets");
+
+        assert!(matches!(err, CampaignError::Permission(_)));
+        assert_eq!(None, campaign.store().get::<Stress>(pc).await.expect("should have read stress"));
+    }
+}
@@ -0,0 +1,324 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! Special abilities, as defined in content packs, and the abilities a character has gained.
+//!
+//! Each ability carries data-driven [`AbilityEffect`]s that subscribe to the tags of a roll's context by pattern, see
+//! [`RollContext::matches`], e.g. a bonus die on rolls tagged `in darkness`. [`Campaign::roll_with`] resolves the
+//! abilities a roll triggers from its context and records them along with it.
+
+use std::collections::BTreeSet;
+
+#[cfg(feature = "store")]
+use darkforge_data::store::{Content, World};
+use darkforge_data::{Component, Persisted, descriptor::Descriptor};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[cfg(feature = "store")]
+use super::Character;
+use crate::context::RollContext;
+#[cfg(feature = "store")]
+use crate::{
+    campaign::{Campaign, CampaignError, Result},
+    permission::Actor,
+};
+
+/// Content pack category holding the special abilities.
+pub const ABILITY_CATEGORY: &str = "ability";
+
+/// A special ability a character can gain, e.g. Shadow or Ghost Veil.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct AbilityDefinition {
+    /// The id of the ability in the content pack.
+    pub id: Uuid,
+    /// The ability's descriptor.
+    pub descriptor: Descriptor<'static>,
+    /// What the ability does in play.
+    #[serde(default)]
+    pub effects: Vec<AbilityEffect>,
+}
+
+impl AbilityDefinition {
+    /// Returns whether any effect of the ability applies to a roll made in the context.
+    #[must_use]
+    pub fn triggered_by(&self, context: &RollContext) -> bool {
+        self.effects.iter().any(|effect| effect.triggered_by(context))
+    }
+}
+
+/// An effect of a special ability.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case", tag = "kind")]
+pub enum AbilityEffect {
+    /// Adds dice to rolls whose context holds a tag matching any of the patterns, e.g. `+1d` when `in darkness`.
+    Dice {
+        /// The tag patterns of the rolls improved, see [`RollContext::matches`].
+        tags: BTreeSet<String>,
+        /// The dice added to the pool.
+        bonus: u8,
+    },
+    /// Shifts the effect of rolls whose context holds a tag matching any of the patterns, down if negative.
+    Effect {
+        /// The tag patterns of the rolls affected, see [`RollContext::matches`].
+        tags: BTreeSet<String>,
+        /// The effect levels shifted.
+        levels: i8,
+    },
+}
+
+impl AbilityEffect {
+    /// Returns whether the effect applies to a roll made in the context.
+    #[must_use]
+    pub fn triggered_by(&self, context: &RollContext) -> bool {
+        match self {
+            AbilityEffect::Dice { tags, .. } | AbilityEffect::Effect { tags, .. } => context.matches_any(tags),
+        }
+    }
+}
+
+/// The abilities a roll triggers and what they add to it, see [`triggered`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Triggered {
+    /// The ids of the abilities triggered, in the order given.
+    pub abilities: Vec<Uuid>,
+    /// The dice added to the pool.
+    pub dice: u8,
+    /// The effect levels shifted.
+    pub effect: i8,
+}
+
+/// Returns the abilities triggered by a roll made in the context and what they add to it.
+#[must_use]
+pub fn triggered(abilities: &[AbilityDefinition], context: &RollContext) -> Triggered {
+    let mut triggered = Triggered::default();
+    for ability in abilities.iter().filter(|ability| ability.triggered_by(context)) {
+        triggered.abilities.push(ability.id);
+        for effect in ability.effects.iter().filter(|effect| effect.triggered_by(context)) {
+            match effect {
+                AbilityEffect::Dice { bonus, .. } => triggered.dice = triggered.dice.saturating_add(*bonus),
+                AbilityEffect::Effect { levels, .. } => triggered.effect = triggered.effect.saturating_add(*levels),
+            }
+        }
+    }
+
+    triggered
+}
+
+/// The special abilities a character has gained, by id.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct Abilities(BTreeSet<Uuid>);
+
+impl Abilities {
+    /// Returns true if the character has gained the ability.
+    #[must_use]
+    pub fn contains(&self, ability: Uuid) -> bool {
+        self.0.contains(&ability)
+    }
+
+    /// Returns the ids of the abilities gained.
+    pub fn iter(&self) -> impl Iterator<Item = Uuid> + '_ {
+        self.0.iter().copied()
+    }
+
+    /// Returns true if no ability was gained.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl Component for Abilities {}
+
+impl Persisted for Abilities {
+    const KIND: &'static str = "abilities";
+}
+
+#[cfg(feature = "store")]
+impl Campaign {
+    /// Returns the special abilities characters can gain, as loaded from the content pack, sorted by label.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CampaignError`] if the store cannot be read.
+    pub async fn abilities(&mut self) -> Result<Vec<AbilityDefinition>> {
+        Ok(self
+            .store()
+            .sorted_entries(ABILITY_CATEGORY, |definition: &AbilityDefinition| definition.descriptor.label())
+            .await?)
+    }
+
+    /// Grants the special ability to the character, e.g. when they advance their playbook, and returns their abilities.
+    /// Granting an ability again has no effect.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::MissingCharacter`] if the entity is not a character,
+    /// [`CampaignError::MissingAbility`] if the ability does not exist, [`CampaignError::Permission`] if the actor does
+    /// not own the character, or another [`CampaignError`] if the store cannot be read or written.
+    pub async fn grant_ability(&mut self, actor: Actor, character: Uuid, ability: Uuid) -> Result<Abilities> {
+        let store = self.store();
+        store
+            .get::<Character>(character)
+            .await?
+            .ok_or(CampaignError::MissingCharacter(character))?;
+        store
+            .entry::<AbilityDefinition>(ABILITY_CATEGORY, ability)
+            .await?
+            .ok_or(CampaignError::MissingAbility(ability))?;
+
+        let mut abilities = store.get::<Abilities>(character).await?.unwrap_or_default();
+        if abilities.0.insert(ability) {
+            self.update(actor, character, &abilities).await?;
+        }
+
+        Ok(abilities)
+    }
+
+    /// Returns the abilities of the character a roll made in the context triggers, and what they add to it, skipping
+    /// abilities no longer in the pack.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CampaignError`] if the store cannot be read.
+    pub async fn triggered_abilities(&mut self, character: Uuid, context: &RollContext) -> Result<Triggered> {
+        let store = self.store();
+        let abilities = store.get::<Abilities>(character).await?.unwrap_or_default();
+
+        let mut gained = Vec::new();
+        for ability in abilities.iter() {
+            gained.extend(store.entry::<AbilityDefinition>(ABILITY_CATEGORY, ability).await?);
+        }
+
+        Ok(triggered(&gained, context))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::permission::Actor;
+    #[cfg(feature = "store")]
+    use crate::{action::tests::Loaded, campaign::tests::bootstrap, effect::Effect};
+
+    fn ability(label: &str, effects: Vec<AbilityEffect>) -> AbilityDefinition {
+        AbilityDefinition {
+            id: Uuid::new_v4(),
+            descriptor: Descriptor::new(Uuid::new_v4(), label.to_string(), ""),
+            effects,
+        }
+    }
+
+    fn tags(patterns: &[&str]) -> BTreeSet<String> {
+        patterns.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn should_sum_effects_of_triggered_abilities() {
+        let abilities = [
+            ability(
+                "Nightwalker",
+                vec![AbilityEffect::Dice {
+                    tags: tags(&["in darkness"]),
+                    bonus: 1,
+                }],
+            ),
+            ability(
+                "Canal Rat",
+                vec![AbilityEffect::Effect {
+                    tags: tags(&["on *"]),
+                    levels: 1,
+                }],
+            ),
+            ability(
+                "Smuggler",
+                vec![
+                    AbilityEffect::Dice {
+                        tags: tags(&["* cargo"]),
+                        bonus: 2,
+                    },
+                    AbilityEffect::Effect {
+                        tags: tags(&["on water"]),
+                        levels: 1,
+                    },
+                ],
+            ),
+        ];
+        let context = RollContext::new(Actor::Gm).tag("in darkness").tag("on water");
+
+        let triggered = triggered(&abilities, &context);
+
+        assert_eq!(
+            Triggered {
+                abilities: abilities.iter().map(|ability| ability.id).collect(),
+                dice: 1,
+                effect: 2,
+            },
+            triggered
+        );
+        assert_eq!(
+            Triggered::default(),
+            super::triggered(&abilities, &RollContext::new(Actor::Gm).tag("unseen"))
+        );
+    }
+
+    #[cfg(feature = "store")]
+    #[tokio::test]
+    async fn should_apply_abilities_triggered_by_roll_context() {
+        let player = Uuid::new_v4();
+        let mut campaign = bootstrap().await;
+        let arcy = campaign
+            .enlist(Actor::Gm, player, &Character::new("Arcy"))
+            .await
+            .expect("should have enlisted character");
+        let abilities = campaign.abilities().await.expect("should have listed abilities");
+        let nightwalker = abilities
+            .iter()
+            .find(|ability| ability.descriptor.label() == "Nightwalker")
+            .expect("should have a Nightwalker ability");
+
+        let gained = campaign
+            .grant_ability(Actor::Player(player), arcy, nightwalker.id)
+            .await
+            .expect("should have granted ability");
+        assert!(gained.contains(nightwalker.id));
+        let err = campaign
+            .grant_ability(Actor::Gm, arcy, Uuid::new_v4())
+            .await
+            .expect_err("should have refused unknown ability");
+        assert!(matches!(err, CampaignError::MissingAbility(_)), "unexpected error: {err}");
+
+        let dark = RollContext::new(Actor::Player(player)).tag("in the deepest shadows");
+        let roll = campaign
+            .roll_with(dark, Some(arcy), "Prowl", 1, &Loaded::new(&[3, 5]))
+            .await
+            .expect("should have rolled");
+        assert_eq!((2, vec![3, 5], vec![nightwalker.id]), (roll.rating, roll.dice, roll.abilities));
+
+        let canal_rat = abilities
+            .iter()
+            .find(|ability| ability.descriptor.label() == "Canal Rat")
+            .expect("should have a Canal Rat ability");
+        campaign
+            .grant_ability(Actor::Gm, arcy, canal_rat.id)
+            .await
+            .expect("should have granted ability");
+        let afloat = RollContext::new(Actor::Player(player)).tag("on water");
+        let roll = campaign
+            .roll_with(afloat, Some(arcy), "Finesse", 1, &Loaded::new(&[3, 5]))
+            .await
+            .expect("should have rolled");
+        assert_eq!((1, vec![canal_rat.id]), (roll.rating, roll.abilities));
+        assert_eq!(Some(Effect::Great), roll.context.map(|context| context.effect));
+    }
+}
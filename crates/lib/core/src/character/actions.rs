@@ -1,6 +1,286 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! Actions and attributes, as defined in content packs, and the character's ratings in them.
+
+use std::collections::BTreeMap;
+
+use darkforge_data::{Component, Persisted, descriptor::Descriptor};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use uuid::Uuid;
+
+/// Content pack category holding the actions.
+pub const ACTION_CATEGORY: &str = "action";
+
+/// Content pack category holding the attributes.
+pub const ATTRIBUTE_CATEGORY: &str = "attribute";
+
+/// Error type for allocating action dots.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum AllocationError {
+    /// An action has more dots than a new character may start with.
+    #[error("{action} has {dots} dots but a new character may start with at most {max}")]
+    CreationRating {
+        /// The id of the action.
+        action: Uuid,
+        /// The dots in the action.
+        dots: u8,
+        /// The most dots an action may start with.
+        max: u8,
+    },
+    /// More dots are assigned than a new character may start with.
+    #[error("{dots} dots are assigned but a new character may start with at most {max}")]
+    CreationTotal {
+        /// The dots assigned across all actions.
+        dots: u32,
+        /// The most dots a new character may start with.
+        max: u8,
+    },
+    /// The action is already at the highest rating an action can have.
+    #[error("{action} is already rated {max}, the highest rating")]
+    Maxed {
+        /// The id of the action.
+        action: Uuid,
+        /// The highest rating.
+        max: u8,
+    },
+    /// The playbook caps the actions of the attribute below the rating asked for.
+    #[error("{action} cannot go above {max} as the playbook caps the actions of {attribute}")]
+    Capped {
+        /// The id of the action.
+        action: Uuid,
+        /// The id of the attribute grouping the action.
+        attribute: Uuid,
+        /// The cap on the actions of the attribute.
+        max: u8,
+    },
+}
+
+/// A playbook's cap on the ratings of the actions of an attribute, e.g. no more than 3 dots in any Prowess action.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AttributeCap {
+    /// The id of the attribute.
+    pub attribute: Uuid,
+    /// The highest rating the actions of the attribute can have.
+    pub max: u8,
+}
+
+/// An action characters can take, e.g. Hunt or Study.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ActionDefinition {
+    /// The id of the action in the content pack.
+    pub id: Uuid,
+    /// The action's descriptor.
+    pub descriptor: Descriptor<'static>,
+}
+
+/// An attribute grouping actions, e.g. Insight.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct AttributeDefinition {
+    /// The id of the attribute in the content pack.
+    pub id: Uuid,
+    /// The attribute's descriptor.
+    pub descriptor: Descriptor<'static>,
+    /// The ids of the actions the attribute groups.
+    #[serde(default)]
+    pub actions: Vec<Uuid>,
+}
+
+/// The character's action ratings, as dots from 0 up to [`Ratings::MAX`], keyed by action id.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct Ratings(BTreeMap<Uuid, u8>);
+
+impl Ratings {
+    /// The highest rating an action can have.
+    pub const MAX: u8 = 4;
+    /// The highest rating an action can have when the character is created.
+    pub const CREATION_MAX: u8 = 2;
+    /// The dots a new character may assign across all actions.
+    pub const CREATION_TOTAL: u8 = 7;
+
+    /// Returns the rating of the action, 0 if the character has no dots in it.
+    #[must_use]
+    pub fn rating(&self, action: Uuid) -> u8 {
+        self.0.get(&action).copied().unwrap_or_default()
+    }
+
+    /// Sets the rating of the action, capped at [`Ratings::MAX`], and returns the rating set.
+    pub fn set(&mut self, action: Uuid, dots: u8) -> u8 {
+        let dots = dots.min(Ratings::MAX);
+        if dots == 0 {
+            self.0.remove(&action);
+        } else {
+            self.0.insert(action, dots);
+        }
+
+        dots
+    }
+
+    /// Returns the attribute rating: the number of its actions with at least one dot.
+    #[must_use]
+    pub fn attribute(&self, attribute: &AttributeDefinition) -> u8 {
+        let rated = attribute.actions.iter().filter(|&&action| self.rating(action) > 0).count();
+        u8::try_from(rated).unwrap_or(u8::MAX)
+    }
+
+    /// Returns the dots assigned across all actions.
+    #[must_use]
+    pub fn total(&self) -> u32 {
+        self.0.values().copied().map(u32::from).sum()
+    }
+
+    /// Checks the ratings are within the limits of a new character: at most [`Ratings::CREATION_MAX`] dots in an action
+    /// and [`Ratings::CREATION_TOTAL`] dots overall.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AllocationError::CreationRating`] for the first action rated too high, or
+    /// [`AllocationError::CreationTotal`] if too many dots are assigned overall.
+    pub fn validate_creation(&self) -> Result<(), AllocationError> {
+        if let Some((&action, &dots)) = self.0.iter().find(|&(_, &dots)| dots > Ratings::CREATION_MAX) {
+            return Err(AllocationError::CreationRating {
+                action,
+                dots,
+                max: Ratings::CREATION_MAX,
+            });
+        }
+
+        let dots = self.total();
+        if dots > u32::from(Ratings::CREATION_TOTAL) {
+            return Err(AllocationError::CreationTotal {
+                dots,
+                max: Ratings::CREATION_TOTAL,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Checks the action can be advanced by a dot and returns its rating once advanced. The rating may not go above
+    /// [`Ratings::MAX`], nor above the cap the playbook puts on the attributes grouping the action.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AllocationError::Maxed`] if the action is already at [`Ratings::MAX`], or [`AllocationError::Capped`]
+    /// if a cap of the playbook forbids the advance.
+    pub fn validate_advance(&self, action: Uuid, attributes: &[AttributeDefinition], caps: &[AttributeCap]) -> Result<u8, AllocationError> {
+        let rating = self.rating(action);
+        if rating >= Ratings::MAX {
+            return Err(AllocationError::Maxed { action, max: Ratings::MAX });
+        }
+
+        let grouping = |cap: &&AttributeCap| {
+            attributes
+                .iter()
+                .any(|attribute| attribute.id == cap.attribute && attribute.actions.contains(&action))
+        };
+        if let Some(cap) = caps.iter().filter(grouping).find(|cap| rating >= cap.max) {
+            return Err(AllocationError::Capped {
+                action,
+                attribute: cap.attribute,
+                max: cap.max,
+            });
+        }
+
+        Ok(rating + 1)
+    }
+}
+
+impl Component for Ratings {}
+
+impl Persisted for Ratings {
+    const KIND: &'static str = "ratings";
+}
+
 #[cfg(test)]
 mod tests {
+    use rstest::rstest;
+    use uuid::uuid;
+
     use super::*;
 
-    fn test_calculate_attribute_level(#[case] attribute: Attribute) {}
+    const HUNT: Uuid = uuid!("0f51ccf8-d0aa-4d43-91cc-f8d0aabd43b0");
+    const STUDY: Uuid = uuid!("31071085-1d8c-4676-8710-851d8cf6763c");
+    const SWAY: Uuid = uuid!("6b0f6f3e-8a4d-4c2b-9b7e-2f5d1c3a9e41");
+    const SKIRMISH: Uuid = uuid!("c4a1e2d7-5b3f-4e8a-a6c9-7d2e0f1b8a53");
+    const INSIGHT: Uuid = uuid!("9e3b7c21-4f6a-4d8e-b1c5-3a7f2e9d6b04");
+    const RESOLVE: Uuid = uuid!("e7d2a9b4-1c8f-4a3e-9f6b-5c0d8e2a7f19");
+
+    #[rstest]
+    #[case::set(2, 2)]
+    #[case::capped(7, Ratings::MAX)]
+    #[case::cleared(0, 0)]
+    fn should_set_rating_up_to_max(#[case] dots: u8, #[case] expect: u8) {
+        let mut ratings = Ratings::default();
+        ratings.set(HUNT, 1);
+
+        assert_eq!(expect, ratings.set(HUNT, dots));
+        assert_eq!(expect, ratings.rating(HUNT));
+    }
+
+    #[rstest]
+    #[case::within(&[(HUNT, 2), (STUDY, 2), (SWAY, 2)], Ok(()))]
+    #[case::rating(&[(HUNT, 3)], Err(AllocationError::CreationRating { action: HUNT, dots: 3, max: 2 }))]
+    #[case::total(
+        &[(HUNT, 2), (STUDY, 2), (SWAY, 2), (SKIRMISH, 2)],
+        Err(AllocationError::CreationTotal { dots: 8, max: 7 })
+    )]
+    fn should_validate_creation_limits(#[case] dots: &[(Uuid, u8)], #[case] expect: Result<(), AllocationError>) {
+        let mut ratings = Ratings::default();
+        for &(action, dots) in dots {
+            ratings.set(action, dots);
+        }
+
+        assert_eq!(expect, ratings.validate_creation());
+    }
+
+    #[rstest]
+    #[case::advanced(STUDY, Ok(3))]
+    #[case::maxed(SWAY, Err(AllocationError::Maxed { action: SWAY, max: Ratings::MAX }))]
+    #[case::capped(HUNT, Err(AllocationError::Capped { action: HUNT, attribute: INSIGHT, max: 3 }))]
+    fn should_validate_advance(#[case] action: Uuid, #[case] expect: Result<u8, AllocationError>) {
+        let mut ratings = Ratings::default();
+        ratings.set(HUNT, 3);
+        ratings.set(STUDY, 2);
+        ratings.set(SWAY, 4);
+        let attributes = [
+            AttributeDefinition {
+                id: INSIGHT,
+                descriptor: Descriptor::new(INSIGHT, "Insight", "Your mental acuity."),
+                actions: vec![HUNT],
+            },
+            AttributeDefinition {
+                id: RESOLVE,
+                descriptor: Descriptor::new(RESOLVE, "Resolve", "Your strength of will."),
+                actions: vec![STUDY, SWAY],
+            },
+        ];
+        let caps = [AttributeCap { attribute: INSIGHT, max: 3 }, AttributeCap { attribute: RESOLVE, max: 4 }];
+
+        assert_eq!(expect, ratings.validate_advance(action, &attributes, &caps));
+    }
+
+    #[test]
+    fn should_rate_attribute_from_its_rated_actions() {
+        let mut ratings = Ratings::default();
+        ratings.set(HUNT, 2);
+        let insight = AttributeDefinition {
+            id: Uuid::new_v4(),
+            descriptor: Descriptor::new(Uuid::new_v4(), "Insight", "Your mental acuity."),
+            actions: vec![HUNT, STUDY],
+        };
+
+        assert_eq!(1, ratings.attribute(&insight));
+    }
 }
@@ -1 +1,242 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! Player characters and the toll the game takes on them.
+
+mod ability;
 mod actions;
+mod sheet;
+mod trauma;
+
+use darkforge_data::{Component, Persisted};
+use serde::{Deserialize, Serialize};
+
+pub use self::{
+    ability::{ABILITY_CATEGORY, Abilities, AbilityDefinition, AbilityEffect, Triggered, triggered},
+    actions::{ACTION_CATEGORY, ATTRIBUTE_CATEGORY, ActionDefinition, AllocationError, AttributeCap, AttributeDefinition, Ratings},
+    sheet::Sheet,
+    trauma::{Contribution, TRAUMA_CATEGORY, TraumaDefinition, TraumaEffect, Traumas},
+};
+
+/// A character sheet.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Character {
+    name: String,
+}
+
+impl Character {
+    /// Creates a new character.
+    #[must_use]
+    pub fn new(name: impl Into<String>) -> Character {
+        Character { name: name.into() }
+    }
+
+    /// Returns the character's name.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// The stress a character has taken, from 0 up to [`Stress::MAX`] at which point they suffer trauma.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct Stress(u8);
+
+impl Stress {
+    /// The stress at which a character suffers trauma.
+    pub const MAX: u8 = 9;
+
+    /// Creates a new stress value, capped at [`Stress::MAX`].
+    #[must_use]
+    pub fn new(value: u8) -> Stress {
+        Stress(value.min(Stress::MAX))
+    }
+
+    /// Returns the stress value.
+    #[must_use]
+    pub fn value(self) -> u8 {
+        self.0
+    }
+}
+
+/// The coin a character has put aside, from 0 up to [`Stash::MAX`].
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct Stash(u8);
+
+impl Stash {
+    /// The most coin a stash can hold.
+    pub const MAX: u8 = 40;
+
+    /// Creates a new stash, capped at [`Stash::MAX`].
+    #[must_use]
+    pub fn new(value: u8) -> Stash {
+        Stash(value.min(Stash::MAX))
+    }
+
+    /// Returns the coin in the stash.
+    #[must_use]
+    pub fn value(self) -> u8 {
+        self.0
+    }
+
+    /// Moves as much coin as fits from `other` into this stash, leaving the remainder in `other`.
+    pub fn absorb(&mut self, other: &mut Stash) {
+        let moved = other.0.min(Stash::MAX - self.0);
+        self.0 += moved;
+        other.0 -= moved;
+    }
+}
+
+/// The harm a character suffers from, by level.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Harm {
+    /// Level 1 harm, e.g. battered or drained.
+    #[serde(default)]
+    pub lesser: Vec<String>,
+    /// Level 2 harm, e.g. exhausted or deep cut.
+    #[serde(default)]
+    pub moderate: Vec<String>,
+    /// Level 3 harm, e.g. broken leg or shot in the chest.
+    #[serde(default)]
+    pub severe: Option<String>,
+    /// Level 4 harm, a catastrophic and permanent consequence, e.g. death.
+    #[serde(default)]
+    pub fatal: Option<String>,
+}
+
+impl Harm {
+    /// Lesser and moderate harm each fill up to this many slots.
+    pub const SLOTS: usize = 2;
+    /// The level of catastrophic harm.
+    pub const FATAL: u8 = 4;
+
+    /// Marks harm of the given level, moving it up a level for as long as its level is full. Returns the level the
+    /// harm was marked at, or 0 if the given level was 0.
+    pub fn suffer(&mut self, level: u8, description: impl Into<String>) -> u8 {
+        let description = description.into();
+        match level {
+            0 => 0,
+            1 if self.lesser.len() < Harm::SLOTS => {
+                self.lesser.push(description);
+                1
+            }
+            1 | 2 if self.moderate.len() < Harm::SLOTS => {
+                self.moderate.push(description);
+                2
+            }
+            1..=3 if self.severe.is_none() => {
+                self.severe = Some(description);
+                3
+            }
+            _ => {
+                self.fatal = Some(description);
+                Harm::FATAL
+            }
+        }
+    }
+
+    /// Reduces every harm by one level: lesser harm is healed, moderate harm becomes lesser and severe harm becomes
+    /// moderate. Fatal harm cannot be healed.
+    pub fn heal(&mut self) {
+        self.lesser = std::mem::take(&mut self.moderate);
+        if let Some(severe) = self.severe.take() {
+            self.moderate.push(severe);
+        }
+    }
+}
+
+impl Component for Character {}
+
+impl Persisted for Character {
+    const KIND: &'static str = "character";
+}
+
+impl Component for Stress {}
+
+impl Persisted for Stress {
+    const KIND: &'static str = "stress";
+}
+
+impl Component for Stash {}
+
+impl Persisted for Stash {
+    const KIND: &'static str = "stash";
+}
+
+impl Component for Harm {}
+
+impl Persisted for Harm {
+    const KIND: &'static str = "harm";
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case::none(0, 0)]
+    #[case::some(4, 4)]
+    #[case::max(9, 9)]
+    #[case::over(12, 9)]
+    fn should_cap_stress(#[case] value: u8, #[case] expect: u8) {
+        assert_eq!(expect, Stress::new(value).value());
+    }
+
+    #[rstest]
+    #[case::none(Harm::default(), 0, 0)]
+    #[case::lesser(Harm::default(), 1, 1)]
+    #[case::lesser_full(Harm { lesser: vec!["Battered".into(); 2], ..Harm::default() }, 1, 2)]
+    #[case::moderate_full(Harm { moderate: vec!["Deep cut".into(); 2], ..Harm::default() }, 2, 3)]
+    #[case::all_full(Harm { moderate: vec!["Deep cut".into(); 2], severe: Some("Broken leg".into()), ..Harm::default() }, 2, 4)]
+    #[case::fatal(Harm::default(), 4, 4)]
+    fn should_suffer_harm_at_first_free_level(#[case] mut harm: Harm, #[case] level: u8, #[case] expect: u8) {
+        assert_eq!(expect, harm.suffer(level, "Shot"));
+    }
+
+    #[rstest]
+    #[case::all(10, 5, 15, 0)]
+    #[case::capped(38, 5, 40, 3)]
+    #[case::full(40, 5, 40, 5)]
+    fn should_absorb_stash_up_to_max(#[case] into: u8, #[case] from: u8, #[case] expect_into: u8, #[case] expect_from: u8) {
+        let (mut into, mut from) = (Stash::new(into), Stash::new(from));
+
+        into.absorb(&mut from);
+
+        assert_eq!((expect_into, expect_from), (into.value(), from.value()));
+    }
+
+    #[test]
+    fn should_heal_harm_by_one_level() {
+        let mut harm = Harm {
+            lesser: vec!["Battered".into()],
+            moderate: vec!["Deep cut".into(), "Exhausted".into()],
+            severe: Some("Broken leg".into()),
+            fatal: None,
+        };
+
+        harm.heal();
+
+        assert_eq!(
+            Harm {
+                lesser: vec!["Deep cut".into(), "Exhausted".into()],
+                moderate: vec!["Broken leg".into()],
+                severe: None,
+                fatal: None,
+            },
+            harm
+        );
+    }
+}
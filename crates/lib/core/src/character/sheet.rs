@@ -0,0 +1,153 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! The character sheet, gathering everything shown about a character.
+
+#[cfg(feature = "store")]
+use darkforge_data::store::{Content, World};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[cfg(feature = "store")]
+use super::{ACTION_CATEGORY, ATTRIBUTE_CATEGORY, ActionDefinition, AttributeDefinition};
+use super::{Abilities, Character, Harm, Ratings, Stress, Traumas};
+#[cfg(feature = "store")]
+use crate::{
+    campaign::{Campaign, CampaignError, Result},
+    permission::Actor,
+};
+
+/// Everything shown on a character sheet.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Sheet {
+    /// The character entity.
+    pub entity: Uuid,
+    /// The character.
+    pub character: Character,
+    /// The stress the character has taken.
+    pub stress: Stress,
+    /// The harm the character suffers from.
+    pub harm: Harm,
+    /// The character's action ratings.
+    pub ratings: Ratings,
+    /// The trauma conditions the character has marked.
+    #[serde(default)]
+    pub traumas: Traumas,
+    /// The special abilities the character has gained.
+    #[serde(default)]
+    pub abilities: Abilities,
+}
+
+#[cfg(feature = "store")]
+impl Campaign {
+    /// Returns the sheet of the character.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::MissingCharacter`] if the entity is not a character, or another [`CampaignError`] if
+    /// the store cannot be read.
+    pub async fn sheet(&mut self, entity: Uuid) -> Result<Sheet> {
+        let store = self.store();
+        let character = store.get::<Character>(entity).await?.ok_or(CampaignError::MissingCharacter(entity))?;
+
+        Ok(Sheet {
+            entity,
+            character,
+            stress: store.get(entity).await?.unwrap_or_default(),
+            harm: store.get(entity).await?.unwrap_or_default(),
+            ratings: store.get(entity).await?.unwrap_or_default(),
+            traumas: store.get(entity).await?.unwrap_or_default(),
+            abilities: store.get(entity).await?.unwrap_or_default(),
+        })
+    }
+
+    /// Sets the character's rating in the action, capped at [`Ratings::MAX`], and returns the updated ratings.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::Permission`] if the actor does not own the character, or another [`CampaignError`]
+    /// if the store cannot be read or written.
+    pub async fn rate(&mut self, actor: Actor, entity: Uuid, action: Uuid, dots: u8) -> Result<Ratings> {
+        let mut ratings = self.store().get::<Ratings>(entity).await?.unwrap_or_default();
+        ratings.set(action, dots);
+        self.update(actor, entity, &ratings).await?;
+
+        Ok(ratings)
+    }
+
+    /// Returns the actions characters can take, as loaded from the content pack.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CampaignError`] if the store cannot be read.
+    pub async fn actions(&mut self) -> Result<Vec<ActionDefinition>> {
+        Ok(self.store().entries(ACTION_CATEGORY).await?)
+    }
+
+    /// Returns the attributes grouping the actions, as loaded from the content pack.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CampaignError`] if the store cannot be read.
+    pub async fn attributes(&mut self) -> Result<Vec<AttributeDefinition>> {
+        Ok(self.store().entries(ATTRIBUTE_CATEGORY).await?)
+    }
+}
+
+#[cfg(all(test, feature = "store"))]
+mod tests {
+    use super::*;
+    use crate::campaign::tests::bootstrap;
+
+    #[tokio::test]
+    async fn should_load_sheet_of_enlisted_character() {
+        let player = Uuid::new_v4();
+        let mut campaign = bootstrap().await;
+        let entity = campaign
+            .enlist(Actor::Gm, player, &Character::new("Arcy"))
+            .await
+            .expect("should have enlisted character");
+        let actions = campaign.actions().await.expect("should have listed actions");
+        let attributes = campaign.attributes().await.expect("should have listed attributes");
+
+        let ratings = campaign
+            .rate(Actor::Player(player), entity, actions[0].id, 2)
+            .await
+            .expect("should have rated action");
+        campaign
+            .update(Actor::Player(player), entity, &Stress::new(3))
+            .await
+            .expect("should have updated stress");
+
+        let sheet = campaign.sheet(entity).await.expect("should have loaded sheet");
+        assert_eq!("Arcy", sheet.character.name());
+        assert_eq!(Stress::new(3), sheet.stress);
+        assert_eq!(Harm::default(), sheet.harm);
+        assert_eq!(ratings, sheet.ratings);
+        assert_eq!(2, sheet.ratings.rating(actions[0].id));
+        assert_eq!(12, actions.len());
+        assert_eq!(actions.len(), attributes.iter().map(|attribute| attribute.actions.len()).sum::<usize>());
+    }
+
+    #[tokio::test]
+    async fn should_fail_to_load_sheet_of_missing_character() {
+        let mut campaign = bootstrap().await;
+        let missing = Uuid::new_v4();
+
+        let err = campaign.sheet(missing).await.expect_err("should have failed to load sheet");
+
+        assert!(
+            matches!(err, CampaignError::MissingCharacter(id) if id == missing),
+            "unexpected error: {err}"
+        );
+    }
+}
@@ -0,0 +1,308 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! Trauma conditions, as defined in content packs, and the traumas a character has marked.
+//!
+//! Each condition carries data-driven [`TraumaEffect`]s rather than being a plain label on the sheet: prompts shown to
+//! the player when an action goes against their nature, and contributions to the experience triggers reviewed at the
+//! end of a session.
+
+use std::collections::BTreeSet;
+
+#[cfg(feature = "store")]
+use darkforge_data::store::{Content, World};
+use darkforge_data::{Component, Persisted, descriptor::Descriptor};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[cfg(feature = "store")]
+use super::Character;
+use crate::context::RollContext;
+#[cfg(feature = "store")]
+use crate::{
+    campaign::{Campaign, CampaignError, Result},
+    permission::Actor,
+};
+
+/// Content pack category holding the trauma conditions.
+pub const TRAUMA_CATEGORY: &str = "trauma";
+
+/// A trauma condition a character can mark, e.g. Cold or Haunted.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct TraumaDefinition {
+    /// The id of the condition in the content pack.
+    pub id: Uuid,
+    /// The condition's descriptor.
+    pub descriptor: Descriptor<'static>,
+    /// What the condition does in play.
+    #[serde(default)]
+    pub effects: Vec<TraumaEffect>,
+}
+
+impl TraumaDefinition {
+    /// Returns the text of the prompts the condition raises for a roll made in the context.
+    pub fn prompts<'a>(&'a self, context: &'a RollContext) -> impl Iterator<Item = &'a str> {
+        self.effects.iter().filter_map(|effect| match effect {
+            TraumaEffect::Prompt { tags, text } if context.matches_any(tags) => Some(text.as_str()),
+            _ => None,
+        })
+    }
+}
+
+/// An effect of a trauma condition.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case", tag = "kind")]
+pub enum TraumaEffect {
+    /// Prompts the player when an action's context holds a tag matching any of the patterns, e.g. showing mercy while
+    /// Vicious.
+    Prompt {
+        /// The tag patterns of the actions going against the character's nature, see [`RollContext::matches`].
+        tags: BTreeSet<String>,
+        /// The text of the prompt.
+        text: String,
+    },
+    /// Contributes experience to a trigger reviewed at the end of the session.
+    Xp {
+        /// The experience trigger, e.g. `vice-or-trauma`.
+        trigger: String,
+        /// The experience the trigger is worth at most.
+        xp: u8,
+    },
+}
+
+/// A trauma condition's contribution to an experience trigger, see [`Campaign::trauma_xp`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Contribution {
+    /// The id of the trauma condition.
+    pub trauma: Uuid,
+    /// The experience trigger.
+    pub trigger: String,
+    /// The experience the trigger is worth at most.
+    pub xp: u8,
+}
+
+/// The trauma conditions a character has marked, by id.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct Traumas(BTreeSet<Uuid>);
+
+impl Traumas {
+    /// The number of traumas at which a character must retire.
+    pub const MAX: usize = 4;
+
+    /// Returns true if the character has marked the condition.
+    #[must_use]
+    pub fn contains(&self, trauma: Uuid) -> bool {
+        self.0.contains(&trauma)
+    }
+
+    /// Returns the ids of the conditions marked.
+    pub fn iter(&self) -> impl Iterator<Item = Uuid> + '_ {
+        self.0.iter().copied()
+    }
+
+    /// Returns the number of conditions marked.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns true if no condition is marked.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns true once the character has marked [`Traumas::MAX`] conditions and must retire.
+    #[must_use]
+    pub fn retired(&self) -> bool {
+        self.0.len() >= Traumas::MAX
+    }
+}
+
+impl Component for Traumas {}
+
+impl Persisted for Traumas {
+    const KIND: &'static str = "traumas";
+}
+
+#[cfg(feature = "store")]
+impl Campaign {
+    /// Returns the trauma conditions characters can mark, as loaded from the content pack, sorted by label.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CampaignError`] if the store cannot be read.
+    pub async fn traumas(&mut self) -> Result<Vec<TraumaDefinition>> {
+        Ok(self
+            .store()
+            .sorted_entries(TRAUMA_CATEGORY, |definition: &TraumaDefinition| definition.descriptor.label())
+            .await?)
+    }
+
+    /// Marks the trauma condition on the character, e.g. after their stress overflowed, and returns their traumas.
+    /// Marking a condition again has no effect.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::MissingCharacter`] if the entity is not a character, [`CampaignError::MissingTrauma`]
+    /// if the condition does not exist, [`CampaignError::Retired`] if the character has marked too many traumas to go
+    /// on, [`CampaignError::Permission`] if the actor does not own the character, or another [`CampaignError`] if the
+    /// store cannot be read or written.
+    pub async fn mark_trauma(&mut self, actor: Actor, character: Uuid, trauma: Uuid) -> Result<Traumas> {
+        let store = self.store();
+        store
+            .get::<Character>(character)
+            .await?
+            .ok_or(CampaignError::MissingCharacter(character))?;
+        store
+            .entry::<TraumaDefinition>(TRAUMA_CATEGORY, trauma)
+            .await?
+            .ok_or(CampaignError::MissingTrauma(trauma))?;
+
+        let mut traumas = store.get::<Traumas>(character).await?.unwrap_or_default();
+        if traumas.contains(trauma) {
+            return Ok(traumas);
+        }
+        if traumas.retired() {
+            return Err(CampaignError::Retired(character));
+        }
+        traumas.0.insert(trauma);
+        self.update(actor, character, &traumas).await?;
+
+        Ok(traumas)
+    }
+
+    /// Returns the prompts the character's traumas raise for a roll made in the context, e.g. for a UI to show before
+    /// the player commits to an action against their nature.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CampaignError`] if the store cannot be read.
+    pub async fn trauma_prompts(&mut self, character: Uuid, context: &RollContext) -> Result<Vec<String>> {
+        Ok(self
+            .marked(character)
+            .await?
+            .iter()
+            .flat_map(|trauma| trauma.prompts(context).map(str::to_string))
+            .collect())
+    }
+
+    /// Returns the contributions of the character's traumas to the experience triggers reviewed at the end of a
+    /// session.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CampaignError`] if the store cannot be read.
+    pub async fn trauma_xp(&mut self, character: Uuid) -> Result<Vec<Contribution>> {
+        let mut contributions = Vec::new();
+        for trauma in self.marked(character).await? {
+            contributions.extend(trauma.effects.into_iter().filter_map(|effect| match effect {
+                TraumaEffect::Xp { trigger, xp } => Some(Contribution {
+                    trauma: trauma.id,
+                    trigger,
+                    xp,
+                }),
+                TraumaEffect::Prompt { .. } => None,
+            }));
+        }
+
+        Ok(contributions)
+    }
+
+    /// Returns the definitions of the conditions the character has marked, skipping those no longer in the pack.
+    async fn marked(&mut self, character: Uuid) -> Result<Vec<TraumaDefinition>> {
+        let store = self.store();
+        let traumas = store.get::<Traumas>(character).await?.unwrap_or_default();
+
+        let mut marked = Vec::with_capacity(traumas.len());
+        for trauma in traumas.iter() {
+            marked.extend(store.entry::<TraumaDefinition>(TRAUMA_CATEGORY, trauma).await?);
+        }
+
+        Ok(marked)
+    }
+}
+
+#[cfg(all(test, feature = "store"))]
+mod tests {
+    use super::*;
+    use crate::campaign::tests::bootstrap;
+
+    #[tokio::test]
+    async fn should_raise_effects_of_marked_traumas() {
+        let player = Uuid::new_v4();
+        let mut campaign = bootstrap().await;
+        let arcy = campaign
+            .enlist(Actor::Gm, player, &Character::new("Arcy"))
+            .await
+            .expect("should have enlisted character");
+        let traumas = campaign.traumas().await.expect("should have listed traumas");
+        let vicious = traumas
+            .iter()
+            .find(|trauma| trauma.descriptor.label() == "Vicious")
+            .expect("should have a Vicious trauma");
+
+        let marked = campaign
+            .mark_trauma(Actor::Player(player), arcy, vicious.id)
+            .await
+            .expect("should have marked trauma");
+        assert!(marked.contains(vicious.id));
+        assert_eq!(Some(marked), campaign.sheet(arcy).await.map(|sheet| sheet.traumas).ok());
+
+        let merciful = RollContext::new(Actor::Player(player)).tag("mercy");
+        let prompts = campaign.trauma_prompts(arcy, &merciful).await.expect("should have listed prompts");
+        assert_eq!(1, prompts.len());
+        let unrelated = RollContext::new(Actor::Player(player)).tag("unseen");
+        assert_eq!(
+            Vec::<String>::new(),
+            campaign.trauma_prompts(arcy, &unrelated).await.expect("should have listed prompts")
+        );
+
+        let xp = campaign.trauma_xp(arcy).await.expect("should have listed contributions");
+        assert_eq!(
+            vec![(vicious.id, "vice-or-trauma")],
+            xp.iter().map(|xp| (xp.trauma, xp.trigger.as_str())).collect::<Vec<_>>()
+        );
+    }
+
+    #[tokio::test]
+    async fn should_retire_character_after_max_traumas() {
+        let mut campaign = bootstrap().await;
+        let arcy = campaign
+            .enlist(Actor::Gm, Uuid::new_v4(), &Character::new("Arcy"))
+            .await
+            .expect("should have enlisted character");
+        let traumas = campaign.traumas().await.expect("should have listed traumas");
+
+        let err = campaign
+            .mark_trauma(Actor::Gm, arcy, Uuid::new_v4())
+            .await
+            .expect_err("should have refused unknown trauma");
+        assert!(matches!(err, CampaignError::MissingTrauma(_)), "unexpected error: {err}");
+
+        for trauma in &traumas[..Traumas::MAX] {
+            campaign.mark_trauma(Actor::Gm, arcy, trauma.id).await.expect("should have marked trauma");
+        }
+        let marked = campaign
+            .mark_trauma(Actor::Gm, arcy, traumas[0].id)
+            .await
+            .expect("should have marked trauma again");
+        assert!(marked.retired());
+
+        let err = campaign
+            .mark_trauma(Actor::Gm, arcy, traumas[Traumas::MAX].id)
+            .await
+            .expect_err("should have refused a fifth trauma");
+        assert!(matches!(err, CampaignError::Retired(entity) if entity == arcy), "unexpected error: {err}");
+    }
+}
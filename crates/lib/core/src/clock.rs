@@ -0,0 +1,303 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! Progress clocks track ongoing efforts, looming threats and long-term projects.
+
+#[cfg(feature = "store")]
+use darkforge_data::store::World;
+use darkforge_data::{Component, Persisted};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+#[cfg(feature = "store")]
+use uuid::Uuid;
+
+use crate::{action::Outcome, effect::Effect};
+#[cfg(feature = "store")]
+use crate::{
+    campaign::{self, CLOCK, Campaign, CampaignError},
+    changeset::{Access, Changeset},
+    permission::Actor,
+};
+
+/// Error type for clock operations.
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum ClockError {
+    /// Clocks must have at least one segment.
+    #[error("a clock must have at least one segment")]
+    NoSegments,
+    /// A clock cannot start with more segments filled than it has.
+    #[error("cannot fill {filled} segments of a {segments} segment clock")]
+    Overfilled {
+        /// The number of segments in the clock.
+        segments: u8,
+        /// The number of filled segments requested.
+        filled: u8,
+    },
+}
+
+/// A progress clock, divided into segments that fill up as the tracked effort advances.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Clock {
+    label: String,
+    segments: u8,
+    filled: u8,
+}
+
+impl Clock {
+    /// Creates a new empty clock.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClockError::NoSegments`] if `segments` is zero.
+    pub fn new(label: impl Into<String>, segments: u8) -> Result<Clock, ClockError> {
+        Clock::with_progress(label, segments, 0)
+    }
+
+    /// Creates a new clock with some segments already filled.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClockError::NoSegments`] if `segments` is zero, or [`ClockError::Overfilled`] if `filled` exceeds it.
+    pub fn with_progress(label: impl Into<String>, segments: u8, filled: u8) -> Result<Clock, ClockError> {
+        if segments == 0 {
+            return Err(ClockError::NoSegments);
+        }
+        if filled > segments {
+            return Err(ClockError::Overfilled { segments, filled });
+        }
+
+        Ok(Clock {
+            label: label.into(),
+            segments,
+            filled,
+        })
+    }
+
+    /// Returns the clock's label.
+    #[must_use]
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// Returns the number of segments in the clock.
+    #[must_use]
+    pub fn segments(&self) -> u8 {
+        self.segments
+    }
+
+    /// Returns the number of filled segments.
+    #[must_use]
+    pub fn filled(&self) -> u8 {
+        self.filled
+    }
+
+    /// Fills up to `ticks` segments and returns how many were actually filled.
+    pub fn tick(&mut self, ticks: u8) -> u8 {
+        let ticked = ticks.min(self.segments - self.filled);
+        self.filled += ticked;
+
+        ticked
+    }
+
+    /// Returns true if every segment is filled.
+    #[must_use]
+    pub fn is_complete(&self) -> bool {
+        self.filled == self.segments
+    }
+}
+
+impl Component for Clock {}
+
+impl Persisted for Clock {
+    const KIND: &'static str = "clock";
+}
+
+/// Returns the segments a fortune roll fills on a clock.
+#[must_use]
+pub fn fortune_ticks(outcome: Outcome) -> u8 {
+    match outcome {
+        Outcome::Failure => 1,
+        Outcome::Partial => 2,
+        Outcome::Success => 3,
+        Outcome::Critical => 5,
+    }
+}
+
+/// Returns the segments a score of the given effect fills on a clock.
+#[must_use]
+pub fn effect_ticks(effect: Effect) -> u8 {
+    match effect {
+        Effect::Zero => 0,
+        Effect::Limited => 1,
+        Effect::Standard => 2,
+        Effect::Great => 3,
+        Effect::Extreme => 5,
+    }
+}
+
+/// Journal entry recorded when a clock ticks.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Ticked {
+    /// The segments filled.
+    pub ticked: u8,
+    /// The clock after ticking.
+    pub clock: Clock,
+}
+
+#[cfg(feature = "store")]
+impl Campaign {
+    /// Adds a clock to the campaign, owned by the entity if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::Permission`] if the actor is not the GM, or another [`CampaignError`] if the store cannot
+    /// be written.
+    pub async fn add_clock(&mut self, actor: Actor, owner: Option<Uuid>, clock: &Clock) -> campaign::Result<Uuid> {
+        actor.require_gm()?;
+
+        let entity = Uuid::new_v4();
+        let mut changeset = Changeset::new(actor, Access::Gm).spawn(entity).insert(entity, clock)?;
+        if let Some(owner) = owner {
+            changeset = changeset.relate(owner, entity, CLOCK);
+        }
+        self.apply(changeset.record(Some(entity), "clock.added", clock)?).await?;
+
+        Ok(entity)
+    }
+
+    /// Returns every clock of the campaign, completed or not, along with its entity and owner.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CampaignError`] if the store cannot be read or holds an invalid clock.
+    pub async fn clocks(&mut self) -> campaign::Result<Vec<(Uuid, Option<Uuid>, Clock)>> {
+        let mut clocks = Vec::new();
+        for gathered in self.store().gather(&[Clock::KIND], CLOCK).await? {
+            clocks.push((gathered.entity, gathered.parent, gathered.decode()?));
+        }
+
+        Ok(clocks)
+    }
+
+    /// Fills up to `ticks` segments of the clock, and returns how many were filled along with the updated clock.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::Permission`] if the actor is not the GM, [`CampaignError::MissingClock`] if the clock
+    /// does not exist, or another [`CampaignError`] if the store cannot be read or written.
+    pub async fn tick_clock(&mut self, actor: Actor, entity: Uuid, ticks: u8) -> campaign::Result<Ticked> {
+        actor.require_gm()?;
+
+        let mut clock = self.get::<Clock>(entity).await?.ok_or(CampaignError::MissingClock(entity))?;
+        let ticked = Ticked {
+            ticked: clock.tick(ticks),
+            clock,
+        };
+
+        let changeset = Changeset::new(actor, Access::Gm)
+            .insert(entity, &ticked.clock)?
+            .record(Some(entity), "clock.ticked", &ticked)?;
+        self.apply(changeset).await?;
+
+        Ok(ticked)
+    }
+
+    /// Fills every remaining segment of the clock.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::Permission`] if the actor is not the GM, [`CampaignError::MissingClock`] if the clock
+    /// does not exist, or another [`CampaignError`] if the store cannot be read or written.
+    pub async fn complete_clock(&mut self, actor: Actor, entity: Uuid) -> campaign::Result<Ticked> {
+        self.tick_clock(actor, entity, u8::MAX).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+    #[cfg(feature = "store")]
+    use crate::campaign::tests::bootstrap;
+
+    #[rstest]
+    #[case::partial(4, 0, 2, 2, 2)]
+    #[case::exact(4, 1, 3, 3, 4)]
+    #[case::overflow(6, 5, 3, 1, 6)]
+    #[case::complete(8, 8, 1, 0, 8)]
+    fn should_tick_clock_up_to_its_segments(
+        #[case] segments: u8, #[case] filled: u8, #[case] ticks: u8, #[case] expect_ticked: u8, #[case] expect_filled: u8,
+    ) {
+        let mut clock = Clock::with_progress("Destroy the Red Sashes", segments, filled).expect("should have created clock");
+
+        assert_eq!(expect_ticked, clock.tick(ticks));
+        assert_eq!(expect_filled, clock.filled());
+        assert_eq!(expect_filled == segments, clock.is_complete());
+    }
+
+    #[rstest]
+    #[case::failure(Outcome::Failure, 1)]
+    #[case::partial(Outcome::Partial, 2)]
+    #[case::success(Outcome::Success, 3)]
+    #[case::critical(Outcome::Critical, 5)]
+    fn should_tick_clock_from_fortune_outcome(#[case] outcome: Outcome, #[case] expect: u8) {
+        assert_eq!(expect, fortune_ticks(outcome));
+    }
+
+    #[rstest]
+    #[case::no_segments(0, 0, ClockError::NoSegments)]
+    #[case::overfilled(4, 5, ClockError::Overfilled { segments: 4, filled: 5 })]
+    fn should_fail_to_create_invalid_clock(#[case] segments: u8, #[case] filled: u8, #[case] expect: ClockError) {
+        assert_eq!(Err(expect), Clock::with_progress("Invalid", segments, filled));
+    }
+
+    #[cfg(feature = "store")]
+    #[tokio::test]
+    async fn should_add_tick_and_complete_clock() {
+        let mut campaign = bootstrap().await;
+        let (lampblacks, _) = campaign.factions().await.expect("should have listed factions")[1].clone();
+        let before = campaign.clocks().await.expect("should have listed clocks").len();
+
+        let clock = Clock::new("Alarm", 4).expect("should have created clock");
+        let alarm = campaign.add_clock(Actor::Gm, None, &clock).await.expect("should have added clock");
+        let war = campaign
+            .add_clock(Actor::Gm, Some(lampblacks), &Clock::new("War", 8).expect("should have created clock"))
+            .await
+            .expect("should have added clock");
+
+        let ticked = campaign.tick_clock(Actor::Gm, alarm, 3).await.expect("should have ticked clock");
+        assert_eq!(3, ticked.ticked);
+        let completed = campaign.complete_clock(Actor::Gm, alarm).await.expect("should have completed clock");
+        assert_eq!(1, completed.ticked);
+        assert!(completed.clock.is_complete());
+
+        let clocks = campaign.clocks().await.expect("should have listed clocks");
+        assert_eq!(before + 2, clocks.len());
+        assert!(clocks.contains(&(alarm, None, completed.clock)));
+        assert!(clocks.contains(&(war, Some(lampblacks), Clock::new("War", 8).expect("should have created clock"))));
+    }
+
+    #[cfg(feature = "store")]
+    #[tokio::test]
+    async fn should_fail_to_tick_missing_clock() {
+        let mut campaign = bootstrap().await;
+        let missing = Uuid::new_v4();
+
+        let err = campaign
+            .tick_clock(Actor::Gm, missing, 1)
+            .await
+            .expect_err("should have failed to tick clock");
+
+        assert!(matches!(err, CampaignError::MissingClock(id) if id == missing), "unexpected error: {err}");
+    }
+}
@@ -0,0 +1,477 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! Resolving the consequences characters suffer, and the armor they can use to mitigate them.
+//!
+//! Harm is resolved in two steps: [`Campaign::propose_harm`] lists the armor the character can use, then
+//! [`Campaign::resolve_harm`] applies the player's choice. A consequence starting a clock is accepted with
+//! [`Campaign::start_consequence_clock`], which keeps track of the roll, score and faction it came from.
+
+#[cfg(feature = "store")]
+use darkforge_data::store::World;
+use darkforge_data::{Component, Persisted};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[cfg(feature = "store")]
+use crate::{
+    campaign::{CLOCK, Campaign, CampaignError, Result},
+    changeset::{Access, Changeset},
+    character::Harm,
+    faction::Faction,
+    loadout::Loadout,
+    permission::Actor,
+    roll::ROLLED,
+};
+use crate::{clock::Clock, item::ArmorKind};
+
+/// Kind of the journal entries recorded when a clock is started as a consequence, see [`ClockStarted`].
+pub const CLOCK_STARTED: &str = "consequence.clock_started";
+
+/// Kinds of the journal entries recording rolls that can trigger consequences.
+#[cfg(feature = "store")]
+const ROLLS: [&str; 2] = [ROLLED, "action.resolved"];
+
+/// The armor boxes a character marked since their last downtime.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ArmorUses {
+    #[serde(default)]
+    special: bool,
+    #[serde(default)]
+    used: Vec<ArmorKind>,
+}
+
+impl ArmorUses {
+    /// Returns the uses with special armor granted, e.g. by a playbook ability.
+    #[must_use]
+    pub fn with_special(mut self) -> ArmorUses {
+        self.special = true;
+        self
+    }
+
+    /// Returns true if the character has special armor.
+    #[must_use]
+    pub fn has_special(&self) -> bool {
+        self.special
+    }
+
+    /// Returns true if the box of the given kind is marked.
+    #[must_use]
+    pub fn is_used(&self, kind: ArmorKind) -> bool {
+        self.used.contains(&kind)
+    }
+
+    /// Marks the box of the given kind.
+    pub fn mark(&mut self, kind: ArmorKind) {
+        if !self.is_used(kind) {
+            self.used.push(kind);
+        }
+    }
+
+    /// Clears every marked box.
+    pub fn clear(&mut self) {
+        self.used.clear();
+    }
+}
+
+impl Component for ArmorUses {}
+
+impl Persisted for ArmorUses {
+    const KIND: &'static str = "armor";
+}
+
+/// Harm a character is about to suffer.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct HarmConsequence {
+    /// The level of harm, from 1 (lesser) to 4 (fatal).
+    pub level: u8,
+    /// What the harm is, e.g. "Broken leg".
+    pub description: String,
+}
+
+/// Harm awaiting the player's choice of armor.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HarmResolution {
+    entity: Uuid,
+    harm: HarmConsequence,
+    options: Vec<ArmorKind>,
+}
+
+impl HarmResolution {
+    /// Returns the character suffering the harm.
+    #[must_use]
+    pub fn entity(&self) -> Uuid {
+        self.entity
+    }
+
+    /// Returns the harm being resolved.
+    #[must_use]
+    pub fn harm(&self) -> &HarmConsequence {
+        &self.harm
+    }
+
+    /// Returns the armor the character can use to reduce the harm.
+    #[must_use]
+    pub fn options(&self) -> &[ArmorKind] {
+        &self.options
+    }
+}
+
+/// The outcome of resolving harm.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ResolvedHarm {
+    /// The armor used to reduce the harm, if any.
+    pub armor: Option<ArmorKind>,
+    /// The level of harm after mitigation.
+    pub level: u8,
+    /// The level the harm was marked at on the character sheet, 0 if it was avoided entirely.
+    pub marked: u8,
+}
+
+/// Journal entry recording the use of armor.
+#[cfg(feature = "store")]
+#[derive(Serialize)]
+struct Mitigation {
+    armor: ArmorKind,
+    from: u8,
+    to: u8,
+}
+
+/// A consequence starting a new clock, e.g. the Bluecoats closing in after a botched escape.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ClockConsequence {
+    /// The clock to start.
+    pub clock: Clock,
+    /// The sequence number of the journal entry of the roll triggering the consequence.
+    pub roll: i64,
+    /// The faction behind the consequence, which owns the clock, if any.
+    pub faction: Option<Uuid>,
+}
+
+/// Where a clock started as a consequence came from, attached to the clock.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ClockOrigin {
+    /// The sequence number of the journal entry of the roll triggering the consequence.
+    pub roll: i64,
+    /// The sequence number of the journal entry marking the start of the score the roll was made during, if any.
+    pub score: Option<i64>,
+    /// The faction behind the consequence, if any.
+    pub faction: Option<Uuid>,
+}
+
+impl Component for ClockOrigin {}
+
+impl Persisted for ClockOrigin {
+    const KIND: &'static str = "clock_origin";
+}
+
+/// Journal entry recorded when a clock is started as a consequence.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ClockStarted {
+    /// The clock started.
+    pub clock: Clock,
+    /// Where the clock came from.
+    pub origin: ClockOrigin,
+}
+
+#[cfg(feature = "store")]
+impl Campaign {
+    /// Starts resolving harm suffered by the character, listing the armor they can use.
+    ///
+    /// Regular and heavy armor are offered when carried in the character's loadout and not marked yet, heavy armor only
+    /// once regular armor is marked. Special armor is offered when the character has it and it is not marked yet.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CampaignError`] if the store cannot be read.
+    pub async fn propose_harm(&mut self, entity: Uuid, harm: HarmConsequence) -> Result<HarmResolution> {
+        let store = self.store();
+        let loadout = store.get::<Loadout>(entity).await?.unwrap_or_default();
+        let uses = store.get::<ArmorUses>(entity).await?.unwrap_or_default();
+
+        let mut options = Vec::new();
+        if loadout.has_armor(ArmorKind::Armor) && !uses.is_used(ArmorKind::Armor) {
+            options.push(ArmorKind::Armor);
+        }
+        if loadout.has_armor(ArmorKind::Heavy) && uses.is_used(ArmorKind::Armor) && !uses.is_used(ArmorKind::Heavy) {
+            options.push(ArmorKind::Heavy);
+        }
+        if uses.has_special() && !uses.is_used(ArmorKind::Special) {
+            options.push(ArmorKind::Special);
+        }
+
+        Ok(HarmResolution { entity, harm, options })
+    }
+
+    /// Resolves harm with the player's choice of armor. Using armor marks its box and reduces the harm by one level,
+    /// avoiding lesser harm entirely. The mitigation and the harm suffered are recorded in the journal.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::ArmorUnavailable`] if the chosen armor was not offered, [`CampaignError::Permission`]
+    /// if the actor does not own the character, or another [`CampaignError`] if the store cannot be written.
+    pub async fn resolve_harm(&mut self, actor: Actor, resolution: HarmResolution, choice: Option<ArmorKind>) -> Result<ResolvedHarm> {
+        let HarmResolution { entity, harm, options } = resolution;
+
+        let mut changesets = Vec::new();
+        let mut level = harm.level;
+        if let Some(armor) = choice {
+            if !options.contains(&armor) {
+                return Err(CampaignError::ArmorUnavailable(armor));
+            }
+
+            let mut uses = self.get::<ArmorUses>(entity).await?.unwrap_or_default();
+            uses.mark(armor);
+
+            level = level.saturating_sub(1);
+            let mitigation = Mitigation {
+                armor,
+                from: harm.level,
+                to: level,
+            };
+            changesets.push(Changeset::new(actor, Access::Owner(entity)).insert(entity, &uses)?.record(
+                Some(entity),
+                "harm.mitigated",
+                &mitigation,
+            )?);
+        }
+
+        let mut suffered = Changeset::new(actor, Access::Owner(entity));
+        let mut marked = 0;
+        if level > 0 {
+            let mut sheet = self.get::<Harm>(entity).await?.unwrap_or_default();
+            marked = sheet.suffer(level, harm.description.clone());
+            suffered = suffered.insert(entity, &sheet)?;
+        }
+
+        let resolved = ResolvedHarm {
+            armor: choice,
+            level,
+            marked,
+        };
+        changesets.push(suffered.record(Some(entity), "harm.suffered", &resolved)?);
+        self.apply_all(changesets).await?;
+
+        Ok(resolved)
+    }
+
+    /// Starts the clock of a consequence the GM accepted, owned by the faction behind it if any, and links it to the
+    /// roll that triggered it and the score the roll was made during, see [`ClockOrigin`]. Returns the clock entity.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::Permission`] if the actor is not the GM, [`CampaignError::MissingRoll`] if the journal
+    /// entry is not a roll, [`CampaignError::MissingFaction`] if the faction does not exist, or another
+    /// [`CampaignError`] if the store cannot be read or written.
+    pub async fn start_consequence_clock(&mut self, actor: Actor, consequence: ClockConsequence) -> Result<Uuid> {
+        actor.require_gm()?;
+
+        let ClockConsequence { clock, roll, faction } = consequence;
+        let entries = self.store().journal_between(roll - 1, Some(roll + 1)).await?;
+        if !entries.iter().any(|entry| ROLLS.contains(&entry.kind.as_str())) {
+            return Err(CampaignError::MissingRoll(roll));
+        }
+        if let Some(faction) = faction {
+            self.get::<Faction>(faction).await?.ok_or(CampaignError::MissingFaction(faction))?;
+        }
+        let score = self
+            .scores()
+            .await?
+            .into_iter()
+            .find(|score| score.seq < roll && score.ended.is_none_or(|ended| roll < ended))
+            .map(|score| score.seq);
+
+        let origin = ClockOrigin { roll, score, faction };
+        let entity = Uuid::new_v4();
+        let mut changeset = Changeset::new(actor, Access::Gm)
+            .spawn(entity)
+            .insert(entity, &clock)?
+            .insert(entity, &origin)?;
+        if let Some(faction) = faction {
+            changeset = changeset.relate(faction, entity, CLOCK);
+        }
+        self.apply(changeset.record(Some(entity), CLOCK_STARTED, &ClockStarted { clock, origin })?)
+            .await?;
+
+        Ok(entity)
+    }
+
+    /// Returns where the clock came from, if it was started as a consequence.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CampaignError`] if the store cannot be read.
+    pub async fn clock_origin(&mut self, clock: Uuid) -> Result<Option<ClockOrigin>> {
+        self.get::<ClockOrigin>(clock).await
+    }
+}
+
+#[cfg(all(test, feature = "store"))]
+mod tests {
+    use darkforge_data::descriptor::Descriptor;
+    use rstest::rstest;
+
+    use super::*;
+    use crate::{action::tests::Loaded, campaign::tests::bootstrap, item::Item, loadout::Load, permission::Owner};
+
+    /// Bootstraps a campaign with a character carrying the given armor.
+    async fn campaign(armor: &[ArmorKind], uses: ArmorUses) -> (Campaign, Uuid) {
+        let mut campaign = bootstrap().await;
+
+        let mut loadout = Loadout::new(Load::Heavy);
+        for kind in armor {
+            let item = Item::new(Descriptor::new(Uuid::nil(), "Armor", ""), 1).armor(*kind);
+            loadout.carry(item).expect("should have carried armor");
+        }
+
+        let store = campaign.store();
+        let pc = store.spawn().await.expect("should have spawned character");
+        store.insert(pc, &loadout).await.expect("should have inserted loadout");
+        store.insert(pc, &uses).await.expect("should have inserted armor uses");
+
+        (campaign, pc)
+    }
+
+    fn harm(level: u8) -> HarmConsequence {
+        HarmConsequence {
+            level,
+            description: "Shot in the chest".into(),
+        }
+    }
+
+    #[rstest]
+    #[case::none(&[], ArmorUses::default(), vec![])]
+    #[case::armor(&[ArmorKind::Armor, ArmorKind::Heavy], ArmorUses::default(), vec![ArmorKind::Armor])]
+    #[case::heavy(&[ArmorKind::Armor, ArmorKind::Heavy], { let mut uses = ArmorUses::default(); uses.mark(ArmorKind::Armor); uses }, vec![ArmorKind::Heavy])]
+    #[case::special(&[], ArmorUses::default().with_special(), vec![ArmorKind::Special])]
+    #[tokio::test]
+    async fn should_offer_available_armor(#[case] armor: &[ArmorKind], #[case] uses: ArmorUses, #[case] expect: Vec<ArmorKind>) {
+        let (mut campaign, pc) = campaign(armor, uses).await;
+
+        let resolution = campaign.propose_harm(pc, harm(2)).await.expect("should have proposed harm");
+
+        assert_eq!(expect, resolution.options());
+    }
+
+    #[tokio::test]
+    async fn should_consume_armor_and_reduce_harm() {
+        let (mut campaign, pc) = campaign(&[ArmorKind::Armor], ArmorUses::default()).await;
+
+        let resolution = campaign.propose_harm(pc, harm(3)).await.expect("should have proposed harm");
+        let resolved = campaign
+            .resolve_harm(Actor::Gm, resolution, Some(ArmorKind::Armor))
+            .await
+            .expect("should have resolved harm");
+
+        assert_eq!(
+            ResolvedHarm {
+                armor: Some(ArmorKind::Armor),
+                level: 2,
+                marked: 2
+            },
+            resolved
+        );
+
+        let store = campaign.store();
+        let uses = store
+            .get::<ArmorUses>(pc)
+            .await
+            .expect("should have read uses")
+            .expect("should have uses");
+        assert!(uses.is_used(ArmorKind::Armor));
+        let sheet = store.get::<Harm>(pc).await.expect("should have read harm").expect("should have harm");
+        assert_eq!(vec!["Shot in the chest".to_owned()], sheet.moderate);
+
+        let resolution = campaign.propose_harm(pc, harm(1)).await.expect("should have proposed harm");
+        let err = campaign
+            .resolve_harm(Actor::Gm, resolution, Some(ArmorKind::Armor))
+            .await
+            .expect_err("should have refused used armor");
+        assert!(
+            matches!(err, CampaignError::ArmorUnavailable(ArmorKind::Armor)),
+            "unexpected error: {err}"
+        );
+    }
+
+    #[tokio::test]
+    async fn should_let_owner_avoid_lesser_harm() {
+        let player = Uuid::new_v4();
+        let (mut campaign, pc) = campaign(&[], ArmorUses::default().with_special()).await;
+        campaign.assign(Actor::Gm, pc, player).await.expect("should have assigned character");
+
+        let resolution = campaign.propose_harm(pc, harm(1)).await.expect("should have proposed harm");
+        let resolved = campaign
+            .resolve_harm(Actor::Player(player), resolution, Some(ArmorKind::Special))
+            .await
+            .expect("should have resolved harm");
+
+        assert_eq!(0, resolved.marked);
+        assert_eq!(None, campaign.store().get::<Harm>(pc).await.expect("should have read harm"));
+        assert_eq!(
+            Some(Owner(player)),
+            campaign.store().get::<Owner>(pc).await.expect("should have read owner")
+        );
+    }
+
+    #[tokio::test]
+    async fn should_start_consequence_clock_linked_to_roll_score_and_faction() {
+        let mut campaign = bootstrap().await;
+        let (faction, _) = campaign.factions().await.expect("should have listed factions")[0].clone();
+        let score = campaign.start_score(Actor::Gm, "Lampblacks").await.expect("should have started score");
+        campaign
+            .roll(Actor::Gm, None, "Prowl", 2, &Loaded::new(&[1, 3]))
+            .await
+            .expect("should have rolled");
+        let roll = campaign.rolls(None, 1).await.expect("should have listed rolls")[0].seq;
+
+        let consequence = ClockConsequence {
+            clock: Clock::new("The Bluecoats close in", 4).expect("should have created clock"),
+            roll,
+            faction: Some(faction),
+        };
+        let clock = campaign
+            .start_consequence_clock(Actor::Gm, consequence.clone())
+            .await
+            .expect("should have started clock");
+
+        assert_eq!(
+            Some(ClockOrigin {
+                roll,
+                score: Some(score.seq),
+                faction: Some(faction)
+            }),
+            campaign.clock_origin(clock).await.expect("should have read origin")
+        );
+        assert!(
+            campaign
+                .clocks()
+                .await
+                .expect("should have listed clocks")
+                .contains(&(clock, Some(faction), consequence.clock.clone()))
+        );
+
+        let err = campaign
+            .start_consequence_clock(
+                Actor::Gm,
+                ClockConsequence {
+                    roll: score.seq,
+                    ..consequence
+                },
+            )
+            .await
+            .expect_err("should have refused entry that is not a roll");
+        assert!(
+            matches!(err, CampaignError::MissingRoll(seq) if seq == score.seq),
+            "unexpected error: {err}"
+        );
+    }
+}
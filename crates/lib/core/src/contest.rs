@@ -0,0 +1,382 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! Contests pit two faction clocks against each other, e.g. in a turf war or a long-term rivalry.
+//!
+//! Each side of a [`Contest`] races to fill its own clock. Clocks tick during downtime, with a fortune roll at each
+//! faction's tier, and when the crew scores for one side, by the effect of the score. The first side to fill its
+//! clock wins and the contest is resolved.
+
+#[cfg(feature = "store")]
+use darkforge_data::store::World;
+use darkforge_data::{Component, Persisted};
+#[cfg(feature = "store")]
+use darkforge_rng::dice::Dice;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::action::FortuneRoll;
+#[cfg(feature = "store")]
+use crate::{
+    campaign::{CLOCK, Campaign, CampaignError, Result},
+    changeset::{Access, Changeset},
+    clock::{Clock, effect_ticks, fortune_ticks},
+    effect::Effect,
+    faction::Faction,
+    permission::Actor,
+};
+
+/// Kind of the relationship from the campaign to its contests.
+pub const CONTEST: &str = "contest";
+
+/// One side of a contest.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Side {
+    /// The faction entity.
+    pub faction: Uuid,
+    /// The clock entity the faction fills to win.
+    pub clock: Uuid,
+}
+
+/// Two factions racing to fill their clocks.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Contest {
+    label: String,
+    sides: [Side; 2],
+    winner: Option<Uuid>,
+}
+
+impl Contest {
+    /// Returns the contest's label.
+    #[must_use]
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// Returns both sides of the contest, the challenger first.
+    #[must_use]
+    pub fn sides(&self) -> &[Side; 2] {
+        &self.sides
+    }
+
+    /// Returns the winning faction, if the contest is resolved.
+    #[must_use]
+    pub fn winner(&self) -> Option<Uuid> {
+        self.winner
+    }
+
+    /// Returns true if one side has won.
+    #[must_use]
+    pub fn is_resolved(&self) -> bool {
+        self.winner.is_some()
+    }
+}
+
+impl Component for Contest {}
+
+impl Persisted for Contest {
+    const KIND: &'static str = "contest";
+}
+
+/// Changes applied by advancing a contest.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Progress {
+    /// The fortune rolls made for each side during downtime, empty when the crew scored.
+    pub rolls: Vec<FortuneRoll>,
+    /// The segments filled on each side's clock.
+    pub ticks: [u8; 2],
+    /// The winning faction, if the contest was resolved.
+    pub winner: Option<Uuid>,
+}
+
+/// Journal entry recorded when a contest is resolved.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Resolution {
+    /// The contest's label.
+    pub label: String,
+    /// The winning faction.
+    pub winner: Uuid,
+    /// The losing faction.
+    pub loser: Uuid,
+}
+
+#[cfg(feature = "store")]
+impl Campaign {
+    /// Starts a contest between two factions, each given a new clock with the given number of segments.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::Permission`] if the actor is not the GM, [`CampaignError::MissingFaction`] if a
+    /// faction does not exist, [`CampaignError::InvalidClock`] if a clock has no segments, or another
+    /// [`CampaignError`] if the store cannot be written.
+    pub async fn start_contest(&mut self, actor: Actor, label: impl Into<String>, factions: [(Uuid, u8); 2]) -> Result<Uuid> {
+        actor.require_gm()?;
+
+        let label = label.into();
+        let id = self.id();
+        let store = self.store();
+
+        let mut clocks = Vec::with_capacity(2);
+        for (faction, segments) in factions {
+            let loaded = store.get::<Faction>(faction).await?.ok_or(CampaignError::MissingFaction(faction))?;
+            let clock = Clock::new(label.clone(), segments).map_err(|source| CampaignError::InvalidClock {
+                faction: loaded.descriptor().label().into(),
+                source,
+            })?;
+            clocks.push((faction, clock));
+        }
+
+        let mut changeset = Changeset::new(actor, Access::Gm);
+        let mut sides = Vec::with_capacity(2);
+        for (faction, clock) in clocks {
+            let entity = Uuid::new_v4();
+            changeset = changeset.spawn(entity).insert(entity, &clock)?.relate(faction, entity, CLOCK);
+            sides.push(Side { faction, clock: entity });
+        }
+
+        let contest = Contest {
+            label,
+            sides: [sides[0], sides[1]],
+            winner: None,
+        };
+        let entity = Uuid::new_v4();
+        let changeset =
+            changeset
+                .spawn(entity)
+                .insert(entity, &contest)?
+                .relate(id, entity, CONTEST)
+                .record(Some(entity), "contest.started", &contest)?;
+        self.apply(changeset).await?;
+
+        Ok(entity)
+    }
+
+    /// Returns the contests of the campaign, resolved or not.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CampaignError`] if the store cannot be read.
+    pub async fn contests(&mut self) -> Result<Vec<(Uuid, Contest)>> {
+        let id = self.id();
+        let store = self.store();
+
+        let mut contests = Vec::new();
+        for entity in store.related(id, CONTEST).await? {
+            if let Some(contest) = store.get::<Contest>(entity).await? {
+                contests.push((entity, contest));
+            }
+        }
+
+        Ok(contests)
+    }
+
+    /// Advances the contest by a downtime phase: each side makes a fortune roll at its faction's tier with `dice`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::Permission`] if the actor is not the GM, [`CampaignError::ContestResolved`] if the
+    /// contest is over, or another [`CampaignError`] if the contest cannot be read or written, in which case nothing
+    /// is applied.
+    pub async fn contest_downtime(&mut self, actor: Actor, contest: Uuid, dice: &impl Dice) -> Result<Progress> {
+        actor.require_gm()?;
+        let loaded = self.contest(contest).await?;
+
+        let mut rolls = Vec::with_capacity(2);
+        for side in loaded.sides {
+            let faction = self
+                .store()
+                .get::<Faction>(side.faction)
+                .await?
+                .ok_or(CampaignError::MissingFaction(side.faction))?;
+            rolls.push(FortuneRoll::roll(dice, faction.tier()));
+        }
+
+        let ticks = [fortune_ticks(rolls[0].outcome), fortune_ticks(rolls[1].outcome)];
+        self.advance(actor, contest, loaded, ticks, rolls).await
+    }
+
+    /// Advances the contest after the crew scored in favour of the faction, ticking its clock by the score's effect.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::Permission`] if the actor is not the GM, [`CampaignError::ContestResolved`] if the
+    /// contest is over, [`CampaignError::MissingFaction`] if the faction does not take part in the contest, or another
+    /// [`CampaignError`] if the contest cannot be read or written, in which case nothing is applied.
+    pub async fn contest_score(&mut self, actor: Actor, contest: Uuid, faction: Uuid, effect: Effect) -> Result<Progress> {
+        actor.require_gm()?;
+        let loaded = self.contest(contest).await?;
+
+        let Some(index) = loaded.sides.iter().position(|side| side.faction == faction) else {
+            return Err(CampaignError::MissingFaction(faction));
+        };
+        let mut ticks = [0; 2];
+        ticks[index] = effect_ticks(effect);
+
+        self.advance(actor, contest, loaded, ticks, Vec::new()).await
+    }
+
+    /// Loads a contest that is still running.
+    async fn contest(&mut self, contest: Uuid) -> Result<Contest> {
+        let loaded = self
+            .store()
+            .get::<Contest>(contest)
+            .await?
+            .ok_or(CampaignError::MissingContest(contest))?;
+        if loaded.is_resolved() {
+            return Err(CampaignError::ContestResolved(contest));
+        }
+
+        Ok(loaded)
+    }
+
+    /// Ticks both clocks, resolves the contest if a clock is complete and commits everything in one transaction.
+    ///
+    /// If both clocks complete at once, the side that filled more segments wins, the challenger on a tie.
+    async fn advance(&mut self, actor: Actor, entity: Uuid, mut contest: Contest, ticks: [u8; 2], rolls: Vec<FortuneRoll>) -> Result<Progress> {
+        let store = self.store();
+
+        let mut progress = Progress {
+            rolls,
+            ticks: [0; 2],
+            winner: None,
+        };
+        let mut advanced = Changeset::new(actor, Access::Gm);
+        let mut complete = [false; 2];
+        for (index, side) in contest.sides.iter().enumerate() {
+            let mut clock = store.get::<Clock>(side.clock).await?.ok_or(CampaignError::MissingClock(side.clock))?;
+            progress.ticks[index] = clock.tick(ticks[index]);
+            complete[index] = clock.is_complete();
+            advanced = advanced.insert(side.clock, &clock)?;
+        }
+
+        let winner = match complete {
+            [true, true] => Some(usize::from(progress.ticks[1] > progress.ticks[0])),
+            [true, false] => Some(0),
+            [false, true] => Some(1),
+            [false, false] => None,
+        };
+
+        let mut changesets = vec![advanced.record(Some(entity), "contest.advanced", &progress)?];
+        if let Some(index) = winner {
+            let resolution = Resolution {
+                label: contest.label.clone(),
+                winner: contest.sides[index].faction,
+                loser: contest.sides[1 - index].faction,
+            };
+            contest.winner = Some(resolution.winner);
+            progress.winner = Some(resolution.winner);
+            changesets.push(
+                Changeset::new(actor, Access::Gm)
+                    .insert(entity, &contest)?
+                    .record(Some(entity), "contest.resolved", &resolution)?,
+            );
+        }
+
+        self.apply_all(changesets).await?;
+
+        Ok(progress)
+    }
+}
+
+#[cfg(all(test, feature = "store"))]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+    use crate::{action::tests::Loaded, campaign::tests::bootstrap};
+
+    /// Returns the entity of the faction with the given label.
+    fn faction(factions: &[(Uuid, Faction)], label: &str) -> Uuid {
+        factions
+            .iter()
+            .find(|(_, faction)| faction.descriptor().label() == label)
+            .map(|(entity, _)| *entity)
+            .expect("should have found faction")
+    }
+
+    /// Starts a contest between the Lampblacks and the Red Sashes, both tier 2.
+    async fn start(segments: [u8; 2]) -> (Campaign, Uuid, [Uuid; 2]) {
+        let mut campaign = bootstrap().await;
+        let factions = campaign.factions().await.expect("should have listed factions");
+        let sides = [faction(&factions, "Lampblacks"), faction(&factions, "Red Sashes")];
+
+        let contest = campaign
+            .start_contest(Actor::Gm, "Turf war", [(sides[0], segments[0]), (sides[1], segments[1])])
+            .await
+            .expect("should have started contest");
+
+        (campaign, contest, sides)
+    }
+
+    #[tokio::test]
+    async fn should_resolve_contest_when_score_fills_clock() {
+        let (mut campaign, contest, sides) = start([4, 4]).await;
+
+        let progress = campaign
+            .contest_score(Actor::Gm, contest, sides[1], Effect::Standard)
+            .await
+            .expect("should have advanced contest");
+        assert_eq!([0, 2], progress.ticks);
+        assert_eq!(None, progress.winner);
+
+        let progress = campaign
+            .contest_score(Actor::Gm, contest, sides[1], Effect::Great)
+            .await
+            .expect("should have advanced contest");
+        assert_eq!([0, 2], progress.ticks);
+        assert_eq!(Some(sides[1]), progress.winner);
+
+        let contests = campaign.contests().await.expect("should have listed contests");
+        assert_eq!(1, contests.len());
+        assert_eq!(Some(sides[1]), contests[0].1.winner());
+
+        let err = campaign
+            .contest_score(Actor::Gm, contest, sides[0], Effect::Extreme)
+            .await
+            .expect_err("should have refused to advance resolved contest");
+        assert!(
+            matches!(err, CampaignError::ContestResolved(id) if id == contest),
+            "unexpected error: {err}"
+        );
+    }
+
+    #[rstest]
+    #[case::challenger_ahead(&[6, 6, 4, 1], [5, 2], Some(0))]
+    #[case::defender_ahead(&[4, 1, 6, 6], [2, 5], Some(1))]
+    #[case::tie(&[6, 1, 6, 1], [3, 3], Some(0))]
+    #[case::running(&[1, 1, 1, 1], [8, 8], None)]
+    #[tokio::test]
+    async fn should_advance_contest_during_downtime(#[case] dice: &[u8], #[case] segments: [u8; 2], #[case] expect: Option<usize>) {
+        let (mut campaign, contest, sides) = start(segments).await;
+
+        let progress = campaign
+            .contest_downtime(Actor::Gm, contest, &Loaded::new(dice))
+            .await
+            .expect("should have advanced contest");
+
+        assert_eq!(2, progress.rolls.len());
+        assert_eq!(expect.map(|index| sides[index]), progress.winner);
+    }
+
+    #[tokio::test]
+    async fn should_only_let_gm_start_contest() {
+        let mut campaign = bootstrap().await;
+        let factions = campaign.factions().await.expect("should have listed factions");
+
+        let err = campaign
+            .start_contest(Actor::Player(Uuid::new_v4()), "Turf war", [(factions[0].0, 4), (factions[1].0, 4)])
+            .await
+            .expect_err("should have refused player");
+
+        assert!(matches!(err, CampaignError::Permission(_)), "unexpected error: {err}");
+    }
+}
@@ -0,0 +1,212 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! The fictional context of a roll: who acts against whom, with which action, from which position and to which
+//! effect, along with free-form tags such as "unseen" or "on hostile turf".
+//!
+//! The context is recorded in the journal with the roll, so later analytics and modifiers can depend on the fiction
+//! that led to it. Modifiers subscribe to tags by pattern, see [`RollContext::matches`], so an ability granting a die
+//! `on *` triggers on rolls tagged `on water` as well as `on hostile turf`.
+
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{effect::Effect, permission::Actor};
+
+/// How dangerous an action is, from safest to most dangerous.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Position {
+    /// The character has a dominant advantage, and failure costs little.
+    Controlled,
+    /// The character acts on even terms, the usual position.
+    #[default]
+    Risky,
+    /// The character overreaches, and failure costs dearly.
+    Desperate,
+}
+
+impl Position {
+    /// Every position, from safest to most dangerous.
+    pub const LEVELS: [Position; 3] = [Position::Controlled, Position::Risky, Position::Desperate];
+
+    /// Returns the position shifted by the given number of steps toward danger, or toward safety if negative,
+    /// saturating at [`Position::Controlled`] and [`Position::Desperate`].
+    #[must_use]
+    pub fn shift(self, steps: i8) -> Position {
+        let index = (self as i8).saturating_add(steps).clamp(0, 2);
+        Position::LEVELS[index.unsigned_abs() as usize]
+    }
+}
+
+/// The fictional context of a roll.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct RollContext {
+    /// Who makes the roll.
+    pub actor: Actor,
+    /// The entity the action is directed at, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<Uuid>,
+    /// The id of the action used, as defined in the content pack, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub action: Option<Uuid>,
+    /// How dangerous the action is.
+    #[serde(default)]
+    pub position: Position,
+    /// How much the action can accomplish.
+    #[serde(default)]
+    pub effect: Effect,
+    /// Free-form tags describing the fiction, e.g. "unseen" or "on hostile turf".
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub tags: BTreeSet<String>,
+}
+
+impl RollContext {
+    /// Creates the context of a roll made by the actor, from a risky position to standard effect.
+    #[must_use]
+    pub fn new(actor: Actor) -> RollContext {
+        RollContext {
+            actor,
+            target: None,
+            action: None,
+            position: Position::default(),
+            effect: Effect::default(),
+            tags: BTreeSet::new(),
+        }
+    }
+
+    /// Sets the entity the action is directed at.
+    #[must_use]
+    pub fn target(mut self, target: Uuid) -> RollContext {
+        self.target = Some(target);
+        self
+    }
+
+    /// Sets the action used.
+    #[must_use]
+    pub fn action(mut self, action: Uuid) -> RollContext {
+        self.action = Some(action);
+        self
+    }
+
+    /// Sets the position of the action.
+    #[must_use]
+    pub fn position(mut self, position: Position) -> RollContext {
+        self.position = position;
+        self
+    }
+
+    /// Sets the effect of the action.
+    #[must_use]
+    pub fn effect(mut self, effect: Effect) -> RollContext {
+        self.effect = effect;
+        self
+    }
+
+    /// Adds a tag describing the fiction.
+    #[must_use]
+    pub fn tag(mut self, tag: impl Into<String>) -> RollContext {
+        self.tags.insert(tag.into());
+        self
+    }
+
+    /// Returns whether the context carries the tag.
+    #[must_use]
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.contains(tag)
+    }
+
+    /// Returns whether any tag of the context matches the pattern, in which `*` stands for any run of characters, e.g.
+    /// `in *` matching `in darkness`. A pattern without `*` matches the tag exactly, as [`RollContext::has_tag`] does.
+    #[must_use]
+    pub fn matches(&self, pattern: &str) -> bool {
+        self.tags.iter().any(|tag| glob(pattern, tag))
+    }
+
+    /// Returns whether any tag of the context matches any of the patterns, see [`RollContext::matches`].
+    #[must_use]
+    pub fn matches_any<'a>(&self, patterns: impl IntoIterator<Item = &'a String>) -> bool {
+        patterns.into_iter().any(|pattern| self.matches(pattern))
+    }
+}
+
+/// Returns whether the tag matches the pattern, in which `*` stands for any run of characters.
+fn glob(pattern: &str, tag: &str) -> bool {
+    let mut parts = pattern.split('*').collect::<Vec<_>>();
+    let last = parts.pop().unwrap_or_default();
+    let Some((first, middle)) = parts.split_first() else {
+        return tag == last;
+    };
+
+    let Some(mut rest) = tag.strip_prefix(first) else {
+        return false;
+    };
+    for part in middle {
+        let Some(index) = rest.find(part) else {
+            return false;
+        };
+        rest = &rest[index + part.len()..];
+    }
+
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use darkforge_data::JSONDeserialize;
+    use rstest::rstest;
+
+    use super::*;
+
+    #[test]
+    fn should_build_context_with_tags() {
+        let target = Uuid::new_v4();
+
+        let context = RollContext::new(Actor::Gm)
+            .target(target)
+            .position(Position::Desperate)
+            .effect(Effect::Limited)
+            .tag("unseen")
+            .tag("on hostile turf");
+
+        assert_eq!(Some(target), context.target);
+        assert_eq!(Position::Desperate, context.position);
+        assert_eq!(Effect::Limited, context.effect);
+        assert!(context.has_tag("unseen"));
+        assert!(!context.has_tag("cornered"));
+    }
+
+    #[rstest]
+    #[case::exact("in darkness", "in darkness", true)]
+    #[case::exact_mismatch("in darkness", "in the light", false)]
+    #[case::prefix("in *", "in darkness", true)]
+    #[case::suffix("* water", "on water", true)]
+    #[case::infix("on * turf", "on hostile turf", true)]
+    #[case::infix_mismatch("on * turf", "on hostile ground", false)]
+    #[case::many("*o*o*", "on hostile turf", true)]
+    #[case::overlapping_suffix("a*ab", "ab", false)]
+    #[case::any("*", "deception", true)]
+    fn should_match_tags_by_pattern(#[case] pattern: &str, #[case] tag: &str, #[case] expected: bool) {
+        let context = RollContext::new(Actor::Gm).tag(tag);
+
+        assert_eq!(expected, context.matches(pattern));
+    }
+
+    #[test]
+    fn should_read_context_with_defaults() {
+        let context = RollContext::from_json(r#"{ "actor": "gm" }"#.as_bytes()).expect("should have read context");
+
+        assert_eq!(RollContext::new(Actor::Gm), context);
+    }
+}
@@ -0,0 +1,603 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! The crew the player characters belong to.
+
+use std::collections::BTreeSet;
+
+#[cfg(feature = "store")]
+use darkforge_data::store::World as _;
+use darkforge_data::{Component, Persisted};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::npc::Choice;
+#[cfg(feature = "store")]
+use crate::{
+    campaign::{Campaign, CampaignError, Result},
+    changeset::{Access, Changeset},
+    faction::{Faction, Status},
+    permission::Actor,
+};
+
+/// Kind of the journal entries recorded when a crew starts.
+pub const STARTED: &str = "crew.started";
+/// Kind of the journal entries recorded when a crew is created.
+pub const CREATED: &str = "crew.created";
+/// Kind of the journal entries recorded when a character joins a crew.
+pub const JOINED: &str = "crew.joined";
+/// Relationship linking a crew to its favorite contact.
+pub const CONTACT: &str = "contact";
+/// Relationship linking a crew to each of its member characters.
+pub const MEMBER: &str = "member";
+
+/// Error type for crews that break the creation rules.
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum CrewError {
+    /// The operation is not one the crew type hunts for.
+    #[error("{kind:?} do not hunt for {operation}")]
+    HuntingGrounds {
+        /// The crew type.
+        kind: CrewType,
+        /// The operation chosen.
+        operation: String,
+    },
+    /// The crew must start with exactly [`Founding::UPGRADES`] distinct upgrades.
+    #[error("a crew starts with two distinct upgrades, not {0}")]
+    Upgrades(usize),
+    /// Faction status only shifts by one step on creation.
+    #[error("faction status shifts by one step on crew creation, not {0}")]
+    StatusShift(i8),
+}
+
+/// The types of crew, each with its own kind of operations.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CrewType {
+    /// Killers for hire.
+    Assassins,
+    /// Mercenaries, thugs and toughs.
+    Bravos,
+    /// Acolytes of a forgotten god.
+    Cult,
+    /// Vice dealers.
+    Hawkers,
+    /// Thieves, spies and saboteurs.
+    Shadows,
+    /// Suppliers of illicit goods.
+    Smugglers,
+}
+
+impl CrewType {
+    /// All crew types.
+    pub const ALL: [CrewType; 6] = [
+        CrewType::Assassins,
+        CrewType::Bravos,
+        CrewType::Cult,
+        CrewType::Hawkers,
+        CrewType::Shadows,
+        CrewType::Smugglers,
+    ];
+
+    /// Returns the operations the crew type prefers to hunt for.
+    #[must_use]
+    pub fn operations(self) -> &'static [&'static str] {
+        match self {
+            CrewType::Assassins => &["Accident", "Disappearance", "Murder", "Ransom"],
+            CrewType::Bravos => &["Battle", "Extortion", "Sabotage", "Smash & Grab"],
+            CrewType::Cult => &["Acquisition", "Augury", "Consecration", "Sacrifice"],
+            CrewType::Hawkers => &["Sale", "Supply", "Show of Force", "Socialize"],
+            CrewType::Shadows => &["Burglary", "Espionage", "Robbery", "Sabotage"],
+            CrewType::Smugglers => &["Arcane/Weird", "Arms", "Contraband", "Passengers"],
+        }
+    }
+}
+
+/// How the crew wants to be known on the streets.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Reputation {
+    /// Out to take what is theirs.
+    Ambitious,
+    /// Feared for their violence.
+    Brutal,
+    /// Known for their bold moves.
+    Daring,
+    /// True to their word.
+    Honorable,
+    /// Efficient and reliable.
+    Professional,
+    /// Clever and well connected.
+    Savvy,
+    /// Never seen coming.
+    Subtle,
+    /// Uncanny and unsettling.
+    Strange,
+}
+
+/// The upgrades a crew can start with.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Upgrade {
+    /// A carriage or other land transport.
+    Carriage,
+    /// A boat for the canals.
+    Boat,
+    /// The lair is hidden.
+    Hidden,
+    /// The lair has living quarters.
+    Quarters,
+    /// The lair is secured against intruders.
+    Secure,
+    /// The lair has a vault.
+    Vault,
+    /// The lair has a workshop.
+    Workshop,
+    /// The crew trains Insight.
+    Insight,
+    /// The crew trains Prowess.
+    Prowess,
+    /// The crew trains Resolve.
+    Resolve,
+    /// The crew trains personal playbooks.
+    Personal,
+    /// Fine documents.
+    Documents,
+    /// Fine gear.
+    Gear,
+    /// Fine implements.
+    Implements,
+    /// Fine supplies.
+    Supplies,
+    /// Fine tools.
+    Tools,
+    /// Fine weapons.
+    Weapons,
+}
+
+/// Where and how the crew operates.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct HuntingGrounds {
+    /// The district, e.g. "Crow's Foot".
+    pub district: String,
+    /// The kind of operation, see [`CrewType::operations`].
+    pub operation: String,
+}
+
+/// A crew, as created with [`Campaign::create_crew`].
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Crew {
+    /// The crew's name.
+    pub name: String,
+    /// The crew's type.
+    pub kind: CrewType,
+    /// The crew's reputation.
+    pub reputation: Reputation,
+    /// The crew's hunting grounds.
+    pub hunting_grounds: HuntingGrounds,
+    /// The crew's upgrades.
+    pub upgrades: BTreeSet<Upgrade>,
+}
+
+impl Component for Crew {}
+
+impl Persisted for Crew {
+    const KIND: &'static str = "crew";
+}
+
+/// The choices made when creating a crew.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Founding {
+    /// The crew, with its type, reputation, hunting grounds and starting upgrades.
+    pub crew: Crew,
+    /// The crew's favorite contact.
+    pub contact: Option<Choice>,
+    /// The factions whose status with the crew shifts, e.g. because they helped or were hurt by the crew.
+    pub statuses: Vec<(Uuid, i8)>,
+}
+
+impl Founding {
+    /// The number of upgrades a crew starts with.
+    pub const UPGRADES: usize = 2;
+
+    /// Starts a founding for a crew of the given type, reputation and hunting grounds, without upgrades, contact or
+    /// status shifts.
+    #[must_use]
+    pub fn new(name: impl Into<String>, kind: CrewType, reputation: Reputation, hunting_grounds: HuntingGrounds) -> Founding {
+        Founding {
+            crew: Crew {
+                name: name.into(),
+                kind,
+                reputation,
+                hunting_grounds,
+                upgrades: BTreeSet::new(),
+            },
+            contact: None,
+            statuses: Vec::new(),
+        }
+    }
+
+    /// Returns a ready-made founding for the crew type, with a name, reputation, hunting grounds and two starting
+    /// upgrades, e.g. for one-shots where there is no time to create the crew at the table.
+    #[must_use]
+    pub fn preset(kind: CrewType) -> Founding {
+        let (name, reputation, district, operation, upgrades) = match kind {
+            CrewType::Assassins => (
+                "The Quiet Hands",
+                Reputation::Professional,
+                "Six Towers",
+                "Accident",
+                [Upgrade::Hidden, Upgrade::Weapons],
+            ),
+            CrewType::Bravos => (
+                "The Iron Hounds",
+                Reputation::Brutal,
+                "Crow's Foot",
+                "Extortion",
+                [Upgrade::Quarters, Upgrade::Weapons],
+            ),
+            CrewType::Cult => (
+                "The Veiled Choir",
+                Reputation::Strange,
+                "Charhollow",
+                "Consecration",
+                [Upgrade::Hidden, Upgrade::Implements],
+            ),
+            CrewType::Hawkers => (
+                "The Velvet Lamps",
+                Reputation::Savvy,
+                "Silkshore",
+                "Sale",
+                [Upgrade::Secure, Upgrade::Supplies],
+            ),
+            CrewType::Shadows => (
+                "The Grey Cloaks",
+                Reputation::Daring,
+                "Crow's Foot",
+                "Burglary",
+                [Upgrade::Hidden, Upgrade::Tools],
+            ),
+            CrewType::Smugglers => (
+                "The Tide Runners",
+                Reputation::Subtle,
+                "Docks",
+                "Contraband",
+                [Upgrade::Boat, Upgrade::Vault],
+            ),
+        };
+        let hunting_grounds = HuntingGrounds {
+            district: district.into(),
+            operation: operation.into(),
+        };
+
+        upgrades
+            .into_iter()
+            .fold(Founding::new(name, kind, reputation, hunting_grounds), Founding::upgrade)
+    }
+
+    /// Adds a starting upgrade.
+    #[must_use]
+    pub fn upgrade(mut self, upgrade: Upgrade) -> Founding {
+        self.crew.upgrades.insert(upgrade);
+        self
+    }
+
+    /// Chooses the favorite contact.
+    #[must_use]
+    pub fn contact(mut self, contact: Choice) -> Founding {
+        self.contact = Some(contact);
+        self
+    }
+
+    /// Shifts the status of the faction by `shift`.
+    #[must_use]
+    pub fn status(mut self, faction: Uuid, shift: i8) -> Founding {
+        self.statuses.push((faction, shift));
+        self
+    }
+
+    /// Checks the choices against the crew creation rules.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CrewError::HuntingGrounds`] if the crew type does not hunt for the operation,
+    /// [`CrewError::Upgrades`] if the crew does not start with [`Founding::UPGRADES`] upgrades, or
+    /// [`CrewError::StatusShift`] if a faction status shifts by more than one step.
+    pub fn validate(&self) -> std::result::Result<(), CrewError> {
+        let crew = &self.crew;
+        if !crew.kind.operations().contains(&crew.hunting_grounds.operation.as_str()) {
+            return Err(CrewError::HuntingGrounds {
+                kind: crew.kind,
+                operation: crew.hunting_grounds.operation.clone(),
+            });
+        }
+        if crew.upgrades.len() != Founding::UPGRADES {
+            return Err(CrewError::Upgrades(crew.upgrades.len()));
+        }
+        if let Some((_, shift)) = self.statuses.iter().find(|(_, shift)| !matches!(shift, -1 | 1)) {
+            return Err(CrewError::StatusShift(*shift));
+        }
+
+        Ok(())
+    }
+}
+
+/// The heat a crew has drawn and its wanted level.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Heat {
+    heat: u8,
+    wanted: u8,
+}
+
+impl Heat {
+    /// The heat at which the crew's wanted level increases.
+    pub const MAX_HEAT: u8 = 9;
+    /// The highest wanted level.
+    pub const MAX_WANTED: u8 = 4;
+
+    /// Creates a new heat value, capped at [`Heat::MAX_HEAT`] and [`Heat::MAX_WANTED`].
+    #[must_use]
+    pub fn new(heat: u8, wanted: u8) -> Heat {
+        Heat {
+            heat: heat.min(Heat::MAX_HEAT),
+            wanted: wanted.min(Heat::MAX_WANTED),
+        }
+    }
+
+    /// Returns the crew's heat.
+    #[must_use]
+    pub fn heat(self) -> u8 {
+        self.heat
+    }
+
+    /// Returns the crew's wanted level.
+    #[must_use]
+    pub fn wanted(self) -> u8 {
+        self.wanted
+    }
+
+    /// Reduces the crew's heat by up to `by` and returns how much it was reduced.
+    pub fn reduce(&mut self, by: u8) -> u8 {
+        let reduced = by.min(self.heat);
+        self.heat -= reduced;
+
+        reduced
+    }
+}
+
+impl Component for Heat {}
+
+impl Persisted for Heat {
+    const KIND: &'static str = "heat";
+}
+
+#[cfg(feature = "store")]
+impl Campaign {
+    /// Starts a crew without heat, returning its entity.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::Permission`](crate::campaign::CampaignError::Permission) if the actor is not the GM, or
+    /// another [`CampaignError`](crate::campaign::CampaignError) if the store cannot be written.
+    pub async fn start_crew(&mut self, actor: Actor) -> Result<Uuid> {
+        actor.require_gm()?;
+
+        let heat = Heat::default();
+        let entity = Uuid::new_v4();
+        let changeset = Changeset::new(actor, Access::Gm)
+            .spawn(entity)
+            .insert(entity, &heat)?
+            .record(Some(entity), STARTED, &heat)?;
+        self.apply(changeset).await?;
+
+        Ok(entity)
+    }
+
+    /// Makes the character a member of the crew.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::MissingCrew`] if the crew has no heat, or another [`CampaignError`] if the actor is not
+    /// the GM or the store cannot be read or written.
+    pub async fn join_crew(&mut self, actor: Actor, crew: Uuid, character: Uuid) -> Result<()> {
+        actor.require_gm()?;
+        if self.store().get::<Heat>(crew).await?.is_none() {
+            return Err(CampaignError::MissingCrew(crew));
+        }
+
+        let changeset = Changeset::new(actor, Access::Gm)
+            .relate(crew, character, MEMBER)
+            .record(Some(crew), JOINED, &character)?;
+        self.apply(changeset).await?;
+
+        Ok(())
+    }
+
+    /// Returns true if the character is a member of the crew.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CampaignError`] if the store cannot be read.
+    pub async fn is_member(&mut self, crew: Uuid, character: Uuid) -> Result<bool> {
+        Ok(self.store().related(crew, MEMBER).await?.contains(&character))
+    }
+
+    /// Creates a crew without heat from the choices made, linking it to its favorite contact and shifting the status of
+    /// the factions, all at once.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::Crew`] if the choices break the crew creation rules, see [`Founding::validate`],
+    /// [`CampaignError::MissingFaction`] or [`CampaignError::MissingNpc`] if a faction or the contact does not exist,
+    /// or another [`CampaignError`] if the actor is not the GM or the store cannot be written.
+    pub async fn create_crew(&mut self, actor: Actor, founding: &Founding) -> Result<Uuid> {
+        actor.require_gm()?;
+        founding.validate()?;
+
+        let mut statuses = Vec::new();
+        for &(faction, shift) in &founding.statuses {
+            if self.store().get::<Faction>(faction).await?.is_none() {
+                return Err(CampaignError::MissingFaction(faction));
+            }
+            let status = match statuses.iter().position(|(entity, _)| *entity == faction) {
+                Some(index) => statuses.remove(index).1,
+                None => self.store().get::<Status>(faction).await?.unwrap_or_default(),
+            };
+            statuses.push((faction, Status::new(status.value() + shift)));
+        }
+        let mut created = Vec::new();
+        let contact = match &founding.contact {
+            Some(choice) => Some(self.npc(choice, &mut created).await?),
+            None => None,
+        };
+
+        let entity = Uuid::new_v4();
+        let mut changeset = Changeset::new(actor, Access::Gm)
+            .spawn(entity)
+            .insert(entity, &founding.crew)?
+            .insert(entity, &Heat::default())?;
+        for (npc, created) in &created {
+            changeset = changeset.spawn(*npc).insert(*npc, created)?;
+        }
+        if let Some(contact) = contact {
+            changeset = changeset.relate(entity, contact, CONTACT);
+        }
+        for (faction, status) in &statuses {
+            changeset = changeset.insert(*faction, status)?;
+        }
+        self.apply(changeset.record(Some(entity), CREATED, &founding.crew)?).await?;
+
+        Ok(entity)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "store")]
+    use darkforge_data::descriptor::Descriptor;
+    #[cfg(feature = "store")]
+    use rstest::rstest;
+
+    use super::*;
+    #[cfg(feature = "store")]
+    use crate::{
+        campaign::{CampaignError, tests::bootstrap},
+        npc::Npc,
+    };
+
+    #[cfg(feature = "store")]
+    fn founding() -> Founding {
+        Founding::new(
+            "The Lampblacks' Bane",
+            CrewType::Shadows,
+            Reputation::Daring,
+            HuntingGrounds {
+                district: "Crow's Foot".into(),
+                operation: "Burglary".into(),
+            },
+        )
+        .upgrade(Upgrade::Hidden)
+        .upgrade(Upgrade::Tools)
+    }
+
+    #[test]
+    fn should_follow_creation_rules_given_preset() {
+        for kind in CrewType::ALL {
+            let preset = Founding::preset(kind);
+
+            assert_eq!(kind, preset.crew.kind);
+            assert_eq!(Ok(()), preset.validate(), "preset for {kind:?} breaks the rules");
+        }
+    }
+
+    #[cfg(feature = "store")]
+    #[tokio::test]
+    async fn should_start_crew_without_heat() {
+        let mut campaign = bootstrap().await;
+
+        let crew = campaign.start_crew(Actor::Gm).await.expect("should have started crew");
+
+        assert_eq!(Some(Heat::default()), campaign.get(crew).await.expect("should have read heat"));
+        let err = campaign
+            .start_crew(Actor::Player(Uuid::new_v4()))
+            .await
+            .expect_err("should have failed to start crew");
+        assert!(matches!(err, CampaignError::Permission(_)));
+    }
+
+    #[cfg(feature = "store")]
+    #[tokio::test]
+    async fn should_create_crew_with_contact_and_statuses() {
+        let mut campaign = bootstrap().await;
+        let factions = campaign.factions().await.expect("should have listed factions");
+        let (helped, hurt) = (factions[0].0, factions[1].0);
+        let founding = founding()
+            .contact(Choice::New(Npc::new(Descriptor::new(
+                Uuid::new_v4(),
+                "Slane",
+                "A fence with friends everywhere.",
+            ))))
+            .status(helped, 1)
+            .status(hurt, -1)
+            .status(hurt, -1);
+
+        let crew = campaign.create_crew(Actor::Gm, &founding).await.expect("should have created crew");
+
+        assert_eq!(Some(founding.crew), campaign.get(crew).await.expect("should have read crew"));
+        assert_eq!(Some(Heat::default()), campaign.get(crew).await.expect("should have read heat"));
+        assert_eq!(Some(Status::new(1)), campaign.get(helped).await.expect("should have read status"));
+        assert_eq!(Some(Status::new(-2)), campaign.get(hurt).await.expect("should have read status"));
+        let contacts = campaign.store().related(crew, CONTACT).await.expect("should have read contacts");
+        let npc = campaign.get::<Npc>(contacts[0]).await.expect("should have read contact");
+        assert_eq!(Some("Slane"), npc.as_ref().map(|npc| npc.descriptor().label()));
+    }
+
+    #[rstest]
+    #[case::wrong_operation(
+        Founding { crew: Crew { hunting_grounds: HuntingGrounds { district: "Crow's Foot".into(), operation: "Murder".into() }, ..founding().crew }, ..founding() },
+        CrewError::HuntingGrounds { kind: CrewType::Shadows, operation: "Murder".into() },
+    )]
+    #[cfg(feature = "store")]
+    #[case::too_many_upgrades(founding().upgrade(Upgrade::Boat), CrewError::Upgrades(3))]
+    #[case::too_few_upgrades(Founding { crew: Crew { upgrades: BTreeSet::from([Upgrade::Vault]), ..founding().crew }, ..founding() }, CrewError::Upgrades(1))]
+    #[case::status_shift(founding().status(Uuid::new_v4(), 2), CrewError::StatusShift(2))]
+    #[tokio::test]
+    async fn should_reject_crews_breaking_creation_rules(#[case] founding: Founding, #[case] expected: CrewError) {
+        let mut campaign = bootstrap().await;
+
+        let err = campaign
+            .create_crew(Actor::Gm, &founding)
+            .await
+            .expect_err("should have failed to create crew");
+
+        assert!(matches!(&err, CampaignError::Crew(err) if *err == expected), "unexpected error: {err}");
+        assert_eq!(0, campaign.all::<Crew>().await.expect("should have listed crews").len());
+    }
+
+    #[cfg(feature = "store")]
+    #[tokio::test]
+    async fn should_not_create_crew_with_missing_faction() {
+        let mut campaign = bootstrap().await;
+        let faction = Uuid::new_v4();
+
+        let err = campaign
+            .create_crew(Actor::Gm, &founding().status(faction, 1))
+            .await
+            .expect_err("should have failed to create crew");
+
+        assert!(
+            matches!(err, CampaignError::MissingFaction(id) if id == faction),
+            "unexpected error: {err}"
+        );
+    }
+}
@@ -0,0 +1,218 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! Aggregate view of the campaign state a GM keeps an eye on.
+
+#[cfg(feature = "store")]
+use std::collections::HashMap;
+
+#[cfg(feature = "store")]
+use darkforge_data::store::sql::sqlite::SqliteStore;
+#[cfg(feature = "store")]
+use darkforge_data::{Persisted, store::World};
+use uuid::Uuid;
+
+#[cfg(feature = "store")]
+use crate::campaign::{CLOCK, Result};
+use crate::{
+    character::{Character, Harm, Stress},
+    clock::Clock,
+    crew::Heat,
+    faction::{Faction, Status},
+};
+
+/// A clock still ticking, along with the entity it belongs to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ActiveClock {
+    /// The clock entity.
+    pub entity: Uuid,
+    /// The entity the clock belongs to, if any.
+    pub owner: Option<Uuid>,
+    /// The clock.
+    pub clock: Clock,
+}
+
+/// A faction along with the crew's standing with it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FactionStanding {
+    /// The faction entity.
+    pub entity: Uuid,
+    /// The faction.
+    pub faction: Faction,
+    /// The crew's status with the faction.
+    pub status: Status,
+}
+
+/// The heat drawn by a crew.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CrewHeat {
+    /// The crew entity.
+    pub entity: Uuid,
+    /// The crew's heat and wanted level.
+    pub heat: Heat,
+}
+
+/// The condition of a player character.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CharacterCondition {
+    /// The character entity.
+    pub entity: Uuid,
+    /// The character.
+    pub character: Character,
+    /// The stress the character has taken.
+    pub stress: Stress,
+    /// The harm the character suffers from.
+    pub harm: Harm,
+}
+
+/// Everything a GM screen needs, gathered in a single batch.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GmDashboard {
+    /// The clocks that are not complete yet.
+    pub clocks: Vec<ActiveClock>,
+    /// The factions and the crew's status with them.
+    pub factions: Vec<FactionStanding>,
+    /// The heat drawn by each crew.
+    pub heat: Vec<CrewHeat>,
+    /// The condition of each player character.
+    pub characters: Vec<CharacterCondition>,
+}
+
+impl GmDashboard {
+    /// Components read to build the dashboard.
+    #[cfg(feature = "store")]
+    const KINDS: [&str; 7] = [
+        Clock::KIND,
+        Faction::KIND,
+        Status::KIND,
+        Heat::KIND,
+        Character::KIND,
+        Stress::KIND,
+        Harm::KIND,
+    ];
+
+    /// Loads the dashboard from the store.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CampaignError`](crate::campaign::CampaignError) if the store cannot be read or holds invalid
+    /// components.
+    #[cfg(feature = "store")]
+    pub async fn load(store: &mut SqliteStore) -> Result<GmDashboard> {
+        let mut dashboard = GmDashboard::default();
+        let mut statuses = HashMap::new();
+        let mut stress = HashMap::new();
+        let mut harm = HashMap::new();
+        let mut factions = Vec::new();
+        let mut characters = Vec::new();
+
+        for gathered in store.gather(&GmDashboard::KINDS, CLOCK).await? {
+            let entity = gathered.entity;
+            match gathered.kind.as_str() {
+                Clock::KIND => {
+                    let clock = gathered.decode::<Clock>()?;
+                    if !clock.is_complete() {
+                        dashboard.clocks.push(ActiveClock {
+                            entity,
+                            owner: gathered.parent,
+                            clock,
+                        });
+                    }
+                }
+                Faction::KIND => factions.push((entity, gathered.decode::<Faction>()?)),
+                Status::KIND => {
+                    statuses.insert(entity, gathered.decode::<Status>()?);
+                }
+                Heat::KIND => dashboard.heat.push(CrewHeat {
+                    entity,
+                    heat: gathered.decode()?,
+                }),
+                Character::KIND => characters.push((entity, gathered.decode::<Character>()?)),
+                Stress::KIND => {
+                    stress.insert(entity, gathered.decode::<Stress>()?);
+                }
+                Harm::KIND => {
+                    harm.insert(entity, gathered.decode::<Harm>()?);
+                }
+                _ => {}
+            }
+        }
+
+        dashboard.factions = factions
+            .into_iter()
+            .map(|(entity, faction)| FactionStanding {
+                entity,
+                faction,
+                status: statuses.remove(&entity).unwrap_or_default(),
+            })
+            .collect();
+        dashboard.characters = characters
+            .into_iter()
+            .map(|(entity, character)| CharacterCondition {
+                entity,
+                character,
+                stress: stress.remove(&entity).unwrap_or_default(),
+                harm: harm.remove(&entity).unwrap_or_default(),
+            })
+            .collect();
+
+        Ok(dashboard)
+    }
+}
+
+#[cfg(all(test, feature = "store"))]
+mod tests {
+    use darkforge_data::pack::Pack;
+
+    use super::*;
+    use crate::campaign::tests::{DEFAULTS, bootstrap};
+
+    #[tokio::test]
+    async fn should_load_dashboard_from_campaign() {
+        let pack = Pack::open(DEFAULTS).expect("should have opened pack");
+        let mut campaign = bootstrap().await;
+        let store = campaign.store();
+
+        let crew = store.spawn().await.expect("should have spawned crew");
+        store.insert(crew, &Heat::new(4, 1)).await.expect("should have inserted heat");
+
+        let pc = store.spawn().await.expect("should have spawned character");
+        store.insert(pc, &Character::new("Arcy")).await.expect("should have inserted character");
+        store.insert(pc, &Stress::new(3)).await.expect("should have inserted stress");
+
+        let done = store.spawn().await.expect("should have spawned clock");
+        let clock = Clock::with_progress("Escape the Bluecoats", 4, 4).expect("should have created clock");
+        store.insert(done, &clock).await.expect("should have inserted clock");
+
+        let dashboard = GmDashboard::load(store).await.expect("should have loaded dashboard");
+
+        assert_eq!(pack.entries(crate::faction::CATEGORY).len(), dashboard.factions.len());
+        assert_eq!(6, dashboard.clocks.len());
+        assert!(dashboard.clocks.iter().all(|active| active.owner.is_some() && active.entity != done));
+        assert_eq!(
+            vec![CrewHeat {
+                entity: crew,
+                heat: Heat::new(4, 1)
+            }],
+            dashboard.heat
+        );
+        assert_eq!(
+            vec![CharacterCondition {
+                entity: pc,
+                character: Character::new("Arcy"),
+                stress: Stress::new(3),
+                harm: Harm::default(),
+            }],
+            dashboard.characters
+        );
+    }
+}
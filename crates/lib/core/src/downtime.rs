@@ -0,0 +1,495 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! Downtime activities characters take between scores.
+//!
+//! Each character can take [`FREE_ACTIVITIES`] activities per downtime phase. [`Campaign::downtime`] rolls for one
+//! activity and applies its result along with a journal entry, and the triggers it fires, in a single transaction.
+
+#[cfg(feature = "store")]
+use darkforge_data::store::World;
+use darkforge_data::{Component, Persisted};
+#[cfg(feature = "store")]
+use darkforge_rng::dice::Dice;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    action::FortuneRoll,
+    gambling::{Game, Wager},
+};
+#[cfg(feature = "store")]
+use crate::{
+    campaign::{CLOCK, Campaign, CampaignError, Result},
+    changeset::{Access, Changeset},
+    character::{Harm, Ratings, Stash, Stress},
+    clock::{Clock, fortune_ticks},
+    crew::Heat,
+    permission::Actor,
+    schedule::{Due, Moment},
+};
+
+/// The number of activities a character can take per downtime phase without paying for more.
+pub const FREE_ACTIVITIES: usize = 2;
+
+/// Progress towards healing, filling up to [`Healing::SEGMENTS`] before the character's harm is reduced.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct Healing(u8);
+
+impl Healing {
+    /// The number of segments of the healing clock.
+    pub const SEGMENTS: u8 = 4;
+
+    /// Returns the filled segments of the healing clock.
+    #[must_use]
+    pub fn value(self) -> u8 {
+        self.0
+    }
+}
+
+impl Component for Healing {}
+
+impl Persisted for Healing {
+    const KIND: &'static str = "healing";
+}
+
+/// A downtime activity.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Activity {
+    /// Clear stress by indulging the character's vice, rolling their lowest attribute rating.
+    IndulgeVice,
+    /// Clear stress by gambling in a den as the character's vice, as [`Activity::IndulgeVice`] does, while betting coin
+    /// from their stash on a game of bones.
+    Gamble {
+        /// The bet made.
+        wager: Wager,
+    },
+    /// Tick the character's healing clock, rolling the healer's rating.
+    Recover {
+        /// The healer's rating.
+        rating: u8,
+    },
+    /// Reduce the crew's heat.
+    ReduceHeat {
+        /// The crew entity.
+        crew: Uuid,
+        /// The rating of the action used to lie low.
+        rating: u8,
+    },
+    /// Tick the clock of a long-term project.
+    Project {
+        /// The project's clock entity.
+        clock: Uuid,
+        /// The rating of the action used to work on the project.
+        rating: u8,
+    },
+}
+
+/// The result applied by a downtime activity.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Applied {
+    /// Stress was cleared.
+    StressCleared {
+        /// The stress cleared.
+        cleared: u8,
+        /// True if the roll could have cleared more stress than the character had.
+        overindulged: bool,
+    },
+    /// Stress was cleared and a game of bones was played.
+    Gambled {
+        /// The stress cleared.
+        cleared: u8,
+        /// True if the roll could have cleared more stress than the character had.
+        overindulged: bool,
+        /// The game played.
+        game: Game,
+    },
+    /// The healing clock ticked.
+    Healed {
+        /// The segments filled.
+        ticked: u8,
+        /// True if the healing clock filled and the character's harm was reduced.
+        healed: bool,
+    },
+    /// The crew's heat was reduced.
+    HeatReduced {
+        /// The heat removed.
+        reduced: u8,
+    },
+    /// The project's clock ticked.
+    ProjectTicked {
+        /// The segments filled.
+        ticked: u8,
+        /// True if the project is complete.
+        complete: bool,
+    },
+}
+
+/// The result of a downtime activity.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Outcome {
+    /// The activity taken.
+    pub activity: Activity,
+    /// The roll made.
+    pub roll: FortuneRoll,
+    /// The result applied.
+    pub applied: Applied,
+}
+
+#[cfg(feature = "store")]
+impl Campaign {
+    /// Takes a downtime activity for the character, rolling with `dice`, once the triggers scheduled for the next
+    /// downtime have fired, see [`Campaign::schedule`]. The triggers fire in the same transaction as the activity.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::Permission`] if the actor does not own the character, or is a player working on a clock
+    /// the character does not own or reducing the heat of a crew the character is not a member of,
+    /// [`CampaignError::MissingClock`]
+    /// if a project's clock does not exist, [`CampaignError::MissingCrew`] if a crew has no heat, or another
+    /// [`CampaignError`] if the store cannot be read or written, in which case the activity is not applied.
+    pub async fn downtime(&mut self, actor: Actor, character: Uuid, activity: Activity, dice: &impl Dice) -> Result<Outcome> {
+        self.authorize(actor, character).await?;
+        let access = self.access(character, activity).await?;
+        if access == Access::Gm {
+            actor.require_gm()?;
+        }
+        let Due {
+            mut changesets,
+            clocks,
+            harms,
+            ..
+        } = self.due(actor, Moment::Downtime).await?;
+
+        let mut changeset = Changeset::new(actor, access);
+        let (roll, applied) = match activity {
+            Activity::IndulgeVice => {
+                let (roll, stress, cleared, overindulged) = self.indulge(character, dice).await?;
+                changeset = changeset.insert(character, &stress)?;
+
+                (roll, Applied::StressCleared { cleared, overindulged })
+            }
+            Activity::Gamble { wager } => {
+                let stash = self.store().get::<Stash>(character).await?.unwrap_or_default();
+                if wager.stake > stash.value() {
+                    return Err(CampaignError::Overstaked {
+                        stake: wager.stake,
+                        stash: stash.value(),
+                    });
+                }
+                let (roll, stress, cleared, overindulged) = self.indulge(character, dice).await?;
+                let game = Game::play(dice, wager);
+                let coin = (i16::from(stash.value()) + game.winnings).clamp(0, i16::from(Stash::MAX));
+                changeset = changeset
+                    .insert(character, &stress)?
+                    .insert(character, &Stash::new(u8::try_from(coin).unwrap_or(Stash::MAX)))?;
+
+                (roll, Applied::Gambled { cleared, overindulged, game })
+            }
+            Activity::Recover { rating } => {
+                let roll = FortuneRoll::roll(dice, rating);
+                let store = self.store();
+                let Healing(filled) = if harms.contains_key(&character) {
+                    Healing::default()
+                } else {
+                    store.get::<Healing>(character).await?.unwrap_or_default()
+                };
+                let ticked = fortune_ticks(roll.outcome).min(Healing::SEGMENTS - filled);
+                let healed = filled + ticked == Healing::SEGMENTS;
+
+                if healed {
+                    let mut harm = match harms.get(&character) {
+                        Some(harm) => harm.clone(),
+                        None => store.get::<Harm>(character).await?.unwrap_or_default(),
+                    };
+                    harm.heal();
+                    changeset = changeset.insert(character, &harm)?;
+                    changeset = changeset.insert(character, &Healing::default())?;
+                } else {
+                    changeset = changeset.insert(character, &Healing(filled + ticked))?;
+                }
+
+                (roll, Applied::Healed { ticked, healed })
+            }
+            Activity::ReduceHeat { crew, rating } => {
+                let roll = FortuneRoll::roll(dice, rating);
+                let mut heat = self.store().get::<Heat>(crew).await?.ok_or(CampaignError::MissingCrew(crew))?;
+                let reduced = heat.reduce(fortune_ticks(roll.outcome));
+                changeset = changeset.insert(crew, &heat)?;
+
+                (roll, Applied::HeatReduced { reduced })
+            }
+            Activity::Project { clock: entity, rating } => {
+                let roll = FortuneRoll::roll(dice, rating);
+                let mut clock = match clocks.get(&entity) {
+                    Some(clock) => clock.clone(),
+                    None => self.store().get::<Clock>(entity).await?.ok_or(CampaignError::MissingClock(entity))?,
+                };
+                let ticked = clock.tick(fortune_ticks(roll.outcome));
+                changeset = changeset.insert(entity, &clock)?;
+
+                (
+                    roll,
+                    Applied::ProjectTicked {
+                        ticked,
+                        complete: clock.is_complete(),
+                    },
+                )
+            }
+        };
+
+        let outcome = Outcome { activity, roll, applied };
+        changesets.push(changeset.record(Some(character), "downtime.activity", &outcome)?);
+        self.apply_all(changesets).await?;
+
+        Ok(outcome)
+    }
+
+    /// Returns the access needed for the character to take the activity: the character's own for their clocks and
+    /// crews, and the GM's for any other.
+    async fn access(&mut self, character: Uuid, activity: Activity) -> Result<Access> {
+        let own = match activity {
+            Activity::ReduceHeat { crew, .. } => self.is_member(crew, character).await?,
+            Activity::Project { clock, .. } => self.store().related(character, CLOCK).await?.contains(&clock),
+            Activity::IndulgeVice | Activity::Gamble { .. } | Activity::Recover { .. } => true,
+        };
+
+        Ok(if own { Access::Owner(character) } else { Access::Gm })
+    }
+
+    /// Rolls the character's vice rating to clear their stress, returning the roll, the stress left, the stress cleared
+    /// and whether the roll could have cleared more.
+    async fn indulge(&mut self, character: Uuid, dice: &impl Dice) -> Result<(FortuneRoll, Stress, u8, bool)> {
+        let roll = FortuneRoll::roll(dice, self.vice_rating(character).await?);
+        let stress = self.store().get::<Stress>(character).await?.unwrap_or_default();
+        let relief = roll.dice.iter().max().copied().unwrap_or_default();
+        let cleared = relief.min(stress.value());
+
+        Ok((roll, Stress::new(stress.value() - cleared), cleared, relief > cleared))
+    }
+
+    /// Returns the character's vice rating: their lowest attribute rating.
+    async fn vice_rating(&mut self, character: Uuid) -> Result<u8> {
+        let ratings = self.store().get::<Ratings>(character).await?.unwrap_or_default();
+        let attributes = self.attributes().await?;
+
+        Ok(attributes.iter().map(|attribute| ratings.attribute(attribute)).min().unwrap_or_default())
+    }
+}
+
+#[cfg(all(test, feature = "store"))]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+    use crate::{
+        action::{Outcome, tests::Loaded},
+        campaign::tests::bootstrap,
+        character::Character,
+        permission::PermissionError,
+    };
+
+    const ALICE: Uuid = uuid::uuid!("0b1c0b45-7d1f-4c55-9c2f-5e6e5f1b3a10");
+
+    /// Enlists a character with the given stress.
+    async fn enlist(campaign: &mut Campaign, stress: u8) -> Uuid {
+        let entity = campaign
+            .enlist(Actor::Gm, Uuid::new_v4(), &Character::new("Arcy"))
+            .await
+            .expect("should have enlisted character");
+        campaign
+            .update(Actor::Gm, entity, &Stress::new(stress))
+            .await
+            .expect("should have updated stress");
+
+        entity
+    }
+
+    // A fresh character has no attribute rating, so their vice roll keeps the lowest of two dice.
+    #[rstest]
+    #[case::relief(6, &[4, 6], 4, false)]
+    #[case::overindulged(2, &[5, 6], 2, true)]
+    #[tokio::test]
+    async fn should_clear_stress_when_indulging_vice(#[case] stress: u8, #[case] dice: &[u8], #[case] cleared: u8, #[case] overindulged: bool) {
+        let mut campaign = bootstrap().await;
+        let pc = enlist(&mut campaign, stress).await;
+
+        let outcome = campaign
+            .downtime(Actor::Gm, pc, Activity::IndulgeVice, &Loaded::new(dice))
+            .await
+            .expect("should have indulged vice");
+
+        assert_eq!(Applied::StressCleared { cleared, overindulged }, outcome.applied);
+        let sheet = campaign.sheet(pc).await.expect("should have loaded sheet");
+        assert_eq!(Stress::new(stress - cleared), sheet.stress);
+    }
+
+    #[rstest]
+    #[case::won(None, &[4, 6, 5, 5, 2, 3], 7)]
+    #[case::lost(None, &[4, 6, 1, 1, 2, 3], 3)]
+    #[case::caught(Some(Outcome::Failure), &[4, 6], 3)]
+    #[tokio::test]
+    async fn should_bet_stash_when_gambling(#[case] cheat: Option<Outcome>, #[case] dice: &[u8], #[case] stash: u8) {
+        let mut campaign = bootstrap().await;
+        let pc = enlist(&mut campaign, 3).await;
+        campaign.update(Actor::Gm, pc, &Stash::new(5)).await.expect("should have updated stash");
+
+        let outcome = campaign
+            .downtime(
+                Actor::Gm,
+                pc,
+                Activity::Gamble {
+                    wager: Wager { stake: 2, cheat },
+                },
+                &Loaded::new(dice),
+            )
+            .await
+            .expect("should have gambled");
+
+        let Applied::Gambled { cleared, game, .. } = outcome.applied else {
+            panic!("unexpected result: {:?}", outcome.applied);
+        };
+        assert_eq!(3, cleared);
+        assert_eq!(i16::from(stash) - 5, game.winnings);
+        let sheet = campaign.sheet(pc).await.expect("should have loaded sheet");
+        assert_eq!(Stress::new(0), sheet.stress);
+        assert_eq!(Some(Stash::new(stash)), campaign.get::<Stash>(pc).await.expect("should have read stash"));
+
+        let err = campaign
+            .downtime(
+                Actor::Gm,
+                pc,
+                Activity::Gamble {
+                    wager: Wager { stake: 9, cheat: None },
+                },
+                &Loaded::new(dice),
+            )
+            .await
+            .expect_err("should have refused to stake more than the stash");
+        assert!(
+            matches!(err, CampaignError::Overstaked { stake: 9, stash: s } if s == stash),
+            "unexpected error: {err}"
+        );
+    }
+
+    #[tokio::test]
+    async fn should_heal_harm_when_healing_clock_fills() {
+        let mut campaign = bootstrap().await;
+        let pc = enlist(&mut campaign, 0).await;
+        let mut harm = Harm::default();
+        harm.suffer(2, "Deep cut");
+        campaign.update(Actor::Gm, pc, &harm).await.expect("should have updated harm");
+
+        let first = campaign
+            .downtime(Actor::Gm, pc, Activity::Recover { rating: 2 }, &Loaded::new(&[6, 2]))
+            .await
+            .expect("should have recovered");
+        let second = campaign
+            .downtime(Actor::Gm, pc, Activity::Recover { rating: 2 }, &Loaded::new(&[4, 1]))
+            .await
+            .expect("should have recovered");
+
+        assert_eq!(Applied::Healed { ticked: 3, healed: false }, first.applied);
+        assert_eq!(Applied::Healed { ticked: 1, healed: true }, second.applied);
+        let sheet = campaign.sheet(pc).await.expect("should have loaded sheet");
+        assert_eq!(vec!["Deep cut".to_owned()], sheet.harm.lesser);
+        assert_eq!(Vec::<String>::new(), sheet.harm.moderate);
+    }
+
+    #[tokio::test]
+    async fn should_reduce_heat_and_tick_projects() {
+        let mut campaign = bootstrap().await;
+        let pc = enlist(&mut campaign, 0).await;
+        let store = campaign.store();
+        let crew = store.spawn().await.expect("should have spawned crew");
+        store.insert(crew, &Heat::new(4, 1)).await.expect("should have inserted heat");
+        let project = campaign
+            .add_clock(
+                Actor::Gm,
+                Some(pc),
+                &Clock::new("Forge a new blade", 8).expect("should have created clock"),
+            )
+            .await
+            .expect("should have added clock");
+
+        let heat = campaign
+            .downtime(Actor::Gm, pc, Activity::ReduceHeat { crew, rating: 1 }, &Loaded::new(&[5]))
+            .await
+            .expect("should have reduced heat");
+        let work = campaign
+            .downtime(Actor::Gm, pc, Activity::Project { clock: project, rating: 2 }, &Loaded::new(&[6, 6]))
+            .await
+            .expect("should have worked on project");
+
+        assert_eq!(Applied::HeatReduced { reduced: 2 }, heat.applied);
+        assert_eq!(Applied::ProjectTicked { ticked: 5, complete: false }, work.applied);
+        let store = campaign.store();
+        assert_eq!(Some(Heat::new(2, 1)), store.get(crew).await.expect("should have read heat"));
+    }
+
+    #[tokio::test]
+    async fn should_fail_to_reduce_heat_of_missing_crew() {
+        let mut campaign = bootstrap().await;
+        let pc = enlist(&mut campaign, 0).await;
+        let crew = Uuid::new_v4();
+
+        let err = campaign
+            .downtime(Actor::Gm, pc, Activity::ReduceHeat { crew, rating: 1 }, &Loaded::new(&[5]))
+            .await
+            .expect_err("should have failed to reduce heat");
+
+        assert!(matches!(err, CampaignError::MissingCrew(id) if id == crew), "unexpected error: {err}");
+    }
+
+    #[tokio::test]
+    async fn should_refuse_player_working_on_clock_or_crew_not_their_own() {
+        let mut campaign = bootstrap().await;
+        let pc = campaign
+            .enlist(Actor::Gm, ALICE, &Character::new("Arcy"))
+            .await
+            .expect("should have enlisted character");
+        let crew = campaign.start_crew(Actor::Gm).await.expect("should have started crew");
+        let clock = Clock::new("The Red Sashes strike back", 6).expect("should have created clock");
+        let faction = campaign.add_clock(Actor::Gm, None, &clock).await.expect("should have added clock");
+        let project = campaign.add_clock(Actor::Gm, Some(pc), &clock).await.expect("should have added clock");
+
+        for activity in [Activity::Project { clock: faction, rating: 2 }, Activity::ReduceHeat { crew, rating: 1 }] {
+            let err = campaign
+                .downtime(Actor::Player(ALICE), pc, activity, &Loaded::new(&[6, 6]))
+                .await
+                .expect_err("should have refused activity");
+            assert!(
+                matches!(err, CampaignError::Permission(PermissionError::GmOnly(ALICE))),
+                "unexpected error: {err}"
+            );
+        }
+        assert_eq!(
+            Some(0),
+            campaign
+                .get::<Clock>(faction)
+                .await
+                .expect("should have read clock")
+                .map(|clock| clock.filled())
+        );
+
+        campaign.join_crew(Actor::Gm, crew, pc).await.expect("should have joined crew");
+        for activity in [Activity::Project { clock: project, rating: 2 }, Activity::ReduceHeat { crew, rating: 1 }] {
+            campaign
+                .downtime(Actor::Player(ALICE), pc, activity, &Loaded::new(&[6, 6]))
+                .await
+                .expect("should have taken activity");
+        }
+    }
+}
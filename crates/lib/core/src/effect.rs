@@ -0,0 +1,327 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! Effect levels measure how much an action accomplishes, and the factors that raise or lower them.
+
+use std::{
+    cmp::Ordering,
+    fmt::{self, Display, Formatter},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// How much an action accomplishes, from no effect at all to extreme.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Effect {
+    /// The action accomplishes nothing.
+    Zero,
+    /// The action accomplishes less than hoped.
+    Limited,
+    /// The action accomplishes what one would expect.
+    #[default]
+    Standard,
+    /// The action accomplishes more than usual.
+    Great,
+    /// The action accomplishes far more than usual.
+    Extreme,
+}
+
+impl Effect {
+    /// All effect levels, from lowest to highest.
+    pub const LEVELS: [Effect; 5] = [Effect::Zero, Effect::Limited, Effect::Standard, Effect::Great, Effect::Extreme];
+
+    /// Returns the effect shifted by the given number of levels, saturating at [`Effect::Zero`] and [`Effect::Extreme`].
+    #[must_use]
+    pub fn shift(self, levels: i8) -> Effect {
+        let index = (self as i8).saturating_add(levels).clamp(0, 4);
+        Effect::LEVELS[index.unsigned_abs() as usize]
+    }
+}
+
+/// Which side a factor of effect favors.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Factor {
+    /// The opposition has the upper hand.
+    Disadvantage,
+    /// Neither side has the upper hand.
+    #[default]
+    Even,
+    /// The character has the upper hand.
+    Advantage,
+}
+
+impl Factor {
+    /// Compares the character's side to the opposition's, e.g. the crew's tier to the target's tier.
+    #[must_use]
+    pub fn compare(ours: u8, theirs: u8) -> Factor {
+        match ours.cmp(&theirs) {
+            Ordering::Less => Factor::Disadvantage,
+            Ordering::Equal => Factor::Even,
+            Ordering::Greater => Factor::Advantage,
+        }
+    }
+
+    /// Returns the effect levels the factor is worth.
+    #[must_use]
+    pub fn levels(self) -> i8 {
+        match self {
+            Factor::Disadvantage => -1,
+            Factor::Even => 0,
+            Factor::Advantage => 1,
+        }
+    }
+}
+
+/// The factors that raise or lower the effect of an action and the severity of its consequences.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Factors {
+    /// The quality of the character's tools, or the tier of their crew, compared to the opposition's.
+    #[serde(default)]
+    pub quality: Factor,
+    /// The size of the character's force compared to the opposition's.
+    #[serde(default)]
+    pub scale: Factor,
+    /// Whether the character or the opposition has a special weakness or advantage, e.g. fire against spirits.
+    #[serde(default)]
+    pub potency: Factor,
+}
+
+impl Factors {
+    /// Creates factors comparing the crew's tier to the target's, even in scale and potency.
+    #[must_use]
+    pub fn tiers(tier: u8, target_tier: u8) -> Factors {
+        Factors {
+            quality: Factor::compare(tier, target_tier),
+            ..Factors::default()
+        }
+    }
+
+    /// Returns the effect levels gained, or lost if negative.
+    #[must_use]
+    pub fn levels(self) -> i8 {
+        self.quality.levels() + self.scale.levels() + self.potency.levels()
+    }
+
+    /// Returns the effect of an action with the factors.
+    #[must_use]
+    pub fn effect(self, effect: Effect) -> Effect {
+        effect.shift(self.levels())
+    }
+
+    /// Returns the levels the consequences suffered are worsened by, or lessened by if negative. A stronger opposition
+    /// inflicts worse consequences.
+    #[must_use]
+    pub fn severity(self) -> i8 {
+        -self.levels()
+    }
+}
+
+/// The factors of effect, see [`Factors`].
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FactorKind {
+    /// See [`Factors::quality`].
+    Quality,
+    /// See [`Factors::scale`].
+    Scale,
+    /// See [`Factors::potency`].
+    Potency,
+}
+
+impl Display for FactorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            FactorKind::Quality => "quality",
+            FactorKind::Scale => "scale",
+            FactorKind::Potency => "potency",
+        })
+    }
+}
+
+/// A side of an action, the character's or the opposition's.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Side {
+    /// The quality of the side's tools, or the tier of its crew or faction.
+    pub quality: u8,
+    /// The size of the side's force, from 0 for a single person or small group to 4 for a massive force.
+    pub scale: u8,
+}
+
+impl Side {
+    /// Creates a side of the given quality and scale.
+    #[must_use]
+    pub fn new(quality: u8, scale: u8) -> Side {
+        Side { quality, scale }
+    }
+}
+
+/// How the character goes about an action.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Approach {
+    /// The effect the approach has before the factors are weighed, usually standard.
+    pub effect: Effect,
+    /// Whether the approach exploits a weakness of the opposition, or runs into one of its strengths.
+    #[serde(default)]
+    pub potency: Factor,
+}
+
+impl Approach {
+    /// Creates an approach of the given effect, even in potency.
+    #[must_use]
+    pub fn new(effect: Effect) -> Approach {
+        Approach {
+            effect,
+            potency: Factor::Even,
+        }
+    }
+
+    /// Sets the potency of the approach.
+    #[must_use]
+    pub fn potency(mut self, potency: Factor) -> Approach {
+        self.potency = potency;
+
+        self
+    }
+}
+
+/// A factor weighed by [`assess_effect`].
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Reason {
+    /// The factor weighed.
+    pub factor: FactorKind,
+    /// The side the factor favors.
+    pub favors: Factor,
+    /// The character's and the opposition's values compared, for quality and scale.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compared: Option<(u8, u8)>,
+}
+
+impl Display for Reason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.factor)?;
+        if let Some((ours, theirs)) = self.compared {
+            write!(f, " {ours} vs {theirs}")?;
+        }
+
+        write!(f, ": {:+}", self.favors.levels())
+    }
+}
+
+/// The effect suggested for an action, with the factors that led to it.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Assessment {
+    /// The effect of the approach, before the factors are weighed.
+    pub approach: Effect,
+    /// The factors weighed.
+    pub factors: Factors,
+    /// The suggested effect.
+    pub effect: Effect,
+    /// The factors weighed, itemized in the order quality, scale and potency.
+    pub reasons: Vec<Reason>,
+}
+
+impl Display for Assessment {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{:?} effect", self.effect)?;
+        writeln!(f, "  approach: {:?}", self.approach)?;
+        for reason in &self.reasons {
+            writeln!(f, "  {reason}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Assesses the effect of an action by the `actor` against the `target`, weighing their quality and scale along with
+/// the potency of the approach.
+#[must_use]
+pub fn assess_effect(actor: Side, target: Side, approach: Approach) -> Assessment {
+    let factors = Factors {
+        quality: Factor::compare(actor.quality, target.quality),
+        scale: Factor::compare(actor.scale, target.scale),
+        potency: approach.potency,
+    };
+
+    Assessment {
+        approach: approach.effect,
+        factors,
+        effect: factors.effect(approach.effect),
+        reasons: vec![
+            Reason {
+                factor: FactorKind::Quality,
+                favors: factors.quality,
+                compared: Some((actor.quality, target.quality)),
+            },
+            Reason {
+                factor: FactorKind::Scale,
+                favors: factors.scale,
+                compared: Some((actor.scale, target.scale)),
+            },
+            Reason {
+                factor: FactorKind::Potency,
+                favors: factors.potency,
+                compared: None,
+            },
+        ],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case::none(Effect::Standard, 0, Effect::Standard)]
+    #[case::up(Effect::Standard, 1, Effect::Great)]
+    #[case::down(Effect::Limited, -1, Effect::Zero)]
+    #[case::saturate_up(Effect::Great, 5, Effect::Extreme)]
+    #[case::saturate_down(Effect::Limited, -3, Effect::Zero)]
+    #[case::extremes(Effect::Extreme, i8::MIN, Effect::Zero)]
+    fn should_shift_effect(#[case] effect: Effect, #[case] levels: i8, #[case] expect: Effect) {
+        assert_eq!(expect, effect.shift(levels));
+    }
+
+    #[rstest]
+    #[case::even(Factors::tiers(2, 2), Effect::Standard, 0)]
+    #[case::higher_tier(Factors::tiers(3, 1), Effect::Great, -1)]
+    #[case::lower_tier(Factors::tiers(0, 4), Effect::Limited, 1)]
+    #[case::outmatched(Factors { scale: Factor::Disadvantage, potency: Factor::Disadvantage, ..Factors::tiers(1, 2) }, Effect::Zero, 3)]
+    #[case::balanced(Factors { scale: Factor::Advantage, ..Factors::tiers(1, 2) }, Effect::Standard, 0)]
+    fn should_scale_effect_and_severity_by_factors(#[case] factors: Factors, #[case] expect: Effect, #[case] severity: i8) {
+        assert_eq!(expect, factors.effect(Effect::Standard));
+        assert_eq!(severity, factors.severity());
+    }
+
+    #[rstest]
+    #[case::even(Side::new(2, 0), Side::new(2, 0), Approach::new(Effect::Standard), Effect::Standard)]
+    #[case::better_tools(Side::new(3, 0), Side::new(2, 0), Approach::new(Effect::Standard), Effect::Great)]
+    #[case::outnumbered(Side::new(2, 0), Side::new(2, 2), Approach::new(Effect::Standard), Effect::Limited)]
+    #[case::potent(Side::new(1, 0), Side::new(2, 1), Approach::new(Effect::Great).potency(Factor::Advantage), Effect::Standard)]
+    #[case::overwhelmed(Side::new(0, 0), Side::new(4, 3), Approach::new(Effect::Limited).potency(Factor::Disadvantage), Effect::Zero)]
+    fn should_assess_effect_from_factors(#[case] actor: Side, #[case] target: Side, #[case] approach: Approach, #[case] expect: Effect) {
+        assert_eq!(expect, assess_effect(actor, target, approach).effect);
+    }
+
+    #[test]
+    fn should_itemize_assessment() {
+        let assessment = assess_effect(Side::new(1, 0), Side::new(2, 1), Approach::new(Effect::Great).potency(Factor::Advantage));
+
+        assert_eq!(
+            "Standard effect\n  approach: Great\n  quality 1 vs 2: -1\n  scale 0 vs 1: -1\n  potency: +1\n",
+            assessment.to_string()
+        );
+    }
+}
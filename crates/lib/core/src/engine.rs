@@ -0,0 +1,310 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! Rules services behind object-safe traits, so a game can swap the rules it plays by at runtime.
+//!
+//! The core rules of Blades in the Dark are implemented by [`Blades`]. A hack swaps in its own [`Resolution`] of dice
+//! rolls or its own [`Oracle`] by registering a [`RuleSet`] under the name of its content pack in a [`RulesRegistry`],
+//! which falls back to the core rules for any pack without rules of its own.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
+
+use darkforge_rng::dice::{D6, Dice};
+use rand::RngCore;
+
+use crate::{
+    action::{self, KeepDrop, Outcome, Resistance},
+    odds::{self, Odds, RollSpec},
+    oracle::{self, Complication, Likelihood, Ruling},
+    roll::Roll,
+    table::Table,
+};
+
+/// Rolls that simulations of the odds of pools too large to compute exactly are based on.
+pub const SIMULATED_ROLLS: u32 = 100_000;
+
+/// How dice rolls are resolved into outcomes.
+pub trait Resolution: Send + Sync {
+    /// Rolls a dice pool for the rating, applying the keep and drop rules, and returns the dice kept.
+    fn roll_pool(&self, dice: &dyn Dice, rating: u8, keep_drop: &[KeepDrop]) -> Vec<u8>;
+
+    /// Reads the outcome of the dice kept.
+    fn outcome(&self, dice: &[u8]) -> Outcome;
+
+    /// Reads the stress cost of the dice kept by a resistance roll.
+    fn resistance(&self, dice: Vec<u8>) -> Resistance;
+
+    /// Returns the chance of each outcome of the roll. Simulates [`SIMULATED_ROLLS`] rolls unless overridden.
+    fn odds(&self, spec: RollSpec) -> Odds {
+        let mut counts = BTreeMap::<Outcome, u32>::new();
+        for _ in 0..SIMULATED_ROLLS {
+            let dice = self.roll_pool(&D6::default(), spec.pool(), &[]);
+            *counts.entry(self.outcome(&dice)).or_default() += 1;
+        }
+        let percent = |outcome| f64::from(counts.get(&outcome).copied().unwrap_or_default()) * 100.0 / f64::from(SIMULATED_ROLLS);
+
+        Odds {
+            critical: percent(Outcome::Critical),
+            success: percent(Outcome::Success),
+            partial: percent(Outcome::Partial),
+            failure: percent(Outcome::Failure),
+        }
+    }
+
+    /// Rolls a dice pool for the rating and reads its outcome, as [`Roll::keeping`] does under the core rules.
+    fn roll(&self, label: &str, rating: u8, keep_drop: &[KeepDrop], dice: &dyn Dice) -> Roll {
+        let kept = self.roll_pool(dice, rating, keep_drop);
+
+        Roll {
+            label: label.to_owned(),
+            rating,
+            outcome: self.outcome(&kept),
+            dice: kept,
+            keep_drop: keep_drop.to_vec(),
+            context: None,
+            abilities: Vec::new(),
+            fudged: None,
+            manual: false,
+        }
+    }
+}
+
+/// How the oracle answers yes/no questions.
+pub trait Oracle: Send + Sync {
+    /// Answers the question, drawing complications from `complications` if the answer comes with one.
+    fn ask(&self, question: &str, likelihood: Likelihood, complications: &Table<Complication>, rng: &mut dyn RngCore) -> Ruling;
+}
+
+/// The core rules of Blades in the Dark.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Blades;
+
+impl Resolution for Blades {
+    fn roll_pool(&self, dice: &dyn Dice, rating: u8, keep_drop: &[KeepDrop]) -> Vec<u8> {
+        action::roll_pool_with(&dice, rating, keep_drop)
+    }
+
+    fn outcome(&self, dice: &[u8]) -> Outcome {
+        Outcome::from_dice(dice)
+    }
+
+    fn resistance(&self, dice: Vec<u8>) -> Resistance {
+        Resistance::from_dice(dice)
+    }
+
+    fn odds(&self, spec: RollSpec) -> Odds {
+        odds::tabled(spec)
+            .or_else(|| odds::exact(spec))
+            .map_or_else(|| odds::simulate(spec, SIMULATED_ROLLS), |odds| odds.to_percent())
+    }
+}
+
+impl Oracle for Blades {
+    fn ask(&self, question: &str, likelihood: Likelihood, complications: &Table<Complication>, mut rng: &mut dyn RngCore) -> Ruling {
+        oracle::ask(question, likelihood, complications, &mut rng)
+    }
+}
+
+/// The rules services a game plays by.
+#[derive(Clone)]
+pub struct RuleSet {
+    /// How dice rolls are resolved.
+    pub resolution: Arc<dyn Resolution>,
+    /// How the oracle answers questions.
+    pub oracle: Arc<dyn Oracle>,
+}
+
+impl RuleSet {
+    /// Creates a rule set from its services.
+    #[must_use]
+    pub fn new(resolution: Arc<dyn Resolution>, oracle: Arc<dyn Oracle>) -> RuleSet {
+        RuleSet { resolution, oracle }
+    }
+
+    /// Returns the core rules with the resolution replaced.
+    #[must_use]
+    pub fn with_resolution(resolution: Arc<dyn Resolution>) -> RuleSet {
+        RuleSet {
+            resolution,
+            ..RuleSet::default()
+        }
+    }
+
+    /// Returns the core rules with the oracle replaced.
+    #[must_use]
+    pub fn with_oracle(oracle: Arc<dyn Oracle>) -> RuleSet {
+        RuleSet {
+            oracle,
+            ..RuleSet::default()
+        }
+    }
+}
+
+impl Default for RuleSet {
+    /// The core rules of Blades in the Dark.
+    fn default() -> Self {
+        RuleSet::new(Arc::new(Blades), Arc::new(Blades))
+    }
+}
+
+/// The rule sets of content packs, by pack name, falling back to a default rule set for packs without rules of their
+/// own.
+#[derive(Clone, Default)]
+pub struct RulesRegistry {
+    fallback: RuleSet,
+    packs: HashMap<String, RuleSet>,
+}
+
+impl RulesRegistry {
+    /// Creates a registry falling back to `fallback`.
+    #[must_use]
+    pub fn new(fallback: RuleSet) -> RulesRegistry {
+        RulesRegistry {
+            fallback,
+            packs: HashMap::new(),
+        }
+    }
+
+    /// Registers the rules of the pack, returning the rules it replaces if the pack had some.
+    pub fn register(&mut self, pack: impl Into<String>, rules: RuleSet) -> Option<RuleSet> {
+        self.packs.insert(pack.into(), rules)
+    }
+
+    /// Removes the rules of the pack, returning them if the pack had some.
+    pub fn unregister(&mut self, pack: &str) -> Option<RuleSet> {
+        self.packs.remove(pack)
+    }
+
+    /// Returns the rules of the pack, or the fallback if the pack has none.
+    #[must_use]
+    pub fn rules(&self, pack: &str) -> &RuleSet {
+        self.packs.get(pack).unwrap_or(&self.fallback)
+    }
+
+    /// Returns the rules of the first of the loaded packs that has some, or the fallback if none do.
+    #[must_use]
+    pub fn resolve<'a>(&self, packs: impl IntoIterator<Item = &'a str>) -> &RuleSet {
+        packs.into_iter().find_map(|pack| self.packs.get(pack)).unwrap_or(&self.fallback)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use super::*;
+    use crate::{action::tests::Loaded, oracle::Answer, table::Row};
+
+    /// A hack where every roll is desperate: only sixes count.
+    struct Grim;
+
+    impl Resolution for Grim {
+        fn roll_pool(&self, dice: &dyn Dice, rating: u8, keep_drop: &[KeepDrop]) -> Vec<u8> {
+            Blades.roll_pool(dice, rating, keep_drop)
+        }
+
+        #[expect(clippy::naive_bytecount, reason = "Dice pools hold a handful of dice")]
+        fn outcome(&self, dice: &[u8]) -> Outcome {
+            match dice.iter().filter(|&&die| die == 6).count() {
+                0 => Outcome::Failure,
+                1 => Outcome::Success,
+                _ => Outcome::Critical,
+            }
+        }
+
+        fn resistance(&self, dice: Vec<u8>) -> Resistance {
+            Resistance { dice, stress: 6 }
+        }
+    }
+
+    /// An oracle that always says no.
+    struct Stubborn;
+
+    impl Oracle for Stubborn {
+        fn ask(&self, question: &str, likelihood: Likelihood, _: &Table<Complication>, _: &mut dyn RngCore) -> Ruling {
+            Ruling {
+                question: question.to_owned(),
+                likelihood,
+                answer: Answer::No,
+                complication: None,
+            }
+        }
+    }
+
+    fn registry() -> RulesRegistry {
+        let mut registry = RulesRegistry::default();
+        registry.register("grim", RuleSet::with_resolution(Arc::new(Grim)));
+        registry.register("stubborn", RuleSet::with_oracle(Arc::new(Stubborn)));
+
+        registry
+    }
+
+    #[rstest::rstest]
+    #[case::core("hungry_goblins", &[4, 5], Outcome::Partial)]
+    #[case::hack("grim", &[4, 5], Outcome::Failure)]
+    #[case::hack_six("grim", &[6, 2], Outcome::Success)]
+    #[case::core_six("hungry_goblins", &[6, 2], Outcome::Success)]
+    fn should_resolve_roll_by_pack_rules(#[case] pack: &str, #[case] dice: &[u8], #[case] expect: Outcome) {
+        let roll = registry().rules(pack).resolution.roll("Skirmish", 2, &[], &Loaded::new(dice));
+
+        assert_eq!(dice, roll.dice);
+        assert_eq!(expect, roll.outcome);
+    }
+
+    #[test]
+    fn should_resolve_rules_of_first_pack_with_rules() {
+        let registry = registry();
+        let mut rng = StdRng::seed_from_u64(1948);
+        let complications = Table::new(vec![Row::new(
+            1,
+            Complication {
+                id: uuid::Uuid::new_v4(),
+                text: "The Bluecoats take an interest.".into(),
+            },
+        )]);
+
+        let rules = registry.resolve(["core", "stubborn", "grim"]);
+        let ruling = rules.oracle.ask("Is the door locked?", Likelihood::VeryLikely, &complications, &mut rng);
+
+        assert_eq!(Answer::No, ruling.answer);
+        assert_eq!(Outcome::Partial, rules.resolution.outcome(&[4]));
+    }
+
+    #[test]
+    fn should_compute_core_odds_exactly() {
+        let spec = RollSpec::new(2);
+
+        assert_eq!(
+            odds::exact(spec).expect("should have computed odds").to_percent(),
+            RulesRegistry::default().rules("core").resolution.odds(spec)
+        );
+    }
+
+    #[test]
+    fn should_simulate_hack_odds() {
+        let odds = registry().rules("grim").resolution.odds(RollSpec::new(1));
+
+        assert!(
+            (odds.failure - 500.0 / 6.0).abs() < 1.0,
+            "should have failed 5 rolls out of 6, got {}",
+            odds.failure
+        );
+        assert!(
+            odds.partial.abs() < f64::EPSILON,
+            "should never have a partial success, got {}",
+            odds.partial
+        );
+    }
+}
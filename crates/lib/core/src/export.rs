@@ -0,0 +1,321 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! Character and campaign exports, written as versioned JSON with [`darkforge_data::export`] so they can be carried
+//! over to other installs and still load after upgrading the crate.
+
+use darkforge_data::export::Exported;
+#[cfg(feature = "store")]
+use darkforge_data::pack::Pack;
+#[cfg(feature = "store")]
+use darkforge_data::store::sql::sqlite::SqliteStore;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    campaign::CampaignInfo,
+    character::{Character, Harm, Ratings, Stash, Stress},
+    clock::Clock,
+    roster::Standing,
+};
+#[cfg(feature = "store")]
+use crate::{
+    campaign::{Campaign, CampaignError, Result},
+    changeset::{Access, Changeset},
+    permission::{Actor, Owner},
+};
+
+/// Journal entry kind recorded when a character is imported.
+pub const CHARACTER_IMPORTED: &str = "character.imported";
+
+/// A character as exported, with everything on their sheet and their stash.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct CharacterExport {
+    /// The character.
+    pub character: Character,
+    /// The stress the character has taken.
+    pub stress: Stress,
+    /// The harm the character suffers from.
+    pub harm: Harm,
+    /// The character's action ratings.
+    pub ratings: Ratings,
+    /// The coin in the character's stash.
+    pub stash: Stash,
+}
+
+impl Exported for CharacterExport {
+    const KIND: &'static str = "character";
+}
+
+/// A character of a campaign's roster, as exported.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct RosterExport {
+    /// The player owning the character.
+    pub player: Uuid,
+    /// Whether the character is still played.
+    pub standing: Standing,
+    /// The character.
+    pub character: CharacterExport,
+}
+
+/// A campaign as exported: its roster and the clocks not tied to any entity. The factions and their clocks are set up
+/// again from the content pack when the campaign is imported.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct CampaignExport {
+    /// Information about the campaign.
+    pub info: CampaignInfo,
+    /// Every character of the roster, in the order they were enlisted.
+    pub roster: Vec<RosterExport>,
+    /// The clocks not tied to any entity.
+    pub clocks: Vec<Clock>,
+}
+
+impl Exported for CampaignExport {
+    const KIND: &'static str = "campaign";
+}
+
+#[cfg(feature = "store")]
+impl Campaign {
+    /// Exports the character.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::MissingCharacter`] if the entity is not a character, or another [`CampaignError`] if
+    /// the store cannot be read.
+    pub async fn export_character(&mut self, entity: Uuid) -> Result<CharacterExport> {
+        let sheet = self.sheet(entity).await?;
+
+        Ok(CharacterExport {
+            character: sheet.character,
+            stress: sheet.stress,
+            harm: sheet.harm,
+            ratings: sheet.ratings,
+            stash: self.get(entity).await?.unwrap_or_default(),
+        })
+    }
+
+    /// Adds an exported character to the player's roster, as an active character. Players can only import characters
+    /// for themselves.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::Permission`] if the actor is another player, or another [`CampaignError`] if the store
+    /// cannot be written.
+    pub async fn import_character(&mut self, actor: Actor, player: Uuid, export: &CharacterExport) -> Result<Uuid> {
+        let entity = Uuid::new_v4();
+        self.apply(importing(actor, player, entity, Standing::Active, export)?).await?;
+
+        Ok(entity)
+    }
+
+    /// Exports the campaign.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CampaignError`] if the store cannot be read or holds invalid components.
+    pub async fn export(&mut self) -> Result<CampaignExport> {
+        let mut roster = Vec::new();
+        for entry in self.roster(None).await? {
+            roster.push(RosterExport {
+                player: entry.player,
+                standing: entry.standing,
+                character: self.export_character(entry.entity).await?,
+            });
+        }
+        let clocks = self
+            .clocks()
+            .await?
+            .into_iter()
+            .filter_map(|(_, owner, clock)| owner.is_none().then_some(clock))
+            .collect();
+
+        Ok(CampaignExport {
+            info: self.info().clone(),
+            roster,
+            clocks,
+        })
+    }
+
+    /// Initializes a fresh campaign in the store from an exported campaign, setting up its factions from the content
+    /// pack it was bootstrapped from.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::PackMismatch`] if the pack is not the one the campaign was bootstrapped from,
+    /// [`CampaignError::AlreadyExists`] if the store already holds a campaign, or another [`CampaignError`] if the pack
+    /// is invalid or the store cannot be written.
+    pub async fn import(store: SqliteStore, pack: &Pack, export: &CampaignExport) -> Result<Campaign> {
+        if pack.name() != export.info.pack {
+            return Err(CampaignError::PackMismatch {
+                expected: export.info.pack.clone(),
+                found: pack.name().into(),
+            });
+        }
+
+        let mut campaign = Campaign::bootstrap(store, export.info.name.clone(), pack).await?;
+        campaign.restore(export).await?;
+
+        Ok(campaign)
+    }
+
+    /// Adds the roster and the clocks not tied to any entity of an exported campaign to this one, in a single
+    /// transaction.
+    pub(crate) async fn restore(&mut self, export: &CampaignExport) -> Result<()> {
+        let mut changesets = Vec::new();
+        for entry in &export.roster {
+            changesets.push(importing(Actor::Gm, entry.player, Uuid::new_v4(), entry.standing, &entry.character)?);
+        }
+        for clock in &export.clocks {
+            let entity = Uuid::new_v4();
+            changesets.push(
+                Changeset::new(Actor::Gm, Access::Gm)
+                    .spawn(entity)
+                    .insert(entity, clock)?
+                    .record(Some(entity), "clock.added", clock)?,
+            );
+        }
+        self.apply_all(changesets).await?;
+
+        Ok(())
+    }
+}
+
+/// Adds the exported character to the player's roster as the entity.
+#[cfg(feature = "store")]
+fn importing(actor: Actor, player: Uuid, entity: Uuid, standing: Standing, export: &CharacterExport) -> Result<Changeset> {
+    Ok(Changeset::new(actor, Access::Anyone)
+        .spawn(entity)
+        .insert(entity, &Owner(player))?
+        .insert(entity, &export.character)?
+        .insert(entity, &standing)?
+        .insert(entity, &export.stress)?
+        .insert(entity, &export.harm)?
+        .insert(entity, &export.ratings)?
+        .insert(entity, &export.stash)?
+        .record(Some(entity), CHARACTER_IMPORTED, &export.character)?)
+}
+
+#[cfg(all(test, feature = "store"))]
+mod tests {
+    use darkforge_data::export::{self, ExportError};
+    use uuid::uuid;
+
+    use super::*;
+    use crate::{
+        campaign::tests::{DEFAULTS, bootstrap},
+        permission::PermissionError,
+        roster::StashRule,
+    };
+
+    const ALICE: Uuid = uuid!("0b1c0b45-7d1f-4c55-9c2f-5e6e5f1b3a10");
+    const BOB: Uuid = uuid!("9a8d6c3e-2f4b-4b1a-8e5d-7c6b5a4f3e2d");
+
+    #[tokio::test]
+    async fn should_carry_character_over_to_another_campaign() {
+        let mut campaign = bootstrap().await;
+        let entity = campaign
+            .enlist(Actor::Gm, ALICE, &Character::new("Arcy"))
+            .await
+            .expect("should have enlisted character");
+        campaign
+            .update(Actor::Gm, entity, &Stress::new(3))
+            .await
+            .expect("should have stressed character");
+        let exported = campaign.export_character(entity).await.expect("should have exported character");
+        let mut json = Vec::new();
+        export::export(&exported, &mut json).expect("should have written export");
+
+        let mut other = bootstrap().await;
+        let imported = export::import::<CharacterExport>(json.as_slice()).expect("should have read export");
+        let entity = other
+            .import_character(Actor::Player(ALICE), ALICE, &imported)
+            .await
+            .expect("should have imported character");
+
+        assert_eq!(exported, other.export_character(entity).await.expect("should have exported character"));
+        assert_eq!(
+            Some(Standing::Active),
+            other.get::<Standing>(entity).await.expect("should have read standing")
+        );
+    }
+
+    #[tokio::test]
+    async fn should_refuse_importing_character_for_another_player() {
+        let mut campaign = bootstrap().await;
+        let entity = campaign
+            .enlist(Actor::Gm, ALICE, &Character::new("Arcy"))
+            .await
+            .expect("should have enlisted character");
+        let exported = campaign.export_character(entity).await.expect("should have exported character");
+
+        let err = campaign
+            .import_character(Actor::Player(BOB), ALICE, &exported)
+            .await
+            .expect_err("should have refused import");
+
+        assert!(
+            matches!(err, CampaignError::Permission(PermissionError::OtherPlayer { player: BOB, other: ALICE })),
+            "unexpected error: {err}"
+        );
+    }
+
+    #[tokio::test]
+    async fn should_import_exported_campaign() {
+        let mut campaign = bootstrap().await;
+        let arcy = campaign
+            .enlist(Actor::Gm, ALICE, &Character::new("Arcy"))
+            .await
+            .expect("should have enlisted character");
+        campaign
+            .enlist(Actor::Gm, BOB, &Character::new("Bazso"))
+            .await
+            .expect("should have enlisted character");
+        campaign
+            .retire(Actor::Gm, arcy, StashRule::Personal)
+            .await
+            .expect("should have retired character");
+        campaign
+            .add_clock(Actor::Gm, None, &Clock::with_progress("Heat", 6, 2).expect("should have created clock"))
+            .await
+            .expect("should have added clock");
+        let exported = campaign.export().await.expect("should have exported campaign");
+
+        let pack = Pack::open(DEFAULTS).expect("should have opened pack");
+        let store = SqliteStore::memory().await.expect("should have opened store");
+        let mut imported = Campaign::import(store, &pack, &exported).await.expect("should have imported campaign");
+
+        assert_eq!(exported, imported.export().await.expect("should have exported campaign"));
+        assert_eq!(
+            campaign.factions().await.expect("should have listed factions").len(),
+            imported.factions().await.expect("should have listed factions").len()
+        );
+    }
+
+    #[tokio::test]
+    async fn should_refuse_campaign_exports_of_newer_schema() {
+        let json = br#"{ "kind": "campaign", "schema": 99, "data": {} }"#;
+
+        let err = export::import::<CampaignExport>(json.as_slice()).expect_err("should have refused export");
+
+        assert!(
+            matches!(
+                err,
+                ExportError::UnsupportedVersion {
+                    kind: "campaign",
+                    found: 99,
+                    latest: 1
+                }
+            ),
+            "unexpected error: {err}"
+        );
+    }
+}
@@ -0,0 +1,283 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! Factions are the gangs, institutions and powers of the city the crew deals with.
+
+use std::str::FromStr;
+
+use darkforge_data::{Component, Persisted, descriptor::Descriptor};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[cfg(feature = "store")]
+use crate::{
+    campaign::{self, CLOCK, Campaign, CampaignError, FACTION},
+    changeset::{Access, Changeset},
+    clock::Clock,
+    permission::Actor,
+};
+
+/// Content pack category holding faction templates.
+pub const CATEGORY: &str = "faction";
+
+/// How firmly a faction holds on to its tier.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Hold {
+    /// The faction is vulnerable and may lose its tier.
+    Weak,
+    /// The faction is secure in its tier.
+    Strong,
+}
+
+impl FromStr for Hold {
+    type Err = ();
+
+    /// Parses a hold from its name, ignoring case.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "weak" => Ok(Hold::Weak),
+            "strong" => Ok(Hold::Strong),
+            _ => Err(()),
+        }
+    }
+}
+
+/// The crew's standing with a faction, from -3 (at war) to +3 (allies).
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct Status(i8);
+
+impl Status {
+    /// The lowest possible status, the faction is at war with the crew.
+    pub const WAR: Status = Status(-3);
+    /// The highest possible status, the faction is allied with the crew.
+    pub const ALLY: Status = Status(3);
+
+    /// Creates a new status, clamped between [`Status::WAR`] and [`Status::ALLY`].
+    #[must_use]
+    pub fn new(value: i8) -> Status {
+        Status(value.clamp(Status::WAR.0, Status::ALLY.0))
+    }
+
+    /// Returns the status value.
+    #[must_use]
+    pub fn value(self) -> i8 {
+        self.0
+    }
+}
+
+/// A faction in the campaign world.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Faction {
+    descriptor: Descriptor<'static>,
+    tier: u8,
+    hold: Hold,
+}
+
+impl Faction {
+    /// Creates a new faction.
+    #[must_use]
+    pub fn new(descriptor: Descriptor<'static>, tier: u8, hold: Hold) -> Faction {
+        Faction { descriptor, tier, hold }
+    }
+
+    /// Returns the faction's descriptor.
+    #[must_use]
+    pub fn descriptor(&self) -> &Descriptor<'static> {
+        &self.descriptor
+    }
+
+    /// Returns the faction's tier.
+    #[must_use]
+    pub fn tier(&self) -> u8 {
+        self.tier
+    }
+
+    /// Returns how firmly the faction holds its tier.
+    #[must_use]
+    pub fn hold(&self) -> Hold {
+        self.hold
+    }
+}
+
+impl Component for Faction {}
+
+impl Persisted for Faction {
+    const KIND: &'static str = "faction";
+}
+
+impl Component for Status {}
+
+impl Persisted for Status {
+    const KIND: &'static str = "status";
+}
+
+/// A clock a faction starts the campaign with.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ClockTemplate {
+    /// The clock's label.
+    pub label: String,
+    /// The number of segments in the clock.
+    pub segments: u8,
+    /// The number of segments filled at the start of the campaign.
+    #[serde(default)]
+    pub filled: u8,
+}
+
+/// A faction as defined in a content pack, with its starting status and clocks.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct FactionTemplate {
+    /// The id of the template in the content pack.
+    pub id: Uuid,
+    /// The faction's descriptor.
+    pub descriptor: Descriptor<'static>,
+    /// The faction's starting tier.
+    pub tier: u8,
+    /// How firmly the faction starts holding its tier.
+    pub hold: Hold,
+    /// The crew's starting status with the faction.
+    #[serde(default)]
+    pub status: Status,
+    /// The clocks the faction starts with.
+    #[serde(default)]
+    pub clocks: Vec<ClockTemplate>,
+}
+
+impl FactionTemplate {
+    /// Creates the faction described by this template.
+    #[must_use]
+    pub fn faction(&self) -> Faction {
+        Faction::new(self.descriptor.clone(), self.tier, self.hold)
+    }
+}
+
+#[cfg(feature = "store")]
+impl Campaign {
+    /// Adds the faction described by the template to the campaign, along with its starting status and clocks.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::Permission`] if the actor is not the GM, [`CampaignError::InvalidClock`] if one of the
+    /// template's clocks is invalid, or another [`CampaignError`] if the store cannot be written, in which case nothing
+    /// is added.
+    pub async fn add_faction(&mut self, actor: Actor, template: &FactionTemplate) -> campaign::Result<Uuid> {
+        actor.require_gm()?;
+
+        let entity = Uuid::new_v4();
+        let mut changeset = Changeset::new(actor, Access::Gm)
+            .spawn(entity)
+            .insert(entity, &template.faction())?
+            .insert(entity, &template.status)?
+            .relate(self.id(), entity, FACTION);
+        for clock in &template.clocks {
+            let clock = Clock::with_progress(clock.label.clone(), clock.segments, clock.filled).map_err(|source| CampaignError::InvalidClock {
+                faction: template.descriptor.label().into(),
+                source,
+            })?;
+            let clock_entity = Uuid::new_v4();
+            changeset = changeset
+                .spawn(clock_entity)
+                .insert(clock_entity, &clock)?
+                .relate(entity, clock_entity, CLOCK);
+        }
+        self.apply(changeset.record(Some(entity), "faction.added", &template.faction())?).await?;
+
+        Ok(entity)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use darkforge_data::JSONDeserialize;
+    use rstest::rstest;
+
+    use super::*;
+    #[cfg(feature = "store")]
+    use crate::{campaign::tests::bootstrap, permission::PermissionError};
+
+    #[rstest]
+    #[case::war(-5, -3)]
+    #[case::neutral(0, 0)]
+    #[case::friendly(2, 2)]
+    #[case::ally(7, 3)]
+    fn should_clamp_status(#[case] value: i8, #[case] expect: i8) {
+        assert_eq!(expect, Status::new(value).value());
+    }
+
+    #[test]
+    fn should_deserialize_template_with_defaults() {
+        let template = FactionTemplate::from_json(
+            r#"{
+                "id": "45a83a2c-066b-4676-9179-c3109025f4d7",
+                "descriptor": {
+                    "id": "d0a9b7b4-d84a-4765-baf1-b3f0f5297d51",
+                    "label": "Bluecoats",
+                    "description": "The City Watch of Doskvol."
+                },
+                "tier": 3,
+                "hold": "strong"
+            }"#
+            .as_bytes(),
+        )
+        .expect("should have deserialized template");
+
+        assert_eq!(Status::default(), template.status);
+        assert_eq!(Vec::<ClockTemplate>::new(), template.clocks);
+        assert_eq!(Hold::Strong, template.faction().hold());
+    }
+
+    #[cfg(feature = "store")]
+    #[tokio::test]
+    async fn should_add_faction_with_its_clocks() {
+        let mut campaign = bootstrap().await;
+        let before = campaign.factions().await.expect("should have listed factions").len();
+        let template = FactionTemplate {
+            id: Uuid::new_v4(),
+            descriptor: Descriptor::new(Uuid::new_v4(), "Ink Rakes", "Thieves and forgers of Crow's Foot."),
+            tier: 1,
+            hold: Hold::Weak,
+            status: Status::new(-1),
+            clocks: vec![ClockTemplate {
+                label: "Forge a charter".into(),
+                segments: 6,
+                filled: 2,
+            }],
+        };
+
+        let entity = campaign.add_faction(Actor::Gm, &template).await.expect("should have added faction");
+
+        let factions = campaign.factions().await.expect("should have listed factions");
+        assert_eq!(before + 1, factions.len());
+        assert!(factions.contains(&(entity, template.faction())));
+        let clocks = campaign.clocks().await.expect("should have listed clocks");
+        assert!(clocks.iter().any(|(_, owner, clock)| *owner == Some(entity) && clock.filled() == 2));
+    }
+
+    #[cfg(feature = "store")]
+    #[tokio::test]
+    async fn should_only_let_gm_add_faction() {
+        let mut campaign = bootstrap().await;
+        let template = FactionTemplate {
+            id: Uuid::new_v4(),
+            descriptor: Descriptor::new(Uuid::new_v4(), "Ink Rakes", "Thieves and forgers of Crow's Foot."),
+            tier: 1,
+            hold: Hold::Weak,
+            status: Status::default(),
+            clocks: Vec::new(),
+        };
+
+        let result = campaign.add_faction(Actor::Player(Uuid::new_v4()), &template).await;
+
+        assert!(matches!(result, Err(CampaignError::Permission(PermissionError::GmOnly(_)))));
+    }
+}
@@ -0,0 +1,198 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! Games of chance played in Doskvol's gambling dens, e.g. by characters indulging their vice during downtime.
+//!
+//! In a game of bones, the character and the house each roll [`BONES`] dice and the higher total takes the stake, the
+//! house winning ties. A character may cheat by making an action roll, e.g. Finesse to palm loaded bones, whose
+//! outcome decides whether the cheat works and whether the house notices, see [`Cheat`].
+
+use darkforge_rng::dice::Dice;
+use serde::{Deserialize, Serialize};
+
+use crate::{action::Outcome, odds::Ratio};
+
+/// The number of dice each side rolls in a game of bones.
+pub const BONES: usize = 3;
+
+/// A bet on a game of bones.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Wager {
+    /// The coin staked, taken from the character's stash.
+    pub stake: u8,
+    /// The outcome of the action roll made to cheat, if the character cheats.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cheat: Option<Outcome>,
+}
+
+/// How an attempt to cheat turned out.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Cheat {
+    /// The character's lowest die was swapped for a six, unnoticed.
+    Unnoticed,
+    /// The character's lowest die was swapped for a six, but the house grew suspicious and the GM may inflict a
+    /// consequence, e.g. a complication or heat.
+    Suspected,
+    /// The character was caught before the bones were rolled and forfeits the stake.
+    Caught,
+}
+
+impl Cheat {
+    /// Reads how the cheat turned out from the outcome of the action roll made to cheat.
+    #[must_use]
+    pub fn from_outcome(outcome: Outcome) -> Cheat {
+        match outcome {
+            Outcome::Critical | Outcome::Success => Cheat::Unnoticed,
+            Outcome::Partial => Cheat::Suspected,
+            Outcome::Failure => Cheat::Caught,
+        }
+    }
+}
+
+/// A game of bones played against the house.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Game {
+    /// The bet made.
+    pub wager: Wager,
+    /// How the attempt to cheat turned out, if the character cheated.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cheat: Option<Cheat>,
+    /// The character's dice, after cheating, or none if they were caught.
+    pub player: Vec<u8>,
+    /// The house's dice, or none if the character was caught.
+    pub house: Vec<u8>,
+    /// The coin won, negative if the stake was lost.
+    pub winnings: i16,
+}
+
+impl Game {
+    /// Plays a game of bones for the wager, rolling with `dice`.
+    pub fn play(dice: &impl Dice, wager: Wager) -> Game {
+        let cheat = wager.cheat.map(Cheat::from_outcome);
+        if cheat == Some(Cheat::Caught) {
+            return Game {
+                wager,
+                cheat,
+                player: Vec::new(),
+                house: Vec::new(),
+                winnings: -i16::from(wager.stake),
+            };
+        }
+
+        let mut player = dice.roll_pool(BONES);
+        if cheat.is_some() {
+            load(&mut player);
+        }
+        let house = dice.roll_pool(BONES);
+        let won = total(&player) > total(&house);
+
+        Game {
+            wager,
+            cheat,
+            player,
+            house,
+            winnings: if won { i16::from(wager.stake) } else { -i16::from(wager.stake) },
+        }
+    }
+
+    /// Returns true if the character won the stake.
+    #[must_use]
+    pub fn won(&self) -> bool {
+        self.winnings > 0
+    }
+}
+
+/// Computes the exact chance of the character beating the house, with a cheat that worked or without cheating.
+#[must_use]
+pub fn odds(cheating: bool) -> Ratio {
+    let rolls = rolls();
+    let mut house = [0u128; 6 * BONES + 1];
+    for roll in &rolls {
+        house[usize::from(total(roll))] += 1;
+    }
+
+    let wins = rolls
+        .into_iter()
+        .map(|mut roll| {
+            if cheating {
+                load(&mut roll);
+            }
+            house[..usize::from(total(&roll))].iter().sum::<u128>()
+        })
+        .sum();
+    let count = house.iter().sum::<u128>();
+
+    Ratio::new(wins, count * count)
+}
+
+/// Swaps the lowest die for a six.
+fn load(dice: &mut [u8]) {
+    if let Some(lowest) = dice.iter_mut().min() {
+        *lowest = 6;
+    }
+}
+
+/// Returns the total of the dice.
+fn total(dice: &[u8]) -> u8 {
+    dice.iter().sum()
+}
+
+/// Returns every roll of [`BONES`] dice.
+fn rolls() -> Vec<Vec<u8>> {
+    (0..BONES).fold(vec![Vec::new()], |rolls, _| {
+        rolls
+            .into_iter()
+            .flat_map(|roll| {
+                (1..=6).map(move |die| {
+                    let mut roll = roll.clone();
+                    roll.push(die);
+                    roll
+                })
+            })
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+    use crate::action::tests::Loaded;
+
+    #[rstest]
+    #[case::fair(None, &[3, 4, 5, 2, 6, 3], None, vec![3, 4, 5], 4)]
+    #[case::tie_goes_to_house(None, &[3, 4, 5, 6, 5, 1], None, vec![3, 4, 5], -4)]
+    #[case::unnoticed(Some(Outcome::Critical), &[1, 4, 5, 6, 5, 3], Some(Cheat::Unnoticed), vec![6, 4, 5], 4)]
+    #[case::suspected(Some(Outcome::Partial), &[1, 1, 1, 6, 6, 6], Some(Cheat::Suspected), vec![6, 1, 1], -4)]
+    #[case::caught(Some(Outcome::Failure), &[6, 6, 6, 1, 1, 1], Some(Cheat::Caught), vec![], -4)]
+    fn should_play_bones_against_house(
+        #[case] cheat: Option<Outcome>, #[case] dice: &[u8], #[case] expected: Option<Cheat>, #[case] player: Vec<u8>, #[case] winnings: i16,
+    ) {
+        let game = Game::play(&Loaded::new(dice), Wager { stake: 4, cheat });
+
+        assert_eq!(expected, game.cheat);
+        assert_eq!(player, game.player);
+        assert_eq!(winnings, game.winnings);
+        assert_eq!(winnings > 0, game.won());
+    }
+
+    #[test]
+    fn should_compute_exact_odds_of_beating_house() {
+        // Out of 216 × 216 pairs of rolls, 4332 are ties and the rest are split evenly between both sides.
+        assert_eq!(Ratio::new(21_162, 46_656), odds(false));
+
+        let cheating = odds(true);
+        assert!(cheating.to_f64() > 0.75, "cheating only wins {cheating}");
+    }
+}
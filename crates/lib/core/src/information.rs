@@ -0,0 +1,197 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! The gather information move: a character asks a question and a fortune roll tells how much they learn.
+
+#[cfg(feature = "store")]
+use darkforge_data::store::Content;
+use darkforge_rng::dice::Dice;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::action::{FortuneRoll, Outcome};
+#[cfg(feature = "store")]
+use crate::{
+    campaign::{Campaign, Result},
+    permission::Actor,
+};
+
+/// Content pack category holding the questions characters can ask.
+pub const CATEGORY: &str = "question";
+
+/// A question a character can ask when gathering information.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Question {
+    /// The id of the question in the content pack.
+    pub id: Uuid,
+    /// The question asked.
+    pub text: String,
+    /// The actions best suited to answer the question.
+    #[serde(default)]
+    pub actions: Vec<Uuid>,
+}
+
+impl Question {
+    /// Returns true if the action is one of those suited to answer the question.
+    #[must_use]
+    pub fn suits(&self, action: Uuid) -> bool {
+        self.actions.contains(&action)
+    }
+}
+
+/// How much detail the answer to a question holds.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Detail {
+    /// A partial or vague answer.
+    Limited,
+    /// A clear answer, without much depth.
+    Standard,
+    /// A complete and detailed answer.
+    Great,
+    /// A complete answer, along with more than was asked.
+    Exceptional,
+}
+
+impl From<Outcome> for Detail {
+    fn from(outcome: Outcome) -> Detail {
+        match outcome {
+            Outcome::Failure => Detail::Limited,
+            Outcome::Partial => Detail::Standard,
+            Outcome::Success => Detail::Great,
+            Outcome::Critical => Detail::Exceptional,
+        }
+    }
+}
+
+/// The answer to a question, for the GM to flesh out as a clue.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Clue {
+    /// The id of the question asked.
+    pub question: Uuid,
+    /// The question asked.
+    pub text: String,
+    /// The action used to gather the information.
+    pub action: Uuid,
+    /// True if the action is one of those suited to answer the question.
+    pub suited: bool,
+    /// The fortune roll made.
+    pub roll: FortuneRoll,
+    /// How much detail the answer holds.
+    pub detail: Detail,
+}
+
+/// Gathers information by rolling the rating of the action used as a fortune roll.
+pub fn gather_information(question: &Question, action: Uuid, rating: u8, dice: &impl Dice) -> Clue {
+    let roll = FortuneRoll::roll(dice, rating);
+
+    Clue {
+        question: question.id,
+        text: question.text.clone(),
+        action,
+        suited: question.suits(action),
+        detail: roll.outcome.into(),
+        roll,
+    }
+}
+
+#[cfg(feature = "store")]
+impl Campaign {
+    /// Gathers information for the character and records the clue in the journal.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::Permission`](crate::campaign::CampaignError::Permission) if the actor does not own the
+    /// character, or another [`CampaignError`](crate::campaign::CampaignError) if the store cannot be written.
+    pub async fn gather_information(
+        &mut self, actor: Actor, character: Uuid, question: &Question, action: Uuid, rating: u8, dice: &impl Dice,
+    ) -> Result<Clue> {
+        let clue = gather_information(question, action, rating, dice);
+        self.record(actor, Some(character), "information.gathered", &clue).await?;
+
+        Ok(clue)
+    }
+
+    /// Returns the questions characters can ask, as loaded from the content pack.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CampaignError`](crate::campaign::CampaignError) if the store cannot be read.
+    pub async fn questions(&mut self) -> Result<Vec<Question>> {
+        Ok(self.store().entries(CATEGORY).await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "store")]
+    use darkforge_data::store::World;
+    use rstest::rstest;
+    use uuid::uuid;
+
+    use super::*;
+    use crate::action::tests::Loaded;
+    #[cfg(feature = "store")]
+    use crate::campaign::tests::bootstrap;
+
+    const STUDY: Uuid = uuid!("31071085-1d8c-4676-8710-851d8cf6763c");
+    const WRECK: Uuid = uuid!("3abf9fbc-26e5-46e6-bf9f-bc26e556e6d4");
+
+    fn question() -> Question {
+        Question {
+            id: uuid!("ae3225f6-56f2-4e31-85f7-1bef4f3a1587"),
+            text: "What do they intend to do?".into(),
+            actions: vec![STUDY],
+        }
+    }
+
+    #[rstest]
+    #[case::limited(2, &[3, 1], Detail::Limited)]
+    #[case::standard(1, &[5], Detail::Standard)]
+    #[case::great(2, &[6, 2], Detail::Great)]
+    #[case::exceptional(3, &[6, 1, 6], Detail::Exceptional)]
+    #[case::zero_rating(0, &[6, 4], Detail::Standard)]
+    fn should_map_fortune_to_detail(#[case] rating: u8, #[case] results: &[u8], #[case] expect: Detail) {
+        let clue = gather_information(&question(), STUDY, rating, &Loaded::new(results));
+
+        assert_eq!(expect, clue.detail);
+        assert!(clue.suited);
+    }
+
+    #[test]
+    fn should_flag_unsuited_action() {
+        let clue = gather_information(&question(), WRECK, 1, &Loaded::new(&[4]));
+
+        assert!(!clue.suited);
+    }
+
+    #[cfg(feature = "store")]
+    #[tokio::test]
+    async fn should_gather_information_from_pack_questions() {
+        let mut campaign = bootstrap().await;
+        let pc = campaign.store().spawn().await.expect("should have spawned character");
+
+        let questions = campaign.questions().await.expect("should have listed questions");
+        let question = questions
+            .iter()
+            .find(|question| question.text == "What do they intend to do?")
+            .expect("should have defined question");
+
+        let clue = campaign
+            .gather_information(Actor::Gm, pc, question, STUDY, 2, &Loaded::new(&[4, 6]))
+            .await
+            .expect("should have gathered information");
+
+        assert_eq!(Detail::Great, clue.detail);
+        assert!(clue.suited);
+    }
+}
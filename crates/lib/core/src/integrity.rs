@@ -0,0 +1,209 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! Integrity of the static data a campaign runs on, checked against the content packs the game shipped with so that
+//! leagues and shared campaigns can require vanilla data.
+//!
+//! Every pack is hashed as it is loaded, see [`PackHash`]. [`Campaign::verify_content`] compares those hashes with the
+//! hashes of the packs shipped, and the entries the store holds with the packs as they were loaded, reporting a
+//! [`ContentMismatch`] for each difference: a modded pack is loaded in place of the shipped one, or the static data was
+//! edited in the database afterwards.
+
+use darkforge_data::pack::PackHash;
+#[cfg(feature = "store")]
+use darkforge_data::store::{Content as _, sql::sqlite::SqliteStore};
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "store")]
+use crate::campaign::{Campaign, Result};
+
+/// A difference between the static data of a campaign and the content packs the game shipped with.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ContentMismatch {
+    /// A pack shipped with the game was not loaded, or was loaded before packs were hashed.
+    Missing {
+        /// The name of the pack.
+        pack: String,
+    },
+    /// The pack loaded is not the one shipped with the game, e.g. a modded pack.
+    Modded {
+        /// The name of the pack.
+        pack: String,
+        /// The categories that differ, empty if the hash shipped only gives the digest of the whole pack.
+        categories: Vec<String>,
+    },
+    /// The entries held for the pack changed since it was loaded, e.g. edited in the database.
+    Tampered {
+        /// The name of the pack.
+        pack: String,
+        /// The categories that changed.
+        categories: Vec<String>,
+    },
+    /// A pack was loaded that the game did not ship with.
+    Unexpected {
+        /// The name of the pack.
+        pack: String,
+    },
+}
+
+impl ContentMismatch {
+    /// Returns the name of the pack that mismatches.
+    #[must_use]
+    pub fn pack(&self) -> &str {
+        match self {
+            ContentMismatch::Missing { pack }
+            | ContentMismatch::Modded { pack, .. }
+            | ContentMismatch::Tampered { pack, .. }
+            | ContentMismatch::Unexpected { pack } => pack,
+        }
+    }
+}
+
+/// Compares the hashes of the packs loaded with those of the packs shipped, by pack name.
+#[must_use]
+pub fn compare(shipped: &[PackHash], loaded: &[PackHash]) -> Vec<ContentMismatch> {
+    let mut mismatches = shipped
+        .iter()
+        .filter_map(|expected| {
+            let Some(found) = loaded.iter().find(|hash| hash.pack == expected.pack) else {
+                return Some(ContentMismatch::Missing { pack: expected.pack.clone() });
+            };
+
+            (found.digest != expected.digest).then(|| ContentMismatch::Modded {
+                pack: expected.pack.clone(),
+                categories: if expected.categories.is_empty() {
+                    Vec::new()
+                } else {
+                    expected.differing(found)
+                },
+            })
+        })
+        .collect::<Vec<_>>();
+    mismatches.extend(
+        loaded
+            .iter()
+            .filter(|found| !shipped.iter().any(|expected| expected.pack == found.pack))
+            .map(|found| ContentMismatch::Unexpected { pack: found.pack.clone() }),
+    );
+
+    mismatches
+}
+
+#[cfg(feature = "store")]
+impl Campaign {
+    /// Returns the hashes of the content packs as they were last loaded in the store, ordered by pack name.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::Store`](crate::campaign::CampaignError::Store) if the store cannot be read.
+    pub async fn content_hashes(&mut self) -> Result<Vec<PackHash>> {
+        Ok(self.store().pack_hashes().await?)
+    }
+
+    /// Checks the static data held in the store against the hashes of the packs the game shipped with, see
+    /// [`Pack::hash`](darkforge_data::pack::Pack::hash), returning the mismatches ordered by pack name, none if it is
+    /// vanilla data. The static data is shared by the campaigns of the store, so it can be checked before any is open.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::Store`](crate::campaign::CampaignError::Store) if the store cannot be read.
+    pub async fn verify_packs(store: &mut SqliteStore, shipped: &[PackHash]) -> Result<Vec<ContentMismatch>> {
+        let loaded = store.pack_hashes().await?;
+        let mut mismatches = compare(shipped, &loaded);
+        for hash in &loaded {
+            let current = store.hash_content(&hash.pack).await?;
+            if current.digest != hash.digest {
+                mismatches.push(ContentMismatch::Tampered {
+                    pack: hash.pack.clone(),
+                    categories: hash.differing(&current),
+                });
+            }
+        }
+        mismatches.sort_by(|a, b| a.pack().cmp(b.pack()));
+
+        Ok(mismatches)
+    }
+
+    /// Checks the static data the campaign runs on against the hashes of the packs the game shipped with, see
+    /// [`Campaign::verify_packs`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::Store`](crate::campaign::CampaignError::Store) if the store cannot be read.
+    pub async fn verify_content(&mut self, shipped: &[PackHash]) -> Result<Vec<ContentMismatch>> {
+        Campaign::verify_packs(self.store(), shipped).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "store")]
+    use darkforge_data::pack::Pack;
+
+    use super::*;
+    #[cfg(feature = "store")]
+    use crate::campaign::tests::{DEFAULTS, bootstrap};
+
+    #[test]
+    fn should_compare_loaded_packs_with_shipped_ones() {
+        let hash = |pack: &str, digest: &str| PackHash {
+            pack: pack.into(),
+            digest: digest.into(),
+            ..PackHash::default()
+        };
+        let shipped = [hash("defaults", "1a"), hash("deep-cuts", "2b"), hash("gangs", "3c")];
+        let loaded = [hash("defaults", "1a"), hash("gangs", "4d"), hash("homebrew", "5e")];
+
+        assert_eq!(
+            vec![
+                ContentMismatch::Missing { pack: "deep-cuts".into() },
+                ContentMismatch::Modded {
+                    pack: "gangs".into(),
+                    categories: Vec::new()
+                },
+                ContentMismatch::Unexpected { pack: "homebrew".into() },
+            ],
+            compare(&shipped, &loaded)
+        );
+    }
+
+    #[cfg(feature = "store")]
+    #[tokio::test]
+    async fn should_verify_content_against_shipped_packs() {
+        let mut campaign = bootstrap().await;
+        let shipped = Pack::open(DEFAULTS).expect("should have opened pack");
+
+        assert_eq!(
+            Vec::<ContentMismatch>::new(),
+            campaign.verify_content(&[shipped.hash()]).await.expect("should have verified content")
+        );
+
+        let modded = Pack::parse(
+            "defaults",
+            [("faction", r#"[{ "id": "7ef1bdbe-84de-4699-b1bd-be84de569995", "name": "Bluecoats" }]"#)],
+        )
+        .expect("should have parsed pack");
+        campaign
+            .store()
+            .reload_pack(&modded, |_| Ok(Vec::new()))
+            .await
+            .expect("should have reloaded pack");
+        let mismatches = campaign.verify_content(&[shipped.hash()]).await.expect("should have verified content");
+
+        let [ContentMismatch::Modded { pack, categories }] = mismatches.as_slice() else {
+            panic!("should have found the modded pack, found {mismatches:?}");
+        };
+        assert_eq!("defaults", pack);
+        assert_eq!(shipped.categories().map(String::from).collect::<Vec<_>>(), *categories);
+    }
+}
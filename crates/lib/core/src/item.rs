@@ -0,0 +1,255 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! Items, their quality relative to the crew's tier, and crafting new ones.
+
+use darkforge_data::{Component, Persisted, descriptor::Descriptor};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::effect::Effect;
+
+/// Content pack category holding item definitions.
+pub const CATEGORY: &str = "item";
+
+/// The highest quality an item can reach.
+pub const MAX_QUALITY: u8 = 6;
+
+/// The kinds of armor that can absorb harm.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum ArmorKind {
+    /// Regular armor.
+    Armor,
+    /// Heavy armor, worn on top of regular armor.
+    Heavy,
+    /// Special armor granted by an ability rather than an item.
+    Special,
+}
+
+/// An item a character can carry.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Item {
+    descriptor: Descriptor<'static>,
+    load: u8,
+    #[serde(default)]
+    fine: bool,
+    #[serde(default)]
+    unique: bool,
+    #[serde(default)]
+    bonus: i8,
+    #[serde(default)]
+    armor: Option<ArmorKind>,
+}
+
+impl Item {
+    /// Creates a new item with the given load.
+    #[must_use]
+    pub fn new(descriptor: Descriptor<'static>, load: u8) -> Item {
+        Item {
+            descriptor,
+            load,
+            fine: false,
+            unique: false,
+            bonus: 0,
+            armor: None,
+        }
+    }
+
+    /// Returns the item marked as fine, improving its quality by one.
+    #[must_use]
+    pub fn fine(mut self) -> Item {
+        self.fine = true;
+        self
+    }
+
+    /// Returns the item marked as unique, the only one of its kind.
+    #[must_use]
+    pub fn unique(mut self) -> Item {
+        self.unique = true;
+        self
+    }
+
+    /// Returns the item with its quality shifted relative to the crew's tier, e.g. by crafting.
+    #[must_use]
+    pub fn with_bonus(mut self, bonus: i8) -> Item {
+        self.bonus = bonus;
+        self
+    }
+
+    /// Returns the item as armor of the given kind.
+    #[must_use]
+    pub fn armor(mut self, kind: ArmorKind) -> Item {
+        self.armor = Some(kind);
+        self
+    }
+
+    /// Returns the kind of armor the item provides, if any.
+    #[must_use]
+    pub fn armor_kind(&self) -> Option<ArmorKind> {
+        self.armor
+    }
+
+    /// Returns the item's descriptor.
+    #[must_use]
+    pub fn descriptor(&self) -> &Descriptor<'static> {
+        &self.descriptor
+    }
+
+    /// Returns the load the item takes up.
+    #[must_use]
+    pub fn load(&self) -> u8 {
+        self.load
+    }
+
+    /// Returns true if the item is fine.
+    #[must_use]
+    pub fn is_fine(&self) -> bool {
+        self.fine
+    }
+
+    /// Returns true if the item is unique.
+    #[must_use]
+    pub fn is_unique(&self) -> bool {
+        self.unique
+    }
+
+    /// Returns the item's quality in the hands of a crew of the given tier, between 0 and [`MAX_QUALITY`].
+    #[must_use]
+    pub fn quality(&self, tier: u8) -> u8 {
+        let quality = i16::from(tier) + i16::from(self.fine) + i16::from(self.bonus);
+        u8::try_from(quality.clamp(0, i16::from(MAX_QUALITY))).unwrap_or_default()
+    }
+
+    /// Returns the effect of an action using the item against an opposition of the given tier.
+    #[must_use]
+    pub fn effect_against(&self, effect: Effect, tier: u8, target_tier: u8) -> Effect {
+        effect.shift(quality_levels(self.quality(tier), target_tier))
+    }
+}
+
+impl Component for Item {}
+
+impl Persisted for Item {
+    const KIND: &'static str = "item";
+}
+
+/// An item as defined in a content pack.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ItemDefinition {
+    /// The id of the definition in the content pack.
+    pub id: Uuid,
+    /// The item.
+    #[serde(flatten)]
+    pub item: Item,
+}
+
+/// Returns the number of effect levels gained, or lost if negative, when a given quality faces a given tier.
+#[must_use]
+pub fn quality_levels(quality: u8, target_tier: u8) -> i8 {
+    let levels = i16::from(quality) - i16::from(target_tier);
+    i8::try_from(levels).unwrap_or_default()
+}
+
+/// Returns the quality of an item crafted by a crew of the given tier, or `None` if the roll had no effect.
+///
+/// A standard effect produces an item of the crew's tier, each level above or below shifts it by one.
+#[must_use]
+pub fn crafted_quality(tier: u8, effect: Effect) -> Option<u8> {
+    let shift = match effect {
+        Effect::Zero => return None,
+        Effect::Limited => -1,
+        Effect::Standard => 0,
+        Effect::Great => 1,
+        Effect::Extreme => 2,
+    };
+
+    let quality = (i16::from(tier) + shift).clamp(0, i16::from(MAX_QUALITY));
+    u8::try_from(quality).ok()
+}
+
+/// Crafts an item from its definition, setting its quality from the crafting roll's effect.
+///
+/// Returns `None` if the roll had no effect.
+#[must_use]
+pub fn craft(definition: &ItemDefinition, tier: u8, effect: Effect) -> Option<Item> {
+    let quality = crafted_quality(tier, effect)?;
+    let base = definition.item.clone().with_bonus(0).quality(tier);
+    let bonus = i8::try_from(i16::from(quality) - i16::from(base)).unwrap_or_default();
+
+    Some(definition.item.clone().with_bonus(bonus))
+}
+
+#[cfg(test)]
+mod tests {
+    use darkforge_data::pack::Pack;
+    use rstest::rstest;
+    use uuid::uuid;
+
+    use super::*;
+    use crate::campaign::tests::DEFAULTS;
+
+    fn lockpicks() -> Item {
+        Item::new(
+            Descriptor::new(uuid!("1d2c3b4a-5968-4776-8594-a3b2c1d0e9f8"), "Lockpicks", "Tools to pick locks."),
+            1,
+        )
+    }
+
+    #[rstest]
+    #[case::plain(lockpicks(), 2, 2)]
+    #[case::fine(lockpicks().fine(), 2, 3)]
+    #[case::bonus(lockpicks().fine().with_bonus(1), 2, 4)]
+    #[case::malus(lockpicks().with_bonus(-3), 1, 0)]
+    #[case::capped(lockpicks().fine().with_bonus(3), 4, 6)]
+    fn should_compute_quality_relative_to_tier(#[case] item: Item, #[case] tier: u8, #[case] expect: u8) {
+        assert_eq!(expect, item.quality(tier));
+    }
+
+    #[rstest]
+    #[case::even(lockpicks(), 2, 2, Effect::Standard)]
+    #[case::fine_vs_even(lockpicks().fine(), 2, 2, Effect::Great)]
+    #[case::outclassed(lockpicks(), 1, 3, Effect::Zero)]
+    #[case::superior(lockpicks().fine(), 3, 1, Effect::Extreme)]
+    fn should_compute_effect_against_target_tier(#[case] item: Item, #[case] tier: u8, #[case] target: u8, #[case] expect: Effect) {
+        assert_eq!(expect, item.effect_against(Effect::Standard, tier, target));
+    }
+
+    #[rstest]
+    #[case::zero(Effect::Zero, None)]
+    #[case::limited(Effect::Limited, Some(1))]
+    #[case::standard(Effect::Standard, Some(2))]
+    #[case::great(Effect::Great, Some(3))]
+    #[case::extreme(Effect::Extreme, Some(4))]
+    fn should_craft_item_of_quality_from_effect(#[case] effect: Effect, #[case] expect: Option<u8>) {
+        let definition = ItemDefinition {
+            id: uuid!("6c1e4a2b-8d3f-4e5a-9b7c-0d1e2f3a4b5c"),
+            item: lockpicks().fine(),
+        };
+
+        assert_eq!(expect, crafted_quality(2, effect));
+        assert_eq!(expect, craft(&definition, 2, effect).map(|item| item.quality(2)));
+    }
+
+    #[test]
+    fn should_load_item_definitions_from_default_pack() {
+        let pack = Pack::open(DEFAULTS).expect("should have opened pack");
+
+        let items = pack.load::<ItemDefinition>(CATEGORY).expect("should have loaded items");
+
+        let armor = items
+            .iter()
+            .find(|definition| definition.item.descriptor().label() == "Armor")
+            .expect("should have defined armor");
+        assert_eq!(2, armor.item.load());
+    }
+}
@@ -10,18 +10,111 @@
  * You should have received a copy of the GNU Affero General Public License along with this program.
  * If not, see https://www.gnu.org/licenses/.
  */
-mod character;
-
-pub struct Character {
-    name: String,
-}
+/// Module for action rolls and their consequences.
+pub mod action;
+/// Module for campaign bootstrapping and access.
+pub mod campaign;
+/// Module for reviewing what changed in a campaign's world.
+pub mod changes;
+/// Module for atomic, validated world mutations.
+pub mod changeset;
+/// Module for player characters.
+pub mod character;
+/// Module for progress clocks.
+pub mod clock;
+/// Module for consequences and armor.
+pub mod consequence;
+/// Module for contests between faction clocks.
+pub mod contest;
+/// Module for the fictional context of rolls.
+pub mod context;
+/// Module for the crew.
+pub mod crew;
+/// Module for the GM screen.
+pub mod dashboard;
+/// Module for downtime activities.
+pub mod downtime;
+/// Module for effect levels.
+pub mod effect;
+/// Module for swappable rules services and their registry.
+pub mod engine;
+/// Module for character and campaign exports.
+pub mod export;
+/// Module for factions.
+pub mod faction;
+/// Module for games of chance.
+pub mod gambling;
+/// Module for the gather information move.
+pub mod information;
+/// Module for checking the static data against the content packs shipped with the game.
+pub mod integrity;
+/// Module for items and crafting.
+pub mod item;
+/// Module for loads and loadouts.
+pub mod loadout;
+/// Module for the faction map of the GM's faction screen.
+pub mod map;
+/// Module for dice notation of rolls and their translation.
+pub mod notation;
+/// Module for non-player characters.
+pub mod npc;
+/// Module for the odds of roll outcomes.
+pub mod odds;
+/// Module for the starting situations of one-shots.
+pub mod oneshot;
+/// Module for the yes/no oracle of solo play.
+pub mod oracle;
+/// Module for access control on shared campaign state.
+pub mod permission;
+/// Module for the decisions prompted while playing actions.
+pub mod prompt;
+/// Module for journal retention and pruning.
+pub mod retention;
+/// Module for free dice rolls and their history.
+pub mod roll;
+/// Module for player rosters.
+pub mod roster;
+/// Module for the rule reference.
+pub mod rule;
+/// Module for deferred triggers and the campaign calendar.
+pub mod schedule;
+/// Module for the declared layout of campaign databases.
+#[cfg(feature = "store")]
+pub mod schema;
+/// Module for fictive time and flashbacks within a score.
+pub mod score;
+/// Module for scripted special abilities and entanglements.
+#[cfg(feature = "scripting")]
+pub mod script;
+/// Module for play sessions and their reports.
+pub mod session;
+/// Module for session zero decisions and house rules.
+pub mod setup;
+/// Module for save slots listing, duplicating and deleting the campaigns of a store.
+pub mod slot;
+/// Module for plain text stat blocks of NPCs and factions.
+pub mod statblock;
+/// Module for statistics of a campaign over time.
+pub mod stats;
+/// Module for weighted random tables.
+pub mod table;
+/// Module for text templates naming campaign entities.
+pub mod template;
+/// Module for faction turns in solo play.
+pub mod turn;
+/// Module for crew upgrades and their effects on rolls and downtime.
+pub mod upgrade;
+/// Module for seeded world generation.
+pub mod worldgen;
 
+#[expect(dead_code, reason = "Records are placeholders until the rules engine is wired to the data store")]
 pub struct AttributeRecord {
     id: [u8; 16],
     label: String,
     description: String,
 }
 
+#[expect(dead_code, reason = "Records are placeholders until the rules engine is wired to the data store")]
 pub struct ActionRecord {
     id: [u8; 16],
     label: String,
@@ -0,0 +1,158 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! The load a character declares for a score and the items they carry.
+
+use darkforge_data::{Component, Persisted};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::item::{ArmorKind, Item};
+
+/// Error type for loadout operations.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum LoadError {
+    /// The item does not fit in the declared load.
+    #[error("{item} needs {needed} load but only {left} is left")]
+    Overloaded {
+        /// The label of the item.
+        item: String,
+        /// The load the item takes up.
+        needed: u8,
+        /// The load left.
+        left: u8,
+    },
+}
+
+/// How much a character declares they carry, trading speed and stealth for gear.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Load {
+    /// Faster, less conspicuous.
+    Light,
+    /// Looks like a scoundrel ready for trouble.
+    #[default]
+    Normal,
+    /// Slower, obviously armed and equipped.
+    Heavy,
+}
+
+impl Load {
+    /// Returns the total load a character can carry.
+    #[must_use]
+    pub fn capacity(self) -> u8 {
+        match self {
+            Load::Light => 3,
+            Load::Normal => 5,
+            Load::Heavy => 6,
+        }
+    }
+}
+
+/// The items a character carries on a score.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct Loadout {
+    load: Load,
+    items: Vec<Item>,
+}
+
+impl Loadout {
+    /// Creates an empty loadout for the given load.
+    #[must_use]
+    pub fn new(load: Load) -> Loadout {
+        Loadout { load, items: Vec::new() }
+    }
+
+    /// Returns the declared load.
+    #[must_use]
+    pub fn load(&self) -> Load {
+        self.load
+    }
+
+    /// Returns the items carried.
+    #[must_use]
+    pub fn items(&self) -> &[Item] {
+        &self.items
+    }
+
+    /// Returns the load used by the items carried.
+    #[must_use]
+    pub fn used(&self) -> u8 {
+        self.items.iter().map(Item::load).sum()
+    }
+
+    /// Carries the item.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LoadError::Overloaded`] if the item does not fit in the declared load.
+    pub fn carry(&mut self, item: Item) -> Result<(), LoadError> {
+        let left = self.load.capacity().saturating_sub(self.used());
+        if item.load() > left {
+            return Err(LoadError::Overloaded {
+                item: item.descriptor().label().into(),
+                needed: item.load(),
+                left,
+            });
+        }
+
+        self.items.push(item);
+        Ok(())
+    }
+
+    /// Returns true if one of the items carried provides armor of the given kind.
+    #[must_use]
+    pub fn has_armor(&self, kind: ArmorKind) -> bool {
+        self.items.iter().any(|item| item.armor_kind() == Some(kind))
+    }
+}
+
+impl Component for Loadout {}
+
+impl Persisted for Loadout {
+    const KIND: &'static str = "loadout";
+}
+
+#[cfg(test)]
+mod tests {
+    use darkforge_data::descriptor::Descriptor;
+    use rstest::rstest;
+    use uuid::Uuid;
+
+    use super::*;
+
+    fn item(label: &str, load: u8) -> Item {
+        Item::new(Descriptor::new(Uuid::nil(), label.to_owned(), String::new()), load)
+    }
+
+    #[rstest]
+    #[case::fits(Load::Light, 1, Ok(()))]
+    #[case::overloaded(Load::Light, 4, Err(LoadError::Overloaded { item: "Armor".into(), needed: 4, left: 1 }))]
+    #[case::heavy(Load::Heavy, 4, Ok(()))]
+    fn should_carry_items_within_load(#[case] load: Load, #[case] needed: u8, #[case] expect: Result<(), LoadError>) {
+        let mut loadout = Loadout::new(load);
+        loadout.carry(item("Lantern", 2)).expect("should have carried lantern");
+
+        assert_eq!(expect, loadout.carry(item("Armor", needed)));
+    }
+
+    #[test]
+    fn should_provide_armor_from_items() {
+        let mut loadout = Loadout::new(Load::Heavy);
+        loadout
+            .carry(item("Armor", 2).armor(ArmorKind::Armor))
+            .expect("should have carried armor");
+
+        assert!(loadout.has_armor(ArmorKind::Armor));
+        assert!(!loadout.has_armor(ArmorKind::Heavy));
+    }
+}
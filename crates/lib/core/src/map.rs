@@ -0,0 +1,319 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! The faction map of the GM's faction screen.
+//!
+//! A [`FactionMap`] holds the campaign's factions and the relationships between them, placed in the unit square by a
+//! force-directed layout: every faction pushes the others away while relationships pull factions together, allies more
+//! firmly than enemies, and a pull toward the center keeps them all on the map. Laying a map out from the positions of
+//! an earlier one keeps its factions in place, so a map refreshed as the campaign changes does not jump around.
+
+#[cfg(feature = "store")]
+use darkforge_data::store::World as _;
+use rand::Rng;
+use uuid::Uuid;
+
+#[cfg(feature = "store")]
+use crate::campaign::{Campaign, Result};
+use crate::{
+    faction::{Faction, Status},
+    worldgen::{Relation, Relationship},
+};
+
+/// The margin kept free around the edges of the map, so factions drawn near them stay readable.
+const MARGIN: f64 = 0.05;
+
+/// How strongly factions are drawn to the center of the map, so those without relationships do not drift to its edges.
+const GRAVITY: f64 = 1.0;
+
+/// How far factions move toward where their forces push them at each step, so the layout settles instead of
+/// oscillating.
+const DAMPING: f64 = 0.1;
+
+/// A position on the map, both coordinates between 0 and 1.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Point {
+    /// The horizontal coordinate, from left to right.
+    pub x: f64,
+    /// The vertical coordinate, from top to bottom.
+    pub y: f64,
+}
+
+impl Point {
+    /// Returns the distance to the other point.
+    #[must_use]
+    pub fn distance(self, other: Point) -> f64 {
+        (self.x - other.x).hypot(self.y - other.y)
+    }
+}
+
+/// A faction placed on the map.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MapNode {
+    /// The faction entity.
+    pub entity: Uuid,
+    /// The faction.
+    pub faction: Faction,
+    /// The crew's standing with the faction.
+    pub status: Status,
+    /// Where the faction is placed, once the map is laid out.
+    pub position: Point,
+}
+
+/// The factions of a campaign and the relationships between them.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FactionMap {
+    /// The factions, in the order the campaign lists them.
+    pub nodes: Vec<MapNode>,
+    /// The relationships between factions of the map.
+    pub edges: Vec<Relationship>,
+}
+
+impl FactionMap {
+    /// The number of steps simulated to lay a map out.
+    pub const ITERATIONS: u32 = 300;
+
+    /// Creates a map of the factions, with the relationships between them, leaving out relationships with factions
+    /// that are not on the map. Every faction is placed at the origin until the map is laid out.
+    #[must_use]
+    pub fn new(factions: impl IntoIterator<Item = (Uuid, Faction, Status)>, edges: impl IntoIterator<Item = Relationship>) -> FactionMap {
+        let nodes = factions
+            .into_iter()
+            .map(|(entity, faction, status)| MapNode {
+                entity,
+                faction,
+                status,
+                position: Point::default(),
+            })
+            .collect::<Vec<_>>();
+        let placed = |entity: Uuid| nodes.iter().any(|node| node.entity == entity);
+        let edges = edges
+            .into_iter()
+            .filter(|edge| edge.from != edge.to && placed(edge.from) && placed(edge.to))
+            .collect();
+
+        FactionMap { nodes, edges }
+    }
+
+    /// Returns the faction of the map with the entity, if any.
+    #[must_use]
+    pub fn node(&self, entity: Uuid) -> Option<&MapNode> {
+        self.nodes.iter().find(|node| node.entity == entity)
+    }
+
+    /// Lays the map out, starting from the positions of the factions on the `previous` map if given, and from random
+    /// positions drawn from `rng` for the others.
+    pub fn layout(&mut self, previous: Option<&FactionMap>, rng: &mut impl Rng) {
+        let mut settled = true;
+        for node in &mut self.nodes {
+            let placed = previous.and_then(|previous| previous.node(node.entity));
+            settled &= placed.is_some();
+            node.position = placed.map_or_else(
+                || Point {
+                    x: rng.random_range(0.25..0.75),
+                    y: rng.random_range(0.25..0.75),
+                },
+                |placed| placed.position,
+            );
+        }
+        if self.nodes.len() < 2 {
+            for node in &mut self.nodes {
+                node.position = Point { x: 0.5, y: 0.5 };
+            }
+            return;
+        }
+
+        let edges = self
+            .edges
+            .iter()
+            .filter_map(|edge| {
+                let from = self.nodes.iter().position(|node| node.entity == edge.from)?;
+                let to = self.nodes.iter().position(|node| node.entity == edge.to)?;
+                let pull = match edge.relation {
+                    Relation::Ally => 1.0,
+                    Relation::Enemy => 0.25,
+                };
+                Some((from, to, pull))
+            })
+            .collect::<Vec<_>>();
+
+        #[expect(clippy::cast_precision_loss, reason = "Maps hold a handful of factions")]
+        let spacing = 0.5 / (self.nodes.len() as f64).sqrt();
+        // Factions already placed only settle, so the map keeps its shape.
+        let heat = if settled { 0.005 } else { 0.1 };
+        for iteration in 0..FactionMap::ITERATIONS {
+            let temperature = heat * f64::from(FactionMap::ITERATIONS - iteration) / f64::from(FactionMap::ITERATIONS);
+            let mut shifts = vec![Point::default(); self.nodes.len()];
+
+            for (i, node) in self.nodes.iter().enumerate() {
+                for (j, other) in self.nodes.iter().enumerate().skip(i + 1) {
+                    let (dx, dy, distance) = offset(node.position, other.position, i, j);
+                    let push = spacing * spacing / distance;
+                    shifts[i].x += dx / distance * push;
+                    shifts[i].y += dy / distance * push;
+                    shifts[j].x -= dx / distance * push;
+                    shifts[j].y -= dy / distance * push;
+                }
+            }
+            for &(from, to, pull) in &edges {
+                let (dx, dy, distance) = offset(self.nodes[from].position, self.nodes[to].position, from, to);
+                let pull = pull * distance * distance / spacing;
+                shifts[from].x -= dx / distance * pull;
+                shifts[from].y -= dy / distance * pull;
+                shifts[to].x += dx / distance * pull;
+                shifts[to].y += dy / distance * pull;
+            }
+            for (node, shift) in self.nodes.iter().zip(&mut shifts) {
+                shift.x += (0.5 - node.position.x) * GRAVITY;
+                shift.y += (0.5 - node.position.y) * GRAVITY;
+            }
+
+            for (node, shift) in self.nodes.iter_mut().zip(shifts) {
+                let length = shift.x.hypot(shift.y);
+                if length > 0.0 {
+                    let step = (length * DAMPING).min(temperature) / length;
+                    node.position.x = (node.position.x + shift.x * step).clamp(MARGIN, 1.0 - MARGIN);
+                    node.position.y = (node.position.y + shift.y * step).clamp(MARGIN, 1.0 - MARGIN);
+                }
+            }
+        }
+    }
+}
+
+/// Returns the offset from `to` to `from` and its length, nudging factions placed on top of each other apart.
+fn offset(from: Point, to: Point, i: usize, j: usize) -> (f64, f64, f64) {
+    let (dx, dy) = (from.x - to.x, from.y - to.y);
+    let distance = dx.hypot(dy);
+    if distance > 1e-6 {
+        return (dx, dy, distance);
+    }
+
+    let nudge = if i < j { -1e-3 } else { 1e-3 };
+    (nudge, 0.0, 1e-3)
+}
+
+#[cfg(feature = "store")]
+impl Campaign {
+    /// Returns the map of the campaign's factions along with the relationships between them, not yet laid out, see
+    /// [`FactionMap::layout`].
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CampaignError`](crate::campaign::CampaignError) if the store cannot be read.
+    pub async fn faction_map(&mut self) -> Result<FactionMap> {
+        let mut factions = Vec::new();
+        for (entity, faction) in self.factions().await? {
+            let status = self.store().get::<Status>(entity).await?.unwrap_or_default();
+            factions.push((entity, faction, status));
+        }
+        let edges = self.relationships().await?;
+
+        Ok(FactionMap::new(factions, edges))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use darkforge_data::descriptor::Descriptor;
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use super::*;
+    #[cfg(feature = "store")]
+    use crate::campaign::tests::bootstrap;
+    use crate::faction::Hold;
+
+    fn faction(label: &str) -> Faction {
+        Faction::new(Descriptor::new(Uuid::new_v4(), label.to_owned(), ""), 2, Hold::Strong)
+    }
+
+    fn map() -> (FactionMap, [Uuid; 4]) {
+        let ids = [Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4()];
+        let map = FactionMap::new(
+            ids.iter()
+                .zip(["Bluecoats", "Lampblacks", "Red Sashes", "Dimmer Sisters"])
+                .map(|(&id, label)| (id, faction(label), Status::default())),
+            [
+                Relationship {
+                    from: ids[0],
+                    to: ids[1],
+                    relation: Relation::Ally,
+                },
+                Relationship {
+                    from: ids[1],
+                    to: ids[2],
+                    relation: Relation::Enemy,
+                },
+                Relationship {
+                    from: ids[2],
+                    to: Uuid::new_v4(),
+                    relation: Relation::Ally,
+                },
+            ],
+        );
+
+        (map, ids)
+    }
+
+    #[test]
+    fn should_leave_out_relationships_with_factions_off_the_map() {
+        let (map, _) = map();
+
+        assert_eq!(2, map.edges.len());
+    }
+
+    #[test]
+    fn should_lay_out_allies_closer_than_strangers() {
+        let (mut map, ids) = map();
+
+        map.layout(None, &mut StdRng::seed_from_u64(1956));
+
+        let position = |entity| map.node(entity).expect("should have placed faction").position;
+        for node in &map.nodes {
+            assert!((MARGIN..=1.0 - MARGIN).contains(&node.position.x), "{:?} is off the map", node.position);
+            assert!((MARGIN..=1.0 - MARGIN).contains(&node.position.y), "{:?} is off the map", node.position);
+        }
+        assert!(position(ids[0]).distance(position(ids[1])) < position(ids[0]).distance(position(ids[3])));
+        assert!(position(ids[0]).distance(position(ids[1])) < position(ids[1]).distance(position(ids[2])));
+    }
+
+    #[test]
+    fn should_keep_factions_in_place_when_laid_out_again() {
+        let (mut map, _) = map();
+        map.layout(None, &mut StdRng::seed_from_u64(1956));
+
+        let mut refreshed = map.clone();
+        refreshed.layout(Some(&map), &mut StdRng::seed_from_u64(7));
+
+        for (before, after) in map.nodes.iter().zip(&refreshed.nodes) {
+            assert!(
+                before.position.distance(after.position) < 0.05,
+                "{} moved",
+                before.faction.descriptor().label()
+            );
+        }
+    }
+
+    #[cfg(feature = "store")]
+    #[tokio::test]
+    async fn should_map_campaign_factions() {
+        let mut campaign = bootstrap().await;
+        let factions = campaign.factions().await.expect("should have listed factions");
+
+        let map = campaign.faction_map().await.expect("should have read faction map");
+
+        assert_eq!(
+            factions.iter().map(|(entity, _)| *entity).collect::<Vec<_>>(),
+            map.nodes.iter().map(|node| node.entity).collect::<Vec<_>>()
+        );
+        assert_eq!(campaign.relationships().await.expect("should have listed relationships"), map.edges);
+    }
+}
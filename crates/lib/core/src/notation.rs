@@ -0,0 +1,157 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! Rolls are rendered back to dice notation along with their outcome, e.g. `4d6: [6,5,2,1] → Success`, for chat logs
+//! and UIs to show. Keep and drop rules follow the dice rolled, e.g. `3d6dh1: [3,5] → Partial success`. The text of the outcome is looked up by key through [`Translate`], so that each front end renders
+//! rolls in its player's language from its own localization, falling back to English for the keys it lacks.
+
+use std::{
+    collections::HashMap,
+    fmt::{self, Display, Formatter},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    action::{KeepDrop, Outcome},
+    roll::Roll,
+};
+
+/// Trait for localizations translating the text of rolls by key.
+pub trait Translate {
+    /// Returns the translation of the key, or `None` if there is none.
+    fn translate(&self, key: &str) -> Option<String>;
+}
+
+/// Translations loaded from a localization, e.g. a JSON object mapping keys to text.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct Translations(pub HashMap<String, String>);
+
+impl Translate for Translations {
+    fn translate(&self, key: &str) -> Option<String> {
+        self.0.get(key).cloned()
+    }
+}
+
+/// The English text of rolls, used when a localization lacks a key.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct English;
+
+impl Translate for English {
+    fn translate(&self, key: &str) -> Option<String> {
+        let text = match key {
+            "outcome.failure" => "Failure",
+            "outcome.partial" => "Partial success",
+            "outcome.success" => "Success",
+            "outcome.critical" => "Critical",
+            _ => return None,
+        };
+
+        Some(text.into())
+    }
+}
+
+/// Returns the translation key of the outcome.
+#[must_use]
+pub fn key(outcome: Outcome) -> &'static str {
+    match outcome {
+        Outcome::Failure => "outcome.failure",
+        Outcome::Partial => "outcome.partial",
+        Outcome::Success => "outcome.success",
+        Outcome::Critical => "outcome.critical",
+    }
+}
+
+/// A roll displayed in dice notation, with its outcome translated, see [`Roll::notation`].
+pub struct Notation<'a, T: Translate> {
+    roll: &'a Roll,
+    translations: &'a T,
+}
+
+impl<T: Translate> Display for Notation<'_, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let key = key(self.roll.outcome);
+        let outcome = self
+            .translations
+            .translate(key)
+            .or_else(|| English.translate(key))
+            .unwrap_or_else(|| key.into());
+        let dice = self.roll.dice.iter().map(u8::to_string).collect::<Vec<_>>().join(",");
+        let rolled = self
+            .roll
+            .keep_drop
+            .iter()
+            .fold(self.roll.rating, |rolled, rule| rolled.saturating_add(rule.extra()));
+        let rules = self.roll.keep_drop.iter().map(|rule| rule_notation(*rule)).collect::<String>();
+
+        write!(f, "{rolled}d6{rules}: [{dice}] → {outcome}")
+    }
+}
+
+/// Returns the dice notation of a keep and drop rule, e.g. `dh1` for dropping the highest die.
+fn rule_notation(rule: KeepDrop) -> String {
+    match rule {
+        KeepDrop::DropHighest(dice) => format!("dh{dice}"),
+        KeepDrop::DropLowest(dice) => format!("dl{dice}"),
+        KeepDrop::KeepHighest(dice) => format!("kh{dice}"),
+        KeepDrop::KeepLowest(dice) => format!("kl{dice}"),
+    }
+}
+
+impl Roll {
+    /// Returns the roll displayed in dice notation, its outcome translated by the localization.
+    #[must_use]
+    pub fn notation<'a, T: Translate>(&'a self, translations: &'a T) -> Notation<'a, T> {
+        Notation { roll: self, translations }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use darkforge_data::JSONDeserialize as _;
+    use rstest::rstest;
+
+    use super::*;
+
+    fn roll(rating: u8, dice: &[u8]) -> Roll {
+        Roll {
+            label: "Prowl".into(),
+            rating,
+            dice: dice.to_vec(),
+            keep_drop: Vec::new(),
+            outcome: Outcome::from_dice(dice),
+            context: None,
+            abilities: Vec::new(),
+            fudged: None,
+            manual: false,
+        }
+    }
+
+    #[rstest]
+    #[case::success(roll(4, &[6, 5, 2, 1]), "4d6: [6,5,2,1] → Success")]
+    #[case::critical(roll(2, &[6, 6]), "2d6: [6,6] → Critical")]
+    #[case::zero_rating(roll(0, &[3]), "0d6: [3] → Failure")]
+    #[case::keep_drop(Roll { keep_drop: vec![KeepDrop::DropHighest(1), KeepDrop::KeepLowest(1)], ..roll(2, &[3]) }, "3d6dh1kl1: [3] → Failure")]
+    fn should_render_roll_in_english(#[case] roll: Roll, #[case] expect: &str) {
+        assert_eq!(expect, roll.notation(&English).to_string());
+    }
+
+    #[test]
+    fn should_render_roll_with_translations_falling_back_to_english() {
+        let translations =
+            Translations::from_json(r#"{"outcome.partial": "Réussite partielle"}"#.as_bytes()).expect("should have parsed translations");
+
+        assert_eq!("1d6: [4] → Réussite partielle", roll(1, &[4]).notation(&translations).to_string());
+        assert_eq!("1d6: [1] → Failure", roll(1, &[1]).notation(&translations).to_string());
+    }
+}
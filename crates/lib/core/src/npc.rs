@@ -0,0 +1,546 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! Non-player characters, and the friends, rivals and vice purveyors of player characters.
+//!
+//! NPCs the crew runs up against can be given a quick [`StatBlock`], generated from the drives and flaws of the content
+//! pack. It rolls their side of opposed fortune rolls and suggests the position of the actions taken against them.
+
+use std::cmp::Ordering;
+
+#[cfg(feature = "store")]
+use darkforge_data::store::{Content, World};
+use darkforge_data::{Component, Persisted, descriptor::Descriptor};
+use darkforge_rng::dice::Dice;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    action::FortuneRoll,
+    context::{Position, RollContext},
+    table::Table,
+};
+#[cfg(feature = "store")]
+use crate::{
+    campaign::{Campaign, CampaignError, Result},
+    character::Character,
+    permission::Actor,
+    roster::{ENLISTED, enlistment},
+    table::Row,
+};
+
+/// Content pack category holding NPC templates.
+pub const CATEGORY: &str = "npc";
+/// Content pack category holding the weighted table of NPC drives, see [`Row`].
+pub const DRIVE_CATEGORY: &str = "drive";
+/// Content pack category holding the weighted table of NPC flaws, see [`Row`].
+pub const FLAW_CATEGORY: &str = "flaw";
+
+/// A non-player character.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Npc {
+    descriptor: Descriptor<'static>,
+    #[serde(default)]
+    template: Option<Uuid>,
+}
+
+impl Npc {
+    /// Creates a new NPC.
+    #[must_use]
+    pub fn new(descriptor: Descriptor<'static>) -> Npc {
+        Npc { descriptor, template: None }
+    }
+
+    /// Returns the NPC's descriptor.
+    #[must_use]
+    pub fn descriptor(&self) -> &Descriptor<'static> {
+        &self.descriptor
+    }
+
+    /// Returns the id of the content pack template the NPC was created from, if any.
+    #[must_use]
+    pub fn template(&self) -> Option<Uuid> {
+        self.template
+    }
+}
+
+impl Component for Npc {}
+
+impl Persisted for Npc {
+    const KIND: &'static str = "npc";
+}
+
+/// A drive or a flaw an NPC can be generated with.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Motive {
+    /// The id of the drive or flaw in the content pack.
+    pub id: Uuid,
+    /// The drive or flaw, e.g. "Greedy".
+    pub text: String,
+}
+
+/// The quick stats of an NPC opposing the crew.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct StatBlock {
+    /// The tier of the NPC, as for factions.
+    pub tier: u8,
+    /// The quality of the NPC's gear and training, rolled in their fortune rolls.
+    pub quality: u8,
+    /// What the NPC wants, e.g. "Revenge on the Red Sashes".
+    #[serde(default)]
+    pub drives: Vec<String>,
+    /// What the NPC can be exploited through, e.g. "Greedy". Actions tagged with a flaw are suggested a safer position.
+    #[serde(default)]
+    pub flaws: Vec<String>,
+}
+
+/// The highest quality an NPC can be generated with.
+const MAX_QUALITY: u8 = 5;
+
+impl StatBlock {
+    /// Creates the stats of an NPC of the tier, with a quality matching their tier and no drives or flaws.
+    #[must_use]
+    pub fn new(tier: u8) -> StatBlock {
+        StatBlock {
+            tier,
+            quality: tier,
+            ..StatBlock::default()
+        }
+    }
+
+    /// Generates the stats of an NPC of the tier with `rng`: a quality within one of their tier, a drive and a flaw
+    /// picked from the tables.
+    pub fn generate(tier: u8, drives: &Table<Motive>, flaws: &Table<Motive>, rng: &mut impl Rng) -> StatBlock {
+        let quality = tier.saturating_add_signed(rng.random_range(-1..=1)).min(MAX_QUALITY);
+        let text = |table: &Table<Motive>, rng: &mut _| table.roll(rng).map(|motive| motive.text.clone());
+
+        StatBlock {
+            tier,
+            quality,
+            drives: text(drives, rng).into_iter().collect(),
+            flaws: text(flaws, rng).into_iter().collect(),
+        }
+    }
+
+    /// Rolls a fortune roll of the NPC's quality, e.g. to see how well they fare when no character acts against them.
+    pub fn fortune(&self, dice: &impl Dice) -> FortuneRoll {
+        FortuneRoll::roll(dice, self.quality)
+    }
+
+    /// Rolls opposed fortune rolls of the NPC's quality and of the character's rating. The better outcome wins, then
+    /// the higher die, else the rolls are tied.
+    pub fn oppose(&self, dice: &impl Dice, rating: u8) -> Opposition {
+        let npc = self.fortune(dice);
+        let pc = FortuneRoll::roll(dice, rating);
+        let score = |roll: &FortuneRoll| (roll.outcome, roll.dice.iter().max().copied());
+        let winner = match score(&npc).cmp(&score(&pc)) {
+            Ordering::Greater => Some(Opponent::Npc),
+            Ordering::Less => Some(Opponent::Pc),
+            Ordering::Equal => None,
+        };
+
+        Opposition { npc, pc, winner }
+    }
+
+    /// Suggests the position of an action taken against the NPC by a crew of the tier: one step more dangerous if the
+    /// NPC's tier is higher, one step safer if it is lower, and one step safer again if the action's context is tagged
+    /// with one of the NPC's flaws.
+    #[must_use]
+    pub fn position(&self, crew: u8, context: &RollContext) -> Position {
+        let tier = match self.tier.cmp(&crew) {
+            Ordering::Greater => 1,
+            Ordering::Less => -1,
+            Ordering::Equal => 0,
+        };
+        let flaw = if self.flaws.iter().any(|flaw| context.has_tag(flaw)) { -1 } else { 0 };
+
+        context.position.shift(tier + flaw)
+    }
+}
+
+impl Component for StatBlock {}
+
+impl Persisted for StatBlock {
+    const KIND: &'static str = "npc.stats";
+}
+
+/// A side of an opposed roll.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Opponent {
+    /// The NPC.
+    Npc,
+    /// The player character.
+    Pc,
+}
+
+/// The result of opposed fortune rolls between an NPC and a player character, see [`StatBlock::oppose`].
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Opposition {
+    /// The NPC's roll.
+    pub npc: FortuneRoll,
+    /// The character's roll.
+    pub pc: FortuneRoll,
+    /// The side that won, or `None` on a tie.
+    pub winner: Option<Opponent>,
+}
+
+/// An NPC as defined in a content pack.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct NpcTemplate {
+    /// The id of the template in the content pack.
+    pub id: Uuid,
+    /// The NPC's descriptor.
+    pub descriptor: Descriptor<'static>,
+}
+
+impl NpcTemplate {
+    /// Creates the NPC described by this template.
+    #[must_use]
+    pub fn npc(&self) -> Npc {
+        Npc {
+            descriptor: self.descriptor.clone(),
+            template: Some(self.id),
+        }
+    }
+}
+
+/// How a character is linked to an NPC.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Link {
+    /// A close friend or ally.
+    Friend,
+    /// A rival or enemy.
+    Rival,
+    /// The NPC who provides the character with their vice.
+    Vice,
+}
+
+impl Link {
+    /// All links, in the order they are made on character creation.
+    pub const ALL: [Link; 3] = [Link::Friend, Link::Rival, Link::Vice];
+
+    /// Returns the kind of relationship from the character to the NPC.
+    #[must_use]
+    pub fn kind(self) -> &'static str {
+        match self {
+            Link::Friend => "friend",
+            Link::Rival => "rival",
+            Link::Vice => "vice",
+        }
+    }
+}
+
+/// The NPC chosen for a link.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Choice {
+    /// An NPC already in the campaign.
+    Existing(Uuid),
+    /// An NPC from the content pack, created unless an NPC was already created from the same template.
+    Template(Uuid),
+    /// A brand new NPC.
+    New(Npc),
+}
+
+/// The NPCs chosen on character creation.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Contacts {
+    /// The character's close friend.
+    pub friend: Option<Choice>,
+    /// The character's rival.
+    pub rival: Option<Choice>,
+    /// The character's vice purveyor.
+    pub vice: Option<Choice>,
+}
+
+impl Contacts {
+    /// Returns the choice for the given link, if any.
+    #[must_use]
+    pub fn choice(&self, link: Link) -> Option<&Choice> {
+        match link {
+            Link::Friend => self.friend.as_ref(),
+            Link::Rival => self.rival.as_ref(),
+            Link::Vice => self.vice.as_ref(),
+        }
+    }
+}
+
+#[cfg(feature = "store")]
+impl Campaign {
+    /// Creates a character on the player's roster and links them to the chosen NPCs, creating the NPCs as needed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::MissingNpc`] if a chosen NPC or template does not exist, or another [`CampaignError`]
+    /// if the actor cannot create characters for the player or the store cannot be written.
+    pub async fn create_character(&mut self, actor: Actor, player: Uuid, character: &Character, contacts: &Contacts) -> Result<Uuid> {
+        actor.act_for(player)?;
+
+        let mut created = Vec::new();
+        let mut npcs = Vec::new();
+        for link in Link::ALL {
+            if let Some(choice) = contacts.choice(link) {
+                npcs.push((link, self.npc(choice, &mut created).await?));
+            }
+        }
+
+        let entity = Uuid::new_v4();
+        let mut changeset = enlistment(actor, player, entity, character)?;
+        for (npc, created) in &created {
+            changeset = changeset.spawn(*npc).insert(*npc, created)?;
+        }
+        for (link, npc) in npcs {
+            changeset = changeset.relate(entity, npc, link.kind());
+        }
+        self.apply(changeset.record(Some(entity), ENLISTED, character)?).await?;
+
+        Ok(entity)
+    }
+
+    /// Returns the NPCs the character is linked to.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CampaignError`] if the store cannot be read.
+    pub async fn contacts(&mut self, character: Uuid) -> Result<Vec<(Link, Uuid, Npc)>> {
+        let mut contacts = Vec::new();
+        for link in Link::ALL {
+            for entity in self.store().related(character, link.kind()).await? {
+                if let Some(npc) = self.store().get::<Npc>(entity).await? {
+                    contacts.push((link, entity, npc));
+                }
+            }
+        }
+
+        Ok(contacts)
+    }
+
+    /// Resolves the choice to an NPC entity, adding it to the NPCs `created` if it is new, for the changeset using it to
+    /// spawn.
+    pub(crate) async fn npc(&mut self, choice: &Choice, created: &mut Vec<(Uuid, Npc)>) -> Result<Uuid> {
+        let store = self.store();
+        let npc = match choice {
+            Choice::Existing(entity) => {
+                return match store.get::<Npc>(*entity).await? {
+                    Some(_) => Ok(*entity),
+                    None => Err(CampaignError::MissingNpc(*entity)),
+                };
+            }
+            Choice::Template(id) => {
+                let existing = store
+                    .all::<Npc>()
+                    .await?
+                    .into_iter()
+                    .chain(created.iter().cloned())
+                    .find(|(_, npc)| npc.template == Some(*id));
+                if let Some((entity, _)) = existing {
+                    return Ok(entity);
+                }
+
+                store
+                    .entry::<NpcTemplate>(CATEGORY, *id)
+                    .await?
+                    .ok_or(CampaignError::MissingNpc(*id))?
+                    .npc()
+            }
+            Choice::New(npc) => npc.clone(),
+        };
+
+        let entity = Uuid::new_v4();
+        created.push((entity, npc));
+
+        Ok(entity)
+    }
+}
+
+#[cfg(feature = "store")]
+impl Campaign {
+    /// Returns the weighted tables of the drives and of the flaws of the content pack.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CampaignError`] if the content cannot be read.
+    pub async fn motives(&mut self) -> Result<(Table<Motive>, Table<Motive>)> {
+        let store = self.store();
+        let drives = store.entries::<Row<Motive>>(DRIVE_CATEGORY).await?.into_iter().collect();
+        let flaws = store.entries::<Row<Motive>>(FLAW_CATEGORY).await?.into_iter().collect();
+
+        Ok((drives, flaws))
+    }
+
+    /// Generates the stats of the NPC with `rng`, see [`StatBlock::generate`], and attaches them to the NPC.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::MissingNpc`] if the entity is not an NPC, [`CampaignError::Permission`] if the actor is
+    /// not the GM, or another [`CampaignError`] if the store cannot be read or written.
+    pub async fn generate_stats(&mut self, actor: Actor, npc: Uuid, tier: u8, rng: &mut impl Rng) -> Result<StatBlock> {
+        self.store().get::<Npc>(npc).await?.ok_or(CampaignError::MissingNpc(npc))?;
+        let (drives, flaws) = self.motives().await?;
+        let stats = StatBlock::generate(tier, &drives, &flaws, rng);
+        self.update(actor, npc, &stats).await?;
+
+        Ok(stats)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "store")]
+    use darkforge_data::pack::Pack;
+    #[cfg(feature = "store")]
+    use rand::{SeedableRng, rngs::StdRng};
+    use rstest::rstest;
+
+    use super::*;
+    #[cfg(feature = "store")]
+    use crate::campaign::tests::{DEFAULTS, bootstrap};
+    use crate::{action::tests::Loaded, permission::Actor};
+
+    #[cfg(feature = "store")]
+    #[tokio::test]
+    async fn should_link_contacts_on_character_creation() {
+        let player = Uuid::new_v4();
+        let pack = Pack::open(DEFAULTS).expect("should have opened pack");
+        let templates = pack.load::<NpcTemplate>(CATEGORY).expect("should have loaded npcs");
+        let mut campaign = bootstrap().await;
+
+        let friend = campaign.store().spawn().await.expect("should have spawned friend");
+        let npc = Npc::new(Descriptor::new(Uuid::new_v4(), "Marlane", "A retired cat burglar."));
+        campaign.store().insert(friend, &npc).await.expect("should have inserted friend");
+
+        let contacts = Contacts {
+            friend: Some(Choice::Existing(friend)),
+            rival: Some(Choice::New(Npc::new(Descriptor::new(Uuid::new_v4(), "Chael", "A vicious thug.")))),
+            vice: Some(Choice::Template(templates[0].id)),
+        };
+        let arcy = campaign
+            .create_character(Actor::Player(player), player, &Character::new("Arcy"), &contacts)
+            .await
+            .expect("should have created character");
+        let nyryx = campaign
+            .create_character(
+                Actor::Gm,
+                player,
+                &Character::new("Nyryx"),
+                &Contacts {
+                    vice: Some(Choice::Template(templates[0].id)),
+                    ..Contacts::default()
+                },
+            )
+            .await
+            .expect("should have created character");
+
+        let arcy_contacts = campaign.contacts(arcy).await.expect("should have listed contacts");
+        let labels = arcy_contacts
+            .iter()
+            .map(|(link, _, npc)| (*link, npc.descriptor().label().to_owned()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                (Link::Friend, "Marlane".to_owned()),
+                (Link::Rival, "Chael".to_owned()),
+                (Link::Vice, templates[0].descriptor.label().to_owned()),
+            ],
+            labels
+        );
+
+        let nyryx_contacts = campaign.contacts(nyryx).await.expect("should have listed contacts");
+        assert_eq!(arcy_contacts[2].1, nyryx_contacts[0].1, "should have linked the same vice purveyor");
+    }
+
+    #[cfg(feature = "store")]
+    #[tokio::test]
+    async fn should_fail_to_link_missing_npc() {
+        let mut campaign = bootstrap().await;
+        let missing = Uuid::new_v4();
+
+        let err = campaign
+            .create_character(
+                Actor::Gm,
+                Uuid::new_v4(),
+                &Character::new("Arcy"),
+                &Contacts {
+                    friend: Some(Choice::Existing(missing)),
+                    ..Contacts::default()
+                },
+            )
+            .await
+            .expect_err("should have failed to link missing npc");
+
+        assert!(matches!(err, CampaignError::MissingNpc(id) if id == missing), "unexpected error: {err}");
+        assert_eq!(
+            Vec::<crate::roster::RosterEntry>::new(),
+            campaign.roster(None).await.expect("should have listed roster")
+        );
+    }
+
+    #[cfg(feature = "store")]
+    #[tokio::test]
+    async fn should_generate_stats_of_npc() {
+        let mut campaign = bootstrap().await;
+        let npc = campaign.store().spawn().await.expect("should have spawned npc");
+        let bazso = Npc::new(Descriptor::new(Uuid::new_v4(), "Bazso Baz", "Leader of the Lampblacks."));
+        campaign.store().insert(npc, &bazso).await.expect("should have inserted npc");
+
+        let stats = campaign
+            .generate_stats(Actor::Gm, npc, 2, &mut StdRng::seed_from_u64(1847))
+            .await
+            .expect("should have generated stats");
+        assert_eq!(2, stats.tier);
+        assert!((1..=3).contains(&stats.quality), "unexpected quality {}", stats.quality);
+        assert_eq!((1, 1), (stats.drives.len(), stats.flaws.len()));
+        assert_eq!(Some(stats), campaign.get::<StatBlock>(npc).await.expect("should have read stats"));
+
+        campaign
+            .generate_stats(Actor::Player(Uuid::new_v4()), npc, 2, &mut StdRng::seed_from_u64(1847))
+            .await
+            .expect_err("should have refused player");
+        let err = campaign
+            .generate_stats(Actor::Gm, Uuid::new_v4(), 2, &mut StdRng::seed_from_u64(1847))
+            .await
+            .expect_err("should have refused missing npc");
+        assert!(matches!(err, CampaignError::MissingNpc(_)), "unexpected error: {err}");
+    }
+
+    #[rstest]
+    #[case::npc_wins(&[6, 2, 5], Some(Opponent::Npc))]
+    #[case::pc_wins(&[4, 2, 6], Some(Opponent::Pc))]
+    #[case::higher_die(&[5, 3, 4], Some(Opponent::Npc))]
+    #[case::tied(&[6, 2, 6], None)]
+    fn should_oppose_fortune_rolls(#[case] dice: &[u8], #[case] expect: Option<Opponent>) {
+        let stats = StatBlock::new(2);
+
+        let opposition = stats.oppose(&Loaded::new(dice), 1);
+
+        assert_eq!(expect, opposition.winner);
+        assert_eq!(vec![dice[2]], opposition.pc.dice);
+    }
+
+    #[rstest]
+    #[case::even(2, &[], Position::Risky)]
+    #[case::stronger(1, &[], Position::Desperate)]
+    #[case::weaker(3, &[], Position::Controlled)]
+    #[case::flaw(2, &["greedy"], Position::Controlled)]
+    #[case::stronger_flaw(1, &["greedy"], Position::Risky)]
+    fn should_suggest_position_against_npc(#[case] crew: u8, #[case] tags: &[&str], #[case] expect: Position) {
+        let stats = StatBlock {
+            flaws: vec!["greedy".into()],
+            ..StatBlock::new(2)
+        };
+        let context = tags.iter().fold(RollContext::new(Actor::Gm), |context, tag| context.tag(*tag));
+
+        assert_eq!(expect, stats.position(crew, &context));
+    }
+}
@@ -0,0 +1,305 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! One-shots start from a ready-to-play situation for convention play: a crew preset, a cast of NPCs, a target for the
+//! first score, an opposing faction with one clock and a complication, all drawn from the content pack.
+//!
+//! As with world generation, the situation is fully determined by its seed, so a GM can prepare the same one-shot for
+//! every table of a convention.
+
+use darkforge_data::pack::Pack;
+#[cfg(feature = "store")]
+use darkforge_data::store::World as _;
+#[cfg(feature = "store")]
+use darkforge_data::store::sql::sqlite::SqliteStore;
+use rand::{
+    SeedableRng,
+    seq::{IndexedRandom, SliceRandom},
+};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "store")]
+use uuid::Uuid;
+
+#[cfg(feature = "store")]
+use crate::{
+    campaign::{Campaign, Logged},
+    changeset::{Access, Changeset},
+    npc::Choice,
+    permission::Actor,
+};
+use crate::{
+    campaign::{CampaignError, Result},
+    crew::{Crew, CrewType, Founding},
+    faction::{self, ClockTemplate, FactionTemplate, Status},
+    npc::{self, NpcTemplate},
+    oracle::{self, Complication},
+    table::{Row, Table},
+    template::{self, Bindings},
+};
+
+/// Kind of the journal entry recording how a one-shot was generated.
+pub const GENERATED: &str = "oneshot.generated";
+
+/// The score the crew starts a one-shot on.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Target {
+    /// The kind of operation, one the crew type hunts for, see [`CrewType::operations`].
+    pub operation: String,
+    /// Who or what the operation is against.
+    pub mark: String,
+}
+
+impl Target {
+    /// Returns the target as given when starting the score, e.g. `Burglary against Bazso Baz`.
+    #[must_use]
+    pub fn label(&self) -> String {
+        format!("{} against {}", self.operation, self.mark)
+    }
+}
+
+/// The situation a seed generates for a one-shot from a content pack.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct OneShotPlan {
+    /// The seed the one-shot was generated from.
+    pub seed: u64,
+    /// The crew the players take on, see [`Founding::preset`].
+    pub crew: Crew,
+    /// The NPCs of the cast, the first being the crew's contact.
+    pub npcs: Vec<NpcTemplate>,
+    /// The target of the first score.
+    pub target: Target,
+    /// The faction opposing the crew, hostile and with a single clock.
+    pub faction: FactionTemplate,
+    /// The complication bound to get in the way, if the pack has any.
+    pub complication: Option<String>,
+}
+
+/// Generates the starting situation of a one-shot from a seed.
+///
+/// ```no_run
+/// # #[cfg(feature = "store")]
+/// # async fn generate() -> darkforge::campaign::Result<()> {
+/// use darkforge::oneshot::OneShot;
+/// use darkforge_data::{pack::Pack, store::sql::sqlite::SqliteStore};
+///
+/// let pack = Pack::open("data/defaults")?;
+/// let store = SqliteStore::open("oneshot.db").await?;
+/// let campaign = OneShot::new(1903).bootstrap(store, "Convention table 3", &pack).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OneShot {
+    seed: u64,
+}
+
+impl OneShot {
+    /// The number of NPCs in the cast.
+    pub const NPCS: usize = 3;
+    /// The status of the opposing faction with the crew.
+    pub const HOSTILITY: i8 = -2;
+
+    /// Creates a one-shot generator for the seed.
+    #[must_use]
+    pub fn new(seed: u64) -> OneShot {
+        OneShot { seed }
+    }
+
+    /// Returns the seed of the generator.
+    #[must_use]
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Generates the one-shot from the pack without creating it, the same seed and pack always giving the same plan.
+    /// The cast holds fewer than [`OneShot::NPCS`] NPCs if the pack has fewer, and the mark is the opposing faction if
+    /// no NPC is left for it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::NoFaction`] if the pack holds no faction, or another [`CampaignError`] if its NPCs,
+    /// factions or complications are invalid or the complication drawn is an invalid template.
+    pub fn plan(&self, pack: &Pack) -> Result<OneShotPlan> {
+        let mut rng = ChaCha8Rng::seed_from_u64(self.seed);
+
+        // The crew types are a constant of the rules, so one is always drawn.
+        let kind = CrewType::ALL.choose(&mut rng).copied().unwrap_or(CrewType::Shadows);
+        let crew = Founding::preset(kind).crew;
+        let operation = kind.operations().choose(&mut rng).copied().unwrap_or(&crew.hunting_grounds.operation);
+        let operation = operation.to_owned();
+
+        let mut npcs = pack.load::<NpcTemplate>(npc::CATEGORY)?;
+        npcs.shuffle(&mut rng);
+        let mark = npcs.get(OneShot::NPCS).map(|npc| npc.descriptor.label().to_owned());
+        npcs.truncate(OneShot::NPCS);
+
+        let templates = pack.load::<FactionTemplate>(faction::CATEGORY)?;
+        let mut faction = templates.choose(&mut rng).cloned().ok_or(CampaignError::NoFaction)?;
+        let clock = faction.clocks.choose(&mut rng).cloned().unwrap_or_else(|| ClockTemplate {
+            label: format!("{} strike back", faction.descriptor.label()),
+            segments: 4,
+            filled: 0,
+        });
+        faction.clocks = vec![clock];
+        faction.status = Status::new(OneShot::HOSTILITY);
+
+        let complications = pack.load::<Row<Complication>>(oracle::CATEGORY)?.into_iter().collect::<Table<_>>();
+        let complication = match complications.roll(&mut rng) {
+            Some(complication) => {
+                let bindings = npcs
+                    .iter()
+                    .fold(Bindings::default(), |bindings, npc| bindings.bind(template::NPC, npc.descriptor.label()))
+                    .bind(template::CREW, crew.name.clone())
+                    .bind(template::DISTRICT, crew.hunting_grounds.district.clone())
+                    .bind(template::FACTION, faction.descriptor.label());
+                Some(template::render(&complication.text, &bindings, &mut rng)?)
+            }
+            None => None,
+        };
+
+        Ok(OneShotPlan {
+            seed: self.seed,
+            target: Target {
+                operation,
+                mark: mark.unwrap_or_else(|| faction.descriptor.label().to_owned()),
+            },
+            crew,
+            npcs,
+            faction,
+            complication,
+        })
+    }
+
+    /// Initializes a fresh campaign in the store with the one-shot generated from the pack: the opposing faction and
+    /// its clock, the cast of NPCs and the crew, the first NPC being its contact. The plan is recorded in the journal,
+    /// see [`Campaign::one_shot`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::AlreadyExists`](crate::campaign::CampaignError::AlreadyExists) if the store already
+    /// holds a campaign, or another [`CampaignError`] if the pack is invalid or the
+    /// store cannot be written.
+    #[cfg(feature = "store")]
+    pub async fn bootstrap(&self, store: SqliteStore, name: impl Into<String>, pack: &Pack) -> Result<Campaign> {
+        let plan = self.plan(pack)?;
+        let (mut campaign, _) = Campaign::found(store, name, pack, vec![plan.faction.clone()]).await?;
+        let id = campaign.id();
+
+        let mut founding = Founding::preset(plan.crew.kind);
+        let mut changeset = Changeset::new(Actor::Gm, Access::Gm);
+        for template in &plan.npcs {
+            let entity = Uuid::new_v4();
+            changeset = changeset.spawn(entity).insert(entity, &template.npc())?;
+            if founding.contact.is_none() {
+                founding = founding.contact(Choice::Existing(entity));
+            }
+        }
+        campaign.apply(changeset.record(Some(id), GENERATED, &plan)?).await?;
+        campaign.create_crew(Actor::Gm, &founding).await?;
+
+        Ok(campaign)
+    }
+}
+
+#[cfg(feature = "store")]
+impl Campaign {
+    /// Returns the plan of the one-shot the campaign was generated from, if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CampaignError`] if the journal cannot be read or holds an invalid
+    /// plan.
+    pub async fn one_shot(&mut self) -> Result<Option<OneShotPlan>> {
+        let id = self.id();
+        let Some(recorded) = self.store().journal(Some(id), GENERATED, 1).await?.pop() else {
+            return Ok(None);
+        };
+
+        Ok(Some(recorded.decode::<Logged<OneShotPlan>>()?.data))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "store")]
+    use std::collections::BTreeSet;
+
+    use super::*;
+    use crate::campaign::tests::DEFAULTS;
+    #[cfg(feature = "store")]
+    use crate::{campaign::tests::db, npc::Npc};
+
+    fn pack() -> Pack {
+        Pack::open(DEFAULTS).expect("should have opened pack")
+    }
+
+    #[test]
+    fn should_generate_same_one_shot_from_same_seed() {
+        let pack = pack();
+
+        let first = OneShot::new(1903).plan(&pack).expect("should have planned one-shot");
+        let second = OneShot::new(1903).plan(&pack).expect("should have planned one-shot");
+
+        assert_eq!(first, second);
+        assert_eq!(OneShot::NPCS, first.npcs.len());
+        assert_eq!(1, first.faction.clocks.len());
+        assert_eq!(OneShot::HOSTILITY, first.faction.status.value());
+        assert!(first.complication.is_some());
+    }
+
+    #[test]
+    fn should_generate_playable_one_shots() {
+        let pack = pack();
+
+        for seed in 0..16 {
+            let plan = OneShot::new(seed).plan(&pack).expect("should have planned one-shot");
+
+            assert!(plan.crew.kind.operations().contains(&plan.target.operation.as_str()));
+            assert!(plan.npcs.iter().all(|npc| npc.descriptor.label() != plan.target.mark));
+            assert!(
+                plan.complication.as_ref().is_some_and(|complication| !complication.contains('{')),
+                "unrendered complication {:?}",
+                plan.complication
+            );
+        }
+    }
+
+    #[cfg(feature = "store")]
+    #[tokio::test]
+    async fn should_bootstrap_one_shot() {
+        let pack = pack();
+        let generator = OneShot::new(1903);
+        let plan = generator.plan(&pack).expect("should have planned one-shot");
+        let store = SqliteStore::open(db()).await.expect("should have opened store");
+
+        let mut campaign = generator
+            .bootstrap(store, "Convention table 3", &pack)
+            .await
+            .expect("should have bootstrapped campaign");
+
+        let factions = campaign.factions().await.expect("should have listed factions");
+        assert_eq!(
+            vec![plan.faction.descriptor.label()],
+            factions.iter().map(|(_, faction)| faction.descriptor().label()).collect::<Vec<_>>()
+        );
+        assert_eq!(1, campaign.clocks().await.expect("should have listed clocks").len());
+        let npcs = campaign.store().all::<Npc>().await.expect("should have listed NPCs");
+        assert_eq!(
+            plan.npcs.iter().map(|npc| Some(npc.id)).collect::<BTreeSet<_>>(),
+            npcs.iter().map(|(_, npc)| npc.template()).collect::<BTreeSet<_>>()
+        );
+        let crews = campaign.store().all::<Crew>().await.expect("should have listed crews");
+        assert_eq!(vec![&plan.crew], crews.iter().map(|(_, crew)| crew).collect::<Vec<_>>());
+        assert_eq!(Some(plan), campaign.one_shot().await.expect("should have read one-shot"));
+    }
+}
@@ -0,0 +1,357 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! The oracle answers yes/no questions about the fiction for solo and GM-light play.
+//!
+//! How likely the answer is to be yes decides which weighted table the oracle rolls on. Beyond a plain yes or no, it
+//! may answer "yes, and", "yes, but", "no, but" or "no, and", in which case it also draws a complication from the
+//! content pack's complication table to inspire what happens.
+
+use std::{
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+
+#[cfg(feature = "store")]
+use darkforge_data::store::Content as _;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::table::{Row, Table};
+#[cfg(feature = "store")]
+use crate::{
+    campaign::{Campaign, Result},
+    changeset::{Access, Changeset},
+    permission::Actor,
+};
+
+/// Content pack category holding the weighted complication table, see [`Row`].
+pub const CATEGORY: &str = "complication";
+/// Kind of the journal entries recording the oracle's rulings.
+pub const ASKED: &str = "oracle.asked";
+
+/// Error type for oracle operations.
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum OracleError {
+    /// The likelihood is not one of the known likelihoods.
+    #[error("unknown likelihood {0}, expected one of very_unlikely, unlikely, even, likely or very_likely")]
+    UnknownLikelihood(String),
+}
+
+/// How likely the answer to a question is to be yes.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Likelihood {
+    /// The answer is almost certainly no.
+    VeryUnlikely,
+    /// The answer is probably no.
+    Unlikely,
+    /// The answer could go either way.
+    #[default]
+    Even,
+    /// The answer is probably yes.
+    Likely,
+    /// The answer is almost certainly yes.
+    VeryLikely,
+}
+
+impl Likelihood {
+    /// Every likelihood, from the least to the most likely.
+    pub const ALL: [Likelihood; 5] = [
+        Likelihood::VeryUnlikely,
+        Likelihood::Unlikely,
+        Likelihood::Even,
+        Likelihood::Likely,
+        Likelihood::VeryLikely,
+    ];
+
+    /// Returns the name of the likelihood, as parsed by [`Likelihood::from_str`].
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Likelihood::VeryUnlikely => "very_unlikely",
+            Likelihood::Unlikely => "unlikely",
+            Likelihood::Even => "even",
+            Likelihood::Likely => "likely",
+            Likelihood::VeryLikely => "very_likely",
+        }
+    }
+
+    /// Returns the table the oracle rolls on for the likelihood. Each table weighs to 20, as if rolled on a d20.
+    #[must_use]
+    pub fn table(self) -> Table<Answer> {
+        let weights = match self {
+            Likelihood::VeryUnlikely => [4, 10, 2, 2, 1, 1],
+            Likelihood::Unlikely => [3, 8, 2, 2, 4, 1],
+            Likelihood::Even => [2, 6, 2, 2, 6, 2],
+            Likelihood::Likely => [1, 4, 2, 2, 8, 3],
+            Likelihood::VeryLikely => [1, 1, 2, 2, 10, 4],
+        };
+
+        Answer::ALL
+            .into_iter()
+            .zip(weights)
+            .map(|(answer, weight)| Row::new(weight, answer))
+            .collect()
+    }
+}
+
+impl Display for Likelihood {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Likelihood {
+    type Err = OracleError;
+
+    fn from_str(name: &str) -> std::result::Result<Likelihood, OracleError> {
+        let name = name.trim().to_ascii_lowercase().replace([' ', '-'], "_");
+
+        Likelihood::ALL
+            .into_iter()
+            .find(|likelihood| likelihood.name() == name)
+            .ok_or(OracleError::UnknownLikelihood(name))
+    }
+}
+
+/// The oracle's answer to a question.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Answer {
+    /// No, and things get worse.
+    NoAnd,
+    /// No.
+    No,
+    /// No, but something goes your way.
+    NoBut,
+    /// Yes, but at a cost.
+    YesBut,
+    /// Yes.
+    Yes,
+    /// Yes, and something more goes your way.
+    YesAnd,
+}
+
+impl Answer {
+    /// Every answer, from the worst to the best.
+    pub const ALL: [Answer; 6] = [Answer::NoAnd, Answer::No, Answer::NoBut, Answer::YesBut, Answer::Yes, Answer::YesAnd];
+
+    /// Returns true if the answer is a yes, whatever comes with it.
+    #[must_use]
+    pub fn is_yes(self) -> bool {
+        matches!(self, Answer::YesBut | Answer::Yes | Answer::YesAnd)
+    }
+
+    /// Returns true if the answer comes with an "and" or a "but".
+    #[must_use]
+    pub fn is_complicated(self) -> bool {
+        !matches!(self, Answer::No | Answer::Yes)
+    }
+}
+
+impl Display for Answer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Answer::NoAnd => "No, and...",
+            Answer::No => "No.",
+            Answer::NoBut => "No, but...",
+            Answer::YesBut => "Yes, but...",
+            Answer::Yes => "Yes.",
+            Answer::YesAnd => "Yes, and...",
+        })
+    }
+}
+
+/// A complication the oracle may add to its answers.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Complication {
+    /// The id of the complication in the content pack.
+    pub id: Uuid,
+    /// What happens.
+    pub text: String,
+}
+
+/// Journal entry recorded when the oracle answers a question.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Ruling {
+    /// The question asked.
+    pub question: String,
+    /// How likely the answer was to be yes.
+    pub likelihood: Likelihood,
+    /// The oracle's answer.
+    pub answer: Answer,
+    /// What comes with an "and" or a "but", if the content pack has complications.
+    pub complication: Option<String>,
+}
+
+impl Display for Ruling {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({}) {}", self.question, self.likelihood, self.answer)?;
+        if let Some(complication) = &self.complication {
+            write!(f, " {complication}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Rolls the oracle's answer on the table of the likelihood, then a complication from `complications` if the answer
+/// comes with one.
+pub fn ask(question: impl Into<String>, likelihood: Likelihood, complications: &Table<Complication>, rng: &mut impl Rng) -> Ruling {
+    // Every likelihood table has weighted rows, so the fallback never comes up.
+    let answer = likelihood.table().roll(rng).copied().unwrap_or(Answer::No);
+    let complication = if answer.is_complicated() {
+        complications.roll(rng).map(|complication| complication.text.clone())
+    } else {
+        None
+    };
+
+    Ruling {
+        question: question.into(),
+        likelihood,
+        answer,
+        complication,
+    }
+}
+
+#[cfg(feature = "store")]
+impl Campaign {
+    /// Returns the weighted complication table of the content pack.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CampaignError`](crate::campaign::CampaignError) if the content cannot be read.
+    pub async fn complications(&mut self) -> Result<Table<Complication>> {
+        Ok(self.store().entries::<Row<Complication>>(CATEGORY).await?.into_iter().collect())
+    }
+
+    /// Asks the oracle a question with `rng`, drawing complications from the content pack, and records its ruling in
+    /// the journal. Anyone at the table may ask. Complications are text templates, rendered with the campaign's
+    /// entities, see [`Campaign::bindings`].
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CampaignError`](crate::campaign::CampaignError) if the content cannot be read, a complication is an
+    /// invalid template or the ruling cannot be recorded.
+    pub async fn ask_oracle(&mut self, actor: Actor, question: impl Into<String>, likelihood: Likelihood, rng: &mut impl Rng) -> Result<Ruling> {
+        let complications = self.complications().await?;
+        let mut ruling = ask(question, likelihood, &complications, rng);
+        if let Some(complication) = &ruling.complication {
+            ruling.complication = Some(self.render(complication, None, rng).await?);
+        }
+        self.apply(Changeset::new(actor, Access::Anyone).record(None, ASKED, &ruling)?).await?;
+
+        Ok(ruling)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "store")]
+    use darkforge_data::store::World as _;
+    use rand::{SeedableRng, rngs::StdRng};
+    use rstest::rstest;
+
+    use super::*;
+    #[cfg(feature = "store")]
+    use crate::campaign::{Logged, tests::bootstrap};
+
+    const ASKS: usize = 2000;
+
+    fn yes_share(likelihood: Likelihood) -> usize {
+        let table = likelihood.table();
+        table.rows().iter().filter(|row| row.value.is_yes()).map(|row| row.weight as usize).sum()
+    }
+
+    #[rstest]
+    #[case::very_unlikely(Likelihood::VeryUnlikely, 4)]
+    #[case::unlikely(Likelihood::Unlikely, 7)]
+    #[case::even(Likelihood::Even, 10)]
+    #[case::likely(Likelihood::Likely, 13)]
+    #[case::very_likely(Likelihood::VeryLikely, 16)]
+    fn should_weigh_yes_by_likelihood(#[case] likelihood: Likelihood, #[case] expect: usize) {
+        assert_eq!(20, likelihood.table().total());
+        assert_eq!(expect, yes_share(likelihood));
+    }
+
+    #[rstest]
+    #[case::snake("very_likely", Ok(Likelihood::VeryLikely))]
+    #[case::spaced(" Very Unlikely ", Ok(Likelihood::VeryUnlikely))]
+    #[case::dashed("very-likely", Ok(Likelihood::VeryLikely))]
+    #[case::unknown("certain", Err(OracleError::UnknownLikelihood("certain".into())))]
+    fn should_parse_likelihood(#[case] name: &str, #[case] expect: std::result::Result<Likelihood, OracleError>) {
+        assert_eq!(expect, name.parse());
+    }
+
+    #[test]
+    fn should_only_draw_complication_for_and_or_but() {
+        let complications = Table::new(vec![Row::new(
+            1,
+            Complication {
+                id: Uuid::new_v4(),
+                text: "The Bluecoats take an interest.".into(),
+            },
+        )]);
+        let mut rng = StdRng::seed_from_u64(1901);
+
+        let rulings = (0..ASKS)
+            .map(|_| ask("Is the door locked?", Likelihood::Even, &complications, &mut rng))
+            .collect::<Vec<_>>();
+
+        assert!(
+            rulings
+                .iter()
+                .all(|ruling| ruling.answer.is_complicated() == ruling.complication.is_some())
+        );
+        let yes = rulings.iter().filter(|ruling| ruling.answer.is_yes()).count();
+        assert!((900..1100).contains(&yes), "should have answered yes about half the time, got {yes}");
+    }
+
+    #[cfg(feature = "store")]
+    #[tokio::test]
+    async fn should_record_ruling_with_complication_from_pack() {
+        let mut campaign = bootstrap().await;
+        let complications = campaign.complications().await.expect("should have read complications");
+        let mut rng = StdRng::seed_from_u64(1901);
+
+        let mut rulings = Vec::new();
+        while !rulings.iter().any(|ruling: &Ruling| ruling.complication.is_some()) {
+            rulings.push(
+                campaign
+                    .ask_oracle(Actor::Gm, "Is anyone home?", Likelihood::Likely, &mut rng)
+                    .await
+                    .expect("should have asked the oracle"),
+            );
+        }
+
+        let complication = rulings
+            .last()
+            .and_then(|ruling| ruling.complication.clone())
+            .expect("should have a complication");
+        assert!(!complication.contains('{'), "should have rendered complication: {complication}");
+        assert!(
+            complications
+                .rows()
+                .iter()
+                .filter_map(|row| row.value.text.rsplit('}').next())
+                .any(|tail| complication.ends_with(tail)),
+            "unexpected complication: {complication}"
+        );
+        let journal = campaign.store().journal(None, ASKED, 100).await.expect("should have read journal");
+        assert_eq!(rulings.len(), journal.len());
+        let logged = journal[0].decode::<Logged<Ruling>>().expect("should have decoded ruling");
+        assert_eq!(rulings.last(), Some(&logged.data));
+    }
+}
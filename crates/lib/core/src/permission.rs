@@ -0,0 +1,153 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! Access control for shared campaign state.
+//!
+//! Entities may be owned by a player, usually their character sheet. Players can only mutate the entities they own,
+//! while the GM can mutate anything.
+
+use std::fmt::{self, Display, Formatter};
+
+use darkforge_data::{Component, Persisted};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use uuid::Uuid;
+
+/// Error type for permission checks.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum PermissionError {
+    /// The player attempted to mutate an entity they do not own.
+    #[error("player {player} does not own entity {entity}")]
+    NotOwner {
+        /// The player attempting the mutation.
+        player: Uuid,
+        /// The entity being mutated.
+        entity: Uuid,
+    },
+    /// The player attempted an action reserved to the GM.
+    #[error("player {0} cannot perform an action reserved to the GM")]
+    GmOnly(Uuid),
+    /// The player attempted to act on behalf of another player.
+    #[error("player {player} cannot act on behalf of player {other}")]
+    OtherPlayer {
+        /// The player attempting the action.
+        player: Uuid,
+        /// The player the action was attempted for.
+        other: Uuid,
+    },
+}
+
+/// Someone taking part in a campaign.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Actor {
+    /// The game master, who can mutate anything.
+    Gm,
+    /// A player, identified by their id, who can only mutate what they own.
+    Player(Uuid),
+}
+
+/// Component marking the player owning an entity.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct Owner(pub Uuid);
+
+impl Component for Owner {}
+
+impl Persisted for Owner {
+    const KIND: &'static str = "owner";
+}
+
+impl Actor {
+    /// Checks whether the actor may mutate an entity with the given owner.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PermissionError::NotOwner`] if the actor is a player who does not own the entity.
+    pub fn authorize(self, entity: Uuid, owner: Option<Owner>) -> Result<(), PermissionError> {
+        match (self, owner) {
+            (Actor::Gm, _) => Ok(()),
+            (Actor::Player(player), Some(Owner(owner))) if player == owner => Ok(()),
+            (Actor::Player(player), _) => Err(PermissionError::NotOwner { player, entity }),
+        }
+    }
+
+    /// Checks whether the actor is the GM.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PermissionError::GmOnly`] if the actor is a player.
+    pub fn require_gm(self) -> Result<(), PermissionError> {
+        match self {
+            Actor::Gm => Ok(()),
+            Actor::Player(player) => Err(PermissionError::GmOnly(player)),
+        }
+    }
+
+    /// Checks whether the actor may act on behalf of the given player, which only the GM and the player themselves can.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PermissionError::OtherPlayer`] if the actor is another player.
+    pub fn act_for(self, other: Uuid) -> Result<(), PermissionError> {
+        match self {
+            Actor::Player(player) if player != other => Err(PermissionError::OtherPlayer { player, other }),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Display for Actor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Actor::Gm => write!(f, "GM"),
+            Actor::Player(player) => write!(f, "player {player}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+    use uuid::uuid;
+
+    use super::*;
+
+    const ENTITY: Uuid = uuid!("f4f77f73-e1e8-4289-b77f-73e1e86289e0");
+    const ALICE: Uuid = uuid!("0b1c0b45-7d1f-4c55-9c2f-5e6e5f1b3a10");
+    const BOB: Uuid = uuid!("9a8d6c3e-2f4b-4b1a-8e5d-7c6b5a4f3e2d");
+
+    #[rstest]
+    #[case::gm_unowned(Actor::Gm, None, Ok(()))]
+    #[case::gm_owned(Actor::Gm, Some(Owner(ALICE)), Ok(()))]
+    #[case::owner(Actor::Player(ALICE), Some(Owner(ALICE)), Ok(()))]
+    #[case::other_player(Actor::Player(BOB), Some(Owner(ALICE)), Err(PermissionError::NotOwner { player: BOB, entity: ENTITY }))]
+    #[case::unowned(Actor::Player(BOB), None, Err(PermissionError::NotOwner { player: BOB, entity: ENTITY }))]
+    fn should_authorize_owners_and_gm(#[case] actor: Actor, #[case] owner: Option<Owner>, #[case] expect: Result<(), PermissionError>) {
+        assert_eq!(expect, actor.authorize(ENTITY, owner));
+    }
+
+    #[rstest]
+    #[case::gm(Actor::Gm, Ok(()))]
+    #[case::same_player(Actor::Player(ALICE), Ok(()))]
+    #[case::other_player(Actor::Player(BOB), Err(PermissionError::OtherPlayer { player: BOB, other: ALICE }))]
+    fn should_only_let_gm_and_player_act_for_player(#[case] actor: Actor, #[case] expect: Result<(), PermissionError>) {
+        assert_eq!(expect, actor.act_for(ALICE));
+    }
+
+    #[rstest]
+    #[case::gm(Actor::Gm, Ok(()))]
+    #[case::player(Actor::Player(ALICE), Err(PermissionError::GmOnly(ALICE)))]
+    fn should_reserve_actions_to_gm(#[case] actor: Actor, #[case] expect: Result<(), PermissionError>) {
+        assert_eq!(expect, actor.require_gm());
+    }
+}
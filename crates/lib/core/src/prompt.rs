@@ -0,0 +1,956 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! Actions are played as a series of decisions rather than in a single call. Before rolling, the player chooses whether
+//! to push themselves and whether to accept the devil's bargain offered. After a partial success or a failure, they
+//! choose whether to resist each consequence, and with which attribute.
+//!
+//! The character's harm weighs on the roll too: moderate harm takes a die from the pool unless they push through it,
+//! and severe harm means they cannot act without help. The dice added and taken are listed in the roll's
+//! [`modifiers`](ActionRoll::modifiers), so players see why their pool grew or shrank.
+//!
+//! An [`ActionFlow`] queues these decisions as typed [`Prompt`]s for a UI to render and answer one at a time, each
+//! with the list of its possible [`Answer`]s so that they can be navigated with a controller or a keyboard.
+//! [`Campaign::play_action`] plays the rules in between: it rolls once every decision before the roll is made, then
+//! resolves the action once every consequence has been resisted or accepted.
+//!
+//! Tables playing apart, e.g. through a server, can give each kind of prompt a timeout in their [`PromptTimeouts`], so
+//! a player who disconnects does not stall the action: [`ActionFlow::time_out`] answers the prompt for them once its
+//! time is up, e.g. declining the devil's bargain after a minute.
+//!
+//! Devil's bargains can be drawn from the content pack's [`CATEGORY`] table with [`Campaign::devils_bargain`], naming
+//! the campaign's own entities, see [`template`](crate::template).
+
+#[cfg(feature = "store")]
+use std::mem;
+use std::{
+    collections::{BTreeMap, VecDeque},
+    iter,
+    time::Duration,
+};
+
+#[cfg(feature = "store")]
+use darkforge_data::store::{Content as _, World};
+use darkforge_data::{Component, Persisted};
+#[cfg(feature = "store")]
+use darkforge_rng::dice::Dice;
+#[cfg(feature = "store")]
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::{
+    action::{ActionRoll, Consequence, Delta, Modifier, Proposal},
+    context::Position,
+    effect::{Assessment, Effect, Factors},
+};
+#[cfg(feature = "store")]
+use crate::{
+    action::{Outcome, roll_pool},
+    campaign::{Campaign, CampaignError, Result},
+    changeset::{Access, Changeset},
+    character::Harm,
+    context::RollContext,
+    permission::Actor,
+    table::{Row, Table},
+};
+
+/// Content pack category holding the weighted table of devil's bargains, see [`Row`].
+pub const CATEGORY: &str = "bargain";
+/// The stress a character pays to push themselves.
+pub const PUSH_STRESS: u8 = 2;
+/// Tag of the context of rolls made by characters pushing themselves.
+pub const PUSHED: &str = "pushed";
+/// Tag of the context of rolls made with a devil's bargain.
+pub const BARGAINED: &str = "bargained";
+/// Kind of the journal entries recorded when the prompt timeouts are set.
+pub const TIMEOUTS_SET: &str = "prompt.timeouts_set";
+/// How long players have to answer prompts with [`PromptTimeouts::remote`].
+pub const REMOTE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// A devil's bargain as defined in a content pack.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Bargain {
+    /// The id of the bargain in the content pack.
+    pub id: Uuid,
+    /// What the character gives up for a bonus die, as a text template.
+    pub text: String,
+}
+
+/// Error type for answers that do not fit the pending prompt.
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum PromptError {
+    /// There is no prompt to answer.
+    #[error("no decision is pending")]
+    Settled,
+    /// The answer is not one of the prompt's answers.
+    #[error("{answer:?} does not answer {prompt:?}")]
+    Invalid {
+        /// The pending prompt.
+        prompt: Prompt,
+        /// The answer given.
+        answer: Answer,
+    },
+    /// The default answer cannot answer every prompt of the kind.
+    #[error("{answer:?} cannot answer every {kind:?} prompt")]
+    InvalidDefault {
+        /// The kind of prompt.
+        kind: PromptKind,
+        /// The default answer given.
+        answer: Answer,
+    },
+}
+
+/// The rating of an attribute the character can resist with.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct AttributeRating {
+    /// The attribute's label, e.g. "Prowess".
+    pub label: String,
+    /// The character's rating in the attribute.
+    pub rating: u8,
+}
+
+/// A decision the player must make for the action to go on.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case", tag = "kind")]
+pub enum Prompt {
+    /// Push yourself for an extra die, at the cost of [`PUSH_STRESS`] stress.
+    Push,
+    /// Accept the devil's bargain offered by the GM or another player for an extra die.
+    Bargain {
+        /// What the bargain costs, e.g. "The Bluecoats learn who you are".
+        offer: String,
+    },
+    /// Accept a die less for moderate harm, or decline the penalty and push through it at the cost of [`PUSH_STRESS`]
+    /// stress.
+    Wounded {
+        /// The moderate harm, e.g. "Deep cut".
+        harm: String,
+    },
+    /// Accept a teammate's help, adding a die, as the character cannot act alone while suffering from severe harm.
+    /// Declining abandons the action.
+    NeedHelp {
+        /// The severe harm, e.g. "Broken leg".
+        harm: String,
+    },
+    /// Resist the consequence with one of the character's attributes, or accept it.
+    Resist {
+        /// The consequence.
+        consequence: Consequence,
+        /// The attributes the character can resist with.
+        attributes: Vec<AttributeRating>,
+    },
+}
+
+impl Prompt {
+    /// Returns the kind of the prompt.
+    #[must_use]
+    pub fn kind(&self) -> PromptKind {
+        match self {
+            Prompt::Push => PromptKind::Push,
+            Prompt::Bargain { .. } => PromptKind::Bargain,
+            Prompt::Wounded { .. } => PromptKind::Wounded,
+            Prompt::NeedHelp { .. } => PromptKind::NeedHelp,
+            Prompt::Resist { .. } => PromptKind::Resist,
+        }
+    }
+
+    /// Returns the possible answers to the prompt, in the order to present them.
+    #[must_use]
+    pub fn answers(&self) -> Vec<Answer> {
+        match self {
+            Prompt::Push | Prompt::Bargain { .. } | Prompt::Wounded { .. } | Prompt::NeedHelp { .. } => {
+                vec![Answer::Accept, Answer::Decline]
+            }
+            Prompt::Resist { attributes, .. } => iter::once(Answer::Accept)
+                .chain(attributes.iter().map(|attribute| Answer::Resist(attribute.rating)))
+                .collect(),
+        }
+    }
+}
+
+/// An answer to a prompt.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Answer {
+    /// Push yourself, take the bargain or suffer the consequence in full.
+    Accept,
+    /// Do not push yourself or refuse the bargain.
+    Decline,
+    /// Resist the consequence, rolling the attribute rating.
+    Resist(u8),
+}
+
+/// The kinds of [`Prompt`], to give each its own timeout.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum PromptKind {
+    /// See [`Prompt::Push`].
+    Push,
+    /// See [`Prompt::Bargain`].
+    Bargain,
+    /// See [`Prompt::Wounded`].
+    Wounded,
+    /// See [`Prompt::NeedHelp`].
+    NeedHelp,
+    /// See [`Prompt::Resist`].
+    Resist,
+}
+
+impl PromptKind {
+    /// Every kind of prompt.
+    pub const ALL: [PromptKind; 5] = [
+        PromptKind::Push,
+        PromptKind::Bargain,
+        PromptKind::Wounded,
+        PromptKind::NeedHelp,
+        PromptKind::Resist,
+    ];
+
+    /// Returns whether the answer answers every prompt of the kind. Resisting depends on the attributes offered, so
+    /// only accepting the consequence answers every resistance prompt.
+    #[must_use]
+    pub fn answered_by(self, answer: Answer) -> bool {
+        match self {
+            PromptKind::Resist => answer == Answer::Accept,
+            _ => matches!(answer, Answer::Accept | Answer::Decline),
+        }
+    }
+}
+
+/// How long a player has to answer a kind of prompt, and the answer given for them once the time is up.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Timeout {
+    /// How long the prompt waits for an answer.
+    pub after: Duration,
+    /// The answer given once the time is up.
+    pub default: Answer,
+}
+
+/// The timeouts of the prompts of a campaign, by kind of prompt. Prompts of kinds without one wait for an answer, as
+/// they all do by default.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct PromptTimeouts(BTreeMap<PromptKind, Timeout>);
+
+impl Component for PromptTimeouts {}
+
+impl Persisted for PromptTimeouts {
+    const KIND: &'static str = "prompt_timeouts";
+}
+
+impl PromptTimeouts {
+    /// Returns timeouts suited to players answering from afar: every prompt waits [`REMOTE_TIMEOUT`], after which the
+    /// character does not push themselves, refuses the bargain, takes the die penalty of their harm, goes without help
+    /// and suffers the consequence in full.
+    #[must_use]
+    pub fn remote() -> PromptTimeouts {
+        let timeout = |default| Timeout {
+            after: REMOTE_TIMEOUT,
+            default,
+        };
+
+        PromptTimeouts(BTreeMap::from([
+            (PromptKind::Push, timeout(Answer::Decline)),
+            (PromptKind::Bargain, timeout(Answer::Decline)),
+            (PromptKind::Wounded, timeout(Answer::Accept)),
+            (PromptKind::NeedHelp, timeout(Answer::Decline)),
+            (PromptKind::Resist, timeout(Answer::Accept)),
+        ]))
+    }
+
+    /// Gives prompts of the kind the timeout, replacing the one they had.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PromptError::InvalidDefault`] if the default answer does not answer every prompt of the kind, see
+    /// [`PromptKind::answered_by`].
+    pub fn with(mut self, kind: PromptKind, timeout: Timeout) -> std::result::Result<PromptTimeouts, PromptError> {
+        if !kind.answered_by(timeout.default) {
+            return Err(PromptError::InvalidDefault {
+                kind,
+                answer: timeout.default,
+            });
+        }
+        self.0.insert(kind, timeout);
+
+        Ok(self)
+    }
+
+    /// Lets prompts of the kind wait for an answer.
+    #[must_use]
+    pub fn without(mut self, kind: PromptKind) -> PromptTimeouts {
+        self.0.remove(&kind);
+        self
+    }
+
+    /// Returns the timeout of prompts of the kind, if they have one.
+    #[must_use]
+    pub fn get(&self, kind: PromptKind) -> Option<Timeout> {
+        self.0.get(&kind).copied()
+    }
+}
+
+/// Whether the character's harm was weighed before rolling.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum HarmCheck {
+    /// The harm is weighed the next time the action is played.
+    Pending,
+    /// The prompts for the harm were queued.
+    #[cfg(feature = "store")]
+    Done,
+    /// The player declined help the character needs to act.
+    Stranded,
+}
+
+/// An action being played, along with the decisions still pending.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ActionFlow {
+    character: Uuid,
+    label: String,
+    rating: u8,
+    position: Position,
+    effect: Effect,
+    factors: Factors,
+    consequences: Vec<Consequence>,
+    prompts: VecDeque<Prompt>,
+    pushed: bool,
+    bargained: bool,
+    harm: HarmCheck,
+    modifiers: Vec<Modifier>,
+    strain: u8,
+    roll: Option<ActionRoll>,
+    proposals: Vec<Proposal>,
+    delta: Option<Delta>,
+}
+
+impl ActionFlow {
+    /// Starts a risky action with standard effect for the character, rolling the rating. The player is first prompted
+    /// to push themselves.
+    #[must_use]
+    pub fn new(character: Uuid, label: impl Into<String>, rating: u8) -> ActionFlow {
+        ActionFlow {
+            character,
+            label: label.into(),
+            rating,
+            position: Position::default(),
+            effect: Effect::default(),
+            factors: Factors::default(),
+            consequences: Vec::new(),
+            prompts: VecDeque::from([Prompt::Push]),
+            pushed: false,
+            bargained: false,
+            harm: HarmCheck::Pending,
+            modifiers: Vec::new(),
+            strain: 0,
+            roll: None,
+            proposals: Vec::new(),
+            delta: None,
+        }
+    }
+
+    /// Sets the position the character acts from.
+    #[must_use]
+    pub fn position(mut self, position: Position) -> ActionFlow {
+        self.position = position;
+
+        self
+    }
+
+    /// Sets the effect level of the action.
+    #[must_use]
+    pub fn effect(mut self, effect: Effect) -> ActionFlow {
+        self.effect = effect;
+
+        self
+    }
+
+    /// Sets the factors weighed against the opposition, raising or lowering the effect of the action and the severity of
+    /// its consequences, see [`Factors`].
+    #[must_use]
+    pub fn factors(mut self, factors: Factors) -> ActionFlow {
+        self.factors = factors;
+
+        self
+    }
+
+    /// Sets the effect of the action and the factors weighed from an assessment, see
+    /// [`assess_effect`](crate::effect::assess_effect).
+    #[must_use]
+    pub fn assessment(self, assessment: &Assessment) -> ActionFlow {
+        self.effect(assessment.approach).factors(assessment.factors)
+    }
+
+    /// Offers a devil's bargain, the player being prompted to accept it before rolling.
+    #[must_use]
+    pub fn bargain(mut self, offer: impl Into<String>) -> ActionFlow {
+        self.prompts.push_back(Prompt::Bargain { offer: offer.into() });
+
+        self
+    }
+
+    /// Adds a consequence the GM inflicts on a partial success or a failure, the player being prompted to resist it.
+    #[must_use]
+    pub fn consequence(mut self, consequence: Consequence) -> ActionFlow {
+        self.consequences.push(consequence);
+
+        self
+    }
+
+    /// Returns the character acting.
+    #[must_use]
+    pub fn character(&self) -> Uuid {
+        self.character
+    }
+
+    /// Returns the decision pending, if any.
+    #[must_use]
+    pub fn pending(&self) -> Option<&Prompt> {
+        self.prompts.front()
+    }
+
+    /// Returns the action roll, once made.
+    #[must_use]
+    pub fn roll(&self) -> Option<&ActionRoll> {
+        self.roll.as_ref()
+    }
+
+    /// Returns the changes applied by the action, once resolved.
+    #[must_use]
+    pub fn delta(&self) -> Option<&Delta> {
+        self.delta.as_ref()
+    }
+
+    /// Answers the pending prompt, see [`Prompt::answers`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PromptError::Settled`] if no decision is pending, or [`PromptError::Invalid`] if the answer is not one
+    /// of the pending prompt's answers.
+    pub fn answer(&mut self, answer: Answer) -> std::result::Result<(), PromptError> {
+        let prompt = self.prompts.front().ok_or(PromptError::Settled)?;
+        if !prompt.answers().contains(&answer) {
+            return Err(PromptError::Invalid {
+                prompt: prompt.clone(),
+                answer,
+            });
+        }
+
+        match (self.prompts.pop_front(), answer) {
+            (Some(Prompt::Push), Answer::Accept) => self.pushed = true,
+            (Some(Prompt::Bargain { .. }), Answer::Accept) => self.bargained = true,
+            (Some(Prompt::Wounded { harm }), Answer::Accept) => self.modifiers.push(Modifier::Harm { description: harm }),
+            (Some(Prompt::Wounded { .. }), Answer::Decline) => self.strain += PUSH_STRESS,
+            (Some(Prompt::NeedHelp { .. }), Answer::Accept) => self.modifiers.push(Modifier::Assisted),
+            (Some(Prompt::NeedHelp { .. }), Answer::Decline) => {
+                self.harm = HarmCheck::Stranded;
+                self.prompts.clear();
+            }
+            (Some(Prompt::Resist { consequence, .. }), Answer::Accept) => self.proposals.push(Proposal { consequence, resist: None }),
+            (Some(Prompt::Resist { consequence, .. }), Answer::Resist(rating)) => self.proposals.push(Proposal {
+                consequence,
+                resist: Some(rating),
+            }),
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// Returns the timeout of the pending prompt, if any and if it has one.
+    #[must_use]
+    pub fn timeout(&self, timeouts: &PromptTimeouts) -> Option<Timeout> {
+        timeouts.get(self.pending()?.kind())
+    }
+
+    /// Answers the pending prompt with its default answer if it has waited for at least its timeout, as the player may
+    /// have disconnected. Returns the answer given, if any, after which the action is played on as if the player had.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PromptError::Invalid`] if the default answer does not answer the prompt, which timeouts built with
+    /// [`PromptTimeouts::with`] rule out.
+    pub fn time_out(&mut self, timeouts: &PromptTimeouts, waited: Duration) -> std::result::Result<Option<Answer>, PromptError> {
+        let Some(timeout) = self.timeout(timeouts).filter(|timeout| waited >= timeout.after) else {
+            return Ok(None);
+        };
+        self.answer(timeout.default)?;
+
+        Ok(Some(timeout.default))
+    }
+}
+
+#[cfg(feature = "store")]
+impl Campaign {
+    /// Returns the timeouts of the prompts of the campaign, none if they were never set.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CampaignError`] if the store cannot be read.
+    pub async fn prompt_timeouts(&mut self) -> Result<PromptTimeouts> {
+        let id = self.id();
+
+        Ok(self.store().get::<PromptTimeouts>(id).await?.unwrap_or_default())
+    }
+
+    /// Replaces the timeouts of the prompts of the campaign, e.g. as the table starts playing through a server.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::Permission`] if the actor is not the GM, or another [`CampaignError`] if the store
+    /// cannot be written.
+    pub async fn set_prompt_timeouts(&mut self, actor: Actor, timeouts: &PromptTimeouts) -> Result<()> {
+        actor.require_gm()?;
+
+        let id = self.id();
+        let changeset = Changeset::new(actor, Access::Gm)
+            .insert(id, timeouts)?
+            .record(Some(id), TIMEOUTS_SET, timeouts)?;
+        self.apply(changeset).await?;
+
+        Ok(())
+    }
+
+    /// Returns the weighted table of devil's bargains of the content pack.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CampaignError`] if the content cannot be read.
+    pub async fn bargains(&mut self) -> Result<Table<Bargain>> {
+        Ok(self.store().entries::<Row<Bargain>>(CATEGORY).await?.into_iter().collect())
+    }
+
+    /// Draws a devil's bargain to offer the character from the content pack with `rng`, rendered for the character,
+    /// see [`Campaign::bindings`], to offer with [`ActionFlow::bargain`]. Returns `None` if the pack has no bargains.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::Template`] if the bargain drawn is an invalid template, or another [`CampaignError`] if
+    /// the content cannot be read.
+    pub async fn devils_bargain(&mut self, character: Uuid, rng: &mut impl Rng) -> Result<Option<String>> {
+        let bargains = self.bargains().await?;
+        let Some(bargain) = bargains.roll(rng) else {
+            return Ok(None);
+        };
+
+        Ok(Some(self.render(&bargain.text, Some(character), rng).await?))
+    }
+
+    /// Plays the action as far as the decisions made allow, rolling with `dice`. The first time the action is played,
+    /// the player is prompted about the character's moderate and severe harm, if any. Once every decision before the
+    /// roll is made, it rolls the action, with the dice added and taken by the decisions, and prompts the player to
+    /// resist each consequence on a partial success or a failure. Once every consequence is resisted or accepted, it
+    /// resolves the action, see [`Campaign::resolve_action`], and returns the changes applied.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::Permission`] if the actor does not own the character,
+    /// [`CampaignError::MissingCharacter`] if the character does not exist, [`CampaignError::NeedsHelp`] if the player
+    /// declined help for a character suffering from severe harm, or another [`CampaignError`] if the action cannot be
+    /// resolved.
+    pub async fn play_action(&mut self, actor: Actor, flow: &mut ActionFlow, dice: &impl Dice) -> Result<Option<Delta>> {
+        if flow.harm == HarmCheck::Stranded {
+            return Err(CampaignError::NeedsHelp(flow.character));
+        }
+        if flow.harm == HarmCheck::Pending && flow.roll.is_none() {
+            let harm = self.store().get::<Harm>(flow.character).await?.unwrap_or_default();
+            if let Some(moderate) = harm.moderate.first() {
+                flow.prompts.push_front(Prompt::Wounded { harm: moderate.clone() });
+            }
+            if let Some(severe) = harm.severe {
+                flow.prompts.push_front(Prompt::NeedHelp { harm: severe });
+            }
+            flow.harm = HarmCheck::Done;
+        }
+        if flow.delta.is_some() || !flow.prompts.is_empty() {
+            return Ok(flow.delta.clone());
+        }
+
+        let roll = if let Some(roll) = &flow.roll {
+            roll.clone()
+        } else {
+            self.authorize(actor, flow.character).await?;
+            let roll = roll(actor, flow, dice);
+            if roll.outcome() < Outcome::Success && !flow.consequences.is_empty() {
+                let ratings = self.sheet(flow.character).await?.ratings;
+                let attributes = self
+                    .attributes()
+                    .await?
+                    .iter()
+                    .map(|attribute| AttributeRating {
+                        label: attribute.descriptor.label().into(),
+                        rating: ratings.attribute(attribute),
+                    })
+                    .collect::<Vec<_>>();
+                flow.prompts.extend(flow.consequences.iter().map(|consequence| Prompt::Resist {
+                    consequence: consequence.clone().scaled(flow.factors.severity()),
+                    attributes: attributes.clone(),
+                }));
+            }
+            flow.roll = Some(roll.clone());
+            if !flow.prompts.is_empty() {
+                return Ok(None);
+            }
+            roll
+        };
+
+        let delta = self
+            .resolve_action(actor, flow.character, &roll, mem::take(&mut flow.proposals), dice)
+            .await?;
+        flow.delta = Some(delta.clone());
+
+        Ok(Some(delta))
+    }
+}
+
+/// Rolls the action with the decisions made.
+#[cfg(feature = "store")]
+fn roll(actor: Actor, flow: &ActionFlow, dice: &impl Dice) -> ActionRoll {
+    let effect = flow.factors.effect(flow.effect);
+    let mut context = RollContext::new(actor)
+        .target(flow.character)
+        .position(flow.position)
+        .effect(effect)
+        .tag(flow.label.as_str());
+    if flow.pushed {
+        context = context.tag(PUSHED);
+    }
+    if flow.bargained {
+        context = context.tag(BARGAINED);
+    }
+
+    let modifiers = [(flow.pushed, Modifier::Pushed), (flow.bargained, Modifier::Bargained)]
+        .into_iter()
+        .filter_map(|(applies, modifier)| applies.then_some(modifier))
+        .chain(flow.modifiers.iter().cloned())
+        .collect::<Vec<_>>();
+    let pool = modifiers
+        .iter()
+        .fold(flow.rating, |pool, modifier| pool.saturating_add_signed(modifier.dice()));
+
+    ActionRoll {
+        dice: roll_pool(dice, pool),
+        effect,
+        stress: if flow.pushed { PUSH_STRESS } else { 0 } + flow.strain,
+        context: Some(context),
+        modifiers,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "store")]
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use super::*;
+    #[cfg(feature = "store")]
+    use crate::{
+        action::tests::Loaded,
+        campaign::tests::bootstrap,
+        character::{Character, Stress},
+        consequence::HarmConsequence,
+    };
+
+    #[cfg(feature = "store")]
+    fn harm(level: u8) -> Consequence {
+        Consequence::Harm(HarmConsequence {
+            level,
+            description: "Deep cut".into(),
+        })
+    }
+
+    #[cfg(feature = "store")]
+    #[tokio::test]
+    async fn should_draw_devils_bargain_naming_campaign_entities() {
+        let mut campaign = bootstrap().await;
+        let pc = campaign
+            .enlist(Actor::Gm, Uuid::new_v4(), &Character::new("Arcy"))
+            .await
+            .expect("should have enlisted character");
+        let mut rng = StdRng::seed_from_u64(1950);
+
+        let offers = campaign.bargains().await.expect("should have read bargains").rows().len();
+        let mut bargains = Vec::new();
+        for _ in 0..50 {
+            let bargain = campaign
+                .devils_bargain(pc, &mut rng)
+                .await
+                .expect("should have drawn bargain")
+                .expect("should have bargains in pack");
+            bargains.push(bargain);
+        }
+
+        assert!(offers > 0, "should have bargains in pack");
+        assert!(
+            bargains.iter().all(|bargain| !bargain.contains('{')),
+            "should have rendered bargains: {bargains:?}"
+        );
+        assert!(
+            bargains.iter().any(|bargain| bargain.contains("Arcy")),
+            "should have named character: {bargains:?}"
+        );
+    }
+
+    #[cfg(feature = "store")]
+    #[tokio::test]
+    async fn should_prompt_decisions_before_and_after_rolling() {
+        let mut campaign = bootstrap().await;
+        let pc = campaign
+            .enlist(Actor::Gm, Uuid::new_v4(), &Character::new("Arcy"))
+            .await
+            .expect("should have enlisted character");
+        let mut flow = ActionFlow::new(pc, "Prowl", 1)
+            .bargain("The Bluecoats learn who you are")
+            .consequence(harm(2))
+            .consequence(Consequence::ReducedEffect);
+        let dice = Loaded::new(&[2, 3, 5, 6]);
+
+        assert_eq!(Some(&Prompt::Push), flow.pending());
+        assert_eq!(None, campaign.play_action(Actor::Gm, &mut flow, &dice).await.expect("should have waited"));
+        flow.answer(Answer::Accept).expect("should have pushed");
+        flow.answer(Answer::Decline).expect("should have declined bargain");
+
+        assert_eq!(None, campaign.play_action(Actor::Gm, &mut flow, &dice).await.expect("should have rolled"));
+        assert_eq!(Some(Outcome::Failure), flow.roll().map(ActionRoll::outcome));
+        let Some(Prompt::Resist { consequence, attributes }) = flow.pending() else {
+            panic!("should have prompted resistance, got {:?}", flow.pending());
+        };
+        assert_eq!(&harm(2), consequence);
+        assert!(attributes.iter().all(|attribute| attribute.rating == 0));
+        flow.answer(Answer::Resist(0)).expect("should have resisted harm");
+        flow.answer(Answer::Accept).expect("should have accepted reduced effect");
+
+        let delta = campaign
+            .play_action(Actor::Gm, &mut flow, &dice)
+            .await
+            .expect("should have resolved action")
+            .expect("should have a delta");
+        assert_eq!(Stress::new(3), delta.stress);
+        assert_eq!(vec![1], delta.harm);
+        assert_eq!(Effect::Limited, delta.effect);
+        assert!(
+            delta
+                .context
+                .is_some_and(|context| context.has_tag(PUSHED) && !context.has_tag(BARGAINED))
+        );
+        assert_eq!(Err(PromptError::Settled), flow.answer(Answer::Accept));
+    }
+
+    #[cfg(feature = "store")]
+    #[tokio::test]
+    async fn should_take_die_for_moderate_harm_unless_pushed_through() {
+        let mut campaign = bootstrap().await;
+        let pc = campaign
+            .enlist(Actor::Gm, Uuid::new_v4(), &Character::new("Arcy"))
+            .await
+            .expect("should have enlisted character");
+        let wounded = Harm {
+            moderate: vec!["Deep cut".into()],
+            ..Harm::default()
+        };
+        campaign.update(Actor::Gm, pc, &wounded).await.expect("should have harmed character");
+
+        let mut flow = ActionFlow::new(pc, "Skirmish", 2);
+        assert_eq!(
+            None,
+            campaign
+                .play_action(Actor::Gm, &mut flow, &Loaded::new(&[]))
+                .await
+                .expect("should have waited")
+        );
+        assert_eq!(Some(&Prompt::Wounded { harm: "Deep cut".into() }), flow.pending());
+        flow.answer(Answer::Accept).expect("should have accepted penalty");
+        flow.answer(Answer::Accept).expect("should have pushed");
+        campaign
+            .play_action(Actor::Gm, &mut flow, &Loaded::new(&[6, 6, 6]))
+            .await
+            .expect("should have resolved action");
+        let roll = flow.roll().expect("should have rolled");
+        assert_eq!(vec![6, 6], roll.dice);
+        assert_eq!(
+            vec!["pushed: +1d", "Deep cut: -1d"],
+            roll.modifiers.iter().map(ToString::to_string).collect::<Vec<_>>()
+        );
+
+        let mut flow = ActionFlow::new(pc, "Skirmish", 2);
+        campaign
+            .play_action(Actor::Gm, &mut flow, &Loaded::new(&[]))
+            .await
+            .expect("should have waited");
+        flow.answer(Answer::Decline).expect("should have pushed through harm");
+        flow.answer(Answer::Decline).expect("should have declined to push");
+        let delta = campaign
+            .play_action(Actor::Gm, &mut flow, &Loaded::new(&[6, 6]))
+            .await
+            .expect("should have resolved action")
+            .expect("should have a delta");
+        assert_eq!(Some(2), flow.roll().map(|roll| roll.dice.len()));
+        assert_eq!(PUSH_STRESS, delta.paid);
+    }
+
+    #[cfg(feature = "store")]
+    #[tokio::test]
+    async fn should_need_help_with_severe_harm() {
+        let mut campaign = bootstrap().await;
+        let pc = campaign
+            .enlist(Actor::Gm, Uuid::new_v4(), &Character::new("Arcy"))
+            .await
+            .expect("should have enlisted character");
+        let broken = Harm {
+            severe: Some("Broken leg".into()),
+            ..Harm::default()
+        };
+        campaign.update(Actor::Gm, pc, &broken).await.expect("should have harmed character");
+
+        let mut flow = ActionFlow::new(pc, "Prowl", 1);
+        campaign
+            .play_action(Actor::Gm, &mut flow, &Loaded::new(&[]))
+            .await
+            .expect("should have waited");
+        assert_eq!(Some(&Prompt::NeedHelp { harm: "Broken leg".into() }), flow.pending());
+        flow.answer(Answer::Decline).expect("should have declined help");
+        assert_eq!(None, flow.pending());
+        let err = campaign
+            .play_action(Actor::Gm, &mut flow, &Loaded::new(&[6]))
+            .await
+            .expect_err("should have refused to act alone");
+        assert!(matches!(err, CampaignError::NeedsHelp(entity) if entity == pc), "unexpected error: {err}");
+
+        let mut flow = ActionFlow::new(pc, "Prowl", 1);
+        campaign
+            .play_action(Actor::Gm, &mut flow, &Loaded::new(&[]))
+            .await
+            .expect("should have waited");
+        flow.answer(Answer::Accept).expect("should have accepted help");
+        flow.answer(Answer::Decline).expect("should have declined to push");
+        campaign
+            .play_action(Actor::Gm, &mut flow, &Loaded::new(&[4, 6]))
+            .await
+            .expect("should have resolved action");
+        let roll = flow.roll().expect("should have rolled");
+        assert_eq!((vec![4, 6], vec![Modifier::Assisted]), (roll.dice.clone(), roll.modifiers.clone()));
+    }
+
+    #[cfg(feature = "store")]
+    #[tokio::test]
+    async fn should_resolve_success_without_prompting_resistance() {
+        let mut campaign = bootstrap().await;
+        let pc = campaign
+            .enlist(Actor::Gm, Uuid::new_v4(), &Character::new("Arcy"))
+            .await
+            .expect("should have enlisted character");
+        let mut flow = ActionFlow::new(pc, "Finesse", 2).consequence(harm(3));
+        flow.answer(Answer::Decline).expect("should have declined to push");
+
+        let delta = campaign
+            .play_action(Actor::Gm, &mut flow, &Loaded::new(&[6, 2]))
+            .await
+            .expect("should have resolved action")
+            .expect("should have a delta");
+
+        assert_eq!(Outcome::Success, delta.outcome);
+        assert_eq!(Vec::<u8>::new(), delta.harm);
+        assert_eq!(Stress::default(), delta.stress);
+    }
+
+    #[cfg(feature = "store")]
+    #[tokio::test]
+    async fn should_scale_effect_and_consequences_against_higher_tier() {
+        let mut campaign = bootstrap().await;
+        let pc = campaign
+            .enlist(Actor::Gm, Uuid::new_v4(), &Character::new("Arcy"))
+            .await
+            .expect("should have enlisted character");
+        let mut flow = ActionFlow::new(pc, "Skirmish", 1).factors(Factors::tiers(0, 2)).consequence(harm(2));
+        flow.answer(Answer::Decline).expect("should have declined to push");
+
+        assert_eq!(
+            None,
+            campaign
+                .play_action(Actor::Gm, &mut flow, &Loaded::new(&[4]))
+                .await
+                .expect("should have rolled")
+        );
+        assert_eq!(Some(Effect::Limited), flow.roll().map(|roll| roll.effect));
+        assert!(matches!(flow.pending(), Some(Prompt::Resist { consequence, .. }) if *consequence == harm(3)));
+    }
+
+    #[test]
+    fn should_reject_answer_not_fitting_prompt() {
+        let mut flow = ActionFlow::new(Uuid::new_v4(), "Prowl", 1);
+
+        assert_eq!(
+            Err(PromptError::Invalid {
+                prompt: Prompt::Push,
+                answer: Answer::Resist(2)
+            }),
+            flow.answer(Answer::Resist(2))
+        );
+        assert_eq!(Some(&Prompt::Push), flow.pending());
+    }
+
+    #[test]
+    fn should_answer_prompt_for_player_once_timed_out() {
+        let mut flow = ActionFlow::new(Uuid::new_v4(), "Sway", 2).bargain("The Bluecoats learn who you are");
+        let timeouts = PromptTimeouts::remote().without(PromptKind::Push);
+
+        assert_eq!(Ok(None), flow.time_out(&timeouts, Duration::from_secs(3600)));
+        flow.answer(Answer::Accept).expect("should have pushed");
+
+        assert_eq!(Ok(None), flow.time_out(&timeouts, Duration::from_secs(59)));
+        assert_eq!(Ok(Some(Answer::Decline)), flow.time_out(&timeouts, REMOTE_TIMEOUT));
+        assert!(flow.pushed && !flow.bargained);
+        assert_eq!(None, flow.pending());
+    }
+
+    #[test]
+    fn should_reject_default_answer_not_answering_every_prompt() {
+        let timeout = |default| Timeout {
+            after: REMOTE_TIMEOUT,
+            default,
+        };
+
+        assert_eq!(
+            Err(PromptError::InvalidDefault {
+                kind: PromptKind::Resist,
+                answer: Answer::Decline
+            }),
+            PromptTimeouts::default().with(PromptKind::Resist, timeout(Answer::Decline))
+        );
+        assert!(PromptTimeouts::default().with(PromptKind::Push, timeout(Answer::Resist(1))).is_err());
+    }
+
+    #[cfg(feature = "store")]
+    #[tokio::test]
+    async fn should_let_only_gm_set_prompt_timeouts() {
+        let mut campaign = bootstrap().await;
+        assert_eq!(
+            PromptTimeouts::default(),
+            campaign.prompt_timeouts().await.expect("should have read timeouts")
+        );
+
+        let err = campaign
+            .set_prompt_timeouts(Actor::Player(Uuid::new_v4()), &PromptTimeouts::remote())
+            .await
+            .expect_err("should have refused player");
+        assert!(matches!(err, CampaignError::Permission(_)), "unexpected error {err:?}");
+
+        campaign
+            .set_prompt_timeouts(Actor::Gm, &PromptTimeouts::remote())
+            .await
+            .expect("should have set timeouts");
+        assert_eq!(
+            PromptTimeouts::remote(),
+            campaign.prompt_timeouts().await.expect("should have read timeouts")
+        );
+    }
+}
@@ -0,0 +1,259 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! Retention policies bound how much of the journal is kept in detail. Each policy keeps the entries of one kind
+//! recorded during the latest sessions, older ones being pruned and aggregated into a summary for each entity they were
+//! about, so that the journal no longer grows with every roll while the campaign's history can still be counted.
+//!
+//! The journal is pruned whenever a session starts, or on demand with [`Campaign::prune`].
+use std::collections::BTreeMap;
+
+#[cfg(feature = "store")]
+use darkforge_data::store::{Recorded, World as _};
+use darkforge_data::{Component, Persisted};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[cfg(feature = "store")]
+use crate::{
+    campaign::{Campaign, Result},
+    changeset::{Access, Changeset},
+    permission::Actor,
+    session::STARTED,
+};
+
+/// Kind of the journal entries recorded when the retention policies change.
+pub const RETAINED: &str = "retention.set";
+/// Kind of the journal entries summarizing the entries pruned.
+pub const PRUNED: &str = "journal.pruned";
+
+/// The retention policies of the campaign: how many sessions of journal entries to keep in detail, by kind of entry.
+/// Kinds without a policy are kept forever.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Retention {
+    /// The number of sessions kept, by kind of entry.
+    pub sessions: BTreeMap<String, u32>,
+}
+
+impl Retention {
+    /// Keeps the entries of the kind recorded during the latest sessions only, the current one always being kept.
+    #[must_use]
+    pub fn keep(mut self, kind: &str, sessions: u32) -> Retention {
+        self.sessions.insert(kind.into(), sessions.max(1));
+
+        self
+    }
+}
+
+impl Component for Retention {}
+
+impl Persisted for Retention {
+    const KIND: &'static str = "retention";
+}
+
+/// Journal entry summarizing the entries of a kind pruned about an entity.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Summary {
+    /// The kind of entries pruned.
+    pub kind: String,
+    /// The entity the entries were about, if any.
+    pub entity: Option<Uuid>,
+    /// The number of entries pruned.
+    pub entries: u32,
+    /// The sequence number of the first entry pruned.
+    pub first: i64,
+    /// The sequence number of the last entry pruned.
+    pub last: i64,
+    /// When the first entry was recorded, in seconds since the Unix epoch.
+    pub first_at: i64,
+    /// When the last entry was recorded, in seconds since the Unix epoch.
+    pub last_at: i64,
+}
+
+#[cfg(feature = "store")]
+impl Summary {
+    /// Starts a summary with the entry.
+    fn of(recorded: &Recorded) -> Summary {
+        Summary {
+            kind: recorded.kind.clone(),
+            entity: recorded.entity,
+            entries: 1,
+            first: recorded.seq,
+            last: recorded.seq,
+            first_at: recorded.recorded_at,
+            last_at: recorded.recorded_at,
+        }
+    }
+
+    /// Adds an entry recorded after those already summarized.
+    fn add(&mut self, recorded: &Recorded) {
+        self.entries += 1;
+        self.last = recorded.seq;
+        self.last_at = recorded.recorded_at;
+    }
+}
+
+#[cfg(feature = "store")]
+impl Campaign {
+    /// Returns the retention policies of the campaign.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CampaignError`](crate::campaign::CampaignError) if the store cannot be read.
+    pub async fn retention(&mut self) -> Result<Retention> {
+        let id = self.id();
+
+        Ok(self.store().get::<Retention>(id).await?.unwrap_or_default())
+    }
+
+    /// Replaces the retention policies of the campaign, applied the next time the journal is pruned.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::Permission`](crate::campaign::CampaignError::Permission) if the actor is not the GM, or
+    /// another [`CampaignError`](crate::campaign::CampaignError) if the store cannot be written.
+    pub async fn set_retention(&mut self, actor: Actor, retention: &Retention) -> Result<()> {
+        actor.require_gm()?;
+
+        let id = self.id();
+        let changeset = Changeset::new(actor, Access::Gm)
+            .insert(id, retention)?
+            .record(Some(id), RETAINED, retention)?;
+        self.apply(changeset).await?;
+
+        Ok(())
+    }
+
+    /// Prunes the journal entries older than the sessions their retention policy keeps, recording a summary of them for
+    /// each entity they were about, in a single transaction. Session markers and summaries are never pruned.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::Permission`](crate::campaign::CampaignError::Permission) if the actor is not the GM, or
+    /// another [`CampaignError`](crate::campaign::CampaignError) if the store cannot be read or written, in which case
+    /// nothing is pruned.
+    pub async fn prune(&mut self, actor: Actor) -> Result<Vec<Summary>> {
+        actor.require_gm()?;
+
+        let Retention { sessions: policies } = self.retention().await?;
+        let sessions = self.sessions().await?;
+        let mut changesets = Vec::new();
+        let mut summaries = Vec::new();
+        for (kind, keep) in policies {
+            if kind == STARTED || kind == PRUNED {
+                continue;
+            }
+            let Some(oldest) = sessions.len().checked_sub(usize::try_from(keep).unwrap_or(usize::MAX)) else {
+                continue;
+            };
+            let kept = sessions[oldest].seq;
+
+            let mut pruned = BTreeMap::<Option<Uuid>, Summary>::new();
+            for recorded in self.store().journal(None, &kind, u32::MAX).await?.iter().rev() {
+                if recorded.seq >= kept {
+                    break;
+                }
+                pruned
+                    .entry(recorded.entity)
+                    .and_modify(|summary| summary.add(recorded))
+                    .or_insert_with(|| Summary::of(recorded));
+            }
+
+            for (index, summary) in pruned.into_values().enumerate() {
+                let changeset = Changeset::new(actor, Access::Gm);
+                let changeset = if index == 0 { changeset.prune(&kind, kept) } else { changeset };
+                changesets.push(changeset.record(summary.entity, PRUNED, &summary)?);
+                summaries.push(summary);
+            }
+        }
+        if !changesets.is_empty() {
+            self.apply_all(changesets).await?;
+        }
+
+        Ok(summaries)
+    }
+}
+
+#[cfg(all(test, feature = "store"))]
+mod tests {
+    use darkforge_data::store::Verify as _;
+
+    use super::*;
+    use crate::{
+        action::tests::Loaded,
+        campaign::{CampaignError, Logged, tests::bootstrap},
+        roll::ROLLED,
+    };
+
+    #[tokio::test]
+    async fn should_summarize_rolls_older_than_kept_sessions_when_session_starts() {
+        let mut campaign = bootstrap().await;
+        campaign
+            .set_retention(Actor::Gm, &Retention::default().keep(ROLLED, 2))
+            .await
+            .expect("should have set retention");
+        campaign
+            .start_session(Actor::Gm, "Lost in the Fog")
+            .await
+            .expect("should have started session");
+        campaign
+            .roll(Actor::Gm, None, "Fortune", 2, &Loaded::new(&[3, 5]))
+            .await
+            .expect("should have rolled");
+        campaign
+            .roll(Actor::Gm, None, "Prowl", 1, &Loaded::new(&[6]))
+            .await
+            .expect("should have rolled");
+        campaign
+            .roll(Actor::Gm, None, "Engagement", 1, &Loaded::new(&[2]))
+            .await
+            .expect("should have rolled");
+        campaign.start_session(Actor::Gm, "The Heist").await.expect("should have started session");
+        campaign
+            .roll(Actor::Gm, None, "Fortune", 1, &Loaded::new(&[4]))
+            .await
+            .expect("should have rolled");
+        assert_eq!(
+            4,
+            campaign.store().journal(None, ROLLED, 10).await.expect("should have read journal").len()
+        );
+
+        campaign.start_session(Actor::Gm, "Fallout").await.expect("should have started session");
+
+        let rolls = campaign.store().journal(None, ROLLED, 10).await.expect("should have read journal");
+        assert_eq!(1, rolls.len());
+        let summaries = campaign.store().journal(None, PRUNED, 10).await.expect("should have read journal");
+        let Logged { data, .. } = summaries[0].decode::<Logged<Summary>>().expect("should have decoded summary");
+        assert_eq!((ROLLED, None, 3), (data.kind.as_str(), data.entity, data.entries));
+        assert_eq!(3, campaign.sessions().await.expect("should have listed sessions").len());
+        let report = campaign.store().verify().await.expect("should have verified store");
+        assert!(report.is_clean(), "unexpected issues: {:?}", report.issues);
+
+        let pruned = campaign.prune(Actor::Gm).await.expect("should have pruned journal");
+        assert_eq!(Vec::<Summary>::new(), pruned);
+    }
+
+    #[tokio::test]
+    async fn should_only_let_gm_prune_journal() {
+        let mut campaign = bootstrap().await;
+        let player = Actor::Player(Uuid::new_v4());
+
+        let err = campaign
+            .set_retention(player, &Retention::default().keep(ROLLED, 1))
+            .await
+            .expect_err("should have failed to set retention");
+        assert!(matches!(err, CampaignError::Permission(_)));
+
+        let err = campaign.prune(player).await.expect_err("should have failed to prune journal");
+        assert!(matches!(err, CampaignError::Permission(_)));
+    }
+}
@@ -0,0 +1,709 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! Free dice rolls, recorded in the journal so they can be listed in a roll history.
+//!
+//! Tables that allow the GM to fudge can make a [`PendingRoll`] first, overrule its dice, then commit it. The journal
+//! keeps the natural dice along with the ones that stood, so the fudge stays visible in the roll history.
+//!
+//! Tables rolling physical dice can enter the results with [`Campaign::record_manual_roll`] instead, which keeps and
+//! journals them as it would digital dice, flagged as manual.
+
+#[cfg(feature = "store")]
+use darkforge_data::store::World;
+use darkforge_rng::dice::Dice;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::{
+    action::{KeepDrop, Outcome, pool_size, roll_pool_with},
+    context::RollContext,
+    permission::{Actor, PermissionError},
+};
+#[cfg(feature = "store")]
+use crate::{
+    campaign::{Campaign, Logged, Result},
+    changeset::{Access, Changeset},
+    character::Triggered,
+    setup::HouseRule,
+};
+
+/// Kind of the journal entries recording rolls.
+pub const ROLLED: &str = "dice.rolled";
+
+/// Error type for overruling pending rolls.
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum FudgeError {
+    /// Only the GM may overrule rolls.
+    #[error(transparent)]
+    Permission(#[from] PermissionError),
+    /// The dice replacing the roll's are not as many or are not all d6 results.
+    #[error("cannot replace {expected} dice with {found:?}")]
+    Dice {
+        /// The number of dice kept by the roll.
+        expected: usize,
+        /// The dice given.
+        found: Vec<u8>,
+    },
+}
+
+/// Error type for entering the results of physical dice.
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum ManualRollError {
+    /// The dice entered are not as many as the pool rolled for the rating.
+    #[error("a rating of {rating} rolls {expected} dice, not {found}")]
+    Count {
+        /// The rating rolled.
+        rating: u8,
+        /// The number of dice in the pool.
+        expected: usize,
+        /// The number of dice entered.
+        found: usize,
+    },
+    /// A die entered is not a d6 result.
+    #[error("{0} is not a d6 result")]
+    Face(u8),
+}
+
+/// The natural result of a roll the GM overruled.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Fudged {
+    /// The dice kept, as rolled.
+    pub dice: Vec<u8>,
+    /// The outcome of the dice as rolled.
+    pub outcome: Outcome,
+}
+
+/// A roll of a dice pool.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Roll {
+    /// What the roll is for, e.g. the action or the clock rolled for.
+    pub label: String,
+    /// The rating rolled, the number of dice in the pool.
+    pub rating: u8,
+    /// The dice kept.
+    pub dice: Vec<u8>,
+    /// The keep and drop rules applied to the dice rolled, in order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keep_drop: Vec<KeepDrop>,
+    /// The outcome of the roll.
+    pub outcome: Outcome,
+    /// The fictional context of the roll, if given.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<RollContext>,
+    /// The special abilities of the character the context triggered, whose bonuses the rating and effect include.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub abilities: Vec<Uuid>,
+    /// The natural result, if the GM overruled the roll.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fudged: Option<Fudged>,
+    /// Whether the dice were rolled at the table and entered by hand rather than rolled digitally.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub manual: bool,
+}
+
+impl Roll {
+    /// Rolls a dice pool for the rating without recording it, e.g. for games played without a campaign store.
+    #[must_use]
+    pub fn new(label: impl Into<String>, rating: u8, dice: &impl Dice) -> Roll {
+        Roll::keeping(label, rating, &[], dice)
+    }
+
+    /// Rolls a dice pool for the rating as [`Roll::new`] does, keeping the dice chosen by the keep and drop rules, see
+    /// [`roll_pool_with`].
+    #[must_use]
+    pub fn keeping(label: impl Into<String>, rating: u8, keep_drop: &[KeepDrop], dice: &impl Dice) -> Roll {
+        let kept = roll_pool_with(dice, rating, keep_drop);
+
+        Roll {
+            label: label.into(),
+            rating,
+            outcome: Outcome::from_dice(&kept),
+            dice: kept,
+            keep_drop: keep_drop.to_vec(),
+            context: None,
+            abilities: Vec::new(),
+            fudged: None,
+            manual: false,
+        }
+    }
+
+    /// Makes a roll from the results of physical dice, in the order rolled, keeping them by the keep and drop rules as
+    /// [`Roll::keeping`] does, without recording it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ManualRollError::Count`] if `dice` are not as many as the pool rolled for the rating and rules, see
+    /// [`pool_size`], or [`ManualRollError::Face`] if a die is not between 1 and 6.
+    pub fn manual(label: impl Into<String>, rating: u8, keep_drop: &[KeepDrop], dice: &[u8]) -> std::result::Result<Roll, ManualRollError> {
+        let expected = pool_size(rating, keep_drop);
+        if dice.len() != expected {
+            return Err(ManualRollError::Count {
+                rating,
+                expected,
+                found: dice.len(),
+            });
+        }
+        if let Some(&face) = dice.iter().find(|die| !(1..=6).contains(*die)) {
+            return Err(ManualRollError::Face(face));
+        }
+
+        Ok(Roll {
+            manual: true,
+            ..Roll::keeping(label, rating, keep_drop, &Entered(dice))
+        })
+    }
+}
+
+/// Dice that give back results entered by hand, so they go through the same keep and drop rules as digital dice.
+struct Entered<'a>(&'a [u8]);
+
+impl Dice for Entered<'_> {
+    fn roll(&self) -> u8 {
+        self.0.first().copied().unwrap_or(1)
+    }
+
+    fn roll_pool(&self, pool: usize) -> Vec<u8> {
+        self.0.iter().copied().take(pool).collect()
+    }
+
+    fn sides(&self) -> u8 {
+        6
+    }
+}
+
+/// A roll made but not yet recorded, which the GM may overrule before it is committed with
+/// [`Campaign::commit_roll`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PendingRoll {
+    actor: Actor,
+    character: Option<Uuid>,
+    roll: Roll,
+}
+
+#[cfg(feature = "store")]
+impl PendingRoll {
+    fn new(
+        actor: Actor, character: Option<Uuid>, label: String, rating: u8, keep_drop: &[KeepDrop], context: Option<RollContext>, dice: &impl Dice,
+    ) -> Self {
+        Self {
+            actor,
+            character,
+            roll: Roll {
+                context,
+                ..Roll::keeping(label, rating, keep_drop, dice)
+            },
+        }
+    }
+
+    /// Returns the roll as it stands.
+    #[must_use]
+    pub fn roll(&self) -> &Roll {
+        &self.roll
+    }
+
+    /// Replaces the dice kept by the roll, keeping the natural result to record along with it. Overruling the roll
+    /// again keeps the first natural result.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FudgeError::Permission`] if the actor is not the GM, or [`FudgeError::Dice`] if `dice` are not as many
+    /// as the dice kept or are not all between 1 and 6.
+    pub fn overrule(&mut self, actor: Actor, dice: Vec<u8>) -> std::result::Result<(), FudgeError> {
+        actor.require_gm()?;
+        if dice.len() != self.roll.dice.len() || dice.iter().any(|die| !(1..=6).contains(die)) {
+            return Err(FudgeError::Dice {
+                expected: self.roll.dice.len(),
+                found: dice,
+            });
+        }
+
+        let outcome = Outcome::from_dice(&dice);
+        let natural = Fudged {
+            dice: std::mem::replace(&mut self.roll.dice, dice),
+            outcome: std::mem::replace(&mut self.roll.outcome, outcome),
+        };
+        self.roll.fudged.get_or_insert(natural);
+
+        Ok(())
+    }
+}
+
+/// A roll to make as part of a batch.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RollRequest {
+    /// The character rolling, if any.
+    pub character: Option<Uuid>,
+    /// What the roll is for.
+    pub label: String,
+    /// The rating rolled.
+    pub rating: u8,
+}
+
+/// A roll read back from the journal.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RecordedRoll {
+    /// The sequence number of the journal entry.
+    pub seq: i64,
+    /// The character who rolled, if any.
+    pub character: Option<Uuid>,
+    /// Who made the roll.
+    pub actor: Actor,
+    /// The roll.
+    pub roll: Roll,
+    /// When the roll was made, in seconds since the Unix epoch.
+    pub recorded_at: i64,
+}
+
+#[cfg(feature = "store")]
+impl Campaign {
+    /// Rolls a dice pool for the rating with `dice` and records it in the journal, about the character if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::Permission`](crate::campaign::CampaignError::Permission) if the actor does not own the
+    /// character, or rolls for no character without being the GM, or another
+    /// [`CampaignError`](crate::campaign::CampaignError) if the roll cannot be recorded.
+    pub async fn roll(&mut self, actor: Actor, character: Option<Uuid>, label: impl Into<String>, rating: u8, dice: &impl Dice) -> Result<Roll> {
+        self.roll_and_record(actor, character, label.into(), rating, &[], None, dice).await
+    }
+
+    /// Rolls a dice pool as [`Campaign::roll`] does, keeping the dice chosen by the keep and drop rules, e.g. those a
+    /// script asks for, see [`roll_pool_with`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error as [`Campaign::roll`] does.
+    pub async fn roll_keeping(
+        &mut self, actor: Actor, character: Option<Uuid>, label: impl Into<String>, rating: u8, keep_drop: &[KeepDrop], dice: &impl Dice,
+    ) -> Result<Roll> {
+        self.roll_and_record(actor, character, label.into(), rating, keep_drop, None, dice).await
+    }
+
+    /// Rolls a dice pool as [`Campaign::roll`] does, made by the actor of the context, and records the context along
+    /// with the roll. The special abilities of the character the context triggers add their dice to the pool and
+    /// shift its effect, and are recorded with the roll, see [`Campaign::triggered_abilities`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error as [`Campaign::roll`] does.
+    pub async fn roll_with(
+        &mut self, mut context: RollContext, character: Option<Uuid>, label: impl Into<String>, rating: u8, dice: &impl Dice,
+    ) -> Result<Roll> {
+        let triggered = match character {
+            Some(character) => self.triggered_abilities(character, &context).await?,
+            None => Triggered::default(),
+        };
+        context.effect = context.effect.shift(triggered.effect);
+
+        let mut pending = PendingRoll::new(
+            context.actor,
+            character,
+            label.into(),
+            rating.saturating_add(triggered.dice),
+            &[],
+            Some(context),
+            dice,
+        );
+        pending.roll.abilities = triggered.abilities;
+
+        self.commit_roll(pending).await
+    }
+
+    /// Rolls many dice pools at once, e.g. the fortune rolls of a crowd of NPCs, and records them in the journal in a
+    /// single transaction. Every die of the batch is drawn in one call to `dice`, so shared dice are only locked once.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::Permission`](crate::campaign::CampaignError::Permission) if the actor cannot roll one
+    /// of the requests, as with [`Campaign::roll`], or another [`CampaignError`](crate::campaign::CampaignError) if the
+    /// rolls cannot be recorded, in which case none are.
+    pub async fn roll_batch(&mut self, actor: Actor, requests: &[RollRequest], dice: &impl Dice) -> Result<Vec<Roll>> {
+        // A zero rating rolls two dice and keeps the lowest.
+        let pools = requests
+            .iter()
+            .map(|request| if request.rating == 0 { 2 } else { usize::from(request.rating) })
+            .collect::<Vec<_>>();
+        let mut drawn = dice.roll_pool(pools.iter().sum()).into_iter();
+
+        let mut rolls = Vec::with_capacity(requests.len());
+        let mut changesets = Vec::with_capacity(requests.len());
+        for (request, pool) in requests.iter().zip(pools) {
+            let mut kept = drawn.by_ref().take(pool).collect::<Vec<_>>();
+            if request.rating == 0 {
+                kept = kept.into_iter().min().into_iter().collect();
+            }
+            let roll = Roll {
+                label: request.label.clone(),
+                rating: request.rating,
+                outcome: Outcome::from_dice(&kept),
+                dice: kept,
+                keep_drop: Vec::new(),
+                context: None,
+                abilities: Vec::new(),
+                fudged: None,
+                manual: false,
+            };
+            let access = request.character.map_or(Access::Gm, Access::Owner);
+            changesets.push(Changeset::new(actor, access).record(request.character, ROLLED, &roll)?);
+            rolls.push(roll);
+        }
+        self.apply_all(changesets).await?;
+
+        Ok(rolls)
+    }
+
+    /// Rolls a dice pool as [`Campaign::roll_with`] does without recording it, so the GM may overrule it first, see
+    /// [`PendingRoll::overrule`]. The character's special abilities are not resolved, the rating and context given are
+    /// rolled as they are.
+    pub fn pending_roll(&self, context: RollContext, character: Option<Uuid>, label: impl Into<String>, rating: u8, dice: &impl Dice) -> PendingRoll {
+        PendingRoll::new(context.actor, character, label.into(), rating, &[], Some(context), dice)
+    }
+
+    /// Records the results of physical dice rolled at the table, in the order rolled, made by the actor of the context.
+    /// The roll is kept, recorded and listed in the roll history as digital rolls are, flagged as manual.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::ManualRoll`](crate::campaign::CampaignError::ManualRoll) if the dice cannot make a roll
+    /// for the rating, see [`Roll::manual`], [`CampaignError::Setup`](crate::campaign::CampaignError::Setup) if the
+    /// table opted out of [`HouseRule::ManualDice`], or another error as [`Campaign::roll`] does.
+    pub async fn record_manual_roll(
+        &mut self, context: RollContext, character: Option<Uuid>, label: impl Into<String>, rating: u8, dice: Vec<u8>,
+    ) -> Result<Roll> {
+        self.require_house_rule(HouseRule::ManualDice).await?;
+
+        let roll = Roll::manual(label, rating, &[], &dice)?;
+        let pending = PendingRoll {
+            actor: context.actor,
+            character,
+            roll: Roll {
+                context: Some(context),
+                ..roll
+            },
+        };
+
+        self.commit_roll(pending).await
+    }
+
+    /// Records a pending roll in the journal, along with its natural result if the GM overruled it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::Setup`](crate::campaign::CampaignError::Setup) if the GM overruled the roll while the
+    /// table opted out of [`HouseRule::GmFudging`], or another error as [`Campaign::roll`] does.
+    pub async fn commit_roll(&mut self, pending: PendingRoll) -> Result<Roll> {
+        if pending.roll.fudged.is_some() {
+            self.require_house_rule(HouseRule::GmFudging).await?;
+        }
+        self.record(pending.actor, pending.character, ROLLED, &pending.roll).await?;
+
+        Ok(pending.roll)
+    }
+
+    /// Rolls a dice pool and records it along with its context, if any.
+    #[expect(clippy::too_many_arguments, reason = "Every way to roll funnels into this one")]
+    async fn roll_and_record(
+        &mut self, actor: Actor, character: Option<Uuid>, label: String, rating: u8, keep_drop: &[KeepDrop], context: Option<RollContext>,
+        dice: &impl Dice,
+    ) -> Result<Roll> {
+        self.commit_roll(PendingRoll::new(actor, character, label, rating, keep_drop, context, dice))
+            .await
+    }
+
+    /// Returns up to `limit` of the latest rolls, newest first, only those of the character if given.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CampaignError`](crate::campaign::CampaignError) if the journal cannot be read or holds an invalid
+    /// roll.
+    pub async fn rolls(&mut self, character: Option<Uuid>, limit: u32) -> Result<Vec<RecordedRoll>> {
+        let mut rolls = Vec::new();
+        for recorded in self.store().journal(character, ROLLED, limit).await? {
+            let Logged { actor, data } = recorded.decode::<Logged<Roll>>()?;
+            rolls.push(RecordedRoll {
+                seq: recorded.seq,
+                character: recorded.entity,
+                actor,
+                roll: data,
+                recorded_at: recorded.recorded_at,
+            });
+        }
+
+        Ok(rolls)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+    use crate::action::tests::Loaded;
+    #[cfg(feature = "store")]
+    use crate::{
+        campaign::{CampaignError, tests::bootstrap},
+        character::Character,
+        context::Position,
+        effect::Effect,
+    };
+
+    #[test]
+    fn should_roll_without_recording() {
+        let roll = Roll::new("Prowl", 2, &Loaded::new(&[4, 6]));
+
+        assert_eq!(vec![4, 6], roll.dice);
+        assert_eq!((Outcome::Success, 2, None), (roll.outcome, roll.rating, roll.context));
+    }
+
+    #[rstest]
+    #[case::pool(2, &[], &[4, 6], Ok((vec![4, 6], Outcome::Success)))]
+    #[case::zero_rating(0, &[], &[6, 3], Ok((vec![3], Outcome::Failure)))]
+    #[case::extra_dice(2, &[KeepDrop::DropHighest(1)], &[6, 4, 2], Ok((vec![4, 2], Outcome::Partial)))]
+    #[case::too_few(3, &[], &[6, 6], Err(ManualRollError::Count { rating: 3, expected: 3, found: 2 }))]
+    #[case::zero_rating_single_die(0, &[], &[6], Err(ManualRollError::Count { rating: 0, expected: 2, found: 1 }))]
+    #[case::not_d6(2, &[], &[7, 1], Err(ManualRollError::Face(7)))]
+    fn should_keep_manual_dice_as_rolled_ones(
+        #[case] rating: u8, #[case] keep_drop: &[KeepDrop], #[case] dice: &[u8],
+        #[case] expected: std::result::Result<(Vec<u8>, Outcome), ManualRollError>,
+    ) {
+        let roll = Roll::manual("Prowl", rating, keep_drop, dice);
+
+        assert_eq!(
+            expected,
+            roll.map(|roll| {
+                assert!(roll.manual, "should have flagged roll as manual");
+                (roll.dice, roll.outcome)
+            })
+        );
+    }
+
+    #[cfg(feature = "store")]
+    #[tokio::test]
+    async fn should_list_latest_rolls_by_character() {
+        let player = Uuid::new_v4();
+        let mut campaign = bootstrap().await;
+        let arcy = campaign
+            .enlist(Actor::Gm, player, &Character::new("Arcy"))
+            .await
+            .expect("should have enlisted character");
+
+        let roll = campaign
+            .roll(Actor::Player(player), Some(arcy), "Prowl", 2, &Loaded::new(&[4, 6]))
+            .await
+            .expect("should have rolled");
+        campaign
+            .roll(Actor::Gm, None, "Alarm", 1, &Loaded::new(&[2]))
+            .await
+            .expect("should have rolled");
+        campaign
+            .roll(Actor::Player(player), Some(arcy), "Skirmish", 0, &Loaded::new(&[5, 3]))
+            .await
+            .expect("should have rolled");
+
+        assert_eq!(Outcome::Success, roll.outcome);
+        assert_eq!(vec![4, 6], roll.dice);
+
+        let all = campaign.rolls(None, 2).await.expect("should have listed rolls");
+        assert_eq!(
+            vec!["Skirmish", "Alarm"],
+            all.iter().map(|recorded| recorded.roll.label.as_str()).collect::<Vec<_>>()
+        );
+        assert_eq!(Actor::Gm, all[1].actor);
+
+        let own = campaign.rolls(Some(arcy), 10).await.expect("should have listed rolls");
+        assert_eq!(
+            vec!["Skirmish", "Prowl"],
+            own.iter().map(|recorded| recorded.roll.label.as_str()).collect::<Vec<_>>()
+        );
+        assert_eq!(vec![3], own[0].roll.dice);
+        assert_eq!(Outcome::Failure, own[0].roll.outcome);
+    }
+
+    #[cfg(feature = "store")]
+    #[tokio::test]
+    async fn should_record_context_with_roll() {
+        let player = Uuid::new_v4();
+        let mut campaign = bootstrap().await;
+        let arcy = campaign
+            .enlist(Actor::Gm, player, &Character::new("Arcy"))
+            .await
+            .expect("should have enlisted character");
+        let context = RollContext::new(Actor::Player(player))
+            .position(Position::Desperate)
+            .effect(Effect::Great)
+            .tag("unseen")
+            .tag("on hostile turf");
+
+        let roll = campaign
+            .roll_with(context.clone(), Some(arcy), "Prowl", 2, &Loaded::new(&[4, 5]))
+            .await
+            .expect("should have rolled");
+        campaign
+            .roll_with(
+                RollContext::new(Actor::Player(Uuid::new_v4())),
+                Some(arcy),
+                "Prowl",
+                2,
+                &Loaded::new(&[4, 5]),
+            )
+            .await
+            .expect_err("should have refused a roll for another player's character");
+
+        assert_eq!(Some(&context), roll.context.as_ref());
+        let recorded = campaign.rolls(Some(arcy), 1).await.expect("should have listed rolls");
+        assert_eq!(Actor::Player(player), recorded[0].actor);
+        assert_eq!(Some(context), recorded[0].roll.context);
+    }
+
+    #[cfg(feature = "store")]
+    #[tokio::test]
+    async fn should_record_manual_roll() {
+        let player = Uuid::new_v4();
+        let mut campaign = bootstrap().await;
+        let arcy = campaign
+            .enlist(Actor::Gm, player, &Character::new("Arcy"))
+            .await
+            .expect("should have enlisted character");
+        let context = RollContext::new(Actor::Player(player)).position(Position::Desperate);
+
+        let roll = campaign
+            .record_manual_roll(context.clone(), Some(arcy), "Prowl", 2, vec![6, 6])
+            .await
+            .expect("should have recorded roll");
+        let err = campaign
+            .record_manual_roll(context.clone(), Some(arcy), "Prowl", 2, vec![6])
+            .await
+            .expect_err("should have refused too few dice");
+        campaign
+            .record_manual_roll(RollContext::new(Actor::Player(Uuid::new_v4())), Some(arcy), "Prowl", 1, vec![6])
+            .await
+            .expect_err("should have refused a roll for another player's character");
+
+        assert_eq!((Outcome::Critical, true), (roll.outcome, roll.manual));
+        assert!(
+            matches!(err, CampaignError::ManualRoll(ManualRollError::Count { .. })),
+            "unexpected error {err:?}"
+        );
+        let recorded = campaign.rolls(Some(arcy), 10).await.expect("should have listed rolls");
+        assert_eq!(1, recorded.len());
+        assert_eq!(Actor::Player(player), recorded[0].actor);
+        assert_eq!(roll, recorded[0].roll);
+        assert_eq!(Some(context), recorded[0].roll.context);
+    }
+
+    #[cfg(feature = "store")]
+    #[tokio::test]
+    async fn should_record_natural_result_of_overruled_roll() {
+        let player = Uuid::new_v4();
+        let mut campaign = bootstrap().await;
+        let arcy = campaign
+            .enlist(Actor::Gm, player, &Character::new("Arcy"))
+            .await
+            .expect("should have enlisted character");
+
+        let mut pending = campaign.pending_roll(RollContext::new(Actor::Player(player)), Some(arcy), "Prowl", 2, &Loaded::new(&[2, 3]));
+        assert_eq!(
+            Err(FudgeError::Permission(PermissionError::GmOnly(player))),
+            pending.overrule(Actor::Player(player), vec![6, 6])
+        );
+        assert_eq!(
+            Err(FudgeError::Dice { expected: 2, found: vec![6] }),
+            pending.overrule(Actor::Gm, vec![6])
+        );
+        assert_eq!(
+            Err(FudgeError::Dice {
+                expected: 2,
+                found: vec![7, 1]
+            }),
+            pending.overrule(Actor::Gm, vec![7, 1])
+        );
+        pending.overrule(Actor::Gm, vec![4, 2]).expect("should have overruled roll");
+        pending.overrule(Actor::Gm, vec![6, 2]).expect("should have overruled roll again");
+
+        let roll = campaign.commit_roll(pending).await.expect("should have committed roll");
+        assert_eq!(vec![6, 2], roll.dice);
+        assert_eq!(Outcome::Success, roll.outcome);
+        let recorded = campaign.rolls(Some(arcy), 1).await.expect("should have listed rolls");
+        assert_eq!(Actor::Player(player), recorded[0].actor);
+        assert_eq!(
+            Some(Fudged {
+                dice: vec![2, 3],
+                outcome: Outcome::Failure
+            }),
+            recorded[0].roll.fudged
+        );
+    }
+
+    #[cfg(feature = "store")]
+    #[tokio::test]
+    async fn should_roll_batch_in_one_draw() {
+        let player = Uuid::new_v4();
+        let mut campaign = bootstrap().await;
+        let arcy = campaign
+            .enlist(Actor::Gm, player, &Character::new("Arcy"))
+            .await
+            .expect("should have enlisted character");
+        let request = |character: Option<Uuid>, label: &str, rating: u8| RollRequest {
+            character,
+            label: label.into(),
+            rating,
+        };
+
+        let rolls = campaign
+            .roll_batch(
+                Actor::Gm,
+                &[request(None, "Thug", 1), request(None, "Lookout", 0), request(Some(arcy), "Prowl", 2)],
+                &Loaded::new(&[4, 6, 2, 6, 6]),
+            )
+            .await
+            .expect("should have rolled batch");
+
+        assert_eq!(
+            vec![(vec![4], Outcome::Partial), (vec![2], Outcome::Failure), (vec![6, 6], Outcome::Critical)],
+            rolls.iter().map(|roll| (roll.dice.clone(), roll.outcome)).collect::<Vec<_>>()
+        );
+        let recorded = campaign.rolls(None, 10).await.expect("should have listed rolls");
+        assert_eq!(
+            vec!["Prowl", "Lookout", "Thug"],
+            recorded.iter().map(|recorded| recorded.roll.label.as_str()).collect::<Vec<_>>()
+        );
+        assert_eq!(Some(arcy), recorded[0].character);
+
+        campaign
+            .roll_batch(
+                Actor::Player(player),
+                &[request(Some(arcy), "Prowl", 2), request(None, "Thug", 1)],
+                &Loaded::new(&[6, 6, 6]),
+            )
+            .await
+            .expect_err("should have refused player rolling for no character");
+        assert_eq!(3, campaign.rolls(None, 10).await.expect("should have listed rolls").len());
+    }
+
+    #[cfg(feature = "store")]
+    #[tokio::test]
+    async fn should_only_let_gm_roll_for_no_character() {
+        let mut campaign = bootstrap().await;
+
+        campaign
+            .roll(Actor::Player(Uuid::new_v4()), None, "Engagement", 2, &Loaded::new(&[6, 6]))
+            .await
+            .expect_err("should have refused player");
+
+        assert_eq!(
+            Vec::<RecordedRoll>::new(),
+            campaign.rolls(None, 10).await.expect("should have listed rolls")
+        );
+    }
+}
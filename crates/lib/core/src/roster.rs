@@ -0,0 +1,288 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! Rosters of the characters each player has played, including alts and characters retired after trauma.
+
+#[cfg(feature = "store")]
+use darkforge_data::store::{Gathered, World};
+use darkforge_data::{Component, Persisted};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::character::Character;
+#[cfg(feature = "store")]
+use crate::{
+    campaign::{Campaign, Result},
+    changeset::{Access, Changeset},
+    character::Stash,
+    permission::{Actor, Owner},
+};
+
+/// Kind of journal entry recorded when a character is enlisted.
+pub const ENLISTED: &str = "character.enlisted";
+
+/// Whether a character is still played.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Standing {
+    /// The character is in play.
+    #[default]
+    Active,
+    /// The character has left play.
+    Retired,
+}
+
+impl Component for Standing {}
+
+impl Persisted for Standing {
+    const KIND: &'static str = "standing";
+}
+
+/// What happens to the stash of a retiring character.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum StashRule {
+    /// The stash stays with the retired character.
+    #[default]
+    Personal,
+    /// The stash moves, as far as it fits, to the player's other active characters in the order they were enlisted.
+    Shared,
+}
+
+/// A character on a player's roster.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RosterEntry {
+    /// The character entity.
+    pub entity: Uuid,
+    /// The player owning the character.
+    pub player: Uuid,
+    /// The character.
+    pub character: Character,
+    /// Whether the character is still played.
+    pub standing: Standing,
+}
+
+#[cfg(feature = "store")]
+impl Campaign {
+    /// Adds a new active character to the player's roster. Players can only enlist characters for themselves.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::Permission`](crate::campaign::CampaignError::Permission) if the actor is another player,
+    /// or another [`CampaignError`](crate::campaign::CampaignError) if the store cannot be written.
+    pub async fn enlist(&mut self, actor: Actor, player: Uuid, character: &Character) -> Result<Uuid> {
+        let entity = Uuid::new_v4();
+        let changeset = enlistment(actor, player, entity, character)?.record(Some(entity), ENLISTED, character)?;
+        self.apply(changeset).await?;
+
+        Ok(entity)
+    }
+
+    /// Retires the character, applying the given rule to its stash.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::Permission`](crate::campaign::CampaignError::Permission) if the actor does not own the
+    /// character, or another [`CampaignError`](crate::campaign::CampaignError) if the store cannot be written.
+    pub async fn retire(&mut self, actor: Actor, entity: Uuid, rule: StashRule) -> Result<()> {
+        let mut changeset = Changeset::new(actor, Access::Owner(entity)).insert(entity, &Standing::Retired)?;
+
+        if rule == StashRule::Shared {
+            if let Some(Owner(player)) = self.get::<Owner>(entity).await? {
+                let mut stash = self.get::<Stash>(entity).await?.unwrap_or_default();
+                for heir in self
+                    .roster(Some(player))
+                    .await?
+                    .into_iter()
+                    .filter(|entry| entry.entity != entity && entry.standing == Standing::Active)
+                {
+                    let mut heir_stash = self.get::<Stash>(heir.entity).await?.unwrap_or_default();
+                    heir_stash.absorb(&mut stash);
+                    changeset = changeset.insert(heir.entity, &heir_stash)?;
+                }
+                changeset = changeset.insert(entity, &stash)?;
+            }
+        }
+
+        self.apply(changeset.record(Some(entity), "character.retired", &Standing::Retired)?)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Returns every character on the roster, or only those of the given player, in the order they were enlisted.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CampaignError`](crate::campaign::CampaignError) if the store cannot be read or holds invalid
+    /// components.
+    pub async fn roster(&mut self, player: Option<Uuid>) -> Result<Vec<RosterEntry>> {
+        let gathered = self.store().gather(&[Character::KIND, Owner::KIND, Standing::KIND], "").await?;
+
+        let mut roster = Vec::new();
+        for character in gathered.iter().filter(|g| g.is::<Character>()) {
+            let component = |kind: &str| gathered.iter().find(|g| g.entity == character.entity && g.kind == kind);
+            let Some(owner) = component(Owner::KIND) else {
+                continue;
+            };
+
+            let Owner(owner) = owner.decode::<Owner>()?;
+            if player.is_some_and(|player| player != owner) {
+                continue;
+            }
+
+            roster.push(RosterEntry {
+                entity: character.entity,
+                player: owner,
+                character: character.decode()?,
+                standing: component(Standing::KIND)
+                    .map(Gathered::decode::<Standing>)
+                    .transpose()?
+                    .unwrap_or_default(),
+            });
+        }
+
+        Ok(roster)
+    }
+
+    /// Returns the characters currently in play, or only those of the given player, in the order they were enlisted.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CampaignError`](crate::campaign::CampaignError) if the store cannot be read or holds invalid
+    /// components.
+    pub async fn active_roster(&mut self, player: Option<Uuid>) -> Result<Vec<RosterEntry>> {
+        let mut roster = self.roster(player).await?;
+        roster.retain(|entry| entry.standing == Standing::Active);
+
+        Ok(roster)
+    }
+}
+
+/// Returns the changeset creating the character entity on the player's roster, yet to be recorded.
+#[cfg(feature = "store")]
+pub(crate) fn enlistment(actor: Actor, player: Uuid, entity: Uuid, character: &Character) -> Result<Changeset> {
+    Ok(Changeset::new(actor, Access::Anyone)
+        .spawn(entity)
+        .insert(entity, &Owner(player))?
+        .insert(entity, character)?
+        .insert(entity, &Standing::Active)?
+        .insert(entity, &Stash::default())?)
+}
+
+#[cfg(all(test, feature = "store"))]
+mod tests {
+    use uuid::uuid;
+
+    use super::*;
+    use crate::{
+        campaign::{CampaignError, tests::bootstrap},
+        permission::PermissionError,
+    };
+
+    const ALICE: Uuid = uuid!("0b1c0b45-7d1f-4c55-9c2f-5e6e5f1b3a10");
+    const BOB: Uuid = uuid!("9a8d6c3e-2f4b-4b1a-8e5d-7c6b5a4f3e2d");
+
+    #[tokio::test]
+    async fn should_list_active_roster_per_player() {
+        let mut campaign = bootstrap().await;
+
+        let arcy = campaign
+            .enlist(Actor::Player(ALICE), ALICE, &Character::new("Arcy"))
+            .await
+            .expect("should have enlisted");
+        let nyryx = campaign
+            .enlist(Actor::Gm, ALICE, &Character::new("Nyryx"))
+            .await
+            .expect("should have enlisted");
+        let slane = campaign
+            .enlist(Actor::Player(BOB), BOB, &Character::new("Slane"))
+            .await
+            .expect("should have enlisted");
+
+        campaign
+            .retire(Actor::Player(ALICE), arcy, StashRule::Personal)
+            .await
+            .expect("should have retired");
+
+        let standings = |roster: Vec<RosterEntry>| roster.into_iter().map(|entry| (entry.entity, entry.standing)).collect::<Vec<_>>();
+        assert_eq!(
+            vec![(arcy, Standing::Retired), (nyryx, Standing::Active)],
+            standings(campaign.roster(Some(ALICE)).await.expect("should have listed roster"))
+        );
+        assert_eq!(
+            vec![(nyryx, Standing::Active), (slane, Standing::Active)],
+            standings(campaign.active_roster(None).await.expect("should have listed roster"))
+        );
+    }
+
+    #[tokio::test]
+    async fn should_share_stash_with_active_characters_on_retirement() {
+        let mut campaign = bootstrap().await;
+
+        let arcy = campaign
+            .enlist(Actor::Gm, ALICE, &Character::new("Arcy"))
+            .await
+            .expect("should have enlisted");
+        let nyryx = campaign
+            .enlist(Actor::Gm, ALICE, &Character::new("Nyryx"))
+            .await
+            .expect("should have enlisted");
+        campaign.store().insert(arcy, &Stash::new(30)).await.expect("should have set stash");
+        campaign.store().insert(nyryx, &Stash::new(25)).await.expect("should have set stash");
+
+        campaign.retire(Actor::Gm, arcy, StashRule::Shared).await.expect("should have retired");
+
+        let stash = |stash: Option<Stash>| stash.map(Stash::value);
+        assert_eq!(Some(40), stash(campaign.store().get(nyryx).await.expect("should have read stash")));
+        assert_eq!(Some(15), stash(campaign.store().get(arcy).await.expect("should have read stash")));
+    }
+
+    #[tokio::test]
+    async fn should_not_let_players_manage_other_rosters() {
+        let mut campaign = bootstrap().await;
+        let arcy = campaign
+            .enlist(Actor::Gm, ALICE, &Character::new("Arcy"))
+            .await
+            .expect("should have enlisted");
+
+        let err = campaign
+            .enlist(Actor::Player(BOB), ALICE, &Character::new("Slane"))
+            .await
+            .expect_err("should have refused to enlist");
+        assert!(
+            matches!(err, CampaignError::Permission(PermissionError::OtherPlayer { player: BOB, other: ALICE })),
+            "unexpected error: {err}"
+        );
+
+        let err = campaign
+            .retire(Actor::Player(BOB), arcy, StashRule::Personal)
+            .await
+            .expect_err("should have refused to retire");
+        assert!(
+            matches!(err, CampaignError::Permission(PermissionError::NotOwner { .. })),
+            "unexpected error: {err}"
+        );
+    }
+
+    #[tokio::test]
+    async fn should_not_spawn_character_given_refused_enlistment() {
+        let mut campaign = bootstrap().await;
+        let before = campaign.delta(0).await.expect("should have read delta").entities;
+
+        campaign
+            .enlist(Actor::Player(BOB), ALICE, &Character::new("Slane"))
+            .await
+            .expect_err("should have refused to enlist");
+
+        assert_eq!(before, campaign.delta(0).await.expect("should have read delta").entities);
+    }
+}
@@ -0,0 +1,185 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! Rule reference, for in-game help panels.
+//!
+//! The rules text ships in content packs as entries of the [`CATEGORY`] category. Each [`Rule`] is looked up by a
+//! human readable slug and cross-references related rules by their slugs.
+
+use std::collections::BTreeMap;
+
+use darkforge_data::pack::{Pack, PackError};
+#[cfg(feature = "store")]
+use darkforge_data::store::Content;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[cfg(feature = "store")]
+use crate::campaign::{Campaign, Result};
+
+/// Content pack category holding the rules text.
+pub const CATEGORY: &str = "rule";
+
+/// A single rule of the game.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Rule {
+    /// The id of the rule in the content pack.
+    pub id: Uuid,
+    /// The slug the rule is looked up by, e.g. `action-roll`.
+    pub slug: String,
+    /// The rule's title.
+    pub title: String,
+    /// The section of the rules the rule belongs to, e.g. `core` or `downtime`.
+    pub section: String,
+    /// The rule's text.
+    pub text: String,
+    /// The slugs of related rules.
+    #[serde(default)]
+    pub related: Vec<String>,
+}
+
+/// A rule along with the rules it references.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Lookup<'a> {
+    /// The rule looked up.
+    pub rule: &'a Rule,
+    /// The related rules, in the order the rule references them.
+    pub related: Vec<&'a Rule>,
+}
+
+/// An index of rules by slug.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RuleRef {
+    rules: BTreeMap<String, Rule>,
+}
+
+impl RuleRef {
+    /// Indexes the rules by slug. A rule replaces any earlier rule with the same slug.
+    #[must_use]
+    pub fn new(rules: impl IntoIterator<Item = Rule>) -> RuleRef {
+        RuleRef {
+            rules: rules.into_iter().map(|rule| (rule.slug.clone(), rule)).collect(),
+        }
+    }
+
+    /// Indexes the rules of the content pack.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PackError::InvalidEntries`] if a rule does not match the expected shape.
+    pub fn from_pack(pack: &Pack) -> std::result::Result<RuleRef, PackError> {
+        Ok(RuleRef::new(pack.load::<Rule>(CATEGORY)?))
+    }
+
+    /// Returns the rule with the given slug along with its related rules, if any. References to missing rules are
+    /// skipped.
+    #[must_use]
+    pub fn lookup(&self, slug: &str) -> Option<Lookup<'_>> {
+        let rule = self.rules.get(slug)?;
+        let related = rule.related.iter().filter_map(|slug| self.rules.get(slug)).collect();
+
+        Some(Lookup { rule, related })
+    }
+
+    /// Returns the rules of the given section, ordered by slug.
+    pub fn section<'a>(&'a self, section: &'a str) -> impl Iterator<Item = &'a Rule> {
+        self.rules.values().filter(move |rule| rule.section == section)
+    }
+
+    /// Returns every rule, ordered by slug.
+    pub fn rules(&self) -> impl Iterator<Item = &Rule> {
+        self.rules.values()
+    }
+
+    /// Returns the references to missing rules, as pairs of the referencing slug and the missing slug.
+    #[must_use]
+    pub fn dangling(&self) -> Vec<(&str, &str)> {
+        self.rules
+            .values()
+            .flat_map(|rule| rule.related.iter().map(move |related| (rule.slug.as_str(), related.as_str())))
+            .filter(|(_, related)| !self.rules.contains_key(*related))
+            .collect()
+    }
+}
+
+#[cfg(feature = "store")]
+impl Campaign {
+    /// Returns the rule reference, as loaded from the content pack.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CampaignError`](crate::campaign::CampaignError) if the store cannot be read.
+    pub async fn rules(&mut self) -> Result<RuleRef> {
+        Ok(RuleRef::new(self.store().entries::<Rule>(CATEGORY).await?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::campaign::tests::DEFAULTS;
+    #[cfg(feature = "store")]
+    use crate::campaign::tests::bootstrap;
+
+    fn rule(slug: &str, related: &[&str]) -> Rule {
+        Rule {
+            id: Uuid::new_v4(),
+            slug: slug.into(),
+            title: slug.into(),
+            section: "core".into(),
+            text: String::new(),
+            related: related.iter().map(|&slug| slug.into()).collect(),
+        }
+    }
+
+    #[test]
+    fn should_look_up_rule_with_related_rules() {
+        let rules = RuleRef::new([
+            rule("action-roll", &["effect", "missing", "stress"]),
+            rule("effect", &[]),
+            rule("stress", &[]),
+        ]);
+
+        let lookup = rules.lookup("action-roll").expect("should have found rule");
+
+        assert_eq!("action-roll", lookup.rule.slug);
+        assert_eq!(
+            vec!["effect", "stress"],
+            lookup.related.iter().map(|rule| rule.slug.as_str()).collect::<Vec<_>>()
+        );
+        assert_eq!(vec![("action-roll", "missing")], rules.dangling());
+        assert_eq!(None, rules.lookup("missing"));
+    }
+
+    #[test]
+    fn should_resolve_every_reference_of_default_pack() {
+        let pack = Pack::open(DEFAULTS).expect("should have opened pack");
+
+        let rules = RuleRef::from_pack(&pack).expect("should have loaded rules");
+
+        assert_eq!(Vec::<(&str, &str)>::new(), rules.dangling());
+        assert_eq!(pack.entries(CATEGORY).len(), rules.rules().count(), "should have unique slugs");
+    }
+
+    #[cfg(feature = "store")]
+    #[tokio::test]
+    async fn should_look_up_rules_of_campaign() {
+        let mut campaign = bootstrap().await;
+
+        let rules = campaign.rules().await.expect("should have loaded rules");
+        let lookup = rules.lookup("action-roll").expect("should have found action roll");
+
+        assert_eq!("Action roll", lookup.rule.title);
+        assert_eq!(4, lookup.related.len());
+        assert_eq!(3, rules.section("downtime").count());
+    }
+}
@@ -0,0 +1,490 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! The scheduler defers game events until a condition is met: the next downtime, the next session or a day of the
+//! campaign's calendar. Triggers are stored like any other component, so they survive reopening the campaign, and fire
+//! automatically when the campaign reaches their condition.
+
+#[cfg(feature = "store")]
+use std::collections::{HashMap, hash_map::Entry};
+
+#[cfg(feature = "store")]
+use darkforge_data::store::World as _;
+use darkforge_data::{Component, Persisted};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[cfg(feature = "store")]
+use crate::{
+    campaign::{Campaign, CampaignError, Result},
+    changeset::{Access, Changeset},
+    character::Harm,
+    clock::Clock,
+    downtime::Healing,
+    permission::Actor,
+};
+
+/// Kind of the journal entries recorded when a trigger is scheduled.
+pub const SCHEDULED: &str = "trigger.scheduled";
+/// Kind of the journal entries recorded when a trigger fires.
+pub const FIRED: &str = "trigger.fired";
+/// Kind of the journal entries recorded when days pass on the calendar.
+pub const DAYS_PASSED: &str = "calendar.passed";
+
+/// When a trigger fires.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Condition {
+    /// When the next downtime activity is taken.
+    NextDowntime,
+    /// When the next session starts.
+    NextSession,
+    /// When the calendar reaches the day.
+    Day(u32),
+}
+
+/// A moment of the campaign at which triggers may fire.
+#[cfg(feature = "store")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Moment {
+    /// A downtime activity is about to be taken.
+    Downtime,
+    /// A session just started.
+    Session,
+    /// The calendar reached the day.
+    Day(u32),
+}
+
+#[cfg(feature = "store")]
+impl Condition {
+    /// Returns true if the condition is met at the moment.
+    fn is_met(self, moment: Moment) -> bool {
+        match (self, moment) {
+            (Condition::NextDowntime, Moment::Downtime) | (Condition::NextSession, Moment::Session) => true,
+            (Condition::Day(due), Moment::Day(today)) => due <= today,
+            _ => false,
+        }
+    }
+}
+
+/// What happens when a trigger fires.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Deferred {
+    /// Fill segments of a clock, e.g. what a faction does once one of its clocks completes.
+    Tick {
+        /// The clock entity.
+        clock: Uuid,
+        /// The segments to fill.
+        ticks: u8,
+    },
+    /// Heal the character's harm by one level and reset their healing clock.
+    Heal {
+        /// The character entity.
+        character: Uuid,
+    },
+    /// Only record the note in the journal, e.g. the consequences of a ritual for the GM to narrate.
+    Note(String),
+}
+
+/// The triggers due at a moment, to apply along with whatever reached the moment.
+#[cfg(feature = "store")]
+#[derive(Debug)]
+pub(crate) struct Due {
+    /// A changeset firing each trigger.
+    pub(crate) changesets: Vec<Changeset>,
+    /// What fired, in order.
+    pub(crate) fired: Vec<Fired>,
+    /// The clocks the triggers tick, in their state once fired.
+    pub(crate) clocks: HashMap<Uuid, Clock>,
+    /// The harm of the characters the triggers heal, once healed.
+    pub(crate) harms: HashMap<Uuid, Harm>,
+}
+
+/// A deferred event waiting for its condition.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Trigger {
+    /// What the trigger is about.
+    pub label: String,
+    /// When the trigger fires.
+    pub condition: Condition,
+    /// What happens when it fires.
+    pub deferred: Deferred,
+    /// False once the trigger has fired.
+    pub pending: bool,
+}
+
+impl Component for Trigger {}
+
+impl Persisted for Trigger {
+    const KIND: &'static str = "trigger";
+}
+
+/// The current day of the campaign's calendar, counted from 0 when the campaign starts.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct Day(pub u32);
+
+impl Component for Day {}
+
+impl Persisted for Day {
+    const KIND: &'static str = "day";
+}
+
+/// Journal entry recorded when a trigger fires.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Fired {
+    /// The trigger entity.
+    pub trigger: Uuid,
+    /// What the trigger was about.
+    pub label: String,
+    /// What happened.
+    pub deferred: Deferred,
+}
+
+#[cfg(feature = "store")]
+impl Campaign {
+    /// Schedules the deferred event to happen once the condition is met.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::Permission`] if the actor is not the GM, or another [`CampaignError`] if the store
+    /// cannot be written.
+    pub async fn schedule(&mut self, actor: Actor, label: impl Into<String>, condition: Condition, deferred: Deferred) -> Result<Uuid> {
+        actor.require_gm()?;
+
+        let trigger = Trigger {
+            label: label.into(),
+            condition,
+            deferred,
+            pending: true,
+        };
+        let entity = Uuid::new_v4();
+        let changeset = Changeset::new(actor, Access::Gm)
+            .spawn(entity)
+            .insert(entity, &trigger)?
+            .record(Some(entity), SCHEDULED, &trigger)?;
+        self.apply(changeset).await?;
+
+        Ok(entity)
+    }
+
+    /// Returns the triggers waiting for their condition, along with their entities, in the order they were scheduled.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CampaignError`] if the store cannot be read.
+    pub async fn pending_triggers(&mut self) -> Result<Vec<(Uuid, Trigger)>> {
+        let triggers = self.store().all::<Trigger>().await?;
+
+        Ok(triggers.into_iter().filter(|(_, trigger)| trigger.pending).collect())
+    }
+
+    /// Returns the current day of the campaign's calendar.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CampaignError`] if the store cannot be read.
+    pub async fn today(&mut self) -> Result<u32> {
+        let id = self.id();
+        let Day(today) = self.store().get::<Day>(id).await?.unwrap_or_default();
+
+        Ok(today)
+    }
+
+    /// Advances the calendar by the number of days, firing the triggers due by the new day in the same transaction.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::Permission`] if the actor is not the GM, or another [`CampaignError`] if a trigger
+    /// refers to a missing entity or the store cannot be read or written, in which case the calendar does not move.
+    pub async fn pass_days(&mut self, actor: Actor, days: u32) -> Result<Vec<Fired>> {
+        actor.require_gm()?;
+
+        let id = self.id();
+        let today = Day(self.today().await?.saturating_add(days));
+        let Due { mut changesets, fired, .. } = self.due(actor, Moment::Day(today.0)).await?;
+        changesets.push(
+            Changeset::new(actor, Access::Gm)
+                .insert(id, &today)?
+                .record(Some(id), DAYS_PASSED, &today)?,
+        );
+        self.apply_all(changesets).await?;
+
+        Ok(fired)
+    }
+
+    /// Fires the triggers whose condition is met at the moment, in a single transaction.
+    pub(crate) async fn fire(&mut self, actor: Actor, moment: Moment) -> Result<Vec<Fired>> {
+        let Due { changesets, fired, .. } = self.due(actor, moment).await?;
+        if !changesets.is_empty() {
+            self.apply_all(changesets).await?;
+        }
+
+        Ok(fired)
+    }
+
+    /// Returns a changeset firing each trigger whose condition is met at the moment, along with what fired and the
+    /// state they leave. Anyone reaching the moment fires the triggers, as the GM agreed to them when scheduling.
+    ///
+    /// Triggers acting on the same entity each set its state so far, the last one applied holding all their effects.
+    pub(crate) async fn due(&mut self, actor: Actor, moment: Moment) -> Result<Due> {
+        let mut clocks = HashMap::new();
+        let mut harms = HashMap::new();
+        let mut changesets = Vec::new();
+        let mut fired = Vec::new();
+        for (entity, mut trigger) in self.pending_triggers().await? {
+            if !trigger.condition.is_met(moment) {
+                continue;
+            }
+
+            trigger.pending = false;
+            let mut changeset = Changeset::new(actor, Access::Anyone).insert(entity, &trigger)?;
+            match &trigger.deferred {
+                Deferred::Tick { clock, ticks } => {
+                    let current = match clocks.entry(*clock) {
+                        Entry::Occupied(entry) => entry.into_mut(),
+                        Entry::Vacant(entry) => entry.insert(self.get::<Clock>(*clock).await?.ok_or(CampaignError::MissingClock(*clock))?),
+                    };
+                    current.tick(*ticks);
+                    changeset = changeset.insert(*clock, current)?;
+                }
+                Deferred::Heal { character } => {
+                    let harm = match harms.entry(*character) {
+                        Entry::Occupied(entry) => entry.into_mut(),
+                        Entry::Vacant(entry) => entry.insert(self.get::<Harm>(*character).await?.unwrap_or_default()),
+                    };
+                    harm.heal();
+                    changeset = changeset.insert(*character, harm)?.insert(*character, &Healing::default())?;
+                }
+                Deferred::Note(_) => {}
+            }
+
+            let event = Fired {
+                trigger: entity,
+                label: trigger.label,
+                deferred: trigger.deferred,
+            };
+            changesets.push(changeset.record(Some(entity), FIRED, &event)?);
+            fired.push(event);
+        }
+
+        Ok(Due {
+            changesets,
+            fired,
+            clocks,
+            harms,
+        })
+    }
+}
+
+#[cfg(all(test, feature = "store"))]
+mod tests {
+    use darkforge_data::store::sql::sqlite::SqliteStore;
+
+    use super::*;
+    use crate::{
+        action::tests::Loaded,
+        campaign::tests::{DEFAULTS, bootstrap, db},
+        character::Character,
+        downtime::Activity,
+    };
+
+    async fn alarm(campaign: &mut Campaign) -> Uuid {
+        let clock = Clock::new("Alarm", 6).expect("should have created clock");
+
+        campaign.add_clock(Actor::Gm, None, &clock).await.expect("should have added clock")
+    }
+
+    #[tokio::test]
+    async fn should_fire_session_trigger_when_next_session_starts() {
+        let mut campaign = bootstrap().await;
+        let clock = alarm(&mut campaign).await;
+        campaign
+            .schedule(
+                Actor::Gm,
+                "The Red Sashes retaliate",
+                Condition::NextSession,
+                Deferred::Tick { clock, ticks: 2 },
+            )
+            .await
+            .expect("should have scheduled trigger");
+        campaign
+            .schedule(Actor::Gm, "Word spreads", Condition::NextSession, Deferred::Tick { clock, ticks: 1 })
+            .await
+            .expect("should have scheduled trigger");
+
+        campaign.start_session(Actor::Gm, "Fallout").await.expect("should have started session");
+        campaign.start_session(Actor::Gm, "Aftermath").await.expect("should have started session");
+
+        let ticked = campaign.store().get::<Clock>(clock).await.expect("should have read clock");
+        assert_eq!(Some(3), ticked.map(|clock| clock.filled()));
+        assert_eq!(0, campaign.pending_triggers().await.expect("should have listed triggers").len());
+        let journal = campaign.store().journal(None, FIRED, 10).await.expect("should have read journal");
+        assert_eq!(2, journal.len());
+    }
+
+    #[tokio::test]
+    async fn should_fire_downtime_trigger_before_activity() {
+        let mut campaign = bootstrap().await;
+        let pc = campaign
+            .enlist(Actor::Gm, Uuid::new_v4(), &Character::new("Arcy"))
+            .await
+            .expect("should have enlisted character");
+        let mut harm = Harm::default();
+        harm.suffer(2, "Broken arm");
+        campaign.update(Actor::Gm, pc, &harm).await.expect("should have updated harm");
+        campaign
+            .schedule(
+                Actor::Gm,
+                "The physicker's work pays off",
+                Condition::NextDowntime,
+                Deferred::Heal { character: pc },
+            )
+            .await
+            .expect("should have scheduled trigger");
+
+        campaign
+            .downtime(Actor::Gm, pc, Activity::IndulgeVice, &Loaded::new(&[]))
+            .await
+            .expect("should have taken downtime");
+
+        let harm = campaign.store().get::<Harm>(pc).await.expect("should have read harm");
+        assert_eq!(Some(vec!["Broken arm".to_owned()]), harm.map(|harm| harm.lesser));
+    }
+
+    #[tokio::test]
+    async fn should_not_fire_downtime_trigger_when_activity_fails() {
+        let mut campaign = bootstrap().await;
+        let pc = campaign
+            .enlist(Actor::Gm, Uuid::new_v4(), &Character::new("Arcy"))
+            .await
+            .expect("should have enlisted character");
+        let clock = alarm(&mut campaign).await;
+        campaign
+            .schedule(
+                Actor::Gm,
+                "The watch closes in",
+                Condition::NextDowntime,
+                Deferred::Tick { clock, ticks: 2 },
+            )
+            .await
+            .expect("should have scheduled trigger");
+
+        campaign
+            .downtime(
+                Actor::Gm,
+                pc,
+                Activity::ReduceHeat {
+                    crew: Uuid::new_v4(),
+                    rating: 1,
+                },
+                &Loaded::new(&[5]),
+            )
+            .await
+            .expect_err("should have failed to reduce heat");
+
+        assert_eq!(1, campaign.pending_triggers().await.expect("should have listed triggers").len());
+        let alarm = campaign.store().get::<Clock>(clock).await.expect("should have read clock");
+        assert_eq!(Some(0), alarm.map(|clock| clock.filled()));
+    }
+
+    #[tokio::test]
+    async fn should_tick_project_from_its_state_once_downtime_trigger_fired() {
+        let mut campaign = bootstrap().await;
+        let pc = campaign
+            .enlist(Actor::Gm, Uuid::new_v4(), &Character::new("Arcy"))
+            .await
+            .expect("should have enlisted character");
+        let clock = Clock::new("Forge a new blade", 8).expect("should have created clock");
+        let project = campaign.add_clock(Actor::Gm, Some(pc), &clock).await.expect("should have added clock");
+        campaign
+            .schedule(
+                Actor::Gm,
+                "The smith lends a hand",
+                Condition::NextDowntime,
+                Deferred::Tick { clock: project, ticks: 2 },
+            )
+            .await
+            .expect("should have scheduled trigger");
+
+        campaign
+            .downtime(Actor::Gm, pc, Activity::Project { clock: project, rating: 1 }, &Loaded::new(&[4]))
+            .await
+            .expect("should have worked on project");
+
+        let ticked = campaign.store().get::<Clock>(project).await.expect("should have read clock");
+        assert_eq!(Some(4), ticked.map(|clock| clock.filled()));
+    }
+
+    #[tokio::test]
+    async fn should_fire_day_triggers_once_calendar_reaches_them_after_reopening() {
+        let path = db();
+        let pack = darkforge_data::pack::Pack::open(DEFAULTS).expect("should have opened pack");
+        let store = SqliteStore::open(&path).await.expect("should have opened store");
+        let mut campaign = Campaign::bootstrap(store, "Crow's Foot", &pack)
+            .await
+            .expect("should have bootstrapped campaign");
+        let clock = alarm(&mut campaign).await;
+        campaign
+            .schedule(
+                Actor::Gm,
+                "The ritual's price",
+                Condition::Day(3),
+                Deferred::Note("A ghost follows the Whisper".into()),
+            )
+            .await
+            .expect("should have scheduled trigger");
+        campaign
+            .schedule(Actor::Gm, "The Bluecoats close in", Condition::Day(5), Deferred::Tick { clock, ticks: 6 })
+            .await
+            .expect("should have scheduled trigger");
+
+        let mut campaign = Campaign::open(SqliteStore::open(&path).await.expect("should have opened store"))
+            .await
+            .expect("should have reopened campaign");
+        assert_eq!(2, campaign.pending_triggers().await.expect("should have listed triggers").len());
+
+        let fired = campaign.pass_days(Actor::Gm, 4).await.expect("should have passed days");
+        assert_eq!(
+            vec!["The ritual's price"],
+            fired.iter().map(|fired| fired.label.as_str()).collect::<Vec<_>>()
+        );
+        assert_eq!(4, campaign.today().await.expect("should have read calendar"));
+
+        let fired = campaign.pass_days(Actor::Gm, 1).await.expect("should have passed days");
+        assert_eq!(1, fired.len());
+        let completed = campaign.store().get::<Clock>(clock).await.expect("should have read clock");
+        assert_eq!(Some(true), completed.map(|clock| clock.is_complete()));
+        assert_eq!(
+            Vec::<Fired>::new(),
+            campaign.pass_days(Actor::Gm, 10).await.expect("should have passed days")
+        );
+    }
+
+    #[tokio::test]
+    async fn should_only_let_gm_schedule_triggers() {
+        let mut campaign = bootstrap().await;
+
+        let err = campaign
+            .schedule(
+                Actor::Player(Uuid::new_v4()),
+                "Sneaky",
+                Condition::NextSession,
+                Deferred::Note("Nope".into()),
+            )
+            .await
+            .expect_err("should have failed to schedule trigger");
+
+        assert!(matches!(err, CampaignError::Permission(_)));
+    }
+}
@@ -0,0 +1,97 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! The layout of campaign databases, declared in one place.
+//!
+//! The [`registry`] declares the tables created by the store's migrations along with every component saved in a
+//! campaign, so tools such as the verify command or an editor dock can list them without reading the code.
+
+use darkforge_data::store::{schema::Registry, sql::sqlite};
+
+use crate::{
+    campaign::CampaignInfo,
+    character::{Abilities, Character, Harm, Ratings, Stash, Stress, Traumas},
+    clock::Clock,
+    consequence::{ArmorUses, ClockOrigin},
+    contest::Contest,
+    crew::{Crew, Heat},
+    downtime::Healing,
+    faction::{Faction, Status},
+    item::Item,
+    loadout::Loadout,
+    npc::{Npc, StatBlock},
+    permission::Owner,
+    prompt::PromptTimeouts,
+    retention::Retention,
+    roster::Standing,
+    schedule::{Day, Trigger},
+    setup::Setup,
+    slot::Playtime,
+    worldgen::Situation,
+};
+
+/// Returns the registry of the tables of a campaign database and the components saved in them.
+#[must_use]
+pub fn registry() -> Registry {
+    sqlite::registry()
+        .register::<Abilities>()
+        .register::<ArmorUses>()
+        .register::<CampaignInfo>()
+        .register::<Character>()
+        .register::<Clock>()
+        .register::<ClockOrigin>()
+        .register::<Contest>()
+        .register::<Crew>()
+        .register::<Day>()
+        .register::<Faction>()
+        .register::<Harm>()
+        .register::<Healing>()
+        .register::<Heat>()
+        .register::<Item>()
+        .register::<Loadout>()
+        .register::<Npc>()
+        .register::<Owner>()
+        .register::<Playtime>()
+        .register::<PromptTimeouts>()
+        .register::<Ratings>()
+        .register::<Retention>()
+        .register::<Setup>()
+        .register::<Situation>()
+        .register::<Standing>()
+        .register::<Stash>()
+        .register::<StatBlock>()
+        .register::<Status>()
+        .register::<Stress>()
+        .register::<Traumas>()
+        .register::<Trigger>()
+}
+
+#[cfg(all(test, feature = "store"))]
+mod tests {
+    use darkforge_data::store::schema::Reflect;
+
+    use super::*;
+    use crate::campaign::tests::bootstrap;
+
+    #[tokio::test]
+    async fn should_register_every_kind_saved_by_a_campaign() {
+        let mut campaign = bootstrap().await;
+
+        let saved = campaign.store().schema().await.expect("should have read schema");
+        let declared = registry().schema();
+
+        assert_eq!(0, declared.missing(&saved).len());
+        for kind in saved.kinds.keys() {
+            assert!(declared.kinds.contains_key(kind), "kind {kind} is not registered");
+        }
+    }
+}
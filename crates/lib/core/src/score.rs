@@ -0,0 +1,471 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! Fictive time and flashbacks within a score.
+//!
+//! Scores are delimited in the journal by the markers the GM records when starting and ending one. While a score is in
+//! progress, the GM records the fictive time that passes and the players record the flashbacks their characters call
+//! for, so the score can be replayed as a [`ScoreTimeline`] and GMs who houserule a flashback budget can see how much
+//! of it was used, see [`FlashbackUsage`].
+use std::fmt::{self, Display, Formatter};
+
+#[cfg(feature = "store")]
+use darkforge_data::store::{Recorded, World as _};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[cfg(feature = "store")]
+use crate::{
+    campaign::{Campaign, CampaignError, Logged, Result},
+    permission::Actor,
+};
+
+/// Kind of the journal entries marking the start of a score.
+pub const STARTED: &str = "score.started";
+/// Kind of the journal entries recording fictive time passing during a score.
+pub const ELAPSED: &str = "score.elapsed";
+/// Kind of the journal entries recording a flashback during a score.
+pub const FLASHBACK: &str = "score.flashback";
+/// Kind of the journal entries marking the end of a score.
+pub const ENDED: &str = "score.ended";
+
+/// Journal entry recorded when a score starts.
+#[cfg(feature = "store")]
+#[derive(Serialize, Deserialize)]
+struct Marker {
+    target: String,
+}
+
+/// How much of a stretch a flashback is, which sets its stress cost.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// An ordinary action at an easy opportunity, costing no stress.
+    Simple,
+    /// A complex action or an unlikely opportunity, costing 1 stress.
+    Complex,
+    /// An elaborate action or a very unlikely opportunity, costing 2 stress.
+    Elaborate,
+}
+
+impl Severity {
+    /// Returns the stress the flashback costs.
+    #[must_use]
+    pub fn stress(self) -> u8 {
+        match self {
+            Severity::Simple => 0,
+            Severity::Complex => 1,
+            Severity::Elaborate => 2,
+        }
+    }
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Simple => "simple",
+            Severity::Complex => "complex",
+            Severity::Elaborate => "elaborate",
+        })
+    }
+}
+
+/// Journal entry recorded when fictive time passes during a score.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Elapsed {
+    /// The minutes of fictive time that passed.
+    pub minutes: u32,
+    /// What took that long.
+    pub note: String,
+}
+
+/// Journal entry recorded when a character flashes back during a score.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Flashback {
+    /// What the character did back then.
+    pub description: String,
+    /// How much of a stretch it is.
+    pub severity: Severity,
+}
+
+/// A score, as marked in the journal.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Score {
+    /// The score's number, starting at 1.
+    pub number: usize,
+    /// What the crew is after.
+    pub target: String,
+    /// The sequence number of the journal entry marking its start.
+    pub seq: i64,
+    /// The sequence number of the journal entry marking its end, or `None` while it is in progress.
+    pub ended: Option<i64>,
+}
+
+/// Something that happened during a score.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Event {
+    /// The score started.
+    Started,
+    /// Fictive time passed.
+    Elapsed(Elapsed),
+    /// A character, or the crew if `None`, flashed back.
+    Flashback(Option<Uuid>, Flashback),
+    /// The score ended.
+    Ended,
+}
+
+impl Display for Event {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Event::Started => write!(f, "started"),
+            Event::Elapsed(Elapsed { minutes, note }) => write!(f, "{note} ({minutes} minutes)"),
+            Event::Flashback(_, Flashback { description, severity }) => {
+                write!(f, "flashback: {description} ({severity}, {} stress)", severity.stress())
+            }
+            Event::Ended => write!(f, "ended"),
+        }
+    }
+}
+
+/// An event along with the fictive time into the score it happened at.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Moment {
+    /// The minutes of fictive time elapsed since the score started, once the event is over.
+    pub minute: u32,
+    /// What happened.
+    pub event: Event,
+}
+
+/// Formats minutes of fictive time as hours and minutes, e.g. `1:05`.
+#[must_use]
+pub fn clock_time(minutes: u32) -> String {
+    format!("{}:{:02}", minutes / 60, minutes % 60)
+}
+
+/// The flashbacks called for during a score.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FlashbackUsage {
+    /// The number of flashbacks, by severity from simple to elaborate.
+    pub by_severity: [usize; 3],
+    /// The stress they cost.
+    pub stress: u32,
+}
+
+impl FlashbackUsage {
+    /// Returns the number of flashbacks.
+    #[must_use]
+    pub fn count(&self) -> usize {
+        self.by_severity.iter().sum()
+    }
+
+    /// Counts a flashback.
+    #[cfg(feature = "store")]
+    fn add(&mut self, severity: Severity) {
+        self.by_severity[severity as usize] += 1;
+        self.stress += u32::from(severity.stress());
+    }
+}
+
+/// What happened during a score, in order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScoreTimeline {
+    /// The score.
+    pub score: Score,
+    /// The events of the score, oldest first.
+    pub moments: Vec<Moment>,
+    /// The minutes of fictive time elapsed so far.
+    pub elapsed: u32,
+    /// The flashbacks called for so far.
+    pub flashbacks: FlashbackUsage,
+}
+
+#[cfg(feature = "store")]
+impl ScoreTimeline {
+    fn new(score: Score) -> ScoreTimeline {
+        ScoreTimeline {
+            score,
+            moments: vec![Moment {
+                minute: 0,
+                event: Event::Started,
+            }],
+            elapsed: 0,
+            flashbacks: FlashbackUsage::default(),
+        }
+    }
+
+    /// Adds the event of the journal entry to the timeline, if it is one of the score's after its start.
+    fn push(&mut self, recorded: &Recorded) -> Result<()> {
+        let event = match recorded.kind.as_str() {
+            ELAPSED => {
+                let Logged { data, .. } = recorded.decode::<Logged<Elapsed>>()?;
+                self.elapsed = self.elapsed.saturating_add(data.minutes);
+                Event::Elapsed(data)
+            }
+            FLASHBACK => {
+                let Logged { data, .. } = recorded.decode::<Logged<Flashback>>()?;
+                self.flashbacks.add(data.severity);
+                Event::Flashback(recorded.entity, data)
+            }
+            ENDED => Event::Ended,
+            _ => return Ok(()),
+        };
+        self.moments.push(Moment { minute: self.elapsed, event });
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "store")]
+impl Campaign {
+    /// Starts a score against the target, marking it in the journal.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::ScoreInProgress`] if another score has not ended, [`CampaignError::Permission`] if the
+    /// actor is not the GM, or another [`CampaignError`] if the marker cannot be recorded.
+    pub async fn start_score(&mut self, actor: Actor, target: impl Into<String>) -> Result<Score> {
+        if let Some(score) = self.current_score().await? {
+            return Err(CampaignError::ScoreInProgress(score.number));
+        }
+
+        self.record(actor, None, STARTED, &Marker { target: target.into() }).await?;
+
+        self.current_score().await?.ok_or(CampaignError::NoScore)
+    }
+
+    /// Records fictive time passing during the score in progress. Returns the minutes elapsed since it started.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::NoScore`] if no score is in progress, [`CampaignError::Permission`] if the actor is not
+    /// the GM, or another [`CampaignError`] if the journal cannot be read or written.
+    pub async fn pass_time(&mut self, actor: Actor, minutes: u32, note: impl Into<String>) -> Result<u32> {
+        let score = self.current_score().await?.ok_or(CampaignError::NoScore)?;
+        let elapsed = Elapsed { minutes, note: note.into() };
+        self.record(actor, None, ELAPSED, &elapsed).await?;
+
+        Ok(self.timeline(score).await?.elapsed)
+    }
+
+    /// Records a flashback called for by the character during the score in progress, or by the crew as a whole if no
+    /// character is given. Returns the flashbacks called for since the score started.
+    ///
+    /// Only the flashback is recorded: the stress it costs is paid as any other.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::NoScore`] if no score is in progress, [`CampaignError::Permission`] if the actor does
+    /// not own the character, or is not the GM when no character is given, or another [`CampaignError`] if the journal
+    /// cannot be read or written.
+    pub async fn flashback(&mut self, actor: Actor, character: Option<Uuid>, flashback: &Flashback) -> Result<FlashbackUsage> {
+        let score = self.current_score().await?.ok_or(CampaignError::NoScore)?;
+        self.record(actor, character, FLASHBACK, flashback).await?;
+
+        Ok(self.timeline(score).await?.flashbacks)
+    }
+
+    /// Ends the score in progress. Returns its timeline.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::NoScore`] if no score is in progress, [`CampaignError::Permission`] if the actor is not
+    /// the GM, or another [`CampaignError`] if the journal cannot be read or written.
+    pub async fn end_score(&mut self, actor: Actor) -> Result<ScoreTimeline> {
+        let score = self.current_score().await?.ok_or(CampaignError::NoScore)?;
+        let ended = self.record(actor, None, ENDED, &()).await?;
+
+        self.timeline(Score { ended: Some(ended), ..score }).await
+    }
+
+    /// Returns every score started so far, oldest first.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CampaignError`] if the journal cannot be read or holds an invalid marker.
+    pub async fn scores(&mut self) -> Result<Vec<Score>> {
+        let mut markers = self.store().journal(None, STARTED, u32::MAX).await?;
+        markers.reverse();
+        let mut ends = self.store().journal(None, ENDED, u32::MAX).await?;
+        ends.reverse();
+
+        markers
+            .into_iter()
+            .enumerate()
+            .map(|(index, recorded)| {
+                let Logged { data, .. } = recorded.decode::<Logged<Marker>>()?;
+                Ok(Score {
+                    number: index + 1,
+                    target: data.target,
+                    seq: recorded.seq,
+                    ended: ends.iter().map(|end| end.seq).find(|&end| end > recorded.seq),
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the score in progress, if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CampaignError`] if the journal cannot be read or holds an invalid marker.
+    pub async fn current_score(&mut self) -> Result<Option<Score>> {
+        Ok(self.scores().await?.pop().filter(|score| score.ended.is_none()))
+    }
+
+    /// Returns the timeline of the score with the given number, or of the latest one if none is given.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::MissingScore`] if no such score was started, or another [`CampaignError`] if the
+    /// journal cannot be read or holds an invalid entry.
+    pub async fn score_timeline(&mut self, number: Option<usize>) -> Result<ScoreTimeline> {
+        let mut scores = self.scores().await?;
+        let score = match number {
+            Some(number) if (1..=scores.len()).contains(&number) => scores.swap_remove(number - 1),
+            Some(number) => return Err(CampaignError::MissingScore(number)),
+            None => scores.pop().ok_or(CampaignError::MissingScore(1))?,
+        };
+
+        self.timeline(score).await
+    }
+
+    /// Replays the journal entries of the score into its timeline.
+    pub(crate) async fn timeline(&mut self, score: Score) -> Result<ScoreTimeline> {
+        let entries = self.store().journal_between(score.seq, score.ended.map(|ended| ended + 1)).await?;
+
+        let mut timeline = ScoreTimeline::new(score);
+        for recorded in &entries {
+            timeline.push(recorded)?;
+        }
+
+        Ok(timeline)
+    }
+}
+
+#[cfg(all(test, feature = "store"))]
+mod tests {
+    use super::*;
+    use crate::{campaign::tests::bootstrap, character::Character, permission::PermissionError};
+
+    fn flashback(description: &str, severity: Severity) -> Flashback {
+        Flashback {
+            description: description.into(),
+            severity,
+        }
+    }
+
+    #[tokio::test]
+    async fn should_track_time_and_flashbacks_of_score() {
+        let player = Uuid::new_v4();
+        let mut campaign = bootstrap().await;
+        let pc = campaign
+            .enlist(Actor::Gm, player, &Character::new("Arcy"))
+            .await
+            .expect("should have enlisted character");
+
+        let score = campaign
+            .start_score(Actor::Gm, "The Bell Tower")
+            .await
+            .expect("should have started score");
+        assert_eq!(
+            CampaignError::ScoreInProgress(1).to_string(),
+            campaign
+                .start_score(Actor::Gm, "The Docks")
+                .await
+                .expect_err("should have refused second score")
+                .to_string()
+        );
+        let elapsed = campaign
+            .pass_time(Actor::Gm, 45, "Climbing the tower")
+            .await
+            .expect("should have passed time");
+        campaign
+            .flashback(Actor::Player(player), Some(pc), &flashback("Bribed the bell ringer", Severity::Complex))
+            .await
+            .expect("should have flashed back");
+        campaign
+            .pass_time(Actor::Gm, 20, "Picking the lock")
+            .await
+            .expect("should have passed time");
+        let usage = campaign
+            .flashback(Actor::Gm, None, &flashback("Left a rope on the roof", Severity::Simple))
+            .await
+            .expect("should have flashed back");
+        let timeline = campaign.end_score(Actor::Gm).await.expect("should have ended score");
+
+        assert_eq!((1, "The Bell Tower", None), (score.number, score.target.as_str(), score.ended));
+        assert_eq!(45, elapsed);
+        assert_eq!((2, [1, 1, 0], 1), (usage.count(), usage.by_severity, usage.stress));
+        assert_eq!((65, usage), (timeline.elapsed, timeline.flashbacks));
+        assert_eq!(
+            vec![
+                (0, "started".to_string()),
+                (45, "Climbing the tower (45 minutes)".to_string()),
+                (45, "flashback: Bribed the bell ringer (complex, 1 stress)".to_string()),
+                (65, "Picking the lock (20 minutes)".to_string()),
+                (65, "flashback: Left a rope on the roof (simple, 0 stress)".to_string()),
+                (65, "ended".to_string()),
+            ],
+            timeline
+                .moments
+                .iter()
+                .map(|moment| (moment.minute, moment.event.to_string()))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(timeline, campaign.score_timeline(Some(1)).await.expect("should have replayed timeline"));
+        assert_eq!(None, campaign.current_score().await.expect("should have read current score"));
+    }
+
+    #[tokio::test]
+    async fn should_keep_scores_apart() {
+        let mut campaign = bootstrap().await;
+        campaign
+            .start_score(Actor::Gm, "The Bell Tower")
+            .await
+            .expect("should have started score");
+        campaign.pass_time(Actor::Gm, 30, "Sneaking in").await.expect("should have passed time");
+        campaign.end_score(Actor::Gm).await.expect("should have ended score");
+        campaign.start_score(Actor::Gm, "The Docks").await.expect("should have started score");
+        campaign.pass_time(Actor::Gm, 10, "Rowing").await.expect("should have passed time");
+
+        let latest = campaign.score_timeline(None).await.expect("should have replayed timeline");
+
+        assert_eq!(
+            (2, "The Docks", 10, 2),
+            (latest.score.number, latest.score.target.as_str(), latest.elapsed, latest.moments.len())
+        );
+        assert_eq!(
+            Some(latest.score),
+            campaign.current_score().await.expect("should have read current score")
+        );
+    }
+
+    #[tokio::test]
+    async fn should_refuse_score_events_out_of_turn() {
+        let mut campaign = bootstrap().await;
+
+        let err = campaign.pass_time(Actor::Gm, 10, "Waiting").await.expect_err("should have refused time");
+        assert!(matches!(err, CampaignError::NoScore), "unexpected error: {err}");
+        let err = campaign.end_score(Actor::Gm).await.expect_err("should have refused end");
+        assert!(matches!(err, CampaignError::NoScore), "unexpected error: {err}");
+        let err = campaign.score_timeline(Some(2)).await.expect_err("should have refused timeline");
+        assert!(matches!(err, CampaignError::MissingScore(2)), "unexpected error: {err}");
+
+        let err = campaign
+            .start_score(Actor::Player(Uuid::new_v4()), "The Bell Tower")
+            .await
+            .expect_err("should have refused player");
+        assert!(
+            matches!(err, CampaignError::Permission(PermissionError::GmOnly(_))),
+            "unexpected error: {err}"
+        );
+    }
+}
@@ -0,0 +1,596 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! Content packs can express unusual special abilities and custom entanglements as [Rhai](https://rhai.rs) scripts.
+//!
+//! Scripts run in a sandbox: they cannot import modules or reach the file system, and are cut off after a bounded
+//! number of operations. They only see the context of the hook they run on and act through a `forge` object, which
+//! adjusts the roll about to be made and queues commands the campaign applies, checked, in a single transaction:
+//!
+//! ```rhai
+//! if action == "Skirmish" && rating < 2 {
+//!     forge.bonus_dice(1);
+//!     forge.take_stress(1);
+//! }
+//! ```
+//!
+//! Hacks rolling extra dice and keeping only some of them ask for keep and drop rules, e.g. `forge.drop_highest(1)`
+//! for a disadvantage, rather than adjusting the dice once rolled.
+use darkforge_data::descriptor::Descriptor;
+#[cfg(feature = "store")]
+use darkforge_data::store::{Content, World};
+#[cfg(feature = "store")]
+use rand::Rng;
+use rhai::{Array, Dynamic, Engine, EvalAltResult, Scope, module_resolvers::DummyModuleResolver};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::{action::KeepDrop, character::Stress, effect::Effect};
+#[cfg(feature = "store")]
+use crate::{
+    campaign::{Campaign, CampaignError, Result},
+    changeset::{Access, Changeset},
+    clock::Clock,
+    permission::Actor,
+    template,
+};
+
+/// Content pack category holding scripts.
+pub const CATEGORY: &str = "script";
+/// Kind of the journal entries recording the commands applied by a script.
+pub const APPLIED: &str = "script.applied";
+
+/// Number of operations after which a script is stopped.
+const MAX_OPERATIONS: u64 = 10_000;
+/// Number of commands a single run of a script can queue.
+const MAX_COMMANDS: usize = 16;
+/// Number of dice a script can add to or remove from a roll, and keep or drop with a single rule.
+const MAX_BONUS_DICE: i8 = 3;
+/// Number of keep and drop rules a single run of a script can add.
+const MAX_KEEP_DROP: usize = 4;
+
+/// Error type for script operations.
+#[derive(Error, Debug)]
+pub enum ScriptError {
+    /// No script with that id is loaded from the content pack.
+    #[error("script {0} does not exist")]
+    Missing(Uuid),
+    /// A script was run on a hook other than the one it is written for.
+    #[error("script {script} runs on {expected:?} hooks, not {found:?}")]
+    WrongHook {
+        /// The script.
+        script: Uuid,
+        /// The hook the script is written for.
+        expected: Hook,
+        /// The hook it was run on.
+        found: Hook,
+    },
+    /// A script does not compile.
+    #[error("failed to compile script {0}: {1}")]
+    Compile(Uuid, String),
+    /// A script failed while running, or exceeded the sandbox's limits.
+    #[error("script {0} failed: {1}")]
+    Runtime(Uuid, String),
+}
+
+/// The point in the rules a script hooks into.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Hook {
+    /// Before an action roll, e.g. a special ability granting bonus dice.
+    Action,
+    /// When rolling entanglements during downtime.
+    Entanglement,
+}
+
+/// A script as defined in a content pack.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Script {
+    /// The id of the script in the content pack.
+    pub id: Uuid,
+    /// The script's descriptor, e.g. the special ability it implements.
+    pub descriptor: Descriptor<'static>,
+    /// The hook the script runs on.
+    pub hook: Hook,
+    /// The Rhai source of the script.
+    pub source: String,
+}
+
+/// What a script is told about the hook it runs on.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Context {
+    /// An action roll about to be made, exposed to the script as `action`, `rating` and `effect`.
+    Action {
+        /// The label of the action rolled.
+        action: String,
+        /// The action rating.
+        rating: u8,
+        /// The effect level before the roll.
+        effect: Effect,
+    },
+    /// An entanglement roll, exposed to the script as `heat`, `wanted` and `dice`.
+    Entanglement {
+        /// The crew's heat.
+        heat: u8,
+        /// The crew's wanted level.
+        wanted: u8,
+        /// The dice kept on the entanglement roll.
+        dice: Vec<u8>,
+    },
+}
+
+impl Context {
+    /// Returns the hook the context is for.
+    #[must_use]
+    pub fn hook(&self) -> Hook {
+        match self {
+            Context::Action { .. } => Hook::Action,
+            Context::Entanglement { .. } => Hook::Entanglement,
+        }
+    }
+}
+
+/// A change to the campaign queued by a script.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Command {
+    /// The character takes stress, or clears it if negative.
+    Stress(i8),
+    /// A clock ticks.
+    Tick {
+        /// The clock entity.
+        clock: Uuid,
+        /// The number of segments to fill.
+        ticks: u8,
+    },
+    /// A note for the table, e.g. describing an entanglement.
+    Note(String),
+}
+
+/// What a script asks for: adjustments to the roll about to be made and commands to apply.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Plan {
+    /// The dice added to the pool, or removed if negative.
+    pub bonus_dice: i8,
+    /// The keep and drop rules to roll with, in order, see [`roll_pool_with`](crate::action::roll_pool_with).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keep_drop: Vec<KeepDrop>,
+    /// The levels the effect is shifted by.
+    pub effect_shift: i8,
+    /// The commands queued, in order.
+    pub commands: Vec<Command>,
+}
+
+impl Plan {
+    /// Returns the rating and effect to roll with once the plan's adjustments are applied.
+    #[must_use]
+    pub fn adjust(&self, rating: u8, effect: Effect) -> (u8, Effect) {
+        (rating.saturating_add_signed(self.bonus_dice), effect.shift(self.effect_shift))
+    }
+
+    fn queue(&mut self, command: Command) -> std::result::Result<(), Box<EvalAltResult>> {
+        if self.commands.len() >= MAX_COMMANDS {
+            return Err(format!("cannot queue more than {MAX_COMMANDS} commands").into());
+        }
+        self.commands.push(command);
+
+        Ok(())
+    }
+
+    fn bonus_dice(&mut self, dice: i64) -> std::result::Result<(), Box<EvalAltResult>> {
+        self.bonus_dice = i8::try_from(i64::from(self.bonus_dice) + dice)
+            .ok()
+            .filter(|total| (-MAX_BONUS_DICE..=MAX_BONUS_DICE).contains(total))
+            .ok_or_else(|| format!("bonus dice must stay between -{MAX_BONUS_DICE} and {MAX_BONUS_DICE}"))?;
+
+        Ok(())
+    }
+
+    fn keep_drop(&mut self, rule: fn(u8) -> KeepDrop, dice: i64) -> std::result::Result<(), Box<EvalAltResult>> {
+        let dice = u8::try_from(dice)
+            .ok()
+            .filter(|dice| (1..=MAX_BONUS_DICE.unsigned_abs()).contains(dice))
+            .ok_or_else(|| format!("keep and drop rules must take between 1 and {MAX_BONUS_DICE} dice"))?;
+        if self.keep_drop.len() >= MAX_KEEP_DROP {
+            return Err(format!("cannot add more than {MAX_KEEP_DROP} keep and drop rules").into());
+        }
+        self.keep_drop.push(rule(dice));
+
+        Ok(())
+    }
+
+    fn shift_effect(&mut self, levels: i64) {
+        self.effect_shift = i8::try_from(levels.clamp(-4, 4)).unwrap_or_default().saturating_add(self.effect_shift);
+    }
+
+    fn stress(&mut self, stress: i64) -> std::result::Result<(), Box<EvalAltResult>> {
+        let stress = i8::try_from(stress)
+            .ok()
+            .filter(|stress| stress.unsigned_abs() <= Stress::MAX)
+            .ok_or_else(|| format!("stress must be at most {}", Stress::MAX))?;
+
+        self.queue(Command::Stress(stress))
+    }
+
+    fn tick(&mut self, clock: &str, ticks: i64) -> std::result::Result<(), Box<EvalAltResult>> {
+        let clock = Uuid::parse_str(clock).map_err(|err| format!("invalid clock {clock}: {err}"))?;
+        let ticks = u8::try_from(ticks).map_err(|_| format!("invalid number of ticks {ticks}"))?;
+
+        self.queue(Command::Tick { clock, ticks })
+    }
+}
+
+/// The sandboxed engine scripts run in.
+pub struct Sandbox {
+    engine: Engine,
+}
+
+impl Default for Sandbox {
+    fn default() -> Self {
+        let mut engine = Engine::new();
+        engine
+            .set_module_resolver(DummyModuleResolver::new())
+            .set_max_operations(MAX_OPERATIONS)
+            .set_max_call_levels(16)
+            .set_max_expr_depths(32, 16)
+            .set_max_string_size(1024)
+            .set_max_array_size(64)
+            .set_max_map_size(64)
+            .disable_symbol("eval")
+            .on_print(|_| {})
+            .on_debug(|_, _, _| {});
+
+        engine
+            .register_type_with_name::<Plan>("Forge")
+            .register_fn("bonus_dice", Plan::bonus_dice)
+            .register_fn("drop_highest", |plan: &mut Plan, dice: i64| plan.keep_drop(KeepDrop::DropHighest, dice))
+            .register_fn("drop_lowest", |plan: &mut Plan, dice: i64| plan.keep_drop(KeepDrop::DropLowest, dice))
+            .register_fn("keep_highest", |plan: &mut Plan, dice: i64| plan.keep_drop(KeepDrop::KeepHighest, dice))
+            .register_fn("keep_lowest", |plan: &mut Plan, dice: i64| plan.keep_drop(KeepDrop::KeepLowest, dice))
+            .register_fn("shift_effect", Plan::shift_effect)
+            .register_fn("take_stress", |plan: &mut Plan, stress: i64| plan.stress(stress))
+            .register_fn("clear_stress", |plan: &mut Plan, stress: i64| plan.stress(-stress))
+            .register_fn("tick_clock", |plan: &mut Plan, clock: &str, ticks: i64| plan.tick(clock, ticks))
+            .register_fn("note", |plan: &mut Plan, note: &str| plan.queue(Command::Note(note.into())));
+
+        Sandbox { engine }
+    }
+}
+
+impl Sandbox {
+    /// Runs the script in the given context and returns what it asks for.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ScriptError::WrongHook`] if the context is not for the script's hook, or [`ScriptError::Compile`] or
+    /// [`ScriptError::Runtime`] if the script does not compile, fails or exceeds the sandbox's limits.
+    pub fn run(&self, script: &Script, context: &Context) -> std::result::Result<Plan, ScriptError> {
+        if script.hook != context.hook() {
+            return Err(ScriptError::WrongHook {
+                script: script.id,
+                expected: script.hook,
+                found: context.hook(),
+            });
+        }
+
+        let ast = self
+            .engine
+            .compile(&script.source)
+            .map_err(|err| ScriptError::Compile(script.id, err.to_string()))?;
+
+        let mut scope = Scope::new();
+        match context {
+            Context::Action { action, rating, effect } => {
+                scope.push_constant("action", action.clone());
+                scope.push_constant("rating", i64::from(*rating));
+                scope.push_constant("effect", format!("{effect:?}").to_lowercase());
+            }
+            Context::Entanglement { heat, wanted, dice } => {
+                scope.push_constant("heat", i64::from(*heat));
+                scope.push_constant("wanted", i64::from(*wanted));
+                scope.push_constant("dice", dice.iter().map(|&die| Dynamic::from(i64::from(die))).collect::<Array>());
+            }
+        }
+        scope.push("forge", Plan::default());
+
+        self.engine
+            .run_ast_with_scope(&mut scope, &ast)
+            .map_err(|err| ScriptError::Runtime(script.id, err.to_string()))?;
+
+        Ok(scope.get_value::<Plan>("forge").unwrap_or_default())
+    }
+}
+
+/// Journal entry recorded when the commands of a script are applied.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Applied {
+    /// The script run.
+    pub script: Uuid,
+    /// What the script asked for.
+    pub plan: Plan,
+}
+
+#[cfg(feature = "store")]
+impl Campaign {
+    /// Returns the scripts of the content pack running on the hook.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CampaignError`] if the content cannot be read.
+    pub async fn scripts(&mut self, hook: Hook) -> Result<Vec<Script>> {
+        let scripts = self.store().entries::<Script>(CATEGORY).await?;
+
+        Ok(scripts.into_iter().filter(|script| script.hook == hook).collect())
+    }
+
+    /// Runs a script of the content pack for the character in the sandbox, then applies the commands it queued along
+    /// with a journal entry in a single transaction. Notes are text templates, rendered with `rng` for the character,
+    /// see [`Campaign::bindings`]. The roll adjustments are returned for the caller to roll with, see [`Plan::adjust`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::Permission`] if the actor does not own the character, [`CampaignError::Script`] if the
+    /// script does not exist or fails, [`CampaignError::Template`] if it notes an invalid template,
+    /// [`CampaignError::MissingClock`] if it ticks a clock that does not exist, or another [`CampaignError`] if the
+    /// store cannot be read or written, in which case nothing is applied.
+    pub async fn run_script(
+        &mut self, actor: Actor, character: Uuid, script: Uuid, context: &Context, sandbox: &Sandbox, rng: &mut impl Rng,
+    ) -> Result<Plan> {
+        self.authorize(actor, character).await?;
+
+        let script = self
+            .store()
+            .entry::<Script>(CATEGORY, script)
+            .await?
+            .ok_or(ScriptError::Missing(script))?;
+        let mut plan = sandbox.run(&script, context)?;
+        if plan.commands.iter().any(|command| matches!(command, Command::Note(_))) {
+            let bindings = self.bindings(Some(character)).await?;
+            for command in &mut plan.commands {
+                if let Command::Note(note) = command {
+                    *note = template::render(note, &bindings, rng)?;
+                }
+            }
+        }
+
+        let store = self.store();
+
+        let mut stress = store.get::<Stress>(character).await?.unwrap_or_default().value();
+        let mut clocks = Vec::<(Uuid, Clock)>::new();
+        for command in &plan.commands {
+            match command {
+                Command::Stress(change) => stress = stress.saturating_add_signed(*change).min(Stress::MAX),
+                Command::Tick { clock: entity, ticks } => {
+                    let index = if let Some(index) = clocks.iter().position(|(clock, _)| clock == entity) {
+                        index
+                    } else {
+                        let loaded = store.get::<Clock>(*entity).await?.ok_or(CampaignError::MissingClock(*entity))?;
+                        clocks.push((*entity, loaded));
+                        clocks.len() - 1
+                    };
+                    clocks[index].1.tick(*ticks);
+                }
+                Command::Note(_) => {}
+            }
+        }
+
+        let mut changeset = Changeset::new(actor, Access::Owner(character)).insert(character, &Stress::new(stress))?;
+        for (entity, clock) in &clocks {
+            changeset = changeset.insert(*entity, clock)?;
+        }
+        let applied = Applied { script: script.id, plan };
+        self.apply(changeset.record(Some(character), APPLIED, &applied)?).await?;
+
+        Ok(applied.plan)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+    #[cfg(feature = "store")]
+    use uuid::uuid;
+
+    use super::*;
+    #[cfg(feature = "store")]
+    use crate::{campaign::tests::bootstrap, character::Character, permission::PermissionError};
+
+    #[cfg(feature = "store")]
+    const AMBUSH: Uuid = uuid!("fd27be20-5322-4d89-aeba-67e791c162c9");
+    #[cfg(feature = "store")]
+    const PUSH: Uuid = uuid!("579c2ce6-84ee-48c8-a778-80502a5b5ed1");
+    #[cfg(feature = "store")]
+    const ENTANGLEMENTS: Uuid = uuid!("3131c248-b9e8-4b6d-b1ab-021b0c9f4902");
+
+    fn script(hook: Hook, source: &str) -> Script {
+        Script {
+            id: Uuid::new_v4(),
+            descriptor: Descriptor::new(Uuid::new_v4(), "Test", "A test script."),
+            hook,
+            source: source.into(),
+        }
+    }
+
+    fn action(label: &str, rating: u8) -> Context {
+        Context::Action {
+            action: label.into(),
+            rating,
+            effect: Effect::Standard,
+        }
+    }
+
+    #[cfg(feature = "store")]
+    #[rstest]
+    #[case::ambushed("Skirmish", 1, (2, Effect::Standard))]
+    #[case::not_ambushed("Sway", 1, (1, Effect::Standard))]
+    #[tokio::test]
+    async fn should_adjust_roll_with_ability_script(#[case] label: &str, #[case] rating: u8, #[case] adjusted: (u8, Effect)) {
+        let mut campaign = bootstrap().await;
+        let pc = campaign
+            .enlist(Actor::Gm, Uuid::new_v4(), &Character::new("Arcy"))
+            .await
+            .expect("should have enlisted character");
+
+        let plan = campaign
+            .run_script(Actor::Gm, pc, AMBUSH, &action(label, rating), &Sandbox::default(), &mut rand::rng())
+            .await
+            .expect("should have run script");
+
+        assert_eq!(adjusted, plan.adjust(rating, Effect::Standard));
+    }
+
+    #[cfg(feature = "store")]
+    #[tokio::test]
+    async fn should_apply_script_commands() {
+        let player = Uuid::new_v4();
+        let mut campaign = bootstrap().await;
+        let pc = campaign
+            .enlist(Actor::Gm, player, &Character::new("Arcy"))
+            .await
+            .expect("should have enlisted character");
+
+        let plan = campaign
+            .run_script(
+                Actor::Player(player),
+                pc,
+                PUSH,
+                &action("Finesse", 2),
+                &Sandbox::default(),
+                &mut rand::rng(),
+            )
+            .await
+            .expect("should have run script");
+
+        assert_eq!(vec![Command::Stress(2)], plan.commands);
+        assert_eq!(Stress::new(2), campaign.sheet(pc).await.expect("should have loaded sheet").stress);
+        let recorded = campaign.store().journal(Some(pc), APPLIED, 10).await.expect("should have read journal");
+        assert_eq!(1, recorded.len());
+
+        let err = campaign
+            .run_script(
+                Actor::Player(Uuid::new_v4()),
+                pc,
+                PUSH,
+                &action("Finesse", 2),
+                &Sandbox::default(),
+                &mut rand::rng(),
+            )
+            .await
+            .expect_err("should have refused another player");
+        assert!(
+            matches!(err, CampaignError::Permission(PermissionError::NotOwner { .. })),
+            "unexpected error: {err}"
+        );
+    }
+
+    #[cfg(feature = "store")]
+    #[rstest]
+    #[case::cold(2, &[2], "Gang trouble or the usual suspects")]
+    #[case::warm(4, &[5, 1], "Rivals or cooperation")]
+    #[case::hot(7, &[6], "Show of force or unquiet dead")]
+    #[tokio::test]
+    async fn should_read_entanglement_from_script(#[case] heat: u8, #[case] dice: &[u8], #[case] note: &str) {
+        let mut campaign = bootstrap().await;
+        let scripts = campaign.scripts(Hook::Entanglement).await.expect("should have listed scripts");
+        let entanglements = scripts
+            .iter()
+            .find(|script| script.id == ENTANGLEMENTS)
+            .expect("should have entanglements");
+
+        let context = Context::Entanglement {
+            heat,
+            wanted: 1,
+            dice: dice.to_vec(),
+        };
+        let plan = Sandbox::default().run(entanglements, &context).expect("should have run script");
+
+        assert_eq!(vec![Command::Note(note.into())], plan.commands);
+    }
+
+    #[cfg(feature = "store")]
+    #[tokio::test]
+    async fn should_render_entanglement_notes_for_campaign() {
+        let player = Uuid::new_v4();
+        let mut campaign = bootstrap().await;
+        let pc = campaign
+            .enlist(Actor::Gm, player, &Character::new("Arcy"))
+            .await
+            .expect("should have enlisted character");
+        let factions = campaign.bindings(None).await.expect("should have bound placeholders");
+        let context = Context::Entanglement {
+            heat: 4,
+            wanted: 1,
+            dice: vec![3],
+        };
+
+        let plan = campaign
+            .run_script(Actor::Player(player), pc, ENTANGLEMENTS, &context, &Sandbox::default(), &mut rand::rng())
+            .await
+            .expect("should have run script");
+
+        let [Command::Note(note)] = plan.commands.as_slice() else {
+            panic!("should have noted one entanglement, got {:?}", plan.commands);
+        };
+        assert!(
+            factions
+                .values(template::FACTION)
+                .iter()
+                .any(|faction| *note == format!("Gang trouble or reprisals from {faction}")),
+            "unexpected note: {note}"
+        );
+    }
+
+    #[rstest]
+    #[case::syntax("forge.bonus_dice(", "failed to compile")]
+    #[case::runaway("loop { }", "Too many operations")]
+    #[case::too_many_dice("forge.bonus_dice(4);", "bonus dice must stay between")]
+    #[case::invalid_clock("forge.tick_clock(\"nope\", 1);", "invalid clock")]
+    #[case::too_many_kept("forge.keep_highest(4);", "keep and drop rules must take between")]
+    #[case::too_many_rules("for i in 0..5 { forge.drop_lowest(1); }", "cannot add more than")]
+    #[case::import("import \"secrets\" as s;", "Module not found")]
+    fn should_contain_misbehaving_scripts(#[case] source: &str, #[case] message: &str) {
+        let err = Sandbox::default()
+            .run(&script(Hook::Action, source), &action("Prowl", 2))
+            .expect_err("should have stopped script");
+
+        assert!(err.to_string().contains(message), "unexpected error: {err}");
+    }
+
+    #[test]
+    fn should_plan_keep_drop_rules() {
+        let plan = Sandbox::default()
+            .run(&script(Hook::Action, "forge.drop_highest(1); forge.keep_lowest(2);"), &action("Prowl", 2))
+            .expect("should have run script");
+
+        assert_eq!(vec![KeepDrop::DropHighest(1), KeepDrop::KeepLowest(2)], plan.keep_drop);
+    }
+
+    #[test]
+    fn should_refuse_script_for_other_hook() {
+        let err = Sandbox::default()
+            .run(&script(Hook::Entanglement, "forge.note(\"hi\");"), &action("Prowl", 2))
+            .expect_err("should have refused hook");
+
+        assert!(
+            matches!(
+                err,
+                ScriptError::WrongHook {
+                    expected: Hook::Entanglement,
+                    found: Hook::Action,
+                    ..
+                }
+            ),
+            "unexpected error: {err}"
+        );
+    }
+}
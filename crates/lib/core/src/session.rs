@@ -0,0 +1,725 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! Play sessions are delimited in the journal by the markers the GM records when starting one. Everything recorded
+//! between a marker and the next one belongs to that session and can be exported as a Markdown report, ready to post
+//! on a campaign wiki.
+//!
+//! A session's [`LuckReport`] compares each player's free rolls to their odds, for a bit of fun at the end of the night.
+#[cfg(feature = "store")]
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+
+#[cfg(feature = "store")]
+use darkforge_data::store::{Recorded, World};
+#[cfg(feature = "store")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "store")]
+use uuid::Uuid;
+
+#[cfg(feature = "store")]
+use crate::{
+    action::{Delta, Outcome},
+    campaign::{Campaign, CampaignError, Logged, Result},
+    character::Character,
+    clock::{Clock, Ticked},
+    consequence::ResolvedHarm,
+    contest::{Contest, Progress, Resolution},
+    downtime::{self, Activity, Applied},
+    faction::Faction,
+    gambling::Cheat,
+    information::Clue,
+    odds::{self, RollSpec},
+    roll::ROLLED,
+    schedule::Moment,
+    score::{self, Event, ScoreTimeline},
+};
+use crate::{permission::Actor, roll::Roll};
+
+/// Kind of the journal entries marking the start of a session.
+pub const STARTED: &str = "session.started";
+
+/// Journal entry recorded when a session starts.
+#[cfg(feature = "store")]
+#[derive(Serialize, Deserialize)]
+struct Marker {
+    title: String,
+}
+
+/// A play session, as marked in the journal.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Session {
+    /// The session's number, starting at 1.
+    pub number: usize,
+    /// The session's title.
+    pub title: String,
+    /// The sequence number of the journal entry marking its start.
+    pub seq: i64,
+    /// When the session started, in seconds since the Unix epoch.
+    pub started_at: i64,
+}
+
+/// A human-readable account of what happened during a session, displayed as Markdown.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SessionReport {
+    /// The session reported on.
+    pub session: Session,
+    /// The scores played out: action rolls, harm suffered, information gathered, clocks and contests.
+    pub scores: Vec<String>,
+    /// The fictive time passed and the flashbacks called for during the scores started, see [`ScoreTimeline`].
+    pub timeline: Vec<String>,
+    /// The free dice rolls made.
+    pub rolls: Vec<String>,
+    /// The downtime activities taken.
+    pub downtime: Vec<String>,
+}
+
+impl Display for SessionReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "# Session {}: {}", self.session.number, self.session.title)?;
+        for (heading, lines) in [
+            ("Scores", &self.scores),
+            ("Timeline", &self.timeline),
+            ("Rolls", &self.rolls),
+            ("Downtime", &self.downtime),
+        ] {
+            if heading == "Timeline" && lines.is_empty() {
+                continue;
+            }
+            writeln!(f, "\n## {heading}\n")?;
+            if lines.is_empty() {
+                writeln!(f, "_Nothing recorded._")?;
+            }
+            for line in lines {
+                writeln!(f, "- {line}")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Iterations simulated to estimate the odds of pools too large to compute exactly.
+#[cfg(feature = "store")]
+const SIMULATED: u32 = 10_000;
+
+/// How lucky the players were during a session.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LuckReport {
+    /// The session reported on.
+    pub session: Session,
+    /// The luck of each actor who rolled or spent stress, in the order they first did.
+    pub players: Vec<Luck>,
+}
+
+/// How lucky a player, or the GM, was during a session.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Luck {
+    /// The player, or the GM.
+    pub actor: Actor,
+    /// The number of free rolls made.
+    pub rolls: usize,
+    /// The chance their rolls had of a full or critical success on average, in percent.
+    pub expected: f64,
+    /// The share of their rolls that were full or critical successes, in percent.
+    pub actual: f64,
+    /// The roll least likely to turn out as well as it did.
+    pub luckiest: Option<Fluke>,
+    /// The roll least likely to turn out as badly as it did.
+    pub unluckiest: Option<Fluke>,
+    /// The stress paid before rolling and taken resisting consequences.
+    pub stress: u32,
+}
+
+/// A roll along with the chance it had to turn out as well, or as badly, as it did.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Fluke {
+    /// The roll.
+    pub roll: Roll,
+    /// The chance, in percent.
+    pub chance: f64,
+}
+
+#[cfg(feature = "store")]
+impl Luck {
+    fn new(actor: Actor) -> Luck {
+        Luck {
+            actor,
+            rolls: 0,
+            expected: 0.0,
+            actual: 0.0,
+            luckiest: None,
+            unluckiest: None,
+            stress: 0,
+        }
+    }
+
+    /// Counts a roll, keeping it if it is the luckiest or unluckiest so far.
+    #[expect(clippy::cast_precision_loss, reason = "A session holds far fewer rolls than an f64 counts exactly")]
+    fn roll(&mut self, roll: Roll) {
+        let spec = RollSpec::new(roll.rating);
+        let odds = odds::tabled(spec)
+            .or_else(|| odds::exact(spec))
+            .map_or_else(|| odds::simulate(spec, SIMULATED), |odds| odds.to_percent());
+        let (worse, better) = ALL.iter().fold((0.0, 0.0), |(worse, better), &outcome| {
+            let chance = odds.of(outcome);
+            (
+                if outcome <= roll.outcome { worse + chance } else { worse },
+                if outcome >= roll.outcome { better + chance } else { better },
+            )
+        });
+
+        let count = self.rolls as f64;
+        let succeeded = if roll.outcome >= Outcome::Success { 100.0 } else { 0.0 };
+        self.expected = (self.expected * count + odds.success + odds.critical) / (count + 1.0);
+        self.actual = (self.actual * count + succeeded) / (count + 1.0);
+        self.rolls += 1;
+
+        if self.luckiest.as_ref().is_none_or(|fluke| better < fluke.chance) {
+            self.luckiest = Some(Fluke {
+                roll: roll.clone(),
+                chance: better,
+            });
+        }
+        if self.unluckiest.as_ref().is_none_or(|fluke| worse < fluke.chance) {
+            self.unluckiest = Some(Fluke { roll, chance: worse });
+        }
+    }
+}
+
+/// Every outcome, from worst to best.
+#[cfg(feature = "store")]
+const ALL: [Outcome; 4] = [Outcome::Failure, Outcome::Partial, Outcome::Success, Outcome::Critical];
+
+#[cfg(feature = "store")]
+impl Campaign {
+    /// Starts a new session with the given title, marking it in the journal, then fires the triggers scheduled for the
+    /// next session, see [`Campaign::schedule`], and prunes the journal, see [`Campaign::prune`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::Permission`] if the actor is not the GM, or another [`CampaignError`] if the marker
+    /// cannot be recorded, a trigger cannot fire or the journal cannot be pruned.
+    pub async fn start_session(&mut self, actor: Actor, title: impl Into<String>) -> Result<Session> {
+        let marker = Marker { title: title.into() };
+        self.record(actor, None, STARTED, &marker).await?;
+        self.fire(actor, Moment::Session).await?;
+        self.prune(actor).await?;
+
+        let mut sessions = self.sessions().await?;
+        sessions.pop().ok_or(CampaignError::MissingSession(1))
+    }
+
+    /// Returns every session started so far, oldest first.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CampaignError`] if the journal cannot be read or holds an invalid marker.
+    pub async fn sessions(&mut self) -> Result<Vec<Session>> {
+        let mut markers = self.store().journal(None, STARTED, u32::MAX).await?;
+        markers.reverse();
+
+        markers
+            .into_iter()
+            .enumerate()
+            .map(|(index, recorded)| {
+                let Logged { data, .. } = recorded.decode::<Logged<Marker>>()?;
+                Ok(Session {
+                    number: index + 1,
+                    title: data.title,
+                    seq: recorded.seq,
+                    started_at: recorded.recorded_at,
+                })
+            })
+            .collect()
+    }
+
+    /// Reports on the session with the given number, or the latest one if none is given.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::MissingSession`] if no such session was started, or another [`CampaignError`] if the
+    /// journal cannot be read or holds an invalid entry.
+    pub async fn session_report(&mut self, number: Option<usize>) -> Result<SessionReport> {
+        let (session, next) = self.session(number).await?;
+
+        let mut report = SessionReport {
+            session,
+            scores: Vec::new(),
+            timeline: Vec::new(),
+            rolls: Vec::new(),
+            downtime: Vec::new(),
+        };
+        let mut names = Names::default();
+        for recorded in self.store().journal_between(report.session.seq, next).await? {
+            self.report(&mut report, &mut names, &recorded).await?;
+        }
+        for score in self.scores().await? {
+            if score.seq > report.session.seq && next.is_none_or(|next| score.seq < next) {
+                let timeline = self.timeline(score).await?;
+                self.timeline_lines(&mut report, &mut names, timeline).await?;
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Reports on how lucky each player was during the session with the given number, or the latest one if none is
+    /// given: their expected and actual success rates, their luckiest and unluckiest free rolls and the stress they
+    /// spent on actions.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::MissingSession`] if no such session was started, or another [`CampaignError`] if the
+    /// journal cannot be read or holds an invalid entry.
+    pub async fn luck_report(&mut self, number: Option<usize>) -> Result<LuckReport> {
+        let (session, next) = self.session(number).await?;
+
+        let mut players = Vec::<Luck>::new();
+        for recorded in self.store().journal_between(session.seq, next).await? {
+            let (actor, roll, stress) = match recorded.kind.as_str() {
+                kind if kind == ROLLED => {
+                    let Logged { actor, data } = recorded.decode::<Logged<Roll>>()?;
+                    (actor, Some(data), 0)
+                }
+                "action.resolved" => {
+                    let Logged { actor, data } = recorded.decode::<Logged<Delta>>()?;
+                    let resisted = data.resistances.iter().filter_map(|resistance| u8::try_from(resistance.stress).ok());
+                    (actor, None, resisted.map(u32::from).sum::<u32>() + u32::from(data.paid))
+                }
+                _ => continue,
+            };
+
+            let index = if let Some(index) = players.iter().position(|luck| luck.actor == actor) {
+                index
+            } else {
+                players.push(Luck::new(actor));
+                players.len() - 1
+            };
+            let luck = &mut players[index];
+            luck.stress += stress;
+            if let Some(roll) = roll {
+                luck.roll(roll);
+            }
+        }
+
+        Ok(LuckReport { session, players })
+    }
+
+    /// Returns the session with the given number, or the latest one, along with the sequence number of the next one's
+    /// marker, if any.
+    async fn session(&mut self, number: Option<usize>) -> Result<(Session, Option<i64>)> {
+        let sessions = self.sessions().await?;
+        let index = match number {
+            Some(number) if (1..=sessions.len()).contains(&number) => number - 1,
+            Some(number) => return Err(CampaignError::MissingSession(number)),
+            None => sessions.len().checked_sub(1).ok_or(CampaignError::MissingSession(1))?,
+        };
+        let next = sessions.get(index + 1).map(|next| next.seq);
+
+        Ok((sessions[index].clone(), next))
+    }
+
+    /// Adds a line to the report for the journal entry, if it is one reported on.
+    async fn report(&mut self, report: &mut SessionReport, names: &mut Names, recorded: &Recorded) -> Result<()> {
+        let entity = recorded.entity.unwrap_or_default();
+        match recorded.kind.as_str() {
+            "action.resolved" => {
+                let Logged { data, .. } = recorded.decode::<Logged<Delta>>()?;
+                let line = self.action_line(names, entity, data).await?;
+                report.scores.push(line);
+            }
+            "harm.suffered" => {
+                let Logged { data, .. } = recorded.decode::<Logged<ResolvedHarm>>()?;
+                let name = names.get(self, entity).await?;
+                let armor = if data.armor.is_some() { " after armor" } else { "" };
+                report.scores.push(match data.marked {
+                    0 => format!("**{name}** avoided harm{armor}"),
+                    marked => format!("**{name}** suffered level {} harm{armor}, marked at level {marked}", data.level),
+                });
+            }
+            "information.gathered" => {
+                let Logged { data, .. } = recorded.decode::<Logged<Clue>>()?;
+                report.scores.push(format!(
+                    "**{}** gathered information on \"{}\": {} detail",
+                    names.get(self, entity).await?,
+                    data.text,
+                    format!("{:?}", data.detail).to_lowercase()
+                ));
+            }
+            "clock.ticked" => {
+                let Logged { data, .. } = recorded.decode::<Logged<Ticked>>()?;
+                let clock = &data.clock;
+                report.scores.push(format!(
+                    "**{}** ticked by {} ({}/{})",
+                    clock.label(),
+                    data.ticked,
+                    clock.filled(),
+                    clock.segments()
+                ));
+            }
+            "contest.advanced" => {
+                let Logged { data, .. } = recorded.decode::<Logged<Progress>>()?;
+                let contest = names.get(self, entity).await?;
+                report
+                    .scores
+                    .push(format!("**{contest}** advanced by {} and {}", data.ticks[0], data.ticks[1]));
+            }
+            "contest.resolved" => {
+                let Logged { data, .. } = recorded.decode::<Logged<Resolution>>()?;
+                report.scores.push(format!(
+                    "**{}** won {} against {}",
+                    names.get(self, data.winner).await?,
+                    data.label,
+                    names.get(self, data.loser).await?
+                ));
+            }
+            kind if kind == ROLLED => {
+                let Logged { actor, data } = recorded.decode::<Logged<Roll>>()?;
+                let who = match (recorded.entity, actor) {
+                    (Some(character), _) => names.get(self, character).await?,
+                    (None, Actor::Gm) => "GM".to_string(),
+                    (None, player) => player.to_string(),
+                };
+                let dice = data.dice.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ");
+                report.rolls.push(format!(
+                    "**{who}** rolled {} ({}d): {dice}, {}",
+                    data.label,
+                    data.rating,
+                    outcome(data.outcome)
+                ));
+            }
+            "downtime.activity" => {
+                let Logged { data, .. } = recorded.decode::<Logged<downtime::Outcome>>()?;
+                let line = self.downtime_line(names, entity, data).await?;
+                report.downtime.push(line);
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// Adds a line to the report for each moment of the score's timeline.
+    async fn timeline_lines(&mut self, report: &mut SessionReport, names: &mut Names, timeline: ScoreTimeline) -> Result<()> {
+        for score::Moment { minute, event } in timeline.moments {
+            let who = match &event {
+                Event::Flashback(Some(character), _) => format!("{}'s ", names.get(self, *character).await?),
+                _ => String::new(),
+            };
+            report
+                .timeline
+                .push(format!("**{}** at {}: {who}{event}", timeline.score.target, score::clock_time(minute)));
+        }
+
+        Ok(())
+    }
+
+    /// Describes an action roll resolved by the character.
+    async fn action_line(&mut self, names: &mut Names, character: Uuid, delta: Delta) -> Result<String> {
+        let mut parts = vec![format!(
+            "**{}** rolled {} with {} effect",
+            names.get(self, character).await?,
+            outcome(delta.outcome),
+            format!("{:?}", delta.effect).to_lowercase()
+        )];
+        parts.extend(delta.harm.iter().map(|level| format!("marked harm at level {level}")));
+        if delta.trauma {
+            parts.push("suffered trauma".to_string());
+        }
+        for (clock, ticked) in delta.clocks {
+            parts.push(format!("ticked {} by {ticked}", names.get(self, clock).await?));
+        }
+
+        Ok(parts.join(", "))
+    }
+
+    /// Describes a downtime activity taken by the character.
+    async fn downtime_line(&mut self, names: &mut Names, character: Uuid, outcome: downtime::Outcome) -> Result<String> {
+        let activity = match outcome.activity {
+            Activity::IndulgeVice => "indulged their vice".to_string(),
+            Activity::Gamble { wager } => format!("gambled {} coin", wager.stake),
+            Activity::Recover { .. } => "recovered".to_string(),
+            Activity::ReduceHeat { .. } => "lay low".to_string(),
+            Activity::Project { clock, .. } => format!("worked on {}", names.get(self, clock).await?),
+        };
+        let applied = match outcome.applied {
+            Applied::StressCleared { cleared, overindulged } => {
+                format!("cleared {cleared} stress{}", if overindulged { ", overindulging" } else { "" })
+            }
+            Applied::Gambled { cleared, overindulged, game } => format!(
+                "cleared {cleared} stress{}, {} {} coin{}",
+                if overindulged { ", overindulging" } else { "" },
+                if game.won() { "winning" } else { "losing" },
+                game.winnings.unsigned_abs(),
+                match game.cheat {
+                    Some(Cheat::Caught) => ", caught cheating",
+                    Some(Cheat::Suspected) => ", suspected of cheating",
+                    Some(Cheat::Unnoticed) | None => "",
+                }
+            ),
+            Applied::Healed { ticked, healed } => format!("ticked healing by {ticked}{}", if healed { ", healing their harm" } else { "" }),
+            Applied::HeatReduced { reduced } => format!("reduced heat by {reduced}"),
+            Applied::ProjectTicked { ticked, complete } => format!("ticked it by {ticked}{}", if complete { ", completing it" } else { "" }),
+        };
+
+        Ok(format!("**{}** {activity}: {applied}", names.get(self, character).await?))
+    }
+}
+
+/// Describes an outcome in a sentence.
+#[cfg(feature = "store")]
+fn outcome(outcome: Outcome) -> &'static str {
+    match outcome {
+        Outcome::Failure => "a failure",
+        Outcome::Partial => "a partial success",
+        Outcome::Success => "a full success",
+        Outcome::Critical => "a critical success",
+    }
+}
+
+/// Cache of the names of the entities mentioned in a report.
+#[cfg(feature = "store")]
+#[derive(Default)]
+struct Names(HashMap<Uuid, String>);
+
+#[cfg(feature = "store")]
+impl Names {
+    /// Returns the name of a character, faction, clock or contest, or its id if it has none.
+    async fn get(&mut self, campaign: &mut Campaign, entity: Uuid) -> Result<String> {
+        if let Some(name) = self.0.get(&entity) {
+            return Ok(name.clone());
+        }
+
+        let store = campaign.store();
+        let name = if let Some(character) = store.get::<Character>(entity).await? {
+            character.name().to_string()
+        } else if let Some(faction) = store.get::<Faction>(entity).await? {
+            faction.descriptor().label().to_string()
+        } else if let Some(clock) = store.get::<Clock>(entity).await? {
+            clock.label().to_string()
+        } else if let Some(contest) = store.get::<Contest>(entity).await? {
+            contest.label().to_string()
+        } else {
+            entity.to_string()
+        };
+        self.0.insert(entity, name.clone());
+
+        Ok(name)
+    }
+}
+
+#[cfg(all(test, feature = "store"))]
+mod tests {
+    use super::*;
+    use crate::{
+        action::{ActionRoll, tests::Loaded},
+        campaign::tests::bootstrap,
+        effect::Effect,
+        permission::PermissionError,
+        score::{Flashback, Severity},
+    };
+
+    #[tokio::test]
+    async fn should_number_sessions_in_order() {
+        let mut campaign = bootstrap().await;
+
+        let err = campaign
+            .start_session(Actor::Player(Uuid::new_v4()), "The Bell Tower")
+            .await
+            .expect_err("should have refused player");
+        assert!(
+            matches!(err, CampaignError::Permission(PermissionError::GmOnly(_))),
+            "unexpected error: {err}"
+        );
+
+        let first = campaign
+            .start_session(Actor::Gm, "The Bell Tower")
+            .await
+            .expect("should have started session");
+        let second = campaign.start_session(Actor::Gm, "Aftermath").await.expect("should have started session");
+
+        assert_eq!((1, "The Bell Tower"), (first.number, first.title.as_str()));
+        assert_eq!((2, "Aftermath"), (second.number, second.title.as_str()));
+        assert_eq!(vec![first, second], campaign.sessions().await.expect("should have listed sessions"));
+    }
+
+    #[tokio::test]
+    async fn should_report_events_of_session() {
+        let mut campaign = bootstrap().await;
+        let pc = campaign
+            .enlist(Actor::Gm, Uuid::new_v4(), &Character::new("Arcy"))
+            .await
+            .expect("should have enlisted character");
+        let alarm = campaign
+            .add_clock(Actor::Gm, None, &Clock::new("Alarm", 4).expect("should have created clock"))
+            .await
+            .expect("should have added clock");
+
+        campaign
+            .roll(Actor::Gm, None, "Before", 1, &Loaded::new(&[6]))
+            .await
+            .expect("should have rolled");
+        campaign
+            .start_session(Actor::Gm, "The Bell Tower")
+            .await
+            .expect("should have started session");
+        campaign
+            .roll(Actor::Gm, Some(pc), "Prowl", 2, &Loaded::new(&[4, 5]))
+            .await
+            .expect("should have rolled");
+        campaign.tick_clock(Actor::Gm, alarm, 2).await.expect("should have ticked clock");
+        campaign
+            .downtime(Actor::Gm, pc, Activity::IndulgeVice, &Loaded::new(&[2, 3]))
+            .await
+            .expect("should have indulged vice");
+        campaign.start_session(Actor::Gm, "Aftermath").await.expect("should have started session");
+        campaign
+            .roll(Actor::Gm, None, "After", 1, &Loaded::new(&[1]))
+            .await
+            .expect("should have rolled");
+
+        let report = campaign.session_report(Some(1)).await.expect("should have reported session");
+
+        assert_eq!(vec!["**Alarm** ticked by 2 (2/4)"], report.scores);
+        assert_eq!(vec!["**Arcy** rolled Prowl (2d): 4, 5, a partial success"], report.rolls);
+        assert_eq!(vec!["**Arcy** indulged their vice: cleared 0 stress, overindulging"], report.downtime);
+        assert_eq!(
+            "# Session 1: The Bell Tower\n\n\
+             ## Scores\n\n- **Alarm** ticked by 2 (2/4)\n\n\
+             ## Rolls\n\n- **Arcy** rolled Prowl (2d): 4, 5, a partial success\n\n\
+             ## Downtime\n\n- **Arcy** indulged their vice: cleared 0 stress, overindulging\n",
+            report.to_string()
+        );
+
+        let latest = campaign.session_report(None).await.expect("should have reported session");
+        assert_eq!(2, latest.session.number);
+        assert_eq!(vec!["**GM** rolled After (1d): 1, a failure"], latest.rolls);
+    }
+
+    #[tokio::test]
+    async fn should_report_score_timeline_of_session() {
+        let mut campaign = bootstrap().await;
+        let pc = campaign
+            .enlist(Actor::Gm, Uuid::new_v4(), &Character::new("Arcy"))
+            .await
+            .expect("should have enlisted character");
+        campaign
+            .start_session(Actor::Gm, "The Bell Tower")
+            .await
+            .expect("should have started session");
+        campaign
+            .start_score(Actor::Gm, "The Bell Tower")
+            .await
+            .expect("should have started score");
+        campaign
+            .pass_time(Actor::Gm, 75, "Climbing the tower")
+            .await
+            .expect("should have passed time");
+        let flashback = Flashback {
+            description: "Bribed the bell ringer".into(),
+            severity: Severity::Elaborate,
+        };
+        campaign
+            .flashback(Actor::Gm, Some(pc), &flashback)
+            .await
+            .expect("should have flashed back");
+
+        let report = campaign.session_report(None).await.expect("should have reported session");
+
+        assert_eq!(
+            vec![
+                "**The Bell Tower** at 0:00: started",
+                "**The Bell Tower** at 1:15: Climbing the tower (75 minutes)",
+                "**The Bell Tower** at 1:15: Arcy's flashback: Bribed the bell ringer (elaborate, 2 stress)",
+            ],
+            report.timeline
+        );
+        assert!(
+            report.to_string().contains("## Timeline\n\n- **The Bell Tower** at 0:00: started\n"),
+            "should have rendered timeline: {report}"
+        );
+    }
+
+    #[tokio::test]
+    async fn should_report_luck_of_players() {
+        let player = Uuid::new_v4();
+        let mut campaign = bootstrap().await;
+        let pc = campaign
+            .enlist(Actor::Gm, player, &Character::new("Arcy"))
+            .await
+            .expect("should have enlisted character");
+        campaign
+            .start_session(Actor::Gm, "The Bell Tower")
+            .await
+            .expect("should have started session");
+        for (label, die) in [("Prowl", 6), ("Skirmish", 2), ("Sway", 3)] {
+            campaign
+                .roll(Actor::Player(player), Some(pc), label, 1, &Loaded::new(&[die]))
+                .await
+                .expect("should have rolled");
+        }
+        campaign
+            .roll(Actor::Gm, None, "Alarm", 2, &Loaded::new(&[1, 6]))
+            .await
+            .expect("should have rolled");
+        let pushed = ActionRoll {
+            dice: vec![6],
+            effect: Effect::Standard,
+            stress: 2,
+            context: None,
+            modifiers: Vec::new(),
+        };
+        campaign
+            .resolve_action(Actor::Player(player), pc, &pushed, Vec::new(), &Loaded::new(&[]))
+            .await
+            .expect("should have resolved action");
+
+        let report = campaign.luck_report(None).await.expect("should have reported luck");
+
+        let close = |expected: f64, actual: f64| (expected - actual).abs() < 0.01;
+        assert_eq!(
+            vec![Actor::Player(player), Actor::Gm],
+            report.players.iter().map(|luck| luck.actor).collect::<Vec<_>>()
+        );
+        let luck = &report.players[0];
+        assert_eq!((3, 2), (luck.rolls, luck.stress));
+        assert!(close(100.0 / 6.0, luck.expected), "unexpected expected rate: {}", luck.expected);
+        assert!(close(100.0 / 3.0, luck.actual), "unexpected actual rate: {}", luck.actual);
+        let luckiest = luck.luckiest.as_ref().expect("should have a luckiest roll");
+        assert_eq!("Prowl", luckiest.roll.label);
+        assert!(close(100.0 / 6.0, luckiest.chance), "unexpected chance: {}", luckiest.chance);
+        let unluckiest = luck.unluckiest.as_ref().expect("should have an unluckiest roll");
+        assert_eq!("Skirmish", unluckiest.roll.label);
+        assert!(close(50.0, unluckiest.chance), "unexpected chance: {}", unluckiest.chance);
+        assert_eq!((1, 0), (report.players[1].rolls, report.players[1].stress));
+    }
+
+    #[rstest::rstest]
+    #[case::none(None)]
+    #[case::unknown(Some(3))]
+    #[case::zero(Some(0))]
+    #[tokio::test]
+    async fn should_fail_to_report_missing_session(#[case] number: Option<usize>) {
+        let mut campaign = bootstrap().await;
+        if number.is_some() {
+            campaign
+                .start_session(Actor::Gm, "The Bell Tower")
+                .await
+                .expect("should have started session");
+        }
+
+        let err = campaign.session_report(number).await.expect_err("should have failed to report");
+
+        assert!(matches!(err, CampaignError::MissingSession(_)), "unexpected error: {err}");
+    }
+}
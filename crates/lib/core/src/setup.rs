@@ -0,0 +1,382 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! Session zero decisions, agreed on when a campaign starts and amended as play goes on.
+//!
+//! The [`Setup`] of a campaign holds the tone the table aims for, the lines and veils it agreed on, and the house rules
+//! in play. Anyone at the table may draw a line or a veil, while only the GM may set the tone, lift a line or a veil, or
+//! toggle a house rule. Rules a table may opt out of check their toggle with [`Campaign::house_rule`].
+
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+
+#[cfg(feature = "store")]
+use darkforge_data::store::World as _;
+use darkforge_data::{Component, Persisted};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[cfg(feature = "store")]
+use crate::{
+    campaign::{Campaign, Result},
+    changeset::{Access, Changeset},
+    permission::Actor,
+};
+
+/// Kind of the journal entries recorded when the setup is amended.
+pub const AMENDED: &str = "setup.amended";
+
+/// Error type for house rules.
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum SetupError {
+    /// No house rule has that name.
+    #[error("unknown house rule {0}")]
+    UnknownHouseRule(String),
+    /// The table opted out of the house rule.
+    #[error("house rule {0} is not in play")]
+    NotInPlay(HouseRule),
+}
+
+/// A rule the table may opt in or out of.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum HouseRule {
+    /// The GM may overrule dice before rolls are recorded, see
+    /// [`PendingRoll::overrule`](crate::roll::PendingRoll::overrule).
+    GmFudging,
+    /// Dice rolled at the table may be entered by hand, see [`Campaign::record_manual_roll`].
+    ManualDice,
+}
+
+impl HouseRule {
+    /// Every house rule.
+    pub const ALL: [HouseRule; 2] = [HouseRule::GmFudging, HouseRule::ManualDice];
+
+    /// Returns the name of the house rule, e.g. `gm_fudging`.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            HouseRule::GmFudging => "gm_fudging",
+            HouseRule::ManualDice => "manual_dice",
+        }
+    }
+
+    /// Returns whether the house rule is in play until the table decides otherwise.
+    #[must_use]
+    pub fn default_in_play(self) -> bool {
+        match self {
+            HouseRule::GmFudging | HouseRule::ManualDice => true,
+        }
+    }
+}
+
+impl Display for HouseRule {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for HouseRule {
+    type Err = SetupError;
+
+    fn from_str(name: &str) -> std::result::Result<HouseRule, SetupError> {
+        let name = name.trim().to_ascii_lowercase().replace([' ', '-'], "_");
+
+        HouseRule::ALL
+            .into_iter()
+            .find(|rule| rule.name() == name)
+            .ok_or(SetupError::UnknownHouseRule(name))
+    }
+}
+
+/// The decisions of session zero.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Setup {
+    /// The tone the table aims for, e.g. "grim and desperate, with moments of gallows humor".
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub tone: String,
+    /// Content kept out of the game entirely.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub lines: Vec<String>,
+    /// Content that may happen in the game but only off screen.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub veils: Vec<String>,
+    /// The house rules toggled away from their default, see [`HouseRule::default_in_play`].
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub house_rules: BTreeMap<HouseRule, bool>,
+}
+
+impl Setup {
+    /// Returns whether the house rule is in play.
+    #[must_use]
+    pub fn in_play(&self, rule: HouseRule) -> bool {
+        self.house_rules.get(&rule).copied().unwrap_or_else(|| rule.default_in_play())
+    }
+
+    /// Applies the amendment. Drawing a line or a veil the table already agreed on changes nothing.
+    pub fn amend(&mut self, amendment: Amendment) {
+        match amendment {
+            Amendment::Tone(tone) => self.tone = tone,
+            Amendment::Line(line) if !self.lines.contains(&line) => self.lines.push(line),
+            Amendment::Veil(veil) if !self.veils.contains(&veil) => self.veils.push(veil),
+            Amendment::Line(_) | Amendment::Veil(_) => {}
+            Amendment::Lift(content) => {
+                self.lines.retain(|line| *line != content);
+                self.veils.retain(|veil| *veil != content);
+            }
+            Amendment::HouseRule { rule, in_play } if in_play == rule.default_in_play() => {
+                self.house_rules.remove(&rule);
+            }
+            Amendment::HouseRule { rule, in_play } => {
+                self.house_rules.insert(rule, in_play);
+            }
+        }
+    }
+}
+
+impl Component for Setup {}
+
+impl Persisted for Setup {
+    const KIND: &'static str = "setup";
+}
+
+/// A change to the decisions of session zero.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Amendment {
+    /// Sets the tone.
+    Tone(String),
+    /// Draws a line.
+    Line(String),
+    /// Draws a veil.
+    Veil(String),
+    /// Lifts a line or a veil.
+    Lift(String),
+    /// Puts a house rule in or out of play.
+    HouseRule {
+        /// The house rule toggled.
+        rule: HouseRule,
+        /// Whether the house rule is in play.
+        in_play: bool,
+    },
+}
+
+impl Amendment {
+    /// Returns whether only the GM may make the amendment, anyone at the table being able to draw lines and veils.
+    #[must_use]
+    pub fn is_gm_only(&self) -> bool {
+        !matches!(self, Amendment::Line(_) | Amendment::Veil(_))
+    }
+}
+
+#[cfg(feature = "store")]
+impl Campaign {
+    /// Returns the decisions of session zero, the defaults if none were made.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CampaignError`](crate::campaign::CampaignError) if the store cannot be read.
+    pub async fn setup(&mut self) -> Result<Setup> {
+        let id = self.id();
+
+        Ok(self.store().get::<Setup>(id).await?.unwrap_or_default())
+    }
+
+    /// Returns whether the house rule is in play.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CampaignError`](crate::campaign::CampaignError) if the store cannot be read.
+    pub async fn house_rule(&mut self, rule: HouseRule) -> Result<bool> {
+        Ok(self.setup().await?.in_play(rule))
+    }
+
+    /// Replaces the decisions of session zero, e.g. once the table has filled in the questionnaire.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::Permission`](crate::campaign::CampaignError::Permission) if the actor is not the GM, or
+    /// another [`CampaignError`](crate::campaign::CampaignError) if the store cannot be written.
+    pub async fn set_setup(&mut self, actor: Actor, setup: &Setup) -> Result<()> {
+        actor.require_gm()?;
+
+        let id = self.id();
+        let changeset = Changeset::new(actor, Access::Gm).insert(id, setup)?.record(Some(id), AMENDED, setup)?;
+        self.apply(changeset).await?;
+
+        Ok(())
+    }
+
+    /// Amends the decisions of session zero and returns them as amended.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::Permission`](crate::campaign::CampaignError::Permission) if the amendment is GM only and
+    /// the actor is a player, see [`Amendment::is_gm_only`], or another
+    /// [`CampaignError`](crate::campaign::CampaignError) if the store cannot be read or written.
+    pub async fn amend_setup(&mut self, actor: Actor, amendment: Amendment) -> Result<Setup> {
+        let access = if amendment.is_gm_only() {
+            actor.require_gm()?;
+            Access::Gm
+        } else {
+            Access::Anyone
+        };
+
+        let id = self.id();
+        let mut setup = self.setup().await?;
+        let changeset = Changeset::new(actor, access).record(Some(id), AMENDED, &amendment)?;
+        setup.amend(amendment);
+        self.apply(changeset.insert(id, &setup)?).await?;
+
+        Ok(setup)
+    }
+
+    /// Checks that the house rule is in play.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::Setup`](crate::campaign::CampaignError::Setup) if the table opted out of the house rule.
+    pub(crate) async fn require_house_rule(&mut self, rule: HouseRule) -> Result<()> {
+        if self.house_rule(rule).await? {
+            Ok(())
+        } else {
+            Err(SetupError::NotInPlay(rule).into())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+    #[cfg(feature = "store")]
+    use uuid::Uuid;
+
+    use super::*;
+    #[cfg(feature = "store")]
+    use crate::{
+        action::tests::Loaded,
+        campaign::{CampaignError, tests::bootstrap},
+        context::RollContext,
+    };
+
+    #[rstest]
+    #[case::name("manual_dice", Ok(HouseRule::ManualDice))]
+    #[case::spaced("GM fudging", Ok(HouseRule::GmFudging))]
+    #[case::unknown("stress_relief", Err(SetupError::UnknownHouseRule("stress_relief".into())))]
+    fn should_parse_house_rules(#[case] name: &str, #[case] expected: std::result::Result<HouseRule, SetupError>) {
+        assert_eq!(expected, name.parse::<HouseRule>());
+    }
+
+    #[test]
+    fn should_amend_setup() {
+        let mut setup = Setup::default();
+
+        setup.amend(Amendment::Tone("grim".into()));
+        setup.amend(Amendment::Line("harm to children".into()));
+        setup.amend(Amendment::Line("harm to children".into()));
+        setup.amend(Amendment::Veil("torture".into()));
+        setup.amend(Amendment::HouseRule {
+            rule: HouseRule::GmFudging,
+            in_play: false,
+        });
+        assert_eq!(
+            (vec!["harm to children".to_owned()], vec!["torture".to_owned()]),
+            (setup.lines.clone(), setup.veils.clone())
+        );
+        assert!(!setup.in_play(HouseRule::GmFudging));
+        assert!(setup.in_play(HouseRule::ManualDice));
+
+        setup.amend(Amendment::Lift("torture".into()));
+        setup.amend(Amendment::HouseRule {
+            rule: HouseRule::GmFudging,
+            in_play: true,
+        });
+        assert_eq!(
+            Setup {
+                tone: "grim".into(),
+                lines: vec!["harm to children".into()],
+                ..Setup::default()
+            },
+            setup
+        );
+    }
+
+    #[cfg(feature = "store")]
+    #[tokio::test]
+    async fn should_let_anyone_draw_lines_but_only_gm_toggle_house_rules() {
+        let mut campaign = bootstrap().await;
+        let player = Actor::Player(Uuid::new_v4());
+
+        campaign
+            .amend_setup(player, Amendment::Veil("torture".into()))
+            .await
+            .expect("should have drawn veil");
+        let err = campaign
+            .amend_setup(player, Amendment::Lift("torture".into()))
+            .await
+            .expect_err("should have refused player");
+        assert!(matches!(err, CampaignError::Permission(_)), "unexpected error {err:?}");
+        let setup = campaign
+            .amend_setup(
+                Actor::Gm,
+                Amendment::HouseRule {
+                    rule: HouseRule::ManualDice,
+                    in_play: false,
+                },
+            )
+            .await
+            .expect("should have toggled house rule");
+
+        assert_eq!(setup, campaign.setup().await.expect("should have read setup"));
+        assert_eq!(vec!["torture".to_owned()], setup.veils);
+        assert!(!campaign.house_rule(HouseRule::ManualDice).await.expect("should have read house rule"));
+    }
+
+    #[cfg(feature = "store")]
+    #[tokio::test]
+    async fn should_refuse_rolls_of_house_rules_out_of_play() {
+        let mut campaign = bootstrap().await;
+        campaign
+            .set_setup(
+                Actor::Gm,
+                &Setup {
+                    house_rules: BTreeMap::from([(HouseRule::GmFudging, false), (HouseRule::ManualDice, false)]),
+                    ..Setup::default()
+                },
+            )
+            .await
+            .expect("should have set setup");
+
+        let err = campaign
+            .record_manual_roll(RollContext::new(Actor::Gm), None, "Fortune", 1, vec![6])
+            .await
+            .expect_err("should have refused manual dice");
+        assert!(
+            matches!(err, CampaignError::Setup(SetupError::NotInPlay(HouseRule::ManualDice))),
+            "unexpected error {err:?}"
+        );
+
+        let mut pending = campaign.pending_roll(RollContext::new(Actor::Gm), None, "Fortune", 1, &Loaded::new(&[2]));
+        pending.overrule(Actor::Gm, vec![6]).expect("should have overruled roll");
+        let err = campaign.commit_roll(pending).await.expect_err("should have refused fudged roll");
+        assert!(
+            matches!(err, CampaignError::Setup(SetupError::NotInPlay(HouseRule::GmFudging))),
+            "unexpected error {err:?}"
+        );
+
+        let pending = campaign.pending_roll(RollContext::new(Actor::Gm), None, "Fortune", 1, &Loaded::new(&[2]));
+        campaign.commit_roll(pending).await.expect("should have committed natural roll");
+    }
+}
@@ -0,0 +1,287 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! Save slots: the campaigns held in a store, listed with when they were last played and for how long, so games can
+//! offer to load, duplicate or delete them without keeping slots of their own.
+//!
+//! A slot is created with [`Campaign::create`] and loaded with [`Campaign::switch`]. Duplicates are made from an export
+//! of the campaign, see [`Campaign::export`], so they hold its roster and free clocks while its factions start afresh
+//! from the content pack.
+
+use std::time::Duration;
+
+#[cfg(feature = "store")]
+use darkforge_data::store::sql::sqlite::SqliteStore;
+use darkforge_data::{Component, Persisted};
+#[cfg(feature = "store")]
+use darkforge_data::{pack::Pack, store::World};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::campaign::CampaignInfo;
+#[cfg(feature = "store")]
+use crate::{
+    campaign::{Campaign, CampaignError, Result},
+    faction::{self, FactionTemplate},
+    permission::Actor,
+};
+
+/// The time spent playing a campaign, in seconds, attached to the campaign entity.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct Playtime(u64);
+
+impl Playtime {
+    /// Returns the time spent playing.
+    #[must_use]
+    pub fn duration(self) -> Duration {
+        Duration::from_secs(self.0)
+    }
+}
+
+impl Component for Playtime {}
+
+impl Persisted for Playtime {
+    const KIND: &'static str = "playtime";
+}
+
+/// A campaign of the store, as listed in a save slot screen.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SaveSlot {
+    /// The id of the campaign.
+    pub id: Uuid,
+    /// Information about the campaign.
+    pub info: CampaignInfo,
+    /// When the last journal entry of the campaign was recorded, in seconds since the Unix epoch.
+    pub last_played: Option<i64>,
+    /// The time spent playing the campaign.
+    pub playtime: Duration,
+}
+
+#[cfg(feature = "store")]
+impl Campaign {
+    /// Lists the campaigns held in the store as save slots, archived ones included, in the order they were created. The
+    /// store is scoped back to the campaign it was scoped to.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::Store`] if the store cannot be migrated or read.
+    pub async fn slots(store: &mut SqliteStore) -> Result<Vec<SaveSlot>> {
+        let scope = store.scope();
+        let slots = read_slots(store).await;
+        store.scope_to(scope);
+
+        slots
+    }
+
+    /// Lists the campaigns held in the same store as this one as save slots, see [`Campaign::slots`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::Store`] if the store cannot be read.
+    pub async fn save_slots(&mut self) -> Result<Vec<SaveSlot>> {
+        Campaign::slots(self.store()).await
+    }
+
+    /// Adds the time elapsed since the campaign was opened or last counted to its playtime, e.g. when the game closes
+    /// it, and returns the time spent playing it. Playtime is not journaled, so counting it does not make the campaign
+    /// look played.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::Store`] if the store cannot be read or written.
+    pub async fn add_playtime(&mut self, elapsed: Duration) -> Result<Duration> {
+        let id = self.id();
+        let store = self.store();
+        let Playtime(played) = store.get::<Playtime>(id).await?.unwrap_or_default();
+        let playtime = Playtime(played.saturating_add(elapsed.as_secs()));
+        store.insert(id, &playtime).await?;
+
+        Ok(playtime.duration())
+    }
+
+    /// Duplicates the campaign into a new save slot of the same store under another name, and switches to the
+    /// duplicate. The duplicate holds the roster and the clocks not tied to any entity, while its factions are set up
+    /// again from the content pack the campaign was bootstrapped from.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::Permission`] if the actor is not the GM, [`CampaignError::PackMismatch`] if the pack
+    /// is not the one the campaign was bootstrapped from, or another [`CampaignError`] if the pack is invalid or the
+    /// store cannot be read or written, in which case the duplicate is deleted again.
+    pub async fn duplicate(mut self, actor: Actor, name: impl Into<String>, pack: &Pack) -> Result<Campaign> {
+        actor.require_gm()?;
+        let export = self.export().await?;
+        if pack.name() != export.info.pack {
+            return Err(CampaignError::PackMismatch {
+                expected: export.info.pack,
+                found: pack.name().into(),
+            });
+        }
+
+        let templates = pack.load::<FactionTemplate>(faction::CATEGORY)?;
+        let (mut campaign, _) = Campaign::settle(self.into_store(), name, pack, templates).await?;
+        if let Err(err) = campaign.restore(&export).await {
+            let id = campaign.id();
+            campaign.store().drop_scope(id).await?;
+            return Err(err);
+        }
+
+        Ok(campaign)
+    }
+
+    /// Deletes another campaign held in the same store along with its entities and journal, freeing its save slot.
+    /// Returns the number of rows deleted.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::Permission`] if the actor is not the GM, [`CampaignError::CampaignOpen`] if the campaign
+    /// is this one, [`CampaignError::MissingCampaign`] if the store does not hold it, or [`CampaignError::Store`] if it
+    /// cannot be written, in which case nothing is deleted.
+    pub async fn delete(&mut self, actor: Actor, campaign: Uuid) -> Result<u64> {
+        actor.require_gm()?;
+        if campaign == self.id() {
+            return Err(CampaignError::CampaignOpen(campaign));
+        }
+        if !self.campaigns().await?.iter().any(|(id, _)| *id == campaign) {
+            return Err(CampaignError::MissingCampaign(campaign));
+        }
+
+        Ok(self.store().drop_scope(campaign).await?)
+    }
+}
+
+/// Reads the save slot of every campaign of the store, scoping the store to each in turn.
+#[cfg(feature = "store")]
+async fn read_slots(store: &mut SqliteStore) -> Result<Vec<SaveSlot>> {
+    let mut slots = Vec::new();
+    for (id, info) in Campaign::list(store).await? {
+        store.scope_to(id);
+        slots.push(SaveSlot {
+            id,
+            info,
+            last_played: store.last_recorded().await?,
+            playtime: store.get::<Playtime>(id).await?.unwrap_or_default().duration(),
+        });
+    }
+
+    Ok(slots)
+}
+
+#[cfg(all(test, feature = "store"))]
+mod tests {
+    use darkforge_data::{
+        sql,
+        store::{
+            Query,
+            sql::{SqlQuery, sqlite::SqliteStore},
+        },
+    };
+
+    use super::*;
+    use crate::{
+        campaign::tests::{DEFAULTS, bootstrap, db},
+        character::Character,
+        export::CHARACTER_IMPORTED,
+    };
+
+    #[tokio::test]
+    async fn should_duplicate_and_delete_save_slots() {
+        let pack = Pack::open(DEFAULTS).expect("should have opened pack");
+        let mut original = bootstrap().await;
+        let first = original.id();
+        original
+            .enlist(Actor::Gm, Uuid::new_v4(), &Character::new("Arcy"))
+            .await
+            .expect("should have enlisted character");
+        assert_eq!(
+            Duration::from_secs(90),
+            original.add_playtime(Duration::from_secs(90)).await.expect("should have added playtime")
+        );
+
+        let mut copy = original
+            .duplicate(Actor::Gm, "Crow's Foot, again", &pack)
+            .await
+            .expect("should have duplicated campaign");
+        let slots = copy.save_slots().await.expect("should have listed slots");
+        assert_eq!(
+            vec![
+                (first, "Crow's Foot", Duration::from_secs(90)),
+                (copy.id(), "Crow's Foot, again", Duration::ZERO)
+            ],
+            slots
+                .iter()
+                .map(|slot| (slot.id, slot.info.name.as_str(), slot.playtime))
+                .collect::<Vec<_>>()
+        );
+        assert!(slots.iter().all(|slot| slot.last_played.is_some()));
+        assert_eq!(1, copy.roster(None).await.expect("should have listed roster").len());
+
+        let err = copy
+            .delete(Actor::Gm, copy.id())
+            .await
+            .expect_err("should have refused to delete open campaign");
+        assert!(
+            matches!(err, CampaignError::CampaignOpen(id) if id == copy.id()),
+            "unexpected error: {err}"
+        );
+        copy.delete(Actor::Gm, first).await.expect("should have deleted campaign");
+        assert_eq!(
+            vec![copy.id()],
+            copy.save_slots()
+                .await
+                .expect("should have listed slots")
+                .iter()
+                .map(|slot| slot.id)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[tokio::test]
+    async fn should_delete_duplicate_given_roster_not_restored() {
+        let pack = Pack::open(DEFAULTS).expect("should have opened pack");
+        let path = db();
+        let store = SqliteStore::open(&path).await.expect("should have opened store");
+        let mut original = Campaign::bootstrap(store, "Crow's Foot", &pack)
+            .await
+            .expect("should have bootstrapped campaign");
+        let first = original.id();
+        original
+            .enlist(Actor::Gm, Uuid::new_v4(), &Character::new("Arcy"))
+            .await
+            .expect("should have enlisted character");
+        let refuse = format!(
+            "CREATE TRIGGER refuse_imports BEFORE INSERT ON journal WHEN new.kind = '{CHARACTER_IMPORTED}'
+             BEGIN SELECT RAISE(ABORT, 'refused'); END;"
+        );
+        let _: Vec<()> = sql!(refuse).run(original.store()).await.expect("should have created trigger");
+
+        original
+            .duplicate(Actor::Gm, "Crow's Foot, again", &pack)
+            .await
+            .err()
+            .expect("should have failed to restore roster");
+
+        let store = SqliteStore::open(&path).await.expect("should have opened store");
+        let mut campaign = Campaign::open(store).await.expect("should have opened campaign");
+        assert_eq!(
+            vec![first],
+            campaign
+                .save_slots()
+                .await
+                .expect("should have listed slots")
+                .iter()
+                .map(|slot| slot.id)
+                .collect::<Vec<_>>()
+        );
+    }
+}
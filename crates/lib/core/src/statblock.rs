@@ -0,0 +1,491 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! Plain text stat blocks for NPCs and factions, quick to type in prep notes and to paste from the clipboard.
+//!
+//! Each block is a run of `key: value` lines, blocks being separated by blank lines. The first line names the NPC or
+//! faction, the key telling which it is:
+//!
+//! ```text
+//! npc: Bazso Baz
+//! description: Leader of the Lampblacks.
+//! tier: 2
+//! quality: 3
+//! drives: Revenge on the Red Sashes
+//! flaws: Greedy, Proud
+//!
+//! faction: The Lampblacks
+//! tier: 2
+//! hold: weak
+//! status: -1
+//! clock: Destroy the Red Sashes (2/8)
+//! ```
+//!
+//! Keys are case insensitive. Lists are separated by commas, and factions take a `clock` line per clock. NPCs without a
+//! tier have no [`StatBlock`], and their quality defaults to their tier. Factions need a tier and a hold. Blocks are
+//! written back in the same format, so they can be exported, edited and imported again.
+use std::{
+    collections::BTreeSet,
+    fmt::{self, Display, Formatter},
+};
+
+use darkforge_data::descriptor::Descriptor;
+#[cfg(feature = "store")]
+use darkforge_data::store::World as _;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use uuid::Uuid;
+
+#[cfg(feature = "store")]
+use crate::{
+    campaign::{CLOCK, Campaign, CampaignError, FACTION, Result},
+    changeset::{Access, Changeset},
+    faction::Faction,
+    permission::Actor,
+};
+use crate::{
+    clock::Clock,
+    faction::{ClockTemplate, FactionTemplate, Hold, Status},
+    npc::{Npc, StatBlock},
+};
+
+/// Kind of the journal entries recording the entities imported from stat blocks.
+pub const IMPORTED: &str = "statblock.imported";
+
+/// Error type for stat blocks that cannot be parsed.
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum BlockError {
+    /// A line is not a `key: value` pair.
+    #[error("line {0}: expected `key: value`")]
+    Malformed(usize),
+    /// A block starts with neither an `npc` nor a `faction` line.
+    #[error("line {line}: a block starts with `npc:` or `faction:`, not `{key}:`")]
+    UnknownKind {
+        /// The line of the block's first key.
+        line: usize,
+        /// The key found.
+        key: String,
+    },
+    /// A key does not apply to the block.
+    #[error("line {line}: unknown key `{key}`")]
+    UnknownKey {
+        /// The line of the key.
+        line: usize,
+        /// The key.
+        key: String,
+    },
+    /// A key other than `clock` is given twice in a block.
+    #[error("line {line}: `{key}` is given twice")]
+    Duplicate {
+        /// The line of the second key.
+        line: usize,
+        /// The key.
+        key: String,
+    },
+    /// A value cannot be read.
+    #[error("line {line}: invalid {key} `{value}`")]
+    Invalid {
+        /// The line of the value.
+        line: usize,
+        /// The key of the value.
+        key: String,
+        /// The value.
+        value: String,
+    },
+    /// A block lacks a required key.
+    #[error("line {line}: the block lacks a `{key}`")]
+    Missing {
+        /// The line the block starts at.
+        line: usize,
+        /// The key lacking.
+        key: &'static str,
+    },
+}
+
+impl BlockError {
+    /// Returns the line the error is about, starting at 1.
+    #[must_use]
+    pub fn line(&self) -> usize {
+        match self {
+            BlockError::Malformed(line)
+            | BlockError::UnknownKind { line, .. }
+            | BlockError::UnknownKey { line, .. }
+            | BlockError::Duplicate { line, .. }
+            | BlockError::Invalid { line, .. }
+            | BlockError::Missing { line, .. } => *line,
+        }
+    }
+}
+
+/// An NPC or a faction, as written in a stat block.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Block {
+    /// An NPC, with their stats if they have a tier.
+    Npc(Npc, Option<StatBlock>),
+    /// A faction, with the crew's status with it and its clocks.
+    Faction(FactionTemplate),
+}
+
+/// Parses stat blocks separated by blank lines.
+///
+/// # Errors
+///
+/// Returns a [`BlockError`] for the first line that cannot be read, or the first block lacking a required key.
+pub fn parse(text: &str) -> std::result::Result<Vec<Block>, BlockError> {
+    let mut blocks = Vec::new();
+    let mut lines = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            if !lines.is_empty() {
+                blocks.push(block(&std::mem::take(&mut lines))?);
+            }
+            continue;
+        }
+
+        let (key, value) = line.split_once(':').ok_or(BlockError::Malformed(index + 1))?;
+        lines.push((index + 1, key.trim().to_lowercase(), value.trim()));
+    }
+    if !lines.is_empty() {
+        blocks.push(block(&lines)?);
+    }
+
+    Ok(blocks)
+}
+
+/// Writes stat blocks separated by blank lines, in the format [`parse`] reads.
+#[must_use]
+pub fn format(blocks: &[Block]) -> String {
+    blocks.iter().map(ToString::to_string).collect::<Vec<_>>().join("\n")
+}
+
+/// Reads a block from its `(line, key, value)` triples, the first of which names the NPC or faction.
+fn block(lines: &[(usize, String, &str)]) -> std::result::Result<Block, BlockError> {
+    let (start, kind, name) = &lines[0];
+    let mut seen = BTreeSet::new();
+    let mut description = "";
+    let (mut tier, mut quality, mut hold, mut status) = (None, None, None, None);
+    let (mut drives, mut flaws, mut clocks) = (Vec::new(), Vec::new(), Vec::new());
+    for (line, key, value) in &lines[1..] {
+        let line = *line;
+        if key != "clock" && !seen.insert(key.as_str()) {
+            return Err(BlockError::Duplicate { line, key: key.clone() });
+        }
+        let invalid = || BlockError::Invalid {
+            line,
+            key: key.clone(),
+            value: (*value).to_owned(),
+        };
+
+        match (kind.as_str(), key.as_str()) {
+            (_, "description") => description = value,
+            (_, "tier") => tier = Some(value.parse::<u8>().map_err(|_| invalid())?),
+            ("npc", "quality") => quality = Some(value.parse::<u8>().map_err(|_| invalid())?),
+            ("npc", "drives") => drives = list(value),
+            ("npc", "flaws") => flaws = list(value),
+            ("faction", "hold") => hold = Some(value.parse::<Hold>().map_err(|()| invalid())?),
+            ("faction", "status") => status = Some(Status::new(value.parse::<i8>().map_err(|_| invalid())?)),
+            ("faction", "clock") => clocks.push(clock(value).ok_or_else(invalid)?),
+            _ => return Err(BlockError::UnknownKey { line, key: key.clone() }),
+        }
+    }
+
+    let descriptor = Descriptor::new(Uuid::new_v4(), (*name).to_owned(), description.to_owned());
+    match kind.as_str() {
+        "npc" => {
+            let block = tier.map(|tier| StatBlock {
+                tier,
+                quality: quality.unwrap_or(tier),
+                drives,
+                flaws,
+            });
+
+            Ok(Block::Npc(Npc::new(descriptor), block))
+        }
+        "faction" => Ok(Block::Faction(FactionTemplate {
+            id: Uuid::new_v4(),
+            descriptor,
+            tier: tier.ok_or(BlockError::Missing { line: *start, key: "tier" })?,
+            hold: hold.ok_or(BlockError::Missing { line: *start, key: "hold" })?,
+            status: status.unwrap_or_default(),
+            clocks,
+        })),
+        _ => Err(BlockError::UnknownKind {
+            line: *start,
+            key: kind.clone(),
+        }),
+    }
+}
+
+/// Splits a list of comma separated values, leaving out empty ones.
+fn list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_owned)
+        .collect()
+}
+
+/// Reads a clock written as its label followed by its progress in parentheses, e.g. `Alarm (2/4)`.
+fn clock(value: &str) -> Option<ClockTemplate> {
+    let (label, progress) = value.strip_suffix(')')?.rsplit_once('(')?;
+    let (filled, segments) = progress.split_once('/')?;
+    let clock = ClockTemplate {
+        label: label.trim().to_owned(),
+        segments: segments.trim().parse().ok()?,
+        filled: filled.trim().parse().ok()?,
+    };
+
+    Clock::with_progress(clock.label.clone(), clock.segments, clock.filled)
+        .is_ok()
+        .then_some(clock)
+}
+
+impl Display for Block {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let (kind, descriptor) = match self {
+            Block::Npc(npc, _) => ("npc", npc.descriptor()),
+            Block::Faction(template) => ("faction", &template.descriptor),
+        };
+        writeln!(f, "{kind}: {}", descriptor.label())?;
+        if !descriptor.description().is_empty() {
+            writeln!(f, "description: {}", descriptor.description().replace('\n', " "))?;
+        }
+
+        match self {
+            Block::Npc(_, None) => {}
+            Block::Npc(_, Some(stats)) => {
+                writeln!(f, "tier: {}", stats.tier)?;
+                writeln!(f, "quality: {}", stats.quality)?;
+                if !stats.drives.is_empty() {
+                    writeln!(f, "drives: {}", stats.drives.join(", "))?;
+                }
+                if !stats.flaws.is_empty() {
+                    writeln!(f, "flaws: {}", stats.flaws.join(", "))?;
+                }
+            }
+            Block::Faction(template) => {
+                writeln!(f, "tier: {}", template.tier)?;
+                writeln!(f, "hold: {}", format!("{:?}", template.hold).to_lowercase())?;
+                writeln!(f, "status: {}", template.status.value())?;
+                for clock in &template.clocks {
+                    writeln!(f, "clock: {} ({}/{})", clock.label, clock.filled, clock.segments)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Journal entry recorded when entities are imported from stat blocks.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Imported {
+    /// The NPCs and factions created, in the order of their blocks.
+    pub entities: Vec<Uuid>,
+}
+
+#[cfg(feature = "store")]
+impl Campaign {
+    /// Creates the NPCs and factions of the stat blocks, along with the factions' clocks, in a single transaction.
+    /// Returns the entities created, in the order of their blocks.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::Block`] if the stat blocks cannot be parsed, [`CampaignError::Permission`] if the actor
+    /// is not the GM, or another [`CampaignError`] if the store cannot be written, in which case nothing is created.
+    pub async fn import_blocks(&mut self, actor: Actor, text: &str) -> Result<Vec<Uuid>> {
+        actor.require_gm()?;
+        let blocks = parse(text)?;
+
+        let mut changeset = Changeset::new(actor, Access::Gm);
+        let mut entities = Vec::with_capacity(blocks.len());
+        for block in blocks {
+            let entity = Uuid::new_v4();
+            changeset = changeset.spawn(entity);
+            changeset = match block {
+                Block::Npc(npc, stats) => {
+                    let changeset = changeset.insert(entity, &npc)?;
+                    match stats {
+                        Some(stats) => changeset.insert(entity, &stats)?,
+                        None => changeset,
+                    }
+                }
+                Block::Faction(template) => {
+                    let mut changeset =
+                        changeset
+                            .insert(entity, &template.faction())?
+                            .insert(entity, &template.status)?
+                            .relate(self.id(), entity, FACTION);
+                    for clock in &template.clocks {
+                        let clock = Clock::with_progress(clock.label.clone(), clock.segments, clock.filled).map_err(|source| {
+                            CampaignError::InvalidClock {
+                                faction: template.descriptor.label().into(),
+                                source,
+                            }
+                        })?;
+                        let clock_entity = Uuid::new_v4();
+                        changeset = changeset
+                            .spawn(clock_entity)
+                            .insert(clock_entity, &clock)?
+                            .relate(entity, clock_entity, CLOCK);
+                    }
+                    changeset
+                }
+            };
+            entities.push(entity);
+        }
+        let imported = Imported { entities };
+        self.apply(changeset.record(None, IMPORTED, &imported)?).await?;
+
+        Ok(imported.entities)
+    }
+
+    /// Writes the NPCs and factions as stat blocks, in the format [`Campaign::import_blocks`] reads.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::MissingNpc`] if an entity is neither an NPC nor a faction, or another [`CampaignError`]
+    /// if the store cannot be read.
+    pub async fn export_blocks(&mut self, entities: &[Uuid]) -> Result<String> {
+        let mut blocks = Vec::with_capacity(entities.len());
+        for &entity in entities {
+            let store = self.store();
+            let block = if let Some(npc) = store.get::<Npc>(entity).await? {
+                Block::Npc(npc, store.get::<StatBlock>(entity).await?)
+            } else if let Some(faction) = store.get::<Faction>(entity).await? {
+                let mut clocks = Vec::new();
+                for clock in store.related(entity, CLOCK).await? {
+                    if let Some(clock) = store.get::<Clock>(clock).await? {
+                        clocks.push(ClockTemplate {
+                            label: clock.label().to_owned(),
+                            segments: clock.segments(),
+                            filled: clock.filled(),
+                        });
+                    }
+                }
+                Block::Faction(FactionTemplate {
+                    id: entity,
+                    descriptor: faction.descriptor().clone(),
+                    tier: faction.tier(),
+                    hold: faction.hold(),
+                    status: store.get::<Status>(entity).await?.unwrap_or_default(),
+                    clocks,
+                })
+            } else {
+                return Err(CampaignError::MissingNpc(entity));
+            };
+            blocks.push(block);
+        }
+
+        Ok(format(&blocks))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+    #[cfg(feature = "store")]
+    use crate::campaign::tests::bootstrap;
+
+    const BLOCKS: &str = "npc: Bazso Baz
+description: Leader of the Lampblacks.
+tier: 2
+quality: 3
+drives: Revenge on the Red Sashes
+flaws: Greedy, Proud
+
+faction: The Lampblacks
+tier: 2
+hold: weak
+status: -1
+clock: Destroy the Red Sashes (2/8)
+
+npc: Lyssa
+";
+
+    #[test]
+    fn should_parse_blocks() {
+        let blocks = parse(BLOCKS).expect("should have parsed blocks");
+
+        let [Block::Npc(bazso, Some(stats)), Block::Faction(lampblacks), Block::Npc(lyssa, None)] = blocks.as_slice() else {
+            panic!("unexpected blocks: {blocks:?}");
+        };
+        assert_eq!(
+            ("Bazso Baz", "Leader of the Lampblacks."),
+            (bazso.descriptor().label(), bazso.descriptor().description())
+        );
+        assert_eq!((2, 3), (stats.tier, stats.quality));
+        assert_eq!(vec!["Revenge on the Red Sashes"], stats.drives);
+        assert_eq!(vec!["Greedy", "Proud"], stats.flaws);
+        assert_eq!(
+            ("The Lampblacks", 2, Hold::Weak, -1),
+            (lampblacks.descriptor.label(), lampblacks.tier, lampblacks.hold, lampblacks.status.value())
+        );
+        assert_eq!(
+            vec![ClockTemplate {
+                label: "Destroy the Red Sashes".into(),
+                segments: 8,
+                filled: 2,
+            }],
+            lampblacks.clocks
+        );
+        assert_eq!("Lyssa", lyssa.descriptor().label());
+        assert_eq!(BLOCKS, format(&blocks));
+    }
+
+    #[rstest]
+    #[case::malformed("npc: Lyssa\nsharp", BlockError::Malformed(2))]
+    #[case::unknown_kind("crew: The Shrikes", BlockError::UnknownKind { line: 1, key: "crew".into() })]
+    #[case::unknown_key("npc: Lyssa\nhold: weak", BlockError::UnknownKey { line: 2, key: "hold".into() })]
+    #[case::duplicate("npc: Lyssa\nTier: 1\ntier: 2", BlockError::Duplicate { line: 3, key: "tier".into() })]
+    #[case::invalid_tier("npc: Lyssa\ntier: high", BlockError::Invalid { line: 2, key: "tier".into(), value: "high".into() })]
+    #[case::invalid_clock(
+        "faction: The Hive\ntier: 4\nhold: strong\nclock: Alarm (5/4)",
+        BlockError::Invalid { line: 4, key: "clock".into(), value: "Alarm (5/4)".into() }
+    )]
+    #[case::missing_hold("\n\nfaction: The Hive\ntier: 4", BlockError::Missing { line: 3, key: "hold" })]
+    fn should_refuse_invalid_blocks(#[case] text: &str, #[case] expect: BlockError) {
+        let line = expect.line();
+
+        assert_eq!(Err(expect), parse(text));
+        assert!(
+            text.lines().nth(line - 1).is_some_and(|line| !line.is_empty()),
+            "should point at a line of the text"
+        );
+    }
+
+    #[cfg(feature = "store")]
+    #[tokio::test]
+    async fn should_import_and_export_blocks() {
+        let mut campaign = bootstrap().await;
+        let factions = campaign.factions().await.expect("should have listed factions").len();
+
+        let err = campaign
+            .import_blocks(Actor::Player(Uuid::new_v4()), BLOCKS)
+            .await
+            .expect_err("should have refused player");
+        assert!(matches!(err, CampaignError::Permission(_)), "unexpected error: {err}");
+        let entities = campaign.import_blocks(Actor::Gm, BLOCKS).await.expect("should have imported blocks");
+
+        assert_eq!(3, entities.len());
+        assert_eq!(factions + 1, campaign.factions().await.expect("should have listed factions").len());
+        let clocks = campaign.store().related(entities[1], CLOCK).await.expect("should have listed clocks");
+        assert_eq!(1, clocks.len());
+        assert_eq!(BLOCKS, campaign.export_blocks(&entities).await.expect("should have exported blocks"));
+        let journal = campaign.store().journal(None, IMPORTED, 10).await.expect("should have read journal");
+        assert_eq!(1, journal.len());
+    }
+}
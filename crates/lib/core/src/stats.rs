@@ -0,0 +1,169 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! Statistics of a campaign over time, for dashboards to chart.
+//!
+//! The statistics are aggregated from the journal by the store, so charting a long campaign does not read every entry
+//! back: scores, coin and trauma are totalled per session, while heat is read every time it was set on a crew sheet.
+
+#[cfg(feature = "store")]
+use darkforge_data::{
+    Persisted,
+    store::{Aggregate as _, Bucket, Tally},
+};
+use uuid::Uuid;
+
+#[cfg(feature = "store")]
+use crate::{
+    campaign::{Campaign, Result},
+    character::Stash,
+    crew::{self, Heat},
+    score, session,
+};
+
+/// A total over a play session.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SessionTotal {
+    /// The session's number, starting at 1, or `None` for what happened before the first session.
+    pub session: Option<usize>,
+    /// The total over the session.
+    pub total: i64,
+}
+
+/// The heat of a crew when it was set.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HeatReading {
+    /// The crew entity.
+    pub crew: Uuid,
+    /// The sequence number of the journal entry recording the heat.
+    pub seq: i64,
+    /// When the heat was set, in seconds since the Unix epoch.
+    pub recorded_at: i64,
+    /// The crew's heat.
+    pub heat: i64,
+}
+
+/// Series of a campaign's statistics, oldest first.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    /// The scores ended, per session.
+    pub scores: Vec<SessionTotal>,
+    /// The coin the characters' stashes gained, per session.
+    pub coin: Vec<SessionTotal>,
+    /// The times characters suffered trauma from taking more stress than they could, per session.
+    pub trauma: Vec<SessionTotal>,
+    /// The heat of the crews when they started and whenever their sheet was updated.
+    pub heat: Vec<HeatReading>,
+}
+
+#[cfg(feature = "store")]
+impl Campaign {
+    /// Returns the statistics of the campaign, see [`Stats`].
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CampaignError`](crate::campaign::CampaignError) if the store cannot be read.
+    pub async fn stats(&mut self) -> Result<Stats> {
+        let stash = format!("{}.updated", Stash::KIND);
+        let heat = format!("{}.updated", Heat::KIND);
+        let store = self.store();
+
+        Ok(Stats {
+            scores: per_session(store.tally(session::STARTED, score::ENDED, Tally::Count).await?),
+            coin: per_session(store.tally(session::STARTED, &stash, Tally::Gain("$.data")).await?),
+            trauma: per_session(
+                store
+                    .tally(session::STARTED, "action.resolved", Tally::CountWhere("$.data.trauma"))
+                    .await?,
+            ),
+            heat: store
+                .series(&[crew::STARTED, &heat], "$.data.heat")
+                .await?
+                .into_iter()
+                .filter_map(|sample| {
+                    Some(HeatReading {
+                        crew: sample.entity?,
+                        seq: sample.seq,
+                        recorded_at: sample.recorded_at,
+                        heat: sample.value,
+                    })
+                })
+                .collect(),
+        })
+    }
+}
+
+/// Numbers the sessions of the buckets tallied between session markers.
+#[cfg(feature = "store")]
+fn per_session(buckets: Vec<Bucket>) -> Vec<SessionTotal> {
+    let mut number = 0;
+    buckets
+        .into_iter()
+        .map(|bucket| SessionTotal {
+            session: bucket.marker.map(|_| {
+                number += 1;
+                number
+            }),
+            total: bucket.value,
+        })
+        .collect()
+}
+
+#[cfg(all(test, feature = "store"))]
+mod tests {
+    use serde::Serialize;
+
+    use super::*;
+    use crate::{campaign::tests::bootstrap, character::Character, permission::Actor};
+
+    #[derive(Serialize)]
+    struct Resolved {
+        trauma: bool,
+    }
+
+    #[tokio::test]
+    async fn should_chart_campaign_per_session() {
+        let mut campaign = bootstrap().await;
+        let crew = campaign.start_crew(Actor::Gm).await.expect("should have started crew");
+        let arcy = campaign
+            .enlist(Actor::Gm, Uuid::new_v4(), &Character::new("Arcy"))
+            .await
+            .expect("should have enlisted character");
+
+        campaign.start_session(Actor::Gm, "Lost").await.expect("should have started session");
+        campaign.start_score(Actor::Gm, "Lampblacks").await.expect("should have started score");
+        campaign
+            .update(Actor::Gm, crew, &Heat::new(4, 0))
+            .await
+            .expect("should have updated heat");
+        campaign.end_score(Actor::Gm).await.expect("should have ended score");
+        campaign.update(Actor::Gm, arcy, &Stash::new(3)).await.expect("should have updated stash");
+
+        campaign.start_session(Actor::Gm, "Found").await.expect("should have started session");
+        campaign
+            .record(Actor::Gm, Some(arcy), "action.resolved", &Resolved { trauma: true })
+            .await
+            .expect("should have recorded action");
+        campaign.update(Actor::Gm, arcy, &Stash::new(1)).await.expect("should have updated stash");
+
+        let stats = campaign.stats().await.expect("should have read stats");
+
+        let totals = |series: &[SessionTotal]| series.iter().map(|total| (total.session, total.total)).collect::<Vec<_>>();
+        assert_eq!(vec![(Some(1), 1), (Some(2), 0)], totals(&stats.scores));
+        assert_eq!(vec![(Some(1), 3), (Some(2), 0)], totals(&stats.coin));
+        assert_eq!(vec![(Some(1), 0), (Some(2), 1)], totals(&stats.trauma));
+        assert_eq!(
+            vec![(crew, 0), (crew, 4)],
+            stats.heat.iter().map(|reading| (reading.crew, reading.heat)).collect::<Vec<_>>()
+        );
+    }
+}
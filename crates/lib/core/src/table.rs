@@ -0,0 +1,152 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! Weighted tables pick one of their rows at random, rows with a higher weight coming up more often.
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// A row of a weighted table.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Row<T> {
+    /// How likely the row is to come up, relative to the other rows of the table.
+    #[serde(default = "Row::<T>::default_weight")]
+    pub weight: u32,
+    /// The value of the row.
+    #[serde(flatten)]
+    pub value: T,
+}
+
+impl<T> Row<T> {
+    /// Creates a new row.
+    #[must_use]
+    pub fn new(weight: u32, value: T) -> Row<T> {
+        Row { weight, value }
+    }
+
+    /// Rows without a weight are as likely as any other row without one.
+    fn default_weight() -> u32 {
+        1
+    }
+}
+
+/// A table of rows picked at random according to their weight.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct Table<T> {
+    rows: Vec<Row<T>>,
+}
+
+impl<T> Table<T> {
+    /// Creates a new table from its rows.
+    #[must_use]
+    pub fn new(rows: Vec<Row<T>>) -> Table<T> {
+        Table { rows }
+    }
+
+    /// Returns the rows of the table.
+    #[must_use]
+    pub fn rows(&self) -> &[Row<T>] {
+        &self.rows
+    }
+
+    /// Returns the sum of the weights of the rows.
+    #[must_use]
+    pub fn total(&self) -> u32 {
+        self.rows.iter().map(|row| row.weight).sum()
+    }
+
+    /// Returns the row covering `roll`, each row covering as many consecutive values as its weight starting from zero,
+    /// or `None` if `roll` is not below the [total](Table::total) of the table.
+    #[must_use]
+    pub fn pick(&self, roll: u32) -> Option<&T> {
+        let mut remaining = roll;
+        for row in &self.rows {
+            if remaining < row.weight {
+                return Some(&row.value);
+            }
+            remaining -= row.weight;
+        }
+
+        None
+    }
+
+    /// Picks a row at random, or returns `None` if the table has no weighted rows.
+    pub fn roll(&self, rng: &mut impl Rng) -> Option<&T> {
+        match self.total() {
+            0 => None,
+            total => self.pick(rng.random_range(0..total)),
+        }
+    }
+}
+
+impl<T> FromIterator<Row<T>> for Table<T> {
+    fn from_iter<I: IntoIterator<Item = Row<T>>>(rows: I) -> Table<T> {
+        Table::new(rows.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use darkforge_data::JSONDeserialize;
+    use rand::{SeedableRng, rngs::StdRng};
+    use rstest::rstest;
+
+    use super::*;
+
+    fn table() -> Table<&'static str> {
+        Table::new(vec![Row::new(1, "rarely"), Row::new(0, "never"), Row::new(3, "often")])
+    }
+
+    #[rstest]
+    #[case::first(0, Some("rarely"))]
+    #[case::second(1, Some("often"))]
+    #[case::last(3, Some("often"))]
+    #[case::beyond(4, None)]
+    fn should_pick_row_covering_roll(#[case] roll: u32, #[case] expect: Option<&str>) {
+        assert_eq!(expect, table().pick(roll).copied());
+    }
+
+    #[test]
+    fn should_roll_rows_according_to_their_weight() {
+        let mut rng = StdRng::seed_from_u64(1898);
+        let table = table();
+
+        let often = (0..4000).filter(|_| table.roll(&mut rng) == Some(&"often")).count();
+
+        assert!(
+            (2800..3200).contains(&often),
+            "should have rolled often about three times in four, got {often}"
+        );
+    }
+
+    #[test]
+    fn should_not_roll_empty_table() {
+        let table = Table::<&str>::new(vec![Row::new(0, "never")]);
+
+        assert_eq!(None, table.roll(&mut StdRng::seed_from_u64(0)));
+    }
+
+    #[test]
+    fn should_deserialize_rows_with_default_weight() {
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Answer {
+            text: String,
+        }
+
+        let table =
+            Table::<Answer>::from_json(r#"[{"text": "Yes"}, {"text": "No", "weight": 2}]"#.as_bytes()).expect("should have deserialized table");
+
+        assert_eq!(vec![1, 2], table.rows().iter().map(|row| row.weight).collect::<Vec<_>>());
+        assert_eq!(3, table.total());
+    }
+}
@@ -0,0 +1,327 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! Text templates let content packs write generated text, such as entanglements, devil's bargains and oracle
+//! complications, that names the campaign's own characters, crews, districts, factions and NPCs.
+//!
+//! A placeholder is a name between braces, e.g. `{faction}`, replaced by one of the values bound to that name, picked
+//! at random. It may give a fallback after a bar, e.g. `{faction|a rival gang}`, used when nothing is bound to the name.
+//! A placeholder used twice in a template stands for the same value both times, and doubled braces stand for
+//! themselves:
+//!
+//! ```
+//! use darkforge::template::{Bindings, Template};
+//!
+//! let template = "{npc|A stranger} of {faction} wants a word. {{Again}}.".parse::<Template>().unwrap();
+//! let bindings = Bindings::default().bind("faction", "the Red Sashes");
+//!
+//! assert_eq!(
+//!     "A stranger of the Red Sashes wants a word. {Again}.",
+//!     template.render(&bindings, &mut rand::rng()).unwrap()
+//! );
+//! ```
+use std::{
+    collections::{BTreeMap, HashMap},
+    str::FromStr,
+};
+
+#[cfg(feature = "store")]
+use darkforge_data::store::World as _;
+use rand::{Rng, seq::IndexedRandom};
+use thiserror::Error;
+#[cfg(feature = "store")]
+use uuid::Uuid;
+
+#[cfg(feature = "store")]
+use crate::{
+    campaign::{Campaign, Result},
+    character::Character,
+    crew::Crew,
+    faction::Faction,
+    npc::Npc,
+};
+
+/// Placeholder bound to the name of the character concerned.
+pub const CHARACTER: &str = "character";
+/// Placeholder bound to the names of the crews.
+pub const CREW: &str = "crew";
+/// Placeholder bound to the districts the crews hunt in.
+pub const DISTRICT: &str = "district";
+/// Placeholder bound to the labels of the factions.
+pub const FACTION: &str = "faction";
+/// Placeholder bound to the names of the NPCs.
+pub const NPC: &str = "npc";
+
+/// Error type for template operations.
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum TemplateError {
+    /// A placeholder is opened but never closed.
+    #[error("placeholder opened at {0} is never closed")]
+    Unclosed(usize),
+    /// A closing brace closes no placeholder.
+    #[error("closing brace at {0} closes no placeholder")]
+    Unopened(usize),
+    /// A placeholder has no name.
+    #[error("placeholder at {0} has no name")]
+    Unnamed(usize),
+    /// Nothing is bound to a placeholder without a fallback.
+    #[error("nothing is bound to placeholder {0}")]
+    Unbound(String),
+}
+
+/// A piece of a template.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Part {
+    /// Text kept as is.
+    Text(String),
+    /// A placeholder, with the text used when nothing is bound to it.
+    Placeholder { name: String, fallback: Option<String> },
+}
+
+/// A parsed text template.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+impl FromStr for Template {
+    type Err = TemplateError;
+
+    fn from_str(source: &str) -> std::result::Result<Self, Self::Err> {
+        let mut parts = Vec::new();
+        let mut text = String::new();
+        let mut chars = source.char_indices().peekable();
+        while let Some((at, c)) = chars.next() {
+            match c {
+                '{' if chars.next_if(|&(_, next)| next == '{').is_some() => text.push('{'),
+                '}' if chars.next_if(|&(_, next)| next == '}').is_some() => text.push('}'),
+                '}' => return Err(TemplateError::Unopened(at)),
+                '{' => {
+                    let start = at + 1;
+                    let end = chars
+                        .by_ref()
+                        .find(|&(_, next)| next == '}')
+                        .map(|(end, _)| end)
+                        .ok_or(TemplateError::Unclosed(at))?;
+                    let (name, fallback) = match source[start..end].split_once('|') {
+                        Some((name, fallback)) => (name, Some(fallback.to_owned())),
+                        None => (&source[start..end], None),
+                    };
+                    let name = name.trim();
+                    if name.is_empty() {
+                        return Err(TemplateError::Unnamed(at));
+                    }
+                    if !text.is_empty() {
+                        parts.push(Part::Text(std::mem::take(&mut text)));
+                    }
+                    parts.push(Part::Placeholder {
+                        name: name.to_owned(),
+                        fallback,
+                    });
+                }
+                c => text.push(c),
+            }
+        }
+        if !text.is_empty() {
+            parts.push(Part::Text(text));
+        }
+
+        Ok(Template { parts })
+    }
+}
+
+impl Template {
+    /// Returns the names of the placeholders of the template, in order, with repeats.
+    pub fn placeholders(&self) -> impl Iterator<Item = &str> {
+        self.parts.iter().filter_map(|part| match part {
+            Part::Placeholder { name, .. } => Some(name.as_str()),
+            Part::Text(_) => None,
+        })
+    }
+
+    /// Renders the template, replacing each placeholder by one of the values bound to its name picked with `rng`, or
+    /// by its fallback if nothing is bound to it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TemplateError::Unbound`] if nothing is bound to a placeholder without a fallback.
+    pub fn render(&self, bindings: &Bindings, rng: &mut impl Rng) -> std::result::Result<String, TemplateError> {
+        let mut picked = HashMap::<&str, &str>::new();
+        let mut rendered = String::new();
+        for part in &self.parts {
+            match part {
+                Part::Text(text) => rendered.push_str(text),
+                Part::Placeholder { name, fallback } => {
+                    let value = if let Some(value) = picked.get(name.as_str()) {
+                        value
+                    } else {
+                        let value = bindings
+                            .values(name)
+                            .choose(rng)
+                            .map(String::as_str)
+                            .or(fallback.as_deref())
+                            .ok_or_else(|| TemplateError::Unbound(name.clone()))?;
+                        picked.insert(name, value);
+                        value
+                    };
+                    rendered.push_str(value);
+                }
+            }
+        }
+
+        Ok(rendered)
+    }
+}
+
+/// Parses and renders the template in one go, see [`Template::render`].
+///
+/// # Errors
+///
+/// Returns a [`TemplateError`] if the template is invalid or a placeholder without a fallback is unbound.
+pub fn render(source: &str, bindings: &Bindings, rng: &mut impl Rng) -> std::result::Result<String, TemplateError> {
+    source.parse::<Template>()?.render(bindings, rng)
+}
+
+/// The values placeholders may be replaced by, by name.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Bindings(BTreeMap<String, Vec<String>>);
+
+impl Bindings {
+    /// Adds a value the placeholder may be replaced by.
+    #[must_use]
+    pub fn bind(mut self, name: impl Into<String>, value: impl Into<String>) -> Bindings {
+        self.0.entry(name.into()).or_default().push(value.into());
+
+        self
+    }
+
+    /// Returns the values the placeholder may be replaced by.
+    #[must_use]
+    pub fn values(&self, name: &str) -> &[String] {
+        self.0.get(name).map_or(&[], Vec::as_slice)
+    }
+}
+
+#[cfg(feature = "store")]
+impl Campaign {
+    /// Returns the bindings of the standard placeholders: the [`CHARACTER`] concerned if any, and the [`CREW`]s, the
+    /// [`DISTRICT`]s they hunt in, the [`FACTION`]s and the [`NPC`]s of the campaign.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CampaignError`](crate::campaign::CampaignError) if the store cannot be read.
+    pub async fn bindings(&mut self, character: Option<Uuid>) -> Result<Bindings> {
+        let store = self.store();
+        let mut bindings = Bindings::default();
+        if let Some(character) = character {
+            if let Some(character) = store.get::<Character>(character).await? {
+                bindings = bindings.bind(CHARACTER, character.name());
+            }
+        }
+        for (_, crew) in store.all::<Crew>().await? {
+            bindings = bindings.bind(CREW, crew.name).bind(DISTRICT, crew.hunting_grounds.district);
+        }
+        for (_, faction) in store.all::<Faction>().await? {
+            bindings = bindings.bind(FACTION, faction.descriptor().label());
+        }
+        for (_, npc) in store.all::<Npc>().await? {
+            bindings = bindings.bind(NPC, npc.descriptor().label());
+        }
+
+        Ok(bindings)
+    }
+
+    /// Renders the template with the bindings of the standard placeholders for the character, see
+    /// [`Campaign::bindings`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::Template`](crate::campaign::CampaignError::Template) if the template is invalid or a
+    /// placeholder without a fallback is unbound, or another [`CampaignError`](crate::campaign::CampaignError) if the
+    /// store cannot be read.
+    pub async fn render(&mut self, source: &str, character: Option<Uuid>, rng: &mut impl Rng) -> Result<String> {
+        let template = source.parse::<Template>()?;
+        let bindings = self.bindings(character).await?;
+
+        Ok(template.render(&bindings, rng)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{SeedableRng, rngs::StdRng};
+    use rstest::rstest;
+
+    use super::*;
+    #[cfg(feature = "store")]
+    use crate::{campaign::tests::bootstrap, permission::Actor};
+
+    #[rstest]
+    #[case::plain("The Bluecoats take an interest.", Ok("The Bluecoats take an interest."))]
+    #[case::bound("{character} owes {faction} a favour.", Ok("Arcy owes the Red Sashes a favour."))]
+    #[case::repeated("{faction} and {faction}", Ok("the Red Sashes and the Red Sashes"))]
+    #[case::fallback("{npc|A stranger} waits.", Ok("A stranger waits."))]
+    #[case::bound_over_fallback("{faction|Someone} waits.", Ok("the Red Sashes waits."))]
+    #[case::escaped("{{character}} is {character}", Ok("{character} is Arcy"))]
+    #[case::unbound("{npc} waits.", Err(TemplateError::Unbound("npc".into())))]
+    #[case::unclosed("Hello {character", Err(TemplateError::Unclosed(6)))]
+    #[case::unopened("Hello character}", Err(TemplateError::Unopened(15)))]
+    #[case::unnamed("Hello { |you}", Err(TemplateError::Unnamed(6)))]
+    fn should_render_template(#[case] source: &str, #[case] expect: std::result::Result<&str, TemplateError>) {
+        let bindings = Bindings::default().bind(CHARACTER, "Arcy").bind(FACTION, "the Red Sashes");
+
+        let rendered = render(source, &bindings, &mut StdRng::seed_from_u64(1950));
+
+        assert_eq!(expect.map(str::to_owned), rendered);
+    }
+
+    #[test]
+    fn should_pick_among_bound_values() {
+        let bindings = Bindings::default().bind(FACTION, "the Red Sashes").bind(FACTION, "the Lampblacks");
+        let template = "{faction}".parse::<Template>().expect("should have parsed template");
+        let mut rng = StdRng::seed_from_u64(1950);
+
+        let rendered = (0..100)
+            .map(|_| template.render(&bindings, &mut rng).expect("should have rendered template"))
+            .collect::<std::collections::BTreeSet<_>>();
+
+        assert_eq!(vec!["the Lampblacks", "the Red Sashes"], rendered.into_iter().collect::<Vec<_>>());
+    }
+
+    #[cfg(feature = "store")]
+    #[tokio::test]
+    async fn should_bind_campaign_entities() {
+        let mut campaign = bootstrap().await;
+        let pc = campaign
+            .enlist(Actor::Gm, Uuid::new_v4(), &Character::new("Arcy"))
+            .await
+            .expect("should have enlisted character");
+        let factions = campaign.all::<Faction>().await.expect("should have listed factions");
+
+        let bindings = campaign.bindings(Some(pc)).await.expect("should have bound placeholders");
+
+        assert_eq!(["Arcy"], bindings.values(CHARACTER));
+        assert_eq!(factions.len(), bindings.values(FACTION).len());
+        assert!(!factions.is_empty(), "should have factions to bind");
+        let rendered = campaign
+            .render("{character} crossed {faction}.", Some(pc), &mut StdRng::seed_from_u64(1950))
+            .await
+            .expect("should have rendered template");
+        assert!(
+            bindings
+                .values(FACTION)
+                .iter()
+                .any(|faction| rendered == format!("Arcy crossed {faction}.")),
+            "unexpected rendering: {rendered}"
+        );
+    }
+}
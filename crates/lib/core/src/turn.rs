@@ -0,0 +1,290 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! Faction turns advance the factions between sessions, so a crew can play solo without a GM moving the world.
+//!
+//! Each turn, every faction works on its first unfinished clock, taking up a new goal from the content pack's weighted
+//! goal table once it has none, then makes a fortune roll at its tier to see how far the clock advances.
+
+use std::fmt::{self, Display, Formatter};
+
+use darkforge_data::descriptor::Descriptor;
+#[cfg(feature = "store")]
+use darkforge_data::store::Content as _;
+#[cfg(feature = "store")]
+use darkforge_rng::dice::Dice;
+#[cfg(feature = "store")]
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{action::FortuneRoll, clock::Clock};
+#[cfg(feature = "store")]
+use crate::{
+    campaign::{CLOCK, Campaign, CampaignError, Result},
+    changeset::{Access, Changeset},
+    clock,
+    permission::Actor,
+    table::{Row, Table},
+};
+
+/// Content pack category holding the weighted goal table, see [`Row`].
+pub const CATEGORY: &str = "goal";
+/// Kind of the journal entries recording what a faction did during a turn.
+pub const WORLD_EVENT: &str = "world.event";
+
+/// A goal a faction can take up, tracked by a clock of its segments.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Goal {
+    /// The id of the goal in the content pack.
+    pub id: Uuid,
+    /// The goal's descriptor, its label naming the clock.
+    pub descriptor: Descriptor<'static>,
+    /// The number of segments of the clock tracking the goal.
+    pub segments: u8,
+}
+
+/// Journal entry recorded when a faction works on a clock during a turn.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct WorldEvent {
+    /// The faction entity.
+    pub faction: Uuid,
+    /// The faction's name.
+    pub name: String,
+    /// The clock entity.
+    pub clock: Uuid,
+    /// True if the faction took up the clock's goal this turn.
+    pub started: bool,
+    /// The fortune roll made at the faction's tier.
+    pub roll: FortuneRoll,
+    /// The segments filled.
+    pub ticked: u8,
+    /// The clock after ticking.
+    pub progress: Clock,
+}
+
+impl Display for WorldEvent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let clock = &self.progress;
+        if self.started {
+            write!(f, "{} take up a new goal: {}. ", self.name, clock.label())?;
+        }
+        if clock.is_complete() {
+            write!(f, "{} complete {}!", self.name, clock.label())
+        } else {
+            write!(
+                f,
+                "{} advance {} by {} ({}/{}).",
+                self.name,
+                clock.label(),
+                self.ticked,
+                clock.filled(),
+                clock.segments()
+            )
+        }
+    }
+}
+
+/// What happened in the world during a faction turn, written as Markdown when displayed.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct TurnSummary {
+    /// The event of each faction that acted, in the order they acted.
+    pub events: Vec<WorldEvent>,
+}
+
+impl TurnSummary {
+    /// Returns the events of the factions that completed a clock this turn.
+    pub fn completed(&self) -> impl Iterator<Item = &WorldEvent> {
+        self.events.iter().filter(|event| event.progress.is_complete())
+    }
+}
+
+impl Display for TurnSummary {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "# Faction turn")?;
+        writeln!(f)?;
+        if self.events.is_empty() {
+            return writeln!(f, "_The factions lie low._");
+        }
+        for event in &self.events {
+            writeln!(f, "- {event}")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "store")]
+impl Campaign {
+    /// Returns the weighted goal table of the content pack.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CampaignError`] if the content cannot be read.
+    pub async fn goals(&mut self) -> Result<Table<Goal>> {
+        Ok(self.store().entries::<Row<Goal>>(CATEGORY).await?.into_iter().collect())
+    }
+
+    /// Plays a faction turn: every faction makes a fortune roll at its tier with `dice` and ticks its first unfinished
+    /// clock accordingly, first taking up a goal picked from the [goal table](Campaign::goals) with `rng` if it has no
+    /// unfinished clock. A [`WORLD_EVENT`] is recorded for each faction, all in a single transaction. Factions without
+    /// a clock to work on when the goal table is empty sit the turn out.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::Permission`] if the actor is not the GM, [`CampaignError::InvalidClock`] if a picked
+    /// goal has no segments, or another [`CampaignError`] if the store cannot be read or written, in which case the
+    /// turn is not played.
+    pub async fn faction_turn(&mut self, actor: Actor, dice: &impl Dice, rng: &mut impl Rng) -> Result<TurnSummary> {
+        actor.require_gm()?;
+
+        let goals = self.goals().await?;
+        let clocks = self.clocks().await?;
+        let mut summary = TurnSummary::default();
+        let mut changesets = Vec::new();
+        for (faction, details) in self.factions().await? {
+            let active = clocks
+                .iter()
+                .find(|(_, owner, clock)| *owner == Some(faction) && !clock.is_complete())
+                .map(|(entity, _, clock)| (*entity, clock.clone()));
+
+            let mut changeset = Changeset::new(actor, Access::Gm);
+            let (entity, mut progress, started) = if let Some((entity, clock)) = active {
+                (entity, clock, false)
+            } else {
+                let Some(goal) = goals.roll(rng) else { continue };
+                let clock = Clock::new(goal.descriptor.label(), goal.segments).map_err(|source| CampaignError::InvalidClock {
+                    faction: details.descriptor().label().into(),
+                    source,
+                })?;
+                let entity = Uuid::new_v4();
+                changeset = changeset.spawn(entity).relate(faction, entity, CLOCK);
+                (entity, clock, true)
+            };
+
+            let roll = FortuneRoll::roll(dice, details.tier());
+            let ticked = progress.tick(clock::fortune_ticks(roll.outcome));
+            let event = WorldEvent {
+                faction,
+                name: details.descriptor().label().into(),
+                clock: entity,
+                started,
+                roll,
+                ticked,
+                progress,
+            };
+            changesets.push(changeset.insert(entity, &event.progress)?.record(Some(faction), WORLD_EVENT, &event)?);
+            summary.events.push(event);
+        }
+        self.apply_all(changesets).await?;
+
+        Ok(summary)
+    }
+}
+
+#[cfg(all(test, feature = "store"))]
+mod tests {
+    use darkforge_data::store::World as _;
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use super::*;
+    use crate::{
+        action::{Outcome, tests::Loaded},
+        campaign::tests::bootstrap,
+    };
+
+    #[tokio::test]
+    async fn should_load_goal_table_from_default_pack() {
+        let mut campaign = bootstrap().await;
+
+        let goals = campaign.goals().await.expect("should have read goals");
+
+        assert!(!goals.rows().is_empty(), "should have goals");
+        assert!(goals.rows().iter().all(|row| row.weight > 0 && row.value.segments > 0));
+    }
+
+    #[tokio::test]
+    async fn should_advance_every_faction_clock_by_fortune_roll() {
+        let mut campaign = bootstrap().await;
+        let factions = campaign.factions().await.expect("should have listed factions");
+        let before = campaign.clocks().await.expect("should have listed clocks");
+
+        // The Bluecoats roll first at tier 3, every other faction fails on exhausted dice.
+        let summary = campaign
+            .faction_turn(Actor::Gm, &Loaded::new(&[4, 6, 2]), &mut StdRng::seed_from_u64(1900))
+            .await
+            .expect("should have played faction turn");
+
+        assert_eq!(factions.len(), summary.events.len());
+        let bluecoats = &summary.events[0];
+        assert_eq!("Bluecoats", bluecoats.name);
+        assert_eq!(Outcome::Success, bluecoats.roll.outcome);
+        assert_eq!(3, bluecoats.ticked);
+        assert!(!bluecoats.started);
+        assert!(summary.events[1..].iter().all(|event| event.ticked == 1));
+
+        // The Spirit Wardens start without a clock, so they take up a goal.
+        let started = summary.events.iter().filter(|event| event.started).collect::<Vec<_>>();
+        assert_eq!(
+            vec!["Spirit Wardens"],
+            started.iter().map(|event| event.name.as_str()).collect::<Vec<_>>()
+        );
+
+        let clocks = campaign.clocks().await.expect("should have listed clocks");
+        assert_eq!(before.len() + 1, clocks.len());
+        assert!(clocks.contains(&(bluecoats.clock, Some(bluecoats.faction), bluecoats.progress.clone())));
+
+        let journal = campaign.store().journal(None, WORLD_EVENT, 100).await.expect("should have read journal");
+        assert_eq!(factions.len(), journal.len());
+    }
+
+    #[tokio::test]
+    async fn should_take_up_new_goal_once_clock_is_complete() {
+        let mut campaign = bootstrap().await;
+        let (clock, lampblacks, _) = campaign
+            .clocks()
+            .await
+            .expect("should have listed clocks")
+            .into_iter()
+            .find(|(_, _, clock)| clock.label() == "Destroy the Red Sashes")
+            .expect("should have the Lampblacks' clock");
+        campaign.complete_clock(Actor::Gm, clock).await.expect("should have completed clock");
+        let goals = campaign.goals().await.expect("should have read goals");
+
+        let summary = campaign
+            .faction_turn(Actor::Gm, &Loaded::new(&[]), &mut StdRng::seed_from_u64(1900))
+            .await
+            .expect("should have played faction turn");
+
+        let event = summary
+            .events
+            .iter()
+            .find(|event| Some(event.faction) == lampblacks)
+            .expect("should have moved the Lampblacks");
+        assert!(event.started);
+        assert!(goals.rows().iter().any(|row| row.value.descriptor.label() == event.progress.label()));
+        assert_eq!(1, event.progress.filled());
+        assert!(summary.to_string().contains("Lampblacks take up a new goal"));
+    }
+
+    #[tokio::test]
+    async fn should_only_let_gm_play_faction_turn() {
+        let mut campaign = bootstrap().await;
+
+        let err = campaign
+            .faction_turn(Actor::Player(Uuid::new_v4()), &Loaded::new(&[]), &mut StdRng::seed_from_u64(0))
+            .await
+            .expect_err("should have failed to play faction turn");
+
+        assert!(matches!(err, CampaignError::Permission(_)));
+    }
+}
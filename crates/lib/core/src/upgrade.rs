@@ -0,0 +1,229 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! Crew upgrades, as defined in content packs, and the modifiers they bring to rolls and downtime.
+//!
+//! Each [`Upgrade`] a crew can have is given data-driven [`UpgradeEffect`]s by an [`UpgradeDefinition`] of the content
+//! pack, e.g. fine weapons improving the quality of rolls tagged `weapons`, or Insight training marking an extra
+//! experience when a character trains Insight. [`Campaign::upgrade_effects`] gathers the effects of a crew's upgrades,
+//! which [`Campaign::assess_upgraded`] weighs in the effect of a roll and [`Campaign::training_xp`] in downtime.
+
+use std::collections::BTreeSet;
+
+use darkforge_data::descriptor::Descriptor;
+#[cfg(feature = "store")]
+use darkforge_data::store::{Content, World};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[cfg(feature = "store")]
+use crate::{
+    campaign::{Campaign, CampaignError, Result},
+    crew::Crew,
+    effect::{Approach, Assessment, Side, assess_effect},
+    item::MAX_QUALITY,
+};
+use crate::{context::RollContext, crew::Upgrade};
+
+/// Content pack category holding the upgrade definitions.
+pub const CATEGORY: &str = "upgrade";
+
+/// The experience a character marks when training in downtime, before the crew's upgrades.
+pub const TRAINING_XP: u8 = 1;
+
+/// What a crew upgrade does in play.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct UpgradeDefinition {
+    /// The id of the definition in the content pack.
+    pub id: Uuid,
+    /// The upgrade's descriptor.
+    pub descriptor: Descriptor<'static>,
+    /// The upgrade defined.
+    pub upgrade: Upgrade,
+    /// What the upgrade does in play.
+    #[serde(default)]
+    pub effects: Vec<UpgradeEffect>,
+}
+
+/// An effect of a crew upgrade.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case", tag = "kind")]
+pub enum UpgradeEffect {
+    /// Improves the quality of rolls whose context holds a tag matching any of the patterns, e.g. the kind of gear used.
+    Quality {
+        /// The tag patterns of the rolls improved, see [`RollContext::matches`].
+        tags: BTreeSet<String>,
+        /// The quality added.
+        bonus: u8,
+    },
+    /// Marks extra experience when a character trains the track in downtime.
+    TrainingXp {
+        /// The experience track trained, e.g. `insight` or `playbook`.
+        track: String,
+        /// The extra experience marked.
+        xp: u8,
+    },
+}
+
+/// Returns the quality the effects add to a roll made in the context.
+#[must_use]
+pub fn quality_bonus(effects: &[UpgradeEffect], context: &RollContext) -> u8 {
+    effects
+        .iter()
+        .filter_map(|effect| match effect {
+            UpgradeEffect::Quality { tags, bonus } if context.matches_any(tags) => Some(*bonus),
+            _ => None,
+        })
+        .fold(0, u8::saturating_add)
+}
+
+/// Returns the experience marked when training the track in downtime, [`TRAINING_XP`] along with what the effects add.
+#[must_use]
+pub fn training_xp(effects: &[UpgradeEffect], track: &str) -> u8 {
+    effects
+        .iter()
+        .filter_map(|effect| match effect {
+            UpgradeEffect::TrainingXp { track: trained, xp } if trained == track => Some(*xp),
+            _ => None,
+        })
+        .fold(TRAINING_XP, u8::saturating_add)
+}
+
+#[cfg(feature = "store")]
+impl Campaign {
+    /// Returns the definitions of the upgrades crews can have, as loaded from the content pack, sorted by label.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CampaignError`] if the store cannot be read.
+    pub async fn upgrades(&mut self) -> Result<Vec<UpgradeDefinition>> {
+        Ok(self
+            .store()
+            .sorted_entries(CATEGORY, |definition: &UpgradeDefinition| definition.descriptor.label())
+            .await?)
+    }
+
+    /// Returns the effects of the crew's upgrades, skipping upgrades the content pack does not define.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::MissingCrew`] if the entity is not a crew, or another [`CampaignError`] if the store
+    /// cannot be read.
+    pub async fn upgrade_effects(&mut self, crew: Uuid) -> Result<Vec<UpgradeEffect>> {
+        let upgrades = self.store().get::<Crew>(crew).await?.ok_or(CampaignError::MissingCrew(crew))?.upgrades;
+
+        Ok(self
+            .upgrades()
+            .await?
+            .into_iter()
+            .filter(|definition| upgrades.contains(&definition.upgrade))
+            .flat_map(|definition| definition.effects)
+            .collect())
+    }
+
+    /// Assesses the effect of a roll made in the context by a member of the crew, as [`assess_effect`] does, with the
+    /// quality of the `actor` improved by the crew's upgrades up to [`MAX_QUALITY`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error as [`Campaign::upgrade_effects`] does.
+    pub async fn assess_upgraded(&mut self, crew: Uuid, context: &RollContext, actor: Side, target: Side, approach: Approach) -> Result<Assessment> {
+        let bonus = quality_bonus(&self.upgrade_effects(crew).await?, context);
+        let actor = Side::new(actor.quality.saturating_add(bonus).min(MAX_QUALITY), actor.scale);
+
+        Ok(assess_effect(actor, target, approach))
+    }
+
+    /// Returns the experience a member of the crew marks when training the track in downtime, see [`training_xp`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error as [`Campaign::upgrade_effects`] does.
+    pub async fn training_xp(&mut self, crew: Uuid, track: &str) -> Result<u8> {
+        Ok(training_xp(&self.upgrade_effects(crew).await?, track))
+    }
+}
+
+#[cfg(all(test, feature = "store"))]
+mod tests {
+    use std::cmp::Ordering;
+
+    use darkforge_data::collation::Collation;
+
+    use super::*;
+    use crate::{
+        campaign::tests::bootstrap,
+        crew::{CrewType, Founding, HuntingGrounds, Reputation},
+        effect::{Effect, Factor},
+        permission::Actor,
+    };
+
+    #[tokio::test]
+    async fn should_apply_effects_of_crew_upgrades() {
+        let mut campaign = bootstrap().await;
+        let founding = Founding::new(
+            "The Lampblacks' Bane",
+            CrewType::Shadows,
+            Reputation::Daring,
+            HuntingGrounds {
+                district: "Crow's Foot".into(),
+                operation: "Burglary".into(),
+            },
+        )
+        .upgrade(Upgrade::Insight)
+        .upgrade(Upgrade::Weapons);
+        let crew = campaign.create_crew(Actor::Gm, &founding).await.expect("should have created crew");
+
+        assert_eq!(2, campaign.training_xp(crew, "insight").await.expect("should have counted experience"));
+        assert_eq!(
+            TRAINING_XP,
+            campaign.training_xp(crew, "prowess").await.expect("should have counted experience")
+        );
+
+        let armed = RollContext::new(Actor::Gm).tag("weapons");
+        let assessment = campaign
+            .assess_upgraded(crew, &armed, Side::new(1, 0), Side::new(1, 0), Approach::new(Effect::Standard))
+            .await
+            .expect("should have assessed effect");
+        assert_eq!((Factor::Advantage, Effect::Great), (assessment.factors.quality, assessment.effect));
+
+        let unarmed = RollContext::new(Actor::Gm).tag("unseen");
+        let assessment = campaign
+            .assess_upgraded(crew, &unarmed, Side::new(1, 0), Side::new(1, 0), Approach::new(Effect::Standard))
+            .await
+            .expect("should have assessed effect");
+        assert_eq!((Factor::Even, Effect::Standard), (assessment.factors.quality, assessment.effect));
+    }
+
+    #[tokio::test]
+    async fn should_define_every_upgrade_in_default_pack() {
+        let mut campaign = bootstrap().await;
+
+        let upgrades = campaign.upgrades().await.expect("should have listed upgrades");
+
+        assert_eq!(17, upgrades.iter().map(|definition| definition.upgrade).collect::<BTreeSet<_>>().len());
+    }
+
+    #[tokio::test]
+    async fn should_list_upgrades_by_label_per_the_collation_of_the_campaign() {
+        let mut campaign = bootstrap().await;
+        campaign.collate_with(Collation::new("sv").expect("should have created swedish collation"));
+
+        let upgrades = campaign.upgrades().await.expect("should have listed upgrades");
+
+        assert!(
+            upgrades
+                .windows(2)
+                .all(|pair| { campaign.collation().compare(pair[0].descriptor.label(), pair[1].descriptor.label()) != Ordering::Greater })
+        );
+    }
+}
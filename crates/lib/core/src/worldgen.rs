@@ -0,0 +1,355 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! World generation seeds a new campaign with a procedurally selected set of factions, a starting situation and the
+//! relationships between the factions, all drawn from the content pack.
+//!
+//! Generation is fully determined by its seed, so two players generating a world with the same pack and seed start in
+//! the same world.
+
+#[cfg(feature = "store")]
+use darkforge_data::store::World as _;
+#[cfg(feature = "store")]
+use darkforge_data::store::sql::sqlite::SqliteStore;
+use darkforge_data::{Component, Persisted, descriptor::Descriptor, pack::Pack};
+use rand::{SeedableRng, seq::SliceRandom};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    campaign::Result,
+    faction::{self, ClockTemplate, FactionTemplate},
+    table::{Row, Table},
+};
+#[cfg(feature = "store")]
+use crate::{
+    campaign::{Campaign, CampaignError},
+    changeset::{Access, Changeset},
+    clock::Clock,
+    permission::Actor,
+};
+
+/// Content pack category holding the weighted table of starting situations, see [`Row`].
+pub const CATEGORY: &str = "situation";
+/// Kind of the journal entry recording how a world was generated.
+pub const GENERATED: &str = "world.generated";
+
+/// The situation a generated campaign starts in.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Situation {
+    /// The id of the situation in the content pack.
+    pub id: Uuid,
+    /// The situation's descriptor.
+    pub descriptor: Descriptor<'static>,
+    /// The clocks ticking at the start of the campaign, owned by no faction.
+    #[serde(default)]
+    pub clocks: Vec<ClockTemplate>,
+}
+
+impl Component for Situation {}
+
+impl Persisted for Situation {
+    const KIND: &'static str = "situation";
+}
+
+/// How two factions stand with each other.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Relation {
+    /// The factions work together.
+    Ally,
+    /// The factions work against each other.
+    Enemy,
+}
+
+impl Relation {
+    /// Every relation, in the order they are looked up.
+    pub const ALL: [Relation; 2] = [Relation::Ally, Relation::Enemy];
+
+    /// Returns the kind of the relationship linking the factions in the store.
+    #[must_use]
+    pub fn kind(self) -> &'static str {
+        match self {
+            Relation::Ally => "faction.ally",
+            Relation::Enemy => "faction.enemy",
+        }
+    }
+}
+
+/// A relationship between two factions, identified by their template ids in a [`WorldPlan`] or by their entities in a
+/// campaign.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Relationship {
+    /// The first faction.
+    pub from: Uuid,
+    /// The second faction.
+    pub to: Uuid,
+    /// How they stand with each other.
+    pub relation: Relation,
+}
+
+/// The world a seed generates from a content pack.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct WorldPlan {
+    /// The seed the world was generated from.
+    pub seed: u64,
+    /// The starting situation, if the pack has any.
+    pub situation: Option<Situation>,
+    /// The factions selected, in pack order.
+    pub factions: Vec<FactionTemplate>,
+    /// The relationships between the selected factions.
+    pub relationships: Vec<Relationship>,
+}
+
+/// Generates the starting world of a campaign from a seed.
+///
+/// ```no_run
+/// # #[cfg(feature = "store")]
+/// # async fn generate() -> darkforge::campaign::Result<()> {
+/// use darkforge::worldgen::WorldGen;
+/// use darkforge_data::{pack::Pack, store::sql::sqlite::SqliteStore};
+///
+/// let pack = Pack::open("data/defaults")?;
+/// let store = SqliteStore::open("campaign.db").await?;
+/// let campaign = WorldGen::new(1902).factions(4).bootstrap(store, "Crow's Foot", &pack).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WorldGen {
+    seed: u64,
+    factions: usize,
+}
+
+impl WorldGen {
+    /// The number of factions selected unless told otherwise.
+    pub const DEFAULT_FACTIONS: usize = 5;
+
+    /// Creates a world generator for the seed.
+    #[must_use]
+    pub fn new(seed: u64) -> WorldGen {
+        WorldGen {
+            seed,
+            factions: WorldGen::DEFAULT_FACTIONS,
+        }
+    }
+
+    /// Sets how many factions to select, every faction of the pack being selected if it has fewer.
+    #[must_use]
+    pub fn factions(self, count: usize) -> WorldGen {
+        WorldGen { factions: count, ..self }
+    }
+
+    /// Returns the seed of the generator.
+    #[must_use]
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Generates the world from the pack without creating it, the same seed and pack always giving the same plan.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CampaignError`] if the pack's factions or situations are invalid.
+    pub fn plan(&self, pack: &Pack) -> Result<WorldPlan> {
+        let mut rng = ChaCha8Rng::seed_from_u64(self.seed);
+
+        let situations = pack.load::<Row<Situation>>(CATEGORY)?.into_iter().collect::<Table<_>>();
+        let situation = situations.roll(&mut rng).cloned();
+
+        let templates = pack.load::<FactionTemplate>(faction::CATEGORY)?;
+        let mut picked = (0..templates.len()).collect::<Vec<_>>();
+        picked.shuffle(&mut rng);
+        picked.truncate(self.factions);
+        picked.sort_unstable();
+        let factions = picked.into_iter().map(|index| templates[index].clone()).collect::<Vec<_>>();
+
+        let relations = Table::new(vec![
+            Row::new(1, Some(Relation::Ally)),
+            Row::new(1, Some(Relation::Enemy)),
+            Row::new(3, None),
+        ]);
+        let mut relationships = Vec::new();
+        for (index, from) in factions.iter().enumerate() {
+            for to in &factions[index + 1..] {
+                if let Some(&Some(relation)) = relations.roll(&mut rng) {
+                    relationships.push(Relationship {
+                        from: from.id,
+                        to: to.id,
+                        relation,
+                    });
+                }
+            }
+        }
+
+        Ok(WorldPlan {
+            seed: self.seed,
+            situation,
+            factions,
+            relationships,
+        })
+    }
+
+    /// Initializes a fresh campaign in the store with the world generated from the pack, recording the plan in the
+    /// journal.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::AlreadyExists`] if the store already holds a campaign, or another [`CampaignError`] if
+    /// the pack is invalid or the store cannot be written.
+    #[cfg(feature = "store")]
+    pub async fn bootstrap(&self, store: SqliteStore, name: impl Into<String>, pack: &Pack) -> Result<Campaign> {
+        let plan = self.plan(pack)?;
+        let clocks = plan
+            .situation
+            .iter()
+            .flat_map(|situation| situation.clocks.iter().map(move |clock| (situation, clock)))
+            .map(|(situation, clock)| {
+                Clock::with_progress(clock.label.clone(), clock.segments, clock.filled).map_err(|source| CampaignError::InvalidClock {
+                    faction: situation.descriptor.label().into(),
+                    source,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let (mut campaign, entities) = Campaign::found(store, name, pack, plan.factions.clone()).await?;
+        let id = campaign.id();
+        let entity = |template: Uuid| entities.iter().find(|(id, _)| *id == template).map(|(_, entity)| *entity);
+
+        let mut changeset = Changeset::new(Actor::Gm, Access::Gm);
+        if let Some(situation) = &plan.situation {
+            changeset = changeset.insert(id, situation)?;
+        }
+        for clock in clocks {
+            let clock_entity = Uuid::new_v4();
+            changeset = changeset.spawn(clock_entity).insert(clock_entity, &clock)?;
+        }
+        for relationship in &plan.relationships {
+            if let (Some(from), Some(to)) = (entity(relationship.from), entity(relationship.to)) {
+                changeset = changeset.relate(from, to, relationship.relation.kind());
+            }
+        }
+        campaign.apply(changeset.record(Some(id), GENERATED, &plan)?).await?;
+
+        Ok(campaign)
+    }
+}
+
+#[cfg(feature = "store")]
+impl Campaign {
+    /// Returns the situation the campaign started in, if it was generated with one.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CampaignError`] if the store cannot be read.
+    pub async fn situation(&mut self) -> Result<Option<Situation>> {
+        let id = self.id();
+
+        Ok(self.store().get::<Situation>(id).await?)
+    }
+
+    /// Returns the relationships between the campaign's factions, by faction entity.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CampaignError`] if the store cannot be read.
+    pub async fn relationships(&mut self) -> Result<Vec<Relationship>> {
+        let mut relationships = Vec::new();
+        for (from, _) in self.factions().await? {
+            for relation in Relation::ALL {
+                for to in self.store().related(from, relation.kind()).await? {
+                    relationships.push(Relationship { from, to, relation });
+                }
+            }
+        }
+
+        Ok(relationships)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::campaign::tests::DEFAULTS;
+    #[cfg(feature = "store")]
+    use crate::campaign::tests::db;
+
+    fn pack() -> Pack {
+        Pack::open(DEFAULTS).expect("should have opened pack")
+    }
+
+    #[test]
+    fn should_generate_same_plan_from_same_seed() {
+        let pack = pack();
+
+        let first = WorldGen::new(1902).plan(&pack).expect("should have planned world");
+        let second = WorldGen::new(1902).plan(&pack).expect("should have planned world");
+
+        assert_eq!(first, second);
+        assert_eq!(WorldGen::DEFAULT_FACTIONS, first.factions.len());
+        assert!(first.situation.is_some());
+    }
+
+    #[test]
+    fn should_generate_different_worlds_from_different_seeds() {
+        let pack = pack();
+
+        let plans = (0..8)
+            .map(|seed| WorldGen::new(seed).plan(&pack).expect("should have planned world"))
+            .collect::<Vec<_>>();
+
+        assert!(plans.windows(2).any(|pair| pair[0].factions != pair[1].factions));
+    }
+
+    #[test]
+    fn should_select_every_faction_given_more_than_the_pack_has() {
+        let pack = pack();
+
+        let plan = WorldGen::new(7).factions(100).plan(&pack).expect("should have planned world");
+
+        assert_eq!(pack.entries(faction::CATEGORY).len(), plan.factions.len());
+        assert!(plan.relationships.iter().all(|relationship| relationship.from != relationship.to));
+    }
+
+    #[cfg(feature = "store")]
+    #[tokio::test]
+    async fn should_bootstrap_generated_world() {
+        let pack = pack();
+        let generator = WorldGen::new(1902).factions(4);
+        let plan = generator.plan(&pack).expect("should have planned world");
+        let store = SqliteStore::open(db()).await.expect("should have opened store");
+
+        let mut campaign = generator
+            .bootstrap(store, "Crow's Foot", &pack)
+            .await
+            .expect("should have bootstrapped campaign");
+
+        let factions = campaign.factions().await.expect("should have listed factions");
+        assert_eq!(
+            plan.factions.iter().map(|template| template.descriptor.label()).collect::<Vec<_>>(),
+            factions.iter().map(|(_, faction)| faction.descriptor().label()).collect::<Vec<_>>()
+        );
+        assert_eq!(plan.situation, campaign.situation().await.expect("should have read situation"));
+
+        let situation_clocks = plan.situation.as_ref().map_or(0, |situation| situation.clocks.len());
+        let faction_clocks = plan.factions.iter().map(|template| template.clocks.len()).sum::<usize>();
+        let clocks = campaign.clocks().await.expect("should have listed clocks");
+        assert_eq!(situation_clocks + faction_clocks, clocks.len());
+        assert_eq!(situation_clocks, clocks.iter().filter(|(_, owner, _)| owner.is_none()).count());
+
+        let relationships = campaign.relationships().await.expect("should have listed relationships");
+        assert_eq!(plan.relationships.len(), relationships.len());
+        let journal = campaign.store().journal(None, GENERATED, 1).await.expect("should have read journal");
+        assert_eq!(1, journal.len());
+    }
+}
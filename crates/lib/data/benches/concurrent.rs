@@ -0,0 +1,160 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! Compares the journal modes and connection pools of [`SqliteStoreBuilder`] while a writer rolls and updates
+//! character sheets and several screens read them, as happens during active play.
+//!
+//! Run with `cargo bench -p darkforge-data --bench concurrent`. Every store is built without a cache, so that reads
+//! reach the database.
+
+use std::{
+    env, fs,
+    path::Path,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use darkforge_data::{
+    Component, Persisted,
+    store::{
+        Migrator, World,
+        sql::sqlite::{MIGRATIONS, SqliteStore, SqliteStoreBuilder},
+    },
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// How long each scenario runs.
+const DURATION: Duration = Duration::from_secs(2);
+/// The number of threads reading character sheets.
+const READERS: usize = 4;
+/// The number of characters.
+const CHARACTERS: usize = 16;
+
+/// A named way of building the stores.
+type Scenario = (&'static str, fn(&Path) -> SqliteStoreBuilder);
+
+#[derive(Serialize, Deserialize)]
+struct Stress(u8);
+
+impl Component for Stress {}
+
+impl Persisted for Stress {
+    const KIND: &'static str = "stress";
+}
+
+/// Operation counts of a scenario.
+#[derive(Default)]
+struct Counts {
+    writes: AtomicU64,
+    reads: AtomicU64,
+    failures: AtomicU64,
+}
+
+fn main() {
+    let scenarios: [Scenario; 4] = [
+        ("rollback journal", |path| SqliteStore::builder(path).wal(false).busy_timeout(None)),
+        ("rollback journal, busy timeout", |path| SqliteStore::builder(path).wal(false)),
+        ("wal, busy timeout", |path| SqliteStore::builder(path)),
+        ("wal, busy timeout, 2 readers", |path| SqliteStore::builder(path).readers(2)),
+    ];
+
+    println!("{:<32} {:>12} {:>12} {:>10}", "scenario", "writes/s", "reads/s", "failures");
+    for (name, builder) in scenarios {
+        let dir = env::temp_dir().join(format!("darkforge-bench-{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).expect("should have created directory");
+        let path = dir.join("campaign.db");
+
+        let counts = run(&path, builder);
+        let seconds = DURATION.as_secs();
+        println!(
+            "{name:<32} {:>12} {:>12} {:>10}",
+            counts.writes.load(Ordering::Relaxed) / seconds,
+            counts.reads.load(Ordering::Relaxed) / seconds,
+            counts.failures.load(Ordering::Relaxed),
+        );
+
+        fs::remove_dir_all(dir).expect("should have removed directory");
+    }
+}
+
+/// Runs a writer and [`READERS`] readers on the database for [`DURATION`], each with its own store.
+fn run(path: &Path, builder: fn(&Path) -> SqliteStoreBuilder) -> Counts {
+    let characters = block_on(async {
+        let mut store = builder(path).cached(false).build().await.expect("should have opened store");
+        store.apply(MIGRATIONS).await.expect("should have applied migrations");
+
+        let mut characters = Vec::with_capacity(CHARACTERS);
+        for _ in 0..CHARACTERS {
+            let entity = store.spawn().await.expect("should have spawned character");
+            store.insert(entity, &Stress(0)).await.expect("should have inserted stress");
+            characters.push(entity);
+        }
+
+        characters
+    });
+
+    let characters = Arc::new(characters);
+    let counts = Arc::new(Counts::default());
+    let done = Arc::new(AtomicBool::new(false));
+
+    let mut threads = Vec::with_capacity(READERS + 1);
+    for reader in 0..=READERS {
+        let (path, characters, counts, done) = (path.to_path_buf(), characters.clone(), counts.clone(), done.clone());
+        threads.push(thread::spawn(move || {
+            block_on(async {
+                let mut store = builder(&path).cached(false).build().await.expect("should have opened store");
+                let mut turn = 0usize;
+                while !done.load(Ordering::Relaxed) {
+                    let entity = characters[turn % characters.len()];
+                    turn += 1;
+
+                    if reader == 0 {
+                        let stress = Stress(u8::try_from(turn % 9).unwrap_or_default());
+                        match store.insert(entity, &stress).await {
+                            Ok(()) => counts.writes.fetch_add(1, Ordering::Relaxed),
+                            Err(_) => counts.failures.fetch_add(1, Ordering::Relaxed),
+                        };
+                    } else {
+                        match store.get::<Stress>(entity).await {
+                            Ok(_) => counts.reads.fetch_add(1, Ordering::Relaxed),
+                            Err(_) => counts.failures.fetch_add(1, Ordering::Relaxed),
+                        };
+                    }
+                }
+            });
+        }));
+    }
+
+    let started = Instant::now();
+    thread::sleep(DURATION);
+    done.store(true, Ordering::Relaxed);
+    for thread in threads {
+        thread.join().expect("should have joined thread");
+    }
+    assert!(started.elapsed() >= DURATION);
+
+    Arc::into_inner(counts).unwrap_or_default()
+}
+
+/// Runs the future to completion on a runtime of the current thread.
+fn block_on<F: Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("should have built runtime")
+        .block_on(future)
+}
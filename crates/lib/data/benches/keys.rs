@@ -0,0 +1,168 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! Compares joining the component and journal tables on the entity ids against joining them on the integer keys the
+//! store maps the ids to, in a campaign grown over many sessions.
+//!
+//! Run with `cargo bench -p darkforge-data --bench keys`. The world is seeded through [`SqliteStore`], then each query
+//! is timed on a plain connection so that both forms run against the same database.
+
+use std::{
+    env, fs,
+    time::{Duration, Instant},
+};
+
+use darkforge_data::{
+    Component, Persisted,
+    store::{
+        Change, Migrator, World,
+        sql::sqlite::{MIGRATIONS, SqliteStore},
+    },
+};
+use libsql::{Connection, params};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// The number of entities in the campaign.
+const ENTITIES: usize = 5_000;
+/// The number of journal entries recorded about each entity.
+const ENTRIES: usize = 10;
+/// How many times each query runs.
+const RUNS: u32 = 20;
+
+/// A query joined on the entity ids and the same query joined on the keys.
+type Scenario = (&'static str, &'static str, &'static str);
+
+#[derive(Serialize, Deserialize)]
+struct Stress(u8);
+
+impl Component for Stress {}
+
+impl Persisted for Stress {
+    const KIND: &'static str = "stress";
+}
+
+#[derive(Serialize, Deserialize)]
+struct Archived {
+    archived: bool,
+}
+
+impl Component for Archived {}
+
+impl Persisted for Archived {
+    const KIND: &'static str = "archived";
+}
+
+fn main() {
+    let scenarios: [Scenario; 2] = [
+        (
+            "components of a kind",
+            "SELECT COUNT(*) FROM components c JOIN entities e ON e.id = c.entity_id
+             WHERE c.kind = 'stress' AND e.campaign_id = ?1 AND NOT EXISTS (
+                SELECT 1 FROM components a WHERE a.entity_id = c.entity_id AND a.kind = 'archived'
+                AND json_extract(a.data, '$.archived'))",
+            "SELECT COUNT(*) FROM components c JOIN entities e ON e.key = c.entity_key
+             WHERE c.kind = 'stress' AND e.campaign_id = ?1 AND NOT EXISTS (
+                SELECT 1 FROM components a WHERE a.entity_key = c.entity_key AND a.kind = 'archived'
+                AND json_extract(a.data, '$.archived'))",
+        ),
+        (
+            "journal with entity components",
+            "SELECT COUNT(*) FROM journal j JOIN components c ON c.entity_id = j.entity_id AND c.kind = 'stress'
+             WHERE j.campaign_id = ?1 AND j.kind = 'roll'",
+            "SELECT COUNT(*) FROM journal j JOIN components c ON c.entity_key = j.entity_key AND c.kind = 'stress'
+             WHERE j.campaign_id = ?1 AND j.kind = 'roll'",
+        ),
+    ];
+
+    let dir = env::temp_dir().join(format!("darkforge-bench-{}", Uuid::new_v4()));
+    fs::create_dir_all(&dir).expect("should have created directory");
+    let path = dir.join("campaign.db");
+
+    block_on(async {
+        seed(&path).await;
+
+        let database = libsql::Builder::new_local(&path).build().await.expect("should have opened database");
+        let conn = database.connect().expect("should have connected");
+        let mut rows = conn
+            .query("SELECT campaign_id FROM entities LIMIT 1", ())
+            .await
+            .expect("should have queried");
+        let campaign: Vec<u8> = rows
+            .next()
+            .await
+            .expect("should have read row")
+            .expect("should have found campaign")
+            .get(0)
+            .expect("should have read campaign");
+
+        println!("{:<32} {:>12} {:>12} {:>8}", "query", "ids (ms)", "keys (ms)", "speedup");
+        for (name, ids, keys) in scenarios {
+            let (by_id, id_count) = time(&conn, ids, &campaign).await;
+            let (by_key, key_count) = time(&conn, keys, &campaign).await;
+            assert_eq!(id_count, key_count, "both forms of {name} should have counted the same rows");
+            println!(
+                "{name:<32} {:>12.3} {:>12.3} {:>7.2}x",
+                by_id.as_secs_f64() * 1_000.0,
+                by_key.as_secs_f64() * 1_000.0,
+                by_id.as_secs_f64() / by_key.as_secs_f64(),
+            );
+        }
+    });
+
+    fs::remove_dir_all(dir).expect("should have removed directory");
+}
+
+/// Seeds [`ENTITIES`] entities with stress, every tenth one archived, and [`ENTRIES`] rolls recorded about each.
+async fn seed(path: &std::path::Path) {
+    let mut store = SqliteStore::builder(path).cached(false).build().await.expect("should have opened store");
+    store.apply(MIGRATIONS).await.expect("should have applied migrations");
+
+    for entity in 0..ENTITIES {
+        let id = store.spawn().await.expect("should have spawned entity");
+        let mut changes = vec![
+            Change::insert(id, &Stress(u8::try_from(entity % 9).unwrap_or_default())).expect("should have encoded stress"),
+            Change::insert(id, &Archived { archived: entity % 10 == 0 }).expect("should have encoded archived"),
+        ];
+        for entry in 0..ENTRIES {
+            changes.push(Change::record(Some(id), "roll", &entry).expect("should have encoded roll"));
+        }
+        store.commit(&changes).await.expect("should have committed changes");
+    }
+}
+
+/// Runs the query [`RUNS`] times, returning the mean duration and the count it read.
+async fn time(conn: &Connection, sql: &str, campaign: &[u8]) -> (Duration, i64) {
+    let mut count = 0;
+    let started = Instant::now();
+    for _ in 0..RUNS {
+        let mut rows = conn.query(sql, params![campaign]).await.expect("should have queried");
+        count = rows
+            .next()
+            .await
+            .expect("should have read row")
+            .expect("should have found count")
+            .get(0)
+            .expect("should have read count");
+    }
+
+    (started.elapsed() / RUNS, count)
+}
+
+/// Runs the future to completion on a runtime of the current thread.
+fn block_on<F: Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("should have built runtime")
+        .block_on(future)
+}
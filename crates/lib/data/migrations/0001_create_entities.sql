@@ -0,0 +1,5 @@
+CREATE TABLE IF NOT EXISTS entities (
+    id         BLOB    NOT NULL,
+    created_at INTEGER NOT NULL DEFAULT (unixepoch()),
+    CONSTRAINT entities_pk PRIMARY KEY (id)
+);
@@ -0,0 +1,7 @@
+CREATE TABLE IF NOT EXISTS components (
+    entity_id BLOB NOT NULL,
+    kind      TEXT NOT NULL,
+    data      TEXT NOT NULL,
+    CONSTRAINT components_pk PRIMARY KEY (entity_id, kind),
+    CONSTRAINT components_entity_fk FOREIGN KEY (entity_id) REFERENCES entities (id)
+);
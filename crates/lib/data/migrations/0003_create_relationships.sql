@@ -0,0 +1,8 @@
+CREATE TABLE IF NOT EXISTS relationships (
+    source_id BLOB NOT NULL,
+    target_id BLOB NOT NULL,
+    kind      TEXT NOT NULL,
+    CONSTRAINT relationships_pk PRIMARY KEY (source_id, target_id, kind),
+    CONSTRAINT relationships_source_fk FOREIGN KEY (source_id) REFERENCES entities (id),
+    CONSTRAINT relationships_target_fk FOREIGN KEY (target_id) REFERENCES entities (id)
+);
@@ -0,0 +1,9 @@
+CREATE TABLE IF NOT EXISTS journal (
+    seq         INTEGER NOT NULL,
+    entity_id   BLOB,
+    kind        TEXT    NOT NULL,
+    data        TEXT    NOT NULL,
+    recorded_at INTEGER NOT NULL DEFAULT (unixepoch()),
+    CONSTRAINT journal_pk PRIMARY KEY (seq),
+    CONSTRAINT journal_entity_fk FOREIGN KEY (entity_id) REFERENCES entities (id)
+);
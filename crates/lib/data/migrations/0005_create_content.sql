@@ -0,0 +1,7 @@
+CREATE TABLE IF NOT EXISTS content (
+    category TEXT NOT NULL,
+    id       BLOB NOT NULL,
+    pack     TEXT NOT NULL,
+    data     TEXT NOT NULL,
+    CONSTRAINT content_pk PRIMARY KEY (category, id)
+);
@@ -0,0 +1,5 @@
+CREATE TABLE IF NOT EXISTS pruned (
+    kind   TEXT    NOT NULL,
+    before INTEGER NOT NULL,
+    CONSTRAINT pruned_pk PRIMARY KEY (kind)
+);
@@ -0,0 +1 @@
+ALTER TABLE entities ADD COLUMN campaign_id BLOB NOT NULL DEFAULT X'00000000000000000000000000000000';
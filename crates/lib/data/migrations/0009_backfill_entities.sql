@@ -0,0 +1,3 @@
+UPDATE entities
+SET campaign_id = (SELECT entity_id FROM components WHERE kind = 'campaign' ORDER BY rowid LIMIT 1)
+WHERE EXISTS (SELECT 1 FROM components WHERE kind = 'campaign');
@@ -0,0 +1 @@
+CREATE INDEX IF NOT EXISTS entities_campaign_idx ON entities (campaign_id);
@@ -0,0 +1 @@
+CREATE INDEX IF NOT EXISTS journal_campaign_idx ON journal (campaign_id, kind, seq);
@@ -0,0 +1 @@
+ALTER TABLE entities ADD COLUMN revision INTEGER NOT NULL DEFAULT 0;
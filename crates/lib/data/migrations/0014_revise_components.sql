@@ -0,0 +1 @@
+ALTER TABLE components ADD COLUMN revision INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1 @@
+ALTER TABLE relationships ADD COLUMN revision INTEGER NOT NULL DEFAULT 0;
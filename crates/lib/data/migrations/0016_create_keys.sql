@@ -0,0 +1,6 @@
+CREATE TABLE IF NOT EXISTS keys (
+    key INTEGER NOT NULL,
+    id  BLOB    NOT NULL,
+    CONSTRAINT keys_pk PRIMARY KEY (key),
+    CONSTRAINT keys_id_uq UNIQUE (id)
+);
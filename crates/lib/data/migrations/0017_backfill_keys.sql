@@ -0,0 +1 @@
+INSERT OR IGNORE INTO keys (id) SELECT id FROM entities ORDER BY rowid;
@@ -0,0 +1 @@
+ALTER TABLE entities ADD COLUMN key INTEGER REFERENCES keys (key);
@@ -0,0 +1 @@
+UPDATE entities SET key = (SELECT k.key FROM keys k WHERE k.id = entities.id);
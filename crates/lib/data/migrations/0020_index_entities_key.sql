@@ -0,0 +1 @@
+CREATE UNIQUE INDEX IF NOT EXISTS entities_key_idx ON entities (key, campaign_id);
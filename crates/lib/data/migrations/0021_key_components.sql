@@ -0,0 +1 @@
+ALTER TABLE components ADD COLUMN entity_key INTEGER REFERENCES keys (key);
@@ -0,0 +1 @@
+UPDATE components SET entity_key = (SELECT k.key FROM keys k WHERE k.id = components.entity_id);
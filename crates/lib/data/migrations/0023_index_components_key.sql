@@ -0,0 +1 @@
+CREATE INDEX IF NOT EXISTS components_key_idx ON components (entity_key, kind);
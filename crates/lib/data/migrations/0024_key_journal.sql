@@ -0,0 +1 @@
+ALTER TABLE journal ADD COLUMN entity_key INTEGER REFERENCES keys (key);
@@ -0,0 +1 @@
+UPDATE journal SET entity_key = (SELECT k.key FROM keys k WHERE k.id = journal.entity_id) WHERE entity_id IS NOT NULL;
@@ -0,0 +1 @@
+CREATE INDEX IF NOT EXISTS journal_key_idx ON journal (campaign_id, kind, entity_key, seq);
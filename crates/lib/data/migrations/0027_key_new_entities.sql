@@ -0,0 +1,5 @@
+CREATE TRIGGER IF NOT EXISTS entities_key_trg AFTER INSERT ON entities
+BEGIN
+    INSERT OR IGNORE INTO keys (id) VALUES (new.id);
+    UPDATE entities SET key = (SELECT k.key FROM keys k WHERE k.id = new.id) WHERE rowid = new.rowid;
+END;
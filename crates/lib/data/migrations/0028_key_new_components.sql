@@ -0,0 +1,5 @@
+CREATE TRIGGER IF NOT EXISTS components_key_trg AFTER INSERT ON components
+BEGIN
+    INSERT OR IGNORE INTO keys (id) VALUES (new.entity_id);
+    UPDATE components SET entity_key = (SELECT k.key FROM keys k WHERE k.id = new.entity_id) WHERE rowid = new.rowid;
+END;
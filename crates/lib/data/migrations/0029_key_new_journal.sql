@@ -0,0 +1,5 @@
+CREATE TRIGGER IF NOT EXISTS journal_key_trg AFTER INSERT ON journal WHEN new.entity_id IS NOT NULL
+BEGIN
+    INSERT OR IGNORE INTO keys (id) VALUES (new.entity_id);
+    UPDATE journal SET entity_key = (SELECT k.key FROM keys k WHERE k.id = new.entity_id) WHERE seq = new.seq;
+END;
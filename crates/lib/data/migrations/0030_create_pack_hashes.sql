@@ -0,0 +1,5 @@
+CREATE TABLE IF NOT EXISTS pack_hashes (
+    pack TEXT NOT NULL,
+    hash TEXT NOT NULL,
+    CONSTRAINT pack_hashes_pk PRIMARY KEY (pack)
+);
@@ -0,0 +1,5 @@
+CREATE TABLE IF NOT EXISTS component_schemas (
+    kind TEXT NOT NULL,
+    version INTEGER NOT NULL,
+    CONSTRAINT component_schemas_pk PRIMARY KEY (kind)
+);
@@ -0,0 +1,6 @@
+CREATE TABLE IF NOT EXISTS despawned (
+    entity_id BLOB    NOT NULL,
+    revision  INTEGER NOT NULL,
+    CONSTRAINT despawned_pk PRIMARY KEY (entity_id),
+    CONSTRAINT despawned_entity_fk FOREIGN KEY (entity_id) REFERENCES entities (id)
+);
@@ -0,0 +1,7 @@
+CREATE TABLE IF NOT EXISTS prunes (
+    campaign_id BLOB    NOT NULL,
+    kind        TEXT    NOT NULL,
+    before      INTEGER NOT NULL,
+    revision    INTEGER NOT NULL,
+    CONSTRAINT prunes_pk PRIMARY KEY (campaign_id, kind)
+);
@@ -13,7 +13,10 @@
 
 //! Serialization/deserialization helpers with unified error handling. All types implementing `Serialize` and `Deserialize` from serde should be automatically compatible.
 
-use std::io::{Read, Write};
+use std::{
+    fmt::Write as _,
+    io::{Read, Write},
+};
 
 use anyhow::anyhow;
 use thiserror::Error;
@@ -54,11 +57,70 @@ pub trait JSONDeserialize: serde::de::DeserializeOwned {
     fn from_json(json: impl Read) -> Result<Self> {
         serde_json::from_reader(json).map_err(|e| CodecError::Deserialize(anyhow!(e)))
     }
+
+    /// Deserialize self from the given JSON reader, ignoring `//` and `/* */` comments.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CodecError::Deserialize`] if the reader fails or deserialization fails.
+    fn from_jsonc(mut jsonc: impl Read) -> Result<Self> {
+        let mut text = String::new();
+        jsonc.read_to_string(&mut text).map_err(|e| CodecError::Deserialize(anyhow!(e)))?;
+
+        serde_json::from_str(&strip_comments(&text)).map_err(|e| CodecError::Deserialize(anyhow!(e)))
+    }
+}
+
+/// Removes comments from JSON text, leaving string literals untouched.
+fn strip_comments(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    let mut in_string = false;
+
+    while let Some(c) = chars.next() {
+        if in_string {
+            out.push(c);
+            match c {
+                '\\' => out.extend(chars.next()),
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+
+        match (c, chars.peek()) {
+            ('/', Some('/')) => while chars.next_if(|&n| n != '\n').is_some() {},
+            ('/', Some('*')) => {
+                chars.next();
+                let mut prev = None;
+                for n in chars.by_ref() {
+                    if prev == Some('*') && n == '/' {
+                        break;
+                    }
+                    prev = Some(n);
+                }
+            }
+            _ => {
+                in_string = c == '"';
+                out.push(c);
+            }
+        }
+    }
+
+    out
 }
 
 impl<T> JSONSerialize for T where T: serde::Serialize {}
 impl<T> JSONDeserialize for T where T: serde::de::DeserializeOwned {}
 
+/// Encodes bytes in lowercase hexadecimal.
+pub(crate) fn hex(bytes: &[u8]) -> String {
+    bytes.iter().fold(String::with_capacity(bytes.len() * 2), |mut hex, byte| {
+        let _ = write!(hex, "{byte:02x}");
+        hex
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use proptest::prelude::*;
@@ -74,6 +136,30 @@ mod tests {
         c: Vec<u8>,
     }
 
+    #[test]
+    fn test_from_jsonc() {
+        const JSONC: &str = r#"
+            {
+                // line comment
+                "a": 42, /* block
+                comment */
+                "b": "not // a comment",
+                "c": [1, 2, 3] // trailing comment
+            }
+        "#;
+
+        let deserialized = DummyType::from_jsonc(JSONC.as_bytes()).expect("should have deserialized");
+
+        assert_eq!(
+            DummyType {
+                a: 42,
+                b: Some("not // a comment".into()),
+                c: vec![1, 2, 3],
+            },
+            deserialized
+        );
+    }
+
     proptest! {
         #[test]
         fn test_to_json(dummy in any::<DummyType>()) {
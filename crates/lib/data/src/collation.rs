@@ -0,0 +1,137 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! Locale-aware ordering of the labels and names lists show, so content packs in other languages than English sort
+//! the way their readers expect rather than by byte order.
+//!
+//! [`Collation::root`] orders text by the Unicode root collation, which already places accented letters next to their
+//! base letters. [`Collation::new`] tailors it to the conventions of a language, e.g. Swedish sorting "ö" after "z".
+
+use std::{cmp::Ordering, fmt, sync::Arc};
+
+use icu_collator::{Collator, CollatorError, CollatorOptions};
+use icu_locid::{Locale, ParserError};
+use thiserror::Error;
+
+/// Error type for collations that cannot be created.
+#[derive(Error, Debug)]
+pub enum CollationError {
+    /// The locale is not a valid BCP 47 language tag.
+    #[error("invalid locale {0}: {1}")]
+    Locale(String, ParserError),
+    /// No collation data is available for the locale.
+    #[error("cannot collate for locale {0}: {1}")]
+    Collator(String, CollatorError),
+}
+
+/// Orders text by the collation rules of a locale. Cloning a collation shares its rules.
+#[derive(Clone)]
+pub struct Collation {
+    locale: Locale,
+    collator: Arc<Collator>,
+}
+
+impl Collation {
+    /// Creates a collation for the locale, given as a BCP 47 language tag such as `sv` or `de-AT`. Locales without
+    /// rules of their own fall back to those of their language, then to the root collation.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CollationError::Locale`] if the tag is invalid, or [`CollationError::Collator`] if no collation data
+    /// is available for it.
+    pub fn new(locale: &str) -> Result<Collation, CollationError> {
+        let locale = locale.parse::<Locale>().map_err(|e| CollationError::Locale(locale.to_string(), e))?;
+
+        Collation::of(locale)
+    }
+
+    /// Returns the root collation, which orders text the same in every locale.
+    ///
+    /// # Panics
+    ///
+    /// Never in practice, as the collation data compiled in always holds the root collation.
+    #[must_use]
+    pub fn root() -> Collation {
+        Collation::of(Locale::UND).expect("should have compiled collation data for the root locale")
+    }
+
+    fn of(locale: Locale) -> Result<Collation, CollationError> {
+        let collator = Collator::try_new(&(&locale).into(), CollatorOptions::new()).map_err(|e| CollationError::Collator(locale.to_string(), e))?;
+
+        Ok(Collation {
+            locale,
+            collator: Arc::new(collator),
+        })
+    }
+
+    /// Returns the language tag of the locale the collation follows, `und` for the root collation.
+    #[must_use]
+    pub fn locale(&self) -> String {
+        self.locale.to_string()
+    }
+
+    /// Compares two strings by the collation rules.
+    #[must_use]
+    pub fn compare(&self, a: &str, b: &str) -> Ordering {
+        self.collator.compare(a, b)
+    }
+
+    /// Sorts the items by the text `key` picks out of them, keeping items with equal text in their order.
+    pub fn sort_by_key<T>(&self, items: &mut [T], key: impl Fn(&T) -> &str) {
+        items.sort_by(|a, b| self.compare(key(a), key(b)));
+    }
+}
+
+impl Default for Collation {
+    fn default() -> Collation {
+        Collation::root()
+    }
+}
+
+impl fmt::Debug for Collation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Collation").field("locale", &self.locale()).finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_sort_accented_letters_with_their_base_letter() {
+        let mut names = vec!["Zed", "Émile", "Adèle", "Eli"];
+        Collation::root().sort_by_key(&mut names, |name| name);
+
+        assert_eq!(vec!["Adèle", "Eli", "Émile", "Zed"], names);
+    }
+
+    #[test]
+    fn should_follow_the_rules_of_the_locale() {
+        let mut swedish = vec!["Öland", "Zinkgruvan", "Orsa"];
+        Collation::new("sv")
+            .expect("should have created swedish collation")
+            .sort_by_key(&mut swedish, |name| name);
+        let mut german = swedish.clone();
+        Collation::new("de")
+            .expect("should have created german collation")
+            .sort_by_key(&mut german, |name| name);
+
+        assert_eq!(vec!["Orsa", "Zinkgruvan", "Öland"], swedish);
+        assert_eq!(vec!["Öland", "Orsa", "Zinkgruvan"], german);
+    }
+
+    #[test]
+    fn should_reject_invalid_locales() {
+        assert!(matches!(Collation::new("not a locale"), Err(CollationError::Locale(..))));
+    }
+}
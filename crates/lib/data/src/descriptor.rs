@@ -27,9 +27,11 @@
 
 use std::borrow::Cow;
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::markup::{Markup, MarkupError};
+
 /// An entity descriptor containing a UUID, label, and description.
 ///
 /// # Example
@@ -49,13 +51,51 @@ use uuid::Uuid;
 /// let desc = Descriptor::from_json(JSON.as_bytes()).expect("should have deserialized descriptor");
 ///
 /// ```
-#[derive(Deserialize, Debug, PartialEq)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct Descriptor<'a> {
     id: Uuid,
     label: Cow<'a, str>,
     description: Cow<'a, str>,
 }
 
+impl<'a> Descriptor<'a> {
+    /// Creates a new descriptor.
+    pub fn new(id: Uuid, label: impl Into<Cow<'a, str>>, description: impl Into<Cow<'a, str>>) -> Self {
+        Self {
+            id,
+            label: label.into(),
+            description: description.into(),
+        }
+    }
+
+    /// Returns the descriptor's id.
+    #[must_use]
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+
+    /// Returns the descriptor's label.
+    #[must_use]
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// Returns the descriptor's description.
+    #[must_use]
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    /// Parses the descriptor's description as markup, see [`Markup`].
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`MarkupError`] if the description is not valid markup.
+    pub fn markup(&self) -> Result<Markup, MarkupError> {
+        Markup::parse(&self.description)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
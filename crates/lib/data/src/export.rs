@@ -0,0 +1,238 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! Versioned JSON exports, carrying data such as characters, campaigns or packs between installs.
+//!
+//! Every export is wrapped in an envelope naming its `kind` and the `schema` version it was written with. Importing an
+//! export written with an older schema upgrades it step by step to the current one first, so exports keep loading as
+//! the exported types change.
+
+use std::io::{Read, Write};
+
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use serde_json::Value;
+use thiserror::Error;
+
+/// Error type for exports and imports.
+#[derive(Error, Debug)]
+pub enum ExportError {
+    /// The export holds another kind of data.
+    #[error("expected a {expected} export, found a {found} export")]
+    WrongKind {
+        /// The kind of export expected.
+        expected: &'static str,
+        /// The kind of the export.
+        found: String,
+    },
+    /// The export was written with a schema version this version of the crate cannot read.
+    #[error("unsupported {kind} export schema version {found}, only versions 1 to {latest} are supported")]
+    UnsupportedVersion {
+        /// The kind of the export.
+        kind: &'static str,
+        /// The schema version of the export.
+        found: u32,
+        /// The latest schema version supported.
+        latest: u32,
+    },
+    /// An upgrade from an older schema version failed.
+    #[error("failed to upgrade {kind} export from schema version {from}: {reason}")]
+    Upgrade {
+        /// The kind of the export.
+        kind: &'static str,
+        /// The schema version upgraded from.
+        from: u32,
+        /// Why the upgrade failed.
+        reason: String,
+    },
+    /// The export is not valid JSON, or does not match its schema.
+    #[error("invalid export: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// Result type for exports and imports.
+pub type Result<T> = std::result::Result<T, ExportError>;
+
+/// Upgrades the data of an export by one schema version.
+pub type Upgrade = fn(Value) -> std::result::Result<Value, String>;
+
+/// Trait for types that can be exported as versioned JSON.
+pub trait Exported: Serialize + DeserializeOwned {
+    /// Name of the kind of export, written in the envelope.
+    const KIND: &'static str;
+
+    /// Upgrades from each older schema version to the next, the first one upgrading version 1 to 2. Append an upgrade
+    /// whenever the serialized shape of the type changes, which bumps the current schema version.
+    const UPGRADES: &'static [Upgrade] = &[];
+
+    /// Returns the current schema version, written in new exports.
+    #[must_use]
+    fn schema() -> u32 {
+        u32::try_from(Self::UPGRADES.len()).map_or(u32::MAX, |upgrades| upgrades.saturating_add(1))
+    }
+}
+
+/// The envelope wrapping the data of every export.
+#[derive(Serialize, Deserialize)]
+struct Envelope<T> {
+    kind: String,
+    schema: u32,
+    data: T,
+}
+
+/// Writes the value as an export of the current schema version.
+///
+/// # Errors
+///
+/// Returns [`ExportError::Json`] if the value cannot be serialized or written.
+pub fn export<T: Exported>(value: &T, out: impl Write) -> Result<()> {
+    let envelope = Envelope {
+        kind: T::KIND.into(),
+        schema: T::schema(),
+        data: value,
+    };
+
+    Ok(serde_json::to_writer_pretty(out, &envelope)?)
+}
+
+/// Reads an export, upgrading it to the current schema version if it was written with an older one.
+///
+/// # Errors
+///
+/// Returns [`ExportError::WrongKind`] if the export holds another kind of data, [`ExportError::UnsupportedVersion`]
+/// if it was written with an unknown schema version, [`ExportError::Upgrade`] if it cannot be upgraded, or
+/// [`ExportError::Json`] if it is not valid.
+pub fn import<T: Exported>(input: impl Read) -> Result<T> {
+    let envelope = serde_json::from_reader::<_, Envelope<Value>>(input)?;
+    if envelope.kind != T::KIND {
+        return Err(ExportError::WrongKind {
+            expected: T::KIND,
+            found: envelope.kind,
+        });
+    }
+
+    Ok(serde_json::from_value(upgrade::<T>(envelope.schema, envelope.data)?)?)
+}
+
+/// Upgrades the data of an export written with the given schema version to the current one.
+fn upgrade<T: Exported>(schema: u32, mut data: Value) -> Result<Value> {
+    let unsupported = || ExportError::UnsupportedVersion {
+        kind: T::KIND,
+        found: schema,
+        latest: T::schema(),
+    };
+    let first = usize::try_from(schema.checked_sub(1).ok_or_else(unsupported)?).map_err(|_| unsupported())?;
+    let upgrades = T::UPGRADES.get(first..).ok_or_else(unsupported)?;
+
+    for (from, upgrade) in (schema..).zip(upgrades) {
+        data = upgrade(data).map_err(|reason| ExportError::Upgrade { kind: T::KIND, from, reason })?;
+    }
+
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+    use serde_json::json;
+
+    use super::*;
+
+    /// A type whose `name` was split into a `first` and `last` name in version 2, then gained a `level` in version 3.
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Person {
+        first: String,
+        last: String,
+        level: u8,
+    }
+
+    impl Exported for Person {
+        const KIND: &'static str = "person";
+        const UPGRADES: &'static [Upgrade] = &[split_name, add_level];
+    }
+
+    fn split_name(mut data: Value) -> std::result::Result<Value, String> {
+        let name = data["name"].take();
+        let (first, last) = name.as_str().and_then(|name| name.split_once(' ')).ok_or("name has no last name")?;
+
+        Ok(json!({ "first": first, "last": last }))
+    }
+
+    #[expect(clippy::unnecessary_wraps, reason = "Upgrades share the signature of those that can fail")]
+    fn add_level(mut data: Value) -> std::result::Result<Value, String> {
+        data["level"] = json!(1);
+
+        Ok(data)
+    }
+
+    fn alice() -> Person {
+        Person {
+            first: "Alice".into(),
+            last: "Liddell".into(),
+            level: 1,
+        }
+    }
+
+    #[test]
+    fn should_round_trip_export_with_current_schema() {
+        let mut json = Vec::new();
+        export(&alice(), &mut json).expect("should have exported");
+
+        let value = serde_json::from_slice::<Value>(&json).expect("should have written json");
+        assert_eq!(json!("person"), value["kind"]);
+        assert_eq!(json!(3), value["schema"]);
+        assert_eq!(alice(), import::<Person>(json.as_slice()).expect("should have imported"));
+    }
+
+    #[rstest]
+    #[case::first(json!({ "kind": "person", "schema": 1, "data": { "name": "Alice Liddell" } }))]
+    #[case::second(json!({ "kind": "person", "schema": 2, "data": { "first": "Alice", "last": "Liddell" } }))]
+    fn should_upgrade_older_exports(#[case] export: Value) {
+        let json = serde_json::to_vec(&export).expect("should have serialized export");
+
+        assert_eq!(alice(), import::<Person>(json.as_slice()).expect("should have imported"));
+    }
+
+    #[rstest]
+    #[case::zero(0)]
+    #[case::newer(4)]
+    fn should_refuse_unsupported_versions(#[case] schema: u32) {
+        let json = serde_json::to_vec(&json!({ "kind": "person", "schema": schema, "data": {} })).expect("should have serialized export");
+
+        let err = import::<Person>(json.as_slice()).expect_err("should have refused export");
+
+        assert!(
+            matches!(err, ExportError::UnsupportedVersion { kind: "person", found, latest: 3 } if found == schema),
+            "unexpected error: {err}"
+        );
+    }
+
+    #[test]
+    fn should_refuse_other_kinds() {
+        let json = serde_json::to_vec(&json!({ "kind": "pack", "schema": 1, "data": {} })).expect("should have serialized export");
+
+        let err = import::<Person>(json.as_slice()).expect_err("should have refused export");
+
+        assert!(
+            matches!(err, ExportError::WrongKind { expected: "person", ref found } if found == "pack"),
+            "unexpected error: {err}"
+        );
+    }
+
+    #[test]
+    fn should_report_failed_upgrades() {
+        let json = serde_json::to_vec(&json!({ "kind": "person", "schema": 1, "data": { "name": "Alice" } })).expect("should have serialized export");
+
+        let err = import::<Person>(json.as_slice()).expect_err("should have failed to upgrade");
+
+        assert!(matches!(err, ExportError::Upgrade { from: 1, .. }), "unexpected error: {err}");
+    }
+}
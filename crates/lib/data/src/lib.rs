@@ -23,16 +23,32 @@
 /// Module for data storage.
 pub mod store;
 
+/// Module for locale-aware ordering of text.
+pub mod collation;
+/// Module for entity descriptors.
+pub mod descriptor;
+/// Module for versioned JSON exports.
+pub mod export;
+/// Module for linting content packs.
+pub mod lint;
+/// Module for the markup of descriptions.
+pub mod markup;
+/// Module for content packs.
+pub mod pack;
+/// Module for platform-appropriate save locations.
+pub mod paths;
+
 mod codec;
 
 mod uuid;
 
 use std::sync::{Arc, RwLock};
 
+use serde::{Serialize, de::DeserializeOwned};
 use thiserror::Error;
 
 pub use crate::codec::{CodecError, JSONDeserialize, JSONSerialize};
-use crate::uuid::Uuid;
+use crate::{export::Upgrade, uuid::Uuid};
 
 /// Result type for operations in the data crate.
 pub type Result<T> = std::result::Result<T, DataError>;
@@ -46,6 +62,23 @@ pub type Entity = Uuid;
 /// Trait for data components.
 pub trait Component {}
 
+/// Trait for components that can be saved in and loaded from a store.
+pub trait Persisted: Component + Serialize + DeserializeOwned {
+    /// Name of the component kind, used as its key in the store.
+    const KIND: &'static str;
+
+    /// Upgrades the stored payloads of the kind from each older schema version to the next, the first one upgrading
+    /// version 1 to 2. Append an upgrade whenever the serialized shape of the type changes, e.g. a field is renamed, and
+    /// stores rewrite the components they hold on load, see [`UpgradeComponents`](store::schema::UpgradeComponents).
+    const UPGRADES: &'static [Upgrade] = &[];
+
+    /// Returns the current schema version of the kind, that of the components written by this version of the crate.
+    #[must_use]
+    fn schema() -> u32 {
+        u32::try_from(Self::UPGRADES.len()).map_or(u32::MAX, |upgrades| upgrades.saturating_add(1))
+    }
+}
+
 /// Error type for operations in the data crate.
 #[derive(Error, Debug)]
 pub enum DataError {}
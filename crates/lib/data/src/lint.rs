@@ -0,0 +1,690 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! Linting of content packs, catching the mistakes that load fine yet play wrong, each reported where to fix it.
+//!
+//! [`PackLinter::lint`] reports:
+//! - missing translations: a pack may hold translations in its `translations` directory, one `.json` or `.jsonc` file
+//!   per locale mapping keys to text, and every locale should translate the keys any other one does,
+//! - dangling slug references: [`Slug`]s, markup links and the `related` slugs of an entry's own category that name no
+//!   entry of the pack, along with markup that does not parse,
+//! - unreachable table entries: the rows of a weighted table with a weight of zero, which never come up,
+//! - stat values out of range, e.g. a tier above 5 or a clock with more segments filled than it has.
+//!
+//! Each [`Diagnostic`] gives the line and column of the offending value in its source file.
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fmt, fs,
+    iter::Peekable,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+    str::Chars,
+};
+
+use serde_json::{Map, Value};
+
+use crate::{
+    codec::JSONDeserialize,
+    markup::{Markup, Target},
+    pack::{Pack, PackError, Result, Slug},
+};
+
+/// The directory of a pack holding its translations.
+pub const TRANSLATIONS: &str = "translations";
+
+/// The fields holding markup, see [`Markup`].
+const MARKUP_FIELDS: [&str; 2] = ["description", "text"];
+
+/// The field listing the slugs of related entries of the same category.
+const RELATED: &str = "related";
+
+/// The field holding the weight of a row of a weighted table.
+const WEIGHT: &str = "weight";
+
+/// How serious a [`Diagnostic`] is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// Worth knowing, but nothing to fix.
+    Info,
+    /// Likely a mistake, though the pack still plays.
+    Warning,
+    /// A mistake that breaks the pack in play.
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        })
+    }
+}
+
+/// What a [`Diagnostic`] is about.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Lint {
+    /// A locale lacks the translation of a key another locale translates.
+    MissingTranslation,
+    /// A slug names no entry of the pack, or names an entry of another pack.
+    DanglingSlug,
+    /// A description does not parse as markup.
+    InvalidMarkup,
+    /// A row of a weighted table never comes up.
+    UnreachableEntry,
+    /// A stat value is out of its range.
+    OutOfRange,
+}
+
+impl fmt::Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Lint::MissingTranslation => "missing-translation",
+            Lint::DanglingSlug => "dangling-slug",
+            Lint::InvalidMarkup => "invalid-markup",
+            Lint::UnreachableEntry => "unreachable-entry",
+            Lint::OutOfRange => "out-of-range",
+        })
+    }
+}
+
+/// A position in a source file, counting lines and columns from 1.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Position {
+    /// The line.
+    pub line: usize,
+    /// The column, in characters.
+    pub column: usize,
+}
+
+impl Default for Position {
+    fn default() -> Position {
+        Position { line: 1, column: 1 }
+    }
+}
+
+/// A problem found in a content pack, displayed as `file:line:column: severity[lint]: message`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// How serious the problem is.
+    pub severity: Severity,
+    /// What the problem is about.
+    pub lint: Lint,
+    /// The source file holding the problem.
+    pub file: PathBuf,
+    /// The position of the offending value in the file.
+    pub position: Position,
+    /// What is wrong, and how to fix it.
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}: {}[{}]: {}",
+            self.file.display(),
+            self.position.line,
+            self.position.column,
+            self.severity,
+            self.lint,
+            self.message
+        )
+    }
+}
+
+/// Lints content packs, see the [module documentation](self).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PackLinter {
+    ranges: BTreeMap<String, RangeInclusive<i64>>,
+}
+
+impl Default for PackLinter {
+    /// Checks the stats of the Blades in the Dark SRD: tiers from 0 to 5, statuses from -3 to 3 and clocks of 1 to 12
+    /// segments.
+    fn default() -> PackLinter {
+        PackLinter::new().range("tier", 0..=5).range("status", -3..=3).range("segments", 1..=12)
+    }
+}
+
+impl PackLinter {
+    /// Creates a linter checking no stat ranges, see [`PackLinter::range`].
+    #[must_use]
+    pub fn new() -> PackLinter {
+        PackLinter { ranges: BTreeMap::new() }
+    }
+
+    /// Returns the linter checking that the numbers of the given field, at any depth of an entry, are whole and within
+    /// the range, in place of any range it already checks the field against.
+    #[must_use]
+    pub fn range(mut self, field: impl Into<String>, range: RangeInclusive<i64>) -> PackLinter {
+        self.ranges.insert(field.into(), range);
+        self
+    }
+
+    /// Lints the content pack in the given directory. Returns the diagnostics ordered by file then position.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PackError`] if the pack cannot be opened, or if a translation file cannot be read or parsed.
+    pub fn lint(&self, path: impl AsRef<Path>) -> Result<Vec<Diagnostic>> {
+        let path = path.as_ref();
+        let pack = Pack::open(path)?;
+        let mut diagnostics = Vec::new();
+
+        for (category, file) in sources(path)? {
+            let text = fs::read_to_string(&file).map_err(|e| PackError::Io(file.clone(), e))?;
+            let mut check = Check {
+                linter: self,
+                pack: &pack,
+                category: &category,
+                file: &file,
+                source: Source::scan(&text),
+                diagnostics: &mut diagnostics,
+            };
+            check.entries();
+        }
+
+        diagnostics.extend(translations(&path.join(TRANSLATIONS))?);
+        diagnostics.sort_by(|a, b| (&a.file, a.position).cmp(&(&b.file, b.position)));
+
+        Ok(diagnostics)
+    }
+}
+
+/// Returns the `.json` and `.jsonc` files of the directory, by their stem, or nothing if there is no such directory.
+fn sources(dir: &Path) -> Result<BTreeMap<String, PathBuf>> {
+    if !dir.is_dir() {
+        return Ok(BTreeMap::new());
+    }
+
+    let mut files = BTreeMap::new();
+    for file in fs::read_dir(dir).map_err(|e| PackError::Io(dir.into(), e))? {
+        let file = file.map_err(|e| PackError::Io(dir.into(), e))?.path();
+        if let (Some(stem), Some("json" | "jsonc")) = (file.file_stem(), file.extension().and_then(|e| e.to_str())) {
+            files.insert(stem.to_string_lossy().into_owned(), file);
+        }
+    }
+
+    Ok(files)
+}
+
+/// Reports the keys each translation file in the directory lacks, that another one translates.
+fn translations(dir: &Path) -> Result<Vec<Diagnostic>> {
+    let locales = sources(dir)?
+        .into_iter()
+        .map(|(locale, file)| {
+            let text = fs::read_to_string(&file).map_err(|e| PackError::Io(file.clone(), e))?;
+            let keys = BTreeMap::<String, Value>::from_jsonc(text.as_bytes()).map_err(|e| PackError::Codec(file.clone(), e))?;
+            Ok((locale, (file, Source::scan(&text).at(""), keys)))
+        })
+        .collect::<Result<BTreeMap<_, _>>>()?;
+
+    let mut diagnostics = Vec::new();
+    let all = locales.values().flat_map(|(_, _, keys)| keys.keys()).collect::<BTreeSet<_>>();
+    for (locale, (file, position, keys)) in &locales {
+        for key in all.iter().filter(|key| !keys.contains_key(key.as_str())) {
+            let translated = locales
+                .iter()
+                .filter(|(_, (_, _, keys))| keys.contains_key(key.as_str()))
+                .map(|(locale, _)| locale.as_str())
+                .collect::<Vec<_>>();
+            diagnostics.push(Diagnostic {
+                severity: Severity::Warning,
+                lint: Lint::MissingTranslation,
+                file: file.clone(),
+                position: *position,
+                message: format!("{key} is not translated into {locale}, as it is into {}", translated.join(", ")),
+            });
+        }
+    }
+
+    Ok(diagnostics)
+}
+
+/// The lints of the entries of a single category file.
+struct Check<'a> {
+    linter: &'a PackLinter,
+    pack: &'a Pack,
+    category: &'a str,
+    file: &'a Path,
+    source: Source,
+    diagnostics: &'a mut Vec<Diagnostic>,
+}
+
+impl Check<'_> {
+    fn entries(&mut self) {
+        let pack = self.pack;
+        let entries = pack.entries(self.category);
+        let weighted = entries.iter().any(|entry| entry.data.get(WEIGHT).is_some());
+
+        for (index, entry) in entries.iter().enumerate() {
+            let pointer = format!("/{index}");
+            if weighted {
+                self.weight(&entry.slug(), entry.data.get(WEIGHT), &pointer);
+            }
+            self.value(None, &entry.data, &pointer);
+        }
+    }
+
+    fn report(&mut self, severity: Severity, lint: Lint, pointer: &str, message: String) {
+        self.diagnostics.push(Diagnostic {
+            severity,
+            lint,
+            file: self.file.into(),
+            position: self.source.at(pointer),
+            message,
+        });
+    }
+
+    fn weight(&mut self, slug: &str, weight: Option<&Value>, pointer: &str) {
+        let pointer = format!("{pointer}/{WEIGHT}");
+        match weight {
+            // Rows without a weight weigh one.
+            None => {}
+            Some(weight) if weight.as_u64() == Some(0) => self.report(
+                Severity::Warning,
+                Lint::UnreachableEntry,
+                &pointer,
+                format!("{slug} has a weight of zero and never comes up in the {} table", self.category),
+            ),
+            Some(weight) if weight.as_u64().is_some_and(|weight| u32::try_from(weight).is_ok()) => {}
+            Some(weight) => self.report(
+                Severity::Error,
+                Lint::OutOfRange,
+                &pointer,
+                format!("{slug} has a weight of {weight}, expected a whole number from 0 to {}", u32::MAX),
+            ),
+        }
+    }
+
+    fn value(&mut self, field: Option<&str>, value: &Value, pointer: &str) {
+        match value {
+            Value::Object(fields) => {
+                self.clock(fields, pointer);
+                for (field, value) in fields {
+                    self.value(Some(field), value, &format!("{pointer}/{}", escape(field)));
+                }
+            }
+            Value::Array(values) => {
+                for (index, value) in values.iter().enumerate() {
+                    self.value(field, value, &format!("{pointer}/{index}"));
+                }
+            }
+            Value::String(text) if field == Some(RELATED) => self.related(text, pointer),
+            Value::String(text) if field.is_some_and(|field| MARKUP_FIELDS.contains(&field)) => self.markup(text, pointer),
+            Value::String(text) if is_slug(text) => {
+                if let Ok(slug) = text.parse() {
+                    self.slug(&slug, pointer);
+                }
+            }
+            Value::Number(number) => {
+                if let Some((field, range)) = field.and_then(|field| self.linter.ranges.get_key_value(field)) {
+                    if !number.as_i64().is_some_and(|number| range.contains(&number)) {
+                        self.report(
+                            Severity::Error,
+                            Lint::OutOfRange,
+                            pointer,
+                            format!("{field} of {number} is out of range, expected {} to {}", range.start(), range.end()),
+                        );
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Checks that a clock has no more segments filled than it has.
+    fn clock(&mut self, fields: &Map<String, Value>, pointer: &str) {
+        let (Some(segments), Some(filled)) = (
+            fields.get("segments").and_then(Value::as_u64),
+            fields.get("filled").and_then(Value::as_u64),
+        ) else {
+            return;
+        };
+
+        if filled > segments {
+            self.report(
+                Severity::Error,
+                Lint::OutOfRange,
+                &format!("{pointer}/filled"),
+                format!("{filled} segments filled of a {segments} segment clock"),
+            );
+        }
+    }
+
+    fn related(&mut self, slug: &str, pointer: &str) {
+        let related = Slug::new(self.pack.name(), self.category, slug);
+        if self.pack.resolve(&related).is_none() {
+            self.report(
+                Severity::Error,
+                Lint::DanglingSlug,
+                pointer,
+                format!("related {slug} names no entry of the {} category", self.category),
+            );
+        }
+    }
+
+    fn markup(&mut self, text: &str, pointer: &str) {
+        match Markup::parse(text) {
+            Ok(markup) => {
+                for link in markup.links() {
+                    if let Target::Entry(slug) = link {
+                        self.slug(slug, pointer);
+                    }
+                }
+            }
+            Err(e) => self.report(Severity::Error, Lint::InvalidMarkup, pointer, format!("invalid markup: {e}")),
+        }
+    }
+
+    fn slug(&mut self, slug: &Slug, pointer: &str) {
+        if slug.pack != self.pack.name() {
+            self.report(
+                Severity::Info,
+                Lint::DanglingSlug,
+                pointer,
+                format!("{slug} names an entry of the {} pack, which is not checked", slug.pack),
+            );
+        } else if self.pack.resolve(slug).is_none() {
+            self.report(Severity::Error, Lint::DanglingSlug, pointer, format!("{slug} names no entry of the pack"));
+        }
+    }
+}
+
+/// Returns whether the text reads as a slug, `pack:category:slug` of lowercase letters, digits and hyphens, the pack
+/// starting with a letter, rather than as any text holding two colons.
+fn is_slug(text: &str) -> bool {
+    let parts = text.split(':').collect::<Vec<_>>();
+    parts.len() == 3
+        && parts[0].starts_with(|c: char| c.is_ascii_lowercase())
+        && parts
+            .iter()
+            .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-'))
+}
+
+/// Escapes a field name for a JSON pointer.
+fn escape(field: &str) -> String {
+    field.replace('~', "~0").replace('/', "~1")
+}
+
+/// The positions of the values of a JSON or JSONC source, by JSON pointer.
+struct Source {
+    positions: HashMap<String, Position>,
+}
+
+impl Source {
+    fn scan(text: &str) -> Source {
+        let mut scanner = Scanner {
+            chars: text.chars().peekable(),
+            position: Position::default(),
+            positions: HashMap::new(),
+        };
+        scanner.value("");
+
+        Source {
+            positions: scanner.positions,
+        }
+    }
+
+    /// Returns the position of the value the pointer refers to, else that of its closest parent.
+    fn at(&self, pointer: &str) -> Position {
+        let mut pointer = pointer;
+        loop {
+            if let Some(position) = self.positions.get(pointer) {
+                return *position;
+            }
+            match pointer.rfind('/') {
+                Some(end) => pointer = &pointer[..end],
+                None => return Position::default(),
+            }
+        }
+    }
+}
+
+/// Walks a JSON or JSONC source, recording the position of every value. Malformed sources are walked as far as they
+/// make sense, as packs are parsed before they are linted.
+struct Scanner<'a> {
+    chars: Peekable<Chars<'a>>,
+    position: Position,
+    positions: HashMap<String, Position>,
+}
+
+impl Scanner<'_> {
+    fn bump(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        if c == '\n' {
+            self.position.line += 1;
+            self.position.column = 1;
+        } else {
+            self.position.column += 1;
+        }
+
+        Some(c)
+    }
+
+    /// Skips whitespace and comments.
+    fn skip(&mut self) {
+        while let Some(&c) = self.chars.peek() {
+            if c.is_whitespace() {
+                self.bump();
+                continue;
+            }
+            if c != '/' {
+                return;
+            }
+
+            self.bump();
+            match self.bump() {
+                Some('/') => while self.bump().is_some_and(|c| c != '\n') {},
+                Some('*') => {
+                    let mut last = None;
+                    while let Some(c) = self.bump() {
+                        if last == Some('*') && c == '/' {
+                            break;
+                        }
+                        last = Some(c);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn value(&mut self, pointer: &str) {
+        self.skip();
+        self.positions.insert(pointer.to_owned(), self.position);
+
+        match self.chars.peek() {
+            Some('{') => {
+                self.bump();
+                loop {
+                    self.skip();
+                    match self.chars.peek() {
+                        Some('"') => {
+                            let field = self.string();
+                            self.skip();
+                            if self.chars.peek() == Some(&':') {
+                                self.bump();
+                            }
+                            self.value(&format!("{pointer}/{}", escape(&field)));
+                        }
+                        Some('}') | None => break,
+                        // Commas, and whatever a malformed source holds.
+                        Some(_) => {
+                            self.bump();
+                        }
+                    }
+                }
+                self.bump();
+            }
+            Some('[') => {
+                self.bump();
+                let mut index = 0;
+                loop {
+                    self.skip();
+                    match self.chars.peek() {
+                        Some(',') => {
+                            self.bump();
+                        }
+                        Some(']' | '}') | None => break,
+                        Some(_) => {
+                            self.value(&format!("{pointer}/{index}"));
+                            index += 1;
+                        }
+                    }
+                }
+                self.bump();
+            }
+            Some('"') => {
+                self.string();
+            }
+            Some(_) => {
+                while self
+                    .chars
+                    .peek()
+                    .is_some_and(|&c| !c.is_whitespace() && !matches!(c, ',' | ']' | '}' | '/'))
+                {
+                    self.bump();
+                }
+            }
+            None => {}
+        }
+    }
+
+    /// Reads a string literal, returning its contents with escaped characters kept as they are.
+    fn string(&mut self) -> String {
+        let mut text = String::new();
+        self.bump();
+        while let Some(c) = self.bump() {
+            match c {
+                '"' => break,
+                '\\' => text.extend(self.bump()),
+                c => text.push(c),
+            }
+        }
+
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    /// Writes the given files into a fresh pack directory named after the pack.
+    fn write_pack(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let dir = env::temp_dir().join(format!("darkforge-lint-{}", std::process::id())).join(name);
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join(TRANSLATIONS)).expect("should have created pack directory");
+
+        for (file, content) in files {
+            fs::write(dir.join(file), content).expect("should have written pack file");
+        }
+
+        dir
+    }
+
+    #[test]
+    fn should_report_nothing_given_default_pack() {
+        let pack = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../../data/defaults");
+
+        let diagnostics = PackLinter::default().lint(pack).expect("should have linted pack");
+
+        assert_eq!(Vec::<Diagnostic>::new(), diagnostics);
+    }
+
+    #[test]
+    fn should_report_diagnostics_with_their_position() {
+        let dir = write_pack(
+            "bitd",
+            &[
+                (
+                    "faction.jsonc",
+                    r#"[
+  // The city watch
+  {
+    "id": "7ef1bdbe-84de-4699-b1bd-be84de569995",
+    "descriptor": { "label": "Bluecoats", "description": "Rivals of [the Crows](bitd:faction:the-crows)." },
+    "tier": 7,
+    "clocks": [{ "label": "Crackdown", "segments": 4, "filled": 6 }]
+  },
+  {
+    "id": "4a1a0b8e-2b36-4a3e-9d0c-8e1f4f5d6c7b",
+    "descriptor": { "label": "Lampblacks", "description": "*Rivals of **[the Bluecoats](bitd:faction:bluecoats)" },
+    "ally": "other:faction:red-sashes"
+  }
+]"#,
+                ),
+                (
+                    "goal.json",
+                    r#"[
+  { "id": "9b873642-dadc-4f04-9142-246afe86da1c", "title": "Expand", "weight": 0 },
+  { "id": "a85975c8-95bc-4678-b726-ccc3710f130f", "title": "Hide", "weight": 2 }
+]"#,
+                ),
+                (
+                    "rule.json",
+                    r#"[{ "id": "03194f93-7035-4def-b5f4-1a7fd6e23a2b", "slug": "stress", "related": ["stress", "trauma"] }]"#,
+                ),
+                ("translations/en.json", r#"{ "greeting": "Hello", "farewell": "Goodbye" }"#),
+                ("translations/fr.json", r#"{ "greeting": "Bonjour" }"#),
+            ],
+        );
+
+        let diagnostics = PackLinter::default().lint(&dir).expect("should have linted pack");
+
+        assert_eq!(
+            vec![
+                ("faction.jsonc", 5, 58, Severity::Error, Lint::DanglingSlug),
+                ("faction.jsonc", 6, 13, Severity::Error, Lint::OutOfRange),
+                ("faction.jsonc", 7, 65, Severity::Error, Lint::OutOfRange),
+                ("faction.jsonc", 11, 59, Severity::Error, Lint::InvalidMarkup),
+                ("faction.jsonc", 12, 13, Severity::Info, Lint::DanglingSlug),
+                ("goal.json", 2, 80, Severity::Warning, Lint::UnreachableEntry),
+                ("rule.json", 1, 90, Severity::Error, Lint::DanglingSlug),
+                ("fr.json", 1, 1, Severity::Warning, Lint::MissingTranslation),
+            ],
+            diagnostics
+                .iter()
+                .map(|d| (
+                    d.file.file_name().and_then(|name| name.to_str()).unwrap_or_default(),
+                    d.position.line,
+                    d.position.column,
+                    d.severity,
+                    d.lint
+                ))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            format!(
+                "{}:6:13: error[out-of-range]: tier of 7 is out of range, expected 0 to 5",
+                dir.join("faction.jsonc").display()
+            ),
+            diagnostics[1].to_string()
+        );
+    }
+
+    #[test]
+    fn should_locate_values_given_comments() {
+        let source = Source::scan("// heading\n[\n  /* first */ { \"a\": [1, \"x//y\"] },\n  2\n]");
+
+        assert_eq!(Position { line: 2, column: 1 }, source.at(""));
+        assert_eq!(Position { line: 3, column: 26 }, source.at("/0/a/1"));
+        assert_eq!(Position { line: 4, column: 3 }, source.at("/1"));
+        assert_eq!(Position { line: 3, column: 26 }, source.at("/0/a/1/missing"));
+    }
+}
@@ -0,0 +1,419 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! Limited markup for descriptions, so lore entries can stress words and cross-reference one another.
+//!
+//! Descriptions may hold `**bold**` and `*italic*` text, and links written `[text](target)` whose target is either the
+//! id of an entity or the [`Slug`] of a content entry, e.g. `[the Lampblacks](bitd:faction:the-lampblacks)`. A
+//! backslash escapes the character after it. [`Markup::parse`] checks the syntax, [`Markup::dangling`] checks the
+//! targets against a store, and the markup renders to Godot's `BBCode` or to plain text.
+
+use std::{fmt, str::FromStr};
+
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::{
+    pack::Slug,
+    store::{Content, World},
+};
+
+/// Error type for markup that cannot be parsed.
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum MarkupError {
+    /// A marker is opened and never closed, at the given byte offset.
+    #[error("unclosed {marker} at {at}")]
+    Unclosed {
+        /// The marker left open.
+        marker: &'static str,
+        /// The byte offset of the marker.
+        at: usize,
+    },
+    /// A link is written inside the text of another, at the given byte offset.
+    #[error("nested link at {0}")]
+    NestedLink(usize),
+    /// The text of a link is not followed by its target in parentheses, at the given byte offset.
+    #[error("link at {0} has no target")]
+    MissingTarget(usize),
+    /// The target of a link is neither an id nor a slug.
+    #[error("invalid link target {0}")]
+    InvalidTarget(String),
+}
+
+/// What a link points to.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Target {
+    /// An entity of the campaign, by id.
+    Entity(Uuid),
+    /// A content entry, by slug.
+    Entry(Slug),
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Target::Entity(id) => write!(f, "{id}"),
+            Target::Entry(slug) => write!(f, "{slug}"),
+        }
+    }
+}
+
+impl FromStr for Target {
+    type Err = MarkupError;
+
+    fn from_str(s: &str) -> Result<Target, MarkupError> {
+        if let Ok(id) = Uuid::parse_str(s) {
+            return Ok(Target::Entity(id));
+        }
+
+        s.parse::<Slug>()
+            .map(Target::Entry)
+            .map_err(|_| MarkupError::InvalidTarget(s.to_string()))
+    }
+}
+
+/// A run of marked up text.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Span {
+    /// Plain text.
+    Text(String),
+    /// Text in bold.
+    Bold(Vec<Span>),
+    /// Text in italics.
+    Italic(Vec<Span>),
+    /// Text linking to another entity or entry.
+    Link {
+        /// What the link points to.
+        target: Target,
+        /// The text of the link.
+        text: Vec<Span>,
+    },
+}
+
+/// A parsed description.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Markup {
+    spans: Vec<Span>,
+}
+
+impl Markup {
+    /// Parses the marked up text.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`MarkupError`] if a marker is left open, a link is nested in another or has no valid target.
+    pub fn parse(text: &str) -> Result<Markup, MarkupError> {
+        let mut parser = Parser { text, at: 0, in_link: false };
+        let spans = parser.spans(None)?;
+
+        Ok(Markup { spans })
+    }
+
+    /// Returns the spans of the markup.
+    #[must_use]
+    pub fn spans(&self) -> &[Span] {
+        &self.spans
+    }
+
+    /// Returns the targets of the links, in order.
+    #[must_use]
+    pub fn links(&self) -> Vec<&Target> {
+        let mut links = Vec::new();
+        walk(&self.spans, &mut |span| {
+            if let Span::Link { target, .. } = span {
+                links.push(target);
+            }
+        });
+
+        links
+    }
+
+    /// Returns the targets of the links the store holds nothing for: entities of its campaign without components, or
+    /// slugs no loaded entry has.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the store if it cannot be read.
+    pub async fn dangling<S: World + Content>(&self, store: &mut S) -> Result<Vec<Target>, S::Error> {
+        let mut dangling = Vec::new();
+        for target in self.links() {
+            let found = match target {
+                Target::Entity(id) => store.exists(*id).await.into()?,
+                Target::Entry(slug) => store.resolve(slug).await.into()?.is_some(),
+            };
+            if !found && !dangling.contains(target) {
+                dangling.push(target.clone());
+            }
+        }
+
+        Ok(dangling)
+    }
+
+    /// Renders the markup to the `BBCode` of Godot's `RichTextLabel`, links becoming `[url]` tags whose meta is their
+    /// target, for the `meta_clicked` signal to hand back.
+    #[must_use]
+    pub fn to_bbcode(&self) -> String {
+        let mut bbcode = String::new();
+        bbcode_of(&self.spans, &mut bbcode);
+        bbcode
+    }
+
+    /// Renders the markup to plain text, keeping the text of links.
+    #[must_use]
+    pub fn to_plain(&self) -> String {
+        let mut plain = String::new();
+        walk(&self.spans, &mut |span| {
+            if let Span::Text(text) = span {
+                plain.push_str(text);
+            }
+        });
+
+        plain
+    }
+}
+
+impl FromStr for Markup {
+    type Err = MarkupError;
+
+    fn from_str(s: &str) -> Result<Markup, MarkupError> {
+        Markup::parse(s)
+    }
+}
+
+/// Calls `f` on every span, parents before their children.
+fn walk<'a>(spans: &'a [Span], f: &mut impl FnMut(&'a Span)) {
+    for span in spans {
+        f(span);
+        match span {
+            Span::Text(_) => {}
+            Span::Bold(children) | Span::Italic(children) | Span::Link { text: children, .. } => walk(children, f),
+        }
+    }
+}
+
+fn bbcode_of(spans: &[Span], bbcode: &mut String) {
+    for span in spans {
+        match span {
+            Span::Text(text) => {
+                for c in text.chars() {
+                    match c {
+                        '[' => bbcode.push_str("[lb]"),
+                        ']' => bbcode.push_str("[rb]"),
+                        c => bbcode.push(c),
+                    }
+                }
+            }
+            Span::Bold(children) => {
+                bbcode.push_str("[b]");
+                bbcode_of(children, bbcode);
+                bbcode.push_str("[/b]");
+            }
+            Span::Italic(children) => {
+                bbcode.push_str("[i]");
+                bbcode_of(children, bbcode);
+                bbcode.push_str("[/i]");
+            }
+            Span::Link { target, text } => {
+                bbcode.push_str("[url=");
+                bbcode.push_str(&target.to_string());
+                bbcode.push(']');
+                bbcode_of(text, bbcode);
+                bbcode.push_str("[/url]");
+            }
+        }
+    }
+}
+
+/// Parses markup one span at a time, `at` being the byte offset of the text left to parse.
+struct Parser<'a> {
+    text: &'a str,
+    at: usize,
+    in_link: bool,
+}
+
+impl Parser<'_> {
+    /// Parses spans up to the closing marker, consuming it, or to the end of the text if there is none.
+    fn spans(&mut self, closing: Option<&'static str>) -> Result<Vec<Span>, MarkupError> {
+        let mut spans = Vec::new();
+        let mut text = String::new();
+        while let Some(c) = self.rest().chars().next() {
+            if let Some(closing) = closing
+                && self.closes(closing)
+            {
+                self.at += closing.len();
+                push_text(&mut spans, &mut text);
+                return Ok(spans);
+            }
+
+            let start = self.at;
+            if self.rest().starts_with("**") {
+                self.at += 2;
+                push_text(&mut spans, &mut text);
+                spans.push(Span::Bold(self.enclosed("**", start)?));
+            } else if c == '*' {
+                self.at += 1;
+                push_text(&mut spans, &mut text);
+                spans.push(Span::Italic(self.enclosed("*", start)?));
+            } else if c == '[' {
+                if self.in_link {
+                    return Err(MarkupError::NestedLink(start));
+                }
+                self.at += 1;
+                push_text(&mut spans, &mut text);
+                spans.push(self.link(start)?);
+            } else if c == '\\' {
+                self.at += 1;
+                if let Some(escaped) = self.rest().chars().next() {
+                    text.push(escaped);
+                    self.at += escaped.len_utf8();
+                }
+            } else {
+                text.push(c);
+                self.at += c.len_utf8();
+            }
+        }
+
+        if let Some(marker) = closing {
+            return Err(MarkupError::Unclosed { marker, at: self.at });
+        }
+        push_text(&mut spans, &mut text);
+
+        Ok(spans)
+    }
+
+    /// Parses the spans enclosed by a marker opened at `start`.
+    fn enclosed(&mut self, marker: &'static str, start: usize) -> Result<Vec<Span>, MarkupError> {
+        self.spans(Some(marker)).map_err(|err| match err {
+            MarkupError::Unclosed { marker, .. } => MarkupError::Unclosed { marker, at: start },
+            err => err,
+        })
+    }
+
+    /// Parses the text and target of a link opened at `start`.
+    fn link(&mut self, start: usize) -> Result<Span, MarkupError> {
+        self.in_link = true;
+        let text = self.enclosed("]", start);
+        self.in_link = false;
+        let text = text?;
+
+        let Some(rest) = self.rest().strip_prefix('(') else {
+            return Err(MarkupError::MissingTarget(start));
+        };
+        let Some(end) = rest.find(')') else {
+            return Err(MarkupError::Unclosed { marker: "(", at: self.at });
+        };
+        let target = rest[..end].trim().parse()?;
+        self.at += end + 2;
+
+        Ok(Span::Link { target, text })
+    }
+
+    /// Returns whether the text left starts with the closing marker, a single `*` not closing italics where bold opens.
+    fn closes(&self, closing: &str) -> bool {
+        self.rest().starts_with(closing) && !(closing == "*" && self.rest().starts_with("**") && !self.rest().starts_with("***"))
+    }
+
+    fn rest(&self) -> &str {
+        &self.text[self.at..]
+    }
+}
+
+/// Moves the text gathered so far into a span of its own, if any.
+fn push_text(spans: &mut Vec<Span>, text: &mut String) {
+    if !text.is_empty() {
+        spans.push(Span::Text(std::mem::take(text)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LAMPBLACKS: &str = "[the **Lampblacks**](bitd:faction:the-lampblacks)";
+
+    #[test]
+    fn should_parse_bold_italic_and_links() {
+        let markup = Markup::parse(&format!("*Feared* by {LAMPBLACKS}.")).expect("should have parsed markup");
+
+        assert_eq!(
+            &[
+                Span::Italic(vec![Span::Text("Feared".into())]),
+                Span::Text(" by ".into()),
+                Span::Link {
+                    target: Target::Entry(Slug::new("bitd", "faction", "the-lampblacks")),
+                    text: vec![Span::Text("the ".into()), Span::Bold(vec![Span::Text("Lampblacks".into())])],
+                },
+                Span::Text(".".into()),
+            ][..],
+            markup.spans()
+        );
+    }
+
+    #[test]
+    fn should_render_bbcode_and_plain_text() {
+        let id = Uuid::new_v4();
+        let markup = Markup::parse(&format!(r"**Bazso** \[Baz\] leads {LAMPBLACKS}, ***see*** [him]({id})")).expect("should have parsed markup");
+
+        assert_eq!(
+            format!(
+                "[b]Bazso[/b] [lb]Baz[rb] leads [url=bitd:faction:the-lampblacks]the [b]Lampblacks[/b][/url], \
+                 [b][i]see[/i][/b] [url={id}]him[/url]"
+            ),
+            markup.to_bbcode()
+        );
+        assert_eq!("Bazso [Baz] leads the Lampblacks, see him", markup.to_plain());
+    }
+
+    #[test]
+    fn should_reject_invalid_markup() {
+        assert_eq!(Err(MarkupError::Unclosed { marker: "**", at: 4 }), Markup::parse("and **never closed"));
+        assert_eq!(Err(MarkupError::Unclosed { marker: "]", at: 0 }), Markup::parse("[dangling"));
+        assert_eq!(Err(MarkupError::NestedLink(7)), Markup::parse("[outer [inner](a:b:c)](a:b:c)"));
+        assert_eq!(Err(MarkupError::MissingTarget(0)), Markup::parse("[text] and more"));
+        assert_eq!(Err(MarkupError::InvalidTarget("nowhere".into())), Markup::parse("[text](nowhere)"));
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[tokio::test]
+    async fn should_list_links_to_nothing_in_the_store() {
+        use crate::{pack::Pack, store::sql::sqlite::fixture::memory_store};
+
+        let mut store = memory_store(vec![]).await;
+        let pack = Pack::parse(
+            "bitd",
+            [(
+                "faction",
+                format!(r#"[{{ "id": "{}", "descriptor": {{ "label": "The Lampblacks" }} }}]"#, Uuid::new_v4()),
+            )],
+        )
+        .expect("should have parsed pack");
+        store.load_pack(&pack).await.expect("should have loaded pack");
+        let npc = store.spawn().await.expect("should have spawned entity");
+        store.record(None, "npc.met", &npc).await.expect("should have recorded entry");
+        let ghost = Uuid::new_v4();
+
+        let markup = Markup::parse(&format!(
+            "{LAMPBLACKS} hired [Bazso]({npc}) and [someone]({ghost}) against [the Reds](bitd:faction:the-red-sashes)"
+        ))
+        .expect("should have parsed markup");
+        let dangling = markup.dangling(&mut store).await.expect("should have checked links");
+
+        assert_eq!(
+            vec![
+                Target::Entity(npc),
+                Target::Entity(ghost),
+                Target::Entry(Slug::new("bitd", "faction", "the-red-sashes")),
+            ],
+            dangling
+        );
+    }
+}
@@ -0,0 +1,817 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! Content packs bundle the read-only static data of a setting.
+//!
+//! A pack is a directory of `.json` or `.jsonc` files. Each file holds one category of entries, named after the file
+//! stem (e.g. `faction.jsonc` holds the `faction` category), as an array of objects carrying a unique `id`.
+//!
+//! Entries are also known by a [`Slug`], `pack:category:slug`, that survives the pack being re-imported under fresh
+//! ids. An entry's slug is its `slug` field if it has one, else it is generated from its `descriptor.label`, `title` or
+//! `name`, else it is its id.
+//!
+//! A [`PackHash`] digests the entries of a pack, so the static data a game runs on can be checked against the packs it
+//! shipped with, e.g. for leagues requiring vanilla data.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    fs::{self, File},
+    io,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use ring::digest::{Context, SHA256};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use serde_json::Value;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::{
+    codec::{CodecError, JSONDeserialize, hex},
+    export::Exported,
+};
+
+/// Error type for content pack operations.
+#[derive(Error, Debug)]
+pub enum PackError {
+    /// The pack directory or one of its files could not be read.
+    #[error("failed to read {0}: {1}")]
+    Io(PathBuf, #[source] io::Error),
+    /// A pack file is not valid JSON.
+    #[error("invalid pack file {0}: {1}")]
+    Codec(PathBuf, #[source] CodecError),
+    /// An entry does not carry a valid `id`.
+    #[error("entry {index} in category {category} has no valid id")]
+    MissingId {
+        /// The category of the entry.
+        category: String,
+        /// The position of the entry in its file.
+        index: usize,
+    },
+    /// Entries of a category do not match the expected shape.
+    #[error("invalid entries in category {0}: {1}")]
+    InvalidEntries(String, #[source] serde_json::Error),
+    /// Two entries of a category share a slug.
+    #[error("entries {first} and {second} in category {category} share the slug {slug}")]
+    SlugCollision {
+        /// The category of the entries.
+        category: String,
+        /// The slug they share.
+        slug: String,
+        /// The id of the first entry.
+        first: Uuid,
+        /// The id of the second entry.
+        second: Uuid,
+    },
+    /// A slug is not of the form `pack:category:slug`.
+    #[error("invalid slug {0}, expected pack:category:slug")]
+    InvalidSlug(String),
+}
+
+/// Result type for content pack operations.
+pub type Result<T> = std::result::Result<T, PackError>;
+
+/// A single entry of a content pack.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Entry {
+    /// The unique id of the entry.
+    pub id: Uuid,
+    /// The raw entry data.
+    pub data: Value,
+}
+
+impl Entry {
+    /// Returns the slug of the entry within its category: its `slug` field if it has one, else its `descriptor.label`,
+    /// `title` or `name` in kebab case, else its id.
+    #[must_use]
+    pub fn slug(&self) -> String {
+        if let Some(slug) = self.data.get("slug").and_then(Value::as_str) {
+            return slug.into();
+        }
+
+        [&["descriptor", "label"][..], &["title"], &["name"]]
+            .iter()
+            .find_map(|path| path.iter().try_fold(&self.data, |data, key| data.get(key))?.as_str())
+            .map(kebab_case)
+            .filter(|slug| !slug.is_empty())
+            .unwrap_or_else(|| self.id.to_string())
+    }
+}
+
+/// The stable reference to a content entry, `pack:category:slug`, that holds across versions of the pack whatever the
+/// entry's id.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(into = "String", try_from = "String")]
+pub struct Slug {
+    /// The name of the pack.
+    pub pack: String,
+    /// The category of the entry.
+    pub category: String,
+    /// The slug of the entry within its category, see [`Entry::slug`].
+    pub slug: String,
+}
+
+impl Slug {
+    /// Creates the slug of an entry of the given pack and category.
+    pub fn new(pack: impl Into<String>, category: impl Into<String>, slug: impl Into<String>) -> Slug {
+        Slug {
+            pack: pack.into(),
+            category: category.into(),
+            slug: slug.into(),
+        }
+    }
+}
+
+impl fmt::Display for Slug {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.pack, self.category, self.slug)
+    }
+}
+
+impl FromStr for Slug {
+    type Err = PackError;
+
+    fn from_str(s: &str) -> Result<Slug> {
+        let mut parts = s.splitn(3, ':');
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(pack), Some(category), Some(slug)) if ![pack, category, slug].contains(&"") => Ok(Slug::new(pack, category, slug)),
+            _ => Err(PackError::InvalidSlug(s.into())),
+        }
+    }
+}
+
+impl From<Slug> for String {
+    fn from(slug: Slug) -> String {
+        slug.to_string()
+    }
+}
+
+impl TryFrom<String> for Slug {
+    type Error = PackError;
+
+    fn try_from(s: String) -> Result<Slug> {
+        s.parse()
+    }
+}
+
+/// How a content entry differs between two versions of a pack.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Changed {
+    /// The entry is new.
+    Added,
+    /// The entry's data changed.
+    Updated,
+    /// The entry is gone.
+    Removed,
+}
+
+/// An entry that differs between two versions of a pack.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EntryChange {
+    /// The category of the entry.
+    pub category: String,
+    /// The unique id of the entry.
+    pub id: Uuid,
+    /// How the entry differs.
+    pub change: Changed,
+    /// The new entry data, or [`Value::Null`] if it was removed.
+    pub data: Value,
+}
+
+/// The SHA-256 digests of the entries of a pack, whole and by category, in lowercase hexadecimal.
+///
+/// The digests do not depend on the order of the entries, nor on the formatting of the files they were read from. Empty
+/// categories are left out, as they hold nothing to load.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct PackHash {
+    /// The name of the pack.
+    pub pack: String,
+    /// The digest of the whole pack.
+    pub digest: String,
+    /// The digest of each category of the pack holding entries.
+    pub categories: BTreeMap<String, String>,
+}
+
+impl PackHash {
+    /// Digests the entries of the pack of the given name, each given along with its category.
+    pub fn of<'a>(pack: impl Into<String>, entries: impl IntoIterator<Item = (&'a str, &'a Entry)>) -> PackHash {
+        let mut sorted = BTreeMap::<&str, BTreeMap<Uuid, String>>::new();
+        for (category, entry) in entries {
+            sorted.entry(category).or_default().insert(entry.id, entry.data.to_string());
+        }
+
+        let categories = sorted
+            .into_iter()
+            .map(|(category, entries)| {
+                let mut context = Context::new(&SHA256);
+                for (id, data) in entries {
+                    context.update(id.as_bytes());
+                    update(&mut context, &data);
+                }
+                (category.to_owned(), hex(context.finish().as_ref()))
+            })
+            .collect::<BTreeMap<_, _>>();
+
+        let pack = pack.into();
+        let mut context = Context::new(&SHA256);
+        update(&mut context, &pack);
+        for (category, digest) in &categories {
+            update(&mut context, category);
+            update(&mut context, digest);
+        }
+
+        PackHash {
+            pack,
+            digest: hex(context.finish().as_ref()),
+            categories,
+        }
+    }
+
+    /// Returns the categories whose digest differs from the other hash, those found on one side only included, in
+    /// alphabetical order.
+    #[must_use]
+    pub fn differing(&self, other: &PackHash) -> Vec<String> {
+        self.categories
+            .keys()
+            .chain(other.categories.keys())
+            .filter(|category| self.categories.get(*category) != other.categories.get(*category))
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .cloned()
+            .collect()
+    }
+}
+
+/// Feeds the text to the digest, prefixed with its length so that consecutive texts cannot run into each other.
+fn update(context: &mut Context, text: &str) {
+    context.update(&u64::try_from(text.len()).unwrap_or(u64::MAX).to_le_bytes());
+    context.update(text.as_bytes());
+}
+
+/// A content pack loaded from disk.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(into = "PackData", try_from = "PackData")]
+pub struct Pack {
+    name: String,
+    categories: BTreeMap<String, Vec<Entry>>,
+}
+
+impl Pack {
+    /// Opens the content pack in the given directory. The pack is named after the directory.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PackError`] if a file cannot be read or parsed, or if an entry has no valid id.
+    pub fn open(path: impl AsRef<Path>) -> Result<Pack> {
+        let path = path.as_ref();
+        let name = path.file_name().map_or_else(String::new, |n| n.to_string_lossy().into_owned());
+
+        let mut categories = BTreeMap::new();
+        for file in fs::read_dir(path).map_err(|e| PackError::Io(path.into(), e))? {
+            let file = file.map_err(|e| PackError::Io(path.into(), e))?.path();
+            let (Some(category), Some("json" | "jsonc")) = (file.file_stem(), file.extension().and_then(|e| e.to_str())) else {
+                continue;
+            };
+
+            let category = category.to_string_lossy().into_owned();
+            let entries = read_entries(&file, &category)?;
+            categories.insert(category, entries);
+        }
+
+        Ok(Pack { name, categories })
+    }
+
+    /// Parses a content pack from the contents of its files, given by category, for engines that embed packs in their
+    /// own resources rather than reading the pack directory at runtime.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PackError`] if a file cannot be parsed, or if an entry has no valid id.
+    pub fn parse<C, S>(name: impl Into<String>, files: impl IntoIterator<Item = (C, S)>) -> Result<Pack>
+    where
+        C: Into<String>,
+        S: AsRef<str>,
+    {
+        let categories = files
+            .into_iter()
+            .map(|(category, source)| {
+                let category = category.into();
+                let file = PathBuf::from(format!("{category}.jsonc"));
+                let entries = parse_entries(&file, source.as_ref().as_bytes(), &category)?;
+                Ok((category, entries))
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Pack {
+            name: name.into(),
+            categories,
+        })
+    }
+
+    /// Returns the name of the pack.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the names of the categories in the pack, in alphabetical order.
+    pub fn categories(&self) -> impl Iterator<Item = &str> {
+        self.categories.keys().map(String::as_str)
+    }
+
+    /// Returns the raw entries of the given category, or an empty slice if the pack does not have it.
+    #[must_use]
+    pub fn entries(&self, category: &str) -> &[Entry] {
+        self.categories.get(category).map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns the slug of the entry of the given category with the given id, if any.
+    #[must_use]
+    pub fn slug(&self, category: &str, id: Uuid) -> Option<Slug> {
+        let entry = self.entries(category).iter().find(|entry| entry.id == id)?;
+        Some(Slug::new(self.name.as_str(), category, entry.slug()))
+    }
+
+    /// Returns the id of the entry the slug refers to, if it names an entry of this pack.
+    #[must_use]
+    pub fn resolve(&self, slug: &Slug) -> Option<Uuid> {
+        if slug.pack != self.name {
+            return None;
+        }
+
+        self.entries(&slug.category)
+            .iter()
+            .find(|entry| entry.slug() == slug.slug)
+            .map(|entry| entry.id)
+    }
+
+    /// Deserializes the entries of the given category.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PackError::InvalidEntries`] if an entry does not match the expected shape.
+    pub fn load<T: DeserializeOwned>(&self, category: &str) -> Result<Vec<T>> {
+        self.entries(category)
+            .iter()
+            .map(|entry| serde_json::from_value(entry.data.clone()))
+            .collect::<std::result::Result<_, _>>()
+            .map_err(|e| PackError::InvalidEntries(category.into(), e))
+    }
+
+    /// Digests the entries of the pack, see [`PackHash`].
+    #[must_use]
+    pub fn hash(&self) -> PackHash {
+        PackHash::of(
+            self.name.as_str(),
+            self.categories
+                .iter()
+                .flat_map(|(category, entries)| entries.iter().map(move |entry| (category.as_str(), entry))),
+        )
+    }
+
+    /// Compares the pack against the entries previously loaded from it, given along with their category. Returns the
+    /// entries added, updated and removed, ordered by category then id.
+    #[must_use]
+    pub fn diff(&self, loaded: &[(String, Entry)]) -> Vec<EntryChange> {
+        let previous = loaded
+            .iter()
+            .map(|(category, entry)| ((category.as_str(), entry.id), &entry.data))
+            .collect::<BTreeMap<_, _>>();
+        let current = self
+            .categories
+            .iter()
+            .flat_map(|(category, entries)| entries.iter().map(move |entry| ((category.as_str(), entry.id), &entry.data)))
+            .collect::<BTreeMap<_, _>>();
+
+        let mut changes = current
+            .iter()
+            .filter_map(|(&(category, id), &data)| {
+                let change = match previous.get(&(category, id)) {
+                    None => Changed::Added,
+                    Some(&old) if old != data => Changed::Updated,
+                    Some(_) => return None,
+                };
+
+                Some(EntryChange {
+                    category: category.into(),
+                    id,
+                    change,
+                    data: data.clone(),
+                })
+            })
+            .chain(
+                previous
+                    .keys()
+                    .filter(|key| !current.contains_key(*key))
+                    .map(|&(category, id)| EntryChange {
+                        category: category.into(),
+                        id,
+                        change: Changed::Removed,
+                        data: Value::Null,
+                    }),
+            )
+            .collect::<Vec<_>>();
+        changes.sort_by(|a, b| (&a.category, a.id).cmp(&(&b.category, b.id)));
+
+        changes
+    }
+}
+
+impl Exported for Pack {
+    const KIND: &'static str = "pack";
+}
+
+/// The entries of a pack by category, as exported.
+#[derive(Serialize, Deserialize)]
+struct PackData {
+    name: String,
+    categories: BTreeMap<String, Vec<Value>>,
+}
+
+impl From<Pack> for PackData {
+    fn from(pack: Pack) -> PackData {
+        let categories = pack
+            .categories
+            .into_iter()
+            .map(|(category, entries)| (category, entries.into_iter().map(|entry| entry.data).collect()))
+            .collect();
+
+        PackData { name: pack.name, categories }
+    }
+}
+
+impl TryFrom<PackData> for Pack {
+    type Error = PackError;
+
+    fn try_from(data: PackData) -> Result<Pack> {
+        let categories = data
+            .categories
+            .into_iter()
+            .map(|(category, values)| {
+                let entries = validate_entries(values, &category)?;
+                Ok((category, entries))
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Pack { name: data.name, categories })
+    }
+}
+
+/// Reads and validates the entries of a single category file.
+fn read_entries(file: &Path, category: &str) -> Result<Vec<Entry>> {
+    let reader = File::open(file).map_err(|e| PackError::Io(file.into(), e))?;
+    parse_entries(file, reader, category)
+}
+
+/// Parses and validates the entries of a single category, read from the given file.
+fn parse_entries(file: &Path, reader: impl io::Read, category: &str) -> Result<Vec<Entry>> {
+    let values = Vec::<Value>::from_jsonc(reader).map_err(|e| PackError::Codec(file.into(), e))?;
+    validate_entries(values, category)
+}
+
+/// Checks that every entry of the category carries a valid `id`, and that no two entries share a slug.
+fn validate_entries(values: Vec<Value>, category: &str) -> Result<Vec<Entry>> {
+    let entries = values
+        .into_iter()
+        .enumerate()
+        .map(|(index, data)| {
+            let id = data
+                .get("id")
+                .and_then(Value::as_str)
+                .and_then(|id| Uuid::parse_str(id).ok())
+                .ok_or_else(|| PackError::MissingId {
+                    category: category.into(),
+                    index,
+                })?;
+
+            Ok(Entry { id, data })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut slugs = BTreeMap::new();
+    for entry in &entries {
+        if let Some(first) = slugs.insert(entry.slug(), entry.id) {
+            return Err(PackError::SlugCollision {
+                category: category.into(),
+                slug: entry.slug(),
+                first,
+                second: entry.id,
+            });
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Turns a label into lowercase words joined by hyphens, dropping apostrophes, e.g. `Crow's Foot` into `crows-foot`.
+fn kebab_case(label: &str) -> String {
+    label
+        .chars()
+        .filter(|c| !matches!(c, '\'' | '’'))
+        .collect::<String>()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use rstest::rstest;
+    use serde::Deserialize;
+    use uuid::uuid;
+
+    use super::*;
+    use crate::export;
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Named {
+        id: Uuid,
+        name: String,
+    }
+
+    /// Writes the given files into a fresh pack directory.
+    fn write_pack(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let dir = env::temp_dir().join(format!("darkforge-pack-{}-{name}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).expect("should have created pack directory");
+
+        for (file, content) in files {
+            fs::write(dir.join(file), content).expect("should have written pack file");
+        }
+
+        dir
+    }
+
+    #[test]
+    fn should_open_pack_with_categories_from_file_names() {
+        let dir = write_pack(
+            "valid",
+            &[
+                (
+                    "faction.jsonc",
+                    r#"[
+                        // The city watch
+                        { "id": "7ef1bdbe-84de-4699-b1bd-be84de569995", "name": "Bluecoats" }
+                    ]"#,
+                ),
+                ("npc.json", r"[]"),
+                ("README.md", "ignored"),
+            ],
+        );
+
+        let pack = Pack::open(&dir).expect("should have opened pack");
+
+        assert_eq!(vec!["faction", "npc"], pack.categories().collect::<Vec<_>>());
+        assert_eq!(
+            vec![Named {
+                id: uuid!("7ef1bdbe-84de-4699-b1bd-be84de569995"),
+                name: "Bluecoats".into()
+            }],
+            pack.load::<Named>("faction").expect("should have loaded factions")
+        );
+        assert_eq!(&[] as &[Entry], pack.entries("missing"));
+    }
+
+    #[test]
+    fn should_fail_to_open_pack_given_entry_without_id() {
+        let dir = write_pack("missing-id", &[("faction.json", r#"[{ "name": "Bluecoats" }]"#)]);
+
+        let err = Pack::open(&dir).expect_err("should have failed to open pack");
+
+        assert!(matches!(err, PackError::MissingId { index: 0, .. }), "unexpected error: {err}");
+    }
+
+    #[test]
+    fn should_parse_pack_from_file_contents() {
+        let pack = Pack::parse(
+            "embedded",
+            [(
+                "faction",
+                r#"[
+                    // The city watch
+                    { "id": "7ef1bdbe-84de-4699-b1bd-be84de569995", "name": "Bluecoats" }
+                ]"#,
+            )],
+        )
+        .expect("should have parsed pack");
+
+        assert_eq!("embedded", pack.name());
+        assert_eq!(
+            vec![Named {
+                id: uuid!("7ef1bdbe-84de-4699-b1bd-be84de569995"),
+                name: "Bluecoats".into()
+            }],
+            pack.load::<Named>("faction").expect("should have loaded factions")
+        );
+    }
+
+    #[test]
+    fn should_fail_to_parse_pack_given_invalid_json() {
+        let err = Pack::parse("embedded", [("faction", "[{")]).expect_err("should have failed to parse pack");
+
+        assert!(
+            matches!(err, PackError::Codec(ref file, _) if file == Path::new("faction.jsonc")),
+            "unexpected error: {err}"
+        );
+    }
+
+    #[test]
+    fn should_round_trip_pack_through_export() {
+        let pack = Pack::parse(
+            "embedded",
+            [("faction", r#"[{ "id": "7ef1bdbe-84de-4699-b1bd-be84de569995", "name": "Bluecoats" }]"#)],
+        )
+        .expect("should have parsed pack");
+
+        let mut json = Vec::new();
+        export::export(&pack, &mut json).expect("should have exported pack");
+
+        assert_eq!(pack, export::import::<Pack>(json.as_slice()).expect("should have imported pack"));
+    }
+
+    #[test]
+    fn should_generate_slugs_given_entries() {
+        let pack = Pack::parse(
+            "embedded",
+            [(
+                "npc",
+                r#"[
+                    { "id": "00000000-0000-4000-8000-000000000001", "slug": "bazso", "descriptor": { "label": "Bazso Baz" } },
+                    { "id": "00000000-0000-4000-8000-000000000002", "descriptor": { "label": "Mylera Klev" } },
+                    { "id": "00000000-0000-4000-8000-000000000003", "title": "Crow's Foot" },
+                    { "id": "00000000-0000-4000-8000-000000000004", "name": "  The Lost  " },
+                    { "id": "00000000-0000-4000-8000-000000000005", "text": "Nameless" }
+                ]"#,
+            )],
+        )
+        .expect("should have parsed pack");
+
+        assert_eq!(
+            vec!["bazso", "mylera-klev", "crows-foot", "the-lost", "00000000-0000-4000-8000-000000000005"],
+            pack.entries("npc").iter().map(Entry::slug).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            Some(Slug::new("embedded", "npc", "mylera-klev")),
+            pack.slug("npc", uuid!("00000000-0000-4000-8000-000000000002"))
+        );
+    }
+
+    #[rstest]
+    #[case::same_pack("embedded:npc:bazso", Some(uuid!("00000000-0000-4000-8000-000000000001")))]
+    #[case::other_pack("other:npc:bazso", None)]
+    #[case::other_category("embedded:faction:bazso", None)]
+    #[case::unknown_slug("embedded:npc:lyssa", None)]
+    fn should_resolve_slug_given_pack(#[case] slug: &str, #[case] expected: Option<Uuid>) {
+        let pack = Pack::parse(
+            "embedded",
+            [(
+                "npc",
+                r#"[{ "id": "00000000-0000-4000-8000-000000000001", "descriptor": { "label": "Bazso" } }]"#,
+            )],
+        )
+        .expect("should have parsed pack");
+
+        assert_eq!(expected, pack.resolve(&slug.parse().expect("should have parsed slug")));
+    }
+
+    #[test]
+    fn should_fail_to_parse_pack_given_colliding_slugs() {
+        let err = Pack::parse(
+            "embedded",
+            [(
+                "faction",
+                r#"[
+                    { "id": "00000000-0000-4000-8000-000000000001", "name": "The Crows" },
+                    { "id": "00000000-0000-4000-8000-000000000002", "slug": "the-crows", "name": "Crows" }
+                ]"#,
+            )],
+        )
+        .expect_err("should have failed to parse pack");
+
+        assert!(
+            matches!(&err, PackError::SlugCollision { slug, second, .. } if slug == "the-crows" && *second == uuid!("00000000-0000-4000-8000-000000000002")),
+            "unexpected error: {err}"
+        );
+    }
+
+    #[rstest]
+    #[case::valid("defaults:faction:the-crows", true)]
+    #[case::colon_in_slug("defaults:rule:a:b", true)]
+    #[case::missing_category("defaults:the-crows", false)]
+    #[case::empty_pack(":faction:the-crows", false)]
+    fn should_parse_slug(#[case] slug: &str, #[case] valid: bool) {
+        let parsed = slug.parse::<Slug>();
+
+        assert_eq!(valid, parsed.is_ok(), "unexpected result: {parsed:?}");
+        if let Ok(parsed) = parsed {
+            assert_eq!(slug, parsed.to_string());
+            assert_eq!(
+                parsed,
+                serde_json::from_value(serde_json::to_value(&parsed).expect("should have serialized slug")).expect("should have deserialized slug")
+            );
+        }
+    }
+
+    #[test]
+    fn should_diff_pack_against_loaded_entries() {
+        const KEPT: Uuid = uuid!("00000000-0000-4000-8000-000000000001");
+        const UPDATED: Uuid = uuid!("00000000-0000-4000-8000-000000000002");
+        const ADDED: Uuid = uuid!("00000000-0000-4000-8000-000000000003");
+        const REMOVED: Uuid = uuid!("00000000-0000-4000-8000-000000000004");
+        let dir = write_pack(
+            "diff",
+            &[(
+                "faction.json",
+                &format!(
+                    r#"[
+                        {{ "id": "{KEPT}", "name": "Bluecoats" }},
+                        {{ "id": "{UPDATED}", "name": "The Crows" }},
+                        {{ "id": "{ADDED}", "name": "Lampblacks" }}
+                    ]"#
+                ),
+            )],
+        );
+        let pack = Pack::open(&dir).expect("should have opened pack");
+        let entry = |id: Uuid, name: &str| {
+            (
+                "faction".to_string(),
+                Entry {
+                    id,
+                    data: serde_json::json!({ "id": id, "name": name }),
+                },
+            )
+        };
+
+        let changes = pack.diff(&[entry(KEPT, "Bluecoats"), entry(UPDATED, "Crows"), entry(REMOVED, "Red Sashes")]);
+
+        assert_eq!(
+            vec![(UPDATED, Changed::Updated), (ADDED, Changed::Added), (REMOVED, Changed::Removed)],
+            changes.iter().map(|change| (change.id, change.change)).collect::<Vec<_>>()
+        );
+        assert_eq!(Some("The Crows"), changes[0].data.get("name").and_then(Value::as_str));
+        assert_eq!(Value::Null, changes[2].data);
+    }
+
+    #[test]
+    fn should_hash_pack_whatever_order_and_formatting_of_its_entries() {
+        let (bluecoats, crows) = (Uuid::new_v4(), Uuid::new_v4());
+        let shipped = Pack::parse(
+            "defaults",
+            [
+                (
+                    "faction",
+                    format!(r#"[{{ "id": "{bluecoats}", "name": "Bluecoats" }}, {{ "id": "{crows}", "name": "Crows" }}]"#),
+                ),
+                ("npc", String::from("[]")),
+            ],
+        )
+        .expect("should have parsed pack");
+        let reordered = Pack::parse(
+            "defaults",
+            [
+                ("npc", String::from("[ ]")),
+                (
+                    "faction",
+                    format!("[\n  {{ \"name\": \"Crows\", \"id\": \"{crows}\" }},\n  {{ \"id\": \"{bluecoats}\", \"name\": \"Bluecoats\" }}\n]"),
+                ),
+            ],
+        )
+        .expect("should have parsed pack");
+        let modded = Pack::parse(
+            "defaults",
+            [
+                (
+                    "faction",
+                    format!(r#"[{{ "id": "{bluecoats}", "name": "Bluecoats" }}, {{ "id": "{crows}", "name": "Ravens" }}]"#),
+                ),
+                ("npc", String::from("[]")),
+            ],
+        )
+        .expect("should have parsed pack");
+
+        let hash = shipped.hash();
+
+        assert_eq!(hash, reordered.hash());
+        assert_eq!(64, hash.digest.len());
+        assert_eq!(vec!["faction"], hash.categories.keys().collect::<Vec<_>>());
+        assert_ne!(hash.digest, modded.hash().digest);
+        assert_eq!(vec![String::from("faction")], hash.differing(&modded.hash()));
+        assert_eq!(Vec::<String>::new(), hash.differing(&reordered.hash()));
+    }
+}
@@ -0,0 +1,153 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! Platform-appropriate locations for the files a game saves: the campaign database, its backups and exports.
+//!
+//! [`SavePaths::platform`] resolves the user data directory of the operating system, while games running in Godot
+//! resolve `user://` and pass it to [`SavePaths::new`] instead.
+
+use std::{
+    env,
+    ffi::OsString,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use thiserror::Error;
+
+/// Error type for save paths that cannot be resolved.
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum SavePathError {
+    /// None of the environment variables locating the user data directory are set.
+    #[error("cannot locate the user data directory: {0} is not set")]
+    MissingVariable(&'static str),
+}
+
+/// The locations of the files a game saves, all under a single root directory.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SavePaths {
+    root: PathBuf,
+}
+
+impl SavePaths {
+    /// File name of the campaign database.
+    pub const DATABASE: &'static str = "campaign.db";
+    /// Name of the directory holding backups.
+    pub const BACKUPS: &'static str = "backups";
+    /// Name of the directory holding exports.
+    pub const EXPORTS: &'static str = "exports";
+
+    /// Creates save paths under the given root directory, e.g. Godot's `user://` once globalized.
+    #[must_use]
+    pub fn new(root: impl Into<PathBuf>) -> SavePaths {
+        SavePaths { root: root.into() }
+    }
+
+    /// Resolves the save paths of the application in the user data directory of the current platform:
+    /// `%APPDATA%` on Windows, `~/Library/Application Support` on macOS and `$XDG_DATA_HOME`, defaulting to
+    /// `~/.local/share`, elsewhere.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SavePathError::MissingVariable`] if the environment does not locate the user data directory.
+    pub fn platform(app: &str) -> Result<SavePaths, SavePathError> {
+        resolve(env::consts::OS, |name| env::var_os(name), app)
+    }
+
+    /// Returns the root directory.
+    #[must_use]
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Returns the path of the campaign database.
+    #[must_use]
+    pub fn database(&self) -> PathBuf {
+        self.root.join(SavePaths::DATABASE)
+    }
+
+    /// Returns the directory holding backups of the campaign database.
+    #[must_use]
+    pub fn backups(&self) -> PathBuf {
+        self.root.join(SavePaths::BACKUPS)
+    }
+
+    /// Returns the directory holding exports, such as session reports.
+    #[must_use]
+    pub fn exports(&self) -> PathBuf {
+        self.root.join(SavePaths::EXPORTS)
+    }
+
+    /// Creates the root, backup and export directories if they do not exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`io::Error`] if a directory cannot be created.
+    pub fn create(&self) -> io::Result<()> {
+        fs::create_dir_all(self.backups())?;
+        fs::create_dir_all(self.exports())
+    }
+}
+
+/// Resolves the save paths of the application on the operating system, reading environment variables with `var`.
+fn resolve(os: &str, var: impl Fn(&str) -> Option<OsString>, app: &str) -> Result<SavePaths, SavePathError> {
+    let data = match os {
+        "windows" => PathBuf::from(var("APPDATA").ok_or(SavePathError::MissingVariable("APPDATA"))?),
+        "macos" => PathBuf::from(var("HOME").ok_or(SavePathError::MissingVariable("HOME"))?).join("Library/Application Support"),
+        _ => match var("XDG_DATA_HOME").filter(|dir| Path::new(dir).is_absolute()) {
+            Some(dir) => PathBuf::from(dir),
+            None => PathBuf::from(var("HOME").ok_or(SavePathError::MissingVariable("HOME"))?).join(".local/share"),
+        },
+    };
+
+    Ok(SavePaths::new(data.join(app)))
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    fn vars(set: &[(&'static str, &'static str)]) -> impl Fn(&str) -> Option<OsString> {
+        move |name| set.iter().find(|(key, _)| *key == name).map(|(_, value)| OsString::from(value))
+    }
+
+    #[rstest]
+    #[case::windows("windows", &[("APPDATA", "C:/Users/pc/AppData/Roaming")], "C:/Users/pc/AppData/Roaming/goblins")]
+    #[case::macos("macos", &[("HOME", "/Users/pc")], "/Users/pc/Library/Application Support/goblins")]
+    #[case::linux_xdg("linux", &[("XDG_DATA_HOME", "/data"), ("HOME", "/home/pc")], "/data/goblins")]
+    #[case::linux_home("linux", &[("HOME", "/home/pc")], "/home/pc/.local/share/goblins")]
+    #[case::linux_relative_xdg("linux", &[("XDG_DATA_HOME", "data"), ("HOME", "/home/pc")], "/home/pc/.local/share/goblins")]
+    fn should_resolve_platform_data_directory(#[case] os: &str, #[case] set: &'static [(&'static str, &'static str)], #[case] expect: &str) {
+        let paths = resolve(os, vars(set), "goblins").expect("should have resolved save paths");
+
+        assert_eq!(Path::new(expect), paths.root());
+    }
+
+    #[rstest]
+    #[case::windows("windows", "APPDATA")]
+    #[case::macos("macos", "HOME")]
+    #[case::linux("linux", "HOME")]
+    fn should_fail_given_missing_variable(#[case] os: &str, #[case] missing: &'static str) {
+        assert_eq!(Err(SavePathError::MissingVariable(missing)), resolve(os, vars(&[]), "goblins"));
+    }
+
+    #[test]
+    fn should_place_files_under_root() {
+        let paths = SavePaths::new("/saves");
+
+        assert_eq!(Path::new("/saves/campaign.db"), paths.database());
+        assert_eq!(Path::new("/saves/backups"), paths.backups());
+        assert_eq!(Path::new("/saves/exports"), paths.exports());
+    }
+}
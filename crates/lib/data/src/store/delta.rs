@@ -0,0 +1,99 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! Deltas of the world, carrying the rows changed since a journal entry from one store to another, e.g. to sync a
+//! campaign saved in the cloud without uploading it whole.
+//!
+//! The journal entry a delta starts from is its checkpoint. A store exports the rows changed since the checkpoint of the
+//! last delta it exported, and the store applying the delta must not have recorded entries after that checkpoint
+//! itself, or the two have diverged.
+//!
+//! Rows removed since the checkpoint are carried as tombstones: the entities despawned, and the kinds of journal
+//! entries pruned along with the entry before which they were.
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{export::Exported, store::Recorded};
+
+/// The rows of a campaign changed since a journal entry.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Delta {
+    /// The campaign the rows belong to.
+    pub campaign: Uuid,
+    /// The journal entry the delta starts from, 0 for a delta holding the whole campaign.
+    pub since: i64,
+    /// The latest journal entry of the delta, from which the next delta starts.
+    pub until: i64,
+    /// The entities created since the checkpoint.
+    pub entities: Vec<Uuid>,
+    /// The components written since the checkpoint.
+    pub components: Vec<ChangedComponent>,
+    /// The relationships created since the checkpoint.
+    pub relationships: Vec<ChangedRelationship>,
+    /// The journal entries recorded after the checkpoint, in order.
+    pub journal: Vec<Recorded>,
+    /// The entities despawned since the checkpoint, whose components and relationships are removed.
+    #[serde(default)]
+    pub despawned: Vec<Uuid>,
+    /// The kinds of journal entries pruned since the checkpoint.
+    #[serde(default)]
+    pub pruned: Vec<PrunedJournal>,
+}
+
+/// A component written since the checkpoint of a delta.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ChangedComponent {
+    /// The entity the component is attached to.
+    pub entity: Uuid,
+    /// The kind of component.
+    pub kind: String,
+    /// The serialized component.
+    pub data: String,
+}
+
+/// A relationship created since the checkpoint of a delta.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ChangedRelationship {
+    /// The source of the relationship.
+    pub source: Uuid,
+    /// The target of the relationship.
+    pub target: Uuid,
+    /// The kind of relationship.
+    pub kind: String,
+}
+
+/// A kind of journal entries pruned since the checkpoint of a delta.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct PrunedJournal {
+    /// The kind of entries removed.
+    pub kind: String,
+    /// The sequence number of the first entry kept.
+    pub before: i64,
+}
+
+impl Delta {
+    /// Returns true if the delta holds no changes.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+            && self.components.is_empty()
+            && self.relationships.is_empty()
+            && self.journal.is_empty()
+            && self.despawned.is_empty()
+            && self.pruned.is_empty()
+    }
+}
+
+impl Exported for Delta {
+    const KIND: &'static str = "delta";
+}
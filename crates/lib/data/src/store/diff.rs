@@ -0,0 +1,520 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! Diffs and merges of world snapshots, to see what changed between two saves of a campaign and to reconcile saves
+//! that diverged from a common one, e.g. when two co-GMs edited their own copy.
+//!
+//! A [`Snapshot`] holds the entities of a campaign along with their components and relationships, and is taken from a
+//! [`Delta`] holding the whole campaign. [`WorldDiff::between`] lists what changed from a snapshot to another, and
+//! [`merge`] applies the changes both sides made to their common base, reporting a [`Conflict`] wherever they changed
+//! the same component differently.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::store::delta::{ChangedRelationship, Delta};
+
+/// The components of an entity, serialized, by kind.
+pub type Components = BTreeMap<String, String>;
+
+/// The state of a campaign's world at a point in time.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Snapshot {
+    /// The components of each entity.
+    pub entities: BTreeMap<Uuid, Components>,
+    /// The relationships between entities.
+    pub relationships: BTreeSet<Relationship>,
+}
+
+/// A relationship between two entities.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Relationship {
+    /// The source of the relationship.
+    pub source: Uuid,
+    /// The target of the relationship.
+    pub target: Uuid,
+    /// The kind of relationship.
+    pub kind: String,
+}
+
+impl From<&ChangedRelationship> for Relationship {
+    fn from(relationship: &ChangedRelationship) -> Self {
+        Relationship {
+            source: relationship.source,
+            target: relationship.target,
+            kind: relationship.kind.clone(),
+        }
+    }
+}
+
+impl From<&Delta> for Snapshot {
+    /// Takes a snapshot of the rows of a delta, which holds the whole world if it starts from entry 0.
+    fn from(delta: &Delta) -> Self {
+        let mut entities = delta
+            .entities
+            .iter()
+            .map(|entity| (*entity, Components::new()))
+            .collect::<BTreeMap<_, _>>();
+        for component in &delta.components {
+            entities
+                .entry(component.entity)
+                .or_default()
+                .insert(component.kind.clone(), component.data.clone());
+        }
+
+        Snapshot {
+            entities,
+            relationships: delta.relationships.iter().map(Relationship::from).collect(),
+        }
+    }
+}
+
+impl Snapshot {
+    /// Returns the serialized component of the kind attached to the entity, if it has one.
+    #[must_use]
+    pub fn component(&self, entity: Uuid, kind: &str) -> Option<&str> {
+        self.entities.get(&entity)?.get(kind).map(String::as_str)
+    }
+}
+
+/// A component that differs between two snapshots.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case", tag = "change")]
+pub enum ComponentChange {
+    /// The component was attached to the entity.
+    Added {
+        /// The entity.
+        entity: Uuid,
+        /// The kind of component.
+        kind: String,
+        /// The serialized component.
+        data: String,
+    },
+    /// The component was removed from the entity, or the entity was.
+    Removed {
+        /// The entity.
+        entity: Uuid,
+        /// The kind of component.
+        kind: String,
+        /// The serialized component, as it was.
+        data: String,
+    },
+    /// The component was replaced.
+    Changed {
+        /// The entity.
+        entity: Uuid,
+        /// The kind of component.
+        kind: String,
+        /// The serialized component, as it was.
+        before: String,
+        /// The serialized component, as it is.
+        after: String,
+    },
+}
+
+/// What changed from a snapshot to another.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct WorldDiff {
+    /// The entities only found in the second snapshot.
+    pub spawned: Vec<Uuid>,
+    /// The entities only found in the first snapshot.
+    pub despawned: Vec<Uuid>,
+    /// The components that differ, by entity then kind.
+    pub components: Vec<ComponentChange>,
+    /// The relationships only found in the second snapshot.
+    pub related: Vec<Relationship>,
+    /// The relationships only found in the first snapshot.
+    pub unrelated: Vec<Relationship>,
+}
+
+impl WorldDiff {
+    /// Lists what changed from the `before` snapshot to the `after` one.
+    #[must_use]
+    pub fn between(before: &Snapshot, after: &Snapshot) -> WorldDiff {
+        let entities = before.entities.keys().chain(after.entities.keys()).collect::<BTreeSet<_>>();
+        let mut components = Vec::new();
+        for entity in &entities {
+            let empty = Components::new();
+            let (was, is) = (
+                before.entities.get(entity).unwrap_or(&empty),
+                after.entities.get(entity).unwrap_or(&empty),
+            );
+            for kind in was.keys().chain(is.keys()).collect::<BTreeSet<_>>() {
+                let (entity, kind) = (**entity, kind.clone());
+                match (was.get(&kind), is.get(&kind)) {
+                    (None, Some(data)) => components.push(ComponentChange::Added {
+                        entity,
+                        kind,
+                        data: data.clone(),
+                    }),
+                    (Some(data), None) => components.push(ComponentChange::Removed {
+                        entity,
+                        kind,
+                        data: data.clone(),
+                    }),
+                    (Some(was), Some(is)) if was != is => components.push(ComponentChange::Changed {
+                        entity,
+                        kind,
+                        before: was.clone(),
+                        after: is.clone(),
+                    }),
+                    _ => {}
+                }
+            }
+        }
+
+        WorldDiff {
+            spawned: entities
+                .iter()
+                .filter(|entity| !before.entities.contains_key(entity))
+                .map(|entity| **entity)
+                .collect(),
+            despawned: entities
+                .iter()
+                .filter(|entity| !after.entities.contains_key(entity))
+                .map(|entity| **entity)
+                .collect(),
+            components,
+            related: after.relationships.difference(&before.relationships).cloned().collect(),
+            unrelated: before.relationships.difference(&after.relationships).cloned().collect(),
+        }
+    }
+
+    /// Returns true if both snapshots are the same.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.spawned.is_empty() && self.despawned.is_empty() && self.components.is_empty() && self.related.is_empty() && self.unrelated.is_empty()
+    }
+}
+
+/// A component both sides of a merge changed differently from their base. The merge keeps our side.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Conflict {
+    /// The entity.
+    pub entity: Uuid,
+    /// The kind of component, or `None` if one side removed the entity while the other changed it.
+    pub kind: Option<String>,
+    /// The serialized component in the base, if it was there, or all the components of the entity if `kind` is `None`.
+    pub base: Option<String>,
+    /// The serialized component on our side, if it is there, or all the components of the entity if `kind` is `None`.
+    pub ours: Option<String>,
+    /// The serialized component on their side, if it is there, or all the components of the entity if `kind` is
+    /// `None`.
+    pub theirs: Option<String>,
+}
+
+/// The result of a three-way merge.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Merge {
+    /// The merged snapshot, keeping our side of each conflict.
+    pub merged: Snapshot,
+    /// The conflicts to resolve, by entity then kind.
+    pub conflicts: Vec<Conflict>,
+}
+
+impl Merge {
+    /// Returns true if both sides merged cleanly.
+    #[must_use]
+    pub fn is_clean(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+}
+
+/// Merges the changes made on our side and on their side to the snapshot they both started from.
+///
+/// A change made on one side only is kept, as is a change made the same way on both. A component changed differently
+/// on both sides, or changed on one side while the other removed its entity, is a conflict. Relationships never
+/// conflict: one created on either side is kept, and one removed on either side is dropped.
+#[must_use]
+pub fn merge(base: &Snapshot, ours: &Snapshot, theirs: &Snapshot) -> Merge {
+    let mut merge = Merge::default();
+    let entities = [base, ours, theirs]
+        .iter()
+        .flat_map(|snapshot| snapshot.entities.keys())
+        .copied()
+        .collect::<BTreeSet<_>>();
+
+    for entity in entities {
+        let [was, mine, yours] = [base, ours, theirs].map(|snapshot| snapshot.entities.get(&entity));
+        let kept = match (was, mine, yours) {
+            (_, Some(mine), Some(yours)) => Some(merge_components(entity, was, mine, yours, &mut merge.conflicts)),
+            (Some(was), Some(kept), None) | (Some(was), None, Some(kept)) if kept != was => {
+                merge.conflicts.push(Conflict {
+                    entity,
+                    kind: None,
+                    base: serialized(Some(was)),
+                    ours: serialized(mine),
+                    theirs: serialized(yours),
+                });
+                mine.cloned()
+            }
+            (None, Some(kept), None) | (None, None, Some(kept)) => Some(kept.clone()),
+            _ => None,
+        };
+        if let Some(components) = kept {
+            merge.merged.entities.insert(entity, components);
+        }
+    }
+
+    merge.merged.relationships = ours
+        .relationships
+        .union(&theirs.relationships)
+        .filter(|relationship| {
+            !base.relationships.contains(relationship) || (ours.relationships.contains(relationship) && theirs.relationships.contains(relationship))
+        })
+        .cloned()
+        .collect();
+
+    merge
+}
+
+/// Serializes the components of an entity as a JSON object of their serialized values by kind.
+fn serialized(components: Option<&Components>) -> Option<String> {
+    components.map(|components| {
+        serde_json::Value::Object(
+            components
+                .iter()
+                .map(|(kind, data)| {
+                    let value = serde_json::from_str(data).unwrap_or_else(|_| serde_json::Value::String(data.clone()));
+                    (kind.clone(), value)
+                })
+                .collect(),
+        )
+        .to_string()
+    })
+}
+
+/// Merges the components of an entity both sides kept, recording the conflicts.
+fn merge_components(entity: Uuid, base: Option<&Components>, ours: &Components, theirs: &Components, conflicts: &mut Vec<Conflict>) -> Components {
+    let empty = Components::new();
+    let base = base.unwrap_or(&empty);
+    let kinds = [base, ours, theirs]
+        .iter()
+        .flat_map(|components| components.keys())
+        .collect::<BTreeSet<_>>();
+
+    let mut merged = Components::new();
+    for kind in kinds {
+        let (was, mine, yours) = (base.get(kind), ours.get(kind), theirs.get(kind));
+        let kept = if mine == yours || yours == was {
+            mine
+        } else if mine == was {
+            yours
+        } else {
+            conflicts.push(Conflict {
+                entity,
+                kind: Some(kind.clone()),
+                base: was.cloned(),
+                ours: mine.cloned(),
+                theirs: yours.cloned(),
+            });
+            mine
+        };
+        if let Some(data) = kept {
+            merged.insert(kind.clone(), data.clone());
+        }
+    }
+
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+    use uuid::uuid;
+
+    use super::*;
+    use crate::store::delta::ChangedComponent;
+
+    const ALICE: Uuid = uuid!("F9168C5E-FEB2-4FAA-B6BF-329BF39FA1E4");
+    const BOB: Uuid = uuid!("0B1C0B45-7D1F-4C55-9C2F-5E6E5F1B3A10");
+    const CREW: Uuid = uuid!("5B0C3F0E-58E2-4E55-9A4C-8D0F1A3E9C21");
+
+    fn snapshot(entities: &[(Uuid, &[(&str, &str)])], relationships: &[(Uuid, Uuid)]) -> Snapshot {
+        Snapshot {
+            entities: entities
+                .iter()
+                .map(|(entity, components)| {
+                    let components = components.iter().map(|(kind, data)| ((*kind).to_owned(), (*data).to_owned())).collect();
+                    (*entity, components)
+                })
+                .collect(),
+            relationships: relationships
+                .iter()
+                .map(|(source, target)| Relationship {
+                    source: *source,
+                    target: *target,
+                    kind: "member".into(),
+                })
+                .collect(),
+        }
+    }
+
+    fn base() -> Snapshot {
+        snapshot(
+            &[(ALICE, &[("stress", "2"), ("trauma", "0")]), (CREW, &[("heat", "1")])],
+            &[(ALICE, CREW)],
+        )
+    }
+
+    #[test]
+    fn should_take_snapshot_of_delta() {
+        let delta = Delta {
+            entities: vec![ALICE, CREW],
+            components: vec![ChangedComponent {
+                entity: ALICE,
+                kind: "stress".into(),
+                data: "2".into(),
+            }],
+            relationships: vec![ChangedRelationship {
+                source: ALICE,
+                target: CREW,
+                kind: "member".into(),
+            }],
+            ..Delta::default()
+        };
+
+        assert_eq!(
+            snapshot(&[(ALICE, &[("stress", "2")]), (CREW, &[])], &[(ALICE, CREW)]),
+            Snapshot::from(&delta)
+        );
+    }
+
+    #[test]
+    fn should_have_empty_diff_between_same_snapshots() {
+        assert!(WorldDiff::between(&base(), &base()).is_empty());
+    }
+
+    #[test]
+    fn should_diff_snapshots() {
+        let after = snapshot(
+            &[(ALICE, &[("stress", "4"), ("vice", "\"gambling\"")]), (BOB, &[("stress", "0")])],
+            &[(BOB, CREW)],
+        );
+
+        let diff = WorldDiff::between(&base(), &after);
+
+        assert_eq!(vec![BOB], diff.spawned);
+        assert_eq!(vec![CREW], diff.despawned);
+        assert_eq!(
+            vec![
+                ComponentChange::Added {
+                    entity: BOB,
+                    kind: "stress".into(),
+                    data: "0".into()
+                },
+                ComponentChange::Removed {
+                    entity: CREW,
+                    kind: "heat".into(),
+                    data: "1".into()
+                },
+                ComponentChange::Changed {
+                    entity: ALICE,
+                    kind: "stress".into(),
+                    before: "2".into(),
+                    after: "4".into()
+                },
+                ComponentChange::Removed {
+                    entity: ALICE,
+                    kind: "trauma".into(),
+                    data: "0".into()
+                },
+                ComponentChange::Added {
+                    entity: ALICE,
+                    kind: "vice".into(),
+                    data: "\"gambling\"".into()
+                },
+            ],
+            diff.components
+        );
+        assert_eq!(snapshot(&[], &[(BOB, CREW)]).relationships.into_iter().collect::<Vec<_>>(), diff.related);
+        assert_eq!(base().relationships.into_iter().collect::<Vec<_>>(), diff.unrelated);
+    }
+
+    #[rstest]
+    #[case::only_ours(
+        snapshot(&[(ALICE, &[("stress", "3"), ("trauma", "0")]), (CREW, &[("heat", "1")])], &[(ALICE, CREW)]),
+        base(),
+        snapshot(&[(ALICE, &[("stress", "3"), ("trauma", "0")]), (CREW, &[("heat", "1")])], &[(ALICE, CREW)])
+    )]
+    #[case::only_theirs(
+        base(),
+        snapshot(&[(ALICE, &[("stress", "2")]), (CREW, &[("heat", "1")])], &[]),
+        snapshot(&[(ALICE, &[("stress", "2")]), (CREW, &[("heat", "1")])], &[])
+    )]
+    #[case::both_sides(
+        snapshot(&[(ALICE, &[("stress", "3"), ("trauma", "0")]), (CREW, &[("heat", "1")]), (BOB, &[])], &[(ALICE, CREW)]),
+        snapshot(&[(ALICE, &[("stress", "2"), ("trauma", "0")]), (CREW, &[("heat", "2")])], &[(ALICE, CREW), (BOB, CREW)]),
+        snapshot(
+            &[(ALICE, &[("stress", "3"), ("trauma", "0")]), (CREW, &[("heat", "2")]), (BOB, &[])],
+            &[(ALICE, CREW), (BOB, CREW)]
+        )
+    )]
+    #[case::same_change(
+        snapshot(&[(ALICE, &[("stress", "3"), ("trauma", "0")]), (CREW, &[("heat", "1")])], &[(ALICE, CREW)]),
+        snapshot(&[(ALICE, &[("stress", "3"), ("trauma", "0")]), (CREW, &[("heat", "1")])], &[(ALICE, CREW)]),
+        snapshot(&[(ALICE, &[("stress", "3"), ("trauma", "0")]), (CREW, &[("heat", "1")])], &[(ALICE, CREW)])
+    )]
+    #[case::removed_unchanged(
+        snapshot(&[(ALICE, &[("stress", "2"), ("trauma", "0")])], &[]),
+        base(),
+        snapshot(&[(ALICE, &[("stress", "2"), ("trauma", "0")])], &[])
+    )]
+    fn should_merge_cleanly(#[case] ours: Snapshot, #[case] theirs: Snapshot, #[case] expected: Snapshot) {
+        let merge = merge(&base(), &ours, &theirs);
+
+        assert!(merge.is_clean(), "should have merged cleanly: {:?}", merge.conflicts);
+        assert_eq!(expected, merge.merged);
+    }
+
+    #[test]
+    fn should_keep_ours_on_conflicting_component() {
+        let ours = snapshot(&[(ALICE, &[("stress", "3"), ("trauma", "0")]), (CREW, &[("heat", "1")])], &[]);
+        let theirs = snapshot(&[(ALICE, &[("stress", "5"), ("trauma", "0")]), (CREW, &[("heat", "1")])], &[]);
+
+        let merge = merge(&base(), &ours, &theirs);
+
+        assert_eq!(ours, merge.merged);
+        assert_eq!(
+            vec![Conflict {
+                entity: ALICE,
+                kind: Some("stress".into()),
+                base: Some("2".into()),
+                ours: Some("3".into()),
+                theirs: Some("5".into()),
+            }],
+            merge.conflicts
+        );
+    }
+
+    #[test]
+    fn should_report_conflict_on_entity_removed_while_changed() {
+        let ours = snapshot(&[(ALICE, &[("stress", "2"), ("trauma", "0")])], &[]);
+        let theirs = snapshot(&[(ALICE, &[("stress", "2"), ("trauma", "0")]), (CREW, &[("heat", "3")])], &[]);
+
+        let merge = merge(&base(), &ours, &theirs);
+
+        assert_eq!(ours, merge.merged);
+        assert_eq!(
+            vec![Conflict {
+                entity: CREW,
+                kind: None,
+                base: Some(r#"{"heat":1}"#.into()),
+                ours: None,
+                theirs: Some(r#"{"heat":3}"#.into()),
+            }],
+            merge.conflicts
+        );
+    }
+}
@@ -10,12 +10,36 @@
  * You should have received a copy of the GNU Affero General Public License along with this program.
  * If not, see https://www.gnu.org/licenses/.
  */
-mod sql;
+/// Module for deltas of the world, exported from one store and applied to another.
+pub mod delta;
+/// Module for diffs and three-way merges of world snapshots.
+pub mod diff;
+/// Module for reference integrity between entities.
+pub mod reference;
+/// Module for the reflection of the database layout.
+pub mod schema;
+/// Module for SQL backed stores.
+#[cfg(feature = "sqlite")]
+pub mod sql;
+/// Module for store integrity checks.
+pub mod verify;
 
 use std::{error, fmt::Debug, future::Future, path::PathBuf};
 
-use serde::Deserialize;
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
 use thiserror::Error;
+use uuid::Uuid;
+
+use crate::{
+    CodecError, Component, JSONDeserialize, Persisted,
+    pack::{EntryChange, Pack, PackHash, Slug},
+    store::{
+        delta::Delta,
+        diff::Relationship,
+        verify::{Repair, Report},
+    },
+};
 
 /// Error type for store operations in the data crate.
 #[derive(Debug, Error)]
@@ -59,6 +83,384 @@ pub trait Migrator {
     /// Result type for migration operations.
     type Result<T>: Into<Result<T, Self::Error>>;
 
-    /// Applies migrations from the given path.
+    /// Applies migrations from the given path, leaving the database as it was if any of them fails.
     fn apply(&self, path: impl Into<PathBuf>) -> impl Future<Output = Self::Result<()>>;
 }
+
+/// Trait for stores that can check and repair their own integrity.
+pub trait Verify: Store {
+    /// Checks the store for integrity issues and returns a report listing them.
+    fn verify(&mut self) -> impl Future<Output = Self::Result<Report>>;
+
+    /// Applies the given repairs to the store, returning the number of rows affected.
+    fn repair(&mut self, repairs: &[Repair]) -> impl Future<Output = Self::Result<u64>>;
+}
+
+/// Trait for stores whose world can be synced with another store holding the same campaign, by exchanging the rows
+/// changed since a journal entry.
+pub trait Replicate: Store {
+    /// Returns the entities, components and relationships written since the journal entry `since`, along with the
+    /// journal entries recorded after it and the entities despawned and journal entries pruned since. Rows written in
+    /// the same revision as `since` may be included again.
+    fn delta(&mut self, since: i64) -> impl Future<Output = Self::Result<Delta>>;
+
+    /// Applies a delta exported from another store in a single transaction, returning the number of rows written.
+    /// Applying a delta twice leaves the store as applying it once.
+    fn apply_delta(&mut self, delta: &Delta) -> impl Future<Output = Self::Result<u64>>;
+}
+
+/// A component read as part of a batch, see [`World::gather`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Gathered {
+    /// The entity the component is attached to.
+    pub entity: Uuid,
+    /// The source of the entity's parent relationship, if any.
+    pub parent: Option<Uuid>,
+    /// The kind of component.
+    pub kind: String,
+    /// The serialized component.
+    pub data: String,
+}
+
+impl Gathered {
+    /// Returns true if the component is of the same kind as `C`.
+    #[must_use]
+    pub fn is<C: Persisted>(&self) -> bool {
+        self.kind == C::KIND
+    }
+
+    /// Deserializes the component.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CodecError`] if the data does not match `C`.
+    pub fn decode<C: Persisted>(&self) -> Result<C, CodecError> {
+        C::from_json(self.data.as_bytes())
+    }
+}
+
+/// An entry of the journal, see [`World::journal`].
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Recorded {
+    /// The sequence number of the entry.
+    pub seq: i64,
+    /// The entity the entry is about, if any.
+    pub entity: Option<Uuid>,
+    /// The kind of entry.
+    pub kind: String,
+    /// The serialized entry data.
+    pub data: String,
+    /// When the entry was recorded, in seconds since the Unix epoch.
+    pub recorded_at: i64,
+}
+
+impl Recorded {
+    /// Deserializes the entry data.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CodecError`] if the data does not match `T`.
+    pub fn decode<T: DeserializeOwned>(&self) -> Result<T, CodecError> {
+        T::from_json(self.data.as_bytes())
+    }
+}
+
+/// A number read from a journal entry, see [`Aggregate::series`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Sample {
+    /// The sequence number of the entry.
+    pub seq: i64,
+    /// The entity the entry is about, if any.
+    pub entity: Option<Uuid>,
+    /// When the entry was recorded, in seconds since the Unix epoch.
+    pub recorded_at: i64,
+    /// The number read.
+    pub value: i64,
+}
+
+/// The tally of the journal entries recorded after a marker and before the next one, see [`Aggregate::tally`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Bucket {
+    /// The sequence number of the marker, or `None` for the entries recorded before the first marker.
+    pub marker: Option<i64>,
+    /// The tally of the entries.
+    pub value: i64,
+}
+
+/// How the journal entries of a [`Bucket`] are tallied.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Tally<'a> {
+    /// Counts the entries.
+    Count,
+    /// Counts the entries whose value at the JSON path is true.
+    CountWhere(&'a str),
+    /// Sums the increases of the number at the JSON path from each entry to the next about the same entity, starting
+    /// from zero, e.g. the coin earned from the successive values of a stash.
+    Gain(&'a str),
+}
+
+/// The archival of an entity, see [`World::archive`]. Archived entities are left out of the listings of the world unless
+/// asked for, e.g. dead NPCs and retired characters, but keep their components, relationships and journal entries, so
+/// they remain in the history and exports of the campaign.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Archived {
+    /// Whether the entity is archived, false once restored.
+    pub archived: bool,
+    /// When the entity was archived, in seconds since the Unix epoch, if it is.
+    pub archived_at: Option<i64>,
+}
+
+impl Component for Archived {}
+impl Persisted for Archived {
+    const KIND: &'static str = "archived";
+}
+
+/// A change to the game world, applied along with others by [`World::commit`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Change {
+    /// Create the entity in the campaign the store is scoped to, so the changes after it can refer to it.
+    Spawn {
+        /// The entity to create.
+        entity: Uuid,
+    },
+    /// Attach a component to an entity, replacing any component of the same kind.
+    Insert {
+        /// The entity to attach the component to.
+        entity: Uuid,
+        /// The kind of component.
+        kind: String,
+        /// The serialized component.
+        data: String,
+    },
+    /// Link the source entity to the target entity.
+    Relate {
+        /// The source of the relationship.
+        source: Uuid,
+        /// The target of the relationship.
+        target: Uuid,
+        /// The kind of relationship.
+        kind: String,
+    },
+    /// Append an event to the journal.
+    Record {
+        /// The entity the event happened to, if any.
+        entity: Option<Uuid>,
+        /// The kind of event.
+        kind: String,
+        /// The serialized event.
+        data: String,
+    },
+    /// Archive the entity, or restore it, see [`Archived`].
+    Archive {
+        /// The entity to archive or restore.
+        entity: Uuid,
+        /// Whether the entity is archived or restored.
+        archived: bool,
+    },
+    /// Remove the components of the entity and the relationships from and to it. The entity itself is kept, so the
+    /// journal entries about it still point to it.
+    Despawn {
+        /// The entity to remove.
+        entity: Uuid,
+    },
+    /// Remove the journal entries of a kind recorded before the entry `before`, which is kept.
+    Prune {
+        /// The kind of entries to remove.
+        kind: String,
+        /// The sequence number of the first entry kept.
+        before: i64,
+    },
+}
+
+impl Change {
+    /// Creates a change creating the entity.
+    #[must_use]
+    pub fn spawn(entity: Uuid) -> Change {
+        Change::Spawn { entity }
+    }
+
+    /// Creates a change attaching the component to the entity.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CodecError::Serialize`] if the component cannot be serialized.
+    pub fn insert<C: Persisted>(entity: Uuid, component: &C) -> Result<Change, CodecError> {
+        Ok(Change::Insert {
+            entity,
+            kind: C::KIND.into(),
+            data: serialize(component)?,
+        })
+    }
+
+    /// Creates a change linking the source entity to the target entity.
+    #[must_use]
+    pub fn relate(source: Uuid, target: Uuid, kind: &str) -> Change {
+        Change::Relate {
+            source,
+            target,
+            kind: kind.into(),
+        }
+    }
+
+    /// Creates a change appending an event to the journal.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CodecError::Serialize`] if the event cannot be serialized.
+    pub fn record(entity: Option<Uuid>, kind: &str, data: &impl Serialize) -> Result<Change, CodecError> {
+        Ok(Change::Record {
+            entity,
+            kind: kind.into(),
+            data: serialize(data)?,
+        })
+    }
+
+    /// Creates a change archiving the entity, leaving it out of the listings of the world.
+    #[must_use]
+    pub fn archive(entity: Uuid) -> Change {
+        Change::Archive { entity, archived: true }
+    }
+
+    /// Creates a change restoring the archived entity, listing it again.
+    #[must_use]
+    pub fn restore(entity: Uuid) -> Change {
+        Change::Archive { entity, archived: false }
+    }
+
+    /// Creates a change removing the components and relationships of the entity.
+    #[must_use]
+    pub fn despawn(entity: Uuid) -> Change {
+        Change::Despawn { entity }
+    }
+
+    /// Creates a change removing the journal entries of the given kind recorded before the entry `before`.
+    #[must_use]
+    pub fn prune(kind: &str, before: i64) -> Change {
+        Change::Prune { kind: kind.into(), before }
+    }
+}
+
+/// Serializes a value into a JSON string.
+fn serialize(value: &impl Serialize) -> Result<String, CodecError> {
+    serde_json::to_string(value).map_err(|e| CodecError::Serialize(anyhow!(e)))
+}
+
+/// Trait for stores holding the dynamic game world: entities, their components, the relationships between them, and
+/// the journal of events that happened to them.
+pub trait World: Store {
+    /// Creates a new entity and returns its id.
+    fn spawn(&mut self) -> impl Future<Output = Self::Result<Uuid>>;
+
+    /// Attaches the component to the entity, replacing any component of the same kind.
+    fn insert<C: Persisted>(&mut self, entity: Uuid, component: &C) -> impl Future<Output = Self::Result<()>>;
+
+    /// Returns whether the entity belongs to the campaign and holds components, which despawned entities do not.
+    fn exists(&mut self, entity: Uuid) -> impl Future<Output = Self::Result<bool>>;
+
+    /// Returns the component of the given kind attached to the entity, if any.
+    fn get<C: Persisted>(&mut self, entity: Uuid) -> impl Future<Output = Self::Result<Option<C>>>;
+
+    /// Returns every component of the given kind along with the entity it is attached to, in insertion order, leaving
+    /// out archived entities.
+    fn all<C: Persisted>(&mut self) -> impl Future<Output = Self::Result<Vec<(Uuid, C)>>> {
+        self.all_with(false)
+    }
+
+    /// Returns every component of the given kind along with the entity it is attached to, in insertion order, including
+    /// archived entities only if `include_archived` is set.
+    fn all_with<C: Persisted>(&mut self, include_archived: bool) -> impl Future<Output = Self::Result<Vec<(Uuid, C)>>>;
+
+    /// Returns every component of the given kind along with the entity it is attached to, ordered by the text `key`
+    /// picks out of it, e.g. its name, per the collation of the store, leaving out archived entities.
+    fn sorted<C: Persisted>(&mut self, key: impl Fn(&C) -> &str + Send) -> impl Future<Output = Self::Result<Vec<(Uuid, C)>>>;
+
+    /// Archives the entity, stamping the time of its archival, see [`Archived`]. Archiving an archived entity keeps
+    /// the time it was first archived.
+    fn archive(&mut self, entity: Uuid) -> impl Future<Output = Self::Result<()>>;
+
+    /// Restores the archived entity, listing it again.
+    fn restore(&mut self, entity: Uuid) -> impl Future<Output = Self::Result<()>>;
+
+    /// Links the source entity to the target entity with a relationship of the given kind.
+    fn relate(&mut self, source: Uuid, target: Uuid, kind: &str) -> impl Future<Output = Self::Result<()>>;
+
+    /// Returns the targets of the source entity's relationships of the given kind.
+    fn related(&mut self, source: Uuid, kind: &str) -> impl Future<Output = Self::Result<Vec<Uuid>>>;
+
+    /// Returns the relationships from and to the entity, in insertion order.
+    fn links(&mut self, entity: Uuid) -> impl Future<Output = Self::Result<Vec<Relationship>>>;
+
+    /// Removes the components of the entity and the relationships from and to it, keeping the entity so the journal
+    /// entries about it still point to it. References to it are not checked, see
+    /// [`Deletion`](reference::Deletion).
+    fn despawn(&mut self, entity: Uuid) -> impl Future<Output = Self::Result<()>>;
+
+    /// Returns every component of the given kinds in a single batch, along with the source of the parent relationship
+    /// of the given kind pointing to its entity, in insertion order, leaving out archived entities.
+    fn gather(&mut self, kinds: &[&str], parent: &str) -> impl Future<Output = Self::Result<Vec<Gathered>>>;
+
+    /// Appends an event to the journal and returns its sequence number.
+    fn record(&mut self, entity: Option<Uuid>, kind: &str, data: &impl Serialize) -> impl Future<Output = Self::Result<i64>>;
+
+    /// Returns up to `limit` journal entries of the given kind, newest first, only those about `entity` if given.
+    fn journal(&mut self, entity: Option<Uuid>, kind: &str, limit: u32) -> impl Future<Output = Self::Result<Vec<Recorded>>>;
+
+    /// Returns the journal entries recorded after the entry `after` and before the entry `before` if any, oldest first.
+    fn journal_between(&mut self, after: i64, before: Option<i64>) -> impl Future<Output = Self::Result<Vec<Recorded>>>;
+
+    /// Applies every change atomically: either all of them are applied or none are. Returns the sequence numbers of
+    /// the journal entries recorded, in order.
+    fn commit(&mut self, changes: &[Change]) -> impl Future<Output = Self::Result<Vec<i64>>>;
+}
+
+/// Trait for stores holding the read-only static data loaded from content packs.
+pub trait Content: Store {
+    /// Loads every entry of the pack, replacing entries with the same category and id. Returns the number of entries
+    /// loaded.
+    fn load_pack(&mut self, pack: &Pack) -> impl Future<Output = Self::Result<u64>> {
+        self.import_pack(pack, |_, _| {})
+    }
+
+    /// Loads every entry of the pack like [`Content::load_pack`], in batches written atomically, calling `progress`
+    /// with the number of entries loaded so far and the total after each batch.
+    fn import_pack(&mut self, pack: &Pack, progress: impl FnMut(u64, u64)) -> impl Future<Output = Self::Result<u64>>;
+
+    /// Reloads a pack previously loaded under the same name, applying only the entries that were added, updated or
+    /// removed since. `record` turns those changes into changes to the world, e.g. journal entries announcing them,
+    /// committed in the same transaction so either the pack is reloaded and recorded or nothing is. Returns the changes
+    /// to the pack, see [`Pack::diff`].
+    fn reload_pack(
+        &mut self, pack: &Pack, record: impl FnOnce(&[EntryChange]) -> Result<Vec<Change>, CodecError> + Send,
+    ) -> impl Future<Output = Self::Result<Vec<EntryChange>>>;
+
+    /// Returns the entry of the given category with the given id, if any.
+    fn entry<T: DeserializeOwned>(&mut self, category: &str, id: Uuid) -> impl Future<Output = Self::Result<Option<T>>>;
+
+    /// Returns every entry of the given category.
+    fn entries<T: DeserializeOwned>(&mut self, category: &str) -> impl Future<Output = Self::Result<Vec<T>>>;
+
+    /// Returns every entry of the given category, ordered by the text `key` picks out of it, e.g. its label, per the
+    /// collation of the store.
+    fn sorted_entries<T: DeserializeOwned>(&mut self, category: &str, key: impl Fn(&T) -> &str + Send) -> impl Future<Output = Self::Result<Vec<T>>>;
+
+    /// Returns the id of the loaded entry the slug refers to, if any, whatever ids the pack was last loaded with.
+    fn resolve(&mut self, slug: &Slug) -> impl Future<Output = Self::Result<Option<Uuid>>>;
+
+    /// Returns the hashes of the packs as they were last loaded or reloaded, ordered by pack name.
+    fn pack_hashes(&mut self) -> impl Future<Output = Self::Result<Vec<PackHash>>>;
+
+    /// Hashes the entries held for the pack of the given name as they stand, which differs from the hash of the pack
+    /// as loaded if they were changed since, e.g. edited in the database.
+    fn hash_content(&mut self, pack: &str) -> impl Future<Output = Self::Result<PackHash>>;
+}
+
+/// Trait for stores aggregating the journal of the campaign where it is kept, e.g. into series to chart.
+pub trait Aggregate: Store {
+    /// Returns the number at the JSON path of every journal entry of the given kinds holding one, oldest first.
+    fn series(&mut self, kinds: &[&str], path: &str) -> impl Future<Output = Self::Result<Vec<Sample>>>;
+
+    /// Tallies the journal entries of a kind between consecutive entries of the `marker` kind, e.g. per session. Every
+    /// marker has a bucket, in order, preceded by one for the entries recorded before the first marker if there are
+    /// any.
+    fn tally(&mut self, marker: &str, kind: &str, tally: Tally<'_>) -> impl Future<Output = Self::Result<Vec<Bucket>>>;
+}
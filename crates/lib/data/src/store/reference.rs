@@ -0,0 +1,239 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! Reference integrity between entities, enforced when one of them is deleted.
+//!
+//! Entities refer to one another through relationships, e.g. a faction to its clocks or a crew to its contacts. When an
+//! entity is deleted, a [`ReferencePolicy`] tells for each kind of relationship whether the entities it leads to are
+//! deleted along with it, merely unlinked, or whether the deletion is refused with a [`ReferenceViolation`] listing the
+//! relationships in the way. A [`Deletion`] walks the relationships of the entity and of those deleted along with it.
+
+use std::collections::BTreeMap;
+
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::store::diff::Relationship;
+
+/// What happens to a relationship of an entity being deleted.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OnDelete {
+    /// The deletion is refused.
+    #[default]
+    Block,
+    /// The relationship is removed, leaving the entity at its other end in place.
+    Nullify,
+    /// The relationship is removed, and its target is deleted along with its source. Relationships pointing to the
+    /// entity being deleted only are removed.
+    Cascade,
+}
+
+/// What happens to the relationships of an entity being deleted, by kind of relationship.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ReferencePolicy {
+    /// What happens to relationships of a kind without a rule of its own.
+    fallback: OnDelete,
+    /// The rules of each kind of relationship.
+    kinds: BTreeMap<String, OnDelete>,
+}
+
+impl ReferencePolicy {
+    /// Creates a policy applying the same rule to every kind of relationship.
+    #[must_use]
+    pub fn new(fallback: OnDelete) -> ReferencePolicy {
+        ReferencePolicy {
+            fallback,
+            kinds: BTreeMap::new(),
+        }
+    }
+
+    /// Applies the rule to the relationships of the given kind instead.
+    #[must_use]
+    pub fn with(mut self, kind: &str, on_delete: OnDelete) -> ReferencePolicy {
+        self.kinds.insert(kind.to_owned(), on_delete);
+
+        self
+    }
+
+    /// Returns what happens to the relationships of the given kind.
+    #[must_use]
+    pub fn on_delete(&self, kind: &str) -> OnDelete {
+        self.kinds.get(kind).copied().unwrap_or(self.fallback)
+    }
+}
+
+/// Error returned when an entity cannot be deleted because a [`ReferencePolicy`] blocks some of the relationships it,
+/// or the entities deleted along with it, has.
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+#[error("entity {entity} is still referenced by {} relationships", .referrers.len())]
+pub struct ReferenceViolation {
+    /// The entity being deleted.
+    pub entity: Uuid,
+    /// The relationships blocking the deletion.
+    pub referrers: Vec<Relationship>,
+}
+
+/// The entities and relationships removed by deleting an entity, found by visiting the relationships of each entity
+/// deleted in turn.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Deletion {
+    /// The entities deleted, the one asked for first.
+    pub entities: Vec<Uuid>,
+    /// The relationships removed.
+    pub relationships: Vec<Relationship>,
+    /// The entities whose relationships are yet to be visited.
+    pending: Vec<Uuid>,
+    /// The relationships blocking the deletion.
+    blocked: Vec<Relationship>,
+}
+
+impl Deletion {
+    /// Starts the deletion of the entity.
+    #[must_use]
+    pub fn new(entity: Uuid) -> Deletion {
+        Deletion {
+            entities: vec![entity],
+            relationships: Vec::new(),
+            pending: vec![entity],
+            blocked: Vec::new(),
+        }
+    }
+
+    /// Returns the next entity whose relationships must be visited, if any.
+    pub fn unvisited(&mut self) -> Option<Uuid> {
+        self.pending.pop()
+    }
+
+    /// Applies the policy to the relationships from and to the entity.
+    pub fn visit(&mut self, entity: Uuid, relationships: &[Relationship], policy: &ReferencePolicy) {
+        for relationship in relationships {
+            if self.relationships.contains(relationship) || self.blocked.contains(relationship) {
+                continue;
+            }
+
+            match policy.on_delete(&relationship.kind) {
+                OnDelete::Block => self.blocked.push(relationship.clone()),
+                OnDelete::Nullify => self.relationships.push(relationship.clone()),
+                OnDelete::Cascade => {
+                    self.relationships.push(relationship.clone());
+                    if relationship.source == entity && !self.entities.contains(&relationship.target) {
+                        self.entities.push(relationship.target);
+                        self.pending.push(relationship.target);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Finishes the deletion once every entity was visited. Blocked relationships between entities that are all
+    /// deleted are removed along with them.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ReferenceViolation`] listing the relationships blocking the deletion, if any.
+    pub fn finish(mut self) -> Result<Deletion, ReferenceViolation> {
+        let (internal, referrers) = self
+            .blocked
+            .drain(..)
+            .partition::<Vec<_>, _>(|relationship| self.entities.contains(&relationship.source) && self.entities.contains(&relationship.target));
+        if !referrers.is_empty() {
+            return Err(ReferenceViolation {
+                entity: self.entities[0],
+                referrers,
+            });
+        }
+        self.relationships.extend(internal);
+
+        Ok(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FACTION: Uuid = uuid::uuid!("5b0c3c27-6f3e-4a39-8a0e-0e4f0c1b2a11");
+    const CLOCK: Uuid = uuid::uuid!("0b1c0b45-7d1f-4c55-9c2f-5e6e5f1b3a10");
+    const CREW: Uuid = uuid::uuid!("f9163a4e-8c1d-4b7e-9a2f-3c4d5e6f7a8b");
+
+    fn relationship(source: Uuid, target: Uuid, kind: &str) -> Relationship {
+        Relationship {
+            source,
+            target,
+            kind: kind.into(),
+        }
+    }
+
+    /// Deletes the entity from a world holding the given relationships.
+    fn delete(entity: Uuid, world: &[Relationship], policy: &ReferencePolicy) -> Result<Deletion, ReferenceViolation> {
+        let mut deletion = Deletion::new(entity);
+        while let Some(next) = deletion.unvisited() {
+            let relationships = world
+                .iter()
+                .filter(|relationship| relationship.source == next || relationship.target == next)
+                .cloned()
+                .collect::<Vec<_>>();
+            deletion.visit(next, &relationships, policy);
+        }
+
+        deletion.finish()
+    }
+
+    #[test]
+    fn should_block_deletion_of_referenced_entity() {
+        let world = [relationship(FACTION, CLOCK, "clock"), relationship(CREW, FACTION, "rival")];
+
+        let err = delete(FACTION, &world, &ReferencePolicy::default()).expect_err("should have blocked deletion");
+
+        assert_eq!(
+            ReferenceViolation {
+                entity: FACTION,
+                referrers: world.to_vec()
+            },
+            err
+        );
+        assert_eq!(format!("entity {FACTION} is still referenced by 2 relationships"), err.to_string());
+    }
+
+    #[test]
+    fn should_cascade_to_targets_and_nullify_referrers() {
+        let world = [relationship(FACTION, CLOCK, "clock"), relationship(CREW, FACTION, "rival")];
+        let policy = ReferencePolicy::new(OnDelete::Nullify).with("clock", OnDelete::Cascade);
+
+        let deletion = delete(FACTION, &world, &policy).expect("should have deleted faction");
+
+        assert_eq!(vec![FACTION, CLOCK], deletion.entities);
+        assert_eq!(world.to_vec(), deletion.relationships);
+    }
+
+    #[test]
+    fn should_not_cascade_to_sources() {
+        let world = [relationship(FACTION, CLOCK, "clock")];
+        let policy = ReferencePolicy::new(OnDelete::Cascade);
+
+        let deletion = delete(CLOCK, &world, &policy).expect("should have deleted clock");
+
+        assert_eq!(vec![CLOCK], deletion.entities);
+        assert_eq!(world.to_vec(), deletion.relationships);
+    }
+
+    #[test]
+    fn should_allow_blocked_relationships_between_deleted_entities() {
+        let world = [relationship(FACTION, CLOCK, "clock"), relationship(CLOCK, FACTION, "owner")];
+        let policy = ReferencePolicy::default().with("clock", OnDelete::Cascade);
+
+        let deletion = delete(FACTION, &world, &policy).expect("should have deleted faction");
+
+        assert_eq!(vec![FACTION, CLOCK], deletion.entities);
+        assert_eq!(world.to_vec(), deletion.relationships);
+    }
+}
@@ -0,0 +1,376 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! Reflection of the database layout.
+//!
+//! A [`Registry`] declares the tables of a store along with the kinds of components saved in it, and gives the
+//! [`Schema`] they are expected to have. Stores implementing [`Reflect`] read the schema of their database back, so the
+//! two can be compared with [`Schema::missing`], e.g. when verifying a store.
+//!
+//! The registry also holds the [`UPGRADES`](Persisted::UPGRADES) of each kind of component, so that stores implementing
+//! [`UpgradeComponents`] rewrite the components they hold as the types saved under each kind change shape, apart from
+//! the migrations of their tables.
+
+use std::{
+    any,
+    collections::BTreeMap,
+    fmt::{self, Display, Formatter},
+};
+
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::{Persisted, export::Upgrade};
+
+/// The type of the values held by a column.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ColumnType {
+    /// Raw bytes, e.g. UUIDs.
+    Blob,
+    /// Whole numbers.
+    Integer,
+    /// Text, e.g. JSON payloads.
+    Text,
+    /// Any other type, as declared by the database.
+    Other(String),
+}
+
+/// A column of a table.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Column {
+    /// The name of the column.
+    pub name: String,
+    /// The type of its values.
+    pub ty: ColumnType,
+    /// Whether it may hold null.
+    pub nullable: bool,
+    /// Whether it is part of the primary key of the table.
+    pub key: bool,
+}
+
+/// A table along with its columns, in the order they are declared.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Table {
+    /// The name of the table.
+    pub name: String,
+    /// The columns of the table.
+    pub columns: Vec<Column>,
+}
+
+/// The layout of a database: its tables and the kinds of components saved in them, along with the type saved under each
+/// kind when known.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Schema {
+    /// The tables, ordered by name.
+    pub tables: Vec<Table>,
+    /// The kinds of components, with the name of the type saved under each when known.
+    pub kinds: BTreeMap<String, Option<String>>,
+}
+
+/// A table or column expected by a schema but missing from another, see [`Schema::missing`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Missing {
+    /// The table missing, or holding the column missing.
+    pub table: String,
+    /// The column missing or of another type, if not the whole table.
+    pub column: Option<String>,
+}
+
+/// Declares the tables of a store and the types of components saved in them.
+#[derive(Clone, Debug, Default)]
+pub struct Registry {
+    tables: BTreeMap<String, Table>,
+    kinds: BTreeMap<String, String>,
+    upgrades: BTreeMap<String, &'static [Upgrade]>,
+}
+
+/// Trait for stores able to read the schema of their database back.
+pub trait Reflect: super::Store {
+    /// Returns the tables of the database along with the kinds of components it holds.
+    fn schema(&mut self) -> impl Future<Output = Self::Result<Schema>>;
+}
+
+/// Error type for components that cannot be upgraded to the current shape of their type.
+#[derive(Error, Debug)]
+pub enum UpgradeError {
+    /// The store holds components written with a schema version this version of the crate cannot read, e.g. by a newer
+    /// version of the game.
+    #[error("unsupported {kind} component schema version {found}, only versions 1 to {latest} are supported")]
+    UnsupportedVersion {
+        /// The kind of the components.
+        kind: String,
+        /// The schema version of the components.
+        found: u32,
+        /// The latest schema version supported.
+        latest: u32,
+    },
+    /// An upgrade from an older schema version failed.
+    #[error("failed to upgrade {kind} component of entity {entity} from schema version {from}: {reason}")]
+    Failed {
+        /// The kind of the component.
+        kind: String,
+        /// The entity of the component.
+        entity: Uuid,
+        /// The schema version upgraded from.
+        from: u32,
+        /// Why the upgrade failed.
+        reason: String,
+    },
+}
+
+/// The components of a kind rewritten by [`UpgradeComponents::upgrade_components`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Upgraded {
+    /// The kind of the components.
+    pub kind: String,
+    /// The schema version they were at.
+    pub from: u32,
+    /// The schema version they are at now.
+    pub to: u32,
+    /// The number of components rewritten.
+    pub components: u64,
+}
+
+/// Trait for stores able to upgrade the components they hold as the types saved under each kind change shape.
+pub trait UpgradeComponents: super::Store {
+    /// Rewrites the components of each kind registered that were written with an older schema version than that of the
+    /// registered type, applying its [`UPGRADES`](Persisted::UPGRADES) in turn, then records the version each kind is
+    /// at. Components of kinds never recorded are taken to be at version 1. Either every kind is upgraded or none is.
+    /// Returns the kinds upgraded.
+    fn upgrade_components(&mut self, registry: &Registry) -> impl Future<Output = Self::Result<Vec<Upgraded>>>;
+}
+
+impl ColumnType {
+    /// Returns the type of a column as declared in SQL, e.g. `BLOB`.
+    #[must_use]
+    pub fn parse(declared: &str) -> ColumnType {
+        match declared.to_ascii_uppercase().as_str() {
+            "BLOB" => ColumnType::Blob,
+            "INTEGER" => ColumnType::Integer,
+            "TEXT" => ColumnType::Text,
+            _ => ColumnType::Other(declared.into()),
+        }
+    }
+}
+
+impl Table {
+    /// Starts declaring a table without columns.
+    #[must_use]
+    pub fn new(name: impl Into<String>) -> Table {
+        Table {
+            name: name.into(),
+            columns: Vec::new(),
+        }
+    }
+
+    /// Declares a column that may not hold null.
+    #[must_use]
+    pub fn column(mut self, name: impl Into<String>, ty: ColumnType) -> Table {
+        self.columns.push(Column {
+            name: name.into(),
+            ty,
+            nullable: false,
+            key: false,
+        });
+        self
+    }
+
+    /// Declares a column that may hold null.
+    #[must_use]
+    pub fn nullable(mut self, name: impl Into<String>, ty: ColumnType) -> Table {
+        self.columns.push(Column {
+            name: name.into(),
+            ty,
+            nullable: true,
+            key: false,
+        });
+        self
+    }
+
+    /// Declares a column that is part of the primary key.
+    #[must_use]
+    pub fn key(mut self, name: impl Into<String>, ty: ColumnType) -> Table {
+        self.columns.push(Column {
+            name: name.into(),
+            ty,
+            nullable: false,
+            key: true,
+        });
+        self
+    }
+
+    /// Returns the column with the given name, if any.
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&Column> {
+        self.columns.iter().find(|column| column.name == name)
+    }
+}
+
+impl Schema {
+    /// Returns the table with the given name, if any.
+    #[must_use]
+    pub fn table(&self, name: &str) -> Option<&Table> {
+        self.tables.iter().find(|table| table.name == name)
+    }
+
+    /// Returns the tables and columns of this schema that `actual` lacks or holds with another type. Tables and columns
+    /// that only `actual` has, and kinds of components, are not compared.
+    #[must_use]
+    pub fn missing(&self, actual: &Schema) -> Vec<Missing> {
+        let mut missing = Vec::new();
+        for table in &self.tables {
+            let Some(found) = actual.table(&table.name) else {
+                missing.push(Missing {
+                    table: table.name.clone(),
+                    column: None,
+                });
+                continue;
+            };
+
+            missing.extend(
+                table
+                    .columns
+                    .iter()
+                    .filter(|column| found.get(&column.name).is_none_or(|found| found.ty != column.ty))
+                    .map(|column| Missing {
+                        table: table.name.clone(),
+                        column: Some(column.name.clone()),
+                    }),
+            );
+        }
+
+        missing
+    }
+}
+
+impl Registry {
+    /// Returns an empty registry.
+    #[must_use]
+    pub fn new() -> Registry {
+        Registry::default()
+    }
+
+    /// Registers a table, replacing any table of the same name.
+    #[must_use]
+    pub fn table(mut self, table: Table) -> Registry {
+        self.tables.insert(table.name.clone(), table);
+        self
+    }
+
+    /// Registers the type of components saved under the kind `C::KIND`.
+    #[must_use]
+    pub fn register<C: Persisted>(mut self) -> Registry {
+        self.kinds.insert(C::KIND.into(), any::type_name::<C>().into());
+        self.upgrades.insert(C::KIND.into(), C::UPGRADES);
+        self
+    }
+
+    /// Returns the upgrades of each registered kind, see [`Persisted::UPGRADES`], ordered by kind. The current schema
+    /// version of a kind is one more than its number of upgrades.
+    pub fn upgrades(&self) -> impl Iterator<Item = (&str, &'static [Upgrade])> {
+        self.upgrades.iter().map(|(kind, upgrades)| (kind.as_str(), *upgrades))
+    }
+
+    /// Returns the schema declared by the registered tables and types.
+    #[must_use]
+    pub fn schema(&self) -> Schema {
+        Schema {
+            tables: self.tables.values().cloned().collect(),
+            kinds: self.kinds.iter().map(|(kind, ty)| (kind.clone(), Some(ty.clone()))).collect(),
+        }
+    }
+}
+
+impl Display for ColumnType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ColumnType::Blob => write!(f, "BLOB"),
+            ColumnType::Integer => write!(f, "INTEGER"),
+            ColumnType::Text => write!(f, "TEXT"),
+            ColumnType::Other(declared) => write!(f, "{declared}"),
+        }
+    }
+}
+
+impl Display for Missing {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match &self.column {
+            Some(column) => write!(f, "column {column} of table {}", self.table),
+            None => write!(f, "table {}", self.table),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::Component;
+
+    #[derive(Serialize, Deserialize)]
+    struct Stress(u8);
+
+    impl Component for Stress {}
+    impl Persisted for Stress {
+        const KIND: &'static str = "stress";
+    }
+
+    fn entities() -> Table {
+        Table::new("entities")
+            .key("id", ColumnType::Blob)
+            .column("created_at", ColumnType::Integer)
+    }
+
+    #[test]
+    fn should_declare_registered_tables_and_kinds() {
+        let schema = Registry::new()
+            .table(Table::new("journal").key("seq", ColumnType::Integer))
+            .table(entities())
+            .register::<Stress>()
+            .schema();
+
+        assert_eq!(
+            vec!["entities", "journal"],
+            schema.tables.iter().map(|table| table.name.as_str()).collect::<Vec<_>>()
+        );
+        assert!(schema.table("entities").and_then(|table| table.get("id")).is_some_and(|id| id.key));
+        assert_eq!(Some(&Some(any::type_name::<Stress>().to_string())), schema.kinds.get("stress"));
+    }
+
+    #[rstest]
+    #[case::same(entities(), vec![])]
+    #[case::extra_column(entities().nullable("campaign_id", ColumnType::Blob), vec![])]
+    #[case::missing_column(Table::new("entities").key("id", ColumnType::Blob), vec![Some("created_at")])]
+    #[case::other_type(
+        Table::new("entities").key("id", ColumnType::Text).column("created_at", ColumnType::Integer),
+        vec![Some("id")]
+    )]
+    #[case::missing_table(Table::new("components"), vec![None])]
+    fn should_list_what_actual_schema_lacks(#[case] actual: Table, #[case] expect: Vec<Option<&str>>) {
+        let declared = Registry::new().table(entities()).schema();
+        let actual = Schema {
+            tables: vec![actual],
+            ..Schema::default()
+        };
+
+        assert_eq!(
+            expect,
+            declared
+                .missing(&actual)
+                .iter()
+                .map(|missing| missing.column.as_deref())
+                .collect::<Vec<_>>()
+        );
+    }
+}
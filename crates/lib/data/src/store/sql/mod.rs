@@ -14,6 +14,7 @@
 use thiserror::Error;
 use uuid::Uuid;
 
+/// Module for the `SQlite` store backend.
 pub mod sqlite;
 
 #[macro_export]
@@ -137,6 +138,12 @@ impl From<&str> for Param {
     }
 }
 
+impl<T: Into<Param>> From<Option<T>> for Param {
+    fn from(value: Option<T>) -> Param {
+        value.map_or(Param::Null, Into::into)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use rstest::rstest;
@@ -165,6 +172,8 @@ mod tests {
     #[case::string_param(sql!("SELECT * FROM test WHERE name = ?", "John Doe".to_string()), SqlQuery{query: "SELECT * FROM test WHERE name = ?".into(), params: Params::Positional(vec![Param::String("John Doe".into())])})]
     #[case::str_param(sql!("SELECT * FROM test WHERE name = ?", "John Doe"), SqlQuery{query: "SELECT * FROM test WHERE name = ?".into(), params: Params::Positional(vec![Param::String("John Doe".into())])})]
     #[case::uuid_param(sql!("SELECT * FROM test WHERE uuid = ?", ID), SqlQuery{query: "SELECT * FROM test WHERE uuid = ?".into(), params: Params::Positional(vec![Param::Uuid(ID)])})]
+    #[case::some_param(sql!("SELECT * FROM test WHERE uuid = ?", Some(ID)), SqlQuery{query: "SELECT * FROM test WHERE uuid = ?".into(), params: Params::Positional(vec![Param::Uuid(ID)])})]
+    #[case::none_param(sql!("SELECT * FROM test WHERE uuid = ?", None::<Uuid>), SqlQuery{query: "SELECT * FROM test WHERE uuid = ?".into(), params: Params::Positional(vec![Param::Null])})]
     fn should_create_query_with_indexed_params(#[case] query: SqlQuery, #[case] expect: SqlQuery) {
         assert_eq!(expect, query);
     }
@@ -0,0 +1,157 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+use uuid::Uuid;
+
+use crate::{
+    sql,
+    store::{
+        Aggregate, Bucket, Sample, Tally,
+        sql::{
+            SqlQuery,
+            sqlite::{Result, SqliteStore, cache::Table},
+        },
+    },
+};
+
+/// Reads the number at a JSON path of the journal entries of some kinds in a campaign.
+const SERIES: &str = "
+    SELECT seq, entity_id, recorded_at, json_extract(data, ?2)
+    FROM journal
+    WHERE campaign_id = ?1 AND kind IN (SELECT value FROM json_each(?3)) AND json_extract(data, ?2) IS NOT NULL
+    ORDER BY seq;
+";
+
+/// Tallies the journal entries of a kind in a campaign between consecutive markers. Each entry counts for one, or for
+/// whether its value at a JSON path is true, or for the increase of that value since the previous entry about the same
+/// entity when gaining.
+const TALLY: &str = "
+    WITH markers AS (
+        SELECT seq FROM journal WHERE campaign_id = ?1 AND kind = ?2
+    ), readings AS (
+        SELECT seq, json_extract(data, ?4) AS value,
+            LAG(json_extract(data, ?4), 1, 0) OVER (PARTITION BY entity_key ORDER BY seq) AS previous
+        FROM journal
+        WHERE campaign_id = ?1 AND kind = ?3
+    ), entries AS (
+        SELECT (SELECT MAX(m.seq) FROM markers m WHERE m.seq < r.seq) AS marker,
+            CASE WHEN ?5 THEN MAX(r.value - r.previous, 0) WHEN ?4 IS NULL OR r.value THEN 1 ELSE 0 END AS value
+        FROM readings r
+    )
+    SELECT marker, SUM(value) FROM entries WHERE marker IS NULL GROUP BY marker
+    UNION ALL
+    SELECT m.seq, COALESCE(SUM(e.value), 0) FROM markers m LEFT JOIN entries e ON e.marker = m.seq GROUP BY m.seq
+    ORDER BY 1;
+";
+
+impl Aggregate for SqliteStore {
+    async fn series(&mut self, kinds: &[&str], path: &str) -> Result<Vec<Sample>> {
+        let kinds = serde_json::to_string(kinds)?;
+        self.fetch_cached(&[Table::Journal], &sql!(SERIES, self.scope, path, kinds), |row| {
+            Ok(Sample {
+                seq: row.get(0)?,
+                entity: row.get::<Option<Vec<u8>>>(1)?.map(|bytes| Uuid::from_slice(&bytes)).transpose()?,
+                recorded_at: row.get(2)?,
+                value: row.get(3)?,
+            })
+        })
+        .await
+    }
+
+    async fn tally(&mut self, marker: &str, kind: &str, tally: Tally<'_>) -> Result<Vec<Bucket>> {
+        let (path, gain) = match tally {
+            Tally::Count => (None, false),
+            Tally::CountWhere(path) => (Some(path), false),
+            Tally::Gain(path) => (Some(path), true),
+        };
+
+        self.fetch_cached(&[Table::Journal], &sql!(TALLY, self.scope, marker, kind, path, i64::from(gain)), |row| {
+            Ok(Bucket {
+                marker: row.get(0)?,
+                value: row.get(1)?,
+            })
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::{World, sql::sqlite::fixture::memory_store};
+
+    #[tokio::test]
+    async fn should_read_series_of_numbers_from_journal() {
+        let mut store = memory_store(vec![]).await;
+        let crew = store.spawn().await.expect("should have spawned entity");
+        store
+            .record(Some(crew), "crew.started", &serde_json::json!({ "heat": 0 }))
+            .await
+            .expect("should have recorded entry");
+        store.record(None, "session.started", &()).await.expect("should have recorded entry");
+        store
+            .record(Some(crew), "heat.updated", &serde_json::json!({ "heat": 4 }))
+            .await
+            .expect("should have recorded entry");
+
+        let series = store
+            .series(&["crew.started", "heat.updated"], "$.heat")
+            .await
+            .expect("should have read series");
+
+        assert_eq!(
+            vec![(1, Some(crew), 0), (3, Some(crew), 4)],
+            series.iter().map(|sample| (sample.seq, sample.entity, sample.value)).collect::<Vec<_>>()
+        );
+    }
+
+    #[tokio::test]
+    async fn should_tally_journal_entries_between_markers() {
+        let mut store = memory_store(vec![]).await;
+        let pc = store.spawn().await.expect("should have spawned entity");
+        let npc = store.spawn().await.expect("should have spawned entity");
+        let record =
+            async |store: &mut SqliteStore, entity, kind: &str, data| store.record(entity, kind, &data).await.expect("should have recorded entry");
+
+        record(&mut store, Some(pc), "stash.updated", serde_json::json!(2)).await;
+        let first = record(&mut store, None, "session.started", serde_json::json!(null)).await;
+        record(&mut store, Some(pc), "stash.updated", serde_json::json!(1)).await;
+        record(&mut store, Some(npc), "stash.updated", serde_json::json!(3)).await;
+        record(&mut store, Some(pc), "action.resolved", serde_json::json!({ "trauma": true })).await;
+        let second = record(&mut store, None, "session.started", serde_json::json!(null)).await;
+        record(&mut store, Some(pc), "stash.updated", serde_json::json!(5)).await;
+        record(&mut store, Some(pc), "action.resolved", serde_json::json!({ "trauma": false })).await;
+        let third = record(&mut store, None, "session.started", serde_json::json!(null)).await;
+
+        let tally = async |store: &mut SqliteStore, kind: &str, tally| {
+            store
+                .tally("session.started", kind, tally)
+                .await
+                .expect("should have tallied entries")
+                .iter()
+                .map(|bucket| (bucket.marker, bucket.value))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            vec![(None, 2), (Some(first), 3), (Some(second), 4), (Some(third), 0)],
+            tally(&mut store, "stash.updated", Tally::Gain("$")).await
+        );
+        assert_eq!(
+            vec![(Some(first), 1), (Some(second), 1), (Some(third), 0)],
+            tally(&mut store, "action.resolved", Tally::Count).await
+        );
+        assert_eq!(
+            vec![(Some(first), 1), (Some(second), 0), (Some(third), 0)],
+            tally(&mut store, "action.resolved", Tally::CountWhere("$.trauma")).await
+        );
+    }
+}
@@ -0,0 +1,226 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+use std::{any::Any, collections::HashMap};
+
+use crate::store::sql::SqlQuery;
+
+/// A table read by cached queries.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum Table {
+    /// Components attached to entities.
+    Components,
+    /// Relationships between entities.
+    Relationships,
+    /// The journal of events.
+    Journal,
+    /// Content pack entries.
+    Content,
+}
+
+/// Key of a cached query: its SQL and its parameters.
+type Key = (String, String);
+
+/// The rows of a cached query along with the tables it read.
+struct Cached {
+    tables: &'static [Table],
+    rows: Box<dyn Any + Send + Sync>,
+}
+
+/// Hit and miss counts of the query cache.
+#[cfg(feature = "tracing")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// The number of queries answered from the cache.
+    pub hits: u64,
+    /// The number of queries run against the database.
+    pub misses: u64,
+}
+
+#[cfg(feature = "tracing")]
+impl CacheStats {
+    /// Returns the share of queries answered from the cache, between 0 and 1.
+    #[must_use]
+    #[expect(clippy::cast_precision_loss, reason = "the rate does not need to be exact")]
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 { 0.0 } else { self.hits as f64 / total as f64 }
+    }
+}
+
+/// Rows of repeated read queries, keyed on the query and its parameters. Writing a table through the store drops every
+/// cached query that read it.
+pub(super) struct Cache {
+    entries: HashMap<Key, Cached>,
+    capacity: usize,
+    #[cfg(feature = "tracing")]
+    stats: CacheStats,
+}
+
+impl Default for Cache {
+    fn default() -> Cache {
+        Cache {
+            entries: HashMap::new(),
+            capacity: Cache::CAPACITY,
+            #[cfg(feature = "tracing")]
+            stats: CacheStats::default(),
+        }
+    }
+}
+
+impl Cache {
+    /// The number of queries cached before the cache is emptied.
+    const CAPACITY: usize = 1024;
+
+    /// Returns a cache that never holds any rows.
+    pub(super) fn disabled() -> Cache {
+        Cache {
+            capacity: 0,
+            ..Cache::default()
+        }
+    }
+
+    /// Returns the key of the query.
+    pub(super) fn key(query: &SqlQuery) -> Key {
+        (query.query.clone(), format!("{:?}", query.params))
+    }
+
+    /// Returns the rows cached for the query, if any.
+    pub(super) fn get<T: Clone + 'static>(&mut self, key: &Key) -> Option<Vec<T>> {
+        let rows = self.entries.get(key).and_then(|cached| cached.rows.downcast_ref::<Vec<T>>()).cloned();
+
+        #[cfg(feature = "tracing")]
+        {
+            if rows.is_some() {
+                self.stats.hits += 1;
+            } else {
+                self.stats.misses += 1;
+            }
+            tracing::trace!(
+                hit = rows.is_some(),
+                hits = self.stats.hits,
+                misses = self.stats.misses,
+                hit_rate = self.stats.hit_rate(),
+                query = key.0.trim(),
+                "query cache lookup"
+            );
+        }
+
+        rows
+    }
+
+    /// Caches the rows of the query, which read the given tables.
+    pub(super) fn put<T: Send + Sync + 'static>(&mut self, key: Key, tables: &'static [Table], rows: Vec<T>) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.len() >= self.capacity {
+            self.entries.clear();
+        }
+        self.entries.insert(
+            key,
+            Cached {
+                tables,
+                rows: Box::new(rows),
+            },
+        );
+    }
+
+    /// Drops every cached query that read one of the tables.
+    pub(super) fn invalidate(&mut self, tables: &[Table]) {
+        self.entries.retain(|_, cached| !cached.tables.iter().any(|table| tables.contains(table)));
+    }
+
+    /// Drops every cached query.
+    pub(super) fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Returns the hit and miss counts of the cache.
+    #[cfg(feature = "tracing")]
+    pub(super) fn stats(&self) -> CacheStats {
+        self.stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{sql, store::sql::SqlQuery};
+
+    const COMPONENTS: &[Table] = &[Table::Components];
+    const GATHERED: &[Table] = &[Table::Components, Table::Relationships];
+
+    #[test]
+    fn should_key_queries_on_their_params() {
+        let stress = Cache::key(&sql!("SELECT data FROM components WHERE kind = ?;", "stress"));
+        let harm = Cache::key(&sql!("SELECT data FROM components WHERE kind = ?;", "harm"));
+
+        assert_ne!(stress, harm);
+        assert_eq!(stress, Cache::key(&sql!("SELECT data FROM components WHERE kind = ?;", "stress")));
+    }
+
+    #[test]
+    fn should_return_cached_rows() {
+        let mut cache = Cache::default();
+        let key = Cache::key(&sql!("SELECT data FROM components;"));
+
+        assert_eq!(None, cache.get::<String>(&key));
+        cache.put(key.clone(), COMPONENTS, vec![String::from("2")]);
+
+        assert_eq!(Some(vec![String::from("2")]), cache.get::<String>(&key));
+        assert_eq!(None, cache.get::<i64>(&key));
+    }
+
+    #[test]
+    fn should_drop_queries_reading_written_tables() {
+        let mut cache = Cache::default();
+        let components = Cache::key(&sql!("SELECT data FROM components;"));
+        let gathered = Cache::key(&sql!("SELECT data FROM components JOIN relationships;"));
+        let journal = Cache::key(&sql!("SELECT data FROM journal;"));
+        cache.put(components.clone(), COMPONENTS, vec![1]);
+        cache.put(gathered.clone(), GATHERED, vec![2]);
+        cache.put(journal.clone(), &[Table::Journal], vec![3]);
+
+        cache.invalidate(&[Table::Relationships]);
+
+        assert_eq!(Some(vec![1]), cache.get::<i32>(&components));
+        assert_eq!(None, cache.get::<i32>(&gathered));
+        assert_eq!(Some(vec![3]), cache.get::<i32>(&journal));
+    }
+
+    #[test]
+    fn should_not_cache_rows_when_disabled() {
+        let mut cache = Cache::disabled();
+        let key = Cache::key(&sql!("SELECT data FROM components;"));
+
+        cache.put(key.clone(), COMPONENTS, vec![1]);
+
+        assert_eq!(None, cache.get::<i32>(&key));
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn should_count_hits_and_misses() {
+        let mut cache = Cache::default();
+        let key = Cache::key(&sql!("SELECT data FROM components;"));
+
+        cache.get::<i32>(&key);
+        cache.put(key.clone(), COMPONENTS, vec![1]);
+        cache.get::<i32>(&key);
+        cache.get::<i32>(&key);
+        cache.get::<i32>(&key);
+
+        assert_eq!(CacheStats { hits: 3, misses: 1 }, cache.stats());
+        assert!((cache.stats().hit_rate() - 0.75).abs() < f64::EPSILON);
+    }
+}
@@ -0,0 +1,501 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! Encryption at rest of the components of sensitive kinds, e.g. the GM's notes about NPCs.
+//!
+//! Components of the kinds given to [`SqliteStoreBuilder::encrypt`](super::SqliteStoreBuilder::encrypt) are sealed with
+//! AES-256-GCM before they are written, bound to their entity and kind so a sealed component cannot be moved to another,
+//! and opened as they are read. A sealed component is stored as a JSON string, so queries and deltas over the
+//! components table still see valid JSON. Components written before the store was encrypted are read as they are, and
+//! sealed the next time they are written. Journal entries and content are not encrypted.
+//!
+//! Games asking the player for a passphrase keep a key file next to the database with [`Key::unlock`], holding the salt
+//! the key is derived with and a check telling a wrong passphrase before anything is sealed with it.
+
+use std::{
+    collections::BTreeSet,
+    fmt, fs, io,
+    num::NonZeroU32,
+    path::{Path, PathBuf},
+};
+
+use ring::{
+    aead::{AES_256_GCM, Aad, LessSafeKey, NONCE_LEN, Nonce, UnboundKey},
+    hmac, pbkdf2,
+    rand::{SecureRandom, SystemRandom},
+};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::{
+    codec::hex,
+    store::sql::sqlite::{Result, SqliteStore},
+};
+
+/// Prefix of the JSON strings holding sealed components, followed by the nonce and the ciphertext in hexadecimal.
+const SEALED: &str = "sealed:v1:";
+
+/// Message signed with a key to check it against a key file.
+const CHECK: &[u8] = b"darkforge key check";
+
+/// Number of PBKDF2 iterations deriving a key from a passphrase.
+const ITERATIONS: NonZeroU32 = NonZeroU32::new(600_000).expect("should be non-zero");
+
+/// Error type for encrypted components.
+#[derive(Error, Debug)]
+pub enum CipherError {
+    /// A sealed component was read by a store that was given no key.
+    #[error("component {kind} of entity {entity} is encrypted and the store has no key")]
+    Locked {
+        /// The entity of the component.
+        entity: Uuid,
+        /// The kind of the component.
+        kind: String,
+    },
+    /// A sealed component could not be opened, as the key is not the one it was sealed with or it was tampered with.
+    #[error("component {kind} of entity {entity} cannot be decrypted with this key")]
+    Rejected {
+        /// The entity of the component.
+        entity: Uuid,
+        /// The kind of the component.
+        kind: String,
+    },
+    /// A sealed component is not valid hexadecimal, or too short to hold a nonce.
+    #[error("component {kind} of entity {entity} is not a valid sealed component")]
+    Malformed {
+        /// The entity of the component.
+        entity: Uuid,
+        /// The kind of the component.
+        kind: String,
+    },
+    /// The system could not provide random bytes for a nonce or a salt.
+    #[error("failed to generate random bytes")]
+    Random,
+    /// The passphrase does not derive the key of the key file.
+    #[error("wrong passphrase")]
+    WrongPassphrase,
+    /// The key file could not be read or written, or is not a key file.
+    #[error("invalid key file {0}: {1}")]
+    KeyFile(PathBuf, #[source] io::Error),
+}
+
+/// A 256-bit key sealing the components of a store.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Key([u8; Key::LEN]);
+
+impl Key {
+    /// Length of a key, in bytes.
+    pub const LEN: usize = 32;
+    /// Length of the salt of [`Key::derive`], in bytes.
+    pub const SALT_LEN: usize = 16;
+
+    /// Creates a key from its bytes, e.g. as kept by the platform's keychain.
+    #[must_use]
+    pub fn new(bytes: [u8; Key::LEN]) -> Key {
+        Key(bytes)
+    }
+
+    /// Derives a key from a passphrase with PBKDF2-HMAC-SHA256. The salt must be kept along with the database, as the
+    /// same passphrase and salt always derive the same key, see [`Key::salt`].
+    #[must_use]
+    pub fn derive(passphrase: &str, salt: &[u8]) -> Key {
+        let mut bytes = [0; Key::LEN];
+        pbkdf2::derive(pbkdf2::PBKDF2_HMAC_SHA256, ITERATIONS, salt, passphrase.as_bytes(), &mut bytes);
+
+        Key(bytes)
+    }
+
+    /// Generates a random salt for [`Key::derive`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CipherError::Random`] if the system cannot provide random bytes.
+    pub fn salt() -> std::result::Result<[u8; Key::SALT_LEN], CipherError> {
+        let mut salt = [0; Key::SALT_LEN];
+        SystemRandom::new().fill(&mut salt).map_err(|_| CipherError::Random)?;
+
+        Ok(salt)
+    }
+}
+
+impl Key {
+    /// Derives the key of a database from a passphrase, with the salt of the key file at the given path, e.g. next to
+    /// the database. The key file is created with a fresh salt if it does not exist, so the first passphrase given sets
+    /// the key.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CipherError::WrongPassphrase`] if the passphrase does not derive the key the file was created with,
+    /// or [`CipherError::KeyFile`] if the file cannot be read or written.
+    pub fn unlock(passphrase: &str, path: impl AsRef<Path>) -> std::result::Result<Key, CipherError> {
+        let path = path.as_ref();
+        let invalid = |err| CipherError::KeyFile(path.into(), err);
+        match fs::read(path) {
+            Ok(file) => {
+                if file.len() != Key::SALT_LEN + hmac::HMAC_SHA256.digest_algorithm().output_len() {
+                    return Err(invalid(io::Error::new(io::ErrorKind::InvalidData, "unexpected length")));
+                }
+
+                let (salt, check) = file.split_at(Key::SALT_LEN);
+                let key = Key::derive(passphrase, salt);
+                hmac::verify(&key.check_key(), CHECK, check).map_err(|_| CipherError::WrongPassphrase)?;
+
+                Ok(key)
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                let salt = Key::salt()?;
+                let key = Key::derive(passphrase, &salt);
+                let check = hmac::sign(&key.check_key(), CHECK);
+                fs::write(path, [salt.as_slice(), check.as_ref()].concat()).map_err(invalid)?;
+
+                Ok(key)
+            }
+            Err(err) => Err(invalid(err)),
+        }
+    }
+
+    /// Returns the key signing the check of a key file.
+    fn check_key(&self) -> hmac::Key {
+        hmac::Key::new(hmac::HMAC_SHA256, &self.0)
+    }
+}
+
+impl fmt::Debug for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Key(..)")
+    }
+}
+
+/// The key of a store along with the kinds of components it seals.
+#[derive(Clone, Debug)]
+pub(super) struct Encryption {
+    pub(super) key: Key,
+    pub(super) kinds: BTreeSet<String>,
+}
+
+/// Seals and opens the components of a store.
+pub(super) struct Cipher {
+    key: LessSafeKey,
+    kinds: BTreeSet<String>,
+    random: SystemRandom,
+}
+
+impl From<Encryption> for Cipher {
+    fn from(encryption: Encryption) -> Cipher {
+        let key = UnboundKey::new(&AES_256_GCM, &encryption.key.0).expect("should be a valid AES-256 key");
+
+        Cipher {
+            key: LessSafeKey::new(key),
+            kinds: encryption.kinds,
+            random: SystemRandom::new(),
+        }
+    }
+}
+
+impl Cipher {
+    /// Seals the component data if its kind is encrypted.
+    fn seal(&self, entity: Uuid, kind: &str, data: String) -> Result<String> {
+        if !self.kinds.contains(kind) {
+            return Ok(data);
+        }
+
+        let mut nonce = [0; NONCE_LEN];
+        self.random.fill(&mut nonce).map_err(|_| CipherError::Random)?;
+        let mut sealed = data.into_bytes();
+        self.key
+            .seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), aad(entity, kind), &mut sealed)
+            .map_err(|_| CipherError::Random)?;
+
+        Ok(serde_json::to_string(&format!("{SEALED}{}{}", hex(&nonce), hex(&sealed)))?)
+    }
+
+    /// Opens the component data if it is sealed.
+    fn open(&self, entity: Uuid, kind: &str, data: String) -> Result<String> {
+        let Some(sealed) = sealed(&data)? else {
+            return Ok(data);
+        };
+
+        let malformed = || CipherError::Malformed { entity, kind: kind.into() };
+        let mut bytes = unhex(&sealed).ok_or_else(malformed)?;
+        if bytes.len() < NONCE_LEN {
+            return Err(malformed().into());
+        }
+        let mut sealed = bytes.split_off(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(&bytes).map_err(|_| malformed())?;
+        let opened = self
+            .key
+            .open_in_place(nonce, aad(entity, kind), &mut sealed)
+            .map_err(|_| CipherError::Rejected { entity, kind: kind.into() })?;
+
+        String::from_utf8(opened.to_vec()).map_err(|_| malformed().into())
+    }
+}
+
+impl SqliteStore {
+    /// Seals the data of a component before it is written, if the store encrypts components of its kind.
+    pub(super) fn seal(&self, entity: Uuid, kind: &str, data: String) -> Result<String> {
+        match &self.cipher {
+            Some(cipher) => cipher.seal(entity, kind, data),
+            None => Ok(data),
+        }
+    }
+
+    /// Opens the data of a component after it is read, if it is sealed.
+    pub(super) fn unseal(&self, entity: Uuid, kind: &str, data: String) -> Result<String> {
+        match &self.cipher {
+            Some(cipher) => cipher.open(entity, kind, data),
+            None if sealed(&data)?.is_some() => Err(CipherError::Locked { entity, kind: kind.into() }.into()),
+            None => Ok(data),
+        }
+    }
+}
+
+/// Returns the hexadecimal nonce and ciphertext of a sealed component, or `None` if the data is not sealed.
+fn sealed(data: &str) -> Result<Option<String>> {
+    if !data.starts_with(&format!("\"{SEALED}")) {
+        return Ok(None);
+    }
+
+    let sealed = serde_json::from_str::<String>(data)?;
+    Ok(sealed.strip_prefix(SEALED).map(str::to_owned))
+}
+
+/// Binds a sealed component to its entity and kind.
+fn aad(entity: Uuid, kind: &str) -> Aad<Vec<u8>> {
+    Aad::from([entity.as_bytes().as_slice(), kind.as_bytes()].concat())
+}
+
+/// Decodes hexadecimal, or returns `None` if it is not valid.
+fn unhex(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, path::PathBuf};
+
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::{
+        Component, Persisted, sql,
+        store::{
+            Migrator, World,
+            sql::{
+                SqlQuery,
+                sqlite::{MIGRATIONS, SqliteError, SqliteStoreBuilder},
+            },
+        },
+    };
+
+    const KEY: [u8; Key::LEN] = [7; Key::LEN];
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+    struct Secret {
+        note: String,
+    }
+
+    impl Component for Secret {}
+    impl Persisted for Secret {
+        const KIND: &'static str = "secret";
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Stress(u8);
+
+    impl Component for Stress {}
+    impl Persisted for Stress {
+        const KIND: &'static str = "stress";
+    }
+
+    fn secret() -> Secret {
+        Secret {
+            note: "Bazso owes the Red Sashes".into(),
+        }
+    }
+
+    /// Opens a migrated store on the database file, encrypting secrets if given a key.
+    async fn store(path: &PathBuf, key: Option<Key>) -> SqliteStore {
+        let builder = SqliteStore::builder(path);
+        let builder = match key {
+            Some(key) => builder.encrypt(key, [Secret::KIND]),
+            None => builder,
+        };
+        let store = builder.build().await.expect("should have opened store");
+        store.apply(MIGRATIONS).await.expect("should have applied migrations");
+
+        store
+    }
+
+    /// Creates an empty directory for a test database.
+    fn temp_db(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("darkforge-{name}-{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).expect("should have created directory");
+
+        dir.join("campaign.db")
+    }
+
+    /// Reads the stored data of a component, as written to the database.
+    async fn raw(store: &SqliteStore, entity: Uuid, kind: &str) -> String {
+        store
+            .fetch(
+                &sql!("SELECT data FROM components WHERE entity_id = ? AND kind = ?;", entity, kind),
+                |row| Ok(row.get::<String>(0)?),
+            )
+            .await
+            .expect("should have read component")
+            .pop()
+            .expect("should have a component")
+    }
+
+    #[tokio::test]
+    async fn should_encrypt_components_of_sensitive_kinds() {
+        let mut store = SqliteStore::builder(SqliteStoreBuilder::MEMORY)
+            .encrypt(Key::new(KEY), [Secret::KIND])
+            .build()
+            .await
+            .expect("should have opened store");
+        store.apply(MIGRATIONS).await.expect("should have applied migrations");
+        let entity = store.spawn().await.expect("should have spawned entity");
+
+        store.insert(entity, &secret()).await.expect("should have inserted secret");
+        store.insert(entity, &Stress(2)).await.expect("should have inserted stress");
+
+        let sealed = raw(&store, entity, Secret::KIND).await;
+        assert!(sealed.starts_with("\"sealed:v1:"), "unexpected data: {sealed}");
+        assert!(!sealed.contains("Bazso"), "should not hold the plaintext: {sealed}");
+        assert_eq!("2", raw(&store, entity, Stress::KIND).await);
+        assert_eq!(Some(secret()), store.get::<Secret>(entity).await.expect("should have read secret"));
+        assert_eq!(vec![(entity, secret())], store.all::<Secret>().await.expect("should have read secrets"));
+        assert_eq!(
+            vec![r#"{"note":"Bazso owes the Red Sashes"}"#.to_string()],
+            store
+                .gather(&[Secret::KIND], "parent")
+                .await
+                .expect("should have gathered secrets")
+                .into_iter()
+                .map(|gathered| gathered.data)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[tokio::test]
+    async fn should_refuse_to_read_encrypted_components_given_no_key_or_another_key() {
+        let path = temp_db("cipher");
+        let mut sealed = store(&path, Some(Key::new(KEY))).await;
+        let entity = sealed.spawn().await.expect("should have spawned entity");
+        sealed.insert(entity, &secret()).await.expect("should have inserted secret");
+        sealed.close().await.expect("should have closed store");
+
+        let locked = store(&path, None)
+            .await
+            .get::<Secret>(entity)
+            .await
+            .expect_err("should have refused to read without a key");
+        let rejected = store(&path, Some(Key::new([8; Key::LEN])))
+            .await
+            .get::<Secret>(entity)
+            .await
+            .expect_err("should have refused to read with another key");
+
+        assert!(
+            matches!(locked, SqliteError::Cipher(CipherError::Locked { .. })),
+            "unexpected error: {locked}"
+        );
+        assert!(
+            matches!(rejected, SqliteError::Cipher(CipherError::Rejected { .. })),
+            "unexpected error: {rejected}"
+        );
+        assert!(locked.is_locked() && rejected.is_locked());
+        fs::remove_dir_all(path.parent().expect("should have a directory")).expect("should have removed database");
+    }
+
+    #[tokio::test]
+    async fn should_read_components_written_before_encryption() {
+        let path = temp_db("plain");
+        let mut plain = store(&path, None).await;
+        let entity = plain.spawn().await.expect("should have spawned entity");
+        plain.insert(entity, &secret()).await.expect("should have inserted secret");
+        plain.close().await.expect("should have closed store");
+
+        let mut sealed = store(&path, Some(Key::new(KEY))).await;
+
+        assert_eq!(Some(secret()), sealed.get::<Secret>(entity).await.expect("should have read secret"));
+        sealed.insert(entity, &secret()).await.expect("should have rewritten secret");
+        assert!(raw(&sealed, entity, Secret::KIND).await.starts_with("\"sealed:v1:"));
+        drop(sealed);
+        fs::remove_dir_all(path.parent().expect("should have a directory")).expect("should have removed database");
+    }
+
+    #[tokio::test]
+    async fn should_refuse_component_moved_to_another_entity() {
+        let mut store = SqliteStore::builder(SqliteStoreBuilder::MEMORY)
+            .encrypt(Key::new(KEY), [Secret::KIND])
+            .build()
+            .await
+            .expect("should have opened store");
+        store.apply(MIGRATIONS).await.expect("should have applied migrations");
+        let entity = store.spawn().await.expect("should have spawned entity");
+        let other = store.spawn().await.expect("should have spawned entity");
+        store.insert(entity, &secret()).await.expect("should have inserted secret");
+
+        let sealed = raw(&store, entity, Secret::KIND).await;
+        store
+            .execute(&sql!(
+                "INSERT INTO components (entity_id, kind, data, revision) VALUES (?, ?, ?, 0);",
+                other,
+                Secret::KIND,
+                sealed
+            ))
+            .await
+            .expect("should have copied component");
+
+        let err = store.get::<Secret>(other).await.expect_err("should have refused the moved component");
+        assert!(
+            matches!(err, SqliteError::Cipher(CipherError::Rejected { .. })),
+            "unexpected error: {err}"
+        );
+    }
+
+    #[test]
+    fn should_unlock_key_file_given_same_passphrase() {
+        let path = temp_db("keyfile").with_extension("key");
+
+        let created = Key::unlock("in the dark", &path).expect("should have created key file");
+        let unlocked = Key::unlock("in the dark", &path).expect("should have unlocked key file");
+        let err = Key::unlock("in the light", &path).expect_err("should have refused another passphrase");
+
+        assert_eq!(created, unlocked);
+        assert!(matches!(err, CipherError::WrongPassphrase), "unexpected error: {err}");
+        assert_ne!(
+            Key::salt().expect("should have generated salt"),
+            Key::salt().expect("should have generated salt")
+        );
+        assert_eq!("Key(..)", format!("{created:?}"));
+        fs::remove_dir_all(path.parent().expect("should have a directory")).expect("should have removed key file");
+    }
+
+    #[test]
+    fn should_round_trip_hex() {
+        assert_eq!("00ff7f", hex(&[0, 255, 127]));
+        assert_eq!(Some(vec![0, 255, 127]), unhex("00ff7f"));
+        assert_eq!(None, unhex("0ff"));
+        assert_eq!(None, unhex("zz"));
+    }
+}
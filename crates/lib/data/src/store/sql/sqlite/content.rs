@@ -0,0 +1,399 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::{
+    CodecError,
+    pack::{Changed, Entry, EntryChange, Pack, PackHash, Slug},
+    sql,
+    store::{
+        Change, Content,
+        sql::{
+            Param, Params, SqlQuery,
+            sqlite::{Result, SqliteStore, cache::Table, store::IntoParams},
+        },
+    },
+};
+
+/// The number of entries written by each insert, keeping its parameters under the historical limit of `SQLite`.
+const BATCH: usize = 200;
+
+/// Records the hash of a pack as it is loaded, replacing the hash it was last loaded with.
+const HASH: &str = "INSERT OR REPLACE INTO pack_hashes (pack, hash) VALUES (?, ?);";
+
+impl Content for SqliteStore {
+    async fn import_pack(&mut self, pack: &Pack, mut progress: impl FnMut(u64, u64)) -> Result<u64> {
+        let entries = pack
+            .categories()
+            .flat_map(|category| pack.entries(category).iter().map(move |entry| (category, entry.id, &entry.data)))
+            .collect::<Vec<_>>();
+        let total = u64::try_from(entries.len()).unwrap_or(u64::MAX);
+
+        let conn = self.pool.get().await?;
+        let tx = conn.transaction().await?;
+
+        let mut loaded = 0;
+        for batch in entries.chunks(BATCH) {
+            let query = insert(pack.name(), batch)?;
+            loaded += tx.execute(query.query.as_str(), query.to_params()?).await?;
+            progress(loaded, total);
+        }
+        let query = sql!(HASH, pack.name(), serde_json::to_string(&pack.hash())?);
+        tx.execute(query.query.as_str(), query.to_params()?).await?;
+
+        tx.commit().await?;
+        self.cache.invalidate(&[Table::Content]);
+        Ok(loaded)
+    }
+
+    async fn reload_pack(
+        &mut self, pack: &Pack, record: impl FnOnce(&[EntryChange]) -> std::result::Result<Vec<Change>, CodecError> + Send,
+    ) -> Result<Vec<EntryChange>> {
+        let loaded = self.loaded(pack.name()).await?;
+        let changes = pack.diff(&loaded);
+        let recorded = record(&changes)?;
+
+        let (removed, written): (Vec<_>, Vec<_>) = changes.iter().partition(|change| change.change == Changed::Removed);
+        let written = written
+            .iter()
+            .map(|change| (change.category.as_str(), change.id, &change.data))
+            .collect::<Vec<_>>();
+
+        let conn = self.pool.get().await?;
+        let tx = conn.transaction().await?;
+        for batch in written.chunks(BATCH) {
+            let query = insert(pack.name(), batch)?;
+            tx.execute(query.query.as_str(), query.to_params()?).await?;
+        }
+        for change in removed {
+            let query = sql!(
+                "DELETE FROM content WHERE category = ? AND id = ? AND pack = ?;",
+                change.category.as_str(),
+                change.id,
+                pack.name()
+            );
+            tx.execute(query.query.as_str(), query.to_params()?).await?;
+        }
+        let query = sql!(HASH, pack.name(), serde_json::to_string(&pack.hash())?);
+        tx.execute(query.query.as_str(), query.to_params()?).await?;
+        let (_, mut written) = self.write(&tx, &recorded).await?;
+        tx.commit().await?;
+        written.push(Table::Content);
+        self.cache.invalidate(&written);
+
+        Ok(changes)
+    }
+
+    async fn entry<T: DeserializeOwned>(&mut self, category: &str, id: Uuid) -> Result<Option<T>> {
+        let mut found = self
+            .fetch_cached(
+                &[Table::Content],
+                &sql!("SELECT data FROM content WHERE category = ? AND id = ?;", category, id),
+                |row| Ok(row.get::<String>(0)?),
+            )
+            .await?;
+
+        Ok(found.pop().map(|data| serde_json::from_str(&data)).transpose()?)
+    }
+
+    async fn entries<T: DeserializeOwned>(&mut self, category: &str) -> Result<Vec<T>> {
+        let found = self
+            .fetch_cached(
+                &[Table::Content],
+                &sql!("SELECT data FROM content WHERE category = ? ORDER BY rowid;", category),
+                |row| Ok(row.get::<String>(0)?),
+            )
+            .await?;
+
+        Ok(found.iter().map(|data| serde_json::from_str(data)).collect::<serde_json::Result<_>>()?)
+    }
+
+    async fn sorted_entries<T: DeserializeOwned>(&mut self, category: &str, key: impl Fn(&T) -> &str + Send) -> Result<Vec<T>> {
+        let mut found = self.entries(category).await?;
+        self.collation.sort_by_key(&mut found, key);
+
+        Ok(found)
+    }
+
+    async fn resolve(&mut self, slug: &Slug) -> Result<Option<Uuid>> {
+        let found = self
+            .fetch_cached(
+                &[Table::Content],
+                &sql!(
+                    "SELECT id, data FROM content WHERE pack = ? AND category = ?;",
+                    slug.pack.as_str(),
+                    slug.category.as_str()
+                ),
+                |row| Ok((row.get::<Vec<u8>>(0)?, row.get::<String>(1)?)),
+            )
+            .await?;
+
+        for (id, data) in found {
+            let entry = Entry {
+                id: Uuid::from_slice(&id)?,
+                data: serde_json::from_str(&data)?,
+            };
+            if entry.slug() == slug.slug {
+                return Ok(Some(entry.id));
+            }
+        }
+
+        Ok(None)
+    }
+
+    async fn pack_hashes(&mut self) -> Result<Vec<PackHash>> {
+        let hashes = self
+            .fetch(&sql!("SELECT hash FROM pack_hashes ORDER BY pack;"), |row| Ok(row.get::<String>(0)?))
+            .await?;
+
+        Ok(hashes.iter().map(|hash| serde_json::from_str(hash)).collect::<serde_json::Result<_>>()?)
+    }
+
+    async fn hash_content(&mut self, pack: &str) -> Result<PackHash> {
+        let loaded = self.loaded(pack).await?;
+
+        Ok(PackHash::of(pack, loaded.iter().map(|(category, entry)| (category.as_str(), entry))))
+    }
+}
+
+impl SqliteStore {
+    /// Reads the entries held for the pack of the given name, each along with its category.
+    async fn loaded(&self, pack: &str) -> Result<Vec<(String, Entry)>> {
+        self.fetch(&sql!("SELECT category, id, data FROM content WHERE pack = ?;", pack), |row| {
+            let entry = Entry {
+                id: Uuid::from_slice(&row.get::<Vec<u8>>(1)?)?,
+                data: serde_json::from_str(&row.get::<String>(2)?)?,
+            };
+            Ok((row.get::<String>(0)?, entry))
+        })
+        .await
+    }
+}
+
+/// Builds a single insert of the entries of the pack, each given its category, id and data, replacing entries with the
+/// same category and id.
+fn insert(pack: &str, entries: &[(&str, Uuid, &Value)]) -> Result<SqlQuery> {
+    let mut params = Vec::with_capacity(entries.len() * 4);
+    for &(category, id, data) in entries {
+        params.extend([
+            Param::from(category),
+            Param::from(id),
+            Param::from(pack),
+            Param::from(serde_json::to_string(data)?),
+        ]);
+    }
+    let rows = vec!["(?, ?, ?, ?)"; entries.len()].join(", ");
+
+    Ok(SqlQuery::new(
+        format!("INSERT OR REPLACE INTO content (category, id, pack, data) VALUES {rows};"),
+        Params::Positional(params),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+
+    use serde::Deserialize;
+    use uuid::uuid;
+
+    use super::*;
+    use crate::store::{World, sql::sqlite::fixture::memory_store};
+
+    const BLUECOATS: Uuid = uuid!("7ef1bdbe-84de-4699-b1bd-be84de569995");
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Named {
+        name: String,
+    }
+
+    #[tokio::test]
+    async fn should_load_pack_entries() {
+        let dir = env::temp_dir().join(format!("darkforge-content-{}", std::process::id()));
+        fs::create_dir_all(&dir).expect("should have created pack directory");
+        fs::write(
+            dir.join("faction.json"),
+            format!(
+                r#"[{{ "id": "{BLUECOATS}", "name": "Bluecoats" }}, {{ "id": "{}", "name": "Crows" }}]"#,
+                Uuid::new_v4()
+            ),
+        )
+        .expect("should have written pack file");
+        let pack = Pack::open(&dir).expect("should have opened pack");
+        let mut store = memory_store(vec![]).await;
+
+        let loaded = store.load_pack(&pack).await.expect("should have loaded pack");
+
+        assert_eq!(2, loaded);
+        assert_eq!(
+            Some(Named { name: "Bluecoats".into() }),
+            store.entry("faction", BLUECOATS).await.expect("should have read entry")
+        );
+        assert_eq!(2, store.entries::<Named>("faction").await.expect("should have read entries").len());
+        assert_eq!(
+            Vec::<Named>::new(),
+            store.entries::<Named>("npc").await.expect("should have read entries")
+        );
+    }
+
+    #[tokio::test]
+    async fn should_import_pack_entries_in_batches() {
+        let factions = (0..450)
+            .map(|n| format!(r#"{{ "id": "{}", "name": "Faction {n}" }}"#, Uuid::new_v4()))
+            .collect::<Vec<_>>();
+        let pack = Pack::parse("defaults", [("faction", format!("[{}]", factions.join(", ")))]).expect("should have parsed pack");
+        let mut store = memory_store(vec![]).await;
+
+        let mut reported = Vec::new();
+        let loaded = store
+            .import_pack(&pack, |loaded, total| reported.push((loaded, total)))
+            .await
+            .expect("should have imported pack");
+
+        assert_eq!(450, loaded);
+        assert_eq!(vec![(200, 450), (400, 450), (450, 450)], reported);
+        assert_eq!(450, store.entries::<Named>("faction").await.expect("should have read entries").len());
+    }
+
+    #[tokio::test]
+    async fn should_resolve_slug_given_pack_reimported_with_new_ids() {
+        let mut store = memory_store(vec![]).await;
+        let load = |id: Uuid| {
+            Pack::parse(
+                "defaults",
+                [(
+                    "faction",
+                    format!(r#"[{{ "id": "{id}", "descriptor": {{ "label": "The Bluecoats" }} }}]"#),
+                )],
+            )
+            .expect("should have parsed pack")
+        };
+        let slug = Slug::new("defaults", "faction", "the-bluecoats");
+
+        store.load_pack(&load(BLUECOATS)).await.expect("should have loaded pack");
+        assert_eq!(Some(BLUECOATS), store.resolve(&slug).await.expect("should have resolved slug"));
+
+        let reimported = Uuid::new_v4();
+        store
+            .reload_pack(&load(reimported), |_| Ok(Vec::new()))
+            .await
+            .expect("should have reloaded pack");
+        assert_eq!(Some(reimported), store.resolve(&slug).await.expect("should have resolved slug"));
+        assert_eq!(
+            None,
+            store
+                .resolve(&Slug::new("other", "faction", "the-bluecoats"))
+                .await
+                .expect("should have resolved slug")
+        );
+    }
+
+    #[tokio::test]
+    async fn should_reload_changed_pack_entries() {
+        let crows = Uuid::new_v4();
+        let dir = env::temp_dir().join(format!("darkforge-reload-{}", std::process::id()));
+        fs::create_dir_all(&dir).expect("should have created pack directory");
+        let write = |factions: &str| fs::write(dir.join("faction.json"), factions).expect("should have written pack file");
+        let mut store = memory_store(vec![]).await;
+
+        write(&format!(
+            r#"[{{ "id": "{BLUECOATS}", "name": "Bluecoats" }}, {{ "id": "{crows}", "name": "Crows" }}]"#
+        ));
+        store
+            .load_pack(&Pack::open(&dir).expect("should have opened pack"))
+            .await
+            .expect("should have loaded pack");
+
+        write(&format!(r#"[{{ "id": "{BLUECOATS}", "name": "The Bluecoats" }}]"#));
+        let changes = store
+            .reload_pack(&Pack::open(&dir).expect("should have opened pack"), |_| Ok(Vec::new()))
+            .await
+            .expect("should have reloaded pack");
+
+        let mut expected = vec![(BLUECOATS, Changed::Updated), (crows, Changed::Removed)];
+        expected.sort();
+        assert_eq!(expected, changes.iter().map(|change| (change.id, change.change)).collect::<Vec<_>>());
+        assert_eq!(
+            vec![Named {
+                name: "The Bluecoats".into()
+            }],
+            store.entries::<Named>("faction").await.expect("should have read entries")
+        );
+
+        let unchanged = store
+            .reload_pack(&Pack::open(&dir).expect("should have opened pack"), |_| Ok(Vec::new()))
+            .await
+            .expect("should have reloaded pack");
+        assert_eq!(Vec::<EntryChange>::new(), unchanged);
+    }
+
+    #[tokio::test]
+    async fn should_reload_one_of_two_packs_recording_its_changes() {
+        let crows = Uuid::new_v4();
+        let defaults = |factions: String| Pack::parse("defaults", [("faction", factions)]).expect("should have parsed pack");
+        let mods = Pack::parse("mods", [("faction", format!(r#"[{{ "id": "{crows}", "name": "Crows" }}]"#))]).expect("should have parsed pack");
+        let mut store = memory_store(vec![]).await;
+        store
+            .load_pack(&defaults(format!(r#"[{{ "id": "{BLUECOATS}", "name": "Bluecoats" }}]"#)))
+            .await
+            .expect("should have loaded pack");
+        store.load_pack(&mods).await.expect("should have loaded pack");
+
+        let changes = store
+            .reload_pack(&defaults("[]".into()), |changes| {
+                changes.iter().map(|change| Change::record(None, "content.changed", &change.id)).collect()
+            })
+            .await
+            .expect("should have reloaded pack");
+
+        assert_eq!(
+            vec![(BLUECOATS, Changed::Removed)],
+            changes.iter().map(|change| (change.id, change.change)).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![Named { name: "Crows".into() }],
+            store.entries::<Named>("faction").await.expect("should have read entries")
+        );
+        let recorded = store.journal(None, "content.changed", u32::MAX).await.expect("should have read journal");
+        assert_eq!(1, recorded.len());
+    }
+
+    #[tokio::test]
+    async fn should_hash_pack_as_loaded_and_as_it_stands() {
+        let pack =
+            Pack::parse("defaults", [("faction", format!(r#"[{{ "id": "{BLUECOATS}", "name": "Bluecoats" }}]"#))]).expect("should have parsed pack");
+        let mut store = memory_store(vec![]).await;
+
+        store.load_pack(&pack).await.expect("should have loaded pack");
+
+        assert_eq!(vec![pack.hash()], store.pack_hashes().await.expect("should have read pack hashes"));
+        assert_eq!(pack.hash(), store.hash_content("defaults").await.expect("should have hashed content"));
+
+        let query = sql!(
+            "UPDATE content SET data = ? WHERE id = ?;",
+            r#"{ "id": "7ef1bdbe-84de-4699-b1bd-be84de569995", "name": "Crows" }"#,
+            BLUECOATS
+        );
+        let conn = store.pool.get().await.expect("should have got connection");
+        conn.execute(query.query.as_str(), query.to_params().expect("should have bound params"))
+            .await
+            .expect("should have tampered with content");
+        drop(conn);
+
+        assert_eq!(vec![pack.hash()], store.pack_hashes().await.expect("should have read pack hashes"));
+        let tampered = store.hash_content("defaults").await.expect("should have hashed content");
+        assert_eq!(vec![String::from("faction")], pack.hash().differing(&tampered));
+    }
+}
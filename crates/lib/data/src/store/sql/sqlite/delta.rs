@@ -0,0 +1,321 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+
+use crate::{
+    sql,
+    store::{
+        Replicate,
+        delta::{ChangedComponent, ChangedRelationship, Delta, PrunedJournal},
+        sql::{
+            SqlQuery,
+            sqlite::{
+                Result, SqliteError, SqliteStore,
+                store::IntoParams,
+                uuid,
+                world::{DESPAWNED, INSERT_COMPONENT, PRUNE, PRUNED, PRUNES, RELATE, STRIP, UNLINK, recorded},
+            },
+        },
+    },
+};
+
+/// Reads the entities of a campaign written after a journal entry.
+const ENTITIES: &str = "SELECT id FROM entities WHERE campaign_id = ?1 AND revision > ?2 ORDER BY rowid;";
+
+/// Reads the components of the entities of a campaign written after a journal entry.
+const COMPONENTS: &str = "
+    SELECT c.entity_id, c.kind, c.data
+    FROM components c
+    JOIN entities e ON e.id = c.entity_id
+    WHERE e.campaign_id = ?1 AND c.revision > ?2
+    ORDER BY c.rowid;
+";
+
+/// Reads the relationships from the entities of a campaign written after a journal entry.
+const RELATIONSHIPS: &str = "
+    SELECT r.source_id, r.target_id, r.kind
+    FROM relationships r
+    JOIN entities e ON e.id = r.source_id
+    WHERE e.campaign_id = ?1 AND r.revision > ?2
+    ORDER BY r.rowid;
+";
+
+/// Reads the journal entries of a campaign recorded after an entry.
+const JOURNAL: &str = "
+    SELECT seq, entity_id, kind, data, recorded_at
+    FROM journal
+    WHERE campaign_id = ?1 AND seq > ?2
+    ORDER BY seq;
+";
+
+/// Reads the entities of a campaign despawned after a journal entry.
+const DESPAWNED_AFTER: &str = "
+    SELECT d.entity_id
+    FROM despawned d
+    JOIN entities e ON e.id = d.entity_id
+    WHERE e.campaign_id = ?1 AND d.revision > ?2
+    ORDER BY d.rowid;
+";
+
+/// Reads the kinds of journal entries of a campaign pruned after a journal entry.
+const PRUNED_AFTER: &str = "SELECT kind, before FROM prunes WHERE campaign_id = ?1 AND revision > ?2 ORDER BY rowid;";
+
+/// Reads the journal entries of every campaign recorded after an entry, to tell whether a delta can be applied.
+const RECORDED_AFTER: &str = "SELECT seq, kind, data, campaign_id FROM journal WHERE seq > ? ORDER BY seq;";
+
+/// Creates an entity of a delta, unless it exists already.
+const APPLY_ENTITY: &str = "
+    INSERT INTO entities (id, campaign_id, revision) VALUES (?, ?, (SELECT COALESCE(MAX(seq), 0) + 1 FROM journal))
+    ON CONFLICT (id) DO NOTHING;
+";
+
+/// Appends a journal entry of a delta under its own sequence number, unless it was applied already.
+const APPLY_ENTRY: &str = "
+    INSERT INTO journal (seq, entity_id, kind, data, recorded_at, campaign_id) VALUES (?, ?, ?, ?, ?, ?)
+    ON CONFLICT (seq) DO NOTHING;
+";
+
+impl Replicate for SqliteStore {
+    /// Reads the rows of the campaign the store is scoped to.
+    async fn delta(&mut self, since: i64) -> Result<Delta> {
+        let entities = self.fetch(&sql!(ENTITIES, self.scope, since), |row| uuid(row, 0)).await?;
+        let components = self
+            .fetch(&sql!(COMPONENTS, self.scope, since), |row| {
+                Ok(ChangedComponent {
+                    entity: uuid(row, 0)?,
+                    kind: row.get(1)?,
+                    data: row.get(2)?,
+                })
+            })
+            .await?;
+        let relationships = self
+            .fetch(&sql!(RELATIONSHIPS, self.scope, since), |row| {
+                Ok(ChangedRelationship {
+                    source: uuid(row, 0)?,
+                    target: uuid(row, 1)?,
+                    kind: row.get(2)?,
+                })
+            })
+            .await?;
+        let journal = self.fetch(&sql!(JOURNAL, self.scope, since), recorded).await?;
+        let despawned = self.fetch(&sql!(DESPAWNED_AFTER, self.scope, since), |row| uuid(row, 0)).await?;
+        let pruned = self
+            .fetch(&sql!(PRUNED_AFTER, self.scope, since), |row| {
+                Ok(PrunedJournal {
+                    kind: row.get(0)?,
+                    before: row.get(1)?,
+                })
+            })
+            .await?;
+
+        Ok(Delta {
+            campaign: self.scope,
+            since,
+            until: journal.last().map_or(since, |entry| entry.seq),
+            entities,
+            components,
+            relationships,
+            journal,
+            despawned,
+            pruned,
+        })
+    }
+
+    /// Removals are applied before the rows written, which may have been written again after the removal. Fails with
+    /// [`SqliteError::Diverged`] if the store recorded an entry after the checkpoint of the delta that the delta does
+    /// not hold, in which case nothing is applied.
+    async fn apply_delta(&mut self, delta: &Delta) -> Result<u64> {
+        let local = self
+            .fetch(&sql!(RECORDED_AFTER, delta.since), |row| {
+                Ok((row.get::<i64>(0)?, row.get::<String>(1)?, row.get::<String>(2)?, uuid(row, 3)?))
+            })
+            .await?;
+        for (seq, kind, data, campaign) in local {
+            let held = delta
+                .journal
+                .iter()
+                .any(|entry| entry.seq == seq && entry.kind == kind && entry.data == data);
+            if !held || campaign != delta.campaign {
+                return Err(SqliteError::Diverged(seq));
+            }
+        }
+
+        let mut queries = Vec::new();
+        queries.extend(delta.entities.iter().map(|entity| sql!(APPLY_ENTITY, *entity, delta.campaign)));
+        queries.extend(delta.journal.iter().map(|entry| {
+            sql!(
+                APPLY_ENTRY,
+                entry.seq,
+                entry.entity,
+                entry.kind.as_str(),
+                entry.data.as_str(),
+                entry.recorded_at,
+                delta.campaign
+            )
+        }));
+        for entity in &delta.despawned {
+            queries.extend([sql!(UNLINK, *entity), sql!(STRIP, *entity), sql!(DESPAWNED, *entity)]);
+        }
+        for pruned in &delta.pruned {
+            queries.extend([
+                sql!(PRUNES, delta.campaign, pruned.kind.as_str(), pruned.before),
+                sql!(PRUNE, pruned.kind.as_str(), pruned.before, delta.campaign),
+                sql!(PRUNED, pruned.kind.as_str(), pruned.before),
+            ]);
+        }
+        queries.extend(
+            delta
+                .components
+                .iter()
+                .map(|component| sql!(INSERT_COMPONENT, component.entity, component.kind.as_str(), component.data.as_str())),
+        );
+        queries.extend(
+            delta
+                .relationships
+                .iter()
+                .map(|relationship| sql!(RELATE, relationship.source, relationship.target, relationship.kind.as_str())),
+        );
+
+        let conn = self.pool.get().await?;
+        let tx = conn.transaction().await?;
+        let mut written = 0;
+        for query in &queries {
+            written += tx.execute(query.query.as_str(), query.to_params()?).await?;
+        }
+        tx.commit().await?;
+        self.cache.clear();
+
+        Ok(written)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::{
+        Component, Persisted,
+        store::{Change, World, sql::sqlite::fixture::memory_store},
+    };
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Stress(u8);
+
+    impl Component for Stress {}
+    impl Persisted for Stress {
+        const KIND: &'static str = "stress";
+    }
+
+    #[tokio::test]
+    async fn should_sync_rows_changed_since_checkpoint() {
+        let mut laptop = memory_store(vec![]).await;
+        let campaign = laptop.spawn_scope().await.expect("should have spawned campaign");
+        let pc = laptop.spawn().await.expect("should have spawned entity");
+        laptop.insert(pc, &Stress(1)).await.expect("should have inserted component");
+        laptop.record(Some(pc), "stress", &Stress(1)).await.expect("should have recorded entry");
+
+        let mut phone = memory_store(vec![]).await;
+        let full = laptop.delta(0).await.expect("should have exported delta");
+        assert_eq!((0, 1), (full.since, full.until));
+        phone.apply_delta(&full).await.expect("should have applied delta");
+        phone.scope_to(campaign);
+        assert_eq!(Some(Stress(1)), phone.get(pc).await.expect("should have read component"));
+
+        let rival = laptop.spawn().await.expect("should have spawned entity");
+        laptop.relate(pc, rival, "rival").await.expect("should have related entities");
+        laptop.insert(pc, &Stress(3)).await.expect("should have replaced component");
+        laptop.record(Some(pc), "stress", &Stress(3)).await.expect("should have recorded entry");
+
+        let delta = laptop.delta(full.until).await.expect("should have exported delta");
+        assert_eq!(vec![rival], delta.entities);
+        assert_eq!(vec![2], delta.journal.iter().map(|entry| entry.seq).collect::<Vec<_>>());
+        phone.apply_delta(&delta).await.expect("should have applied delta");
+        phone.apply_delta(&delta).await.expect("should have applied delta again");
+
+        assert_eq!(Some(Stress(3)), phone.get(pc).await.expect("should have read component"));
+        assert_eq!(vec![rival], phone.related(pc, "rival").await.expect("should have read relationships"));
+        assert_eq!(
+            laptop.journal_between(0, None).await.expect("should have read journal"),
+            phone.journal_between(0, None).await.expect("should have read journal")
+        );
+    }
+
+    #[tokio::test]
+    async fn should_sync_removals_since_checkpoint() {
+        let mut laptop = memory_store(vec![]).await;
+        let campaign = laptop.spawn_scope().await.expect("should have spawned campaign");
+        let (pc, rival) = (
+            laptop.spawn().await.expect("should have spawned entity"),
+            laptop.spawn().await.expect("should have spawned entity"),
+        );
+        laptop.insert(pc, &Stress(1)).await.expect("should have inserted component");
+        laptop.relate(rival, pc, "rival").await.expect("should have related entities");
+        laptop.record(Some(pc), "stress", &Stress(1)).await.expect("should have recorded entry");
+        let kept = laptop.record(None, "session", &()).await.expect("should have recorded entry");
+
+        let mut phone = memory_store(vec![]).await;
+        let full = laptop.delta(0).await.expect("should have exported delta");
+        phone.apply_delta(&full).await.expect("should have applied delta");
+        phone.scope_to(campaign);
+
+        laptop.despawn(pc).await.expect("should have despawned entity");
+        laptop.commit(&[Change::prune("stress", kept)]).await.expect("should have pruned journal");
+
+        let delta = laptop.delta(full.until).await.expect("should have exported delta");
+        assert_eq!(vec![pc], delta.despawned);
+        assert_eq!(
+            vec![PrunedJournal {
+                kind: "stress".into(),
+                before: kept,
+            }],
+            delta.pruned
+        );
+        phone.apply_delta(&delta).await.expect("should have applied delta");
+
+        assert_eq!(None, phone.get::<Stress>(pc).await.expect("should have read component"));
+        assert_eq!(
+            Vec::<Uuid>::new(),
+            phone.related(rival, "rival").await.expect("should have read relationships")
+        );
+        assert_eq!(
+            laptop.journal_between(0, None).await.expect("should have read journal"),
+            phone.journal_between(0, None).await.expect("should have read journal")
+        );
+        assert_eq!(
+            laptop.delta(0).await.expect("should have exported delta").despawned,
+            phone.delta(0).await.expect("should have exported delta").despawned
+        );
+    }
+
+    #[tokio::test]
+    async fn should_refuse_delta_given_diverged_journal() {
+        let mut laptop = memory_store(vec![]).await;
+        laptop.spawn_scope().await.expect("should have spawned campaign");
+        let mut phone = memory_store(vec![]).await;
+        phone
+            .apply_delta(&laptop.delta(0).await.expect("should have exported delta"))
+            .await
+            .expect("should have applied delta");
+
+        laptop.record(None, "session", &()).await.expect("should have recorded entry");
+        phone.scope_to(laptop.scope());
+        phone.record(None, "harm", &()).await.expect("should have recorded entry");
+
+        let err = phone
+            .apply_delta(&laptop.delta(0).await.expect("should have exported delta"))
+            .await
+            .expect_err("should have refused diverged delta");
+        assert!(matches!(err, SqliteError::Diverged(1)), "unexpected error: {err}");
+    }
+}
@@ -0,0 +1,39 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+use crate::store::{
+    Migrator,
+    sql::{
+        SqlQuery,
+        sqlite::{MIGRATIONS, SqliteStore, store::IntoParams},
+    },
+};
+
+/// Opens a store backed by a fresh in-memory database, with the world schema and then the given setup queries applied.
+///
+/// # Panics
+///
+/// Panics if the database cannot be opened or migrated, or if a setup query fails.
+pub async fn memory_store(setup: impl IntoIterator<Item = SqlQuery>) -> SqliteStore {
+    let store = SqliteStore::memory().await.expect("should have opened memory db");
+    store.apply(MIGRATIONS).await.expect("should have applied migrations");
+
+    let conn = store.pool.get().await.expect("should have taken a connection from the pool");
+    for sql in setup {
+        conn.execute(sql.query.as_str(), sql.to_params().expect("should have converted params"))
+            .await
+            .expect("should have initialised database");
+    }
+    drop(conn);
+
+    store
+}
@@ -0,0 +1,144 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+use std::{collections::VecDeque, time::Duration};
+
+/// Timings of the queries the store ran against its database, collected when enabled with
+/// [`SqliteStoreBuilder::metrics`](crate::store::sql::sqlite::SqliteStoreBuilder::metrics). Queries answered from the
+/// cache do not reach the database and are not counted.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StoreMetrics {
+    /// The number of statements and transactions run.
+    pub queries: u64,
+    /// The number of rows they returned or changed.
+    pub rows: u64,
+    /// The time spent running them, waits for a connection excluded.
+    pub elapsed: Duration,
+    /// The time spent waiting for a pooled connection.
+    pub waited: Duration,
+    /// The latest queries that took at least the slow query threshold, the oldest first.
+    pub slow: VecDeque<SlowQuery>,
+}
+
+/// A query that took at least the slow query threshold, waits for a connection included.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SlowQuery {
+    /// The SQL of the query, without its parameters.
+    pub query: String,
+    /// The number of rows it returned or changed.
+    pub rows: u64,
+    /// The time spent running it.
+    pub elapsed: Duration,
+    /// The time spent waiting for a pooled connection before running it.
+    pub waited: Duration,
+}
+
+/// Collects the timings of the store's queries, logging the slow ones.
+#[derive(Debug)]
+pub(super) struct Metrics {
+    threshold: Duration,
+    collected: StoreMetrics,
+}
+
+impl Metrics {
+    /// The number of slow queries kept in the log before the oldest are dropped.
+    const SLOW_LOG: usize = 100;
+
+    /// Returns metrics logging the queries that take at least `threshold`.
+    pub(super) fn new(threshold: Duration) -> Metrics {
+        Metrics {
+            threshold,
+            collected: StoreMetrics::default(),
+        }
+    }
+
+    /// Counts a query that waited `waited` for a connection, then ran for `elapsed` over `rows` rows.
+    pub(super) fn observe(&mut self, query: &str, rows: u64, waited: Duration, elapsed: Duration) {
+        let collected = &mut self.collected;
+        collected.queries += 1;
+        collected.rows += rows;
+        collected.elapsed += elapsed;
+        collected.waited += waited;
+
+        if waited + elapsed < self.threshold {
+            return;
+        }
+        if collected.slow.len() >= Metrics::SLOW_LOG {
+            collected.slow.pop_front();
+        }
+        collected.slow.push_back(SlowQuery {
+            query: query.trim().into(),
+            rows,
+            elapsed,
+            waited,
+        });
+
+        #[cfg(feature = "tracing")]
+        tracing::warn!(query = query.trim(), rows, ?elapsed, ?waited, "slow query");
+    }
+
+    /// Returns the metrics collected so far.
+    pub(super) fn collected(&self) -> &StoreMetrics {
+        &self.collected
+    }
+
+    /// Starts collecting afresh, keeping the threshold.
+    pub(super) fn reset(&mut self) {
+        self.collected = StoreMetrics::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MS: Duration = Duration::from_millis(1);
+
+    #[test]
+    fn should_sum_queries_and_log_slow_ones() {
+        let mut metrics = Metrics::new(10 * MS);
+
+        metrics.observe("SELECT 1;", 1, MS, 2 * MS);
+        metrics.observe(" SELECT data FROM journal; ", 40, 4 * MS, 6 * MS);
+
+        assert_eq!(
+            &StoreMetrics {
+                queries: 2,
+                rows: 41,
+                elapsed: 8 * MS,
+                waited: 5 * MS,
+                slow: VecDeque::from([SlowQuery {
+                    query: "SELECT data FROM journal;".into(),
+                    rows: 40,
+                    elapsed: 6 * MS,
+                    waited: 4 * MS,
+                }]),
+            },
+            metrics.collected()
+        );
+
+        metrics.reset();
+        assert_eq!(&StoreMetrics::default(), metrics.collected());
+    }
+
+    #[test]
+    fn should_keep_latest_slow_queries() {
+        let mut metrics = Metrics::new(Duration::ZERO);
+
+        for rows in 0..=Metrics::SLOW_LOG as u64 {
+            metrics.observe("SELECT 1;", rows, Duration::ZERO, MS);
+        }
+
+        assert_eq!(Metrics::SLOW_LOG, metrics.collected().slow.len());
+        assert_eq!(Some(1), metrics.collected().slow.front().map(|slow| slow.rows));
+    }
+}
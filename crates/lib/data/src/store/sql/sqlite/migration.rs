@@ -12,7 +12,7 @@
  */
 use std::{path::PathBuf, sync::Arc};
 
-use libsql::Database;
+use libsql::{Connection, Database};
 use libsql_migration::errors::LibsqlDirMigratorError;
 use thiserror::Error;
 
@@ -45,11 +45,118 @@ impl Migrator for SqliteMigrator {
     type Error = MigrationError;
     type Result<T> = Result<T, MigrationError>;
 
-    /// Applies migrations from the given path.
+    /// Applies migrations from the given path, see [`migrate`].
     async fn apply(&self, path: impl Into<PathBuf>) -> Self::Result<()> {
         let conn = self.db.connect()?;
-        libsql_migration::dir::migrate(&conn, path.into()).await?;
+        migrate(&conn, path.into()).await
+    }
+}
+
+/// Applies the migrations from the given path within a savepoint, rolled back if any of them fails so that a bad
+/// migration leaves the database as it was, migrations applied before it included.
+pub(super) async fn migrate(conn: &Connection, path: PathBuf) -> Result<(), MigrationError> {
+    conn.execute("SAVEPOINT migrate;", ()).await?;
+    if let Err(err) = libsql_migration::dir::migrate(conn, path).await {
+        conn.execute("ROLLBACK TO migrate;", ()).await?;
+        conn.execute("RELEASE migrate;", ()).await?;
+        return Err(err.into());
+    }
+    conn.execute("RELEASE migrate;", ()).await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, path::Path};
+
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::store::sql::sqlite::fixture::memory_store;
+
+    /// Writes the migrations into a new directory and returns its path.
+    fn migrations(files: &[(&str, &str)]) -> PathBuf {
+        let dir = env::temp_dir().join(format!("darkforge-migrations-{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).expect("should have created directory");
+        for (name, sql) in files {
+            fs::write(dir.join(name), sql).expect("should have written migration");
+        }
+
+        dir
+    }
+
+    /// Returns the names of the tables of the database.
+    async fn tables(conn: &Connection) -> Vec<String> {
+        let mut rows = conn
+            .query("SELECT name FROM sqlite_master WHERE type = 'table' ORDER BY name;", ())
+            .await
+            .expect("should have listed tables");
+
+        let mut names = Vec::new();
+        while let Some(row) = rows.next().await.expect("should have read row") {
+            names.push(row.get::<String>(0).expect("should have read name"));
+        }
+
+        names
+    }
+
+    async fn open(path: &Path) -> SqliteMigrator {
+        let db = libsql::Builder::new_local(path).build().await.expect("should have opened database");
+        SqliteMigrator::new(Arc::new(db))
+    }
+
+    #[tokio::test]
+    async fn should_roll_back_every_migration_given_one_fails() {
+        let dir = migrations(&[
+            ("0001_create_crews.sql", "CREATE TABLE crews (name TEXT NOT NULL);"),
+            ("0002_broken.sql", "CREATE TABLE lairs (name TEXT NOT NULL"),
+        ]);
+        let migrator = open(&dir.join("campaign.db")).await;
+        let conn = migrator.db.connect().expect("should have connected");
+        conn.execute("CREATE TABLE saves (data TEXT NOT NULL);", ())
+            .await
+            .expect("should have created table");
+
+        migrator.apply(&dir).await.expect_err("should have failed to migrate");
+
+        assert_eq!(vec![String::from("saves")], tables(&conn).await);
+        fs::remove_dir_all(dir).expect("should have removed directory");
+    }
+
+    #[tokio::test]
+    async fn should_apply_migrations_once_fixed() {
+        let dir = migrations(&[
+            ("0001_create_crews.sql", "CREATE TABLE crews (name TEXT NOT NULL);"),
+            ("0002_create_lairs.sql", "CREATE TABLE lairs (name TEXT NOT NULL"),
+        ]);
+        let migrator = open(&dir.join("campaign.db")).await;
+        migrator.apply(&dir).await.expect_err("should have failed to migrate");
+
+        fs::write(dir.join("0002_create_lairs.sql"), "CREATE TABLE lairs (name TEXT NOT NULL);").expect("should have fixed migration");
+        migrator.apply(&dir).await.expect("should have migrated");
+
+        let conn = migrator.db.connect().expect("should have connected");
+        assert_eq!(
+            vec![String::from("crews"), String::from("lairs"), String::from("libsql_migrations")],
+            tables(&conn).await
+        );
+        fs::remove_dir_all(dir).expect("should have removed directory");
+    }
+
+    #[tokio::test]
+    async fn should_leave_store_as_it_was_given_migration_fails() {
+        let dir = migrations(&[
+            ("0006_create_crews.sql", "CREATE TABLE crews (name TEXT NOT NULL);"),
+            ("0007_drop_journal.sql", "DROP TABLE journal;"),
+            ("0008_broken.sql", "ALTER TABLE missing ADD COLUMN name TEXT;"),
+        ]);
+        let store = memory_store(vec![]).await;
+        let before = tables(&store.pool.get().await.expect("should have taken a connection")).await;
+
+        store.apply(&dir).await.expect_err("should have failed to migrate");
 
-        Ok(())
+        assert_eq!(before, tables(&store.pool.get().await.expect("should have taken a connection")).await);
+        fs::remove_dir_all(dir).expect("should have removed directory");
     }
 }
@@ -13,17 +13,57 @@
 use std::result;
 
 use bb8::{PooledConnection, RunError};
+use libsql::Row;
 use serde::de::value::Error as SerdeError;
 use thiserror::Error;
 
-use crate::store::sql::sqlite::{migration::MigrationError, pool::LibSqlConnectionManager};
+#[cfg(feature = "tracing")]
+pub use crate::store::sql::sqlite::cache::CacheStats;
+#[cfg(feature = "encryption")]
+pub use crate::store::sql::sqlite::cipher::{CipherError, Key};
+pub use crate::store::sql::sqlite::{
+    metrics::{SlowQuery, StoreMetrics},
+    migration::{MigrationError, SqliteMigrator},
+    pool::LibSqlConnectionManager,
+    retry::{RetryPolicy, StoreUnavailable},
+    schema::registry,
+    store::{SqliteStore, SqliteStoreBuilder},
+};
+use crate::{CodecError, store::schema::UpgradeError};
 
+/// Module for aggregates of the journal.
+mod aggregate;
+/// Module for caching repeated queries.
+mod cache;
+/// Module for encryption at rest of sensitive components.
+#[cfg(feature = "encryption")]
+mod cipher;
+/// Module for content pack functionality.
+mod content;
+/// Module for deltas of the world.
+mod delta;
+/// Module for stores prepared for tests, enabled in downstream crates by the `testing` feature.
+#[cfg(any(test, feature = "testing"))]
+pub mod fixture;
+/// Module for query metrics and the slow query log.
+mod metrics;
 /// Module for database migration functionality.
 mod migration;
 /// Module for database connection pooling functionality.
 mod pool;
+/// Module for retries of operations failing on transient errors.
+mod retry;
+/// Module for the declared and reflected database schema.
+mod schema;
 /// Module for database store functionality.
 mod store;
+/// Module for database integrity checks.
+mod verify;
+/// Module for game world functionality.
+mod world;
+
+/// Path to the migrations creating the world schema.
+pub const MIGRATIONS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/migrations");
 
 /// Type alias for a pooled database connection.
 type Connection<'a> = PooledConnection<'a, LibSqlConnectionManager>;
@@ -45,4 +85,83 @@ pub enum SqliteError {
     /// An error during migration.
     #[error(transparent)]
     MigrationError(#[from] MigrationError),
+    /// A stored UUID could not be decoded.
+    #[error("invalid uuid: {0}")]
+    InvalidUuid(#[from] uuid::Error),
+    /// A stored JSON payload could not be encoded or decoded.
+    #[error("json error: {0}")]
+    Json(#[from] serde_json::Error),
+    /// A change to record along with others could not be encoded.
+    #[error(transparent)]
+    Codec(#[from] CodecError),
+    /// Components could not be upgraded to the current shape of their type.
+    #[error(transparent)]
+    Upgrade(#[from] UpgradeError),
+    /// The store fails fast as operations kept failing on transient errors, see [`RetryPolicy`].
+    #[error(transparent)]
+    Unavailable(#[from] StoreUnavailable),
+    /// A delta cannot be applied as the store recorded a journal entry after its checkpoint that it does not hold.
+    #[error("store recorded journal entry {0} missing from the delta")]
+    Diverged(i64),
+    /// An encrypted component could not be sealed or opened.
+    #[cfg(feature = "encryption")]
+    #[error(transparent)]
+    Cipher(#[from] CipherError),
+}
+
+impl SqliteError {
+    /// Returns true if the database was locked by another connection, e.g. another process saving the same campaign, or
+    /// no pooled connection freed up in time.
+    #[must_use]
+    pub fn is_busy(&self) -> bool {
+        /// Primary result codes of `SQLITE_BUSY` and `SQLITE_LOCKED`.
+        const BUSY: [i32; 2] = [5, 6];
+
+        match self {
+            SqliteError::LibSql(libsql::Error::SqliteFailure(code, _))
+            | SqliteError::Connection(RunError::User(libsql::Error::SqliteFailure(code, _))) => BUSY.contains(&(code & 0xff)),
+            SqliteError::Connection(RunError::TimedOut) => true,
+            _ => false,
+        }
+    }
+
+    /// Returns true if the store fails fast as operations kept failing on transient errors, e.g. so the game holds its
+    /// writes and tries them again later, see [`RetryPolicy`].
+    #[must_use]
+    pub fn is_unavailable(&self) -> bool {
+        matches!(self, SqliteError::Unavailable(_))
+    }
+
+    /// Returns true if an encrypted component could not be read as the store was given no key or the wrong one, e.g.
+    /// so the game asks the player for the passphrase again.
+    #[must_use]
+    pub fn is_locked(&self) -> bool {
+        #[cfg(feature = "encryption")]
+        if let SqliteError::Cipher(CipherError::Locked { .. } | CipherError::Rejected { .. } | CipherError::WrongPassphrase) = self {
+            return true;
+        }
+
+        false
+    }
+}
+
+/// Reads a UUID stored as a 16 byte blob.
+fn uuid(row: &Row, idx: i32) -> Result<uuid::Uuid> {
+    Ok(uuid::Uuid::from_slice(&row.get::<Vec<u8>>(idx)?)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case::busy(SqliteError::LibSql(libsql::Error::SqliteFailure(5, "database is locked".into())), true)]
+    #[case::extended(SqliteError::LibSql(libsql::Error::SqliteFailure(517, "database is locked".into())), true)]
+    #[case::timed_out(SqliteError::Connection(RunError::TimedOut), true)]
+    #[case::constraint(SqliteError::LibSql(libsql::Error::SqliteFailure(19, "constraint failed".into())), false)]
+    fn should_tell_busy_errors(#[case] err: SqliteError, #[case] expect: bool) {
+        assert_eq!(expect, err.is_busy());
+    }
 }
@@ -11,8 +11,14 @@
  * If not, see https://www.gnu.org/licenses/.
  */
 
-use bb8::ManageConnection;
-use libsql::{Database, errors};
+use std::{
+    future::{self, Future},
+    pin::Pin,
+    time::Duration,
+};
+
+use bb8::{CustomizeConnection, ManageConnection};
+use libsql::{Connection, Database, errors};
 
 /// Connection manager for libsql database connections.
 pub struct LibSqlConnectionManager(pub Database);
@@ -23,13 +29,13 @@ impl ManageConnection for LibSqlConnectionManager {
     type Error = errors::Error;
 
     /// Establishes a new database connection.
-    async fn connect(&self) -> Result<Self::Connection, Self::Error> {
-        self.0.connect()
+    fn connect(&self) -> impl Future<Output = Result<Self::Connection, Self::Error>> + Send {
+        future::ready(self.0.connect())
     }
 
     /// Checks if the connection is valid.
     async fn is_valid(&self, conn: &mut Self::Connection) -> Result<(), Self::Error> {
-        conn.execute("SELECT 1;", ()).await.map(|_| ())
+        conn.query("SELECT 1;", ()).await.map(|_| ())
     }
 
     /// Determines if the connection has broken.
@@ -37,3 +43,29 @@ impl ManageConnection for LibSqlConnectionManager {
         false
     }
 }
+
+/// Settings applied to every connection of a pool as it is opened, see
+/// [`SqliteStoreBuilder`](crate::store::sql::sqlite::SqliteStoreBuilder).
+#[derive(Clone, Copy, Debug, Default)]
+pub(super) struct Tuning {
+    /// How long to wait for a lock held by another connection before failing, if at all.
+    pub(super) busy_timeout: Option<Duration>,
+    /// Whether the connection refuses to write.
+    pub(super) read_only: bool,
+}
+
+impl CustomizeConnection<Connection, errors::Error> for Tuning {
+    /// Applies the settings to the new connection.
+    fn on_acquire<'a>(&'a self, conn: &'a mut Connection) -> Pin<Box<dyn Future<Output = Result<(), errors::Error>> + Send + 'a>> {
+        Box::pin(async move {
+            if let Some(timeout) = self.busy_timeout {
+                conn.busy_timeout(timeout)?;
+            }
+            if self.read_only {
+                conn.execute("PRAGMA query_only = ON;", ()).await?;
+            }
+
+            Ok(())
+        })
+    }
+}
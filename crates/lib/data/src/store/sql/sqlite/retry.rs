@@ -0,0 +1,206 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+use std::time::{Duration, Instant};
+
+use thiserror::Error;
+
+/// How a store retries the operations failing on transient errors, e.g. a lock held by another connection or no pooled
+/// connection freeing up in time, see [`SqliteError::is_busy`](crate::store::sql::sqlite::SqliteError::is_busy), and
+/// when it stops trying, see [`SqliteStoreBuilder::retry`](crate::store::sql::sqlite::SqliteStoreBuilder::retry).
+///
+/// An operation is tried up to `attempts` times, waiting `backoff` before the second try and twice as long before each
+/// try after it, up to `max_backoff`. Once `threshold` operations in a row failed every try, the store fails fast with
+/// [`StoreUnavailable`] for the `cooldown`, rather than holding every call up for the whole backoff, then lets one
+/// operation through to find out whether the database is back, failing the others fast until it does.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// The number of times an operation is tried, the first included.
+    pub attempts: u32,
+    /// How long to wait before the second try.
+    pub backoff: Duration,
+    /// The longest wait between two tries.
+    pub max_backoff: Duration,
+    /// The number of operations in a row failing every try after which the store fails fast.
+    pub threshold: u32,
+    /// How long the store fails fast before trying again.
+    pub cooldown: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            attempts: 4,
+            backoff: Duration::from_millis(25),
+            max_backoff: Duration::from_millis(500),
+            threshold: 3,
+            cooldown: Duration::from_secs(5),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Returns how long to wait after the given failed try, counted from 1.
+    fn delay(&self, tries: u32) -> Duration {
+        let factor = 2u32.saturating_pow(tries.saturating_sub(1));
+        self.backoff.saturating_mul(factor).min(self.max_backoff)
+    }
+}
+
+/// The store fails fast as operations kept failing on transient errors, see [`RetryPolicy`]. The game can hold its
+/// writes and try them again once the store is back.
+#[derive(Error, Clone, Copy, Debug, PartialEq, Eq)]
+#[error("store unavailable after {failures} failed operations in a row, retrying in {retry_in:?}")]
+pub struct StoreUnavailable {
+    /// The number of operations in a row that failed every try.
+    pub failures: u32,
+    /// How long until the store tries again.
+    pub retry_in: Duration,
+}
+
+/// Counts the operations failing in a row, and fails fast once too many did.
+#[derive(Debug)]
+pub(super) struct Breaker {
+    policy: RetryPolicy,
+    failures: u32,
+    opened: Option<Instant>,
+}
+
+impl Breaker {
+    /// Creates a closed breaker, letting every operation through.
+    pub(super) fn new(policy: RetryPolicy) -> Breaker {
+        Breaker {
+            policy,
+            failures: 0,
+            opened: None,
+        }
+    }
+
+    /// Returns the policy to try an operation with, or the error to fail fast with if the breaker is open. Once the
+    /// cooldown is over, lets a single operation through to probe the database and fails the others fast for another
+    /// cooldown, unless the probe succeeds and closes the breaker first.
+    pub(super) fn admit(&mut self, now: Instant) -> Result<RetryPolicy, StoreUnavailable> {
+        match self.opened {
+            Some(opened) if now.duration_since(opened) < self.policy.cooldown => Err(StoreUnavailable {
+                failures: self.failures,
+                retry_in: self.policy.cooldown.saturating_sub(now.duration_since(opened)),
+            }),
+            Some(_) => {
+                self.opened = Some(now);
+                Ok(self.policy)
+            }
+            None => Ok(self.policy),
+        }
+    }
+
+    /// Returns how long to wait before trying the operation again after the given failed try, or `None` if it failed
+    /// its last try, in which case the failure is counted.
+    pub(super) fn retry(&mut self, tries: u32, now: Instant) -> Option<Duration> {
+        if tries < self.policy.attempts {
+            return Some(self.policy.delay(tries));
+        }
+
+        self.failures = self.failures.saturating_add(1);
+        if self.failures >= self.policy.threshold {
+            self.opened = Some(now);
+        }
+        None
+    }
+
+    /// Closes the breaker as an operation reached the database, whether it succeeded or failed on anything but a lock.
+    pub(super) fn succeed(&mut self) {
+        self.failures = 0;
+        self.opened = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy {
+            attempts: 3,
+            backoff: Duration::from_millis(10),
+            max_backoff: Duration::from_millis(15),
+            threshold: 2,
+            cooldown: Duration::from_secs(1),
+        }
+    }
+
+    #[test]
+    fn should_back_off_exponentially_up_to_max() {
+        let mut breaker = Breaker::new(policy());
+        let now = Instant::now();
+
+        let delays = (1..=3).map(|tries| breaker.retry(tries, now)).collect::<Vec<_>>();
+
+        assert_eq!(vec![Some(Duration::from_millis(10)), Some(Duration::from_millis(15)), None], delays);
+    }
+
+    #[test]
+    fn should_fail_fast_after_repeated_failures_until_cooldown() {
+        let mut breaker = Breaker::new(policy());
+        let now = Instant::now();
+
+        breaker.retry(3, now);
+        assert_eq!(Ok(policy()), breaker.admit(now));
+        breaker.retry(3, now);
+
+        assert_eq!(
+            Err(StoreUnavailable {
+                failures: 2,
+                retry_in: Duration::from_millis(600),
+            }),
+            breaker.admit(now + Duration::from_millis(400))
+        );
+        assert_eq!(Ok(policy()), breaker.admit(now + Duration::from_secs(1)));
+    }
+
+    #[test]
+    fn should_let_a_single_probe_through_after_cooldown() {
+        let mut breaker = Breaker::new(policy());
+        let now = Instant::now();
+        breaker.retry(3, now);
+        breaker.retry(3, now);
+        let cooled = now + Duration::from_secs(1);
+
+        assert_eq!(Ok(policy()), breaker.admit(cooled));
+        assert_eq!(
+            Err(StoreUnavailable {
+                failures: 2,
+                retry_in: Duration::from_secs(1),
+            }),
+            breaker.admit(cooled)
+        );
+
+        breaker.retry(3, cooled + Duration::from_millis(100));
+        assert!(breaker.admit(cooled + Duration::from_millis(500)).is_err());
+        assert_eq!(Ok(policy()), breaker.admit(cooled + Duration::from_millis(1100)));
+
+        breaker.succeed();
+        assert_eq!(Ok(policy()), breaker.admit(cooled + Duration::from_millis(1100)));
+        assert_eq!(Ok(policy()), breaker.admit(cooled + Duration::from_millis(1100)));
+    }
+
+    #[test]
+    fn should_close_once_an_operation_succeeds() {
+        let mut breaker = Breaker::new(policy());
+        let now = Instant::now();
+        breaker.retry(3, now);
+        breaker.retry(3, now);
+
+        breaker.succeed();
+
+        assert_eq!(Ok(policy()), breaker.admit(now));
+    }
+}
@@ -0,0 +1,367 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+use std::collections::BTreeMap;
+
+use libsql::params;
+use serde_json::Value;
+
+use crate::{
+    sql,
+    store::{
+        schema::{Column, ColumnType, Reflect, Registry, Schema, Table, UpgradeComponents, UpgradeError, Upgraded},
+        sql::{
+            SqlQuery,
+            sqlite::{Result, SqliteStore, uuid},
+        },
+    },
+};
+
+/// Reads the columns of every table of the database, skipping `SQLite`'s own.
+const COLUMNS: &str = "
+    SELECT m.name, p.name, p.type, p.\"notnull\", p.pk
+    FROM sqlite_master m
+    JOIN pragma_table_info(m.name) p
+    WHERE m.type = 'table' AND m.name NOT LIKE 'sqlite_%'
+    ORDER BY m.name, p.cid;
+";
+
+/// Reads the kinds of components attached to entities.
+const KINDS: &str = "SELECT DISTINCT kind FROM components ORDER BY kind;";
+
+/// Reads the schema version each kind of component is at.
+const VERSIONS: &str = "SELECT kind, version FROM component_schemas;";
+
+/// Reads the components of a kind along with their entity.
+const COMPONENTS_OF: &str = "SELECT rowid, entity_id, data FROM components WHERE kind = ?;";
+
+/// Rewrites the data of a component, leaving its revision as it was: upgrading a component does not change it.
+const REWRITE: &str = "UPDATE components SET data = ? WHERE rowid = ?;";
+
+/// Records the schema version a kind of component is at.
+const RECORD_VERSION: &str = "INSERT OR REPLACE INTO component_schemas (kind, version) VALUES (?, ?);";
+
+/// Returns a registry declaring the tables created by [`MIGRATIONS`](super::MIGRATIONS), to which the types of
+/// components saved in the store can be added.
+#[must_use]
+pub fn registry() -> Registry {
+    use ColumnType::{Blob, Integer, Text};
+
+    Registry::new()
+        .table(
+            Table::new("entities")
+                .key("id", Blob)
+                .column("created_at", Integer)
+                .column("campaign_id", Blob)
+                .column("revision", Integer)
+                .nullable("key", Integer),
+        )
+        .table(
+            Table::new("components")
+                .key("entity_id", Blob)
+                .key("kind", Text)
+                .column("data", Text)
+                .column("revision", Integer)
+                .nullable("entity_key", Integer),
+        )
+        .table(
+            Table::new("relationships")
+                .key("source_id", Blob)
+                .key("target_id", Blob)
+                .key("kind", Text)
+                .column("revision", Integer),
+        )
+        .table(
+            Table::new("journal")
+                .key("seq", Integer)
+                .nullable("entity_id", Blob)
+                .column("kind", Text)
+                .column("data", Text)
+                .column("recorded_at", Integer)
+                .column("campaign_id", Blob)
+                .nullable("entity_key", Integer),
+        )
+        .table(
+            Table::new("content")
+                .key("category", Text)
+                .key("id", Blob)
+                .column("pack", Text)
+                .column("data", Text),
+        )
+        .table(Table::new("pruned").key("kind", Text).column("before", Integer))
+        .table(Table::new("keys").key("key", Integer).column("id", Blob))
+        .table(Table::new("pack_hashes").key("pack", Text).column("hash", Text))
+        .table(Table::new("component_schemas").key("kind", Text).column("version", Integer))
+        .table(Table::new("despawned").key("entity_id", Blob).column("revision", Integer))
+        .table(
+            Table::new("prunes")
+                .key("campaign_id", Blob)
+                .key("kind", Text)
+                .column("before", Integer)
+                .column("revision", Integer),
+        )
+}
+
+impl Reflect for SqliteStore {
+    /// Reads the tables from `sqlite_master`, and the kinds of components from the `components` table if it exists.
+    /// The types saved under each kind are not known to the database.
+    async fn schema(&mut self) -> Result<Schema> {
+        let columns = self
+            .fetch(&sql!(COLUMNS), |row| {
+                let column = Column {
+                    name: row.get(1)?,
+                    ty: ColumnType::parse(&row.get::<String>(2)?),
+                    nullable: row.get::<i64>(3)? == 0,
+                    key: row.get::<i64>(4)? > 0,
+                };
+                Ok((row.get::<String>(0)?, column))
+            })
+            .await?;
+
+        let mut tables: Vec<Table> = Vec::new();
+        for (name, column) in columns {
+            match tables.last_mut() {
+                Some(table) if table.name == name => table.columns.push(column),
+                _ => tables.push(Table { name, columns: vec![column] }),
+            }
+        }
+
+        let kinds = if tables.iter().any(|table| table.name == "components") {
+            self.fetch(&sql!(KINDS), |row| Ok((row.get::<String>(0)?, None))).await?
+        } else {
+            Vec::new()
+        };
+
+        Ok(Schema {
+            tables,
+            kinds: kinds.into_iter().collect::<BTreeMap<_, _>>(),
+        })
+    }
+}
+
+impl UpgradeComponents for SqliteStore {
+    /// Upgrades the components within a single transaction, rolled back if any of them fails. Sealed components are
+    /// opened before they are upgraded and sealed again after.
+    async fn upgrade_components(&mut self, registry: &Registry) -> Result<Vec<Upgraded>> {
+        let conn = self.pool.get().await?;
+        let tx = conn.transaction().await?;
+
+        let mut versions = BTreeMap::new();
+        let mut rows = tx.query(VERSIONS, ()).await?;
+        while let Some(row) = rows.next().await? {
+            versions.insert(row.get::<String>(0)?, u32::try_from(row.get::<i64>(1)?).unwrap_or(u32::MAX));
+        }
+
+        let mut upgraded = Vec::new();
+        for (kind, upgrades) in registry.upgrades() {
+            let latest = u32::try_from(upgrades.len()).map_or(u32::MAX, |upgrades| upgrades.saturating_add(1));
+            let from = versions.get(kind).copied().unwrap_or(1);
+            let unsupported = || UpgradeError::UnsupportedVersion {
+                kind: kind.into(),
+                found: from,
+                latest,
+            };
+            let first = from
+                .checked_sub(1)
+                .and_then(|first| usize::try_from(first).ok())
+                .ok_or_else(unsupported)?;
+            let pending = upgrades.get(first..).ok_or_else(unsupported)?;
+
+            if !pending.is_empty() {
+                let mut components = Vec::new();
+                let mut rows = tx.query(COMPONENTS_OF, params![kind]).await?;
+                while let Some(row) = rows.next().await? {
+                    components.push((row.get::<i64>(0)?, uuid(&row, 1)?, row.get::<String>(2)?));
+                }
+
+                for (rowid, entity, data) in &components {
+                    let mut value = serde_json::from_str::<Value>(&self.unseal(*entity, kind, data.clone())?)?;
+                    for (version, upgrade) in (from..).zip(pending) {
+                        value = upgrade(value).map_err(|reason| UpgradeError::Failed {
+                            kind: kind.into(),
+                            entity: *entity,
+                            from: version,
+                            reason,
+                        })?;
+                    }
+                    let data = self.seal(*entity, kind, value.to_string())?;
+                    tx.execute(REWRITE, params![data, *rowid]).await?;
+                }
+
+                upgraded.push(Upgraded {
+                    kind: kind.into(),
+                    from,
+                    to: latest,
+                    components: components.len() as u64,
+                });
+            }
+
+            if versions.get(kind) != Some(&latest) {
+                tx.execute(RECORD_VERSION, params![kind, i64::from(latest)]).await?;
+            }
+        }
+
+        tx.commit().await?;
+        if !upgraded.is_empty() {
+            self.cache.clear();
+        }
+
+        Ok(upgraded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::{
+        World,
+        sql::sqlite::{SqliteError, fixture::memory_store},
+    };
+
+    #[tokio::test]
+    async fn should_migrate_to_registered_schema() {
+        let mut store = memory_store(vec![]).await;
+        let pc = store.spawn().await.expect("should have spawned entity");
+        store.insert(pc, &Stress(2)).await.expect("should have inserted component");
+
+        let schema = store.schema().await.expect("should have read schema");
+
+        assert_eq!(
+            Vec::<String>::new(),
+            registry().schema().missing(&schema).iter().map(ToString::to_string).collect::<Vec<_>>()
+        );
+        assert_eq!(Some(&None), schema.kinds.get("stress"));
+        assert!(
+            schema
+                .table("journal")
+                .and_then(|journal| journal.get("entity_id"))
+                .is_some_and(|column| column.nullable && !column.key)
+        );
+    }
+
+    #[tokio::test]
+    async fn should_upgrade_components_written_with_older_schema() {
+        let mut store = memory_store(vec![]).await;
+        let pc = store.spawn().await.expect("should have spawned entity");
+        store
+            .insert(pc, &Named { name: "Alice Grey".into() })
+            .await
+            .expect("should have inserted component");
+        let registry = registry().register::<Person>();
+
+        let upgraded = store.upgrade_components(&registry).await.expect("should have upgraded components");
+
+        assert_eq!(
+            vec![Upgraded {
+                kind: "person".into(),
+                from: 1,
+                to: 2,
+                components: 1
+            }],
+            upgraded
+        );
+        assert_eq!(
+            Some(Person {
+                first: "Alice".into(),
+                last: "Grey".into()
+            }),
+            store.get::<Person>(pc).await.expect("should have read component")
+        );
+        assert_eq!(
+            Vec::<Upgraded>::new(),
+            store.upgrade_components(&registry).await.expect("should have upgraded components")
+        );
+    }
+
+    #[tokio::test]
+    async fn should_leave_components_as_they_were_given_failed_upgrade() {
+        let mut store = memory_store(vec![]).await;
+        let pc = store.spawn().await.expect("should have spawned entity");
+        store
+            .insert(pc, &Named { name: "Alice".into() })
+            .await
+            .expect("should have inserted component");
+
+        let err = store
+            .upgrade_components(&registry().register::<Person>())
+            .await
+            .expect_err("should have failed to upgrade components");
+
+        assert!(
+            matches!(&err, SqliteError::Upgrade(UpgradeError::Failed { entity, from: 1, .. }) if *entity == pc),
+            "unexpected error: {err}"
+        );
+        assert_eq!(
+            Some(Named { name: "Alice".into() }),
+            store.get::<Named>(pc).await.expect("should have read component")
+        );
+    }
+
+    #[tokio::test]
+    async fn should_refuse_components_written_with_newer_schema() {
+        let mut store = memory_store(vec![]).await;
+        store
+            .upgrade_components(&registry().register::<Person>())
+            .await
+            .expect("should have upgraded components");
+
+        let err = store
+            .upgrade_components(&registry().register::<Named>())
+            .await
+            .expect_err("should have refused components");
+
+        assert!(
+            matches!(&err, SqliteError::Upgrade(UpgradeError::UnsupportedVersion { found: 2, latest: 1, .. })),
+            "unexpected error: {err}"
+        );
+    }
+
+    #[derive(serde::Serialize, serde::Deserialize)]
+    struct Stress(u8);
+
+    impl crate::Component for Stress {}
+    impl crate::Persisted for Stress {
+        const KIND: &'static str = "stress";
+    }
+
+    /// The first shape of a person, with a single name.
+    #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+    struct Named {
+        name: String,
+    }
+
+    impl crate::Component for Named {}
+    impl crate::Persisted for Named {
+        const KIND: &'static str = "person";
+    }
+
+    /// A person whose `name` was split into a `first` and `last` name in version 2.
+    #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+    struct Person {
+        first: String,
+        last: String,
+    }
+
+    impl crate::Component for Person {}
+    impl crate::Persisted for Person {
+        const KIND: &'static str = "person";
+        const UPGRADES: &'static [crate::export::Upgrade] = &[split_name];
+    }
+
+    fn split_name(mut data: Value) -> std::result::Result<Value, String> {
+        let name = data["name"].as_str().ok_or("missing name")?.to_owned();
+        let (first, last) = name.split_once(' ').ok_or_else(|| format!("{name} has no last name"))?;
+        data["first"] = first.into();
+        data["last"] = last.into();
+        Ok(data)
+    }
+}
@@ -10,27 +10,94 @@
  * You should have received a copy of the GNU Affero General Public License along with this program.
  * If not, see https://www.gnu.org/licenses/.
  */
-use std::result;
+use std::{
+    path::{Path, PathBuf},
+    result,
+    sync::{Arc, Mutex, PoisonError},
+    time::{Duration, Instant},
+};
 
 use bb8::Pool;
-use libsql::{Value, de, params, params::IntoValue};
+use libsql::{Row, Value, de, params, params::IntoValue};
 use serde::Deserialize;
+use uuid::Uuid;
 
-use crate::store::{
-    Query, Store,
-    sql::{
-        Param, Params, SqlQuery,
-        sqlite::{Result, SqliteError, pool::LibSqlConnectionManager},
+#[cfg(feature = "tracing")]
+use crate::store::sql::sqlite::cache::CacheStats;
+#[cfg(feature = "encryption")]
+use crate::store::sql::sqlite::cipher::{Cipher, Encryption, Key};
+use crate::{
+    collation::Collation,
+    store::{
+        Migrator, Query, Store,
+        sql::{
+            Param, Params, SqlQuery,
+            sqlite::{
+                Result, SqliteError,
+                cache::{Cache, Table},
+                metrics::{Metrics, StoreMetrics},
+                migration::migrate,
+                pool::{LibSqlConnectionManager, Tuning},
+                retry::{Breaker, RetryPolicy},
+            },
+        },
     },
 };
 
 /// Store implementation for `SQlite` using libsql and bb8 connection pooling.
+///
+/// Repeated reads of the world and content may be answered from a cache, dropped as the tables they read are written
+/// through the store. The cache is off unless turned on with [`SqliteStoreBuilder::cached`], which the store may only
+/// be when it is the only writer of its database.
+///
+/// A database may hold several campaigns. The store reads and writes the entities and journal of one of them, its
+/// scope, which is the nil UUID until [`SqliteStore::scope_to`] is called. Content is shared by every campaign.
 pub struct SqliteStore {
-    pool: Pool<LibSqlConnectionManager>,
+    pub(super) pool: Pool<LibSqlConnectionManager>,
+    pub(super) readers: Option<Pool<LibSqlConnectionManager>>,
+    pub(super) cache: Cache,
+    pub(super) scope: Uuid,
+    metrics: Option<Mutex<Metrics>>,
+    /// Retries operations failing on transient errors, shared by the stores built by the same builder.
+    breaker: Option<Arc<Mutex<Breaker>>>,
+    /// Connection keeping a shared in-memory database alive for as long as the store, as it is dropped with the last
+    /// connection to it.
+    memory: Option<libsql::Connection>,
+    /// Seals and opens the components of sensitive kinds, if the store was given a key.
+    #[cfg(feature = "encryption")]
+    pub(super) cipher: Option<Cipher>,
+    /// Orders the lists sorted by text, see [`World::sorted`](crate::store::World::sorted).
+    pub(super) collation: Collation,
+}
+
+/// Builder for a [`SqliteStore`] backed by a database file, tuned for reads and writes made concurrently during play.
+///
+/// By default the database uses write-ahead logging, so reads no longer wait for writes to finish and the other way
+/// round, and connections wait up to [`SqliteStoreBuilder::BUSY_TIMEOUT`] for a lock instead of failing at once.
+/// Query-heavy screens can add a pool of read-only connections, which the store's reads then run on, leaving the
+/// write connections to writes. The `concurrent` benchmark of this crate compares these settings while rolling and
+/// reading character sheets at once.
+///
+/// Given [`SqliteStoreBuilder::MEMORY`] as its path, the store keeps its data in memory instead, shared by all of its
+/// connections and dropped with the store. Write-ahead logging does not apply to such databases, and as their
+/// connections lock tables rather than the database, a write and a read of the same table made at once fail rather than
+/// wait for each other. They suit tests and previews, not play.
+#[derive(Clone, Debug)]
+pub struct SqliteStoreBuilder {
+    path: PathBuf,
+    wal: bool,
+    busy_timeout: Option<Duration>,
+    readers: u32,
+    cached: bool,
+    slow: Option<Duration>,
+    breaker: Option<Arc<Mutex<Breaker>>>,
+    #[cfg(feature = "encryption")]
+    encryption: Option<Encryption>,
+    collation: Collation,
 }
 
 /// Trait for types that can be converted to SQL parameters.
-trait IntoParams {
+pub(super) trait IntoParams {
     /// Converts the type into SQL parameters.
     fn to_params(&self) -> Result<params::Params>;
 }
@@ -42,8 +109,374 @@ impl Store for SqliteStore {
 
 impl SqliteStore {
     /// Creates a new `SqliteStore` with the given connection pool.
+    #[must_use]
     pub fn new(pool: Pool<LibSqlConnectionManager>) -> SqliteStore {
-        SqliteStore { pool }
+        SqliteStore {
+            pool,
+            readers: None,
+            cache: Cache::disabled(),
+            scope: Uuid::nil(),
+            metrics: None,
+            breaker: None,
+            memory: None,
+            #[cfg(feature = "encryption")]
+            cipher: None,
+            collation: Collation::root(),
+        }
+    }
+
+    /// Returns the campaign whose entities and journal the store reads and writes.
+    #[must_use]
+    pub fn scope(&self) -> Uuid {
+        self.scope
+    }
+
+    /// Switches the store to the entities and journal of another campaign.
+    pub fn scope_to(&mut self, campaign: Uuid) {
+        self.scope = campaign;
+    }
+
+    /// Returns the collation lists sorted by text follow, the root collation unless the store was built with another,
+    /// see [`SqliteStoreBuilder::collation`].
+    #[must_use]
+    pub fn collation(&self) -> &Collation {
+        &self.collation
+    }
+
+    /// Sorts lists by text following the collation from now on, e.g. as the player switches language.
+    pub fn collate_with(&mut self, collation: Collation) {
+        self.collation = collation;
+    }
+
+    /// Opens a store backed by a fresh in-memory database, shared by all of its connections, with the default settings
+    /// of [`SqliteStore::builder`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SqliteError::LibSql`] if the database cannot be opened.
+    pub async fn memory() -> Result<SqliteStore> {
+        SqliteStore::builder(SqliteStoreBuilder::MEMORY).build().await
+    }
+
+    /// Opens a store backed by the database file at the given path, creating the file if it does not exist, with the
+    /// default settings of [`SqliteStore::builder`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SqliteError::LibSql`] if the database cannot be opened.
+    pub async fn open(path: impl AsRef<Path>) -> Result<SqliteStore> {
+        SqliteStore::builder(path.as_ref()).build().await
+    }
+
+    /// Returns a builder for a store backed by the database file at the given path.
+    #[must_use]
+    pub fn builder(path: impl Into<PathBuf>) -> SqliteStoreBuilder {
+        SqliteStoreBuilder {
+            path: path.into(),
+            wal: true,
+            busy_timeout: Some(SqliteStoreBuilder::BUSY_TIMEOUT),
+            readers: 0,
+            cached: false,
+            slow: None,
+            breaker: None,
+            #[cfg(feature = "encryption")]
+            encryption: None,
+            collation: Collation::root(),
+        }
+    }
+
+    /// Closes the store, first moving the write-ahead log into the database file and emptying it, so no log is left
+    /// behind should the process not close the connections cleanly.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SqliteError::LibSql`] if the log cannot be checkpointed, or [`SqliteError::Connection`] if no
+    /// connection is available.
+    pub async fn close(self) -> Result<()> {
+        let conn = self.pool.get().await?;
+        conn.query("PRAGMA wal_checkpoint(TRUNCATE);", ()).await?;
+
+        Ok(())
+    }
+
+    /// Executes a statement and returns the number of rows it changed.
+    pub(super) async fn execute(&self, query: &SqlQuery) -> Result<u64> {
+        self.retrying(|| async {
+            let start = Instant::now();
+            let conn = self.pool.get().await?;
+            let connected = Instant::now();
+            let changed = conn.execute(query.query.as_str(), query.to_params()?).await?;
+
+            self.observe(&query.query, changed, connected - start, connected.elapsed());
+            Ok(changed)
+        })
+        .await
+    }
+
+    /// Runs a query and maps every row it returns, on a read-only connection if the store has any.
+    pub(super) async fn fetch<T>(&self, query: &SqlQuery, map: impl Fn(&Row) -> Result<T>) -> Result<Vec<T>> {
+        let map = &map;
+        self.retrying(|| async {
+            let start = Instant::now();
+            let conn = self.readers.as_ref().unwrap_or(&self.pool).get().await?;
+            let connected = Instant::now();
+            let mut rows = conn.query(query.query.as_str(), query.to_params()?).await?;
+
+            let mut vals = Vec::new();
+            while let Some(row) = rows.next().await? {
+                vals.push(map(&row)?);
+            }
+
+            self.observe(&query.query, vals.len() as u64, connected - start, connected.elapsed());
+            Ok(vals)
+        })
+        .await
+    }
+
+    /// Runs the operation, trying it again while it fails on transient errors if the store was built with a retry
+    /// policy, see [`SqliteStoreBuilder::retry`]. Any other outcome shows the database is reachable and closes the
+    /// breaker. The operation must leave nothing behind when it fails, e.g. by writing in a transaction.
+    pub(super) async fn retrying<T, F: Future<Output = Result<T>>>(&self, operation: impl Fn() -> F) -> Result<T> {
+        let Some(breaker) = &self.breaker else {
+            return operation().await;
+        };
+        let lock = || breaker.lock().unwrap_or_else(PoisonError::into_inner);
+
+        lock().admit(Instant::now())?;
+        let mut tries = 0;
+        loop {
+            tries += 1;
+            match operation().await {
+                Ok(value) => {
+                    lock().succeed();
+                    return Ok(value);
+                }
+                Err(err) if err.is_busy() => {
+                    let Some(delay) = lock().retry(tries, Instant::now()) else {
+                        return Err(err);
+                    };
+                    tokio::time::sleep(delay).await;
+                }
+                Err(err) => {
+                    lock().succeed();
+                    return Err(err);
+                }
+            }
+        }
+    }
+
+    /// Counts a query in the metrics of the store, if it collects any.
+    pub(super) fn observe(&self, query: &str, rows: u64, waited: Duration, elapsed: Duration) {
+        if let Some(metrics) = &self.metrics {
+            metrics
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .observe(query, rows, waited, elapsed);
+        }
+    }
+
+    /// Returns the query metrics collected since the store was opened or they were last reset, if the store collects
+    /// any, see [`SqliteStoreBuilder::metrics`].
+    #[must_use]
+    pub fn metrics(&self) -> Option<StoreMetrics> {
+        self.metrics
+            .as_ref()
+            .map(|metrics| metrics.lock().unwrap_or_else(PoisonError::into_inner).collected().clone())
+    }
+
+    /// Drops the query metrics collected so far, e.g. when the game enters another screen.
+    pub fn reset_metrics(&self) {
+        if let Some(metrics) = &self.metrics {
+            metrics.lock().unwrap_or_else(PoisonError::into_inner).reset();
+        }
+    }
+
+    /// Runs a query reading the given tables and maps every row it returns, unless its rows are cached.
+    pub(super) async fn fetch_cached<T: Clone + Send + Sync + 'static>(
+        &mut self, tables: &'static [Table], query: &SqlQuery, map: impl Fn(&Row) -> Result<T>,
+    ) -> Result<Vec<T>> {
+        let key = Cache::key(query);
+        if let Some(rows) = self.cache.get(&key) {
+            return Ok(rows);
+        }
+
+        let rows = self.fetch(query, map).await?;
+        self.cache.put(key, tables, rows.clone());
+
+        Ok(rows)
+    }
+
+    /// Returns the hit and miss counts of the query cache.
+    #[cfg(feature = "tracing")]
+    #[must_use]
+    pub fn cache_stats(&self) -> CacheStats {
+        self.cache.stats()
+    }
+
+    /// Returns the data of a component to write as is, as stores without encryption do not seal components.
+    #[cfg(not(feature = "encryption"))]
+    #[expect(
+        clippy::unnecessary_wraps,
+        clippy::unused_self,
+        reason = "Mirrors the store sealing components with a key"
+    )]
+    pub(super) fn seal(&self, _entity: Uuid, _kind: &str, data: String) -> Result<String> {
+        Ok(data)
+    }
+
+    /// Returns the data of a component read as is, as stores without encryption do not seal components.
+    #[cfg(not(feature = "encryption"))]
+    #[expect(
+        clippy::unnecessary_wraps,
+        clippy::unused_self,
+        reason = "Mirrors the store opening components with a key"
+    )]
+    pub(super) fn unseal(&self, _entity: Uuid, _kind: &str, data: String) -> Result<String> {
+        Ok(data)
+    }
+}
+
+impl SqliteStoreBuilder {
+    /// How long connections wait for a lock by default.
+    pub const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+    /// Path of an in-memory database, which unlike `SQLite`'s own is shared by every connection of the store.
+    pub const MEMORY: &str = ":memory:";
+
+    /// Sets whether the database uses write-ahead logging. Turning it off does not switch back a database that already
+    /// uses it.
+    #[must_use]
+    pub fn wal(mut self, enabled: bool) -> SqliteStoreBuilder {
+        self.wal = enabled;
+        self
+    }
+
+    /// Sets how long connections wait for a lock held by another connection before failing, if at all.
+    #[must_use]
+    pub fn busy_timeout(mut self, timeout: Option<Duration>) -> SqliteStoreBuilder {
+        self.busy_timeout = timeout;
+        self
+    }
+
+    /// Sets the number of read-only connections reads run on, none by default to run them on the write connections.
+    #[must_use]
+    pub fn readers(mut self, size: u32) -> SqliteStoreBuilder {
+        self.readers = size;
+        self
+    }
+
+    /// Sets whether repeated reads are cached, which they are not by default. Only turn it on when no other store or
+    /// process writes to the database, as the cache only sees the writes made through its own store.
+    #[must_use]
+    pub fn cached(mut self, enabled: bool) -> SqliteStoreBuilder {
+        self.cached = enabled;
+        self
+    }
+
+    /// Collects the number of queries run, the rows they read or changed, the time spent running them and waiting for
+    /// a connection, and logs the queries that take at least `slow`, see [`SqliteStore::metrics`]. Metrics are not
+    /// collected by default.
+    #[must_use]
+    pub fn metrics(mut self, slow: Duration) -> SqliteStoreBuilder {
+        self.slow = Some(slow);
+        self
+    }
+
+    /// Tries operations failing on transient errors again following the policy, failing fast with
+    /// [`SqliteError::Unavailable`] once they keep failing. The stores built by this builder and its clones share the
+    /// count of failures, so that they all fail fast together. Operations fail at once by default.
+    #[must_use]
+    pub fn retry(mut self, policy: RetryPolicy) -> SqliteStoreBuilder {
+        self.breaker = Some(Arc::new(Mutex::new(Breaker::new(policy))));
+        self
+    }
+
+    /// Encrypts the components of the given kinds with the key, as they are written. Encrypted components are read back
+    /// with the same key, and fail to read with [`CipherError::Locked`](super::CipherError::Locked) without one.
+    /// Components are not encrypted by default.
+    #[cfg(feature = "encryption")]
+    #[must_use]
+    pub fn encrypt(mut self, key: Key, kinds: impl IntoIterator<Item = impl Into<String>>) -> SqliteStoreBuilder {
+        self.encryption = Some(Encryption {
+            key,
+            kinds: kinds.into_iter().map(Into::into).collect(),
+        });
+        self
+    }
+
+    /// Sorts lists by text following the collation, e.g. that of the language of the content packs, rather than the
+    /// root collation.
+    #[must_use]
+    pub fn collation(mut self, collation: Collation) -> SqliteStoreBuilder {
+        self.collation = collation;
+        self
+    }
+
+    /// Opens the store, creating the database file if it does not exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SqliteError::LibSql`] if the database cannot be opened or switched to write-ahead logging.
+    pub async fn build(self) -> Result<SqliteStore> {
+        let in_memory = self.path == Path::new(SqliteStoreBuilder::MEMORY);
+        let path = if in_memory {
+            PathBuf::from(format!("file:darkforge-{}?mode=memory&cache=shared", Uuid::new_v4()))
+        } else {
+            self.path
+        };
+
+        let db = libsql::Builder::new_local(&path).build().await?;
+        let memory = if in_memory { Some(db.connect()?) } else { None };
+        if self.wal && !in_memory {
+            db.connect()?.query("PRAGMA journal_mode = WAL;", ()).await?;
+        }
+
+        let tuning = Tuning {
+            busy_timeout: self.busy_timeout,
+            read_only: false,
+        };
+        let pool = Pool::builder()
+            .connection_customizer(Box::new(tuning))
+            .build(LibSqlConnectionManager(db))
+            .await?;
+
+        let mut store = SqliteStore::new(pool);
+        store.memory = memory;
+        if self.cached {
+            store.cache = Cache::default();
+        }
+        store.metrics = self.slow.map(|slow| Mutex::new(Metrics::new(slow)));
+        store.breaker = self.breaker;
+        store.collation = self.collation;
+        #[cfg(feature = "encryption")]
+        {
+            store.cipher = self.encryption.map(Cipher::from);
+        }
+        if self.readers > 0 {
+            let db = libsql::Builder::new_local(&path).build().await?;
+            let tuning = Tuning { read_only: true, ..tuning };
+            let readers = Pool::builder()
+                .max_size(self.readers)
+                .connection_customizer(Box::new(tuning))
+                .build(LibSqlConnectionManager(db))
+                .await?;
+            store.readers = Some(readers);
+        }
+
+        Ok(store)
+    }
+}
+
+impl Migrator for SqliteStore {
+    type Error = SqliteError;
+    type Result<T> = Result<T>;
+
+    /// Applies migrations from the given path using a pooled connection, rolling all of them back if one fails.
+    ///
+    /// Unlike [`SqliteMigrator`](crate::store::sql::sqlite::SqliteMigrator), this migrates the same connections that
+    /// queries run on, which is required for in-memory databases.
+    async fn apply(&self, path: impl Into<PathBuf>) -> Result<()> {
+        let conn = self.pool.get().await?;
+        Ok(migrate(&conn, path.into()).await?)
     }
 }
 
@@ -114,11 +547,23 @@ impl<T: for<'de> Deserialize<'de>> Query<'_, SqliteStore, T> for SqlQuery {
 
 #[cfg(test)]
 mod tests {
-    use uuid::Uuid;
+    use std::{env, fs};
 
     use super::*;
     use crate::sql;
 
+    /// The journal mode of a database.
+    #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+    struct JournalMode {
+        journal_mode: String,
+    }
+
+    /// A name read back from a test table.
+    #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+    struct Name {
+        name: String,
+    }
+
     /// Test struct for testing the `SqliteStore`.
     #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
     struct Test {
@@ -156,6 +601,292 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn should_open_database_in_wal_mode() {
+        let dir = temp_dir("wal");
+        let mut store = SqliteStore::open(dir.join("campaign.db")).await.expect("should have opened store");
+
+        let mode = sql!("PRAGMA journal_mode;").run(&mut store).await.expect("should have read journal mode");
+
+        assert_eq!(vec![JournalMode { journal_mode: "wal".into() }], mode);
+        drop(store);
+        fs::remove_dir_all(dir).expect("should have removed database");
+    }
+
+    #[tokio::test]
+    async fn should_leave_no_write_ahead_log_once_closed() {
+        let dir = temp_dir("close");
+        let path = dir.join("campaign.db");
+        let store = SqliteStore::open(&path).await.expect("should have opened store");
+        store
+            .execute(&sql!("CREATE TABLE test (name TEXT NOT NULL);"))
+            .await
+            .expect("should have created table");
+        store
+            .execute(&sql!("INSERT INTO test (name) VALUES ('John Doe');"))
+            .await
+            .expect("should have written");
+
+        let leaked = libsql::Builder::new_local(&path)
+            .build()
+            .await
+            .expect("should have opened database")
+            .connect()
+            .expect("should have connected");
+        leaked
+            .query("SELECT name FROM test;", ())
+            .await
+            .expect("should have read through the leaked connection");
+
+        store.close().await.expect("should have closed store");
+
+        let log = fs::metadata(dir.join("campaign.db-wal")).map_or(0, |log| log.len());
+        assert_eq!(0, log);
+        drop(leaked);
+        let mut store = SqliteStore::open(&path).await.expect("should have reopened store");
+        let names = sql!("SELECT name FROM test;").run(&mut store).await.expect("should have read names");
+        assert_eq!(vec![Name { name: "John Doe".into() }], names);
+        drop(store);
+        fs::remove_dir_all(dir).expect("should have removed database");
+    }
+
+    #[tokio::test]
+    async fn should_read_writes_on_read_only_connections() {
+        let dir = temp_dir("readers");
+        let store = SqliteStore::builder(dir.join("campaign.db"))
+            .readers(2)
+            .build()
+            .await
+            .expect("should have opened store");
+
+        store
+            .execute(&sql!("CREATE TABLE test (name TEXT NOT NULL);"))
+            .await
+            .expect("should have created table");
+        store
+            .execute(&sql!("INSERT INTO test (name) VALUES ('John Doe');"))
+            .await
+            .expect("should have written");
+
+        let names = store
+            .fetch(&sql!("SELECT name FROM test;"), |row| Ok(row.get::<String>(0)?))
+            .await
+            .expect("should have read names");
+        assert_eq!(vec![String::from("John Doe")], names);
+
+        let readers = store.readers.as_ref().expect("should have read-only connections");
+        readers
+            .get()
+            .await
+            .expect("should have taken a read-only connection")
+            .execute("INSERT INTO test (name) VALUES ('Jane Doe');", ())
+            .await
+            .expect_err("should have refused to write");
+        drop(store);
+        fs::remove_dir_all(dir).expect("should have removed database");
+    }
+
+    #[tokio::test]
+    async fn should_share_memory_db_between_connections() {
+        let store = SqliteStore::builder(SqliteStoreBuilder::MEMORY)
+            .readers(2)
+            .build()
+            .await
+            .expect("should have opened store");
+
+        let first = store.pool.get().await.expect("should have taken a connection");
+        let second = store.pool.get().await.expect("should have taken another connection");
+        first
+            .execute("CREATE TABLE test (name TEXT NOT NULL);", ())
+            .await
+            .expect("should have created table");
+        second
+            .execute("INSERT INTO test (name) VALUES ('John Doe');", ())
+            .await
+            .expect("should have written through another connection");
+        drop((first, second));
+
+        let names = store
+            .fetch(&sql!("SELECT name FROM test;"), |row| Ok(row.get::<String>(0)?))
+            .await
+            .expect("should have read names on a read-only connection");
+        assert_eq!(vec![String::from("John Doe")], names);
+    }
+
+    #[tokio::test]
+    async fn should_isolate_memory_dbs_of_different_stores() {
+        let first = SqliteStore::memory().await.expect("should have opened store");
+        let second = SqliteStore::memory().await.expect("should have opened another store");
+
+        first
+            .execute(&sql!("CREATE TABLE test (name TEXT NOT NULL);"))
+            .await
+            .expect("should have created table");
+
+        second
+            .execute(&sql!("INSERT INTO test (name) VALUES ('John Doe');"))
+            .await
+            .expect_err("should not have seen the other store's table");
+    }
+
+    #[tokio::test]
+    async fn should_collect_metrics_of_queries_run() {
+        let store = SqliteStore::builder(SqliteStoreBuilder::MEMORY)
+            .metrics(Duration::ZERO)
+            .build()
+            .await
+            .expect("should have opened store");
+        store
+            .execute(&sql!("CREATE TABLE test (name TEXT NOT NULL);"))
+            .await
+            .expect("should have created table");
+        store
+            .execute(&sql!("INSERT INTO test (name) VALUES ('John Doe'), ('Jane Doe');"))
+            .await
+            .expect("should have inserted names");
+        store
+            .fetch(&sql!("SELECT name FROM test;"), |row| Ok(row.get::<String>(0)?))
+            .await
+            .expect("should have read names");
+
+        let metrics = store.metrics().expect("should have collected metrics");
+        assert_eq!((3, 4), (metrics.queries, metrics.rows));
+        assert_eq!(Some("SELECT name FROM test;"), metrics.slow.back().map(|slow| slow.query.as_str()));
+
+        store.reset_metrics();
+        assert_eq!(Some(0), store.metrics().map(|metrics| metrics.queries));
+        assert_eq!(None, SqliteStore::memory().await.expect("should have opened store").metrics());
+    }
+
+    #[tokio::test]
+    async fn should_retry_writes_until_lock_is_released() {
+        let dir = temp_dir("retry");
+        let path = dir.join("campaign.db");
+        let store = SqliteStore::builder(&path)
+            .busy_timeout(None)
+            .retry(RetryPolicy {
+                attempts: 10,
+                backoff: Duration::from_millis(5),
+                max_backoff: Duration::from_millis(20),
+                ..RetryPolicy::default()
+            })
+            .build()
+            .await
+            .expect("should have opened store");
+        store
+            .execute(&sql!("CREATE TABLE test (name TEXT NOT NULL);"))
+            .await
+            .expect("should have created table");
+        let other = libsql::Builder::new_local(&path)
+            .build()
+            .await
+            .expect("should have opened database")
+            .connect()
+            .expect("should have connected");
+        other.execute("BEGIN IMMEDIATE;", ()).await.expect("should have taken the write lock");
+
+        let insert = sql!("INSERT INTO test (name) VALUES ('John Doe');");
+        let (written, ()) = tokio::join!(store.execute(&insert), async {
+            tokio::time::sleep(Duration::from_millis(30)).await;
+            other.execute("COMMIT;", ()).await.expect("should have released the write lock");
+        });
+
+        assert_eq!(1, written.expect("should have written once the lock was released"));
+        drop(store);
+        fs::remove_dir_all(dir).expect("should have removed database");
+    }
+
+    #[tokio::test]
+    async fn should_fail_fast_once_writes_keep_failing() {
+        let dir = temp_dir("breaker");
+        let path = dir.join("campaign.db");
+        let store = SqliteStore::builder(&path)
+            .busy_timeout(None)
+            .retry(RetryPolicy {
+                attempts: 2,
+                backoff: Duration::from_millis(1),
+                threshold: 2,
+                ..RetryPolicy::default()
+            })
+            .build()
+            .await
+            .expect("should have opened store");
+        store
+            .execute(&sql!("CREATE TABLE test (name TEXT NOT NULL);"))
+            .await
+            .expect("should have created table");
+        let other = libsql::Builder::new_local(&path)
+            .build()
+            .await
+            .expect("should have opened database")
+            .connect()
+            .expect("should have connected");
+        other.execute("BEGIN IMMEDIATE;", ()).await.expect("should have taken the write lock");
+
+        let insert = sql!("INSERT INTO test (name) VALUES ('John Doe');");
+        for _ in 0..2 {
+            let err = store.execute(&insert).await.expect_err("should have failed while locked");
+            assert!(err.is_busy());
+        }
+        other.execute("COMMIT;", ()).await.expect("should have released the write lock");
+
+        let err = store.execute(&insert).await.expect_err("should have failed fast");
+        assert!(err.is_unavailable());
+        drop(store);
+        fs::remove_dir_all(dir).expect("should have removed database");
+    }
+
+    #[tokio::test]
+    async fn should_close_breaker_once_probe_fails_on_other_error() {
+        let dir = temp_dir("probe");
+        let path = dir.join("campaign.db");
+        let cooldown = Duration::from_millis(50);
+        let store = SqliteStore::builder(&path)
+            .busy_timeout(None)
+            .retry(RetryPolicy {
+                attempts: 1,
+                threshold: 1,
+                cooldown,
+                ..RetryPolicy::default()
+            })
+            .build()
+            .await
+            .expect("should have opened store");
+        store
+            .execute(&sql!("CREATE TABLE test (name TEXT NOT NULL);"))
+            .await
+            .expect("should have created table");
+        let other = libsql::Builder::new_local(&path)
+            .build()
+            .await
+            .expect("should have opened database")
+            .connect()
+            .expect("should have connected");
+        other.execute("BEGIN IMMEDIATE;", ()).await.expect("should have taken the write lock");
+        let insert = sql!("INSERT INTO test (name) VALUES ('John Doe');");
+        store.execute(&insert).await.expect_err("should have failed while locked");
+        other.execute("COMMIT;", ()).await.expect("should have released the write lock");
+        tokio::time::sleep(cooldown).await;
+
+        let err = store
+            .execute(&sql!("INSERT INTO test (name) VALUES (NULL);"))
+            .await
+            .expect_err("should have failed the constraint");
+        assert!(!err.is_busy() && !err.is_unavailable(), "unexpected error: {err}");
+
+        store.execute(&insert).await.expect("should have closed the breaker");
+        drop(store);
+        fs::remove_dir_all(dir).expect("should have removed database");
+    }
+
+    /// Creates an empty directory for a test database.
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("darkforge-{name}-{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).expect("should have created directory");
+
+        dir
+    }
+
     /// Prepares a test database with the given setup queries.
     async fn prepare_db(setup: Vec<SqlQuery>) -> Pool<LibSqlConnectionManager> {
         let db = libsql::Builder::new_local(":memory:")
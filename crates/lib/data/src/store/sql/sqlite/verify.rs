@@ -0,0 +1,352 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+use libsql::{Row, params};
+
+use crate::store::{
+    Verify,
+    schema::Reflect,
+    sql::sqlite::{Connection, MIGRATIONS, Result, SqliteStore, migration::migrate, schema::registry, uuid},
+    verify::{Issue, Repair, Report},
+};
+
+/// Tables whose foreign keys are covered by a more specific check.
+const SPECIFIC_CHECKS: [&str; 2] = ["components", "relationships"];
+
+/// Finds rows violating a foreign key constraint.
+const FOREIGN_KEY_CHECK: &str = "PRAGMA foreign_key_check;";
+
+/// Finds components attached to entities that do not exist.
+const ORPHANED_COMPONENTS: &str = "
+    SELECT c.entity_id, c.kind
+    FROM components c
+    LEFT JOIN entities e ON e.id = c.entity_id
+    WHERE e.id IS NULL
+    ORDER BY c.rowid;
+";
+
+/// Finds relationships whose source or target entity does not exist.
+const DANGLING_RELATIONSHIPS: &str = "
+    SELECT r.source_id, r.target_id, r.kind
+    FROM relationships r
+    LEFT JOIN entities s ON s.id = r.source_id
+    LEFT JOIN entities t ON t.id = r.target_id
+    WHERE s.id IS NULL OR t.id IS NULL
+    ORDER BY r.rowid;
+";
+
+/// Finds holes in the journal sequence, including a missing start. Holes left by pruning entries, before the latest
+/// entry kept, are not gaps.
+const JOURNAL_GAPS: &str = "
+    SELECT MAX(prev, kept), seq
+    FROM (
+        SELECT COALESCE(LAG(seq) OVER (ORDER BY seq), 0) AS prev, seq, (SELECT COALESCE(MAX(before), 1) - 1 FROM pruned) AS kept
+        FROM journal
+    )
+    WHERE seq > MAX(prev, kept) + 1
+    ORDER BY seq;
+";
+
+/// Inserts placeholder entries for every sequence number strictly between the two bounds.
+const FILL_JOURNAL_GAP: &str = "
+    WITH RECURSIVE gap(seq) AS (SELECT ?1 + 1 UNION ALL SELECT seq + 1 FROM gap WHERE seq + 1 < ?2)
+    INSERT INTO journal (seq, kind, data) SELECT seq, 'journal.gap', '{}' FROM gap;
+";
+
+impl Verify for SqliteStore {
+    /// Checks foreign keys, orphaned components, dangling relationships, journal gaps, then the tables and columns
+    /// declared by the [`registry`].
+    async fn verify(&mut self) -> Result<Report> {
+        let conn = self.pool.get().await?;
+        let mut issues = Vec::new();
+
+        issues.extend(foreign_key_violations(&conn).await?);
+        issues.extend(collect(&conn, ORPHANED_COMPONENTS, orphaned_component).await?);
+        issues.extend(collect(&conn, DANGLING_RELATIONSHIPS, dangling_relationship).await?);
+        issues.extend(collect(&conn, JOURNAL_GAPS, journal_gap).await?);
+        drop(conn);
+
+        let schema = self.schema().await?;
+        issues.extend(registry().schema().missing(&schema).into_iter().map(|missing| Issue::SchemaDrift {
+            table: missing.table,
+            column: missing.column,
+        }));
+
+        Ok(Report { issues })
+    }
+
+    /// Applies all repairs in a single transaction.
+    async fn repair(&mut self, repairs: &[Repair]) -> Result<u64> {
+        let conn = self.pool.get().await?;
+        let tx = conn.transaction().await?;
+
+        let mut affected = 0;
+        for repair in repairs {
+            affected += match repair {
+                Repair::DeleteRow { table, row } => {
+                    let query = format!("DELETE FROM \"{}\" WHERE rowid = ?;", table.replace('"', "\"\""));
+                    tx.execute(query.as_str(), params![*row]).await?
+                }
+                Repair::DeleteComponent { entity, kind } => {
+                    tx.execute(
+                        "DELETE FROM components WHERE entity_id = ? AND kind = ?;",
+                        params![entity.as_bytes().as_slice(), kind.as_str()],
+                    )
+                    .await?
+                }
+                Repair::DeleteRelationship { source, target, kind } => {
+                    tx.execute(
+                        "DELETE FROM relationships WHERE source_id = ? AND target_id = ? AND kind = ?;",
+                        params![source.as_bytes().as_slice(), target.as_bytes().as_slice(), kind.as_str()],
+                    )
+                    .await?
+                }
+                Repair::FillJournalGap { after, before } => tx.execute(FILL_JOURNAL_GAP, params![*after, *before]).await?,
+                Repair::Migrate => {
+                    migrate(&tx, MIGRATIONS.into()).await?;
+                    0
+                }
+            };
+        }
+
+        tx.commit().await?;
+        self.cache.clear();
+        Ok(affected)
+    }
+}
+
+/// Runs `PRAGMA foreign_key_check` and converts the violations into issues.
+async fn foreign_key_violations(conn: &Connection<'_>) -> Result<Vec<Issue>> {
+    let violations = collect(conn, FOREIGN_KEY_CHECK, |row| {
+        Ok(Issue::ForeignKeyViolation {
+            table: row.get(0)?,
+            row: row.get(1)?,
+            parent: row.get(2)?,
+        })
+    })
+    .await?;
+
+    Ok(violations
+        .into_iter()
+        .filter(|issue| !matches!(issue, Issue::ForeignKeyViolation { table, .. } if SPECIFIC_CHECKS.contains(&table.as_str())))
+        .collect())
+}
+
+/// Runs a query and maps every row into an issue.
+async fn collect(conn: &Connection<'_>, query: &str, map: impl Fn(&Row) -> Result<Issue>) -> Result<Vec<Issue>> {
+    let mut rows = conn.query(query, ()).await?;
+
+    let mut issues = Vec::new();
+    while let Some(row) = rows.next().await? {
+        issues.push(map(&row)?);
+    }
+
+    Ok(issues)
+}
+
+/// Maps a row of [`ORPHANED_COMPONENTS`] into an issue.
+fn orphaned_component(row: &Row) -> Result<Issue> {
+    Ok(Issue::OrphanedComponent {
+        entity: uuid(row, 0)?,
+        kind: row.get(1)?,
+    })
+}
+
+/// Maps a row of [`DANGLING_RELATIONSHIPS`] into an issue.
+fn dangling_relationship(row: &Row) -> Result<Issue> {
+    Ok(Issue::DanglingRelationship {
+        source: uuid(row, 0)?,
+        target: uuid(row, 1)?,
+        kind: row.get(2)?,
+    })
+}
+
+/// Maps a row of [`JOURNAL_GAPS`] into an issue.
+fn journal_gap(row: &Row) -> Result<Issue> {
+    Ok(Issue::JournalGap {
+        after: row.get(0)?,
+        before: row.get(1)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::{Uuid, uuid};
+
+    use super::*;
+    use crate::{
+        sql,
+        store::sql::{SqlQuery, sqlite::fixture::memory_store},
+    };
+
+    const PC: Uuid = uuid!("f4f77f73-e1e8-4289-b77f-73e1e86289e0");
+    const GHOST: Uuid = uuid!("0b1c0b45-7d1f-4c55-9c2f-5e6e5f1b3a10");
+
+    #[tokio::test]
+    async fn should_report_clean_store() {
+        let mut store = memory_store(vec![
+            sql!("INSERT INTO entities (id) VALUES (?);", PC),
+            sql!("INSERT INTO components (entity_id, kind, data) VALUES (?, 'stress', '2');", PC),
+            sql!("INSERT INTO journal (seq, entity_id, kind, data) VALUES (1, ?, 'stress', '{}');", PC),
+        ])
+        .await;
+
+        let report = store.verify().await.expect("should have verified store");
+
+        assert!(report.is_clean(), "unexpected issues: {:?}", report.issues);
+    }
+
+    #[tokio::test]
+    async fn should_only_report_gaps_after_pruned_entries() {
+        let mut store = memory_store(vec![
+            sql!("INSERT INTO entities (id) VALUES (?);", PC),
+            sql!("INSERT INTO journal (seq, entity_id, kind, data) VALUES (3, ?, 'harm', '{}');", PC),
+            sql!("INSERT INTO journal (seq, entity_id, kind, data) VALUES (4, ?, 'stress', '{}');", PC),
+            sql!("INSERT INTO journal (seq, entity_id, kind, data) VALUES (6, ?, 'stress', '{}');", PC),
+            sql!("INSERT INTO pruned (kind, before) VALUES ('stress', 4);"),
+        ])
+        .await;
+
+        let report = store.verify().await.expect("should have verified store");
+
+        assert_eq!(vec![Issue::JournalGap { after: 4, before: 6 }], report.issues);
+    }
+
+    #[tokio::test]
+    async fn should_report_and_repair_all_issues() {
+        let mut store = memory_store(vec![
+            sql!("PRAGMA foreign_keys = OFF;"),
+            sql!("INSERT INTO entities (id) VALUES (?);", PC),
+            sql!("INSERT INTO components (entity_id, kind, data) VALUES (?, 'stress', '2');", GHOST),
+            sql!(
+                "INSERT INTO relationships (source_id, target_id, kind) VALUES (?, ?, 'rival');",
+                PC,
+                GHOST
+            ),
+            sql!("INSERT INTO journal (seq, entity_id, kind, data) VALUES (1, ?, 'stress', '{}');", PC),
+            sql!("INSERT INTO journal (seq, entity_id, kind, data) VALUES (4, ?, 'stress', '{}');", GHOST),
+        ])
+        .await;
+
+        let report = store.verify().await.expect("should have verified store");
+
+        assert_eq!(
+            vec![
+                Issue::ForeignKeyViolation {
+                    table: "journal".into(),
+                    row: 4,
+                    parent: "entities".into()
+                },
+                Issue::OrphanedComponent {
+                    entity: GHOST,
+                    kind: "stress".into()
+                },
+                Issue::DanglingRelationship {
+                    source: PC,
+                    target: GHOST,
+                    kind: "rival".into()
+                },
+                Issue::JournalGap { after: 1, before: 4 },
+            ],
+            report.issues
+        );
+
+        let affected = store.repair(&report.repairs()).await.expect("should have repaired store");
+        assert_eq!(5, affected);
+
+        let report = store.verify().await.expect("should have verified store");
+        assert!(report.is_clean(), "unexpected issues: {:?}", report.issues);
+    }
+
+    #[tokio::test]
+    async fn should_migrate_database_missing_registered_columns() {
+        let older = std::env::temp_dir().join(format!("darkforge-older-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&older).expect("should have created migrations directory");
+        for entry in std::fs::read_dir(MIGRATIONS).expect("should have listed migrations") {
+            let path = entry.expect("should have read migration").path();
+            let name = path.file_name().expect("should have named migration").to_owned();
+            if name.to_string_lossy().as_ref() < "0007" {
+                std::fs::copy(&path, older.join(name)).expect("should have copied migration");
+            }
+        }
+        let mut store = SqliteStore::memory().await.expect("should have opened store");
+        crate::store::Migrator::apply(&store, &older)
+            .await
+            .expect("should have applied older migrations");
+
+        let report = store.verify().await.expect("should have verified store");
+
+        assert_eq!(
+            vec![
+                Issue::SchemaDrift {
+                    table: "component_schemas".into(),
+                    column: None
+                },
+                Issue::SchemaDrift {
+                    table: "components".into(),
+                    column: Some("revision".into())
+                },
+                Issue::SchemaDrift {
+                    table: "components".into(),
+                    column: Some("entity_key".into())
+                },
+                Issue::SchemaDrift {
+                    table: "despawned".into(),
+                    column: None
+                },
+                Issue::SchemaDrift {
+                    table: "entities".into(),
+                    column: Some("campaign_id".into())
+                },
+                Issue::SchemaDrift {
+                    table: "entities".into(),
+                    column: Some("revision".into())
+                },
+                Issue::SchemaDrift {
+                    table: "entities".into(),
+                    column: Some("key".into())
+                },
+                Issue::SchemaDrift {
+                    table: "journal".into(),
+                    column: Some("campaign_id".into())
+                },
+                Issue::SchemaDrift {
+                    table: "journal".into(),
+                    column: Some("entity_key".into())
+                },
+                Issue::SchemaDrift {
+                    table: "keys".into(),
+                    column: None
+                },
+                Issue::SchemaDrift {
+                    table: "pack_hashes".into(),
+                    column: None
+                },
+                Issue::SchemaDrift {
+                    table: "prunes".into(),
+                    column: None
+                },
+                Issue::SchemaDrift {
+                    table: "relationships".into(),
+                    column: Some("revision".into())
+                },
+            ],
+            report.issues
+        );
+        assert_eq!(vec![Repair::Migrate], report.repairs());
+
+        store.repair(&report.repairs()).await.expect("should have repaired store");
+        let report = store.verify().await.expect("should have verified store");
+        assert!(report.is_clean(), "unexpected issues: {:?}", report.issues);
+        std::fs::remove_dir_all(older).expect("should have removed migrations directory");
+    }
+}
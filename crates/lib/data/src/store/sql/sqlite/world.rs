@@ -0,0 +1,946 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+use std::time::Instant;
+
+use libsql::{Row, Transaction};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::{
+    Persisted, sql,
+    store::{
+        Archived, Change, Gathered, Recorded, World,
+        diff::Relationship,
+        sql::{
+            SqlQuery,
+            sqlite::{Result, SqliteStore, cache::Table, store::IntoParams, uuid},
+        },
+    },
+};
+
+/// Creates an entity in a campaign. Entities, components and relationships are written with the revision of the next
+/// journal entry, so deltas can tell which rows changed since an entry, see [`Replicate`](crate::store::Replicate).
+const SPAWN: &str = "
+    INSERT INTO entities (id, campaign_id, revision) VALUES (?, ?, (SELECT COALESCE(MAX(seq), 0) + 1 FROM journal));
+";
+
+/// Tells whether an entity of a campaign holds any component.
+const EXISTS: &str = "
+    SELECT EXISTS (SELECT 1 FROM entities e JOIN components c ON c.entity_key = e.key WHERE e.id = ?1 AND e.campaign_id = ?2);
+";
+
+/// Attaches a component to an entity, replacing any component of the same kind.
+pub(super) const INSERT_COMPONENT: &str = "
+    INSERT INTO components (entity_id, kind, data, revision) VALUES (?, ?, ?, (SELECT COALESCE(MAX(seq), 0) + 1 FROM journal))
+    ON CONFLICT (entity_id, kind) DO UPDATE SET data = excluded.data, revision = excluded.revision;
+";
+
+/// Links two entities, ignoring relationships that already exist.
+pub(super) const RELATE: &str = "
+    INSERT OR IGNORE INTO relationships (source_id, target_id, kind, revision)
+    VALUES (?, ?, ?, (SELECT COALESCE(MAX(seq), 0) + 1 FROM journal));
+";
+
+/// Archives or restores an entity, stamping the time of its archival unless it was archived already.
+const ARCHIVE: &str = "
+    INSERT INTO components (entity_id, kind, data, revision)
+    VALUES (
+        ?1,
+        ?2,
+        json_object('archived', json(IIF(?3, 'true', 'false')), 'archived_at', IIF(?3, unixepoch(), NULL)),
+        (SELECT COALESCE(MAX(seq), 0) + 1 FROM journal)
+    )
+    ON CONFLICT (entity_id, kind) DO UPDATE SET data = excluded.data, revision = excluded.revision
+    WHERE NOT (?3 AND json_extract(components.data, '$.archived'));
+";
+
+/// Reads the relationships from and to an entity.
+const LINKS: &str = "SELECT source_id, target_id, kind FROM relationships WHERE source_id = ?1 OR target_id = ?1 ORDER BY rowid;";
+
+/// Removes the relationships from and to an entity.
+pub(super) const UNLINK: &str = "DELETE FROM relationships WHERE source_id = ?1 OR target_id = ?1;";
+
+/// Removes the components of an entity.
+pub(super) const STRIP: &str = "DELETE FROM components WHERE entity_id = ?;";
+
+/// Remembers that an entity was despawned, so that deltas carry its removal.
+pub(super) const DESPAWNED: &str = "
+    INSERT INTO despawned (entity_id, revision) VALUES (?, (SELECT COALESCE(MAX(seq), 0) + 1 FROM journal))
+    ON CONFLICT (entity_id) DO UPDATE SET revision = excluded.revision;
+";
+
+/// Appends an entry to the journal of a campaign and returns its sequence number.
+const RECORD: &str = "INSERT INTO journal (entity_id, kind, data, campaign_id) VALUES (?, ?, ?, ?) RETURNING seq;";
+
+/// Removes the journal entries of a kind recorded in a campaign before an entry.
+pub(super) const PRUNE: &str = "DELETE FROM journal WHERE kind = ?1 AND seq < ?2 AND campaign_id = ?3;";
+
+/// Remembers the entry before which a kind of journal entries was pruned, so that the holes left are not gaps.
+pub(super) const PRUNED: &str = "
+    INSERT INTO pruned (kind, before) VALUES (?1, ?2)
+    ON CONFLICT (kind) DO UPDATE SET before = MAX(before, excluded.before);
+";
+
+/// Label of the changes committed in one transaction, in the query metrics.
+const COMMIT: &str = "COMMIT";
+
+/// Reads the latest journal entries of a kind in a campaign, optionally only those about one entity, looked up by its
+/// key.
+const JOURNAL: &str = "
+    SELECT seq, entity_id, kind, data, recorded_at
+    FROM journal
+    WHERE campaign_id = ?4 AND kind = ?1 AND (?2 IS NULL OR entity_key = (SELECT key FROM keys WHERE id = ?2))
+    ORDER BY seq DESC
+    LIMIT ?3;
+";
+
+/// Reads the journal entries of a campaign between two sequence numbers, both excluded, the upper one being optional.
+const ENTRIES: &str = "
+    SELECT seq, entity_id, kind, data, recorded_at
+    FROM journal
+    WHERE campaign_id = ?3 AND seq > ?1 AND (?2 IS NULL OR seq < ?2)
+    ORDER BY seq;
+";
+
+/// Reads the components of a kind attached to the entities of a campaign, leaving out archived entities unless asked.
+/// Components are joined to their entities by their integer keys, which compare far cheaper than the ids.
+const ALL: &str = "
+    SELECT c.entity_id, c.data
+    FROM components c
+    JOIN entities e ON e.key = c.entity_key
+    WHERE c.kind = ?1 AND e.campaign_id = ?2 AND (?3 OR NOT EXISTS (
+        SELECT 1 FROM components a WHERE a.entity_key = c.entity_key AND a.kind = ?4 AND json_extract(a.data, '$.archived')
+    ))
+    ORDER BY c.rowid;
+";
+
+/// Reads the components of a kind attached to the entities of every campaign.
+const ACROSS: &str = "SELECT entity_id, data FROM components WHERE kind = ? ORDER BY rowid;";
+
+/// Remembers the entry before which a kind of journal entries of a campaign was pruned, so that deltas carry the
+/// removal. Written before the entries are removed, as they may include the latest one.
+pub(super) const PRUNES: &str = "
+    INSERT INTO prunes (campaign_id, kind, before, revision) VALUES (?1, ?2, ?3, (SELECT COALESCE(MAX(seq), 0) + 1 FROM journal))
+    ON CONFLICT (campaign_id, kind) DO UPDATE SET before = MAX(before, excluded.before), revision = excluded.revision;
+";
+
+/// Removes every entity of a campaign along with their components and relationships, and the journal of the campaign,
+/// in the order the foreign keys allow. Their integer keys are kept, as they are for despawned entities.
+const DROP_SCOPE: [&str; 6] = [
+    "DELETE FROM despawned WHERE entity_id IN (SELECT id FROM entities WHERE campaign_id = ?1);",
+    "DELETE FROM prunes WHERE campaign_id = ?1;",
+    "DELETE FROM components WHERE entity_id IN (SELECT id FROM entities WHERE campaign_id = ?1);",
+    "DELETE FROM relationships
+     WHERE source_id IN (SELECT id FROM entities WHERE campaign_id = ?1)
+        OR target_id IN (SELECT id FROM entities WHERE campaign_id = ?1);",
+    "DELETE FROM journal WHERE campaign_id = ?1;",
+    "DELETE FROM entities WHERE campaign_id = ?1;",
+];
+
+/// Reads when the last journal entry of a campaign was recorded.
+const LAST_RECORDED: &str = "SELECT MAX(recorded_at) FROM journal WHERE campaign_id = ?;";
+
+/// Reads components of several kinds attached to the entities of a campaign, along with their parent, in one query,
+/// leaving out archived entities. The kinds are passed as a JSON array.
+const GATHER: &str = "
+    SELECT c.entity_id, r.source_id, c.kind, c.data
+    FROM components c
+    JOIN entities e ON e.key = c.entity_key
+    LEFT JOIN relationships r ON r.target_id = c.entity_id AND r.kind = ?1
+    WHERE c.kind IN (SELECT value FROM json_each(?2)) AND e.campaign_id = ?3 AND NOT EXISTS (
+        SELECT 1 FROM components a WHERE a.entity_key = c.entity_key AND a.kind = ?4 AND json_extract(a.data, '$.archived')
+    )
+    ORDER BY c.rowid;
+";
+
+impl World for SqliteStore {
+    async fn spawn(&mut self) -> Result<Uuid> {
+        let id = Uuid::new_v4();
+        self.execute(&sql!(SPAWN, id, self.scope)).await?;
+
+        Ok(id)
+    }
+
+    async fn insert<C: Persisted>(&mut self, entity: Uuid, component: &C) -> Result<()> {
+        let data = self.seal(entity, C::KIND, serde_json::to_string(component)?)?;
+        self.execute(&sql!(INSERT_COMPONENT, entity, C::KIND, data)).await?;
+        self.cache.invalidate(&[Table::Components]);
+
+        Ok(())
+    }
+
+    async fn exists(&mut self, entity: Uuid) -> Result<bool> {
+        let found = self
+            .fetch_cached(&[Table::Components], &sql!(EXISTS, entity, self.scope), |row| Ok(row.get::<i64>(0)?))
+            .await?;
+
+        Ok(found.first().is_some_and(|exists| *exists != 0))
+    }
+
+    async fn get<C: Persisted>(&mut self, entity: Uuid) -> Result<Option<C>> {
+        let mut found = self
+            .fetch_cached(
+                &[Table::Components],
+                &sql!("SELECT data FROM components WHERE entity_id = ? AND kind = ?;", entity, C::KIND),
+                |row| Ok(row.get::<String>(0)?),
+            )
+            .await?;
+
+        found
+            .pop()
+            .map(|data| Ok(serde_json::from_str(&self.unseal(entity, C::KIND, data)?)?))
+            .transpose()
+    }
+
+    async fn all_with<C: Persisted>(&mut self, include_archived: bool) -> Result<Vec<(Uuid, C)>> {
+        let query = sql!(ALL, C::KIND, self.scope, u8::from(include_archived), Archived::KIND);
+        let found = self
+            .fetch_cached(&[Table::Components], &query, |row| Ok((uuid(row, 0)?, row.get::<String>(1)?)))
+            .await?;
+
+        found
+            .into_iter()
+            .map(|(entity, data)| Ok((entity, serde_json::from_str(&self.unseal(entity, C::KIND, data)?)?)))
+            .collect()
+    }
+
+    async fn sorted<C: Persisted>(&mut self, key: impl Fn(&C) -> &str + Send) -> Result<Vec<(Uuid, C)>> {
+        let mut found = self.all::<C>().await?;
+        self.collation.sort_by_key(&mut found, |(_, component)| key(component));
+
+        Ok(found)
+    }
+
+    async fn archive(&mut self, entity: Uuid) -> Result<()> {
+        self.execute(&sql!(ARCHIVE, entity, Archived::KIND, 1u8)).await?;
+        self.cache.invalidate(&[Table::Components]);
+
+        Ok(())
+    }
+
+    async fn restore(&mut self, entity: Uuid) -> Result<()> {
+        self.execute(&sql!(ARCHIVE, entity, Archived::KIND, 0u8)).await?;
+        self.cache.invalidate(&[Table::Components]);
+
+        Ok(())
+    }
+
+    async fn relate(&mut self, source: Uuid, target: Uuid, kind: &str) -> Result<()> {
+        self.execute(&sql!(RELATE, source, target, kind)).await?;
+        self.cache.invalidate(&[Table::Relationships]);
+
+        Ok(())
+    }
+
+    async fn related(&mut self, source: Uuid, kind: &str) -> Result<Vec<Uuid>> {
+        self.fetch_cached(
+            &[Table::Relationships],
+            &sql!(
+                "SELECT target_id FROM relationships WHERE source_id = ? AND kind = ? ORDER BY rowid;",
+                source,
+                kind
+            ),
+            |row| uuid(row, 0),
+        )
+        .await
+    }
+
+    async fn links(&mut self, entity: Uuid) -> Result<Vec<Relationship>> {
+        self.fetch_cached(&[Table::Relationships], &sql!(LINKS, entity), |row| {
+            Ok(Relationship {
+                source: uuid(row, 0)?,
+                target: uuid(row, 1)?,
+                kind: row.get(2)?,
+            })
+        })
+        .await
+    }
+
+    async fn despawn(&mut self, entity: Uuid) -> Result<()> {
+        self.commit(&[Change::despawn(entity)]).await?;
+
+        Ok(())
+    }
+
+    async fn gather(&mut self, kinds: &[&str], parent: &str) -> Result<Vec<Gathered>> {
+        let kinds = serde_json::to_string(kinds)?;
+        let gathered = self
+            .fetch_cached(
+                &[Table::Components, Table::Relationships],
+                &sql!(GATHER, parent, kinds, self.scope, Archived::KIND),
+                |row| {
+                    Ok(Gathered {
+                        entity: uuid(row, 0)?,
+                        parent: row.get::<Option<Vec<u8>>>(1)?.map(|bytes| Uuid::from_slice(&bytes)).transpose()?,
+                        kind: row.get(2)?,
+                        data: row.get(3)?,
+                    })
+                },
+            )
+            .await?;
+
+        gathered
+            .into_iter()
+            .map(|gathered| {
+                let data = self.unseal(gathered.entity, &gathered.kind, gathered.data)?;
+                Ok(Gathered { data, ..gathered })
+            })
+            .collect()
+    }
+
+    async fn record(&mut self, entity: Option<Uuid>, kind: &str, data: &impl Serialize) -> Result<i64> {
+        let data = serde_json::to_string(data)?;
+        let mut seq = self
+            .fetch(&sql!(RECORD, entity, kind, data, self.scope), |row| Ok(row.get::<i64>(0)?))
+            .await?;
+        self.cache.invalidate(&[Table::Journal]);
+
+        Ok(seq.pop().unwrap_or_default())
+    }
+
+    async fn journal(&mut self, entity: Option<Uuid>, kind: &str, limit: u32) -> Result<Vec<Recorded>> {
+        self.fetch_cached(&[Table::Journal], &sql!(JOURNAL, kind, entity, limit, self.scope), recorded)
+            .await
+    }
+
+    async fn journal_between(&mut self, after: i64, before: Option<i64>) -> Result<Vec<Recorded>> {
+        self.fetch_cached(&[Table::Journal], &sql!(ENTRIES, after, before, self.scope), recorded)
+            .await
+    }
+
+    /// Applies the changes in a single transaction, rolled back if any of them fails.
+    async fn commit(&mut self, changes: &[Change]) -> Result<Vec<i64>> {
+        let store = &*self;
+        let (seqs, written) = store.retrying(|| store.transact(changes)).await?;
+        self.cache.invalidate(&written);
+
+        Ok(seqs)
+    }
+}
+
+impl SqliteStore {
+    /// Writes the changes in a single transaction, returning the sequence numbers of the journal entries recorded and
+    /// the tables written.
+    async fn transact(&self, changes: &[Change]) -> Result<(Vec<i64>, Vec<Table>)> {
+        let start = Instant::now();
+        let conn = self.pool.get().await?;
+        let connected = Instant::now();
+        let tx = conn.transaction().await?;
+        let written = self.write(&tx, changes).await?;
+
+        tx.commit().await?;
+        self.observe(COMMIT, changes.len() as u64, connected - start, connected.elapsed());
+        Ok(written)
+    }
+
+    /// Writes the changes within the transaction, leaving it to the caller to commit, returning the sequence numbers of
+    /// the journal entries recorded and the tables written.
+    pub(super) async fn write(&self, tx: &Transaction, changes: &[Change]) -> Result<(Vec<i64>, Vec<Table>)> {
+        let mut seqs = Vec::new();
+        let mut written = Vec::new();
+        for change in changes {
+            let table = match change {
+                Change::Spawn { entity } => {
+                    let query = sql!(SPAWN, *entity, self.scope);
+                    tx.execute(query.query.as_str(), query.to_params()?).await?;
+                    Table::Components
+                }
+                Change::Insert { entity, kind, data } => {
+                    let data = self.seal(*entity, kind, data.clone())?;
+                    let query = sql!(INSERT_COMPONENT, *entity, kind.as_str(), data);
+                    tx.execute(query.query.as_str(), query.to_params()?).await?;
+                    Table::Components
+                }
+                Change::Relate { source, target, kind } => {
+                    let query = sql!(RELATE, *source, *target, kind.as_str());
+                    tx.execute(query.query.as_str(), query.to_params()?).await?;
+                    Table::Relationships
+                }
+                Change::Archive { entity, archived } => {
+                    let query = sql!(ARCHIVE, *entity, Archived::KIND, u8::from(*archived));
+                    tx.execute(query.query.as_str(), query.to_params()?).await?;
+                    Table::Components
+                }
+                Change::Despawn { entity } => {
+                    for query in [sql!(UNLINK, *entity), sql!(STRIP, *entity), sql!(DESPAWNED, *entity)] {
+                        tx.execute(query.query.as_str(), query.to_params()?).await?;
+                    }
+                    if !written.contains(&Table::Relationships) {
+                        written.push(Table::Relationships);
+                    }
+                    Table::Components
+                }
+                Change::Record { entity, kind, data } => {
+                    let query = sql!(RECORD, *entity, kind.as_str(), data.as_str(), self.scope);
+                    let mut rows = tx.query(query.query.as_str(), query.to_params()?).await?;
+                    if let Some(row) = rows.next().await? {
+                        seqs.push(row.get::<i64>(0)?);
+                    }
+                    Table::Journal
+                }
+                Change::Prune { kind, before } => {
+                    for query in [
+                        sql!(PRUNES, self.scope, kind.as_str(), *before),
+                        sql!(PRUNE, kind.as_str(), *before, self.scope),
+                        sql!(PRUNED, kind.as_str(), *before),
+                    ] {
+                        tx.execute(query.query.as_str(), query.to_params()?).await?;
+                    }
+                    Table::Journal
+                }
+            };
+            if !written.contains(&table) {
+                written.push(table);
+            }
+        }
+
+        Ok((seqs, written))
+    }
+
+    /// Spawns an entity rooting a new campaign and switches the store to it, so the entities and journal entries that
+    /// follow belong to the new campaign. Returns the id of the entity, which is also the id of the campaign.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SqliteError::LibSql`](crate::store::sql::sqlite::SqliteError::LibSql) if the entity cannot be written.
+    pub async fn spawn_scope(&mut self) -> Result<Uuid> {
+        let id = Uuid::new_v4();
+        self.execute(&sql!(SPAWN, id, id)).await?;
+        self.scope = id;
+
+        Ok(id)
+    }
+
+    /// Deletes a campaign held in the database, whatever the scope of the store: its entities, their components and
+    /// relationships, and its journal, in a single transaction. Returns the number of rows deleted.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SqliteError::LibSql`](crate::store::sql::sqlite::SqliteError::LibSql) if the rows cannot be deleted,
+    /// in which case none are.
+    pub async fn drop_scope(&mut self, campaign: Uuid) -> Result<u64> {
+        let conn = self.pool.get().await?;
+        let tx = conn.transaction().await?;
+        let mut deleted = 0;
+        for statement in DROP_SCOPE {
+            let query = sql!(statement, campaign);
+            deleted += tx.execute(query.query.as_str(), query.to_params()?).await?;
+        }
+        tx.commit().await?;
+        self.cache.clear();
+
+        Ok(deleted)
+    }
+
+    /// Returns when the last journal entry of the campaign the store is scoped to was recorded, in seconds since the
+    /// Unix epoch, or `None` if its journal is empty.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SqliteError::LibSql`](crate::store::sql::sqlite::SqliteError::LibSql) if the journal cannot be read.
+    pub async fn last_recorded(&mut self) -> Result<Option<i64>> {
+        let recorded = self.fetch(&sql!(LAST_RECORDED, self.scope), |row| Ok(row.get::<Option<i64>>(0)?)).await?;
+
+        Ok(recorded.into_iter().flatten().next())
+    }
+
+    /// Returns the components of a kind attached to the entities of every campaign in the database, whatever the scope
+    /// of the store.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SqliteError::LibSql`](crate::store::sql::sqlite::SqliteError::LibSql) if the components cannot be read,
+    /// or [`SqliteError::Json`](crate::store::sql::sqlite::SqliteError::Json) if they cannot be decoded.
+    pub async fn across<C: Persisted>(&mut self) -> Result<Vec<(Uuid, C)>> {
+        let found = self
+            .fetch_cached(&[Table::Components], &sql!(ACROSS, C::KIND), |row| {
+                Ok((uuid(row, 0)?, row.get::<String>(1)?))
+            })
+            .await?;
+
+        found
+            .into_iter()
+            .map(|(entity, data)| Ok((entity, serde_json::from_str(&self.unseal(entity, C::KIND, data)?)?)))
+            .collect()
+    }
+}
+
+/// Maps a row of [`JOURNAL`] or [`ENTRIES`] into a journal entry.
+pub(super) fn recorded(row: &Row) -> Result<Recorded> {
+    Ok(Recorded {
+        seq: row.get(0)?,
+        entity: row.get::<Option<Vec<u8>>>(1)?.map(|bytes| Uuid::from_slice(&bytes)).transpose()?,
+        kind: row.get(2)?,
+        data: row.get(3)?,
+        recorded_at: row.get(4)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::{
+        Component,
+        collation::Collation,
+        store::{
+            Migrator,
+            sql::sqlite::{MIGRATIONS, fixture::memory_store},
+        },
+    };
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Stress(u8);
+
+    impl Component for Stress {}
+    impl Persisted for Stress {
+        const KIND: &'static str = "stress";
+    }
+
+    #[tokio::test]
+    async fn should_drop_campaign_leaving_others() {
+        let mut store = memory_store(vec![]).await;
+        let kept = store.spawn_scope().await.expect("should have spawned campaign");
+        let pc = store.spawn().await.expect("should have spawned entity");
+        store.insert(pc, &Stress(2)).await.expect("should have inserted component");
+        store.record(Some(pc), "stress.updated", &2).await.expect("should have recorded entry");
+        let dropped = store.spawn_scope().await.expect("should have spawned campaign");
+        let npc = store.spawn().await.expect("should have spawned entity");
+        store.insert(npc, &Stress(1)).await.expect("should have inserted component");
+        store.relate(dropped, npc, "member").await.expect("should have related entities");
+        store.record(Some(npc), "stress.updated", &1).await.expect("should have recorded entry");
+
+        assert!(store.drop_scope(dropped).await.expect("should have dropped campaign") > 0);
+
+        assert_eq!(
+            vec![(pc, Stress(2))],
+            store.across::<Stress>().await.expect("should have read components")
+        );
+        assert_eq!(None, store.last_recorded().await.expect("should have read journal"));
+        store.scope_to(kept);
+        assert!(store.last_recorded().await.expect("should have read journal").is_some());
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Name(String);
+
+    impl Component for Name {}
+    impl Persisted for Name {
+        const KIND: &'static str = "name";
+    }
+
+    #[tokio::test]
+    async fn should_sort_components_by_the_collation_of_the_store() {
+        let mut store = memory_store(vec![]).await;
+        for name in ["Öland", "Zinkgruvan", "Orsa"] {
+            let entity = store.spawn().await.expect("should have spawned entity");
+            store
+                .insert(entity, &Name(name.to_string()))
+                .await
+                .expect("should have inserted component");
+        }
+
+        let names = |sorted: Vec<(Uuid, Name)>| sorted.into_iter().map(|(_, Name(name))| name).collect::<Vec<_>>();
+        let sorted = store.sorted::<Name>(|Name(name)| name).await.expect("should have sorted components");
+        assert_eq!(vec!["Öland", "Orsa", "Zinkgruvan"], names(sorted));
+
+        store.collate_with(Collation::new("sv").expect("should have created swedish collation"));
+        let sorted = store.sorted::<Name>(|Name(name)| name).await.expect("should have sorted components");
+        assert_eq!(vec!["Orsa", "Zinkgruvan", "Öland"], names(sorted));
+    }
+
+    #[tokio::test]
+    async fn should_insert_and_get_components() {
+        let mut store = memory_store(vec![]).await;
+        let pc = store.spawn().await.expect("should have spawned entity");
+        let npc = store.spawn().await.expect("should have spawned entity");
+
+        store.insert(pc, &Stress(2)).await.expect("should have inserted component");
+        store.insert(pc, &Stress(3)).await.expect("should have replaced component");
+        store.insert(npc, &Stress(1)).await.expect("should have inserted component");
+
+        assert_eq!(Some(Stress(3)), store.get(pc).await.expect("should have read component"));
+        assert_eq!(
+            vec![(pc, Stress(3)), (npc, Stress(1))],
+            store.all::<Stress>().await.expect("should have read components")
+        );
+    }
+
+    #[tokio::test]
+    async fn should_return_none_given_missing_component() {
+        let mut store = memory_store(vec![]).await;
+        let pc = store.spawn().await.expect("should have spawned entity");
+
+        assert_eq!(None, store.get::<Stress>(pc).await.expect("should have read component"));
+    }
+
+    #[tokio::test]
+    async fn should_relate_entities() {
+        let mut store = memory_store(vec![]).await;
+        let pc = store.spawn().await.expect("should have spawned entity");
+        let rival = store.spawn().await.expect("should have spawned entity");
+
+        store.relate(pc, rival, "rival").await.expect("should have related entities");
+        store.relate(pc, rival, "rival").await.expect("should have ignored duplicate");
+
+        assert_eq!(vec![rival], store.related(pc, "rival").await.expect("should have read relationships"));
+        assert_eq!(
+            Vec::<Uuid>::new(),
+            store.related(pc, "friend").await.expect("should have read relationships")
+        );
+    }
+
+    #[tokio::test]
+    async fn should_despawn_entity_keeping_its_journal() {
+        let mut store = memory_store(vec![]).await;
+        let crew = store.spawn().await.expect("should have spawned entity");
+        let npc = store.spawn().await.expect("should have spawned entity");
+        let rival = store.spawn().await.expect("should have spawned entity");
+        store.insert(npc, &Stress(1)).await.expect("should have inserted component");
+        store.relate(crew, npc, "contact").await.expect("should have related entities");
+        store.relate(npc, rival, "rival").await.expect("should have related entities");
+        store.record(Some(npc), "stress", &Stress(1)).await.expect("should have recorded entry");
+
+        assert_eq!(
+            vec![
+                Relationship {
+                    source: crew,
+                    target: npc,
+                    kind: "contact".into()
+                },
+                Relationship {
+                    source: npc,
+                    target: rival,
+                    kind: "rival".into()
+                },
+            ],
+            store.links(npc).await.expect("should have read relationships")
+        );
+
+        store.despawn(npc).await.expect("should have despawned entity");
+
+        assert_eq!(
+            Vec::<Relationship>::new(),
+            store.links(npc).await.expect("should have read relationships")
+        );
+        assert_eq!(None, store.get::<Stress>(npc).await.expect("should have read component"));
+        assert_eq!(
+            Some(npc),
+            store.journal(None, "stress", 10).await.expect("should have read journal")[0].entity
+        );
+    }
+
+    #[tokio::test]
+    async fn should_gather_components_with_parent() {
+        let mut store = memory_store(vec![]).await;
+        let crew = store.spawn().await.expect("should have spawned entity");
+        let pc = store.spawn().await.expect("should have spawned entity");
+        let npc = store.spawn().await.expect("should have spawned entity");
+
+        store.insert(pc, &Stress(2)).await.expect("should have inserted component");
+        store.insert(npc, &Stress(1)).await.expect("should have inserted component");
+        store.relate(crew, pc, "member").await.expect("should have related entities");
+        store.relate(crew, npc, "contact").await.expect("should have related entities");
+
+        let gathered = store
+            .gather(&[Stress::KIND, "harm"], "member")
+            .await
+            .expect("should have gathered components");
+
+        assert_eq!(
+            vec![
+                Gathered {
+                    entity: pc,
+                    parent: Some(crew),
+                    kind: "stress".into(),
+                    data: "2".into()
+                },
+                Gathered {
+                    entity: npc,
+                    parent: None,
+                    kind: "stress".into(),
+                    data: "1".into()
+                },
+            ],
+            gathered
+        );
+        assert!(gathered[0].is::<Stress>());
+        assert_eq!(Stress(2), gathered[0].decode::<Stress>().expect("should have decoded component"));
+    }
+
+    #[tokio::test]
+    async fn should_leave_out_archived_entities_unless_included() {
+        let mut store = memory_store(vec![]).await;
+        let pc = store.spawn().await.expect("should have spawned entity");
+        let npc = store.spawn().await.expect("should have spawned entity");
+        store.insert(pc, &Stress(2)).await.expect("should have inserted component");
+        store.insert(npc, &Stress(1)).await.expect("should have inserted component");
+
+        store.archive(npc).await.expect("should have archived entity");
+        let Some(Archived {
+            archived: true,
+            archived_at: Some(at),
+        }) = store.get::<Archived>(npc).await.expect("should have read archival")
+        else {
+            panic!("should have stamped archival");
+        };
+        store.commit(&[Change::archive(npc)]).await.expect("should have archived entity again");
+
+        assert_eq!(vec![(pc, Stress(2))], store.all::<Stress>().await.expect("should have read components"));
+        assert_eq!(
+            vec![(pc, Stress(2)), (npc, Stress(1))],
+            store.all_with::<Stress>(true).await.expect("should have read components")
+        );
+        assert_eq!(
+            vec![pc],
+            store
+                .gather(&[Stress::KIND], "")
+                .await
+                .expect("should have gathered components")
+                .iter()
+                .map(|gathered| gathered.entity)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(Some(Stress(1)), store.get(npc).await.expect("should have read component"));
+        assert_eq!(
+            Some(Archived {
+                archived: true,
+                archived_at: Some(at)
+            }),
+            store.get::<Archived>(npc).await.expect("should have read archival")
+        );
+
+        store.restore(npc).await.expect("should have restored entity");
+        assert_eq!(
+            vec![(pc, Stress(2)), (npc, Stress(1))],
+            store.all::<Stress>().await.expect("should have read components")
+        );
+        assert_eq!(
+            Some(Archived::default()),
+            store.get::<Archived>(npc).await.expect("should have read archival")
+        );
+    }
+
+    #[tokio::test]
+    async fn should_read_latest_journal_entries() {
+        let mut store = memory_store(vec![]).await;
+        let pc = store.spawn().await.expect("should have spawned entity");
+        let npc = store.spawn().await.expect("should have spawned entity");
+
+        store.record(Some(pc), "stress", &Stress(1)).await.expect("should have recorded entry");
+        store.record(Some(npc), "stress", &Stress(2)).await.expect("should have recorded entry");
+        store.record(None, "harm", &Stress(3)).await.expect("should have recorded entry");
+        store.record(Some(pc), "stress", &Stress(4)).await.expect("should have recorded entry");
+
+        let latest = store.journal(None, "stress", 2).await.expect("should have read journal");
+        assert_eq!(vec![4, 2], latest.iter().map(|entry| entry.seq).collect::<Vec<_>>());
+        assert_eq!(Some(pc), latest[0].entity);
+        assert_eq!(Stress(4), latest[0].decode::<Stress>().expect("should have decoded entry"));
+
+        let about = store.journal(Some(pc), "stress", 10).await.expect("should have read journal");
+        assert_eq!(vec![4, 1], about.iter().map(|entry| entry.seq).collect::<Vec<_>>());
+
+        let between = store.journal_between(1, Some(4)).await.expect("should have read journal");
+        assert_eq!(vec![2, 3], between.iter().map(|entry| entry.seq).collect::<Vec<_>>());
+        let after = store.journal_between(2, None).await.expect("should have read journal");
+        assert_eq!(vec![3, 4], after.iter().map(|entry| entry.seq).collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn should_commit_changes_atomically() {
+        let mut store = memory_store(vec![]).await;
+        let pc = store.spawn().await.expect("should have spawned entity");
+        let rival = store.spawn().await.expect("should have spawned entity");
+
+        let seqs = store
+            .commit(&[
+                Change::insert(pc, &Stress(4)).expect("should have serialized component"),
+                Change::relate(pc, rival, "rival"),
+                Change::record(Some(pc), "stress", &Stress(4)).expect("should have serialized event"),
+            ])
+            .await
+            .expect("should have committed changes");
+
+        assert_eq!(vec![1], seqs);
+        assert_eq!(Some(Stress(4)), store.get(pc).await.expect("should have read component"));
+        assert_eq!(vec![rival], store.related(pc, "rival").await.expect("should have read relationships"));
+
+        let ghost = Uuid::new_v4();
+        store
+            .commit(&[
+                Change::insert(pc, &Stress(7)).expect("should have serialized component"),
+                Change::relate(pc, ghost, "rival"),
+            ])
+            .await
+            .expect_err("should have failed to relate missing entity");
+
+        assert_eq!(Some(Stress(4)), store.get(pc).await.expect("should have read component"));
+    }
+
+    #[tokio::test]
+    async fn should_spawn_entity_along_with_its_components() {
+        let mut store = memory_store(vec![]).await;
+        let (pc, orphan) = (Uuid::new_v4(), Uuid::new_v4());
+
+        store
+            .commit(&[
+                Change::spawn(pc),
+                Change::insert(pc, &Stress(2)).expect("should have serialized component"),
+            ])
+            .await
+            .expect("should have committed changes");
+        store
+            .commit(&[Change::spawn(orphan), Change::relate(orphan, Uuid::new_v4(), "rival")])
+            .await
+            .expect_err("should have failed to relate missing entity");
+
+        assert_eq!(Some(Stress(2)), store.get(pc).await.expect("should have read component"));
+        store
+            .insert(orphan, &Stress(1))
+            .await
+            .expect_err("should have rolled the entity back along with the changeset");
+    }
+
+    #[tokio::test]
+    async fn should_read_writes_of_another_store_on_the_same_database() {
+        let dir = env::temp_dir().join(format!("darkforge-writers-{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).expect("should have created directory");
+        let path = dir.join("campaign.db");
+        let mut forge = SqliteStore::open(&path).await.expect("should have opened store");
+        forge.apply(MIGRATIONS).await.expect("should have applied migrations");
+        let mut sheet = SqliteStore::open(&path).await.expect("should have opened store");
+        let pc = forge.spawn().await.expect("should have spawned entity");
+
+        forge.insert(pc, &Stress(1)).await.expect("should have inserted component");
+        assert_eq!(Some(Stress(1)), forge.get(pc).await.expect("should have read component"));
+        sheet.insert(pc, &Stress(3)).await.expect("should have inserted component");
+
+        assert_eq!(Some(Stress(3)), forge.get(pc).await.expect("should have read the other store's write"));
+        drop((forge, sheet));
+        fs::remove_dir_all(dir).expect("should have removed database");
+    }
+
+    #[tokio::test]
+    async fn should_prune_journal_entries_of_kind_before_entry() {
+        let mut store = memory_store(vec![]).await;
+        let pc = store.spawn().await.expect("should have spawned entity");
+
+        store.record(Some(pc), "stress", &Stress(1)).await.expect("should have recorded entry");
+        store.record(None, "harm", &Stress(2)).await.expect("should have recorded entry");
+        store.record(Some(pc), "stress", &Stress(3)).await.expect("should have recorded entry");
+        store.record(Some(pc), "stress", &Stress(4)).await.expect("should have recorded entry");
+        assert_eq!(3, store.journal(None, "stress", 10).await.expect("should have read journal").len());
+
+        store.commit(&[Change::prune("stress", 4)]).await.expect("should have pruned journal");
+
+        let kept = store.journal_between(0, None).await.expect("should have read journal");
+        assert_eq!(vec![2, 4], kept.iter().map(|entry| entry.seq).collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn should_read_fresh_data_after_writes() {
+        let mut store = memory_store(vec![]).await;
+        let pc = store.spawn().await.expect("should have spawned entity");
+        let rival = store.spawn().await.expect("should have spawned entity");
+
+        store.insert(pc, &Stress(1)).await.expect("should have inserted component");
+        assert_eq!(Some(Stress(1)), store.get(pc).await.expect("should have read component"));
+        assert_eq!(
+            Vec::<Uuid>::new(),
+            store.related(pc, "rival").await.expect("should have read relationships")
+        );
+        assert_eq!(0, store.journal(None, "stress", 10).await.expect("should have read journal").len());
+
+        store
+            .commit(&[
+                Change::insert(pc, &Stress(5)).expect("should have serialized component"),
+                Change::relate(pc, rival, "rival"),
+                Change::record(Some(pc), "stress", &Stress(5)).expect("should have serialized event"),
+            ])
+            .await
+            .expect("should have committed changes");
+
+        assert_eq!(Some(Stress(5)), store.get(pc).await.expect("should have read component"));
+        assert_eq!(vec![rival], store.related(pc, "rival").await.expect("should have read relationships"));
+        assert_eq!(1, store.journal(None, "stress", 10).await.expect("should have read journal").len());
+    }
+
+    #[tokio::test]
+    async fn should_read_journal_about_despawned_entity() {
+        let mut store = memory_store(vec![]).await;
+        let pc = store.spawn().await.expect("should have spawned entity");
+        store.insert(pc, &Stress(1)).await.expect("should have inserted component");
+        store.record(Some(pc), "stress", &Stress(1)).await.expect("should have recorded entry");
+
+        store.despawn(pc).await.expect("should have despawned entity");
+
+        let about = store.journal(Some(pc), "stress", 10).await.expect("should have read journal");
+        assert_eq!(vec![Some(pc)], about.iter().map(|entry| entry.entity).collect::<Vec<_>>());
+        assert_eq!(
+            Vec::<(Uuid, Stress)>::new(),
+            store.all::<Stress>().await.expect("should have read components")
+        );
+    }
+
+    #[tokio::test]
+    async fn should_record_journal_entries_in_sequence() {
+        let mut store = memory_store(vec![]).await;
+        let pc = store.spawn().await.expect("should have spawned entity");
+
+        let first = store.record(Some(pc), "stress", &Stress(2)).await.expect("should have recorded entry");
+        let second = store.record(None, "session", &()).await.expect("should have recorded entry");
+
+        assert_eq!((1, 2), (first, second));
+    }
+
+    #[tokio::test]
+    async fn should_scope_entities_and_journal_by_campaign() {
+        let mut store = memory_store(vec![]).await;
+        let first = store.spawn_scope().await.expect("should have spawned campaign");
+        let pc = store.spawn().await.expect("should have spawned entity");
+        store.insert(pc, &Stress(2)).await.expect("should have inserted component");
+        store.record(Some(pc), "stress", &Stress(2)).await.expect("should have recorded entry");
+
+        let second = store.spawn_scope().await.expect("should have spawned campaign");
+        let npc = store.spawn().await.expect("should have spawned entity");
+        store.insert(npc, &Stress(5)).await.expect("should have inserted component");
+        store.record(Some(npc), "stress", &Stress(5)).await.expect("should have recorded entry");
+
+        assert_eq!(second, store.scope());
+        assert_eq!(vec![(npc, Stress(5))], store.all::<Stress>().await.expect("should have read components"));
+        assert_eq!(
+            vec![2],
+            store
+                .journal_between(0, None)
+                .await
+                .expect("should have read journal")
+                .iter()
+                .map(|entry| entry.seq)
+                .collect::<Vec<_>>()
+        );
+
+        store.scope_to(first);
+        assert_eq!(vec![(pc, Stress(2))], store.all::<Stress>().await.expect("should have read components"));
+        assert_eq!(
+            Some(pc),
+            store.journal(None, "stress", 10).await.expect("should have read journal")[0].entity
+        );
+        assert_eq!(
+            vec![(pc, Stress(2)), (npc, Stress(5))],
+            store.across::<Stress>().await.expect("should have read components")
+        );
+    }
+}
@@ -0,0 +1,229 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! Integrity checking for stores.
+//!
+//! A [`Report`] lists every [`Issue`] found while verifying a store. Most issues can be fixed automatically, in which
+//! case [`Issue::repair`] suggests the [`Repair`] that the store will apply when asked to.
+
+use std::fmt::{self, Display, Formatter};
+
+use uuid::Uuid;
+
+/// A single integrity problem found in a store.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Issue {
+    /// A row references a parent row that does not exist.
+    ForeignKeyViolation {
+        /// The table containing the offending row.
+        table: String,
+        /// The row id of the offending row.
+        row: i64,
+        /// The table the row should have referenced.
+        parent: String,
+    },
+    /// A component is attached to an entity that does not exist.
+    OrphanedComponent {
+        /// The missing entity.
+        entity: Uuid,
+        /// The kind of component.
+        kind: String,
+    },
+    /// A relationship points from or to an entity that does not exist.
+    DanglingRelationship {
+        /// The source of the relationship.
+        source: Uuid,
+        /// The target of the relationship.
+        target: Uuid,
+        /// The kind of relationship.
+        kind: String,
+    },
+    /// The journal sequence skips one or more entries.
+    JournalGap {
+        /// The last sequence number before the gap.
+        after: i64,
+        /// The first sequence number after the gap.
+        before: i64,
+    },
+    /// A table or column declared by the schema registry is missing from the database, or holds another type.
+    SchemaDrift {
+        /// The table missing, or holding the column.
+        table: String,
+        /// The column, if not the whole table.
+        column: Option<String>,
+    },
+}
+
+/// An action that fixes an [`Issue`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Repair {
+    /// Delete the row with the given id from the given table.
+    DeleteRow {
+        /// The table to delete from.
+        table: String,
+        /// The row id to delete.
+        row: i64,
+    },
+    /// Delete the component of the given kind from the given entity.
+    DeleteComponent {
+        /// The entity the component is attached to.
+        entity: Uuid,
+        /// The kind of component.
+        kind: String,
+    },
+    /// Delete the relationship between the given entities.
+    DeleteRelationship {
+        /// The source of the relationship.
+        source: Uuid,
+        /// The target of the relationship.
+        target: Uuid,
+        /// The kind of relationship.
+        kind: String,
+    },
+    /// Fill the journal gap with placeholder entries so the sequence is contiguous again.
+    FillJournalGap {
+        /// The last sequence number before the gap.
+        after: i64,
+        /// The first sequence number after the gap.
+        before: i64,
+    },
+    /// Apply the migrations not yet applied, which add the tables and columns an older database lacks.
+    Migrate,
+}
+
+/// Result of verifying a store.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Report {
+    /// The issues found, in the order they were detected.
+    pub issues: Vec<Issue>,
+}
+
+impl Issue {
+    /// Returns the repair that fixes this issue.
+    #[must_use]
+    pub fn repair(&self) -> Repair {
+        match self {
+            Issue::ForeignKeyViolation { table, row, .. } => Repair::DeleteRow {
+                table: table.clone(),
+                row: *row,
+            },
+            Issue::OrphanedComponent { entity, kind } => Repair::DeleteComponent {
+                entity: *entity,
+                kind: kind.clone(),
+            },
+            Issue::DanglingRelationship { source, target, kind } => Repair::DeleteRelationship {
+                source: *source,
+                target: *target,
+                kind: kind.clone(),
+            },
+            Issue::JournalGap { after, before } => Repair::FillJournalGap {
+                after: *after,
+                before: *before,
+            },
+            Issue::SchemaDrift { .. } => Repair::Migrate,
+        }
+    }
+}
+
+impl Report {
+    /// Returns true if no issues were found.
+    #[must_use]
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// Returns the repairs that fix every issue in the report, migrating at most once.
+    #[must_use]
+    pub fn repairs(&self) -> Vec<Repair> {
+        let mut repairs = Vec::new();
+        for repair in self.issues.iter().map(Issue::repair) {
+            if repair != Repair::Migrate || !repairs.contains(&Repair::Migrate) {
+                repairs.push(repair);
+            }
+        }
+
+        repairs
+    }
+}
+
+impl Display for Issue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Issue::ForeignKeyViolation { table, row, parent } => {
+                write!(f, "row {row} in {table} references a missing row in {parent}")
+            }
+            Issue::OrphanedComponent { entity, kind } => write!(f, "component {kind} is attached to missing entity {entity}"),
+            Issue::DanglingRelationship { source, target, kind } => {
+                write!(f, "relationship {kind} from {source} to {target} references a missing entity")
+            }
+            Issue::JournalGap { after, before } => write!(f, "journal skips from entry {after} to entry {before}"),
+            Issue::SchemaDrift { table, column: Some(column) } => {
+                write!(f, "column {column} of table {table} is missing or of another type")
+            }
+            Issue::SchemaDrift { table, column: None } => write!(f, "table {table} is missing"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+    use uuid::uuid;
+
+    use super::*;
+
+    const ID: Uuid = uuid!("F9168C5E-FEB2-4FAA-B6BF-329BF39FA1E4");
+    const OTHER: Uuid = uuid!("0B1C0B45-7D1F-4C55-9C2F-5E6E5F1B3A10");
+
+    #[rstest]
+    #[case::foreign_key(
+        Issue::ForeignKeyViolation { table: "components".into(), row: 3, parent: "entities".into() },
+        Repair::DeleteRow { table: "components".into(), row: 3 }
+    )]
+    #[case::orphaned_component(
+        Issue::OrphanedComponent { entity: ID, kind: "stress".into() },
+        Repair::DeleteComponent { entity: ID, kind: "stress".into() }
+    )]
+    #[case::dangling_relationship(
+        Issue::DanglingRelationship { source: ID, target: OTHER, kind: "rival".into() },
+        Repair::DeleteRelationship { source: ID, target: OTHER, kind: "rival".into() }
+    )]
+    #[case::journal_gap(Issue::JournalGap { after: 4, before: 7 }, Repair::FillJournalGap { after: 4, before: 7 })]
+    #[case::schema_drift(Issue::SchemaDrift { table: "journal".into(), column: Some("campaign_id".into()) }, Repair::Migrate)]
+    fn should_suggest_repair_for_issue(#[case] issue: Issue, #[case] expect: Repair) {
+        assert_eq!(expect, issue.repair());
+    }
+
+    #[test]
+    fn should_migrate_once_for_all_schema_drift() {
+        let report = Report {
+            issues: vec![
+                Issue::SchemaDrift {
+                    table: "entities".into(),
+                    column: Some("campaign_id".into()),
+                },
+                Issue::JournalGap { after: 4, before: 7 },
+                Issue::SchemaDrift {
+                    table: "pruned".into(),
+                    column: None,
+                },
+            ],
+        };
+
+        assert_eq!(vec![Repair::Migrate, Repair::FillJournalGap { after: 4, before: 7 }], report.repairs());
+    }
+
+    #[test]
+    fn should_be_clean_given_no_issues() {
+        assert!(Report::default().is_clean());
+    }
+}
@@ -0,0 +1,70 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+#ifndef DARKFORGE_H
+#define DARKFORGE_H
+
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define DARKFORGE_MAX_DICE 10
+
+#define DARKFORGE_OK 0
+#define DARKFORGE_ERR_NULL -1
+#define DARKFORGE_ERR_POOL -2
+#define DARKFORGE_ERR_POSITION -3
+#define DARKFORGE_ERR_EFFECT -4
+
+#define DARKFORGE_POSITION_CONTROLLED 0
+#define DARKFORGE_POSITION_RISKY 1
+#define DARKFORGE_POSITION_DESPERATE 2
+
+#define DARKFORGE_EFFECT_ZERO 0
+#define DARKFORGE_EFFECT_LIMITED 1
+#define DARKFORGE_EFFECT_STANDARD 2
+#define DARKFORGE_EFFECT_GREAT 3
+#define DARKFORGE_EFFECT_EXTREME 4
+
+#define DARKFORGE_OUTCOME_FAILURE 0
+#define DARKFORGE_OUTCOME_PARTIAL 1
+#define DARKFORGE_OUTCOME_SUCCESS 2
+#define DARKFORGE_OUTCOME_CRITICAL 3
+
+/* The result of an action roll. */
+typedef struct DarkforgeRollResult {
+    /* The dice kept, the first dice_count are set. */
+    uint8_t dice[DARKFORGE_MAX_DICE];
+    /* The number of dice kept. */
+    uint8_t dice_count;
+    /* One of the DARKFORGE_OUTCOME_* constants. */
+    int32_t outcome;
+    /* One of the DARKFORGE_EFFECT_* constants, raised by a level on a critical. */
+    int32_t effect;
+    /* The level of harm the position calls for if the roll fell short, from 1 to 3, or 0 on a success. */
+    uint8_t harm;
+} DarkforgeRollResult;
+
+/*
+ * Rolls an action of pool dice at the given position and effect, writing the result to out_result. A pool of zero
+ * rolls two dice and keeps the lowest. Returns DARKFORGE_OK, or one of the DARKFORGE_ERR_* constants if the arguments
+ * are invalid, in which case out_result is left untouched.
+ */
+int32_t darkforge_roll_action(uint8_t pool, int32_t position, int32_t effect, DarkforgeRollResult *out_result);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* DARKFORGE_H */
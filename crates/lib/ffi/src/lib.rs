@@ -0,0 +1,217 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! # Dark Forge FFI
+//!
+//! A minimal C ABI over the Dark Forge rules engine, for engines other than Godot, e.g. Unity, Unreal or Godot Mono,
+//! to roll actions by the same rules without going through the Godot extension. The matching declarations are in
+//! `include/darkforge.h`.
+//!
+//! Positions, effects and outcomes cross the boundary as plain integers, ordered from safest to most dangerous, from
+//! lowest to highest effect and from worst to best outcome respectively, see [`DARKFORGE_POSITION_RISKY`] and its
+//! siblings.
+
+use darkforge::{
+    action::{Outcome, roll_pool},
+    context::Position,
+    effect::Effect,
+};
+use darkforge_rng::dice::{D6, Dice};
+
+/// The most dice an action can roll.
+pub const DARKFORGE_MAX_DICE: usize = 10;
+
+/// The roll succeeded.
+pub const DARKFORGE_OK: i32 = 0;
+/// The result pointer is null.
+pub const DARKFORGE_ERR_NULL: i32 = -1;
+/// The pool exceeds [`DARKFORGE_MAX_DICE`].
+pub const DARKFORGE_ERR_POOL: i32 = -2;
+/// The position is not one of the `DARKFORGE_POSITION_*` constants.
+pub const DARKFORGE_ERR_POSITION: i32 = -3;
+/// The effect is not one of the `DARKFORGE_EFFECT_*` constants.
+pub const DARKFORGE_ERR_EFFECT: i32 = -4;
+
+/// The character has a dominant advantage.
+pub const DARKFORGE_POSITION_CONTROLLED: i32 = 0;
+/// The character acts on even terms.
+pub const DARKFORGE_POSITION_RISKY: i32 = 1;
+/// The character overreaches.
+pub const DARKFORGE_POSITION_DESPERATE: i32 = 2;
+
+/// The action accomplishes nothing.
+pub const DARKFORGE_EFFECT_ZERO: i32 = 0;
+/// The action accomplishes less than hoped.
+pub const DARKFORGE_EFFECT_LIMITED: i32 = 1;
+/// The action accomplishes what one would expect.
+pub const DARKFORGE_EFFECT_STANDARD: i32 = 2;
+/// The action accomplishes more than usual.
+pub const DARKFORGE_EFFECT_GREAT: i32 = 3;
+/// The action accomplishes far more than usual.
+pub const DARKFORGE_EFFECT_EXTREME: i32 = 4;
+
+/// The action failed.
+pub const DARKFORGE_OUTCOME_FAILURE: i32 = 0;
+/// The action succeeded at a cost.
+pub const DARKFORGE_OUTCOME_PARTIAL: i32 = 1;
+/// The action succeeded.
+pub const DARKFORGE_OUTCOME_SUCCESS: i32 = 2;
+/// The action succeeded with increased effect.
+pub const DARKFORGE_OUTCOME_CRITICAL: i32 = 3;
+
+/// The result of an action roll.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DarkforgeRollResult {
+    /// The dice kept, the first `dice_count` are set.
+    pub dice: [u8; DARKFORGE_MAX_DICE],
+    /// The number of dice kept.
+    pub dice_count: u8,
+    /// One of the `DARKFORGE_OUTCOME_*` constants.
+    pub outcome: i32,
+    /// One of the `DARKFORGE_EFFECT_*` constants, raised by a level on a critical.
+    pub effect: i32,
+    /// The level of harm the position calls for if the roll fell short, from 1 (lesser) to 3 (severe), or 0 on a
+    /// success.
+    pub harm: u8,
+}
+
+/// Rolls an action of `pool` dice at the given position and effect, writing the result to `out_result`. A pool of zero
+/// rolls two dice and keeps the lowest.
+///
+/// Returns [`DARKFORGE_OK`], or one of the `DARKFORGE_ERR_*` constants if the arguments are invalid, in which case
+/// `out_result` is left untouched.
+///
+/// # Safety
+///
+/// `out_result` must be null or point to memory valid for writing a [`DarkforgeRollResult`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn darkforge_roll_action(pool: u8, position: i32, effect: i32, out_result: *mut DarkforgeRollResult) -> i32 {
+    if out_result.is_null() {
+        return DARKFORGE_ERR_NULL;
+    }
+
+    match roll_action(&D6::default(), pool, position, effect) {
+        Ok(result) => {
+            // SAFETY: the caller guarantees that a non-null `out_result` is valid for writes.
+            unsafe { out_result.write(result) };
+            DARKFORGE_OK
+        }
+        Err(code) => code,
+    }
+}
+
+/// Rolls an action with `dice`, returning the error code if the arguments are invalid.
+fn roll_action(dice: &impl Dice, pool: u8, position: i32, effect: i32) -> Result<DarkforgeRollResult, i32> {
+    if usize::from(pool) > DARKFORGE_MAX_DICE {
+        return Err(DARKFORGE_ERR_POOL);
+    }
+    let position = match position {
+        DARKFORGE_POSITION_CONTROLLED => Position::Controlled,
+        DARKFORGE_POSITION_RISKY => Position::Risky,
+        DARKFORGE_POSITION_DESPERATE => Position::Desperate,
+        _ => return Err(DARKFORGE_ERR_POSITION),
+    };
+    let effect = usize::try_from(effect)
+        .ok()
+        .and_then(|effect| Effect::LEVELS.get(effect).copied())
+        .ok_or(DARKFORGE_ERR_EFFECT)?;
+
+    let kept = roll_pool(dice, pool);
+    let outcome = Outcome::from_dice(&kept);
+    let mut result = DarkforgeRollResult {
+        dice_count: u8::try_from(kept.len()).unwrap_or_default(),
+        outcome: outcome as i32,
+        effect: if outcome == Outcome::Critical { effect.shift(1) } else { effect } as i32,
+        harm: if outcome < Outcome::Success { position as u8 + 1 } else { 0 },
+        ..DarkforgeRollResult::default()
+    };
+    result.dice[..kept.len()].copy_from_slice(&kept);
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, collections::VecDeque, ptr};
+
+    use rstest::rstest;
+
+    use super::*;
+
+    struct Loaded(RefCell<VecDeque<u8>>);
+
+    impl Dice for Loaded {
+        fn roll(&self) -> u8 {
+            self.0.borrow_mut().pop_front().expect("should have a loaded die left")
+        }
+
+        fn roll_pool(&self, pool: usize) -> Vec<u8> {
+            (0..pool).map(|_| self.roll()).collect()
+        }
+
+        fn sides(&self) -> u8 {
+            6
+        }
+    }
+
+    fn loaded(dice: &[u8]) -> Loaded {
+        Loaded(RefCell::new(dice.iter().copied().collect()))
+    }
+
+    #[rstest]
+    #[case::failure(&[1, 3], DARKFORGE_POSITION_RISKY, DARKFORGE_OUTCOME_FAILURE, DARKFORGE_EFFECT_STANDARD, 2)]
+    #[case::partial(&[2, 5], DARKFORGE_POSITION_DESPERATE, DARKFORGE_OUTCOME_PARTIAL, DARKFORGE_EFFECT_STANDARD, 3)]
+    #[case::success(&[6, 2], DARKFORGE_POSITION_CONTROLLED, DARKFORGE_OUTCOME_SUCCESS, DARKFORGE_EFFECT_STANDARD, 0)]
+    #[case::critical(&[6, 6], DARKFORGE_POSITION_RISKY, DARKFORGE_OUTCOME_CRITICAL, DARKFORGE_EFFECT_GREAT, 0)]
+    fn should_roll_action(#[case] dice: &[u8], #[case] position: i32, #[case] outcome: i32, #[case] effect: i32, #[case] harm: u8) {
+        let result = roll_action(&loaded(dice), 2, position, DARKFORGE_EFFECT_STANDARD).expect("should have rolled");
+
+        assert_eq!(dice, &result.dice[..usize::from(result.dice_count)]);
+        assert_eq!((outcome, effect, harm), (result.outcome, result.effect, result.harm));
+    }
+
+    #[test]
+    fn should_keep_lowest_die_of_zero_pool() {
+        let result = roll_action(&loaded(&[5, 3]), 0, DARKFORGE_POSITION_RISKY, DARKFORGE_EFFECT_LIMITED).expect("should have rolled");
+
+        assert_eq!((1, 3), (result.dice_count, result.dice[0]));
+        assert_eq!(DARKFORGE_EFFECT_LIMITED, result.effect);
+    }
+
+    #[rstest]
+    #[case::pool(11, DARKFORGE_POSITION_RISKY, DARKFORGE_EFFECT_STANDARD, DARKFORGE_ERR_POOL)]
+    #[case::position(2, 3, DARKFORGE_EFFECT_STANDARD, DARKFORGE_ERR_POSITION)]
+    #[case::effect(2, DARKFORGE_POSITION_RISKY, -1, DARKFORGE_ERR_EFFECT)]
+    fn should_reject_invalid_arguments(#[case] pool: u8, #[case] position: i32, #[case] effect: i32, #[case] code: i32) {
+        let mut result = DarkforgeRollResult::default();
+
+        // SAFETY: `result` is valid for writes.
+        assert_eq!(code, unsafe { darkforge_roll_action(pool, position, effect, &raw mut result) });
+        assert_eq!(DarkforgeRollResult::default(), result);
+    }
+
+    #[test]
+    fn should_roll_through_c_abi() {
+        let mut result = DarkforgeRollResult::default();
+
+        // SAFETY: `result` is valid for writes.
+        assert_eq!(DARKFORGE_OK, unsafe {
+            darkforge_roll_action(3, DARKFORGE_POSITION_RISKY, DARKFORGE_EFFECT_STANDARD, &raw mut result)
+        });
+        assert_eq!(3, result.dice_count);
+        // SAFETY: a null result is rejected before being written to.
+        assert_eq!(DARKFORGE_ERR_NULL, unsafe {
+            darkforge_roll_action(3, DARKFORGE_POSITION_RISKY, DARKFORGE_EFFECT_STANDARD, ptr::null_mut())
+        });
+    }
+}
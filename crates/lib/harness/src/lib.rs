@@ -0,0 +1,179 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! # Dark Forge Harness
+//!
+//! A deterministic harness playing full scores against a campaign held in memory: the engagement roll, the actions
+//! and their consequences, the payoff and the downtime that follows. Dice are rolled from a seed, so a score scripted
+//! with the same seed always plays out the same way and its final state can be asserted on.
+//!
+//! Hacks building their own rule modules on Dark Forge bootstrap the harness from their content pack and script scores
+//! as regression tests, reaching for [`Harness::campaign`] to exercise their modules between the steps. The scores
+//! played can be recorded and replayed step by step to reproduce a bug, see [`replay`].
+//!
+//! ## Examples
+//!
+//! ```no_run
+//! use darkforge::{action::Proposal, character::Stash, downtime::Activity};
+//! use darkforge_harness::{
+//!     Harness,
+//!     score::{Action, Payoff, Score},
+//! };
+//!
+//! # async fn play() -> darkforge::campaign::Result<()> {
+//! let mut harness = Harness::defaults(1915).await?;
+//! let pc = harness.enlist("Arcy").await?;
+//! let crew = harness.crew().await?;
+//!
+//! let score = Score::new("The Silver Nail", 2)
+//!     .action(Action::new(pc, "Prowl", 2))
+//!     .payoff(Payoff::default().coin(pc, 4).heat(crew, 2))
+//!     .downtime(pc, Activity::ReduceHeat { crew, rating: 1 });
+//! let playthrough = harness.play(&score).await?;
+//!
+//! assert_eq!(1, playthrough.actions.len());
+//! assert_eq!(Stash::new(4), harness.character(pc).await?.stash);
+//! # Ok(())
+//! # }
+//! ```
+
+use darkforge::{
+    campaign::{Campaign, Result},
+    character::Character,
+    export::CharacterExport,
+    permission::Actor,
+};
+use darkforge_data::{pack::Pack, store::sql::sqlite::SqliteStore};
+use darkforge_rng::{
+    dice::{D6, Dice},
+    rng::{Audit, SeededRandom},
+};
+use uuid::Uuid;
+
+use crate::replay::Recording;
+
+/// Module for recording playthroughs and replaying them.
+pub mod replay;
+/// Module for scripted scores and their playthroughs.
+pub mod score;
+/// Module for generating synthetic campaigns of configurable size.
+pub mod synthetic;
+
+/// Path to the default content pack shipped with Dark Forge.
+pub const DEFAULTS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../../../data/defaults");
+
+/// A campaign held in memory along with seeded dice, to play scores deterministically.
+pub struct Harness {
+    campaign: Campaign,
+    dice: D6<SeededRandom<u8>>,
+    seed: u64,
+    recording: Option<Recording>,
+}
+
+impl Harness {
+    /// Bootstraps a campaign from the content pack in a fresh in-memory database, rolling dice from the seed.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CampaignError`](darkforge::campaign::CampaignError) if the campaign cannot be bootstrapped.
+    pub async fn new(pack: &Pack, seed: u64) -> Result<Harness> {
+        let store = SqliteStore::memory().await?;
+        let campaign = Campaign::bootstrap(store, "Harness", pack).await?;
+
+        Ok(Harness::with(campaign, seed))
+    }
+
+    /// Wraps the campaign, rolling dice from the seed.
+    fn with(campaign: Campaign, seed: u64) -> Harness {
+        Harness {
+            campaign,
+            dice: D6::new(seeded(seed)),
+            seed,
+            recording: None,
+        }
+    }
+
+    /// Bootstraps a campaign from the default content pack, see [`Harness::new`].
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CampaignError`](darkforge::campaign::CampaignError) if the pack cannot be read or the campaign
+    /// cannot be bootstrapped.
+    pub async fn defaults(seed: u64) -> Result<Harness> {
+        let pack = Pack::open(DEFAULTS)?;
+
+        Harness::new(&pack, seed).await
+    }
+
+    /// Returns the seed the dice are rolled from.
+    #[must_use]
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Returns the campaign played.
+    pub fn campaign(&mut self) -> &mut Campaign {
+        &mut self.campaign
+    }
+
+    /// Returns the seed, the algorithm and every die rolled so far, so the playthrough can be verified by replaying it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a thread panicked while rolling the dice, see [`D::with_rng`](darkforge_rng::dice::D::with_rng).
+    #[must_use]
+    pub fn audit(&self) -> Audit<u8> {
+        self.dice
+            .with_rng(SeededRandom::audit)
+            .expect("the harness dice should always be audited")
+    }
+
+    /// Returns the seeded dice, to roll for steps played outside of a score.
+    #[must_use]
+    pub fn dice(&self) -> &impl Dice {
+        &self.dice
+    }
+
+    /// Enlists a new character for a new player, returning the character's entity.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CampaignError`](darkforge::campaign::CampaignError) if the store cannot be written.
+    pub async fn enlist(&mut self, name: &str) -> Result<Uuid> {
+        self.campaign.enlist(Actor::Gm, Uuid::new_v4(), &Character::new(name)).await
+    }
+
+    /// Starts a crew without heat, returning its entity.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CampaignError`](darkforge::campaign::CampaignError) if the store cannot be written.
+    pub async fn crew(&mut self) -> Result<Uuid> {
+        self.campaign.start_crew(Actor::Gm).await
+    }
+
+    /// Returns the current state of the character, to assert on.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::MissingCharacter`](darkforge::campaign::CampaignError::MissingCharacter) if the entity
+    /// is not a character, or another [`CampaignError`](darkforge::campaign::CampaignError) if the store cannot be
+    /// read.
+    pub async fn character(&mut self, entity: Uuid) -> Result<CharacterExport> {
+        self.campaign.export_character(entity).await
+    }
+}
+
+/// Creates a generator of d6 values from the seed.
+fn seeded(seed: u64) -> SeededRandom<u8> {
+    SeededRandom::new(1, 6, seed).expect("a d6 should always have valid bounds").audited()
+}
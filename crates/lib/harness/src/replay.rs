@@ -0,0 +1,364 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! A replay re-executes a recorded playthrough step by step, so a rules bug met while playing can be reproduced from
+//! the recording attached to a bug report.
+//!
+//! [`Harness::start_recording`] exports the campaign as it stands, along with the seed of the dice and how many were
+//! rolled so far, then every score played is recorded as a [`Step`] holding the score, the journal entries it recorded
+//! and the world it left behind. [`Replay::start`] restores the campaign in memory and rolls the dice back to where they
+//! were, and [`Harness::replay_step`] plays each step again, failing with a [`ReplayError`] as soon as the replayed
+//! state diverges from the recorded one. Only the scores are recorded: changes made to the campaign between them make
+//! the replay diverge.
+//!
+//! Entities spawned while playing get fresh ids on every playthrough, so only the entities the campaign held when the
+//! recording started are compared.
+
+use std::collections::BTreeSet;
+
+use darkforge::campaign::{Campaign, CampaignError, Result};
+use darkforge_data::{
+    export::Exported,
+    pack::Pack,
+    store::{
+        Recorded,
+        delta::Delta,
+        diff::{Snapshot, WorldDiff},
+        sql::sqlite::SqliteStore,
+    },
+};
+use darkforge_rng::dice::Dice;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::{
+    Harness,
+    score::{Playthrough, Score},
+};
+
+/// Error type for replays diverging from their recording.
+#[derive(Error, Debug)]
+pub enum ReplayError {
+    /// The campaign failed while replaying.
+    #[error(transparent)]
+    Campaign(#[from] CampaignError),
+    /// The replay holds no step of the given index.
+    #[error("replay has no step {0}")]
+    MissingStep(usize),
+    /// The step recorded other journal entries than those it recorded when played.
+    #[error("step {step} recorded {replayed:?} instead of {recorded:?}")]
+    Journal {
+        /// The index of the step.
+        step: usize,
+        /// The journal entries recorded when the step was played.
+        recorded: Vec<Event>,
+        /// The journal entries recorded when it was replayed.
+        replayed: Vec<Event>,
+    },
+    /// The step left the world in another state than it did when played.
+    #[error("step {step} diverged from the recorded world")]
+    World {
+        /// The index of the step.
+        step: usize,
+        /// What changed from the recorded world to the replayed one.
+        diff: Box<WorldDiff>,
+    },
+}
+
+/// A journal entry recorded by a step, without its sequence number and time, which differ on every playthrough.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Event {
+    /// The entity the entry is about, if any.
+    pub entity: Option<Uuid>,
+    /// The kind of entry.
+    pub kind: String,
+    /// The serialized entry data.
+    pub data: String,
+}
+
+impl From<Recorded> for Event {
+    fn from(recorded: Recorded) -> Self {
+        Event {
+            entity: recorded.entity,
+            kind: recorded.kind,
+            data: recorded.data,
+        }
+    }
+}
+
+/// A score played while recording, along with what it did.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Step {
+    /// The score played.
+    pub score: Score,
+    /// The journal entries the score recorded, in order.
+    pub events: Vec<Event>,
+    /// The entities the campaign held when the recording started, as the score left them.
+    pub world: Snapshot,
+}
+
+/// A recorded playthrough, exported along with a bug report to replay it.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Replay {
+    /// The seed the dice were rolled from.
+    pub seed: u64,
+    /// How many dice were rolled before the recording started.
+    pub rolled: usize,
+    /// The whole campaign when the recording started.
+    pub start: Delta,
+    /// The scores played since, in order.
+    pub steps: Vec<Step>,
+}
+
+impl Exported for Replay {
+    const KIND: &'static str = "replay";
+}
+
+impl Replay {
+    /// Restores the campaign the recording started from in memory, loading its content pack first, with the dice
+    /// rolled back to where they were, ready to replay the steps with [`Harness::replay_step`].
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CampaignError`] if the campaign cannot be restored.
+    pub async fn start(&self, pack: &Pack) -> Result<Harness> {
+        let campaign = Campaign::sync(SqliteStore::memory().await?, pack, &self.start).await?;
+        let harness = Harness::with(campaign, self.seed);
+        harness.dice.roll_pool(self.rolled);
+
+        Ok(harness)
+    }
+
+    /// Restores the campaign and replays every step in order, returning the harness as the last step left it.
+    ///
+    /// # Errors
+    ///
+    /// Returns the [`ReplayError`] of the first step that diverges from its recording.
+    pub async fn run(&self, pack: &Pack) -> std::result::Result<Harness, ReplayError> {
+        let mut harness = self.start(pack).await?;
+        for step in 0..self.steps.len() {
+            harness.replay_step(self, step).await?;
+        }
+
+        Ok(harness)
+    }
+}
+
+/// A replay being recorded, along with the journal entry the next step starts after.
+pub(crate) struct Recording {
+    replay: Replay,
+    checkpoint: i64,
+    entities: BTreeSet<Uuid>,
+}
+
+impl Harness {
+    /// Starts recording the scores played from now on, replacing any recording in progress.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CampaignError`] if the campaign cannot be exported.
+    pub async fn start_recording(&mut self) -> Result<()> {
+        let start = self.campaign.delta(0).await?;
+        let rolled = self.audit().values.len();
+        self.recording = Some(Recording {
+            checkpoint: start.until,
+            entities: Snapshot::from(&start).entities.into_keys().collect(),
+            replay: Replay {
+                seed: self.seed,
+                rolled,
+                start,
+                steps: Vec::new(),
+            },
+        });
+
+        Ok(())
+    }
+
+    /// Stops recording, returning the replay recorded if any.
+    pub fn stop_recording(&mut self) -> Option<Replay> {
+        self.recording.take().map(|recording| recording.replay)
+    }
+
+    /// Plays the step of the replay again, checking that it records the same journal entries and leaves the world as
+    /// it did when recorded. The harness must have been restored with [`Replay::start`] and have replayed the steps
+    /// before it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ReplayError::MissingStep`] if the replay has no such step, [`ReplayError::Journal`] or
+    /// [`ReplayError::World`] if the step diverges, or [`ReplayError::Campaign`] if it cannot be played.
+    pub async fn replay_step(&mut self, replay: &Replay, step: usize) -> std::result::Result<Playthrough, ReplayError> {
+        let recorded = replay.steps.get(step).ok_or(ReplayError::MissingStep(step))?;
+        let checkpoint = self.campaign.delta(0).await?.until;
+        let playthrough = self.play(&recorded.score).await?;
+
+        let entities = Snapshot::from(&replay.start).entities.into_keys().collect();
+        let (_, events, world) = self.step_from(checkpoint, &entities).await?;
+        if events != recorded.events {
+            return Err(ReplayError::Journal {
+                step,
+                recorded: recorded.events.clone(),
+                replayed: events,
+            });
+        }
+        if world != recorded.world {
+            return Err(ReplayError::World {
+                step,
+                diff: Box::new(WorldDiff::between(&recorded.world, &world)),
+            });
+        }
+
+        Ok(playthrough)
+    }
+
+    /// Records the score just played as a step of the replay, if recording.
+    pub(crate) async fn record(&mut self, score: &Score) -> Result<()> {
+        let Some(Recording { checkpoint, entities, .. }) = &self.recording else {
+            return Ok(());
+        };
+
+        let (checkpoint, entities) = (*checkpoint, entities.clone());
+        let (until, events, world) = self.step_from(checkpoint, &entities).await?;
+        if let Some(recording) = &mut self.recording {
+            recording.checkpoint = until;
+            recording.replay.steps.push(Step {
+                score: score.clone(),
+                events,
+                world,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Returns the latest journal entry and those recorded after the checkpoint, and the world as it stands for the given
+    /// entities.
+    async fn step_from(&mut self, checkpoint: i64, entities: &BTreeSet<Uuid>) -> Result<(i64, Vec<Event>, Snapshot)> {
+        let Delta { until, journal, .. } = self.campaign.delta(checkpoint).await?;
+        let Snapshot {
+            entities: world,
+            relationships,
+        } = self.campaign.snapshot().await?;
+
+        Ok((
+            until,
+            journal.into_iter().map(Event::from).collect(),
+            Snapshot {
+                entities: world.into_iter().filter(|(entity, _)| entities.contains(entity)).collect(),
+                relationships: relationships
+                    .into_iter()
+                    .filter(|relationship| entities.contains(&relationship.source) && entities.contains(&relationship.target))
+                    .collect(),
+            },
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use darkforge::{
+        action::{Consequence, Proposal},
+        clock::Clock,
+        downtime::Activity,
+        permission::Actor,
+    };
+    use darkforge_data::export::{export, import};
+
+    use super::*;
+    use crate::{
+        DEFAULTS,
+        score::{Action, Payoff},
+    };
+
+    /// Records two scores played after a first one, returning the replay and the character playing them.
+    async fn recorded() -> (Harness, Replay, Uuid) {
+        let mut harness = Harness::defaults(1966).await.expect("should have created harness");
+        let pc = harness.enlist("Arcy").await.expect("should have enlisted character");
+        let crew = harness.crew().await.expect("should have started crew");
+        let alarm = harness
+            .campaign()
+            .add_clock(Actor::Gm, None, &Clock::new("Alarm", 6).expect("should have created clock"))
+            .await
+            .expect("should have added clock");
+        harness
+            .play(&Score::new("Warm-up", 1).action(Action::new(pc, "Study", 1)))
+            .await
+            .expect("should have played score");
+
+        harness.start_recording().await.expect("should have started recording");
+        let score = Score::new("The Silver Nail", 2)
+            .action(Action::new(pc, "Prowl", 1).consequence(Proposal {
+                consequence: Consequence::Complication { clock: alarm, ticks: 2 },
+                resist: None,
+            }))
+            .payoff(Payoff::default().coin(pc, 2).heat(crew, 2));
+        harness.play(&score).await.expect("should have played score");
+        harness
+            .play(&Score::new("Lying Low", 1).downtime(pc, Activity::ReduceHeat { crew, rating: 2 }))
+            .await
+            .expect("should have played score");
+        let replay = harness.stop_recording().expect("should have recorded replay");
+
+        (harness, replay, pc)
+    }
+
+    #[tokio::test]
+    async fn should_replay_recorded_scores_to_the_same_state() {
+        let (mut played, replay, pc) = recorded().await;
+        let mut exported = Vec::new();
+        export(&replay, &mut exported).expect("should have exported replay");
+        let imported = import::<Replay>(exported.as_slice()).expect("should have imported replay");
+        let pack = Pack::open(DEFAULTS).expect("should have opened pack");
+
+        let mut replayed = imported.run(&pack).await.expect("should have replayed scores");
+
+        assert_eq!(2, imported.steps.len());
+        assert_eq!(played.audit(), replayed.audit());
+        assert_eq!(
+            played.character(pc).await.expect("should have read character"),
+            replayed.character(pc).await.expect("should have read character")
+        );
+    }
+
+    #[tokio::test]
+    async fn should_fail_on_the_step_that_diverges() {
+        let (_, mut replay, _) = recorded().await;
+        let pack = Pack::open(DEFAULTS).expect("should have opened pack");
+        replay.steps[1].world = replay.steps[0].world.clone();
+
+        let mut harness = replay.start(&pack).await.expect("should have restored campaign");
+        harness.replay_step(&replay, 0).await.expect("should have replayed first step");
+        let err = harness.replay_step(&replay, 1).await.expect_err("should have diverged on second step");
+
+        let ReplayError::World { step, diff } = err else {
+            panic!("should have diverged from the recorded world, got {err}");
+        };
+        assert_eq!(1, step);
+        assert_ne!(WorldDiff::default(), *diff);
+        assert!(matches!(harness.replay_step(&replay, 2).await, Err(ReplayError::MissingStep(2))));
+    }
+
+    #[tokio::test]
+    async fn should_fail_when_the_dice_roll_otherwise() {
+        let (_, mut replay, _) = recorded().await;
+        let pack = Pack::open(DEFAULTS).expect("should have opened pack");
+        replay.rolled += 1;
+
+        let err = replay.run(&pack).await.map(|_| ()).expect_err("should have diverged");
+
+        assert!(
+            matches!(err, ReplayError::Journal { step: 0, .. }),
+            "should have diverged on first step, got {err}"
+        );
+    }
+}
@@ -0,0 +1,346 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! A score is scripted as the steps the table plays through, from the engagement roll to the downtime that follows.
+//! [`Harness::play`] plays them in order on the GM's behalf, starting a session named after the score so that
+//! everything it changes is journaled.
+use std::collections::BTreeMap;
+
+use darkforge::{
+    action::{ActionRoll, Delta, Proposal, roll_pool},
+    campaign::Result,
+    character::Stash,
+    context::{Position, RollContext},
+    crew::Heat,
+    downtime::{self, Activity},
+    effect::Effect,
+    permission::Actor,
+    roll::Roll,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::Harness;
+
+/// An action a character takes during the score.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Action {
+    /// The character acting.
+    pub character: Uuid,
+    /// The action rolled, e.g. "Prowl".
+    pub label: String,
+    /// The rating rolled.
+    pub rating: u8,
+    /// The position the character acts from.
+    pub position: Position,
+    /// The effect level of the action.
+    pub effect: Effect,
+    /// The consequences the GM inflicts on a partial success or a failure.
+    pub consequences: Vec<Proposal>,
+}
+
+impl Action {
+    /// Creates a risky action with standard effect and no consequences.
+    #[must_use]
+    pub fn new(character: Uuid, label: impl Into<String>, rating: u8) -> Action {
+        Action {
+            character,
+            label: label.into(),
+            rating,
+            position: Position::default(),
+            effect: Effect::Standard,
+            consequences: Vec::new(),
+        }
+    }
+
+    /// Sets the position the character acts from.
+    #[must_use]
+    pub fn position(mut self, position: Position) -> Action {
+        self.position = position;
+
+        self
+    }
+
+    /// Sets the effect level of the action.
+    #[must_use]
+    pub fn effect(mut self, effect: Effect) -> Action {
+        self.effect = effect;
+
+        self
+    }
+
+    /// Adds a consequence inflicted on a partial success or a failure.
+    #[must_use]
+    pub fn consequence(mut self, proposal: Proposal) -> Action {
+        self.consequences.push(proposal);
+
+        self
+    }
+}
+
+/// What the crew earns and the heat it draws once the score is over.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Payoff {
+    /// The coin each character puts in their stash.
+    pub coin: BTreeMap<Uuid, u8>,
+    /// The heat each crew draws.
+    pub heat: BTreeMap<Uuid, u8>,
+}
+
+impl Payoff {
+    /// Adds coin to the character's stash.
+    #[must_use]
+    pub fn coin(mut self, character: Uuid, coin: u8) -> Payoff {
+        *self.coin.entry(character).or_default() += coin;
+
+        self
+    }
+
+    /// Adds heat to the crew.
+    #[must_use]
+    pub fn heat(mut self, crew: Uuid, heat: u8) -> Payoff {
+        *self.heat.entry(crew).or_default() += heat;
+
+        self
+    }
+}
+
+/// A score scripted from the engagement roll to the downtime that follows.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Score {
+    /// The name of the score, used as the title of its session.
+    pub name: String,
+    /// The rating of the engagement roll.
+    pub engagement: u8,
+    /// The actions taken, in order.
+    pub actions: Vec<Action>,
+    /// The payoff of the score.
+    pub payoff: Payoff,
+    /// The downtime activities taken afterwards by each character, in order.
+    pub downtime: Vec<(Uuid, Activity)>,
+}
+
+impl Score {
+    /// Creates a score with the rating of its engagement roll.
+    #[must_use]
+    pub fn new(name: impl Into<String>, engagement: u8) -> Score {
+        Score {
+            name: name.into(),
+            engagement,
+            actions: Vec::new(),
+            payoff: Payoff::default(),
+            downtime: Vec::new(),
+        }
+    }
+
+    /// Adds an action taken during the score.
+    #[must_use]
+    pub fn action(mut self, action: Action) -> Score {
+        self.actions.push(action);
+
+        self
+    }
+
+    /// Sets the payoff of the score.
+    #[must_use]
+    pub fn payoff(mut self, payoff: Payoff) -> Score {
+        self.payoff = payoff;
+
+        self
+    }
+
+    /// Adds a downtime activity the character takes after the score.
+    #[must_use]
+    pub fn downtime(mut self, character: Uuid, activity: Activity) -> Score {
+        self.downtime.push((character, activity));
+
+        self
+    }
+}
+
+/// What happened while playing a score.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Playthrough {
+    /// The engagement roll.
+    pub engagement: Roll,
+    /// The changes applied by each action, in order.
+    pub actions: Vec<Delta>,
+    /// The outcome of each downtime activity, in order.
+    pub downtime: Vec<downtime::Outcome>,
+}
+
+impl Harness {
+    /// Plays the score on the GM's behalf: starts a session named after it, rolls the engagement, resolves the actions
+    /// and their consequences, pays off the crew then takes the downtime activities. The score is recorded as a step
+    /// of the replay if recording, see [`Harness::start_recording`].
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CampaignError`](darkforge::campaign::CampaignError) if a step refers to a missing entity, e.g. a
+    /// crew without heat, or the store cannot be read or written. The steps played before it remain applied.
+    pub async fn play(&mut self, score: &Score) -> Result<Playthrough> {
+        self.campaign.start_session(Actor::Gm, score.name.as_str()).await?;
+        let engagement = self.campaign.roll(Actor::Gm, None, "Engagement", score.engagement, &self.dice).await?;
+
+        let mut actions = Vec::new();
+        for action in &score.actions {
+            let roll = ActionRoll {
+                dice: roll_pool(&self.dice, action.rating),
+                effect: action.effect,
+                stress: 0,
+                context: Some(
+                    RollContext::new(Actor::Gm)
+                        .target(action.character)
+                        .position(action.position)
+                        .effect(action.effect)
+                        .tag(action.label.as_str()),
+                ),
+                modifiers: Vec::new(),
+            };
+            let delta = self
+                .campaign
+                .resolve_action(Actor::Gm, action.character, &roll, action.consequences.clone(), &self.dice)
+                .await?;
+            actions.push(delta);
+        }
+
+        for (&character, &coin) in &score.payoff.coin {
+            let stash = self.campaign.get::<Stash>(character).await?.unwrap_or_default();
+            self.campaign
+                .update(Actor::Gm, character, &Stash::new(stash.value().saturating_add(coin)))
+                .await?;
+        }
+        for (&crew, &heat) in &score.payoff.heat {
+            let current = self.campaign.get::<Heat>(crew).await?.unwrap_or_default();
+            self.campaign
+                .update(Actor::Gm, crew, &Heat::new(current.heat().saturating_add(heat), current.wanted()))
+                .await?;
+        }
+
+        let mut downtime = Vec::new();
+        for &(character, activity) in &score.downtime {
+            downtime.push(self.campaign.downtime(Actor::Gm, character, activity, &self.dice).await?);
+        }
+        self.record(score).await?;
+
+        Ok(Playthrough {
+            engagement,
+            actions,
+            downtime,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use darkforge::{
+        action::{Consequence, Outcome},
+        character::Stress,
+        clock::Clock,
+        consequence::HarmConsequence,
+    };
+
+    use super::*;
+
+    /// Scripts a score where everything that can go wrong does.
+    async fn heist(harness: &mut Harness) -> (Score, Uuid, Uuid, Uuid) {
+        let pc = harness.enlist("Arcy").await.expect("should have enlisted character");
+        let crew = harness.crew().await.expect("should have started crew");
+        let alarm = harness
+            .campaign()
+            .add_clock(Actor::Gm, None, &Clock::new("Alarm", 4).expect("should have created clock"))
+            .await
+            .expect("should have added clock");
+
+        let score = Score::new("The Silver Nail", 1)
+            .action(
+                Action::new(pc, "Prowl", 0)
+                    .position(Position::Desperate)
+                    .consequence(Proposal {
+                        consequence: Consequence::Harm(HarmConsequence {
+                            level: 2,
+                            description: "Deep cut".into(),
+                        }),
+                        resist: Some(1),
+                    })
+                    .consequence(Proposal {
+                        consequence: Consequence::Complication { clock: alarm, ticks: 2 },
+                        resist: None,
+                    }),
+            )
+            .action(Action::new(pc, "Finesse", 2).effect(Effect::Great))
+            .payoff(Payoff::default().coin(pc, 4).heat(crew, 3))
+            .downtime(pc, Activity::ReduceHeat { crew, rating: 1 })
+            .downtime(pc, Activity::IndulgeVice);
+
+        (score, pc, crew, alarm)
+    }
+
+    #[tokio::test]
+    async fn should_play_same_score_the_same_way_given_same_seed() {
+        let mut first = Harness::defaults(1915).await.expect("should have created harness");
+        let mut second = Harness::defaults(1915).await.expect("should have created harness");
+        let (score, pc, ..) = heist(&mut first).await;
+        let (replay, replayed, ..) = heist(&mut second).await;
+
+        let played = first.play(&score).await.expect("should have played score");
+        let replayed_through = second.play(&replay).await.expect("should have played score");
+
+        assert_eq!(played.engagement, replayed_through.engagement);
+        assert_eq!(
+            played.actions.iter().map(|delta| (delta.outcome, delta.stress)).collect::<Vec<_>>(),
+            replayed_through
+                .actions
+                .iter()
+                .map(|delta| (delta.outcome, delta.stress))
+                .collect::<Vec<_>>()
+        );
+        let state = first.character(pc).await.expect("should have read character");
+        let replayed_state = second.character(replayed).await.expect("should have read character");
+        assert_eq!(
+            (state.stress, state.harm, state.stash),
+            (replayed_state.stress, replayed_state.harm, replayed_state.stash)
+        );
+
+        let audit = first.audit();
+        assert_eq!(1915, audit.seed);
+        assert!(!audit.values.is_empty(), "should have recorded the dice rolled");
+        assert_eq!(audit, second.audit());
+        assert!(audit.verify().expect("should have replayed the dice"));
+    }
+
+    #[tokio::test]
+    async fn should_play_full_score_to_final_state() {
+        let mut harness = Harness::defaults(1915).await.expect("should have created harness");
+        let (score, pc, crew, alarm) = heist(&mut harness).await;
+
+        let played = harness.play(&score).await.expect("should have played score");
+
+        assert_eq!(vec![5], played.engagement.dice);
+        assert_eq!(
+            vec![Outcome::Failure, Outcome::Failure],
+            played.actions.iter().map(|delta| delta.outcome).collect::<Vec<_>>()
+        );
+        let state = harness.character(pc).await.expect("should have read character");
+        assert_eq!(Stress::default(), state.stress);
+        assert_eq!(vec!["Deep cut".to_owned()], state.harm.lesser);
+        assert_eq!(Stash::new(4), state.stash);
+        let campaign = harness.campaign();
+        assert_eq!(Some(Heat::new(1, 0)), campaign.get(crew).await.expect("should have read heat"));
+        let clock = campaign
+            .get::<Clock>(alarm)
+            .await
+            .expect("should have read clock")
+            .expect("should have a clock");
+        assert_eq!(2, clock.filled());
+    }
+}
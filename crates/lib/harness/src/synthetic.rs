@@ -0,0 +1,198 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! Synthetic campaigns of configurable size, to measure queries, exports and the Godot UI against a campaign far
+//! larger than any table plays through.
+//!
+//! The campaign is bootstrapped from a content pack, then filled with made up factions, sessions and rolls drawn from
+//! a seed, so the same seed always generates the same campaign.
+//!
+//! ```no_run
+//! # async fn generate() -> darkforge::campaign::Result<()> {
+//! use darkforge_data::{pack::Pack, store::sql::sqlite::SqliteStore};
+//! use darkforge_harness::{DEFAULTS, synthetic::Synthetic};
+//!
+//! let pack = Pack::open(DEFAULTS)?;
+//! let store = SqliteStore::open("load.db").await?;
+//! let campaign = Synthetic::new(1975).sessions(50).factions(200).rolls(10_000).generate(store, &pack).await?;
+//! # Ok(())
+//! # }
+//! ```
+use darkforge::{
+    campaign::{Campaign, Result},
+    faction::{ClockTemplate, FactionTemplate, Hold, Status},
+    permission::Actor,
+    roll::RollRequest,
+};
+use darkforge_data::{descriptor::Descriptor, pack::Pack, store::sql::sqlite::SqliteStore};
+use darkforge_rng::{
+    dice::{D6, Dice},
+    rng::SeededRandom,
+};
+use uuid::Uuid;
+
+/// Generates synthetic campaigns holding the given number of sessions, factions and rolls.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Synthetic {
+    seed: u64,
+    sessions: usize,
+    factions: usize,
+    rolls: usize,
+}
+
+impl Synthetic {
+    /// The number of sessions started unless told otherwise.
+    pub const DEFAULT_SESSIONS: usize = 10;
+    /// The number of factions added unless told otherwise, on top of the pack's.
+    pub const DEFAULT_FACTIONS: usize = 20;
+    /// The number of rolls made unless told otherwise.
+    pub const DEFAULT_ROLLS: usize = 1_000;
+
+    /// Creates a generator drawing from the seed.
+    #[must_use]
+    pub fn new(seed: u64) -> Synthetic {
+        Synthetic {
+            seed,
+            sessions: Synthetic::DEFAULT_SESSIONS,
+            factions: Synthetic::DEFAULT_FACTIONS,
+            rolls: Synthetic::DEFAULT_ROLLS,
+        }
+    }
+
+    /// Sets how many sessions to start.
+    #[must_use]
+    pub fn sessions(self, count: usize) -> Synthetic {
+        Synthetic { sessions: count, ..self }
+    }
+
+    /// Sets how many factions to add on top of the pack's.
+    #[must_use]
+    pub fn factions(self, count: usize) -> Synthetic {
+        Synthetic { factions: count, ..self }
+    }
+
+    /// Sets how many rolls to make, spread evenly across the sessions.
+    #[must_use]
+    pub fn rolls(self, count: usize) -> Synthetic {
+        Synthetic { rolls: count, ..self }
+    }
+
+    /// Returns the seed of the generator.
+    #[must_use]
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Initializes a fresh campaign in the store from the pack, then fills it with the factions, sessions and rolls.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CampaignError::AlreadyExists`](darkforge::campaign::CampaignError::AlreadyExists) if the store already
+    /// holds a campaign, or another [`CampaignError`](darkforge::campaign::CampaignError) if the pack is invalid or the
+    /// store cannot be written.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a d6 cannot be created, which never happens.
+    pub async fn generate(&self, store: SqliteStore, pack: &Pack) -> Result<Campaign> {
+        let dice = D6::new(SeededRandom::new(1, 6, self.seed).expect("a d6 should always have valid bounds"));
+        let mut campaign = Campaign::bootstrap(store, format!("Synthetic {}", self.seed), pack).await?;
+
+        for index in 1..=self.factions {
+            campaign.add_faction(Actor::Gm, &faction(index, &dice)).await?;
+        }
+
+        let sessions = self.sessions.max(1);
+        for session in 0..sessions {
+            if self.sessions > 0 {
+                campaign.start_session(Actor::Gm, format!("Session {}", session + 1)).await?;
+            }
+
+            // The first sessions make one more roll each until the remainder is spent.
+            let count = self.rolls / sessions + usize::from(session < self.rolls % sessions);
+            let requests = (0..count)
+                .map(|_| RollRequest {
+                    character: None,
+                    label: "Fortune".into(),
+                    rating: (dice.roll() - 1) % 4,
+                })
+                .collect::<Vec<_>>();
+            campaign.roll_batch(Actor::Gm, &requests, &dice).await?;
+        }
+
+        Ok(campaign)
+    }
+}
+
+/// Makes up the faction numbered `index`, with a tier, hold, status and clock drawn from the dice.
+fn faction(index: usize, dice: &impl Dice) -> FactionTemplate {
+    let segments = [4, 6, 8][usize::from(dice.roll() - 1) % 3];
+
+    FactionTemplate {
+        id: Uuid::new_v4(),
+        descriptor: Descriptor::new(Uuid::new_v4(), format!("Faction {index}"), "A synthetic faction."),
+        tier: dice.roll() - 1,
+        hold: if dice.roll() > 3 { Hold::Strong } else { Hold::Weak },
+        status: Status::new(i8::try_from(dice.roll()).unwrap_or_default() - 4),
+        clocks: vec![ClockTemplate {
+            label: format!("Scheme {index}"),
+            segments,
+            filled: dice.roll() % segments,
+        }],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use darkforge::roll::RecordedRoll;
+
+    use super::*;
+    use crate::DEFAULTS;
+
+    async fn generate(synthetic: Synthetic) -> Campaign {
+        let pack = Pack::open(DEFAULTS).expect("should have opened pack");
+        let store = SqliteStore::memory().await.expect("should have opened store");
+
+        synthetic.generate(store, &pack).await.expect("should have generated campaign")
+    }
+
+    #[tokio::test]
+    async fn should_generate_campaign_of_given_size() {
+        let pack = Pack::open(DEFAULTS).expect("should have opened pack");
+        let store = SqliteStore::memory().await.expect("should have opened store");
+        let packed = Campaign::bootstrap(store, "Pack", &pack)
+            .await
+            .expect("should have bootstrapped campaign")
+            .factions()
+            .await
+            .expect("should have listed factions")
+            .len();
+
+        let mut campaign = generate(Synthetic::new(1975).sessions(3).factions(4).rolls(10)).await;
+
+        assert_eq!(packed + 4, campaign.factions().await.expect("should have listed factions").len());
+        assert_eq!(3, campaign.sessions().await.expect("should have listed sessions").len());
+        assert_eq!(10, campaign.rolls(None, u32::MAX).await.expect("should have listed rolls").len());
+    }
+
+    #[tokio::test]
+    async fn should_generate_same_rolls_from_same_seed() {
+        let mut first = generate(Synthetic::new(1975).sessions(2).factions(1).rolls(5)).await;
+        let mut second = generate(Synthetic::new(1975).sessions(2).factions(1).rolls(5)).await;
+
+        let rolls = |recorded: Vec<RecordedRoll>| recorded.into_iter().map(|recorded| recorded.roll).collect::<Vec<_>>();
+        assert_eq!(
+            rolls(first.rolls(None, u32::MAX).await.expect("should have listed rolls")),
+            rolls(second.rolls(None, u32::MAX).await.expect("should have listed rolls"))
+        );
+    }
+}
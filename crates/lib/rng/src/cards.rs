@@ -10,3 +10,660 @@
  * You should have received a copy of the GNU Affero General Public License along with this program.
  * If not, see https://www.gnu.org/licenses/.
  */
+//! # Cards
+//!
+//! This module provides decks of cards shuffled the way a table would shuffle them.
+//!
+//! The module offers:
+//! - A [`Shuffle`] enum listing the shuffle strategies, from a perfect shuffle to simulated human shuffles
+//! - A [`Deck`] struct holding a draw pile, a discard pile and the strategy used to shuffle them
+//! - A [`Hand`] struct holding the cards dealt to a player
+//! - A [`DeckEvent`] enum reporting when the draw pile runs out
+//!
+//! Some oracle decks intentionally want imperfect shuffles, leaving runs of cards that were drawn together close to each
+//! other, so the strategy is chosen per deck.
+//!
+//! ## Examples
+//!
+//! ```
+//! use darkforge_rng::cards::{Deck, Shuffle};
+//!
+//! let mut deck = Deck::new((1..=13).collect(), Shuffle::Riffle { passes: 3 });
+//! deck.shuffle();
+//!
+//! let card = deck.draw().unwrap();
+//! assert!((1..=13).contains(&card));
+//! assert_eq!(deck.len(), 12);
+//! ```
+
+use core::mem;
+
+use rand::{Rng, seq::SliceRandom as _};
+
+/// Probability that a human shuffling overhand ends the packet they are sliding off at a given card.
+pub const OVERHAND_CUT: f64 = 0.25;
+
+/// Strategy used to shuffle a deck.
+///
+/// # Examples
+///
+/// ```
+/// use darkforge_rng::cards::Shuffle;
+///
+/// let mut cards = (1..=8).collect::<Vec<_>>();
+/// Shuffle::Overhand { passes: 1 }.apply(&mut cards, &mut rand::rng());
+///
+/// cards.sort_unstable();
+/// assert_eq!(cards, (1..=8).collect::<Vec<_>>());
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Shuffle {
+    /// Every order is equally likely, using a Fisher-Yates shuffle.
+    #[default]
+    Perfect,
+    /// Simulates human riffle shuffles: the deck is cut near the middle and both halves are interleaved, cards dropping
+    /// from the larger half more often (the Gilbert-Shannon-Reeds model). A few passes leave long rising runs of the
+    /// previous order in the deck.
+    Riffle {
+        /// The number of riffles.
+        passes: u8,
+    },
+    /// Simulates human overhand shuffles: small packets are slid off the top of the deck onto a new pile, reversing
+    /// their order but keeping the cards of each packet together, see [`OVERHAND_CUT`]. It takes many passes to mix a
+    /// deck.
+    Overhand {
+        /// The number of times the whole deck is slid off.
+        passes: u8,
+    },
+}
+
+impl Shuffle {
+    /// Shuffles the cards with this strategy, the first card being the top of the deck.
+    ///
+    /// # Arguments
+    ///
+    /// * `cards` - The cards to shuffle
+    /// * `rng` - The random number generator driving the shuffle, seed it to replay shuffles
+    #[inline]
+    pub fn apply<T, R: Rng + ?Sized>(self, cards: &mut Vec<T>, rng: &mut R) {
+        match self {
+            Shuffle::Perfect => cards.shuffle(rng),
+            Shuffle::Riffle { passes } => (0..passes).for_each(|_| riffle(cards, rng)),
+            Shuffle::Overhand { passes } => (0..passes).for_each(|_| overhand(cards, rng)),
+        }
+    }
+}
+
+/// Riffles the cards once: cuts them after a binomially distributed number of cards, then drops each card from one
+/// half or the other with a probability proportional to the cards left in it.
+fn riffle<T, R: Rng + ?Sized>(cards: &mut Vec<T>, rng: &mut R) {
+    let cut = (0..cards.len()).filter(|_| rng.random_bool(0.5)).count();
+    let bottom = cards.split_off(cut);
+    let (mut top_left, mut bottom_left) = (cut, bottom.len());
+    let (mut top, mut bottom) = (mem::take(cards).into_iter(), bottom.into_iter());
+
+    while let Some(card) = if rng.random_range(0..top_left + bottom_left) < top_left {
+        top_left -= 1;
+        top.next()
+    } else {
+        bottom_left -= 1;
+        bottom.next()
+    } {
+        cards.push(card);
+        if top_left + bottom_left == 0 {
+            break;
+        }
+    }
+}
+
+/// Slides the cards off the top once in packets, each card ending its packet with probability [`OVERHAND_CUT`].
+fn overhand<T, R: Rng + ?Sized>(cards: &mut Vec<T>, rng: &mut R) {
+    let mut packets = vec![Vec::new()];
+    for card in mem::take(cards) {
+        if let Some(packet) = packets.last_mut() {
+            packet.push(card);
+        }
+        if rng.random_bool(OVERHAND_CUT) {
+            packets.push(Vec::new());
+        }
+    }
+
+    cards.extend(packets.into_iter().rev().flatten());
+}
+
+/// Something that happened to a deck while drawing from it, see [`Deck::take_events`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum DeckEvent {
+    /// The draw pile ran out.
+    Exhausted {
+        /// The number of cards that were asked for but could not be drawn.
+        missing: usize,
+    },
+}
+
+/// A deck of cards, drawn from the top, shuffled with its own strategy.
+///
+/// Cards discarded from hands go to a discard pile, which can be shuffled back into the deck with [`Deck::recycle`].
+///
+/// # Type Parameters
+///
+/// * `T` - The cards of the deck
+///
+/// # Examples
+///
+/// ```
+/// use darkforge_rng::cards::{Deck, Shuffle};
+///
+/// let mut deck = Deck::new(vec!["Ace", "King", "Queen"], Shuffle::Perfect);
+/// assert_eq!(deck.draw(), Some("Ace"));
+///
+/// deck.put_back("Ace");
+/// assert_eq!(deck.cards(), ["King", "Queen", "Ace"]);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Deck<T> {
+    /// The cards left in the draw pile, the first one being the top of the deck
+    cards: Vec<T>,
+
+    /// The cards discarded, the last one being on top of the pile
+    discards: Vec<T>,
+
+    /// The events that happened since they were last taken
+    events: Vec<DeckEvent>,
+
+    /// The strategy used to shuffle the deck
+    strategy: Shuffle,
+}
+
+impl<T> Default for Deck<T> {
+    /// Creates an empty deck shuffled perfectly.
+    #[inline]
+    fn default() -> Self {
+        Self::new(Vec::new(), Shuffle::Perfect)
+    }
+}
+
+impl<T> Deck<T> {
+    /// Creates a deck holding the cards in the given order, the first card being the top of the deck.
+    ///
+    /// # Arguments
+    ///
+    /// * `cards` - The cards of the deck
+    /// * `strategy` - The strategy used to shuffle the deck
+    #[inline]
+    #[must_use]
+    pub fn new(cards: Vec<T>, strategy: Shuffle) -> Self {
+        Self {
+            cards,
+            discards: Vec::new(),
+            events: Vec::new(),
+            strategy,
+        }
+    }
+
+    /// Shuffles the deck with its strategy, using thread-local randomness.
+    #[inline]
+    pub fn shuffle(&mut self) {
+        self.shuffle_with(&mut rand::rng());
+    }
+
+    /// Shuffles the deck with its strategy, using the given random number generator.
+    ///
+    /// # Arguments
+    ///
+    /// * `rng` - The random number generator driving the shuffle, seed it to replay shuffles
+    #[inline]
+    pub fn shuffle_with<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        self.strategy.apply(&mut self.cards, rng);
+    }
+
+    /// Draws the card on top of the deck, if any is left, recording a [`DeckEvent::Exhausted`] event when it draws the
+    /// last card or none is left.
+    #[inline]
+    pub fn draw(&mut self) -> Option<T> {
+        if self.cards.is_empty() {
+            self.events.push(DeckEvent::Exhausted { missing: 1 });
+            return None;
+        }
+
+        let card = self.cards.remove(0);
+        if self.cards.is_empty() {
+            self.events.push(DeckEvent::Exhausted { missing: 0 });
+        }
+
+        Some(card)
+    }
+
+    /// Deals cards from the top of the deck one at a time to each hand in turn, as around a table, until every hand
+    /// holds `cards_each` cards. If the draw pile runs out, the hands dealt last are left short and a single
+    /// [`DeckEvent::Exhausted`] event records how many cards are missing.
+    ///
+    /// # Arguments
+    ///
+    /// * `hands` - The number of hands to deal
+    /// * `cards_each` - The number of cards to deal to each hand
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use darkforge_rng::cards::{Deck, DeckEvent, Shuffle};
+    ///
+    /// let mut deck = Deck::new((1..=5).collect(), Shuffle::Perfect);
+    /// let hands = deck.deal(2, 3);
+    ///
+    /// assert_eq!(hands[0].cards(), [1, 3, 5]);
+    /// assert_eq!(hands[1].cards(), [2, 4]);
+    /// assert_eq!(deck.take_events(), [DeckEvent::Exhausted { missing: 1 }]);
+    /// ```
+    #[inline]
+    pub fn deal(&mut self, hands: usize, cards_each: usize) -> Vec<Hand<T>> {
+        let wanted = hands * cards_each;
+        let dealt = wanted.min(self.cards.len());
+        let mut dealt_hands = (0..hands).map(|_| Hand::default()).collect::<Vec<_>>();
+        for (index, card) in self.cards.drain(..dealt).enumerate() {
+            dealt_hands[index % hands].add(card);
+        }
+
+        if self.cards.is_empty() && wanted > 0 {
+            self.events.push(DeckEvent::Exhausted { missing: wanted - dealt });
+        }
+
+        dealt_hands
+    }
+
+    /// Puts a card on top of the discard pile.
+    #[inline]
+    pub fn discard(&mut self, card: T) {
+        self.discards.push(card);
+    }
+
+    /// Puts the discard pile back under the draw pile and shuffles the whole deck with its strategy, using thread-local
+    /// randomness.
+    #[inline]
+    pub fn recycle(&mut self) {
+        self.recycle_with(&mut rand::rng());
+    }
+
+    /// Puts the discard pile back under the draw pile and shuffles the whole deck with its strategy, using the given
+    /// random number generator.
+    ///
+    /// # Arguments
+    ///
+    /// * `rng` - The random number generator driving the shuffle, seed it to replay shuffles
+    #[inline]
+    pub fn recycle_with<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        self.cards.append(&mut self.discards);
+        self.shuffle_with(rng);
+    }
+
+    /// Returns the events that happened since they were last taken, oldest first, and forgets them.
+    #[inline]
+    pub fn take_events(&mut self) -> Vec<DeckEvent> {
+        mem::take(&mut self.events)
+    }
+
+    /// Puts a card back at the bottom of the deck.
+    #[inline]
+    pub fn put_back(&mut self, card: T) {
+        self.cards.push(card);
+    }
+
+    /// Returns the cards left in the deck, the first one being the top of the deck.
+    #[inline]
+    #[must_use]
+    pub fn cards(&self) -> &[T] {
+        &self.cards
+    }
+
+    /// Returns the cards discarded, the last one being on top of the pile.
+    #[inline]
+    #[must_use]
+    pub fn discards(&self) -> &[T] {
+        &self.discards
+    }
+
+    /// Returns the number of cards left in the deck.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.cards.len()
+    }
+
+    /// Returns whether the deck has no card left.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.cards.is_empty()
+    }
+
+    /// Returns the strategy used to shuffle the deck.
+    #[inline]
+    #[must_use]
+    pub fn strategy(&self) -> Shuffle {
+        self.strategy
+    }
+
+    /// Changes the strategy used to shuffle the deck from now on.
+    #[inline]
+    pub fn set_strategy(&mut self, strategy: Shuffle) {
+        self.strategy = strategy;
+    }
+}
+
+/// The cards held by a player, in the order they were received unless sorted.
+///
+/// # Type Parameters
+///
+/// * `T` - The cards of the hand
+///
+/// # Examples
+///
+/// ```
+/// use darkforge_rng::cards::{Deck, Shuffle};
+///
+/// let mut deck = Deck::new(vec![7, 2, 9, 4], Shuffle::Perfect);
+/// let mut hand = deck.deal(1, 4).remove(0);
+///
+/// hand.sort();
+/// assert_eq!(hand.cards(), [2, 4, 7, 9]);
+///
+/// hand.discard(0, &mut deck);
+/// assert_eq!(hand.cards(), [4, 7, 9]);
+/// assert_eq!(deck.discards(), [2]);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Hand<T> {
+    /// The cards held
+    cards: Vec<T>,
+}
+
+impl<T> Default for Hand<T> {
+    /// Creates an empty hand.
+    #[inline]
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+
+impl<T> Hand<T> {
+    /// Creates a hand holding the given cards.
+    #[inline]
+    #[must_use]
+    pub fn new(cards: Vec<T>) -> Self {
+        Self { cards }
+    }
+
+    /// Adds a card at the end of the hand.
+    #[inline]
+    pub fn add(&mut self, card: T) {
+        self.cards.push(card);
+    }
+
+    /// Draws the card on top of the deck into the hand, returning whether one was left.
+    #[inline]
+    pub fn draw_from(&mut self, deck: &mut Deck<T>) -> bool {
+        deck.draw().map(|card| self.add(card)).is_some()
+    }
+
+    /// Takes the card at the given position out of the hand, e.g. to play it, if the hand holds that many cards.
+    #[inline]
+    pub fn take(&mut self, index: usize) -> Option<T> {
+        (index < self.cards.len()).then(|| self.cards.remove(index))
+    }
+
+    /// Moves the card at the given position to the discard pile of the deck, returning whether the hand held that many
+    /// cards.
+    #[inline]
+    pub fn discard(&mut self, index: usize, deck: &mut Deck<T>) -> bool {
+        self.take(index).map(|card| deck.discard(card)).is_some()
+    }
+
+    /// Moves every card of the hand to the discard pile of the deck, in the order they are held.
+    #[inline]
+    pub fn discard_all(&mut self, deck: &mut Deck<T>) {
+        self.cards.drain(..).for_each(|card| deck.discard(card));
+    }
+
+    /// Sorts the cards of the hand by the given key, keeping the order of cards with equal keys.
+    #[inline]
+    pub fn sort_by_key<K: Ord, F: FnMut(&T) -> K>(&mut self, key: F) {
+        self.cards.sort_by_key(key);
+    }
+
+    /// Returns the cards held.
+    #[inline]
+    #[must_use]
+    pub fn cards(&self) -> &[T] {
+        &self.cards
+    }
+
+    /// Returns the number of cards held.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.cards.len()
+    }
+
+    /// Returns whether the hand holds no card.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.cards.is_empty()
+    }
+}
+
+impl<T: Ord> Hand<T> {
+    /// Sorts the cards of the hand in ascending order.
+    #[inline]
+    pub fn sort(&mut self) {
+        self.cards.sort();
+    }
+}
+
+#[cfg(test)]
+#[expect(clippy::expect_used, reason = "Expect is allowed is tests")]
+mod tests {
+    use std::collections::HashMap;
+
+    use rand::{SeedableRng as _, rngs::StdRng};
+    use rstest::rstest;
+
+    use super::*;
+    use crate::assert_approx;
+
+    const SAMPLES: u32 = 100_000;
+    const ERROR_TOLERANCE_PCT: f64 = 5.0 / 100.0;
+
+    /// Shuffles a fresh deck of `size` cards `samples` times and counts the positions card `0` ends up at.
+    fn top_card_positions(strategy: Shuffle, size: usize, samples: u32, seed: u64) -> Vec<u32> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut positions = vec![0u32; size];
+        for _ in 0..samples {
+            let mut cards = (0..size).collect::<Vec<_>>();
+            strategy.apply(&mut cards, &mut rng);
+            positions[cards.iter().position(|card| *card == 0).expect("should have kept the card")] += 1;
+        }
+
+        positions
+    }
+
+    /// Counts the rising sequences of the cards, i.e. the runs of consecutive cards in their original order.
+    fn rising_sequences(cards: &[usize]) -> usize {
+        let mut positions = vec![0; cards.len()];
+        for (position, card) in cards.iter().enumerate() {
+            positions[*card] = position;
+        }
+
+        1 + positions.windows(2).filter(|pair| pair[1] < pair[0]).count()
+    }
+
+    #[rstest]
+    #[case::perfect(Shuffle::Perfect)]
+    #[case::riffle(Shuffle::Riffle { passes: 3 })]
+    #[case::overhand(Shuffle::Overhand { passes: 5 })]
+    fn should_keep_every_card(#[case] strategy: Shuffle) {
+        let mut deck = Deck::new((0..52).collect::<Vec<_>>(), strategy);
+
+        deck.shuffle();
+
+        let mut cards = deck.cards().to_vec();
+        cards.sort_unstable();
+        assert_eq!((0..52).collect::<Vec<_>>(), cards);
+    }
+
+    #[rstest]
+    #[case::perfect(Shuffle::Perfect)]
+    #[case::riffle(Shuffle::Riffle { passes: 3 })]
+    #[case::overhand(Shuffle::Overhand { passes: 5 })]
+    fn should_replay_shuffle_given_same_seed(#[case] strategy: Shuffle) {
+        let mut first = Deck::new((0..52).collect::<Vec<_>>(), strategy);
+        let mut second = first.clone();
+
+        first.shuffle_with(&mut StdRng::seed_from_u64(1915));
+        second.shuffle_with(&mut StdRng::seed_from_u64(1915));
+
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn should_deal_every_order_evenly_given_perfect_shuffle() {
+        let mut rng = StdRng::seed_from_u64(1915);
+        let mut orders = HashMap::<Vec<usize>, u32>::new();
+        for _ in 0..SAMPLES {
+            let mut cards = (0..4).collect::<Vec<_>>();
+            Shuffle::Perfect.apply(&mut cards, &mut rng);
+            *orders.entry(cards).or_default() += 1;
+        }
+
+        let approx_expect = f64::from(SAMPLES) / 24.0;
+        #[expect(clippy::cast_sign_loss, clippy::cast_possible_truncation, reason = "Exception as this is a test")]
+        let error_tolerance = (approx_expect * ERROR_TOLERANCE_PCT) as u64;
+
+        assert_eq!(24, orders.len(), "should have dealt every order");
+        #[expect(clippy::cast_possible_truncation, reason = "Exception as this is a test")]
+        for count in orders.into_values() {
+            assert_approx!(approx_expect as i64, i64::from(count), error_tolerance);
+        }
+    }
+
+    #[rstest]
+    #[case::perfect(Shuffle::Perfect)]
+    #[case::riffle(Shuffle::Riffle { passes: 6 })]
+    #[case::overhand(Shuffle::Overhand { passes: 30 })]
+    fn should_send_top_card_anywhere_evenly_given_enough_passes(#[case] strategy: Shuffle) {
+        let positions = top_card_positions(strategy, 8, SAMPLES / 5, 1915);
+
+        let approx_expect = f64::from(SAMPLES / 5) / 8.0;
+        #[expect(clippy::cast_sign_loss, clippy::cast_possible_truncation, reason = "Exception as this is a test")]
+        let error_tolerance = (approx_expect * ERROR_TOLERANCE_PCT * 2.0) as u64;
+
+        #[expect(clippy::cast_possible_truncation, reason = "Exception as this is a test")]
+        for count in positions {
+            assert_approx!(approx_expect as i64, i64::from(count), error_tolerance);
+        }
+    }
+
+    #[test]
+    fn should_drop_top_card_from_top_half_first_given_single_riffle() {
+        let positions = top_card_positions(Shuffle::Riffle { passes: 1 }, 13, SAMPLES, 1915);
+
+        for (position, count) in positions.iter().take(4).enumerate() {
+            let approx_expect = i64::from(SAMPLES >> (position + 1));
+            #[expect(clippy::cast_sign_loss, reason = "Exception as this is a test")]
+            let error_tolerance = (approx_expect / 20) as u64;
+            assert_approx!(approx_expect, i64::from(*count), error_tolerance);
+        }
+    }
+
+    #[test]
+    fn should_leave_two_rising_sequences_at_most_given_single_riffle() {
+        let mut rng = StdRng::seed_from_u64(1915);
+        let mut interleaved = 0;
+        for _ in 0..1_000 {
+            let mut cards = (0..52).collect::<Vec<_>>();
+            Shuffle::Riffle { passes: 1 }.apply(&mut cards, &mut rng);
+
+            let sequences = rising_sequences(&cards);
+            assert!(sequences <= 2, "riffle left {sequences} rising sequences");
+            interleaved += usize::from(sequences == 2);
+        }
+
+        assert!(interleaved > 990, "only {interleaved} riffles interleaved the halves");
+    }
+
+    #[test]
+    fn should_keep_packets_together_given_single_overhand() {
+        let mut rng = StdRng::seed_from_u64(1915);
+        let mut kept = 0u32;
+        for _ in 0..SAMPLES / 10 {
+            let mut cards = (0..52).collect::<Vec<_>>();
+            Shuffle::Overhand { passes: 1 }.apply(&mut cards, &mut rng);
+            kept += u32::try_from(cards.windows(2).filter(|pair| pair[1] == pair[0] + 1).count()).expect("should count fewer pairs than cards");
+        }
+
+        let rate = f64::from(kept) / f64::from(SAMPLES / 10 * 51);
+        #[expect(clippy::cast_possible_truncation, reason = "Exception as this is a test")]
+        let kept_pct = (rate * 100.0).round() as i64;
+        assert_approx!(75i64, kept_pct, 2);
+    }
+
+    #[rstest]
+    #[case::full_round(4, 2, 0, vec![vec![0, 4], vec![1, 5], vec![2, 6], vec![3, 7]], vec![])]
+    #[case::exact(3, 4, 0, vec![vec![0, 3, 6, 9], vec![1, 4, 7, 10], vec![2, 5, 8, 11]], vec![DeckEvent::Exhausted { missing: 0 }])]
+    #[case::short(5, 3, 0, vec![vec![0, 5, 10], vec![1, 6, 11], vec![2, 7], vec![3, 8], vec![4, 9]], vec![DeckEvent::Exhausted { missing: 3 }])]
+    #[case::empty(2, 1, 12, vec![vec![], vec![]], vec![DeckEvent::Exhausted { missing: 0 }, DeckEvent::Exhausted { missing: 2 }])]
+    fn should_deal_cards_around_the_table(
+        #[case] hands: usize, #[case] cards_each: usize, #[case] drawn: usize, #[case] expected: Vec<Vec<usize>>, #[case] events: Vec<DeckEvent>,
+    ) {
+        let mut deck = Deck::new((0..12).collect::<Vec<_>>(), Shuffle::Perfect);
+        for _ in 0..drawn {
+            deck.draw().expect("should have drawn a card");
+        }
+
+        let dealt = deck.deal(hands, cards_each);
+
+        assert_eq!(expected, dealt.iter().map(|hand| hand.cards().to_vec()).collect::<Vec<_>>());
+        assert_eq!(events, deck.take_events());
+        assert_eq!(Vec::<DeckEvent>::new(), deck.take_events(), "should have forgotten the events taken");
+    }
+
+    #[test]
+    fn should_report_exhausted_draw_pile_when_drawing() {
+        let mut deck = Deck::new(vec!["Ace"], Shuffle::Perfect);
+        let mut hand = Hand::default();
+
+        assert!(hand.draw_from(&mut deck));
+        assert!(!hand.draw_from(&mut deck));
+
+        assert_eq!(["Ace"], hand.cards());
+        assert_eq!(
+            vec![DeckEvent::Exhausted { missing: 0 }, DeckEvent::Exhausted { missing: 1 }],
+            deck.take_events()
+        );
+    }
+
+    #[test]
+    fn should_recycle_discarded_cards_into_deck() {
+        let mut deck = Deck::new((0..12).collect::<Vec<_>>(), Shuffle::Riffle { passes: 7 });
+        let mut hands = deck.deal(3, 4);
+
+        hands[0].sort_by_key(|card| core::cmp::Reverse(*card));
+        assert_eq!([9, 6, 3, 0], hands[0].cards());
+        assert!(hands[0].discard(1, &mut deck));
+        assert!(!hands[0].discard(3, &mut deck));
+        assert_eq!(Some(9), hands[0].take(0));
+        hands[1].discard_all(&mut deck);
+
+        assert_eq!([6, 1, 4, 7, 10], deck.discards());
+        assert_eq!(Vec::<usize>::new(), hands[1].cards());
+
+        deck.recycle_with(&mut StdRng::seed_from_u64(1915));
+
+        let mut cards = deck.cards().to_vec();
+        cards.sort_unstable();
+        assert_eq!(vec![1, 4, 6, 7, 10], cards);
+        assert_eq!(Vec::<usize>::new(), deck.discards());
+    }
+}
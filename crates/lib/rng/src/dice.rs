@@ -20,6 +20,7 @@
 //! - A [`Dice`] trait that defines the interface for all dice types
 //! - A generic [`D`] struct that implements dice with any number of sides
 //! - Type aliases for common dice types (D4, D6, D8, D10, D12, D20, D100)
+//! - A lock-free [`Shared`] die for rolling from many threads at once
 //!
 //! ## Examples
 //!
@@ -40,7 +41,7 @@
 
 use std::sync::Mutex;
 
-use crate::rng::{Random, UniformThreadRandom};
+use crate::rng::{Random, RngService, UniformThreadRandom};
 
 /// Trait defining the interface for dice objects.
 ///
@@ -95,6 +96,37 @@ pub trait Dice {
     fn sides(&self) -> u8;
 }
 
+/// Dice behind a reference roll as the dice referred to, so trait objects can be passed where dice are expected.
+///
+/// # Examples
+///
+/// ```
+/// use darkforge_rng::dice::{Dice, D6};
+///
+/// fn highest(dice: &impl Dice) -> u8 {
+///     dice.roll_pool(2).into_iter().max().unwrap_or_default()
+/// }
+///
+/// let d6: &dyn Dice = &D6::default();
+/// assert!((1..=6).contains(&highest(&d6)));
+/// ```
+impl<D: Dice + ?Sized> Dice for &D {
+    #[inline]
+    fn roll(&self) -> u8 {
+        (**self).roll()
+    }
+
+    #[inline]
+    fn roll_pool(&self, pool: usize) -> Vec<u8> {
+        (**self).roll_pool(pool)
+    }
+
+    #[inline]
+    fn sides(&self) -> u8 {
+        (**self).sides()
+    }
+}
+
 /// A ten-sided die (pentagonal trapezohedron).
 ///
 /// # Examples
@@ -192,7 +224,7 @@ pub type D8<R> = D<8, R>;
 /// number generator of type `R` that implements the [`Random<u8>`](Random) trait.
 ///
 /// The die is thread-safe due to the internal mutex protecting the random number generator.
-/// Nevertheless, consider creating new dice for each thread to avoid waiting for the lock.
+/// Nevertheless, consider creating new dice for each thread, or a [`Shared`] die, to avoid waiting for the lock.
 ///
 /// # Type Parameters
 ///
@@ -265,6 +297,31 @@ impl<const SIDES: u8, R: Random<u8>> D<SIDES, R> {
     pub fn new(rng: R) -> Self {
         Self { rng: Mutex::new(rng) }
     }
+
+    /// Runs `f` with the random number generator of the die, e.g. to disclose the seed and the values of an audited
+    /// [`SeededRandom`](crate::rng::SeededRandom).
+    ///
+    /// # Panics
+    ///
+    /// Panics if a thread panicked while rolling the die, poisoning its lock.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use darkforge_rng::dice::{D, Dice};
+    /// use darkforge_rng::rng::SeededRandom;
+    ///
+    /// let d6 = D::<6, _>::new(SeededRandom::new(1, 6, 1915).unwrap().audited());
+    /// let rolls = d6.roll_pool(3);
+    ///
+    /// let audit = d6.with_rng(|rng| rng.audit()).unwrap();
+    /// assert_eq!(audit.values, rolls);
+    /// ```
+    #[inline]
+    pub fn with_rng<T>(&self, f: impl FnOnce(&R) -> T) -> T {
+        #[expect(clippy::unwrap_used, reason = "Mutex being poisoned is a programming error")]
+        f(&self.rng.lock().unwrap())
+    }
 }
 
 impl<const SIDES: u8> Default for D<SIDES, UniformThreadRandom<u8>> {
@@ -309,6 +366,69 @@ impl<const SIDES: u8, R: Random<u8>> Dice for D<SIDES, R> {
     }
 }
 
+/// A die that can be rolled from many threads without waiting on a lock.
+///
+/// Unlike [`D`], which serializes rolls behind a `Mutex`, this die draws from the generator of the
+/// calling thread through an [`RngService`].
+///
+/// # Type Parameters
+///
+/// * `SIDES` - The number of sides on the die (must be a positive integer)
+///
+/// # Examples
+///
+/// ```
+/// use std::{sync::Arc, thread};
+///
+/// use darkforge_rng::dice::{Dice, Shared};
+///
+/// let d6 = Arc::new(Shared::<6>::default());
+///
+/// let handles = (0..4)
+///     .map(|_| {
+///         let d6 = Arc::clone(&d6);
+///         thread::spawn(move || d6.roll_pool(3))
+///     })
+///     .collect::<Vec<_>>();
+///
+/// for handle in handles {
+///     assert_eq!(handle.join().unwrap().len(), 3);
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Shared<const SIDES: u8> {
+    /// The service shared by every thread rolling this die
+    rng: RngService<u8>,
+}
+
+impl<const SIDES: u8> Default for Shared<SIDES> {
+    /// Creates a new die rolling values between 1 and `SIDES` (inclusive).
+    #[inline]
+    fn default() -> Self {
+        Self {
+            #[expect(clippy::unwrap_used, reason = "Exception as this call failing would be a programming error")]
+            rng: RngService::new(1, SIDES).unwrap(),
+        }
+    }
+}
+
+impl<const SIDES: u8> Dice for Shared<SIDES> {
+    #[inline]
+    fn roll(&self) -> u8 {
+        self.rng.sample()
+    }
+
+    #[inline]
+    fn roll_pool(&self, pool: usize) -> Vec<u8> {
+        self.rng.sample_n(pool)
+    }
+
+    #[inline]
+    fn sides(&self) -> u8 {
+        SIDES
+    }
+}
+
 #[cfg(test)]
 #[expect(clippy::expect_used, reason = "Expect is allowed is tests")]
 mod tests {
@@ -331,6 +451,7 @@ mod tests {
     #[case::d20(D20::default())]
     #[case::d100(D100::default())]
     #[case::d42(D42::default())]
+    #[case::shared_d6(Shared::<6>::default())]
     fn should_distribute_values_evenly_when_sampling_single_values(#[case] d: impl Dice) {
         let mut buckets = vec![0u32; d.sides().into()];
 
@@ -360,6 +481,7 @@ mod tests {
     #[case::d20(D20::default())]
     #[case::d100(D100::default())]
     #[case::d42(D42::default())]
+    #[case::shared_d6(Shared::<6>::default())]
     fn should_distribute_values_evenly_when_sampling_many_values(#[case] d: impl Dice) {
         let mut buckets = vec![0u32; d.sides() as usize];
 
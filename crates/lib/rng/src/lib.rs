@@ -19,10 +19,12 @@
 //!
 //! This crate provides utilities for:
 //! - Dice simulation with various numbers of sides
+//! - Decks of cards shuffled perfectly or the way people shuffle them
 //! - Random number generation with different distributions
 //!
 //! ## Modules
 //!
+//! - [`cards`]: Decks of cards and shuffle strategies
 //! - [`dice`]: Dice simulation for tabletop gaming
 //! - [`rng`]: Random number generation
 //!
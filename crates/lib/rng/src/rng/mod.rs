@@ -17,6 +17,10 @@
 //! The module offers:
 //! - A [`Random`] trait for random number generators
 //! - An implementation of this trait using thread-local random number generation ([`UniformThreadRandom`])
+//! - An implementation of this trait replaying the same values for the same seed ([`SeededRandom`]), which can disclose
+//!   its seed and the values it generated for verification ([`Audit`])
+//! - A lock-free service that can be shared between threads ([`RngService`])
+//! - A wrapper biasing values for dramatic pacing, which is not uniform ([`PacedRandom`])
 //! - Test utilities for predictable random number generation
 //!
 //! ## Examples
@@ -39,6 +43,8 @@
 //! }
 //! ```
 
+mod paced;
+mod service;
 #[cfg(test)]
 pub mod test;
 
@@ -47,11 +53,13 @@ use std::fmt;
 
 use fmt::Formatter;
 use rand::{
+    SeedableRng as _,
     distr::{Distribution as _, Uniform, uniform::SampleUniform},
-    prelude::ThreadRng,
+    prelude::{StdRng, ThreadRng},
 };
 use thiserror::Error;
 
+pub use self::{paced::PacedRandom, service::RngService};
 use crate::Result;
 
 /// Error type for random number generation operations.
@@ -215,7 +223,7 @@ impl<T: SampleUniform> UniformThreadRandom<T> {
 
 impl<T: SampleUniform> Debug for UniformThreadRandom<T> {
     #[inline]
-    #[expect(clippy::min_ident_chars, reason = "Conflicts with lint requiring same names as trait")]
+    #[allow(clippy::min_ident_chars, reason = "Conflicts with lint requiring same names as trait")]
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         f.debug_struct("UniformThreadRandom").finish()
     }
@@ -247,6 +255,191 @@ impl<T: SampleUniform> Random<T> for UniformThreadRandom<T> {
     }
 }
 
+/// A random number generator that produces uniformly distributed values from a seed, the same seed always producing
+/// the same values.
+///
+/// This is useful to replay games deterministically, e.g. in regression tests.
+///
+/// # Type Parameters
+///
+/// * `T` - The type of values generated by this random number generator. `T` must implement `SampleUniform`
+///
+/// # Examples
+///
+/// ```
+/// use darkforge_rng::rng::{Random, SeededRandom};
+///
+/// let mut first = SeededRandom::new(1, 6, 1915).unwrap();
+/// let mut second = SeededRandom::new(1, 6, 1915).unwrap();
+///
+/// assert_eq!(first.take(10), second.take(10));
+/// ```
+pub struct SeededRandom<T: SampleUniform> {
+    /// The uniform distribution used to generate random values
+    distribution: Uniform<T>,
+
+    /// The seeded random number generator used to generate random values
+    rng: StdRng,
+
+    /// The seed the generator was created from
+    seed: u64,
+
+    /// The lower and upper bounds (inclusive) of the values generated
+    bounds: (T, T),
+
+    /// The values generated since the generator was audited, if it is
+    log: Option<Vec<T>>,
+}
+
+/// The record of the values generated by an audited [`SeededRandom`], disclosing everything needed to replay them.
+///
+/// This supports organized play, where the rolls of a game may have to be verified after the fact.
+///
+/// # Examples
+///
+/// ```
+/// use darkforge_rng::rng::{Random, SeededRandom};
+///
+/// let mut rng = SeededRandom::new(1u8, 6, 1915).unwrap().audited();
+/// let rolls = rng.take(10);
+///
+/// let audit = rng.audit().unwrap();
+/// assert_eq!(audit.seed, 1915);
+/// assert_eq!(audit.values, rolls);
+/// assert!(audit.verify().unwrap());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Audit<T> {
+    /// The algorithm of the generator, see [`SeededRandom::ALGORITHM`]
+    pub algorithm: &'static str,
+    /// The seed the generator was created from
+    pub seed: u64,
+    /// The lower bound (inclusive) of the values generated
+    pub low: T,
+    /// The upper bound (inclusive) of the values generated
+    pub high: T,
+    /// The values generated, in order
+    pub values: Vec<T>,
+}
+
+impl<T: SampleUniform + Clone + PartialEq> Audit<T> {
+    /// Replays the generator from the disclosed seed and bounds, returning whether it generates the same values.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the bounds are invalid, such as if `low > high`.
+    #[inline]
+    pub fn verify(&self) -> Result<bool> {
+        let mut replay = SeededRandom::new(self.low.clone(), self.high.clone(), self.seed)?;
+
+        Ok(self.algorithm == SeededRandom::<T>::ALGORITHM && replay.take(self.values.len()) == self.values)
+    }
+}
+
+impl<T: SampleUniform> SeededRandom<T> {
+    /// The algorithm generating the values from the seed: the standard generator of the `rand` crate, currently
+    /// `ChaCha` with 12 rounds. Replaying a seed needs the same algorithm.
+    pub const ALGORITHM: &'static str = "rand 0.9 StdRng (ChaCha12)";
+
+    /// Creates a new random number generator with the specified bounds and seed.
+    ///
+    /// # Arguments
+    ///
+    /// * `low` - The lower bound (inclusive)
+    /// * `high` - The upper bound (inclusive)
+    /// * `seed` - The seed determining the values generated
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the bounds are invalid, such as if `low > high`.
+    #[inline]
+    pub fn new(low: T, high: T, seed: u64) -> Result<Self>
+    where
+        T: Clone,
+    {
+        let distribution = Uniform::new_inclusive(low.clone(), high.clone()).map_err(RngError::InvalidDistribution)?;
+        Ok(Self {
+            distribution,
+            rng: StdRng::seed_from_u64(seed),
+            seed,
+            bounds: (low, high),
+            log: None,
+        })
+    }
+
+    /// Records every value generated from now on, so they can be exported with [`SeededRandom::audit`].
+    #[inline]
+    #[must_use]
+    pub fn audited(mut self) -> Self {
+        self.log.get_or_insert_with(Vec::new);
+        self
+    }
+
+    /// Returns the seed the generator was created from.
+    #[inline]
+    #[must_use]
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Returns the algorithm of the generator, see [`SeededRandom::ALGORITHM`].
+    #[inline]
+    #[must_use]
+    pub fn algorithm(&self) -> &'static str {
+        Self::ALGORITHM
+    }
+}
+
+impl<T: SampleUniform + Clone> SeededRandom<T> {
+    /// Returns the seed, the algorithm, the bounds and the values generated since the generator was audited, or `None`
+    /// if it is not, see [`SeededRandom::audited`].
+    #[inline]
+    #[must_use]
+    pub fn audit(&self) -> Option<Audit<T>> {
+        let values = self.log.clone()?;
+
+        Some(Audit {
+            algorithm: Self::ALGORITHM,
+            seed: self.seed,
+            low: self.bounds.0.clone(),
+            high: self.bounds.1.clone(),
+            values,
+        })
+    }
+}
+
+impl<T: SampleUniform> Debug for SeededRandom<T> {
+    #[inline]
+    #[allow(clippy::min_ident_chars, reason = "Conflicts with lint requiring same names as trait")]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SeededRandom").finish()
+    }
+}
+
+impl<T: SampleUniform + Clone> Random<T> for SeededRandom<T> {
+    /// Generates the next random value within the configured bounds.
+    #[inline]
+    fn next(&mut self) -> T {
+        let value = self.distribution.sample(&mut self.rng);
+        if let Some(log) = &mut self.log {
+            log.push(value.clone());
+        }
+
+        value
+    }
+
+    /// Generates multiple random values within the configured bounds.
+    #[inline]
+    fn take(&mut self, n: usize) -> Vec<T> {
+        let values = (&self.distribution).sample_iter(&mut self.rng).take(n).collect::<Vec<_>>();
+        if let Some(log) = &mut self.log {
+            log.extend_from_slice(&values);
+        }
+
+        values
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use rand::distr::uniform::Error;
@@ -259,4 +452,50 @@ mod tests {
         let err = UniformThreadRandom::new(10, 5).expect_err("should have failed");
         assert_eq!(DFRngError::RngError(RngError::InvalidDistribution(Error::EmptyRange)), err);
     }
+
+    #[test]
+    fn should_replay_values_given_same_seed() {
+        let mut first = SeededRandom::new(1u8, 6, 1915).expect("should have created generator");
+        let mut second = SeededRandom::new(1u8, 6, 1915).expect("should have created generator");
+        let mut other = SeededRandom::new(1u8, 6, 1916).expect("should have created generator");
+
+        let values = first.take(20);
+
+        assert_eq!(values, second.take(20));
+        assert_ne!(values, other.take(20));
+        assert!(values.iter().all(|value| (1..=6).contains(value)));
+    }
+
+    #[test]
+    fn should_disclose_seed_and_values_given_audited() {
+        let mut rng = SeededRandom::new(1u8, 6, 1915).expect("should have created generator");
+        rng.take(5);
+        assert_eq!(None, rng.audit(), "should not record values until audited");
+
+        let mut rng = rng.audited();
+        let mut values = rng.take(5);
+        values.push(rng.next());
+        let audit = rng.audit().expect("should have audited generator");
+
+        assert_eq!(
+            (1915, SeededRandom::<u8>::ALGORITHM, 1, 6),
+            (audit.seed, audit.algorithm, audit.low, audit.high)
+        );
+        assert_eq!(values, audit.values);
+        assert!(
+            !audit.verify().expect("should have replayed generator"),
+            "should not replay values drawn before the audit"
+        );
+
+        let mut rng = SeededRandom::new(1u8, 6, 1915).expect("should have created generator").audited();
+        rng.take(20);
+        let mut audit = rng.audit().expect("should have audited generator");
+        assert!(audit.verify().expect("should have replayed generator"));
+
+        audit.values[3] = if audit.values[3] == 6 { 1 } else { 6 };
+        assert!(
+            !audit.verify().expect("should have replayed generator"),
+            "should have spotted tampered value"
+        );
+    }
 }
@@ -0,0 +1,232 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! # Paced Random Number Generation
+//!
+//! A random number generator wrapper trading fairness for dramatic pacing, for designers who want controllable
+//! randomness in single-player adaptations.
+//!
+//! **The values generated are not uniformly distributed.** [`PacedRandom`] can:
+//! - Bias values towards the middle of their range, averaging several draws as a bell curve does
+//! - Break streaks of low values, guaranteeing a value above a threshold after too many in a row, as a pity timer does
+//!
+//! Tabletop rolls should keep using fair generators; pacing is opt-in, one generator at a time.
+//!
+//! ## Examples
+//!
+//! ```
+//! use darkforge_rng::{
+//!     dice::{D, Dice},
+//!     rng::{PacedRandom, UniformThreadRandom},
+//! };
+//!
+//! // A d6 favouring 3s and 4s, and never rolling more than two 1s or 2s in a row
+//! let rng = PacedRandom::new(UniformThreadRandom::new(1, 6).unwrap()).centered(3).pity(2, 2);
+//! let d6 = D::<6, _>::new(rng);
+//!
+//! let rolls = d6.roll_pool(100);
+//! assert!(rolls.windows(3).all(|streak| streak.iter().any(|&roll| roll > 2)));
+//! ```
+
+use core::fmt::{self, Debug, Formatter};
+
+use super::Random;
+
+/// The number of times a value is drawn again to break a streak before giving up, in case the wrapped generator cannot
+/// draw above the threshold at all.
+const PITY_ATTEMPTS: u8 = 64;
+
+/// A streak-breaking rule, see [`PacedRandom::pity`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Pity {
+    /// The highest value counting towards a streak
+    threshold: u8,
+    /// The longest streak allowed
+    streak: u8,
+}
+
+/// A random number generator wrapper biasing the values of another one for dramatic pacing.
+///
+/// **Not uniform**: see the [module documentation](self) for the biases it applies.
+///
+/// # Type Parameters
+///
+/// * `R` - The wrapped random number generator
+pub struct PacedRandom<R: Random<u8>> {
+    /// The wrapped random number generator
+    rng: R,
+
+    /// The number of draws averaged into each value, 1 keeping the wrapped distribution
+    samples: u8,
+
+    /// The streak-breaking rule, if any
+    pity: Option<Pity>,
+
+    /// The length of the current streak of values at or below the pity threshold
+    streak: u8,
+}
+
+impl<R: Random<u8>> PacedRandom<R> {
+    /// Wraps the random number generator, without biasing its values until configured to.
+    ///
+    /// # Arguments
+    ///
+    /// * `rng` - The random number generator to wrap
+    #[inline]
+    #[must_use]
+    pub fn new(rng: R) -> Self {
+        Self {
+            rng,
+            samples: 1,
+            pity: None,
+            streak: 0,
+        }
+    }
+
+    /// Biases values towards the middle of their range by averaging `samples` draws into each value, rounded to the
+    /// nearest. The more samples, the narrower the bell curve; 1 or 0 keeps the wrapped distribution.
+    ///
+    /// # Arguments
+    ///
+    /// * `samples` - The number of draws averaged into each value
+    #[inline]
+    #[must_use]
+    pub fn centered(mut self, samples: u8) -> Self {
+        self.samples = samples.max(1);
+        self
+    }
+
+    /// Breaks streaks of low values: once `streak` values in a row were at or below `threshold`, the next value is
+    /// drawn again until it is above it.
+    ///
+    /// # Arguments
+    ///
+    /// * `threshold` - The highest value counting towards a streak
+    /// * `streak` - The longest streak allowed
+    #[inline]
+    #[must_use]
+    pub fn pity(mut self, threshold: u8, streak: u8) -> Self {
+        self.pity = Some(Pity { threshold, streak });
+        self
+    }
+
+    /// Draws a value, averaging as many draws of the wrapped generator as configured.
+    fn draw(&mut self) -> u8 {
+        let samples = u16::from(self.samples);
+        let sum = self.rng.take(usize::from(self.samples)).into_iter().map(u16::from).sum::<u16>();
+
+        u8::try_from((sum + samples / 2) / samples).unwrap_or(u8::MAX)
+    }
+}
+
+impl<R: Random<u8>> Debug for PacedRandom<R> {
+    #[inline]
+    #[allow(clippy::min_ident_chars, reason = "Conflicts with lint requiring same names as trait")]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PacedRandom")
+            .field("samples", &self.samples)
+            .field("pity", &self.pity)
+            .field("streak", &self.streak)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<R: Random<u8>> Random<u8> for PacedRandom<R> {
+    /// Generates the next value, biased as configured.
+    #[inline]
+    fn next(&mut self) -> u8 {
+        let mut value = self.draw();
+        let Some(Pity { threshold, streak }) = self.pity else {
+            return value;
+        };
+
+        if self.streak >= streak {
+            for _ in 0..PITY_ATTEMPTS {
+                if value > threshold {
+                    break;
+                }
+                value = self.draw();
+            }
+        }
+        self.streak = if value > threshold { 0 } else { self.streak.saturating_add(1) };
+
+        value
+    }
+
+    /// Generates multiple values, biased as configured.
+    #[inline]
+    fn take(&mut self, n: usize) -> Vec<u8> {
+        (0..n).map(|_| self.next()).collect()
+    }
+}
+
+#[cfg(test)]
+#[expect(clippy::expect_used, reason = "Expect is allowed is tests")]
+mod tests {
+    use super::*;
+    use crate::rng::{SeededRandom, test::Repeat};
+
+    const SAMPLES: usize = 100_000;
+
+    /// Counts the values of 1 to 6 drawn from the generator.
+    fn histogram(rng: &mut impl Random<u8>) -> [usize; 6] {
+        let mut buckets = [0; 6];
+        for value in rng.take(SAMPLES) {
+            buckets[usize::from(value - 1)] += 1;
+        }
+
+        buckets
+    }
+
+    #[test]
+    fn should_keep_wrapped_distribution_given_no_pacing() {
+        let mut paced = PacedRandom::new(SeededRandom::new(1u8, 6, 1915).expect("should have created generator"));
+        let mut fair = SeededRandom::new(1u8, 6, 1915).expect("should have created generator");
+
+        assert_eq!(fair.take(100), paced.take(100));
+    }
+
+    #[test]
+    fn should_bias_values_towards_middle_given_centered() {
+        let fair = histogram(&mut SeededRandom::new(1u8, 6, 1915).expect("should have created generator"));
+        let centered = histogram(&mut PacedRandom::new(SeededRandom::new(1u8, 6, 1915).expect("should have created generator")).centered(3));
+
+        assert_eq!(SAMPLES, centered.iter().sum::<usize>());
+        assert!(centered[0] * 4 < fair[0], "1s were not rarer: {centered:?} against {fair:?}");
+        assert!(centered[5] * 4 < fair[5], "6s were not rarer: {centered:?} against {fair:?}");
+        assert!(
+            centered[2] + centered[3] > (fair[2] + fair[3]) * 3 / 2,
+            "3s and 4s were not likelier: {centered:?}"
+        );
+    }
+
+    #[test]
+    fn should_break_streaks_of_low_values_given_pity() {
+        let mut paced = PacedRandom::new(SeededRandom::new(1u8, 6, 1915).expect("should have created generator")).pity(3, 2);
+
+        let values = paced.take(SAMPLES);
+
+        assert!(
+            values.windows(3).all(|streak| streak.iter().any(|&value| value > 3)),
+            "found a streak of 3 values of 3 or less"
+        );
+        let fair = SeededRandom::new(1u8, 6, 1915).expect("should have created generator").take(SAMPLES);
+        assert!(values.iter().filter(|&&value| value > 3).count() > fair.iter().filter(|&&value| value > 3).count());
+    }
+
+    #[test]
+    fn should_give_up_breaking_streak_given_generator_cannot_draw_above_threshold() {
+        let mut paced = PacedRandom::new(Repeat(1u8)).pity(3, 1);
+
+        assert_eq!(vec![1, 1, 1], paced.take(3));
+    }
+}
@@ -0,0 +1,228 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+//! # Shared Random Number Generation
+//!
+//! A thread-safe random number generator that can be shared between threads without locking.
+//!
+//! [`RngService`] only holds the distribution to sample from. Every draw uses the generator of the
+//! calling thread, so parallel systems such as faction turns each roll against their own generator
+//! instead of contending on a single lock.
+//!
+//! ## Examples
+//!
+//! ```
+//! use std::{sync::Arc, thread};
+//!
+//! use darkforge_rng::rng::RngService;
+//!
+//! let service = Arc::new(RngService::new(1u8, 6).unwrap());
+//!
+//! let handles = (0..4)
+//!     .map(|_| {
+//!         let service = Arc::clone(&service);
+//!         thread::spawn(move || service.sample_n(10))
+//!     })
+//!     .collect::<Vec<_>>();
+//!
+//! for handle in handles {
+//!     let values = handle.join().unwrap();
+//!     assert!(values.iter().all(|value| (1..=6).contains(value)));
+//! }
+//! ```
+
+use core::fmt::{self, Debug, Formatter};
+
+use rand::distr::{Distribution as _, Uniform, uniform::SampleUniform};
+
+use super::{Random, RngError};
+use crate::Result;
+
+/// A random number generator that can be shared between threads without locking.
+///
+/// Each call samples from the thread-local generator of the calling thread, so the service is
+/// `Send + Sync` and cheap to clone.
+///
+/// # Type Parameters
+///
+/// * `T` - The type of values generated by this service. `T` must implement `SampleUniform`
+///
+/// # Examples
+///
+/// ```
+/// use darkforge_rng::rng::{Random, RngService};
+///
+/// let service = RngService::new(1, 100).unwrap();
+///
+/// // Sample through a shared reference
+/// let value = service.sample();
+/// assert!(value >= 1 && value <= 100);
+///
+/// // Or through the `Random` trait
+/// let mut rng = service.clone();
+/// let values = rng.take(5);
+/// assert_eq!(values.len(), 5);
+/// ```
+pub struct RngService<T: SampleUniform> {
+    /// The uniform distribution shared by every thread
+    distribution: Uniform<T>,
+}
+
+impl<T: SampleUniform> RngService<T> {
+    /// Creates a new service with the specified bounds.
+    ///
+    /// # Arguments
+    ///
+    /// * `low` - The lower bound (inclusive)
+    /// * `high` - The upper bound (inclusive)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use darkforge_rng::rng::RngService;
+    ///
+    /// let service = RngService::new(1, 6).unwrap();
+    ///
+    /// // This will fail because the lower bound is greater than the upper bound
+    /// let result = RngService::<u8>::new(10, 5);
+    /// assert!(result.is_err());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the bounds are invalid, such as if `low > high`.
+    #[inline]
+    pub fn new(low: T, high: T) -> Result<Self> {
+        let distribution = Uniform::new_inclusive(low, high).map_err(RngError::InvalidDistribution)?;
+        Ok(Self { distribution })
+    }
+
+    /// Generates a random value using the generator of the calling thread.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use darkforge_rng::rng::RngService;
+    ///
+    /// let service = RngService::new(1, 20).unwrap();
+    /// let value = service.sample();
+    /// assert!(value >= 1 && value <= 20);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn sample(&self) -> T {
+        self.distribution.sample(&mut rand::rng())
+    }
+
+    /// Generates `n` random values using the generator of the calling thread.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The number of random values to generate
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use darkforge_rng::rng::RngService;
+    ///
+    /// let service = RngService::new(1, 10).unwrap();
+    /// let values = service.sample_n(5);
+    /// assert_eq!(values.len(), 5);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn sample_n(&self, n: usize) -> Vec<T> {
+        (&self.distribution).sample_iter(rand::rng()).take(n).collect()
+    }
+}
+
+impl<T: SampleUniform> Clone for RngService<T>
+where
+    Uniform<T>: Clone,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            distribution: self.distribution.clone(),
+        }
+    }
+}
+
+impl<T: SampleUniform> Debug for RngService<T> {
+    #[inline]
+    #[allow(clippy::min_ident_chars, reason = "Conflicts with lint requiring same names as trait")]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RngService").finish()
+    }
+}
+
+impl<T: SampleUniform> Random<T> for RngService<T> {
+    #[inline]
+    fn next(&mut self) -> T {
+        self.sample()
+    }
+
+    #[inline]
+    fn take(&mut self, n: usize) -> Vec<T> {
+        self.sample_n(n)
+    }
+}
+
+impl<T: SampleUniform> Random<T> for &RngService<T> {
+    #[inline]
+    fn next(&mut self) -> T {
+        self.sample()
+    }
+
+    #[inline]
+    fn take(&mut self, n: usize) -> Vec<T> {
+        self.sample_n(n)
+    }
+}
+
+#[cfg(test)]
+#[expect(clippy::expect_used, reason = "Expect is allowed is tests")]
+mod tests {
+    use std::{sync::Arc, thread};
+
+    use rand::distr::uniform::Error;
+
+    use super::*;
+    use crate::DFRngError;
+
+    #[test]
+    fn should_return_error_when_upper_bound_is_smaller_than_lower_bound() {
+        let err = RngService::new(10, 5).expect_err("should have failed");
+        assert_eq!(DFRngError::RngError(RngError::InvalidDistribution(Error::EmptyRange)), err);
+    }
+
+    #[test]
+    fn should_sample_within_bounds_from_many_threads() {
+        let service = Arc::new(RngService::new(1u8, 6).expect("should have created the service"));
+
+        let handles = (0..8)
+            .map(|_| {
+                let service = Arc::clone(&service);
+                thread::spawn(move || service.sample_n(10_000))
+            })
+            .collect::<Vec<_>>();
+
+        for handle in handles {
+            let values = handle.join().expect("should have joined the thread");
+            assert_eq!(values.len(), 10_000);
+            assert!(values.iter().all(|value| (1..=6).contains(value)), "should have stayed within bounds");
+            for face in 1..=6 {
+                assert!(values.contains(&face), "should have rolled a {face}");
+            }
+        }
+    }
+}
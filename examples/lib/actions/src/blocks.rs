@@ -0,0 +1,86 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+use std::{
+    fs,
+    io::{BufRead, Write},
+    path::PathBuf,
+    process::ExitCode,
+};
+
+use clap::Subcommand;
+use darkforge::{campaign::Campaign, npc::Npc, permission::Actor};
+use darkforge_data::store::sql::sqlite::SqliteStore;
+use uuid::Uuid;
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Create NPCs and factions from stat blocks, read from a file or pasted on standard input
+    Import {
+        /// Path to the stat blocks, standard input if not given
+        file: Option<PathBuf>,
+        /// Path to the campaign database
+        #[arg(long, default_value = "campaign.db")]
+        db: PathBuf,
+    },
+    /// Write NPCs and factions as stat blocks, every one of them if no id is given
+    Export {
+        /// Ids of the NPCs and factions
+        ids: Vec<Uuid>,
+        /// Path to the campaign database
+        #[arg(long, default_value = "campaign.db")]
+        db: PathBuf,
+    },
+}
+
+impl Command {
+    pub async fn run(self, input: &mut impl BufRead, out: &mut impl Write) -> anyhow::Result<ExitCode> {
+        match self {
+            Command::Import { file, db } => import(file, db, input, out).await,
+            Command::Export { ids, db } => export(ids, db, out).await,
+        }
+    }
+}
+
+async fn import(file: Option<PathBuf>, db: PathBuf, input: &mut impl BufRead, out: &mut impl Write) -> anyhow::Result<ExitCode> {
+    let text = if let Some(file) = file {
+        fs::read_to_string(file)?
+    } else {
+        let mut text = String::new();
+        input.read_to_string(&mut text)?;
+        text
+    };
+
+    let mut campaign = Campaign::open(SqliteStore::open(db).await?).await?;
+    let entities = campaign.import_blocks(Actor::Gm, &text).await?;
+    writeln!(out, "imported {} stat block(s)", entities.len())?;
+    for entity in entities {
+        writeln!(out, "{entity}")?;
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+async fn export(ids: Vec<Uuid>, db: PathBuf, out: &mut impl Write) -> anyhow::Result<ExitCode> {
+    let mut campaign = Campaign::open(SqliteStore::open(db).await?).await?;
+    let ids = if ids.is_empty() {
+        let mut ids = campaign.all::<Npc>().await?.into_iter().map(|(entity, _)| entity).collect::<Vec<_>>();
+        ids.extend(campaign.factions().await?.into_iter().map(|(entity, _)| entity));
+        ids
+    } else {
+        ids
+    };
+
+    write!(out, "{}", campaign.export_blocks(&ids).await?)?;
+
+    Ok(ExitCode::SUCCESS)
+}
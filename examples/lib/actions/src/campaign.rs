@@ -0,0 +1,234 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Write},
+    path::PathBuf,
+    process::ExitCode,
+};
+
+use clap::Subcommand;
+use darkforge::{campaign::Campaign, export::CampaignExport, worldgen::WorldGen};
+use darkforge_data::{
+    export,
+    pack::Pack,
+    store::{Verify, delta::Delta, sql::sqlite::SqliteStore},
+};
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Create a new campaign database from a content pack
+    New {
+        /// Name of the campaign
+        name: String,
+        /// Path to the campaign database
+        #[arg(long, default_value = "campaign.db")]
+        db: PathBuf,
+        /// Path to the content pack directory
+        #[arg(long, default_value = "data/defaults")]
+        pack: PathBuf,
+        /// Generate the starting world from this seed instead of using every faction of the pack
+        #[arg(long)]
+        seed: Option<u64>,
+        /// Number of factions to select when generating the world
+        #[arg(long, default_value_t = WorldGen::DEFAULT_FACTIONS, requires = "seed")]
+        factions: usize,
+    },
+    /// Export a campaign database as versioned JSON
+    Export {
+        /// Path to the campaign database
+        #[arg(long, default_value = "campaign.db")]
+        db: PathBuf,
+        /// Path to write the export to
+        #[arg(long, default_value = "campaign.json")]
+        to: PathBuf,
+    },
+    /// Create a new campaign database from an export, upgrading exports of older versions
+    Import {
+        /// Path to the export
+        #[arg(long, default_value = "campaign.json")]
+        from: PathBuf,
+        /// Path to the campaign database
+        #[arg(long, default_value = "campaign.db")]
+        db: PathBuf,
+        /// Path to the content pack directory the campaign was created from
+        #[arg(long, default_value = "data/defaults")]
+        pack: PathBuf,
+    },
+    /// Export the changes made to a campaign database since a journal entry, to sync another device
+    Delta {
+        /// Path to the campaign database
+        #[arg(long, default_value = "campaign.db")]
+        db: PathBuf,
+        /// Sequence number of the last journal entry the other device holds, 0 for the whole campaign
+        #[arg(long, default_value_t = 0)]
+        since: i64,
+        /// Path to write the delta to
+        #[arg(long, default_value = "delta.json")]
+        to: PathBuf,
+    },
+    /// Apply a delta exported on another device to a campaign database
+    Sync {
+        /// Path to the delta
+        #[arg(long, default_value = "delta.json")]
+        from: PathBuf,
+        /// Path to the campaign database
+        #[arg(long, default_value = "campaign.db")]
+        db: PathBuf,
+        /// Path to the content pack directory the campaign was created from
+        #[arg(long, default_value = "data/defaults")]
+        pack: PathBuf,
+    },
+    /// Check a campaign database for integrity issues
+    Verify {
+        /// Path to the campaign database
+        #[arg(long, default_value = "campaign.db")]
+        db: PathBuf,
+        /// Apply the suggested repairs
+        #[arg(long)]
+        repair: bool,
+    },
+}
+
+impl Command {
+    pub async fn run(self, out: &mut impl Write) -> anyhow::Result<ExitCode> {
+        match self {
+            Command::New {
+                name,
+                db,
+                pack,
+                seed,
+                factions,
+            } => new(name, db, pack, seed.map(|seed| WorldGen::new(seed).factions(factions)), out).await,
+            Command::Export { db, to } => export(db, to, out).await,
+            Command::Import { from, db, pack } => import(from, db, pack, out).await,
+            Command::Delta { db, since, to } => delta(db, since, to, out).await,
+            Command::Sync { from, db, pack } => sync(from, db, pack, out).await,
+            Command::Verify { db, repair } => verify(db, repair, out).await,
+        }
+    }
+}
+
+async fn new(name: String, db: PathBuf, pack: PathBuf, generator: Option<WorldGen>, out: &mut impl Write) -> anyhow::Result<ExitCode> {
+    let pack = Pack::open(pack)?;
+    let store = SqliteStore::open(&db).await?;
+    let mut campaign = match generator {
+        Some(generator) => generator.bootstrap(store, name, &pack).await?,
+        None => Campaign::bootstrap(store, name, &pack).await?,
+    };
+
+    let factions = campaign.factions().await?;
+    writeln!(
+        out,
+        "created campaign {} in {} with {} faction(s) from pack {}",
+        campaign.info().name,
+        db.display(),
+        factions.len(),
+        campaign.info().pack
+    )?;
+    if let Some(situation) = campaign.situation().await? {
+        writeln!(out, "starting situation: {}", situation.descriptor.label())?;
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+async fn export(db: PathBuf, to: PathBuf, out: &mut impl Write) -> anyhow::Result<ExitCode> {
+    let mut campaign = Campaign::open(SqliteStore::open(db).await?).await?;
+    let exported = campaign.export().await?;
+    export::export(&exported, BufWriter::new(File::create(&to)?))?;
+
+    writeln!(
+        out,
+        "exported campaign {} with {} character(s) to {}",
+        exported.info.name,
+        exported.roster.len(),
+        to.display()
+    )?;
+
+    Ok(ExitCode::SUCCESS)
+}
+
+async fn import(from: PathBuf, db: PathBuf, pack: PathBuf, out: &mut impl Write) -> anyhow::Result<ExitCode> {
+    let exported = export::import::<CampaignExport>(BufReader::new(File::open(from)?))?;
+    let pack = Pack::open(pack)?;
+    let campaign = Campaign::import(SqliteStore::open(&db).await?, &pack, &exported).await?;
+
+    writeln!(
+        out,
+        "imported campaign {} with {} character(s) into {}",
+        campaign.info().name,
+        exported.roster.len(),
+        db.display()
+    )?;
+
+    Ok(ExitCode::SUCCESS)
+}
+
+async fn delta(db: PathBuf, since: i64, to: PathBuf, out: &mut impl Write) -> anyhow::Result<ExitCode> {
+    let mut campaign = Campaign::open(SqliteStore::open(db).await?).await?;
+    let delta = campaign.delta(since).await?;
+    export::export(&delta, BufWriter::new(File::create(&to)?))?;
+
+    writeln!(
+        out,
+        "exported {} journal entry(ies) of campaign {} up to {} to {}",
+        delta.journal.len(),
+        campaign.info().name,
+        delta.until,
+        to.display()
+    )?;
+
+    Ok(ExitCode::SUCCESS)
+}
+
+async fn sync(from: PathBuf, db: PathBuf, pack: PathBuf, out: &mut impl Write) -> anyhow::Result<ExitCode> {
+    let delta = export::import::<Delta>(BufReader::new(File::open(from)?))?;
+    let pack = Pack::open(pack)?;
+    let campaign = Campaign::sync(SqliteStore::open(&db).await?, &pack, &delta).await?;
+
+    writeln!(
+        out,
+        "synced campaign {} in {} up to journal entry {}",
+        campaign.info().name,
+        db.display(),
+        delta.until
+    )?;
+
+    Ok(ExitCode::SUCCESS)
+}
+
+async fn verify(db: PathBuf, repair: bool, out: &mut impl Write) -> anyhow::Result<ExitCode> {
+    let mut store = SqliteStore::open(db).await?;
+    let report = store.verify().await?;
+
+    if report.is_clean() {
+        writeln!(out, "no issues found")?;
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    writeln!(out, "found {} issue(s):", report.issues.len())?;
+    for issue in &report.issues {
+        writeln!(out, "  - {issue}")?;
+    }
+
+    if !repair {
+        writeln!(out, "run again with --repair to fix them")?;
+        return Ok(ExitCode::FAILURE);
+    }
+
+    let affected = store.repair(&report.repairs()).await?;
+    writeln!(out, "repaired {affected} row(s)")?;
+
+    Ok(ExitCode::SUCCESS)
+}
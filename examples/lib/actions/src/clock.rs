@@ -0,0 +1,196 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+use std::{collections::HashMap, io::Write, path::PathBuf, process::ExitCode};
+
+use anyhow::{anyhow, bail};
+use clap::Subcommand;
+use darkforge::{campaign::Campaign, clock::Clock, permission::Actor};
+use darkforge_data::store::sql::sqlite::SqliteStore;
+use uuid::Uuid;
+
+/// Pie glyphs showing how far along a clock is, from empty to complete.
+const PIES: [char; 5] = ['○', '◔', '◑', '◕', '●'];
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Add a clock to the campaign
+    Add {
+        /// Label of the clock
+        label: String,
+        /// Number of segments in the clock
+        #[arg(long, default_value_t = 4)]
+        segments: u8,
+        /// Number of segments already filled
+        #[arg(long, default_value_t = 0)]
+        filled: u8,
+        /// Name of the faction owning the clock
+        #[arg(long)]
+        faction: Option<String>,
+        /// Path to the campaign database
+        #[arg(long, default_value = "campaign.db")]
+        db: PathBuf,
+    },
+    /// Fill segments of a clock
+    Tick {
+        /// Id of the clock, or a unique prefix of it
+        clock: String,
+        /// Number of segments to fill
+        #[arg(long, default_value_t = 1)]
+        ticks: u8,
+        /// Path to the campaign database
+        #[arg(long, default_value = "campaign.db")]
+        db: PathBuf,
+    },
+    /// List the clocks of the campaign
+    List {
+        /// Include completed clocks
+        #[arg(long)]
+        all: bool,
+        /// Path to the campaign database
+        #[arg(long, default_value = "campaign.db")]
+        db: PathBuf,
+    },
+    /// Fill every remaining segment of a clock
+    Complete {
+        /// Id of the clock, or a unique prefix of it
+        clock: String,
+        /// Path to the campaign database
+        #[arg(long, default_value = "campaign.db")]
+        db: PathBuf,
+    },
+}
+
+impl Command {
+    pub async fn run(self, out: &mut impl Write) -> anyhow::Result<ExitCode> {
+        match self {
+            Command::Add {
+                label,
+                segments,
+                filled,
+                faction,
+                db,
+            } => add(label, segments, filled, faction, db, out).await,
+            Command::Tick { clock, ticks, db } => tick(&clock, Some(ticks), db, out).await,
+            Command::List { all, db } => list(all, db, out).await,
+            Command::Complete { clock, db } => tick(&clock, None, db, out).await,
+        }
+    }
+}
+
+async fn add(label: String, segments: u8, filled: u8, faction: Option<String>, db: PathBuf, out: &mut impl Write) -> anyhow::Result<ExitCode> {
+    let mut campaign = Campaign::open(SqliteStore::open(db).await?).await?;
+    let owner = match faction {
+        Some(name) => Some(
+            campaign
+                .factions()
+                .await?
+                .into_iter()
+                .find(|(_, faction)| faction.descriptor().label().eq_ignore_ascii_case(&name))
+                .map(|(entity, _)| entity)
+                .ok_or_else(|| anyhow!("no faction named {name}"))?,
+        ),
+        None => None,
+    };
+
+    let clock = Clock::with_progress(label, segments, filled)?;
+    let entity = campaign.add_clock(Actor::Gm, owner, &clock).await?;
+    writeln!(out, "added clock {entity}")?;
+    writeln!(out, "{}", render(&clock))?;
+
+    Ok(ExitCode::SUCCESS)
+}
+
+async fn tick(clock: &str, ticks: Option<u8>, db: PathBuf, out: &mut impl Write) -> anyhow::Result<ExitCode> {
+    let mut campaign = Campaign::open(SqliteStore::open(db).await?).await?;
+    let entity = find(&mut campaign, clock).await?;
+
+    let ticked = match ticks {
+        Some(ticks) => campaign.tick_clock(Actor::Gm, entity, ticks).await?,
+        None => campaign.complete_clock(Actor::Gm, entity).await?,
+    };
+    writeln!(out, "filled {} segment(s)", ticked.ticked)?;
+    writeln!(out, "{}", render(&ticked.clock))?;
+    if ticked.clock.is_complete() {
+        writeln!(out, "clock complete")?;
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+async fn list(all: bool, db: PathBuf, out: &mut impl Write) -> anyhow::Result<ExitCode> {
+    let mut campaign = Campaign::open(SqliteStore::open(db).await?).await?;
+    let factions = campaign
+        .factions()
+        .await?
+        .into_iter()
+        .map(|(entity, faction)| (entity, faction.descriptor().label().to_owned()))
+        .collect::<HashMap<_, _>>();
+
+    let clocks = campaign
+        .clocks()
+        .await?
+        .into_iter()
+        .filter(|(_, _, clock)| all || !clock.is_complete())
+        .collect::<Vec<_>>();
+    if clocks.is_empty() {
+        writeln!(out, "no clocks")?;
+    }
+
+    for (entity, owner, clock) in clocks {
+        write!(out, "{entity}  {}", render(&clock))?;
+        match owner.and_then(|owner| factions.get(&owner)) {
+            Some(faction) => writeln!(out, " ({faction})")?,
+            None => writeln!(out)?,
+        }
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Finds the clock with the given id, or the only clock whose id starts with it.
+async fn find(campaign: &mut Campaign, id: &str) -> anyhow::Result<Uuid> {
+    if let Ok(id) = Uuid::parse_str(id) {
+        return Ok(id);
+    }
+
+    let prefix = id.to_ascii_lowercase();
+    let mut matches = campaign
+        .clocks()
+        .await?
+        .into_iter()
+        .map(|(entity, _, _)| entity)
+        .filter(|entity| entity.to_string().starts_with(&prefix));
+
+    match (matches.next(), matches.next()) {
+        (Some(entity), None) => Ok(entity),
+        (None, _) => bail!("no clock matches {id}"),
+        (Some(_), Some(_)) => bail!("several clocks match {id}"),
+    }
+}
+
+/// Renders the clock as a pie glyph, one glyph per segment, the progress and the label, e.g. `◑ ●●○○ 2/4 Alarm`.
+fn render(clock: &Clock) -> String {
+    let (filled, segments) = (usize::from(clock.filled()), usize::from(clock.segments()));
+    let pie = if clock.is_complete() {
+        PIES[4]
+    } else {
+        PIES[(filled * 4).div_ceil(segments).min(3)]
+    };
+
+    format!(
+        "{pie} {}{} {filled}/{segments} {}",
+        "●".repeat(filled),
+        "○".repeat(segments - filled),
+        clock.label()
+    )
+}
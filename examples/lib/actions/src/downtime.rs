@@ -0,0 +1,217 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+use std::{
+    io::{BufRead, Write},
+    path::PathBuf,
+    process::ExitCode,
+};
+
+use anyhow::{anyhow, bail};
+use darkforge::{
+    action::{self, roll_pool},
+    campaign::Campaign,
+    crew::Heat,
+    downtime::{Activity, Applied, FREE_ACTIVITIES, Outcome},
+    gambling::{Cheat, Wager},
+    permission::Actor,
+};
+use darkforge_data::store::sql::sqlite::SqliteStore;
+use darkforge_rng::dice::D6;
+use uuid::Uuid;
+
+/// The activities offered, in menu order.
+const MENU: [&str; 5] = ["Indulge vice", "Recover", "Reduce heat", "Work on a long-term project", "Gamble at bones"];
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Name of the character taking downtime
+    character: String,
+    /// Path to the campaign database
+    #[arg(long, default_value = "campaign.db")]
+    db: PathBuf,
+}
+
+impl Args {
+    pub async fn run(self, input: &mut impl BufRead, out: &mut impl Write) -> anyhow::Result<ExitCode> {
+        let mut campaign = Campaign::open(SqliteStore::open(&self.db).await?).await?;
+        let entity = campaign
+            .active_roster(None)
+            .await?
+            .into_iter()
+            .find(|entry| entry.character.name().eq_ignore_ascii_case(&self.character))
+            .map(|entry| entry.entity)
+            .ok_or_else(|| anyhow!("no active character named {}", self.character))?;
+
+        let sheet = campaign.sheet(entity).await?;
+        writeln!(out, "downtime for {} (stress {})", sheet.character.name(), sheet.stress.value())?;
+
+        for round in 1..=FREE_ACTIVITIES {
+            writeln!(out, "activity {round} of {FREE_ACTIVITIES}:")?;
+            for (index, label) in MENU.iter().enumerate() {
+                writeln!(out, "  {}) {label}", index + 1)?;
+            }
+            writeln!(out, "  0) Done")?;
+
+            let Some(activity) = choose(&mut campaign, entity, input, out).await? else {
+                break;
+            };
+            let outcome = campaign.downtime(Actor::Gm, entity, activity, &D6::default()).await?;
+            report(&outcome, out)?;
+        }
+
+        let sheet = campaign.sheet(entity).await?;
+        writeln!(out, "downtime over for {} (stress {})", sheet.character.name(), sheet.stress.value())?;
+
+        Ok(ExitCode::SUCCESS)
+    }
+}
+
+/// Asks for an activity and the details it needs. Returns `None` when the player is done.
+async fn choose(campaign: &mut Campaign, character: Uuid, input: &mut impl BufRead, out: &mut impl Write) -> anyhow::Result<Option<Activity>> {
+    let activity = match ask(input, out, "> ")? {
+        0 => return Ok(None),
+        1 => Activity::IndulgeVice,
+        2 => Activity::Recover {
+            rating: rating(ask(input, out, "healer rating? ")?)?,
+        },
+        3 => {
+            let crews = campaign.all::<Heat>().await?;
+            if crews.is_empty() {
+                bail!("the campaign has no crew");
+            }
+            for (index, (crew, heat)) in crews.iter().enumerate() {
+                writeln!(out, "  {}) crew {crew} (heat {})", index + 1, heat.heat())?;
+            }
+            let (crew, _) = pick(&crews, ask(input, out, "crew? ")?)?;
+
+            Activity::ReduceHeat {
+                crew: *crew,
+                rating: rating(ask(input, out, "action rating? ")?)?,
+            }
+        }
+        4 => {
+            let clocks = campaign
+                .clocks()
+                .await?
+                .into_iter()
+                .filter(|(_, owner, clock)| *owner == Some(character) && !clock.is_complete())
+                .collect::<Vec<_>>();
+            if clocks.is_empty() {
+                bail!("the character has no long-term project; add one with `forge clock add`");
+            }
+            for (index, (_, _, clock)) in clocks.iter().enumerate() {
+                writeln!(out, "  {}) {} {}/{}", index + 1, clock.label(), clock.filled(), clock.segments())?;
+            }
+            let (clock, _, _) = pick(&clocks, ask(input, out, "project? ")?)?;
+
+            Activity::Project {
+                clock: *clock,
+                rating: rating(ask(input, out, "action rating? ")?)?,
+            }
+        }
+        5 => {
+            let stake = u8::try_from(ask(input, out, "stake? ")?).map_err(|_| anyhow!("stake is too high"))?;
+            writeln!(out, "  0) Play fair")?;
+            writeln!(out, "  1) Cheat")?;
+            let cheat = match ask(input, out, "> ")? {
+                0 => None,
+                1 => Some(action::Outcome::from_dice(&roll_pool(
+                    &D6::default(),
+                    rating(ask(input, out, "action rating? ")?)?,
+                ))),
+                choice => bail!("no option {choice}"),
+            };
+
+            Activity::Gamble {
+                wager: Wager { stake, cheat },
+            }
+        }
+        choice => bail!("no activity {choice}"),
+    };
+
+    Ok(Some(activity))
+}
+
+/// Prints the prompt and reads a number from the input.
+fn ask(input: &mut impl BufRead, out: &mut impl Write, prompt: &str) -> anyhow::Result<u64> {
+    write!(out, "{prompt}")?;
+    out.flush()?;
+
+    let mut line = String::new();
+    if input.read_line(&mut line)? == 0 {
+        bail!("no answer given");
+    }
+    writeln!(out)?;
+
+    line.trim().parse().map_err(|_| anyhow!("expected a number, got {}", line.trim()))
+}
+
+/// Converts an answer into a rating.
+fn rating(answer: u64) -> anyhow::Result<u8> {
+    u8::try_from(answer).map_err(|_| anyhow!("rating {answer} is too high"))
+}
+
+/// Picks an option by its 1-based menu number.
+fn pick<T>(options: &[T], answer: u64) -> anyhow::Result<&T> {
+    usize::try_from(answer)
+        .ok()
+        .and_then(|answer| answer.checked_sub(1))
+        .and_then(|index| options.get(index))
+        .ok_or_else(|| anyhow!("no option {answer}"))
+}
+
+/// Prints the roll and the result of an activity.
+fn report(outcome: &Outcome, out: &mut impl Write) -> anyhow::Result<()> {
+    write!(out, "rolled {:?} ({:?}): ", outcome.roll.dice, outcome.roll.outcome)?;
+    match &outcome.applied {
+        Applied::StressCleared { cleared, overindulged } => {
+            write!(out, "cleared {cleared} stress")?;
+            if *overindulged {
+                write!(out, ", overindulged")?;
+            }
+        }
+        Applied::Gambled { cleared, overindulged, game } => {
+            write!(out, "cleared {cleared} stress")?;
+            if *overindulged {
+                write!(out, ", overindulged")?;
+            }
+            match game.cheat {
+                Some(Cheat::Caught) => write!(out, ", caught cheating")?,
+                Some(Cheat::Suspected) => write!(out, ", suspected of cheating")?,
+                Some(Cheat::Unnoticed) | None => {}
+            }
+            write!(out, ", bones {:?} against the house's {:?}", game.player, game.house)?;
+            if game.won() {
+                write!(out, ", won {} coin", game.winnings)?;
+            } else {
+                write!(out, ", lost {} coin", game.winnings.unsigned_abs())?;
+            }
+        }
+        Applied::Healed { ticked, healed } => {
+            write!(out, "healing clock ticked {ticked}")?;
+            if *healed {
+                write!(out, ", harm reduced")?;
+            }
+        }
+        Applied::HeatReduced { reduced } => write!(out, "heat reduced by {reduced}")?,
+        Applied::ProjectTicked { ticked, complete } => {
+            write!(out, "project ticked {ticked}")?;
+            if *complete {
+                write!(out, ", project complete")?;
+            }
+        }
+    }
+    writeln!(out)?;
+
+    Ok(())
+}
@@ -0,0 +1,62 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+use std::{collections::HashMap, io::Write, path::PathBuf, process::ExitCode};
+
+use darkforge::campaign::Campaign;
+use darkforge_data::store::sql::sqlite::SqliteStore;
+use serde_json::json;
+
+use crate::format::{Format, Report};
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Output format
+    #[arg(long, value_enum, default_value_t)]
+    format: Format,
+    /// Path to the campaign database
+    #[arg(long, default_value = "campaign.db")]
+    db: PathBuf,
+}
+
+impl Args {
+    pub async fn run(self, out: &mut impl Write) -> anyhow::Result<ExitCode> {
+        let mut campaign = Campaign::open(SqliteStore::open(&self.db).await?).await?;
+        let dashboard = campaign.dashboard().await?;
+
+        let mut clocks = HashMap::<_, Vec<_>>::new();
+        for active in &dashboard.clocks {
+            if let Some(owner) = active.owner {
+                let clock = &active.clock;
+                clocks
+                    .entry(owner)
+                    .or_default()
+                    .push(format!("{} {}/{}", clock.label(), clock.filled(), clock.segments()));
+            }
+        }
+
+        let mut report = Report::new(&["faction", "tier", "hold", "status", "clocks"]);
+        for standing in &dashboard.factions {
+            let faction = &standing.faction;
+            report.push(vec![
+                json!(faction.descriptor().label()),
+                json!(faction.tier()),
+                json!(faction.hold()),
+                json!(standing.status.value()),
+                json!(clocks.remove(&standing.entity).unwrap_or_default()),
+            ]);
+        }
+        report.write(self.format, out)?;
+
+        Ok(ExitCode::SUCCESS)
+    }
+}
@@ -0,0 +1,250 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+use std::{fmt::Write as _, io::Write};
+
+use clap::ValueEnum;
+use serde_json::{Map, Value};
+
+/// Output format of a report.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Format {
+    /// A JSON array of objects
+    Json,
+    /// A plain text table
+    #[default]
+    Table,
+    /// A markdown table
+    Markdown,
+}
+
+/// A report of rows with the same columns, written in any [`Format`].
+pub struct Report {
+    columns: Vec<&'static str>,
+    rows: Vec<Vec<Value>>,
+}
+
+impl Report {
+    /// Creates an empty report with the given columns.
+    pub fn new(columns: &[&'static str]) -> Report {
+        Report {
+            columns: columns.to_vec(),
+            rows: Vec::new(),
+        }
+    }
+
+    /// Adds a row, one value per column.
+    pub fn push(&mut self, row: Vec<Value>) {
+        self.rows.push(row);
+    }
+
+    /// Writes the report in the given format.
+    pub fn write(&self, format: Format, out: &mut impl Write) -> anyhow::Result<()> {
+        match format {
+            Format::Json => self.write_json(out),
+            Format::Table => self.write_table(out),
+            Format::Markdown => self.write_markdown(out),
+        }
+    }
+
+    fn write_json(&self, out: &mut impl Write) -> anyhow::Result<()> {
+        let rows = self
+            .rows
+            .iter()
+            .map(|row| {
+                self.columns
+                    .iter()
+                    .map(|column| (*column).to_owned())
+                    .zip(row.iter().cloned())
+                    .collect::<Map<_, _>>()
+            })
+            .collect::<Vec<_>>();
+        serde_json::to_writer_pretty(&mut *out, &rows)?;
+        writeln!(out)?;
+
+        Ok(())
+    }
+
+    fn write_table(&self, out: &mut impl Write) -> anyhow::Result<()> {
+        let cells = self.cells();
+        let widths = self.widths(&cells);
+
+        let header = self
+            .columns
+            .iter()
+            .zip(&widths)
+            .map(|(column, width)| format!("{:width$}", column.to_uppercase()));
+        writeln!(out, "{}", header.collect::<Vec<_>>().join("  ").trim_end())?;
+        for row in &cells {
+            let line = row.iter().zip(&widths).map(|(cell, width)| format!("{cell:width$}"));
+            writeln!(out, "{}", line.collect::<Vec<_>>().join("  ").trim_end())?;
+        }
+
+        Ok(())
+    }
+
+    fn write_markdown(&self, out: &mut impl Write) -> anyhow::Result<()> {
+        let cells = self.cells();
+
+        writeln!(out, "| {} |", self.columns.join(" | "))?;
+        writeln!(out, "|{}|", self.columns.iter().map(|_| " --- ").collect::<Vec<_>>().join("|"))?;
+        for row in &cells {
+            let row = row.iter().map(|cell| cell.replace('|', "\\|")).collect::<Vec<_>>();
+            writeln!(out, "| {} |", row.join(" | "))?;
+        }
+
+        Ok(())
+    }
+
+    /// Renders every value as text.
+    fn cells(&self) -> Vec<Vec<String>> {
+        self.rows.iter().map(|row| row.iter().map(cell).collect()).collect()
+    }
+
+    /// Returns the width of each column, in characters.
+    fn widths(&self, cells: &[Vec<String>]) -> Vec<usize> {
+        self.columns
+            .iter()
+            .enumerate()
+            .map(|(index, column)| {
+                cells
+                    .iter()
+                    .filter_map(|row| row.get(index))
+                    .map(|cell| cell.chars().count())
+                    .chain([column.len()])
+                    .max()
+                    .unwrap_or_default()
+            })
+            .collect()
+    }
+}
+
+/// Renders a value as text: strings unquoted, arrays joined by commas and null as a dash.
+fn cell(value: &Value) -> String {
+    match value {
+        Value::Null => "-".into(),
+        Value::String(text) => text.clone(),
+        Value::Array(values) if values.is_empty() => "-".into(),
+        Value::Array(values) => values.iter().map(cell).collect::<Vec<_>>().join(", "),
+        other => other.to_string(),
+    }
+}
+
+/// Converts the Markdown written by the reports into an HTML fragment: headings, paragraphs, lists and tables, with
+/// bold and italic text and links. Links to Markdown files are pointed at their HTML counterparts.
+pub fn html(markdown: &str) -> String {
+    let mut html = String::new();
+    let mut lines = markdown.lines().peekable();
+    while let Some(line) = lines.next() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        if let Some((level, heading)) = heading(line) {
+            let _ = writeln!(html, "<h{level}>{}</h{level}>", inline(heading));
+        } else if line.starts_with("- ") {
+            html.push_str("<ul>\n");
+            let mut item = Some(line);
+            while let Some(line) = item {
+                let _ = writeln!(html, "<li>{}</li>", inline(&line[2..]));
+                item = lines.next_if(|line| line.starts_with("- "));
+            }
+            html.push_str("</ul>\n");
+        } else if line.starts_with('|') {
+            html.push_str("<table>\n");
+            html.push_str(&row("th", line));
+            lines.next_if(|line| line.trim_start_matches('|').trim_start().starts_with("---"));
+            while let Some(line) = lines.next_if(|line| line.starts_with('|')) {
+                html.push_str(&row("td", line));
+            }
+            html.push_str("</table>\n");
+        } else {
+            let mut paragraph = vec![line];
+            while let Some(line) =
+                lines.next_if(|line| !line.trim().is_empty() && heading(line).is_none() && !line.starts_with("- ") && !line.starts_with('|'))
+            {
+                paragraph.push(line);
+            }
+            let _ = writeln!(html, "<p>{}</p>", inline(&paragraph.join(" ")));
+        }
+    }
+
+    html
+}
+
+/// Returns the level and text of a Markdown heading, or `None` if the line is not one.
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|&char| char == '#').count();
+
+    (1..=6)
+        .contains(&level)
+        .then(|| line[level..].strip_prefix(' '))
+        .flatten()
+        .map(|text| (level, text))
+}
+
+/// Renders a row of a Markdown table with cells of the given tag, unescaping pipes.
+fn row(tag: &str, line: &str) -> String {
+    let line = line.trim().trim_start_matches('|').trim_end_matches('|').replace("\\|", "\u{0}");
+    let mut html = String::from("<tr>");
+    for cell in line.split('|') {
+        let _ = write!(html, "<{tag}>{}</{tag}>", inline(&cell.trim().replace('\u{0}', "|")));
+    }
+    html.push_str("</tr>\n");
+
+    html
+}
+
+/// Renders the inline Markdown of a line: `**bold**`, `_italic_` and `[links](target.md)`, escaping the rest.
+fn inline(text: &str) -> String {
+    let mut html = String::new();
+    let mut rest = text;
+    while !rest.is_empty() {
+        if let Some((inner, end)) = rest.strip_prefix("**").and_then(|inner| Some((inner, inner.find("**")?))) {
+            let _ = write!(html, "<strong>{}</strong>", inline(&inner[..end]));
+            rest = &inner[end + 2..];
+        } else if let Some((inner, end)) = rest
+            .strip_prefix('_')
+            .and_then(|inner| Some((inner, inner.find('_').filter(|&end| end > 0)?)))
+        {
+            let _ = write!(html, "<em>{}</em>", inline(&inner[..end]));
+            rest = &inner[end + 1..];
+        } else if let Some((label, target, end)) = rest
+            .strip_prefix('[')
+            .and_then(|inner| inner.split_once("]("))
+            .and_then(|(label, target)| Some((label, target, target.find(')')?)))
+        {
+            let link = &target[..end];
+            let href = link.strip_suffix(".md").map_or_else(|| link.to_owned(), |page| format!("{page}.html"));
+            let _ = write!(html, "<a href=\"{}\">{}</a>", escape(&href), inline(label));
+            rest = &target[end + 1..];
+        } else {
+            let next = rest
+                .char_indices()
+                .skip(1)
+                .find(|(_, char)| matches!(char, '*' | '_' | '['))
+                .map_or(rest.len(), |(index, _)| index);
+            html.push_str(&escape(&rest[..next]));
+            rest = &rest[next..];
+        }
+    }
+
+    html
+}
+
+/// Escapes the characters of the text that HTML would interpret.
+pub fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
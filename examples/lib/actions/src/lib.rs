@@ -10,16 +10,80 @@
  * You should have received a copy of the GNU Affero General Public License along with this program.
  * If not, see https://www.gnu.org/licenses/.
  */
-use std::ffi::OsString;
+use std::{
+    ffi::OsString,
+    io::{BufRead, Write},
+    process::ExitCode,
+};
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
 
+mod blocks;
+mod campaign;
+mod clock;
+mod downtime;
+mod faction;
+mod format;
 mod model;
+mod oneshot;
+mod oracle;
+mod pack;
+mod session;
+mod synthesize;
+mod turn;
+mod wiki;
 
 #[derive(Parser)]
 #[command(name = "Forge Actions Example")]
 #[command(version)]
-pub struct Cli {}
+pub struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Import and export NPCs and factions as plain text stat blocks
+    Blocks {
+        #[command(subcommand)]
+        command: blocks::Command,
+    },
+    /// Manage campaign databases
+    Campaign {
+        #[command(subcommand)]
+        command: campaign::Command,
+    },
+    /// Manage progress clocks
+    Clock {
+        #[command(subcommand)]
+        command: clock::Command,
+    },
+    /// Take a character through their downtime activities
+    Downtime(downtime::Args),
+    /// Report the factions' tier, hold, status toward the crew and active clocks
+    Factions(faction::Args),
+    /// Generate a ready-to-play one-shot for convention play: crew, NPCs, target, opposing faction and complication
+    Oneshot(oneshot::Args),
+    /// Ask the oracle a yes/no question, for solo play
+    Oracle(oracle::Args),
+    /// Check content packs for mistakes before shipping them
+    Pack {
+        #[command(subcommand)]
+        command: pack::Command,
+    },
+    /// Start play sessions and export their reports
+    Session {
+        #[command(subcommand)]
+        command: session::Command,
+    },
+    /// Generate a campaign of the given size, to measure queries, exports and the UI against
+    #[command(hide = true)]
+    Synthesize(synthesize::Args),
+    /// Advance the factions' goals between sessions, for solo play
+    Turn(turn::Args),
+    /// Export the campaign's factions, NPCs and session reports as a wiki of linked pages, to share with players
+    Wiki(wiki::Args),
+}
 
 impl Cli {
     pub fn parse_from_args<ITER, ARG>(args: ITER) -> Self
@@ -29,4 +93,29 @@ impl Cli {
     {
         Cli::parse_from(args)
     }
+
+    /// Runs the parsed command, reading answers to its prompts from `input` and writing its output to `out`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the command fails to run.
+    pub fn run(self, input: &mut impl BufRead, out: &mut impl Write) -> anyhow::Result<ExitCode> {
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+
+        match self.command {
+            Some(Command::Blocks { command }) => runtime.block_on(command.run(input, out)),
+            Some(Command::Campaign { command }) => runtime.block_on(command.run(out)),
+            Some(Command::Clock { command }) => runtime.block_on(command.run(out)),
+            Some(Command::Downtime(args)) => runtime.block_on(args.run(input, out)),
+            Some(Command::Factions(args)) => runtime.block_on(args.run(out)),
+            Some(Command::Oneshot(args)) => runtime.block_on(args.run(out)),
+            Some(Command::Oracle(args)) => runtime.block_on(args.run(out)),
+            Some(Command::Pack { command }) => command.run(out),
+            Some(Command::Session { command }) => runtime.block_on(command.run(out)),
+            Some(Command::Synthesize(args)) => runtime.block_on(args.run(out)),
+            Some(Command::Turn(args)) => runtime.block_on(args.run(out)),
+            Some(Command::Wiki(args)) => runtime.block_on(args.run(out)),
+            None => Ok(ExitCode::SUCCESS),
+        }
+    }
 }
@@ -10,12 +10,17 @@
  * You should have received a copy of the GNU Affero General Public License along with this program.
  * If not, see https://www.gnu.org/licenses/.
  */
-use std::{env, process::ExitCode};
+use std::{env, io, process::ExitCode};
 
 use example::Cli;
 use example_act as example;
 
 fn main() -> ExitCode {
-    Cli::parse_from_args(env::args());
-    ExitCode::SUCCESS
+    match Cli::parse_from_args(env::args()).run(&mut io::stdin().lock(), &mut io::stdout()) {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("error: {err:#}");
+            ExitCode::FAILURE
+        }
+    }
 }
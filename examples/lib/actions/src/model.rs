@@ -10,6 +10,7 @@
  * You should have received a copy of the GNU Affero General Public License along with this program.
  * If not, see https://www.gnu.org/licenses/.
  */
+#![expect(dead_code, reason = "Model is not wired to the CLI yet")]
 
 pub struct Character {
     name: String,
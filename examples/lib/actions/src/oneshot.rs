@@ -0,0 +1,86 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+use std::{io::Write, path::PathBuf, process::ExitCode};
+
+use darkforge::oneshot::OneShot;
+use darkforge_data::{pack::Pack, store::sql::sqlite::SqliteStore};
+use serde_json::{Value, json};
+
+use crate::format::{Format, Report};
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Seed the one-shot is generated from, the same seed always giving the same one-shot
+    #[arg(long)]
+    seed: u64,
+    /// Path to the content pack directory
+    #[arg(long, default_value = "data/defaults")]
+    pack: PathBuf,
+    /// Also create a campaign database for the one-shot, to play it or export it with `campaign export`
+    #[arg(long)]
+    db: Option<PathBuf>,
+    /// Name of the campaign created with --db
+    #[arg(long, default_value = "One-shot", requires = "db")]
+    name: String,
+    /// Output format
+    #[arg(long, value_enum, default_value_t)]
+    format: Format,
+}
+
+impl Args {
+    pub async fn run(self, out: &mut impl Write) -> anyhow::Result<ExitCode> {
+        let pack = Pack::open(&self.pack)?;
+        let generator = OneShot::new(self.seed);
+        let plan = match &self.db {
+            Some(db) => {
+                let mut campaign = generator.bootstrap(SqliteStore::open(db).await?, self.name, &pack).await?;
+                campaign.one_shot().await?.ok_or_else(|| anyhow::anyhow!("one-shot was not recorded"))?
+            }
+            None => generator.plan(&pack)?,
+        };
+
+        let crew = &plan.crew;
+        let mut report = Report::new(&["part", "name", "details"]);
+        report.push(vec![
+            json!("crew"),
+            json!(crew.name),
+            json!(format!(
+                "{:?} {:?}, {} in {}",
+                crew.reputation, crew.kind, crew.hunting_grounds.operation, crew.hunting_grounds.district
+            )),
+        ]);
+        for npc in &plan.npcs {
+            report.push(vec![json!("npc"), json!(npc.descriptor.label()), json!(npc.descriptor.description())]);
+        }
+        report.push(vec![json!("target"), json!(plan.target.label()), Value::Null]);
+        let faction = &plan.faction;
+        report.push(vec![
+            json!("faction"),
+            json!(faction.descriptor.label()),
+            json!(format!("tier {}, status {}", faction.tier, faction.status.value())),
+        ]);
+        for clock in &faction.clocks {
+            report.push(vec![
+                json!("clock"),
+                json!(clock.label),
+                json!(format!("{}/{}", clock.filled, clock.segments)),
+            ]);
+        }
+        if let Some(complication) = &plan.complication {
+            report.push(vec![json!("complication"), json!(complication), Value::Null]);
+        }
+        report.write(self.format, out)?;
+
+        Ok(ExitCode::SUCCESS)
+    }
+}
@@ -0,0 +1,41 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+use std::{io::Write, path::PathBuf, process::ExitCode};
+
+use darkforge::{campaign::Campaign, oracle::Likelihood, permission::Actor};
+use darkforge_data::store::sql::sqlite::SqliteStore;
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Yes/no question to ask
+    question: String,
+    /// How likely the answer is to be yes: very-unlikely, unlikely, even, likely or very-likely
+    #[arg(long, default_value_t)]
+    likelihood: Likelihood,
+    /// Path to the campaign database
+    #[arg(long, default_value = "campaign.db")]
+    db: PathBuf,
+}
+
+impl Args {
+    pub async fn run(self, out: &mut impl Write) -> anyhow::Result<ExitCode> {
+        let mut campaign = Campaign::open(SqliteStore::open(&self.db).await?).await?;
+        let ruling = campaign.ask_oracle(Actor::Gm, self.question, self.likelihood, &mut rand::rng()).await?;
+        writeln!(out, "{}", ruling.answer)?;
+        if let Some(complication) = ruling.complication {
+            writeln!(out, "{complication}")?;
+        }
+
+        Ok(ExitCode::SUCCESS)
+    }
+}
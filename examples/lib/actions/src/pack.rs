@@ -0,0 +1,51 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use clap::Subcommand;
+use darkforge_data::lint::{PackLinter, Severity};
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Check a content pack for missing translations, dangling slugs, unreachable table entries and stats out of range
+    Lint {
+        /// Path to the content pack directory
+        #[arg(default_value = "data/defaults")]
+        pack: PathBuf,
+    },
+}
+
+impl Command {
+    pub fn run(self, out: &mut impl Write) -> anyhow::Result<ExitCode> {
+        match self {
+            Command::Lint { pack } => lint(&pack, out),
+        }
+    }
+}
+
+fn lint(pack: &Path, out: &mut impl Write) -> anyhow::Result<ExitCode> {
+    let diagnostics = PackLinter::default().lint(pack)?;
+    for diagnostic in &diagnostics {
+        writeln!(out, "{diagnostic}")?;
+    }
+
+    let count = |severity| diagnostics.iter().filter(|d| d.severity == severity).count();
+    let (errors, warnings) = (count(Severity::Error), count(Severity::Warning));
+    writeln!(out, "linted {}: {errors} error(s), {warnings} warning(s)", pack.display())?;
+
+    Ok(if errors > 0 { ExitCode::FAILURE } else { ExitCode::SUCCESS })
+}
@@ -0,0 +1,151 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+use std::{fs, io::Write, path::PathBuf, process::ExitCode};
+
+use clap::Subcommand;
+use darkforge::{
+    campaign::Campaign,
+    notation::English,
+    permission::Actor,
+    session::{Fluke, Luck},
+};
+use darkforge_data::store::sql::sqlite::SqliteStore;
+use serde_json::{Value, json};
+
+use crate::format::{Format, Report};
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Start a new session, marking it in the journal
+    Start {
+        /// Title of the session
+        title: String,
+        /// Path to the campaign database
+        #[arg(long, default_value = "campaign.db")]
+        db: PathBuf,
+    },
+    /// List the sessions of the campaign
+    List {
+        /// Path to the campaign database
+        #[arg(long, default_value = "campaign.db")]
+        db: PathBuf,
+    },
+    /// Export a session's scores, rolls and downtime as a Markdown report
+    Report {
+        /// Number of the session, the latest one if omitted
+        #[arg(long)]
+        number: Option<usize>,
+        /// Write the report to this file instead of the standard output
+        #[arg(long)]
+        output: Option<PathBuf>,
+        /// Path to the campaign database
+        #[arg(long, default_value = "campaign.db")]
+        db: PathBuf,
+    },
+    /// Report each player's dice luck and stress spent during a session
+    Luck {
+        /// Number of the session, the latest one if omitted
+        #[arg(long)]
+        number: Option<usize>,
+        /// Output format
+        #[arg(long, value_enum, default_value_t)]
+        format: Format,
+        /// Path to the campaign database
+        #[arg(long, default_value = "campaign.db")]
+        db: PathBuf,
+    },
+}
+
+impl Command {
+    pub async fn run(self, out: &mut impl Write) -> anyhow::Result<ExitCode> {
+        match self {
+            Command::Start { title, db } => start(title, db, out).await,
+            Command::List { db } => list(db, out).await,
+            Command::Report { number, output, db } => report(number, output, db, out).await,
+            Command::Luck { number, format, db } => luck(number, format, db, out).await,
+        }
+    }
+}
+
+async fn start(title: String, db: PathBuf, out: &mut impl Write) -> anyhow::Result<ExitCode> {
+    let mut campaign = Campaign::open(SqliteStore::open(db).await?).await?;
+    let session = campaign.start_session(Actor::Gm, title).await?;
+    writeln!(out, "started session {}: {}", session.number, session.title)?;
+
+    Ok(ExitCode::SUCCESS)
+}
+
+async fn list(db: PathBuf, out: &mut impl Write) -> anyhow::Result<ExitCode> {
+    let mut campaign = Campaign::open(SqliteStore::open(db).await?).await?;
+    let sessions = campaign.sessions().await?;
+    if sessions.is_empty() {
+        writeln!(out, "no sessions")?;
+    }
+    for session in sessions {
+        writeln!(out, "{:>3}  {}", session.number, session.title)?;
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+async fn report(number: Option<usize>, output: Option<PathBuf>, db: PathBuf, out: &mut impl Write) -> anyhow::Result<ExitCode> {
+    let mut campaign = Campaign::open(SqliteStore::open(db).await?).await?;
+    let report = campaign.session_report(number).await?;
+
+    match output {
+        Some(path) => {
+            fs::write(&path, report.to_string())?;
+            writeln!(out, "wrote session {} report to {}", report.session.number, path.display())?;
+        }
+        None => write!(out, "{report}")?,
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+async fn luck(number: Option<usize>, format: Format, db: PathBuf, out: &mut impl Write) -> anyhow::Result<ExitCode> {
+    let mut campaign = Campaign::open(SqliteStore::open(db).await?).await?;
+    let luck = campaign.luck_report(number).await?;
+
+    let mut report = Report::new(&["player", "rolls", "expected", "actual", "luckiest", "unluckiest", "stress"]);
+    for Luck {
+        actor,
+        rolls,
+        expected,
+        actual,
+        luckiest,
+        unluckiest,
+        stress,
+    } in &luck.players
+    {
+        report.push(vec![
+            json!(actor.to_string()),
+            json!(rolls),
+            json!(format!("{expected:.0}%")),
+            json!(format!("{actual:.0}%")),
+            fluke(luckiest.as_ref()),
+            fluke(unluckiest.as_ref()),
+            json!(stress),
+        ]);
+    }
+    report.write(format, out)?;
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Describes a roll along with its chance, or null if there is none.
+fn fluke(fluke: Option<&Fluke>) -> Value {
+    fluke.map_or(Value::Null, |Fluke { roll, chance }| {
+        json!(format!("{} {} ({chance:.0}%)", roll.label, roll.notation(&English)))
+    })
+}
@@ -0,0 +1,65 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+use std::{io::Write, path::PathBuf, process::ExitCode};
+
+use darkforge_data::{pack::Pack, store::sql::sqlite::SqliteStore};
+use darkforge_harness::synthetic::Synthetic;
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Path to the campaign database to create
+    #[arg(long, default_value = "synthetic.db")]
+    db: PathBuf,
+    /// Path to the content pack directory
+    #[arg(long, default_value = "data/defaults")]
+    pack: PathBuf,
+    /// Seed the campaign is generated from
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+    /// Number of sessions to start
+    #[arg(long, default_value_t = Synthetic::DEFAULT_SESSIONS)]
+    sessions: usize,
+    /// Number of factions to add on top of the pack's
+    #[arg(long, default_value_t = Synthetic::DEFAULT_FACTIONS)]
+    factions: usize,
+    /// Number of rolls to make across the sessions
+    #[arg(long, default_value_t = Synthetic::DEFAULT_ROLLS)]
+    rolls: usize,
+}
+
+impl Args {
+    pub async fn run(self, out: &mut impl Write) -> anyhow::Result<ExitCode> {
+        let pack = Pack::open(&self.pack)?;
+        let store = SqliteStore::open(&self.db).await?;
+        let mut campaign = Synthetic::new(self.seed)
+            .sessions(self.sessions)
+            .factions(self.factions)
+            .rolls(self.rolls)
+            .generate(store, &pack)
+            .await?;
+
+        let factions = campaign.factions().await?.len();
+        let sessions = campaign.sessions().await?.len();
+        writeln!(
+            out,
+            "generated campaign {} in {} with {} faction(s), {} session(s) and {} roll(s)",
+            campaign.info().name,
+            self.db.display(),
+            factions,
+            sessions,
+            self.rolls
+        )?;
+
+        Ok(ExitCode::SUCCESS)
+    }
+}
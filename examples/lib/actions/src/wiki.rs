@@ -0,0 +1,260 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+use std::{collections::HashMap, fmt::Write as _, fs, io::Write, path::PathBuf, process::ExitCode};
+
+use clap::ValueEnum;
+use darkforge::{
+    campaign::Campaign,
+    npc::{Npc, StatBlock},
+};
+use darkforge_data::store::sql::sqlite::SqliteStore;
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::format::{self, Format, Report};
+
+/// Format of the pages of the wiki.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Pages {
+    /// Markdown files, linked to one another
+    #[default]
+    Markdown,
+    /// Standalone HTML files, linked to one another
+    Html,
+}
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Directory to write the pages to, created if missing
+    #[arg(long, default_value = "wiki")]
+    out: PathBuf,
+    /// Format of the pages
+    #[arg(long, value_enum, default_value_t)]
+    format: Pages,
+    /// Path to the campaign database
+    #[arg(long, default_value = "campaign.db")]
+    db: PathBuf,
+}
+
+impl Args {
+    pub async fn run(self, out: &mut impl Write) -> anyhow::Result<ExitCode> {
+        let mut campaign = Campaign::open(SqliteStore::open(&self.db).await?).await?;
+        let pages = wiki(&mut campaign).await?;
+
+        fs::create_dir_all(&self.out)?;
+        for page in &pages {
+            let (file, contents) = match self.format {
+                Pages::Markdown => (format!("{}.md", page.name), page.markdown.clone()),
+                Pages::Html => (format!("{}.html", page.name), standalone(&page.title, &page.markdown)),
+            };
+            fs::write(self.out.join(file), contents)?;
+        }
+        writeln!(out, "wrote {} pages to {}", pages.len(), self.out.display())?;
+
+        Ok(ExitCode::SUCCESS)
+    }
+}
+
+/// A page of the wiki, written in Markdown.
+struct Page {
+    /// The name of the file, without extension.
+    name: String,
+    /// The title of the page.
+    title: String,
+    /// The contents of the page.
+    markdown: String,
+}
+
+/// Renders the campaign's factions, NPCs and session reports as pages linked from an index, linking the names of
+/// factions and NPCs wherever they are mentioned.
+async fn wiki(campaign: &mut Campaign) -> anyhow::Result<Vec<Page>> {
+    let dashboard = campaign.dashboard().await?;
+    let mut npcs = campaign.all_with::<Npc>(true).await?;
+    npcs.sort_by(|(_, a), (_, b)| a.descriptor().label().cmp(b.descriptor().label()));
+    let sessions = campaign.sessions().await?;
+
+    let mut names = Names::default();
+    for standing in &dashboard.factions {
+        names.add(standing.entity, "faction", standing.faction.descriptor().label());
+    }
+    for (entity, npc) in &npcs {
+        names.add(*entity, "npc", npc.descriptor().label());
+    }
+
+    let mut clocks = HashMap::<_, Vec<_>>::new();
+    for active in &dashboard.clocks {
+        if let Some(owner) = active.owner {
+            clocks.entry(owner).or_default().push(&active.clock);
+        }
+    }
+
+    let mut pages = Vec::new();
+    let mut index = format!(
+        "# {}\n\n_Campaign played with the {} pack._\n",
+        campaign.info().name,
+        campaign.info().pack
+    );
+
+    index.push_str("\n## Factions\n\n");
+    for standing in &dashboard.factions {
+        let (faction, name) = (&standing.faction, names.page(standing.entity));
+        let label = faction.descriptor().label();
+        writeln!(index, "- [{label}]({name}.md)")?;
+
+        let mut markdown = format!("# {label}\n\n{}\n\n", names.link(faction.descriptor().description(), name));
+        let mut report = Report::new(&["tier", "hold", "status"]);
+        report.push(vec![json!(faction.tier()), json!(faction.hold()), json!(standing.status.value())]);
+        markdown.push_str(&table(&report)?);
+        markdown.push_str("\n## Clocks\n\n");
+        match clocks.get(&standing.entity) {
+            Some(clocks) => {
+                for clock in clocks {
+                    writeln!(markdown, "- {} {}/{}", clock.label(), clock.filled(), clock.segments())?;
+                }
+            }
+            None => markdown.push_str("_No clocks._\n"),
+        }
+        pages.push(Page::new(name, label, markdown));
+    }
+
+    index.push_str("\n## NPCs\n\n");
+    if npcs.is_empty() {
+        index.push_str("_No NPCs._\n");
+    }
+    for (entity, npc) in &npcs {
+        let (name, label) = (names.page(*entity), npc.descriptor().label());
+        writeln!(index, "- [{label}]({name}.md)")?;
+
+        let mut markdown = format!("# {label}\n\n{}\n", names.link(npc.descriptor().description(), name));
+        if let Some(stats) = campaign.get::<StatBlock>(*entity).await? {
+            let mut report = Report::new(&["tier", "quality", "drives", "flaws"]);
+            report.push(vec![json!(stats.tier), json!(stats.quality), json!(stats.drives), json!(stats.flaws)]);
+            markdown.push_str("\n## Stats\n\n");
+            markdown.push_str(&table(&report)?);
+        }
+        pages.push(Page::new(name, label, markdown));
+    }
+
+    index.push_str("\n## Sessions\n\n");
+    if sessions.is_empty() {
+        index.push_str("_No sessions played._\n");
+    }
+    for session in &sessions {
+        let name = format!("session-{}", session.number);
+        let title = format!("Session {}: {}", session.number, session.title);
+        writeln!(index, "- [{title}]({name}.md)")?;
+
+        let report = campaign.session_report(Some(session.number)).await?;
+        pages.push(Page::new(&name, &title, names.link(&report.to_string(), &name)));
+    }
+
+    let title = campaign.info().name.clone();
+    pages.insert(0, Page::new("index", &title, index));
+    for page in pages.iter_mut().skip(1) {
+        page.markdown.push_str("\n[Back to the index](index.md)\n");
+    }
+
+    Ok(pages)
+}
+
+impl Page {
+    fn new(name: &str, title: &str, markdown: String) -> Page {
+        Page {
+            name: name.to_owned(),
+            title: title.to_owned(),
+            markdown,
+        }
+    }
+}
+
+/// The pages of the entities named in the wiki, to link their names to.
+#[derive(Default)]
+struct Names {
+    /// The page of each entity.
+    pages: HashMap<Uuid, String>,
+    /// The names of the entities and their page, longest first so the longest name mentioned is linked.
+    links: Vec<(String, String)>,
+}
+
+impl Names {
+    /// Gives the entity a page named after its kind and name, unique among the pages.
+    fn add(&mut self, entity: Uuid, kind: &str, name: &str) {
+        let slug = name
+            .to_lowercase()
+            .split(|char: char| !char.is_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .collect::<Vec<_>>()
+            .join("-");
+        let base = format!("{kind}-{slug}");
+        let mut page = base.clone();
+        for suffix in 2.. {
+            if !self.pages.values().any(|taken| *taken == page) {
+                break;
+            }
+            page = format!("{base}-{suffix}");
+        }
+
+        self.pages.insert(entity, page.clone());
+        self.links.push((name.to_owned(), page));
+        self.links.sort_by(|(a, _), (b, _)| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+    }
+
+    /// Returns the page of the entity.
+    fn page(&self, entity: Uuid) -> &str {
+        self.pages.get(&entity).map_or("index", String::as_str)
+    }
+
+    /// Links the whole words of the text naming an entity to its page, except the page the text is on.
+    fn link(&self, text: &str, on: &str) -> String {
+        let mut linked = String::with_capacity(text.len());
+        let mut rest = text;
+        let mut after_word = false;
+        while let Some(char) = rest.chars().next() {
+            let mention = (!after_word)
+                .then(|| {
+                    self.links.iter().find(|(name, page)| {
+                        page != on && !name.is_empty() && rest.starts_with(name.as_str()) && !rest[name.len()..].starts_with(char::is_alphanumeric)
+                    })
+                })
+                .flatten();
+            if let Some((name, page)) = mention {
+                let _ = write!(linked, "[{name}]({page}.md)");
+                rest = &rest[name.len()..];
+                after_word = name.ends_with(char::is_alphanumeric);
+            } else {
+                linked.push(char);
+                rest = &rest[char.len_utf8()..];
+                after_word = char.is_alphanumeric();
+            }
+        }
+
+        linked
+    }
+}
+
+/// Writes the report as a Markdown table.
+fn table(report: &Report) -> anyhow::Result<String> {
+    let mut markdown = Vec::new();
+    report.write(Format::Markdown, &mut markdown)?;
+
+    Ok(String::from_utf8(markdown)?)
+}
+
+/// Wraps the page's HTML in a standalone document.
+fn standalone(title: &str, markdown: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n</head>\n<body>\n{}</body>\n</html>\n",
+        format::escape(title),
+        format::html(markdown)
+    )
+}
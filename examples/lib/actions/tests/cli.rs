@@ -11,11 +11,26 @@
  * If not, see https://www.gnu.org/licenses/.
  */
 
-use std::{path::Path, sync::LazyLock};
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process,
+    sync::LazyLock,
+};
 
 use assert_cmd::{Command, cargo_bin};
+use darkforge::{campaign::Campaign, character::Character, clock::Clock, permission::Actor};
+use darkforge_data::store::{
+    Migrator,
+    sql::sqlite::{MIGRATIONS, SqliteStore},
+};
 use indoc::indoc;
+use predicates::prelude::*;
 use rstest::rstest;
+use uuid::Uuid;
+
+/// Path to the default content pack.
+const DEFAULTS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../../../data/defaults");
 
 static BINARY: LazyLock<&Path> = LazyLock::new(|| cargo_bin!("forge"));
 
@@ -35,7 +50,22 @@ fn test_prints_version_given_version_flag_is_set(#[case] flag: &str) {
 #[case::full("--help")]
 fn test_prints_help_given_help_flag_is_set(#[case] flag: &str) {
     Command::new(BINARY.clone()).arg(flag).assert().success().stdout(indoc!(
-        "                                           
+        "
+            Usage: forge [COMMAND]
+
+            Commands:
+              blocks    Import and export NPCs and factions as plain text stat blocks
+              campaign  Manage campaign databases
+              clock     Manage progress clocks
+              downtime  Take a character through their downtime activities
+              factions  Report the factions' tier, hold, status toward the crew and active clocks
+              oneshot   Generate a ready-to-play one-shot for convention play: crew, NPCs, target, opposing faction and complication
+              oracle    Ask the oracle a yes/no question, for solo play
+              pack      Check content packs for mistakes before shipping them
+              session   Start play sessions and export their reports
+              turn      Advance the factions' goals between sessions, for solo play
+              wiki      Export the campaign's factions, NPCs and session reports as a wiki of linked pages, to share with players
+              help      Print this message or the help of the given subcommand(s)
 
             Options:
               -h, --help     Print help
@@ -43,3 +73,621 @@ fn test_prints_help_given_help_flag_is_set(#[case] flag: &str) {
         "
     ));
 }
+
+#[test]
+fn test_creates_campaign_given_default_pack() {
+    let db = migrated_db("new");
+
+    Command::new(BINARY.clone())
+        .args(["campaign", "new", "Crow's Foot", "--pack", DEFAULTS, "--db"])
+        .arg(&db)
+        .assert()
+        .success()
+        .stdout(format!(
+            "created campaign Crow's Foot in {} with 7 faction(s) from pack defaults\n",
+            db.display()
+        ));
+
+    Command::new(BINARY.clone())
+        .args(["campaign", "new", "Again", "--pack", DEFAULTS, "--db"])
+        .arg(&db)
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("already holds campaign"));
+
+    Command::new(BINARY.clone())
+        .args(["campaign", "verify", "--db"])
+        .arg(&db)
+        .assert()
+        .success()
+        .stdout("no issues found\n");
+}
+
+#[test]
+fn test_imports_exported_campaign() {
+    let db = campaign_db("export");
+    let copy = env::temp_dir().join(format!("forge-import-{}.db", process::id()));
+    let export = env::temp_dir().join(format!("forge-export-{}.json", process::id()));
+    for path in [&copy, &export] {
+        let _ = fs::remove_file(path);
+    }
+
+    Command::new(BINARY.clone())
+        .args(["campaign", "export", "--db"])
+        .arg(&db)
+        .arg("--to")
+        .arg(&export)
+        .assert()
+        .success()
+        .stdout(format!("exported campaign Crow's Foot with 0 character(s) to {}\n", export.display()));
+    let json = fs::read_to_string(&export).expect("should have written export");
+    assert!(json.contains(r#""schema": 1"#), "missing schema version in {json}");
+
+    Command::new(BINARY.clone())
+        .args(["campaign", "import", "--pack", DEFAULTS, "--from"])
+        .arg(&export)
+        .arg("--db")
+        .arg(&copy)
+        .assert()
+        .success()
+        .stdout(format!("imported campaign Crow's Foot with 0 character(s) into {}\n", copy.display()));
+}
+
+#[test]
+fn test_syncs_campaign_through_delta() {
+    let db = campaign_db("delta");
+    let copy = env::temp_dir().join(format!("forge-sync-{}.db", process::id()));
+    let delta = env::temp_dir().join(format!("forge-delta-{}.json", process::id()));
+    for path in [&copy, &delta] {
+        let _ = fs::remove_file(path);
+    }
+
+    Command::new(BINARY.clone())
+        .args(["campaign", "delta", "--db"])
+        .arg(&db)
+        .arg("--to")
+        .arg(&delta)
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("of campaign Crow's Foot up to"));
+
+    Command::new(BINARY.clone())
+        .args(["campaign", "sync", "--pack", DEFAULTS, "--from"])
+        .arg(&delta)
+        .arg("--db")
+        .arg(&copy)
+        .assert()
+        .success()
+        .stdout(predicates::str::starts_with(format!("synced campaign Crow's Foot in {}", copy.display())));
+}
+
+#[test]
+fn test_generates_same_world_given_same_seed() {
+    let first = env::temp_dir().join(format!("forge-seed-a-{}.db", process::id()));
+    let second = env::temp_dir().join(format!("forge-seed-b-{}.db", process::id()));
+    let mut outputs = Vec::new();
+    for db in [&first, &second] {
+        let _ = fs::remove_file(db);
+        Command::new(BINARY.clone())
+            .args([
+                "campaign",
+                "new",
+                "Crow's Foot",
+                "--pack",
+                DEFAULTS,
+                "--seed",
+                "1902",
+                "--factions",
+                "3",
+                "--db",
+            ])
+            .arg(db)
+            .assert()
+            .success()
+            .stdout(predicates::str::contains("with 3 faction(s)").and(predicates::str::contains("starting situation: ")));
+
+        let output = Command::new(BINARY.clone())
+            .args(["factions", "--format", "json", "--db"])
+            .arg(db)
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        outputs.push(output);
+    }
+
+    assert_eq!(outputs[0], outputs[1]);
+}
+
+#[test]
+fn test_verifies_campaign_given_migrated_database() {
+    let db = migrated_db("verify-clean");
+
+    Command::new(BINARY.clone())
+        .args(["campaign", "verify", "--db"])
+        .arg(&db)
+        .assert()
+        .success()
+        .stdout("no issues found\n");
+}
+
+#[test]
+fn test_fails_to_verify_campaign_given_database_without_schema() {
+    let db = env::temp_dir().join(format!("forge-verify-empty-{}.db", process::id()));
+
+    Command::new(BINARY.clone())
+        .args(["campaign", "verify", "--db"])
+        .arg(&db)
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("no such table"));
+}
+
+#[test]
+fn test_lints_default_pack() {
+    Command::new(BINARY.clone())
+        .args(["pack", "lint", DEFAULTS])
+        .assert()
+        .success()
+        .stdout(predicates::str::ends_with("0 error(s), 0 warning(s)\n"));
+}
+
+#[test]
+fn test_fails_to_lint_pack_given_out_of_range_tier() {
+    let pack = env::temp_dir().join(format!("forge-lint-{}", process::id())).join("bitd");
+    fs::create_dir_all(&pack).expect("should have created pack directory");
+    fs::write(
+        pack.join("faction.json"),
+        r#"[{ "id": "7ef1bdbe-84de-4699-b1bd-be84de569995", "name": "Bluecoats", "tier": 9 }]"#,
+    )
+    .expect("should have written pack file");
+
+    Command::new(BINARY.clone())
+        .args(["pack", "lint"])
+        .arg(&pack)
+        .assert()
+        .failure()
+        .stdout(predicates::str::contains(
+            "faction.json:1:79: error[out-of-range]: tier of 9 is out of range, expected 0 to 5",
+        ));
+}
+
+#[test]
+fn test_manages_clocks_given_campaign() {
+    let db = campaign_db("clock");
+
+    let added = Command::new(BINARY.clone())
+        .args(["clock", "add", "Alarm", "--segments", "4", "--faction", "bluecoats", "--db"])
+        .arg(&db)
+        .assert()
+        .success()
+        .stdout(predicates::str::ends_with("○ ○○○○ 0/4 Alarm\n"));
+    let output = String::from_utf8(added.get_output().stdout.clone()).expect("should have printed utf-8");
+    let id = output
+        .lines()
+        .next()
+        .and_then(|line| line.strip_prefix("added clock "))
+        .expect("should have printed clock id");
+
+    Command::new(BINARY.clone())
+        .args(["clock", "tick", &id[..8], "--ticks", "2", "--db"])
+        .arg(&db)
+        .assert()
+        .success()
+        .stdout("filled 2 segment(s)\n◑ ●●○○ 2/4 Alarm\n");
+
+    Command::new(BINARY.clone())
+        .args(["clock", "list", "--db"])
+        .arg(&db)
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(format!("{id}  ◑ ●●○○ 2/4 Alarm (Bluecoats)\n")));
+
+    Command::new(BINARY.clone())
+        .args(["clock", "complete", id, "--db"])
+        .arg(&db)
+        .assert()
+        .success()
+        .stdout("filled 2 segment(s)\n● ●●●● 4/4 Alarm\nclock complete\n");
+
+    Command::new(BINARY.clone())
+        .args(["clock", "list", "--db"])
+        .arg(&db)
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(id).not());
+
+    Command::new(BINARY.clone())
+        .args(["clock", "list", "--all", "--db"])
+        .arg(&db)
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(format!("{id}  ● ●●●● 4/4 Alarm (Bluecoats)\n")));
+}
+
+#[test]
+fn test_runs_downtime_given_answers() {
+    let db = campaign_db("downtime");
+    block_on(async {
+        let mut campaign = Campaign::open(SqliteStore::open(&db).await.expect("should have opened store"))
+            .await
+            .expect("should have opened campaign");
+        let arcy = campaign
+            .enlist(Actor::Gm, Uuid::new_v4(), &Character::new("Arcy"))
+            .await
+            .expect("should have enlisted character");
+        let project = Clock::new("Forge a new blade", 8).expect("should have created clock");
+        campaign
+            .add_clock(Actor::Gm, Some(arcy), &project)
+            .await
+            .expect("should have added clock");
+    });
+
+    Command::new(BINARY.clone())
+        .args(["downtime", "arcy", "--db"])
+        .arg(&db)
+        .write_stdin("1\n4\n1\n2\n")
+        .assert()
+        .success()
+        .stdout(
+            predicates::str::starts_with("downtime for Arcy (stress 0)\nactivity 1 of 2:\n")
+                .and(predicates::str::contains("cleared 0 stress, overindulged\n"))
+                .and(predicates::str::contains("  1) Forge a new blade 0/8\n"))
+                .and(predicates::str::contains("project ticked"))
+                .and(predicates::str::ends_with("downtime over for Arcy (stress 0)\n")),
+        );
+
+    Command::new(BINARY.clone())
+        .args(["downtime", "Nyryx", "--db"])
+        .arg(&db)
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("no active character named Nyryx"));
+}
+
+#[test]
+fn test_reports_session_given_started_session() {
+    let db = campaign_db("session");
+    let report = env::temp_dir().join(format!("forge-session-{}.md", process::id()));
+
+    Command::new(BINARY.clone())
+        .args(["session", "report", "--db"])
+        .arg(&db)
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("session 1 does not exist"));
+
+    Command::new(BINARY.clone())
+        .args(["session", "start", "The Bell Tower", "--db"])
+        .arg(&db)
+        .assert()
+        .success()
+        .stdout("started session 1: The Bell Tower\n");
+    Command::new(BINARY.clone())
+        .args(["clock", "add", "Alarm", "--db"])
+        .arg(&db)
+        .assert()
+        .success();
+    let clock = block_on(async {
+        let mut campaign = Campaign::open(SqliteStore::open(&db).await.expect("should have opened store"))
+            .await
+            .expect("should have opened campaign");
+        let (entity, ..) = campaign
+            .clocks()
+            .await
+            .expect("should have listed clocks")
+            .into_iter()
+            .find(|(_, _, clock)| clock.label() == "Alarm")
+            .expect("should have added clock");
+        entity
+    });
+    Command::new(BINARY.clone())
+        .args(["clock", "tick", &clock.to_string(), "--ticks", "2", "--db"])
+        .arg(&db)
+        .assert()
+        .success();
+
+    Command::new(BINARY.clone())
+        .args(["session", "list", "--db"])
+        .arg(&db)
+        .assert()
+        .success()
+        .stdout("  1  The Bell Tower\n");
+    Command::new(BINARY.clone())
+        .args(["session", "report", "--number", "1", "--output"])
+        .arg(&report)
+        .arg("--db")
+        .arg(&db)
+        .assert()
+        .success()
+        .stdout(predicates::str::starts_with("wrote session 1 report to "));
+
+    assert_eq!(
+        indoc!(
+            "
+                # Session 1: The Bell Tower
+
+                ## Scores
+
+                - **Alarm** ticked by 2 (2/4)
+
+                ## Rolls
+
+                _Nothing recorded._
+
+                ## Downtime
+
+                _Nothing recorded._
+            "
+        ),
+        fs::read_to_string(&report).expect("should have written report")
+    );
+
+    Command::new(BINARY.clone())
+        .args(["session", "luck", "--format", "markdown", "--db"])
+        .arg(&db)
+        .assert()
+        .success()
+        .stdout(indoc!(
+            "
+            | player | rolls | expected | actual | luckiest | unluckiest | stress |
+            | --- | --- | --- | --- | --- | --- | --- |
+            "
+        ));
+}
+
+#[rstest]
+#[case::table(
+    "table",
+    "FACTION         TIER  HOLD    STATUS  CLOCKS\nBluecoats       3     strong  0       Crush the gangs of Crow's Foot 0/8\n"
+)]
+#[case::markdown(
+    "markdown",
+    "| faction | tier | hold | status | clocks |\n| --- | --- | --- | --- | --- |\n| Bluecoats | 3 | strong | 0 | Crush the gangs of Crow's Foot 0/8 |\n"
+)]
+fn test_reports_factions_given_format(#[case] format: &str, #[case] expect: &str) {
+    let db = campaign_db(&format!("factions-{format}"));
+
+    Command::new(BINARY.clone())
+        .args(["factions", "--format", format, "--db"])
+        .arg(&db)
+        .assert()
+        .success()
+        .stdout(predicates::str::starts_with(expect).and(predicates::str::contains("Spirit Wardens")));
+}
+
+#[test]
+fn test_reports_factions_as_json() {
+    let db = campaign_db("factions-json");
+
+    let output = Command::new(BINARY.clone())
+        .args(["factions", "--format", "json", "--db"])
+        .arg(&db)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let factions: Vec<serde_json::Value> = serde_json::from_slice(&output).expect("should have printed json");
+
+    assert_eq!(7, factions.len());
+    assert_eq!(
+        serde_json::json!({
+            "faction": "Inspectors",
+            "tier": 3,
+            "hold": "strong",
+            "status": -1,
+            "clocks": ["Uncover the gangs' informants 0/8"],
+        }),
+        factions[5]
+    );
+}
+
+#[rstest]
+#[case::markdown("markdown", "index.md", "[Bluecoats](faction-bluecoats.md)")]
+#[case::html("html", "index.html", "<a href=\"faction-bluecoats.html\">Bluecoats</a>")]
+fn test_exports_wiki_given_format(#[case] format: &str, #[case] index: &str, #[case] link: &str) {
+    let db = campaign_db(&format!("wiki-{format}"));
+    let wiki = env::temp_dir().join(format!("forge-wiki-{format}-{}", process::id()));
+    let _ = fs::remove_dir_all(&wiki);
+    Command::new(BINARY.clone())
+        .args(["session", "start", "The Bell Tower", "--db"])
+        .arg(&db)
+        .assert()
+        .success();
+
+    Command::new(BINARY.clone())
+        .args(["wiki", "--format", format, "--out"])
+        .arg(&wiki)
+        .arg("--db")
+        .arg(&db)
+        .assert()
+        .success()
+        .stdout(predicates::str::starts_with("wrote ").and(predicates::str::contains(" pages to ")));
+
+    let contents = fs::read_to_string(wiki.join(index)).expect("should have written the index");
+    assert!(contents.contains("Crow's Foot"), "{contents}");
+    assert!(contents.contains(link), "{contents}");
+    assert!(contents.contains("session-1"), "{contents}");
+    let extension = wiki.join(index).extension().map(ToOwned::to_owned);
+    let faction = wiki
+        .join("faction-bluecoats")
+        .with_extension(extension.expect("should have an extension"));
+    let contents = fs::read_to_string(faction).expect("should have written the faction's page");
+    assert!(contents.contains("Crush the gangs of Crow"), "{contents}");
+}
+
+/// Runs a future to completion on a fresh runtime.
+fn block_on<F: Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("should have built runtime")
+        .block_on(future)
+}
+
+/// Creates a fresh database holding the campaign Crow's Foot, created from the default pack.
+fn campaign_db(name: &str) -> PathBuf {
+    let db = migrated_db(name);
+    Command::new(BINARY.clone())
+        .args(["campaign", "new", "Crow's Foot", "--pack", DEFAULTS, "--db"])
+        .arg(&db)
+        .assert()
+        .success();
+
+    db
+}
+
+/// Creates a fresh database file with the world schema applied.
+fn migrated_db(name: &str) -> PathBuf {
+    let path = env::temp_dir().join(format!("forge-{name}-{}.db", process::id()));
+    let _ = fs::remove_file(&path);
+
+    block_on(async {
+        let store = SqliteStore::open(&path).await.expect("should have opened store");
+        store.apply(MIGRATIONS).await.expect("should have applied migrations");
+    });
+
+    path
+}
+
+#[test]
+fn test_imports_and_exports_stat_blocks() {
+    let db = campaign_db("blocks");
+    let blocks = indoc!(
+        "
+        npc: Bazso Baz
+        tier: 2
+        quality: 3
+        flaws: Greedy
+
+        faction: The Lampblacks
+        tier: 2
+        hold: weak
+        status: -1
+        clock: Destroy the Red Sashes (2/8)
+        "
+    );
+
+    let imported = Command::new(BINARY.clone())
+        .args(["blocks", "import", "--db"])
+        .arg(&db)
+        .write_stdin(blocks)
+        .assert()
+        .success()
+        .stdout(predicates::str::starts_with("imported 2 stat block(s)\n"));
+    let output = String::from_utf8(imported.get_output().stdout.clone()).expect("should have printed utf-8");
+    let ids = output.lines().skip(1).collect::<Vec<_>>();
+
+    Command::new(BINARY.clone())
+        .args(["blocks", "export"])
+        .args(&ids)
+        .arg("--db")
+        .arg(&db)
+        .assert()
+        .success()
+        .stdout(blocks);
+
+    Command::new(BINARY.clone())
+        .args(["blocks", "export", "--db"])
+        .arg(&db)
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("npc: Bazso Baz\n").and(predicates::str::contains("faction: The Lampblacks\n")));
+
+    Command::new(BINARY.clone())
+        .args(["blocks", "import", "--db"])
+        .arg(&db)
+        .write_stdin("npc: Lyssa\ntier: high\n")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("line 2: invalid tier `high`"));
+}
+
+#[test]
+fn test_plays_faction_turn_given_campaign() {
+    let db = campaign_db("turn");
+
+    Command::new(BINARY.clone()).args(["turn", "--db"]).arg(&db).assert().success().stdout(
+        predicates::str::starts_with("# Faction turn\n\n- Bluecoats advance Crush the gangs of Crow's Foot by ")
+            .and(predicates::str::contains("- Spirit Wardens take up a new goal: ")),
+    );
+}
+
+#[test]
+fn test_answers_question_given_likelihood() {
+    let db = campaign_db("oracle");
+
+    Command::new(BINARY.clone())
+        .args(["oracle", "Is the door locked?", "--likelihood", "very_likely", "--db"])
+        .arg(&db)
+        .assert()
+        .success()
+        .stdout(predicates::str::starts_with("Yes").or(predicates::str::starts_with("No")));
+
+    Command::new(BINARY.clone())
+        .args(["oracle", "Is the door locked?", "--likelihood", "certain", "--db"])
+        .arg(&db)
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("unknown likelihood certain"));
+}
+
+#[test]
+fn test_generates_synthetic_campaign_given_size() {
+    let db = env::temp_dir().join(format!("forge-synthesize-{}.db", process::id()));
+    let _ = fs::remove_file(&db);
+
+    Command::new(BINARY.clone())
+        .args([
+            "synthesize",
+            "--pack",
+            DEFAULTS,
+            "--sessions",
+            "3",
+            "--factions",
+            "7",
+            "--rolls",
+            "40",
+            "--db",
+        ])
+        .arg(&db)
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("with 14 faction(s), 3 session(s) and 40 roll(s)"));
+}
+
+#[test]
+fn test_generates_one_shot_given_seed() {
+    let db = env::temp_dir().join(format!("forge-oneshot-{}.db", process::id()));
+    let _ = fs::remove_file(&db);
+
+    let printed = Command::new(BINARY.clone())
+        .args(["oneshot", "--seed", "1903", "--pack", DEFAULTS, "--format", "markdown"])
+        .assert()
+        .success()
+        .stdout(
+            predicates::str::starts_with("| part | name | details |")
+                .and(predicates::str::contains(
+                    "| crew | The Quiet Hands | Professional Assassins, Accident in Six Towers |",
+                ))
+                .and(predicates::str::contains("| target | Accident against Mylera Klev | - |")),
+        )
+        .get_output()
+        .stdout
+        .clone();
+
+    Command::new(BINARY.clone())
+        .args(["oneshot", "--seed", "1903", "--pack", DEFAULTS, "--format", "markdown", "--db"])
+        .arg(&db)
+        .assert()
+        .success()
+        .stdout(predicates::ord::eq(printed));
+    Command::new(BINARY.clone())
+        .args(["factions", "--db"])
+        .arg(&db)
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Bluecoats").and(predicates::str::contains("Crush the gangs of Crow's Foot 0/8")));
+}
@@ -0,0 +1,92 @@
+# Builds the controls of a character sheet and binds them to the CharacterSheet it extends.
+#
+# Set `db`, `character` and, for a player, `player` before the sheet enters the tree, or call `reload()` after
+# changing them.
+extends CharacterSheet
+
+const HARM_LABELS := ["Lesser", "Moderate", "Severe", "Fatal"]
+
+@onready var _name: Label = %Name
+@onready var _stress: HBoxContainer = %Stress
+@onready var _harm: GridContainer = %Harm
+@onready var _actions: GridContainer = %Actions
+
+
+func _ready() -> void:
+	sheet_changed.connect(_refresh)
+	_build()
+	if not character.is_empty():
+		reload()
+
+
+func _build() -> void:
+	for index in max_stress():
+		var box := CheckBox.new()
+		box.toggled.connect(_on_stress_toggled.bind(index))
+		_stress.add_child(box)
+
+	for level in range(HARM_LABELS.size(), 0, -1):
+		var label := Label.new()
+		label.text = "%d %s" % [level, HARM_LABELS[level - 1]]
+		_harm.add_child(label)
+		var boxes := HBoxContainer.new()
+		boxes.name = "Level%d" % level
+		boxes.size_flags_horizontal = Control.SIZE_EXPAND_FILL
+		for slot in harm_slots(level):
+			var edit := LineEdit.new()
+			edit.size_flags_horizontal = Control.SIZE_EXPAND_FILL
+			edit.text_submitted.connect(func(_text: String) -> void: _on_harm_submitted(level))
+			edit.focus_exited.connect(_on_harm_submitted.bind(level))
+			boxes.add_child(edit)
+		_harm.add_child(boxes)
+
+
+func _refresh() -> void:
+	_name.text = character_name()
+
+	for index in _stress.get_child_count():
+		var box: CheckBox = _stress.get_child(index)
+		box.set_pressed_no_signal(index < stress())
+
+	for level in range(1, HARM_LABELS.size() + 1):
+		var marked := harm(level)
+		var boxes := _harm.get_node("Level%d" % level)
+		for slot in boxes.get_child_count():
+			var edit: LineEdit = boxes.get_child(slot)
+			edit.text = marked[slot] if slot < marked.size() else ""
+
+	_refresh_actions()
+
+
+func _refresh_actions() -> void:
+	for child in _actions.get_children():
+		child.queue_free()
+
+	for action in actions():
+		var label := Label.new()
+		label.text = action["label"]
+		label.tooltip_text = action["attribute"]
+		_actions.add_child(label)
+		var dots := HBoxContainer.new()
+		for dot in max_rating():
+			var box := CheckBox.new()
+			box.set_pressed_no_signal(dot < rating(action["id"]))
+			box.toggled.connect(_on_dot_toggled.bind(action["id"], dot))
+			dots.add_child(box)
+		_actions.add_child(dots)
+
+
+func _on_stress_toggled(pressed: bool, index: int) -> void:
+	set_stress(index + 1 if pressed else index)
+
+
+func _on_harm_submitted(level: int) -> void:
+	var descriptions := PackedStringArray()
+	for edit in _harm.get_node("Level%d" % level).get_children():
+		descriptions.append(edit.text)
+	if descriptions != harm(level):
+		set_harm(level, descriptions)
+
+
+func _on_dot_toggled(pressed: bool, action: String, dot: int) -> void:
+	set_rating(action, dot + 1 if pressed else dot)
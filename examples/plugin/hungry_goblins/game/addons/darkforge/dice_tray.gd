@@ -0,0 +1,69 @@
+# A dice tray rolling through the RollService, with the history of recent rolls read from the journal.
+#
+# Leave `character` empty to roll for no one, which only the GM can do. The history can be filtered down to the rolls
+# of the tray's character.
+extends Control
+
+const HISTORY_LIMIT := 20
+const OUTCOME_COLORS := {
+	"failure": Color.INDIAN_RED,
+	"partial": Color.GOLDENROD,
+	"success": Color.MEDIUM_SEA_GREEN,
+	"critical": Color.DEEP_SKY_BLUE,
+}
+
+## Path to the campaign database.
+@export var db := "campaign.db"
+## Id of the player rolling, or empty for the GM.
+@export var player := ""
+## Id of the character rolling, or empty to roll for no one.
+@export var character := ""
+
+var _service := RollService.new()
+
+@onready var _label: LineEdit = %Label
+@onready var _rating: SpinBox = %Rating
+@onready var _dice: HBoxContainer = %Dice
+@onready var _outcome: Label = %Outcome
+@onready var _only_character: CheckBox = %OnlyCharacter
+@onready var _history: ItemList = %History
+
+
+func _ready() -> void:
+	_service.db = db
+	_service.player = player
+	_only_character.disabled = character.is_empty()
+	%Roll.pressed.connect(_on_roll_pressed)
+	_only_character.toggled.connect(func(_pressed: bool) -> void: _refresh_history())
+	_refresh_history()
+
+
+func _on_roll_pressed() -> void:
+	var roll := _service.roll(character, _label.text, int(_rating.value))
+	if roll.is_empty():
+		var code: int = _service.last_error.get("code", DarkForgeError.UNKNOWN)
+		_outcome.text = "Not your character" if code == DarkForgeError.PERMISSION_DENIED else DarkForgeError.code_name(code).capitalize()
+		_outcome.add_theme_color_override("font_color", Color.GRAY)
+		return
+
+	for child in _dice.get_children():
+		child.queue_free()
+	for die in roll["dice"]:
+		var label := Label.new()
+		label.text = str(die)
+		label.add_theme_color_override("font_color", OUTCOME_COLORS[roll["outcome"]] if die == roll["dice"].max() else Color.GRAY)
+		_dice.add_child(label)
+
+	_outcome.text = roll["outcome"].capitalize()
+	_outcome.add_theme_color_override("font_color", OUTCOME_COLORS[roll["outcome"]])
+	_refresh_history()
+
+
+func _refresh_history() -> void:
+	var filter := character if _only_character.button_pressed else ""
+
+	_history.clear()
+	for roll in _service.history(filter, HISTORY_LIMIT):
+		var index := _history.add_item("%s %s" % [roll["label"], roll["notation"]])
+		_history.set_item_custom_fg_color(index, OUTCOME_COLORS[roll["outcome"]])
+		_history.set_item_tooltip(index, "%s, %s" % [roll["actor"], Time.get_datetime_string_from_unix_time(roll["recorded_at"])])
@@ -11,13 +11,28 @@
  * If not, see https://www.gnu.org/licenses/.
  */
 
-use godot::{meta::PropertyHintInfo, prelude::*};
+use darkforge::roster::{RosterEntry, Standing};
+use godot::prelude::*;
 
 #[derive(GodotClass)]
 #[class(base=Resource)]
 pub struct Character {
     base: Base<Resource>,
+    #[export]
     name: GString,
+    #[export]
+    retired: bool,
+}
+
+impl Character {
+    /// Creates a character resource from an entry of a player's roster.
+    pub fn from_roster(entry: &RosterEntry) -> Gd<Character> {
+        Gd::from_init_fn(|base| Self {
+            base,
+            name: entry.character.name().into(),
+            retired: entry.standing == Standing::Retired,
+        })
+    }
 }
 
 #[godot_api]
@@ -26,6 +41,7 @@ impl IResource for Character {
         Self {
             base,
             name: "Character".into(),
+            retired: false,
         }
     }
 }
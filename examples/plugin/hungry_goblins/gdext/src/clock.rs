@@ -0,0 +1,180 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+use std::rc::Rc;
+
+use darkforge::{
+    campaign::{Campaign, Result},
+    clock::Clock,
+    consequence::ClockConsequence,
+    permission::Actor,
+};
+use godot::prelude::*;
+use uuid::Uuid;
+
+use crate::{
+    error::Reporter,
+    forge::{self, Shared},
+};
+
+/// Adds, lists and ticks the progress clocks of a campaign database, as the GM.
+#[derive(GodotClass)]
+#[class(base=RefCounted)]
+pub struct ClockService {
+    base: Base<RefCounted>,
+    /// Path to the campaign database, `user://campaign.db` if empty.
+    #[var]
+    db: GString,
+    /// The error of the last call, see `DarkForgeError`, or an empty dictionary if it succeeded.
+    #[var(get)]
+    last_error: Dictionary,
+    forge: Option<Shared>,
+}
+
+#[godot_api]
+impl IRefCounted for ClockService {
+    fn init(base: Base<RefCounted>) -> Self {
+        Self {
+            base,
+            db: GString::new(),
+            last_error: Dictionary::new(),
+            forge: None,
+        }
+    }
+}
+
+#[godot_api]
+impl ClockService {
+    /// Emitted when a call fails, with the error as recorded in `last_error`.
+    #[signal]
+    fn failed(error: Dictionary);
+
+    /// Returns every clock of the campaign, as dictionaries with an `id`, the `owner` or an empty string, a `label`, its
+    /// `segments`, how many are `filled` and whether it `is_complete`.
+    #[func]
+    fn clocks(&mut self) -> Array<Dictionary> {
+        self.run(async |campaign| campaign.clocks().await)
+            .map(|clocks| {
+                clocks
+                    .iter()
+                    .map(|(id, owner, clock)| {
+                        let mut dictionary = clock_to_dictionary(clock);
+                        dictionary.set("id", id.to_string());
+                        dictionary.set("owner", owner.map(|owner| owner.to_string()).unwrap_or_default());
+                        dictionary
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Adds a clock of the given number of segments, owned by the entity if not empty. Returns the id of the clock, or
+    /// an empty string if it cannot be added.
+    #[func]
+    #[expect(clippy::needless_pass_by_value, reason = "Godot passes arguments to functions by value")]
+    fn add(&mut self, label: GString, segments: i64, owner: GString) -> GString {
+        let owner = if owner.is_empty() {
+            Ok(None)
+        } else {
+            Uuid::parse_str(&owner.to_string()).map(Some)
+        };
+        let Some(owner) = self.record("invalid clock owner", owner.map_err(Into::into)) else {
+            return GString::new();
+        };
+        let clock = Clock::new(label.to_string(), u8::try_from(segments.clamp(0, u8::MAX.into())).unwrap_or_default());
+        let Some(clock) = self.record("invalid clock", clock.map_err(Into::into)) else {
+            return GString::new();
+        };
+
+        self.run(async |campaign| campaign.add_clock(Actor::Gm, owner, &clock).await)
+            .map(|id| id.to_string().into())
+            .unwrap_or_default()
+    }
+
+    /// Starts a clock of the given number of segments as a consequence of the roll recorded in the journal entry `roll`,
+    /// owned by the faction behind it if not empty, and linked to the roll and the score it was made during. Returns
+    /// the id of the clock, or an empty string if it cannot be started.
+    #[func]
+    #[expect(clippy::needless_pass_by_value, reason = "Godot passes arguments to functions by value")]
+    fn start_from_roll(&mut self, label: GString, segments: i64, roll: i64, faction: GString) -> GString {
+        let faction = if faction.is_empty() {
+            Ok(None)
+        } else {
+            Uuid::parse_str(&faction.to_string()).map(Some)
+        };
+        let Some(faction) = self.record("invalid faction", faction.map_err(Into::into)) else {
+            return GString::new();
+        };
+        let clock = Clock::new(label.to_string(), u8::try_from(segments.clamp(0, u8::MAX.into())).unwrap_or_default());
+        let Some(clock) = self.record("invalid clock", clock.map_err(Into::into)) else {
+            return GString::new();
+        };
+
+        let consequence = ClockConsequence { clock, roll, faction };
+        self.run(async |campaign| campaign.start_consequence_clock(Actor::Gm, consequence).await)
+            .map(|id| id.to_string().into())
+            .unwrap_or_default()
+    }
+
+    /// Fills up to `ticks` segments of the clock. Returns the clock as a dictionary as listed by `clocks`, along with
+    /// the number of segments `ticked`, or an empty dictionary if it cannot be ticked.
+    #[func]
+    #[expect(clippy::needless_pass_by_value, reason = "Godot passes arguments to functions by value")]
+    fn tick(&mut self, clock: GString, ticks: i64) -> Dictionary {
+        let Some(entity) = self.record("invalid clock", Uuid::parse_str(&clock.to_string()).map_err(Into::into)) else {
+            return Dictionary::new();
+        };
+        let ticks = u8::try_from(ticks.clamp(0, u8::MAX.into())).unwrap_or_default();
+
+        self.run(async |campaign| campaign.tick_clock(Actor::Gm, entity, ticks).await)
+            .map(|ticked| {
+                let mut dictionary = clock_to_dictionary(&ticked.clock);
+                dictionary.set("id", entity.to_string());
+                dictionary.set("ticked", i64::from(ticked.ticked));
+                dictionary
+            })
+            .unwrap_or_default()
+    }
+}
+
+impl ClockService {
+    /// Creates a service for the clocks of the campaign of the singleton.
+    pub(crate) fn attached(forge: &Shared) -> Gd<ClockService> {
+        Gd::from_init_fn(|base| Self {
+            base,
+            db: GString::new(),
+            last_error: Dictionary::new(),
+            forge: Some(Rc::clone(forge)),
+        })
+    }
+
+    /// Opens the campaign and runs `f`, reporting any error.
+    fn run<T>(&mut self, f: impl AsyncFnOnce(&mut Campaign) -> Result<T>) -> Option<T> {
+        let result = forge::run(self.forge.as_ref(), &self.db, f);
+        self.record("failed to access clocks", result)
+    }
+}
+
+impl Reporter for ClockService {
+    fn last_error_mut(&mut self) -> &mut Dictionary {
+        &mut self.last_error
+    }
+}
+
+fn clock_to_dictionary(clock: &Clock) -> Dictionary {
+    dict! {
+        "label": clock.label(),
+        "segments": i64::from(clock.segments()),
+        "filled": i64::from(clock.filled()),
+        "is_complete": clock.is_complete(),
+    }
+}
@@ -0,0 +1,84 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+use std::{
+    collections::BTreeMap,
+    time::{Duration, Instant},
+};
+
+/// A value waiting to be written, with when it was first and last set.
+struct Pending<V> {
+    value: V,
+    first: Instant,
+    last: Instant,
+}
+
+/// Holds the writes of controls firing many times a second, e.g. a stress stepper held down or a slider dragged, so
+/// only their final value reaches the store.
+///
+/// A write replaces the one pending for the same key, e.g. the entity and field it sets. It is due once no other write
+/// replaced it for the `quiet` period, or once it has been held for the `limit` while writes kept replacing it, so a
+/// control that never settles is still written at a steady rate.
+pub(crate) struct Coalescer<K, V> {
+    quiet: Duration,
+    limit: Duration,
+    pending: BTreeMap<K, Pending<V>>,
+}
+
+impl<K: Ord + Clone, V> Coalescer<K, V> {
+    /// Creates a coalescer holding writes for the `quiet` period, and at most for the `limit`.
+    pub(crate) fn new(quiet: Duration, limit: Duration) -> Coalescer<K, V> {
+        Coalescer {
+            quiet,
+            limit: limit.max(quiet),
+            pending: BTreeMap::new(),
+        }
+    }
+
+    /// Holds the write for the key, replacing the one pending for it if any.
+    pub(crate) fn push(&mut self, key: K, value: V, now: Instant) {
+        let first = self.pending.get(&key).map_or(now, |pending| pending.first);
+        self.pending.insert(key, Pending { value, first, last: now });
+    }
+
+    /// Returns whether any write is pending.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Returns the pending writes, in key order.
+    pub(crate) fn pending(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.pending.iter().map(|(key, pending)| (key, &pending.value))
+    }
+
+    /// Takes the writes due at `now`, in key order.
+    pub(crate) fn due(&mut self, now: Instant) -> Vec<(K, V)> {
+        let due = self
+            .pending
+            .iter()
+            .filter(|(_, pending)| now.duration_since(pending.last) >= self.quiet || now.duration_since(pending.first) >= self.limit)
+            .map(|(key, _)| key.clone())
+            .collect::<Vec<_>>();
+
+        due.into_iter()
+            .filter_map(|key| self.pending.remove(&key).map(|pending| (key, pending.value)))
+            .collect()
+    }
+
+    /// Takes every pending write, due or not, in key order.
+    pub(crate) fn flush(&mut self) -> Vec<(K, V)> {
+        std::mem::take(&mut self.pending)
+            .into_iter()
+            .map(|(key, pending)| (key, pending.value))
+            .collect()
+    }
+}
@@ -0,0 +1,113 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+use darkforge::{campaign::Campaign, permission::Actor};
+use darkforge_data::{
+    pack::{Changed, EntryChange},
+    store::sql::sqlite::SqliteStore,
+};
+use godot::prelude::*;
+
+use crate::{error::Reporter, save};
+
+/// Reloads the content pack of a campaign database while the game or the editor is running.
+///
+/// Only the entries that changed on disk are applied. A `content_changed` signal is emitted for each of them so docks
+/// and scenes showing static data can refresh in place.
+#[derive(GodotClass)]
+#[class(tool, base=Node)]
+pub struct PackReloader {
+    base: Base<Node>,
+    /// Path to the campaign database, `user://campaign.db` if empty.
+    #[export]
+    db: GString,
+    /// The error of the last call, see `DarkForgeError`, or an empty dictionary if it succeeded.
+    #[var(get)]
+    last_error: Dictionary,
+}
+
+#[godot_api]
+impl INode for PackReloader {
+    fn init(base: Base<Node>) -> Self {
+        Self {
+            base,
+            db: GString::new(),
+            last_error: Dictionary::new(),
+        }
+    }
+}
+
+#[godot_api]
+impl PackReloader {
+    /// Emitted for each entry added, updated or removed by a reload, with the entry's category and id and how it
+    /// changed: `added`, `updated` or `removed`.
+    #[signal]
+    fn content_changed(category: GString, id: GString, change: GString);
+
+    /// Emitted once a reload is applied, with the number of entries that changed.
+    #[signal]
+    fn pack_reloaded(changes: i64);
+
+    /// Emitted when a call fails, with the error as recorded in `last_error`.
+    #[signal]
+    fn failed(error: Dictionary);
+
+    /// Reloads the campaign's content pack from the directory at `path`. Returns the number of entries that changed,
+    /// or -1 if the pack cannot be reloaded.
+    #[func]
+    fn reload_pack(&mut self, path: GString) -> i64 {
+        let changes = self.try_reload(String::from(path));
+        let Some(changes) = self.record("failed to reload content pack", changes) else {
+            return -1;
+        };
+
+        for change in &changes {
+            let args = [
+                change.category.to_variant(),
+                change.id.to_string().to_variant(),
+                change_name(change.change).to_variant(),
+            ];
+            self.base_mut().emit_signal("content_changed", &args);
+        }
+        let count = i64::try_from(changes.len()).unwrap_or(i64::MAX);
+        self.base_mut().emit_signal("pack_reloaded", &[count.to_variant()]);
+
+        count
+    }
+}
+
+impl PackReloader {
+    fn try_reload(&self, path: String) -> anyhow::Result<Vec<EntryChange>> {
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+
+        Ok(runtime.block_on(async {
+            let store = SqliteStore::open(save::database(&self.db)).await?;
+            let mut campaign = Campaign::open(store).await?;
+            campaign.reload_pack(Actor::Gm, path).await
+        })?)
+    }
+}
+
+impl Reporter for PackReloader {
+    fn last_error_mut(&mut self) -> &mut Dictionary {
+        &mut self.last_error
+    }
+}
+
+/// Returns the name of the change, as passed to `content_changed`.
+fn change_name(change: Changed) -> &'static str {
+    match change {
+        Changed::Added => "added",
+        Changed::Updated => "updated",
+        Changed::Removed => "removed",
+    }
+}
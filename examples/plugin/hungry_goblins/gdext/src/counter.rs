@@ -0,0 +1,288 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+use std::rc::Rc;
+
+use darkforge::{
+    campaign::{Campaign, CampaignError, Result},
+    character::{Character, Stress},
+    crew::Heat,
+    permission::Actor,
+};
+use godot::prelude::*;
+use uuid::Uuid;
+
+use crate::{
+    error::Reporter,
+    forge::{self, Shared},
+};
+
+/// Milliseconds of animation suggested for each step a counter moves, unless told otherwise.
+pub(crate) const STEP_MS: i64 = 120;
+/// Longest animation suggested for a counter to move, in milliseconds, unless told otherwise.
+pub(crate) const MAX_DURATION_MS: i64 = 600;
+
+/// A counter tracked on an entity of the campaign.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Counter {
+    /// The stress a character has taken.
+    Stress,
+    /// The heat a crew has drawn.
+    Heat,
+    /// The wanted level of a crew.
+    Wanted,
+}
+
+impl Counter {
+    /// Returns the name of the counter in signal payloads.
+    fn name(self) -> &'static str {
+        match self {
+            Counter::Stress => "stress",
+            Counter::Heat => "heat",
+            Counter::Wanted => "wanted",
+        }
+    }
+
+    /// Returns the highest value of the counter.
+    fn max(self) -> u8 {
+        match self {
+            Counter::Stress => Stress::MAX,
+            Counter::Heat => Heat::MAX_HEAT,
+            Counter::Wanted => Heat::MAX_WANTED,
+        }
+    }
+
+    /// Returns the value of the counter on the entity, failing if the entity does not have it.
+    async fn read(self, campaign: &mut Campaign, entity: Uuid) -> Result<u8> {
+        Ok(match self {
+            Counter::Stress => {
+                campaign.get::<Character>(entity).await?.ok_or(CampaignError::MissingCharacter(entity))?;
+                campaign.get::<Stress>(entity).await?.unwrap_or_default().value()
+            }
+            Counter::Heat => campaign.get::<Heat>(entity).await?.ok_or(CampaignError::MissingCrew(entity))?.heat(),
+            Counter::Wanted => campaign.get::<Heat>(entity).await?.ok_or(CampaignError::MissingCrew(entity))?.wanted(),
+        })
+    }
+
+    /// Sets the counter on the entity, capped at its highest value, and returns the value set.
+    async fn write(self, campaign: &mut Campaign, entity: Uuid, value: u8) -> Result<u8> {
+        match self {
+            Counter::Stress => {
+                let stress = Stress::new(value);
+                campaign.update(Actor::Gm, entity, &stress).await?;
+                Ok(stress.value())
+            }
+            Counter::Heat | Counter::Wanted => {
+                let heat = campaign.get::<Heat>(entity).await?.ok_or(CampaignError::MissingCrew(entity))?;
+                let wanted = self == Counter::Wanted;
+                let heat = if wanted {
+                    Heat::new(heat.heat(), value)
+                } else {
+                    Heat::new(value, heat.wanted())
+                };
+                campaign.update(Actor::Gm, entity, &heat).await?;
+                Ok(if wanted { heat.wanted() } else { heat.heat() })
+            }
+        }
+    }
+}
+
+/// Returns the payload of the `counter_changed` signal for the counter of the entity moving from `old` to `new`, with
+/// the milliseconds suggested to animate it, `step_ms` per step up to `max_duration_ms`.
+pub(crate) fn change(entity: Uuid, counter: Counter, old: u8, new: u8, step_ms: i64, max_duration_ms: i64) -> Dictionary {
+    let steps = i64::from(old.abs_diff(new));
+
+    dict! {
+        "entity": entity.to_string(),
+        "counter": counter.name(),
+        "old": i64::from(old),
+        "new": i64::from(new),
+        "max": i64::from(counter.max()),
+        "duration_ms": step_ms.max(0).saturating_mul(steps).min(max_duration_ms.max(0)),
+    }
+}
+
+/// Reads and moves the stress of characters and the heat and wanted level of crews, as the GM, for counters that tween
+/// from one value to the next.
+///
+/// Each change emits `counter_changed` with the old and new values and a suggested duration to animate them over, so
+/// the UI can tween rather than jump. A UI that skips animations, e.g. when the player fast-forwards, reads the values
+/// to settle on with `counters`.
+#[derive(GodotClass)]
+#[class(base=RefCounted)]
+pub struct CounterService {
+    base: Base<RefCounted>,
+    /// Path to the campaign database, `user://campaign.db` if empty.
+    #[var]
+    db: GString,
+    /// Milliseconds of animation suggested for each step a counter moves.
+    #[var]
+    step_ms: i64,
+    /// Longest animation suggested for a counter to move, in milliseconds, however many steps it moves.
+    #[var]
+    max_duration_ms: i64,
+    /// The error of the last call, see `DarkForgeError`, or an empty dictionary if it succeeded.
+    #[var(get)]
+    last_error: Dictionary,
+    forge: Option<Shared>,
+}
+
+#[godot_api]
+impl IRefCounted for CounterService {
+    fn init(base: Base<RefCounted>) -> Self {
+        Self {
+            base,
+            db: GString::new(),
+            step_ms: STEP_MS,
+            max_duration_ms: MAX_DURATION_MS,
+            last_error: Dictionary::new(),
+            forge: None,
+        }
+    }
+}
+
+#[godot_api]
+impl CounterService {
+    /// Emitted when a call fails, with the error as recorded in `last_error`.
+    #[signal]
+    fn failed(error: Dictionary);
+
+    /// Emitted when a counter moves, with the id of the `entity`, the name of the `counter` (`stress`, `heat` or
+    /// `wanted`), its `old` and `new` values, its `max` value and the milliseconds suggested to animate it as
+    /// `duration_ms`.
+    #[signal]
+    fn counter_changed(change: Dictionary);
+
+    /// Returns the current values of the counters of the entity as a dictionary, with the `stress` of a character or
+    /// the `heat` and `wanted` level of a crew, along with their highest values as `max_stress`, `max_heat` and
+    /// `max_wanted`. Returns an empty dictionary if the entity has no counter.
+    #[func]
+    #[expect(clippy::needless_pass_by_value, reason = "Godot passes arguments to functions by value")]
+    fn counters(&mut self, entity: GString) -> Dictionary {
+        let Some(entity) = self.record("invalid entity", Uuid::parse_str(&entity.to_string()).map_err(Into::into)) else {
+            return Dictionary::new();
+        };
+
+        self.run(async |campaign| {
+            let mut counters = Dictionary::new();
+            for counter in [Counter::Stress, Counter::Heat, Counter::Wanted] {
+                match counter.read(campaign, entity).await {
+                    Ok(value) => {
+                        counters.set(counter.name(), i64::from(value));
+                        counters.set(format!("max_{}", counter.name()), i64::from(counter.max()));
+                    }
+                    Err(CampaignError::MissingCharacter(_) | CampaignError::MissingCrew(_)) => {}
+                    Err(err) => return Err(err),
+                }
+            }
+
+            Ok(counters)
+        })
+        .unwrap_or_default()
+    }
+
+    /// Sets the stress the character has taken, capped at the stress track. Returns false if it cannot be set.
+    #[func]
+    #[expect(clippy::needless_pass_by_value, reason = "Godot passes arguments to functions by value")]
+    fn set_stress(&mut self, character: GString, value: i64) -> bool {
+        self.set(&character, Counter::Stress, value)
+    }
+
+    /// Adds to the stress the character has taken, or clears stress if negative. Returns false if it cannot be set.
+    #[func]
+    #[expect(clippy::needless_pass_by_value, reason = "Godot passes arguments to functions by value")]
+    fn add_stress(&mut self, character: GString, amount: i64) -> bool {
+        self.add(&character, Counter::Stress, amount)
+    }
+
+    /// Sets the heat of the crew, capped at the heat track. Returns false if it cannot be set.
+    #[func]
+    #[expect(clippy::needless_pass_by_value, reason = "Godot passes arguments to functions by value")]
+    fn set_heat(&mut self, crew: GString, value: i64) -> bool {
+        self.set(&crew, Counter::Heat, value)
+    }
+
+    /// Adds to the heat of the crew, or reduces it if negative. Returns false if it cannot be set.
+    #[func]
+    #[expect(clippy::needless_pass_by_value, reason = "Godot passes arguments to functions by value")]
+    fn add_heat(&mut self, crew: GString, amount: i64) -> bool {
+        self.add(&crew, Counter::Heat, amount)
+    }
+
+    /// Sets the wanted level of the crew, capped at the highest level. Returns false if it cannot be set.
+    #[func]
+    #[expect(clippy::needless_pass_by_value, reason = "Godot passes arguments to functions by value")]
+    fn set_wanted(&mut self, crew: GString, value: i64) -> bool {
+        self.set(&crew, Counter::Wanted, value)
+    }
+}
+
+impl CounterService {
+    /// Creates a service for the counters of the campaign of the singleton.
+    pub(crate) fn attached(forge: &Shared) -> Gd<CounterService> {
+        Gd::from_init_fn(|base| Self {
+            base,
+            db: GString::new(),
+            step_ms: STEP_MS,
+            max_duration_ms: MAX_DURATION_MS,
+            last_error: Dictionary::new(),
+            forge: Some(Rc::clone(forge)),
+        })
+    }
+
+    /// Sets the counter of the entity to the value, clamped to its range.
+    fn set(&mut self, entity: &GString, counter: Counter, value: i64) -> bool {
+        self.move_by(entity, counter, |_| value)
+    }
+
+    /// Moves the counter of the entity by the amount, clamped to its range.
+    fn add(&mut self, entity: &GString, counter: Counter, amount: i64) -> bool {
+        self.move_by(entity, counter, |old| old.saturating_add(amount))
+    }
+
+    /// Moves the counter of the entity to the value `to` computes from its current one, emitting `counter_changed` if
+    /// it moved.
+    fn move_by(&mut self, entity: &GString, counter: Counter, to: impl FnOnce(i64) -> i64) -> bool {
+        let Some(entity) = self.record("invalid entity", Uuid::parse_str(&entity.to_string()).map_err(Into::into)) else {
+            return false;
+        };
+
+        let moved = self.run(async |campaign| {
+            let old = counter.read(campaign, entity).await?;
+            let value = to(old.into()).clamp(0, counter.max().into());
+            let new = counter.write(campaign, entity, u8::try_from(value).unwrap_or_default()).await?;
+
+            Ok((old, new))
+        });
+        let Some((old, new)) = moved else {
+            return false;
+        };
+
+        if old != new {
+            let change = change(entity, counter, old, new, self.step_ms, self.max_duration_ms);
+            self.base_mut().emit_signal("counter_changed", &[change.to_variant()]);
+        }
+        true
+    }
+
+    /// Opens the campaign and runs `f`, reporting any error.
+    fn run<T>(&mut self, f: impl AsyncFnOnce(&mut Campaign) -> Result<T>) -> Option<T> {
+        let result = forge::run(self.forge.as_ref(), &self.db, f);
+        self.record("failed to access counters", result)
+    }
+}
+
+impl Reporter for CounterService {
+    fn last_error_mut(&mut self) -> &mut Dictionary {
+        &mut self.last_error
+    }
+}
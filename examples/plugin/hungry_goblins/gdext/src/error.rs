@@ -0,0 +1,334 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+    io,
+};
+
+use darkforge::{
+    campaign::CampaignError, clock::ClockError, oracle::OracleError, permission::PermissionError, prompt::PromptError, script::ScriptError,
+    setup::SetupError,
+};
+#[cfg(feature = "encryption")]
+use darkforge_data::store::sql::sqlite::CipherError;
+#[cfg(feature = "store")]
+use darkforge_data::store::sql::sqlite::SqliteError;
+use darkforge_data::{collation::CollationError, pack::PackError};
+use godot::{obj::WithBaseField, prelude::*};
+
+/// The stable codes of the errors reported by the services, for `GDScript` to branch on, e.g.
+/// `if error["code"] == DarkForgeError.SAVE_CONFLICT`.
+///
+/// Services record their last error as a dictionary with the `code`, a `message` for the logs and a `context`
+/// dictionary, holding e.g. the `entity` that does not exist or the `path` of the content pack, and emit it with their
+/// `failed` signal.
+#[derive(GodotClass)]
+#[class(base=RefCounted)]
+pub struct DarkForgeError {
+    base: Base<RefCounted>,
+}
+
+#[godot_api]
+impl IRefCounted for DarkForgeError {
+    fn init(base: Base<RefCounted>) -> Self {
+        Self { base }
+    }
+}
+
+#[godot_api]
+impl DarkForgeError {
+    /// No error.
+    #[constant]
+    pub(crate) const OK: i64 = 0;
+    /// An error without a more specific code.
+    #[constant]
+    pub(crate) const UNKNOWN: i64 = 1;
+    /// An argument is malformed, e.g. an id that is not a UUID.
+    #[constant]
+    pub(crate) const INVALID_ARGUMENT: i64 = 2;
+    /// An entity, e.g. a character or a clock, does not exist, the `entity` is in the context.
+    #[constant]
+    pub(crate) const NOT_FOUND: i64 = 3;
+    /// The player is not allowed to do this, the `player` is in the context.
+    #[constant]
+    pub(crate) const PERMISSION_DENIED: i64 = 4;
+    /// The save is held by another connection, already holds a campaign, or its campaign is open.
+    #[constant]
+    pub(crate) const SAVE_CONFLICT: i64 = 5;
+    /// A content pack cannot be found, the `path` or the `expected` pack is in the context.
+    #[constant]
+    pub(crate) const CONTENT_PACK_MISSING: i64 = 6;
+    /// A content pack cannot be read.
+    #[constant]
+    pub(crate) const CONTENT_PACK_INVALID: i64 = 7;
+    /// The save cannot be read or written.
+    #[constant]
+    pub(crate) const STORAGE: i64 = 8;
+    /// The change breaks a rule of the game, e.g. armor already used.
+    #[constant]
+    pub(crate) const RULE_VIOLATION: i64 = 9;
+    /// A content pack script failed, the `script` is in the context.
+    #[constant]
+    pub(crate) const SCRIPT_FAILED: i64 = 10;
+    /// The save holds encrypted data and the singleton was not unlocked, or was unlocked with another passphrase.
+    #[constant]
+    pub(crate) const LOCKED: i64 = 11;
+    /// The save keeps failing to be read or written, e.g. as another program holds it, so calls fail at once for a
+    /// while, how long in milliseconds is the `retry_in_ms` in the context. Hold the changes and try them again then.
+    #[constant]
+    pub(crate) const UNAVAILABLE: i64 = 12;
+
+    /// Returns the name of the code, e.g. `SAVE_CONFLICT`.
+    #[func]
+    fn code_name(code: i64) -> GString {
+        match code {
+            DarkForgeError::OK => "OK",
+            DarkForgeError::INVALID_ARGUMENT => "INVALID_ARGUMENT",
+            DarkForgeError::NOT_FOUND => "NOT_FOUND",
+            DarkForgeError::PERMISSION_DENIED => "PERMISSION_DENIED",
+            DarkForgeError::SAVE_CONFLICT => "SAVE_CONFLICT",
+            DarkForgeError::CONTENT_PACK_MISSING => "CONTENT_PACK_MISSING",
+            DarkForgeError::CONTENT_PACK_INVALID => "CONTENT_PACK_INVALID",
+            DarkForgeError::STORAGE => "STORAGE",
+            DarkForgeError::RULE_VIOLATION => "RULE_VIOLATION",
+            DarkForgeError::SCRIPT_FAILED => "SCRIPT_FAILED",
+            DarkForgeError::LOCKED => "LOCKED",
+            DarkForgeError::UNAVAILABLE => "UNAVAILABLE",
+            _ => "UNKNOWN",
+        }
+        .into()
+    }
+}
+
+/// An argument passed from `GDScript` that the services cannot make sense of, reported as
+/// [`DarkForgeError::INVALID_ARGUMENT`].
+#[derive(Debug)]
+pub(crate) struct InvalidArgument(pub(crate) String);
+
+impl Display for InvalidArgument {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Error for InvalidArgument {}
+
+/// A service recording the error of its last call and emitting it with its `failed` signal.
+pub(crate) trait Reporter: GodotClass<Base: Inherits<Object>> + WithBaseField {
+    /// Returns the error of the last call, an empty dictionary if it succeeded.
+    fn last_error_mut(&mut self) -> &mut Dictionary;
+
+    /// Records the result of a call, reporting and emitting the error if it failed, see [`report`].
+    fn record<T>(&mut self, what: &str, result: anyhow::Result<T>) -> Option<T> {
+        match result {
+            Ok(value) => {
+                *self.last_error_mut() = Dictionary::new();
+                Some(value)
+            }
+            Err(err) => {
+                let error = report(what, &err);
+                *self.last_error_mut() = error.clone();
+                self.base_mut().upcast_mut::<Object>().emit_signal("failed", &[error.to_variant()]);
+                None
+            }
+        }
+    }
+}
+
+/// Logs the error, prefixed with `what` failed, and returns it as a dictionary, see [`DarkForgeError`].
+fn report(what: &str, err: &anyhow::Error) -> Dictionary {
+    godot_error!("{what}: {err:#}");
+
+    let (code, context) = err.chain().find_map(classify).unwrap_or((DarkForgeError::UNKNOWN, Dictionary::new()));
+    dict! {
+        "code": code,
+        "message": format!("{what}: {err:#}"),
+        "context": context,
+    }
+}
+
+/// Returns the code and context of the error, or `None` if it is not one the services know about.
+fn classify(err: &(dyn Error + 'static)) -> Option<(i64, Dictionary)> {
+    if let Some(err) = err.downcast_ref::<CampaignError>() {
+        return Some(campaign(err));
+    }
+    #[cfg(feature = "store")]
+    if let Some(err) = err.downcast_ref::<SqliteError>() {
+        return Some(storage(err));
+    }
+    #[cfg(feature = "encryption")]
+    if let Some(err) = err.downcast_ref::<CipherError>() {
+        let code = if matches!(err, CipherError::WrongPassphrase) {
+            DarkForgeError::LOCKED
+        } else {
+            DarkForgeError::STORAGE
+        };
+        return Some((code, Dictionary::new()));
+    }
+    if let Some(err) = err.downcast_ref::<PackError>() {
+        return Some(pack(err));
+    }
+    if let Some(err) = err.downcast_ref::<PermissionError>() {
+        return Some(permission(err));
+    }
+    if let Some(err) = err.downcast_ref::<ScriptError>() {
+        return Some(script(err));
+    }
+    if let Some(err) = err.downcast_ref::<SetupError>() {
+        return Some(setup(err));
+    }
+    if err.is::<InvalidArgument>()
+        || err.is::<uuid::Error>()
+        || err.is::<CollationError>()
+        || err.is::<PromptError>()
+        || err.is::<OracleError>()
+        || err.is::<ClockError>()
+    {
+        return Some((DarkForgeError::INVALID_ARGUMENT, Dictionary::new()));
+    }
+
+    None
+}
+
+fn campaign(err: &CampaignError) -> (i64, Dictionary) {
+    match err {
+        #[cfg(feature = "store")]
+        CampaignError::Store(err) => storage(err),
+        CampaignError::Pack(err) => pack(err),
+        CampaignError::Permission(err) => permission(err),
+        CampaignError::Script(err) => script(err),
+        CampaignError::Codec(_) => (DarkForgeError::STORAGE, Dictionary::new()),
+        CampaignError::AlreadyExists(campaign) => (DarkForgeError::SAVE_CONFLICT, dict! { "campaign": campaign.to_string() }),
+        CampaignError::NotFound => (DarkForgeError::NOT_FOUND, Dictionary::new()),
+        CampaignError::MissingCampaign(campaign) => (DarkForgeError::NOT_FOUND, dict! { "campaign": campaign.to_string() }),
+        CampaignError::CampaignOpen(campaign) => (DarkForgeError::SAVE_CONFLICT, dict! { "campaign": campaign.to_string() }),
+        CampaignError::MissingClock(entity)
+        | CampaignError::MissingFaction(entity)
+        | CampaignError::MissingContest(entity)
+        | CampaignError::MissingCrew(entity)
+        | CampaignError::MissingCharacter(entity)
+        | CampaignError::MissingNpc(entity)
+        | CampaignError::MissingTrauma(entity)
+        | CampaignError::MissingAbility(entity) => (DarkForgeError::NOT_FOUND, dict! { "entity": entity.to_string() }),
+        CampaignError::MissingSession(session) => (
+            DarkForgeError::NOT_FOUND,
+            dict! { "session": i64::try_from(*session).unwrap_or(i64::MAX) },
+        ),
+        CampaignError::MissingScore(score) => (DarkForgeError::NOT_FOUND, dict! { "score": i64::try_from(*score).unwrap_or(i64::MAX) }),
+        CampaignError::ScoreInProgress(score) => (
+            DarkForgeError::RULE_VIOLATION,
+            dict! { "score": i64::try_from(*score).unwrap_or(i64::MAX) },
+        ),
+        CampaignError::NoScore => (DarkForgeError::RULE_VIOLATION, Dictionary::new()),
+        CampaignError::MissingRoll(seq) => (DarkForgeError::NOT_FOUND, dict! { "seq": *seq }),
+        CampaignError::PackMismatch { expected, found } => (
+            DarkForgeError::CONTENT_PACK_MISSING,
+            dict! { "expected": expected.as_str(), "found": found.as_str() },
+        ),
+        CampaignError::InvalidClock { faction, .. } => (DarkForgeError::CONTENT_PACK_INVALID, dict! { "faction": faction.as_str() }),
+        CampaignError::Block(err) => (
+            DarkForgeError::INVALID_ARGUMENT,
+            dict! { "line": i64::try_from(err.line()).unwrap_or(i64::MAX) },
+        ),
+        CampaignError::ManualRoll(_) | CampaignError::Markup(_) => (DarkForgeError::INVALID_ARGUMENT, Dictionary::new()),
+        CampaignError::DanglingLinks(targets) => (
+            DarkForgeError::NOT_FOUND,
+            dict! { "targets": targets.iter().map(|target| GString::from(target.to_string())).collect::<PackedStringArray>() },
+        ),
+        CampaignError::Setup(err) => setup(err),
+        CampaignError::Template(_) | CampaignError::NoFaction => (DarkForgeError::CONTENT_PACK_INVALID, Dictionary::new()),
+        CampaignError::ContestResolved(entity) | CampaignError::Retired(entity) | CampaignError::NeedsHelp(entity) => {
+            (DarkForgeError::RULE_VIOLATION, dict! { "entity": entity.to_string() })
+        }
+        CampaignError::Overstaked { stake, stash } => (
+            DarkForgeError::RULE_VIOLATION,
+            dict! { "stake": i64::from(*stake), "stash": i64::from(*stash) },
+        ),
+        CampaignError::ArmorUnavailable(_) | CampaignError::Crew(_) => (DarkForgeError::RULE_VIOLATION, Dictionary::new()),
+        CampaignError::Reference(violation) => (
+            DarkForgeError::RULE_VIOLATION,
+            dict! {
+                "entity": violation.entity.to_string(),
+                "referrers": violation
+                    .referrers
+                    .iter()
+                    .map(|referrer| {
+                        let other = if referrer.source == violation.entity { referrer.target } else { referrer.source };
+                        GString::from(other.to_string())
+                    })
+                    .collect::<PackedStringArray>(),
+            },
+        ),
+        CampaignError::Changeset(_) => (DarkForgeError::UNKNOWN, Dictionary::new()),
+    }
+}
+
+#[cfg(feature = "store")]
+fn storage(err: &SqliteError) -> (i64, Dictionary) {
+    if let SqliteError::Unavailable(unavailable) = err {
+        let retry_in = i64::try_from(unavailable.retry_in.as_millis()).unwrap_or(i64::MAX);
+        return (DarkForgeError::UNAVAILABLE, dict! { "retry_in_ms": retry_in });
+    }
+
+    let code = if err.is_busy() {
+        DarkForgeError::SAVE_CONFLICT
+    } else if err.is_locked() {
+        DarkForgeError::LOCKED
+    } else {
+        DarkForgeError::STORAGE
+    };
+
+    (code, Dictionary::new())
+}
+
+fn pack(err: &PackError) -> (i64, Dictionary) {
+    match err {
+        PackError::Io(path, err) if err.kind() == io::ErrorKind::NotFound => {
+            (DarkForgeError::CONTENT_PACK_MISSING, dict! { "path": path.display().to_string() })
+        }
+        PackError::Io(path, _) | PackError::Codec(path, _) => (DarkForgeError::CONTENT_PACK_INVALID, dict! { "path": path.display().to_string() }),
+        PackError::MissingId { category, .. } | PackError::InvalidEntries(category, _) => {
+            (DarkForgeError::CONTENT_PACK_INVALID, dict! { "category": category.as_str() })
+        }
+        PackError::SlugCollision { category, slug, .. } => (
+            DarkForgeError::CONTENT_PACK_INVALID,
+            dict! { "category": category.as_str(), "slug": slug.as_str() },
+        ),
+        PackError::InvalidSlug(slug) => (DarkForgeError::INVALID_ARGUMENT, dict! { "slug": slug.as_str() }),
+    }
+}
+
+fn permission(err: &PermissionError) -> (i64, Dictionary) {
+    let player = match err {
+        PermissionError::NotOwner { player, .. } | PermissionError::GmOnly(player) | PermissionError::OtherPlayer { player, .. } => player,
+    };
+
+    (DarkForgeError::PERMISSION_DENIED, dict! { "player": player.to_string() })
+}
+
+fn setup(err: &SetupError) -> (i64, Dictionary) {
+    match err {
+        SetupError::UnknownHouseRule(name) => (DarkForgeError::INVALID_ARGUMENT, dict! { "house_rule": name.as_str() }),
+        SetupError::NotInPlay(rule) => (DarkForgeError::RULE_VIOLATION, dict! { "house_rule": rule.name() }),
+    }
+}
+
+fn script(err: &ScriptError) -> (i64, Dictionary) {
+    match err {
+        ScriptError::Missing(script) => (DarkForgeError::NOT_FOUND, dict! { "entity": script.to_string() }),
+        ScriptError::WrongHook { script, .. } | ScriptError::Compile(script, _) | ScriptError::Runtime(script, _) => {
+            (DarkForgeError::SCRIPT_FAILED, dict! { "script": script.to_string() })
+        }
+    }
+}
@@ -0,0 +1,856 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+use std::{
+    cell::RefCell,
+    path::PathBuf,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use anyhow::Context;
+use darkforge::{
+    campaign::{self, Campaign, CampaignInfo},
+    integrity::ContentMismatch,
+    permission::Actor,
+    slot::SaveSlot,
+};
+#[cfg(feature = "encryption")]
+use darkforge_data::store::sql::sqlite::Key;
+use darkforge_data::{
+    collation::Collation,
+    markup::{Markup, Target},
+    pack::{Pack, PackHash},
+    store::{
+        Content, Migrator,
+        sql::sqlite::{MIGRATIONS, RetryPolicy, SqliteStore, SqliteStoreBuilder, StoreMetrics},
+    },
+};
+use godot::{
+    classes::{INode, Node, notify::NodeNotification},
+    prelude::*,
+};
+use tokio::runtime::Runtime;
+use uuid::Uuid;
+
+use crate::{
+    clock::ClockService,
+    counter::CounterService,
+    error::Reporter,
+    pack::{self, ContentPack},
+    prompt::ActionPrompts,
+    roll::RollService,
+    save,
+    selection::CharacterSelection,
+    setup::SetupService,
+    stats::StatsService,
+    task::{Durability, FLUSH_TIMEOUT, Failure, Supervisor},
+};
+
+/// The runtime and campaign shared by the services of the [`DarkForge`] singleton.
+pub(crate) struct Forge {
+    runtime: Runtime,
+    store: SqliteStoreBuilder,
+    campaign: Option<Campaign>,
+    /// The campaign chosen among those of the database, or the first one not archived if none was.
+    selected: Option<Uuid>,
+    /// When the open campaign was opened or its playtime last counted.
+    opened: Option<Instant>,
+    /// The tasks spawned on the runtime, flushed before the campaign is closed.
+    tasks: Supervisor,
+}
+
+/// A handle on the forge of the singleton, held by its services.
+pub(crate) type Shared = Rc<RefCell<Forge>>;
+
+impl Forge {
+    /// Starts a runtime for the campaign database at the given path. The campaign is opened on first use, as the
+    /// database may not hold one yet. Calls failing on a lock held by another program are tried again, until they keep
+    /// failing and the store fails fast for a while, see [`RetryPolicy`].
+    pub(crate) fn open(db: PathBuf) -> anyhow::Result<Forge> {
+        Ok(Forge {
+            runtime: tokio::runtime::Builder::new_current_thread().enable_all().build()?,
+            store: SqliteStore::builder(db).retry(RetryPolicy::default()),
+            campaign: None,
+            selected: None,
+            opened: None,
+            tasks: Supervisor::default(),
+        })
+    }
+
+    /// Starts a runtime for the campaign database at the given path, creating its schema and registering the packs
+    /// with its static store. Query metrics are collected if given the slow query threshold.
+    fn start(db: PathBuf, packs: &[Pack], slow: Option<Duration>) -> anyhow::Result<Forge> {
+        let mut forge = Forge::open(db)?;
+        if let Some(slow) = slow {
+            forge.store = forge.store.metrics(slow);
+        }
+        forge.runtime.block_on(async {
+            let mut store = forge.store.clone().build().await?;
+            store.apply(MIGRATIONS).await?;
+            pack::register(&mut store, packs).await?;
+            store.close().await
+        })?;
+
+        Ok(forge)
+    }
+
+    /// Runs `f` on the campaign, opening it first if it is not open yet.
+    pub(crate) fn run<T>(&mut self, f: impl AsyncFnOnce(&mut Campaign) -> campaign::Result<T>) -> anyhow::Result<T> {
+        self.runtime.block_on(async {
+            let mut campaign = if let Some(campaign) = self.campaign.take() {
+                campaign
+            } else {
+                self.opened = Some(Instant::now());
+                reopen(&self.store, self.selected).await?
+            };
+            let result = f(&mut campaign).await;
+            self.campaign = Some(campaign);
+
+            Ok(result?)
+        })
+    }
+
+    /// Spawns a task on the runtime running `f` on the campaign, opened again on a connection of its own so the task
+    /// does not hold up the services. The task must close the campaign once done with it.
+    pub(crate) fn spawn<F: Future<Output = anyhow::Result<()>> + Send + 'static>(
+        &mut self, durability: Durability, label: &str, f: impl FnOnce(Campaign) -> F + Send + 'static,
+    ) {
+        let store = self.store.clone();
+        let selected = self.campaign.as_ref().map(Campaign::id).or(self.selected);
+        self.tasks
+            .spawn(&self.runtime, durability, label, async move { f(reopen(&store, selected).await?).await });
+    }
+
+    /// Lets the tasks run until they all wait, returning those that failed since last polled.
+    fn poll(&mut self) -> Vec<Failure> {
+        self.tasks.poll(&self.runtime)
+    }
+
+    /// Aborts the cancellable tasks, e.g. when the game changes scene.
+    fn cancel_tasks(&mut self) {
+        self.tasks.cancel();
+    }
+
+    /// Returns the number of tasks still running or not yet reaped.
+    fn pending_tasks(&self) -> usize {
+        self.tasks.pending()
+    }
+
+    /// Lists the campaigns of the database, archived ones included.
+    fn campaigns(&mut self) -> anyhow::Result<Vec<(Uuid, CampaignInfo)>> {
+        self.runtime.block_on(async {
+            if let Some(campaign) = self.campaign.as_mut() {
+                return Ok(campaign.campaigns().await?);
+            }
+
+            let mut store = self.store.clone().build().await?;
+            let campaigns = Campaign::list(&mut store).await?;
+            store.close().await?;
+
+            Ok(campaigns)
+        })
+    }
+
+    /// Lists the campaigns of the database as save slots, with when they were last played and for how long.
+    fn slots(&mut self) -> anyhow::Result<Vec<SaveSlot>> {
+        self.runtime.block_on(async {
+            if let Some(campaign) = self.campaign.as_mut() {
+                return Ok(campaign.save_slots().await?);
+            }
+
+            let mut store = self.store.clone().build().await?;
+            let slots = Campaign::slots(&mut store).await?;
+            store.close().await?;
+
+            Ok(slots)
+        })
+    }
+
+    /// Returns the hashes of the content packs as they were last registered with the database.
+    fn content_hashes(&mut self) -> anyhow::Result<Vec<PackHash>> {
+        self.runtime.block_on(async {
+            if let Some(campaign) = self.campaign.as_mut() {
+                return Ok(campaign.content_hashes().await?);
+            }
+
+            let mut store = self.store.clone().build().await?;
+            let hashes = store.pack_hashes().await?;
+            store.close().await?;
+
+            Ok(hashes)
+        })
+    }
+
+    /// Checks the static data of the database against the hashes of the packs the game shipped with.
+    fn verify_content(&mut self, shipped: &[PackHash]) -> anyhow::Result<Vec<ContentMismatch>> {
+        self.runtime.block_on(async {
+            if let Some(campaign) = self.campaign.as_mut() {
+                return Ok(campaign.verify_content(shipped).await?);
+            }
+
+            let mut store = self.store.clone().build().await?;
+            let mismatches = Campaign::verify_packs(&mut store, shipped).await?;
+            store.close().await?;
+
+            Ok(mismatches)
+        })
+    }
+
+    /// Creates a campaign next to those of the database from the pack, and switches to it.
+    fn create(&mut self, name: String, pack: &Pack) -> anyhow::Result<Uuid> {
+        self.close()?;
+        let campaign = self
+            .runtime
+            .block_on(async { Campaign::create(self.store.clone().build().await?, name, pack).await })?;
+        let id = campaign.id();
+        self.campaign = Some(campaign);
+        self.selected = Some(id);
+        self.opened = Some(Instant::now());
+
+        Ok(id)
+    }
+
+    /// Duplicates a campaign of the database under another name with the pack it was bootstrapped from, and switches
+    /// to the duplicate.
+    fn duplicate(&mut self, id: Uuid, name: String, packs: &[Pack]) -> anyhow::Result<Uuid> {
+        self.switch(id)?;
+        self.count_playtime();
+        let pack = self
+            .campaign
+            .as_ref()
+            .map(|campaign| campaign.info().pack.clone())
+            .and_then(|name| packs.iter().find(|pack| pack.name() == name))
+            .with_context(|| format!("the content pack of campaign {id} is not registered"))?;
+        let Some(campaign) = self.campaign.take() else {
+            anyhow::bail!("campaign {id} is not open");
+        };
+
+        let campaign = self.runtime.block_on(campaign.duplicate(Actor::Gm, name, pack))?;
+        let id = campaign.id();
+        self.campaign = Some(campaign);
+        self.selected = Some(id);
+        self.opened = Some(Instant::now());
+
+        Ok(id)
+    }
+
+    /// Switches to another campaign of the database, once the tasks running on the current one are flushed.
+    fn switch(&mut self, id: Uuid) -> anyhow::Result<()> {
+        self.count_playtime();
+        self.flush()?;
+        let campaign = self.runtime.block_on(async {
+            match self.campaign.take() {
+                Some(campaign) => campaign.switch(id).await,
+                None => Campaign::open_id(self.store.clone().build().await?, id).await,
+            }
+        })?;
+        self.campaign = Some(campaign);
+        self.selected = Some(id);
+        self.opened = Some(Instant::now());
+
+        Ok(())
+    }
+
+    /// Adds the time since the open campaign was opened or its playtime last counted to its playtime, in a task flushed
+    /// before the campaign is closed.
+    fn count_playtime(&mut self) {
+        let Some(elapsed) = self.campaign.as_ref().and(self.opened.take()).map(|opened| opened.elapsed()) else {
+            return;
+        };
+
+        self.spawn(Durability::Flushed, "count playtime", move |mut campaign| async move {
+            campaign.add_playtime(elapsed).await?;
+            Ok(campaign.close().await?)
+        });
+    }
+
+    /// Returns the query metrics of the open campaign's store, if it collects any.
+    fn metrics(&self) -> Option<StoreMetrics> {
+        self.campaign.as_ref().and_then(Campaign::metrics)
+    }
+
+    /// Drops the query metrics collected so far by the open campaign's store.
+    fn reset_metrics(&self) {
+        if let Some(campaign) = &self.campaign {
+            campaign.reset_metrics();
+        }
+    }
+
+    /// Parses the marked up text, returning it along with the targets of its links the campaign holds nothing for.
+    fn markup(&mut self, text: &str) -> anyhow::Result<(Markup, Vec<Target>)> {
+        let markup = Markup::parse(text)?;
+        let dangling = self.run(async |campaign| campaign.dangling_links(&markup).await)?;
+
+        Ok((markup, dangling))
+    }
+
+    /// Sorts the lists of labels and names by the collation from now on, including those of the open campaign.
+    fn collate(&mut self, collation: Collation) {
+        if let Some(campaign) = self.campaign.as_mut() {
+            campaign.collate_with(collation.clone());
+        }
+        self.store = self.store.clone().collation(collation);
+    }
+
+    /// Encrypts the components of the given kinds with the key from now on, and reads those already encrypted with it.
+    /// The campaign is closed so it is opened again with the key on next use.
+    #[cfg(feature = "encryption")]
+    fn unlock(&mut self, key: Key, kinds: Vec<String>) -> anyhow::Result<()> {
+        self.close()?;
+        self.store = self.store.clone().encrypt(key, kinds);
+
+        Ok(())
+    }
+
+    /// Closes the campaign if it is open, counting its playtime and flushing the tasks running on it first, leaving no
+    /// write-ahead log behind. It is opened again on next use.
+    fn close(&mut self) -> anyhow::Result<()> {
+        self.count_playtime();
+        let flushed = self.flush();
+        if let Some(campaign) = self.campaign.take() {
+            self.runtime.block_on(campaign.close())?;
+        }
+
+        flushed
+    }
+
+    /// Aborts the cancellable tasks and waits for the others, up to [`FLUSH_TIMEOUT`], failing if any of them did.
+    fn flush(&mut self) -> anyhow::Result<()> {
+        let failures = self.tasks.flush(&self.runtime, FLUSH_TIMEOUT);
+        match failures.into_iter().next() {
+            Some(Failure { label, error }) => Err(error.context(format!("task {label} failed"))),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Opens the selected campaign of the database, or the first one not archived if none was selected.
+async fn reopen(store: &SqliteStoreBuilder, selected: Option<Uuid>) -> anyhow::Result<Campaign> {
+    let store = store.clone().build().await?;
+    Ok(match selected {
+        Some(id) => Campaign::open_id(store, id).await?,
+        None => Campaign::open(store).await?,
+    })
+}
+
+/// Runs `f` on the campaign of the singleton if given its forge, or else on the campaign database at `db`, opened for
+/// the call.
+pub(crate) fn run<T>(forge: Option<&Shared>, db: &GString, f: impl AsyncFnOnce(&mut Campaign) -> campaign::Result<T>) -> anyhow::Result<T> {
+    match forge {
+        Some(forge) => forge.borrow_mut().run(f),
+        None => Forge::open(save::database(db))?.run(f),
+    }
+}
+
+/// The entry point of the game into Dark Forge, meant to be autoloaded.
+///
+/// When it enters the scene tree, the singleton starts the runtime, creates the schema of the campaign database and
+/// registers its content packs, then exposes the services sharing the open campaign. The campaign is closed when the
+/// game quits, so no write-ahead log is left next to the database.
+#[derive(GodotClass)]
+#[class(base=Node)]
+pub struct DarkForge {
+    base: Base<Node>,
+    /// Path to the campaign database, `user://campaign.db` if empty.
+    #[export]
+    db: GString,
+    /// The content packs to register on start.
+    #[export]
+    packs: Array<Gd<ContentPack>>,
+    /// Collects query metrics, logging the queries that take at least this many milliseconds, if not negative.
+    #[export]
+    slow_query_ms: i64,
+    /// The kinds of components encrypted at rest once the singleton is unlocked, e.g. `npc` for the GM's notes on
+    /// NPCs. Requires the extension to be built with the `encryption` feature.
+    #[export]
+    encrypted_kinds: PackedStringArray,
+    /// The digests of the content packs the game shipped with, by pack name, see `ContentPack.digest()`. If any, the
+    /// static data is checked against them on start, emitting `content_mismatched` if it is modded or was tampered with.
+    #[export]
+    shipped_content: Dictionary,
+    /// The language tag of the locale lists of labels and names are sorted for, e.g. `sv` so "Öland" comes after
+    /// "Zinkgruvan", or the same for every language if empty. See `collate` to switch it once started.
+    #[export]
+    locale: GString,
+    /// Rolls dice and lists the roll history, once started.
+    #[var(get)]
+    rolls: Option<Gd<RollService>>,
+    /// Adds and ticks progress clocks, once started.
+    #[var(get)]
+    clocks: Option<Gd<ClockService>>,
+    /// Moves the stress and heat counters, with old and new values for the UI to tween, once started.
+    #[var(get)]
+    counters: Option<Gd<CounterService>>,
+    /// Plays actions one decision at a time, once started.
+    #[var(get)]
+    prompts: Option<Gd<ActionPrompts>>,
+    /// Lists the characters of the players, once started.
+    #[var(get)]
+    characters: Option<Gd<CharacterSelection>>,
+    /// Displays and amends the decisions of session zero, once started.
+    #[var(get)]
+    setup: Option<Gd<SetupService>>,
+    /// Reads the statistics of the campaign for dashboards to chart, once started.
+    #[var(get)]
+    stats: Option<Gd<StatsService>>,
+    /// The error of the last start or shutdown, see `DarkForgeError`, or an empty dictionary if it succeeded.
+    #[var(get)]
+    last_error: Dictionary,
+    forge: Option<Shared>,
+}
+
+#[godot_api]
+impl INode for DarkForge {
+    fn init(base: Base<Node>) -> Self {
+        Self {
+            base,
+            db: GString::new(),
+            packs: Array::new(),
+            slow_query_ms: -1,
+            encrypted_kinds: PackedStringArray::new(),
+            shipped_content: Dictionary::new(),
+            locale: GString::new(),
+            rolls: None,
+            clocks: None,
+            counters: None,
+            prompts: None,
+            characters: None,
+            setup: None,
+            stats: None,
+            last_error: Dictionary::new(),
+            forge: None,
+        }
+    }
+
+    fn ready(&mut self) {
+        let started = self.start();
+        if self.record("failed to start Dark Forge", started).is_none() {
+            return;
+        }
+
+        self.base_mut().emit_signal("started", &[]);
+        if self.shipped_content.is_empty() {
+            return;
+        }
+
+        let mismatches = self.verify_content();
+        if !mismatches.is_empty() {
+            self.base_mut().emit_signal("content_mismatched", &[mismatches.to_variant()]);
+        }
+    }
+
+    fn process(&mut self, _delta: f64) {
+        let Some(forge) = self.forge.clone() else {
+            return;
+        };
+
+        let failures = forge.borrow_mut().poll();
+        for Failure { label, error } in failures {
+            self.record(&format!("task {label} failed"), Err::<(), _>(error));
+        }
+        if let Some(stats) = &mut self.stats {
+            stats.bind_mut().deliver();
+        }
+    }
+
+    fn exit_tree(&mut self) {
+        self.shutdown();
+    }
+
+    fn on_notification(&mut self, what: NodeNotification) {
+        if what == NodeNotification::WM_CLOSE_REQUEST {
+            self.shutdown();
+        }
+    }
+}
+
+#[godot_api]
+impl DarkForge {
+    /// Emitted once the singleton has started and its services are available.
+    #[signal]
+    fn started();
+
+    /// Emitted when the singleton fails to start, to close the campaign or to run a background task, with the error as
+    /// recorded in `last_error`.
+    #[signal]
+    fn failed(error: Dictionary);
+
+    /// Emitted on start when the static data differs from the `shipped_content`, with the mismatches as
+    /// `verify_content` returns them.
+    #[signal]
+    fn content_mismatched(mismatches: Array<Dictionary>);
+
+    /// Returns whether the singleton has started.
+    #[func]
+    fn is_started(&self) -> bool {
+        self.forge.is_some()
+    }
+
+    /// Aborts the background tasks that need not finish, e.g. reading statistics, so they do not outlive the scene that
+    /// requested them. Call it when the game changes scene. Tasks writing to the campaign are left to finish, and are
+    /// awaited before the campaign is closed.
+    #[func]
+    fn cancel_tasks(&mut self) {
+        if let Some(forge) = &self.forge {
+            forge.borrow_mut().cancel_tasks();
+        }
+    }
+
+    /// Returns the number of background tasks still running or not yet reaped, e.g. for a loading indicator.
+    #[func]
+    fn pending_tasks(&self) -> i64 {
+        self.forge
+            .as_ref()
+            .map_or(0, |forge| i64::try_from(forge.borrow().pending_tasks()).unwrap_or(i64::MAX))
+    }
+
+    /// Returns the content packs as they were last registered with the database, as dictionaries with the name of the
+    /// `pack` and its `digest`, e.g. for a league server to check.
+    #[func]
+    fn content_hashes(&mut self) -> Array<Dictionary> {
+        let hashes = self.forge().and_then(|forge| forge.borrow_mut().content_hashes());
+        self.record("failed to hash content packs", hashes)
+            .map(|hashes| {
+                hashes
+                    .iter()
+                    .map(|hash| {
+                        let mut dictionary = Dictionary::new();
+                        dictionary.set("pack", hash.pack.as_str());
+                        dictionary.set("digest", hash.digest.as_str());
+                        dictionary
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Checks the static data against the `shipped_content`, returning the mismatches as dictionaries with the name of
+    /// the `pack` and the `kind` of mismatch: `missing` if a pack shipped is not registered, `modded` if another version
+    /// of it is, `tampered` if its entries were changed in the database since, with the `categories` changed, and
+    /// `unexpected` for a pack registered that the game did not ship with. Returns an empty array if the game runs on
+    /// vanilla data.
+    #[func]
+    fn verify_content(&mut self) -> Array<Dictionary> {
+        let shipped = self
+            .shipped_content
+            .iter_shared()
+            .map(|(pack, digest)| PackHash {
+                pack: pack.to_string(),
+                digest: digest.to_string(),
+                ..PackHash::default()
+            })
+            .collect::<Vec<_>>();
+        let mismatches = self.forge().and_then(|forge| forge.borrow_mut().verify_content(&shipped));
+        self.record("failed to verify content packs", mismatches)
+            .map(|mismatches| mismatches.iter().map(mismatch_to_dictionary).collect())
+            .unwrap_or_default()
+    }
+
+    /// Returns the campaigns of the database, one per save slot, as dictionaries with an `id`, a `name`, the name of its
+    /// `pack` and whether it is `archived`.
+    #[func]
+    fn campaigns(&mut self) -> Array<Dictionary> {
+        let campaigns = self.forge().and_then(|forge| forge.borrow_mut().campaigns());
+        self.record("failed to list campaigns", campaigns)
+            .map(|campaigns| {
+                campaigns
+                    .iter()
+                    .map(|(id, info)| {
+                        let mut dictionary = Dictionary::new();
+                        dictionary.set("id", id.to_string());
+                        dictionary.set("name", info.name.as_str());
+                        dictionary.set("pack", info.pack.as_str());
+                        dictionary.set("archived", info.archived);
+                        dictionary
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Creates a campaign from the registered pack of the given name in a new save slot, and switches the services to
+    /// it. Returns the id of the campaign, or an empty string if it cannot be created.
+    #[func]
+    #[expect(clippy::needless_pass_by_value, reason = "Godot passes arguments to functions by value")]
+    fn create_campaign(&mut self, name: GString, pack: GString) -> GString {
+        let created = pack::packs(&self.packs).and_then(|packs| {
+            let pack = packs
+                .iter()
+                .find(|candidate| candidate.name() == pack.to_string())
+                .with_context(|| format!("content pack {pack} is not registered"))?;
+            self.forge()?.borrow_mut().create(name.to_string(), pack)
+        });
+
+        self.record("failed to create campaign", created)
+            .map(|id| id.to_string().into())
+            .unwrap_or_default()
+    }
+
+    /// Returns the save slots of the database, one per campaign, as dictionaries with the `id`, `name`, `pack` and
+    /// `archived` flag of the campaign as `campaigns` returns them, when it was `last_played` in seconds since the Unix
+    /// epoch, 0 if never, and the seconds spent playing it as `playtime`. The playtime of the open campaign is counted
+    /// when it is closed or another slot is loaded.
+    #[func]
+    fn save_slots(&mut self) -> Array<Dictionary> {
+        let slots = self.forge().and_then(|forge| forge.borrow_mut().slots());
+        self.record("failed to list save slots", slots)
+            .map(|slots| {
+                slots
+                    .iter()
+                    .map(|slot| {
+                        let mut dictionary = Dictionary::new();
+                        dictionary.set("id", slot.id.to_string());
+                        dictionary.set("name", slot.info.name.as_str());
+                        dictionary.set("pack", slot.info.pack.as_str());
+                        dictionary.set("archived", slot.info.archived);
+                        dictionary.set("last_played", slot.last_played.unwrap_or_default());
+                        dictionary.set("playtime", i64::try_from(slot.playtime.as_secs()).unwrap_or(i64::MAX));
+                        dictionary
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Duplicates the campaign of a save slot into a new slot under another name, as the GM, and switches the services
+    /// to the duplicate. The duplicate holds the roster and free clocks of the campaign, while its factions start
+    /// afresh from its content pack, which must be registered. Returns the id of the duplicate, or an empty string if it
+    /// cannot be made.
+    #[func]
+    #[expect(clippy::needless_pass_by_value, reason = "Godot passes arguments to functions by value")]
+    fn duplicate_campaign(&mut self, id: GString, name: GString) -> GString {
+        let duplicated = Uuid::parse_str(&id.to_string()).map_err(Into::into).and_then(|id| {
+            let packs = pack::packs(&self.packs)?;
+            self.forge()?.borrow_mut().duplicate(id, name.to_string(), &packs)
+        });
+
+        self.record("failed to duplicate campaign", duplicated)
+            .map(|id| id.to_string().into())
+            .unwrap_or_default()
+    }
+
+    /// Deletes the campaign of another save slot, as the GM, along with its journal. The open campaign cannot be deleted,
+    /// failing with `SAVE_CONFLICT`. Returns whether it was deleted.
+    #[func]
+    #[expect(clippy::needless_pass_by_value, reason = "Godot passes arguments to functions by value")]
+    fn delete_campaign(&mut self, id: GString) -> bool {
+        let deleted = Uuid::parse_str(&id.to_string())
+            .map_err(Into::into)
+            .and_then(|id| self.forge()?.borrow_mut().run(async |campaign| campaign.delete(Actor::Gm, id).await));
+
+        self.record("failed to delete campaign", deleted).is_some()
+    }
+
+    /// Switches the services to the campaign of another save slot. Returns whether it could be opened.
+    #[func]
+    #[expect(clippy::needless_pass_by_value, reason = "Godot passes arguments to functions by value")]
+    fn switch_campaign(&mut self, id: GString) -> bool {
+        let switched = Uuid::parse_str(&id.to_string())
+            .map_err(Into::into)
+            .and_then(|id| self.forge()?.borrow_mut().switch(id));
+
+        self.record("failed to switch campaign", switched).is_some()
+    }
+
+    /// Returns the query metrics of the open campaign, as a dictionary with the number of `queries` run, the `rows` they
+    /// read or changed, the milliseconds spent running them as `elapsed_ms` and waiting for a connection as
+    /// `waited_ms`, and the `slow` queries with the same keys plus their `query`. Returns an empty dictionary if no
+    /// metrics are collected, see `slow_query_ms`, or the campaign is not open yet.
+    #[func]
+    fn store_metrics(&self) -> Dictionary {
+        let Some(metrics) = self.forge.as_ref().and_then(|forge| forge.borrow().metrics()) else {
+            return Dictionary::new();
+        };
+
+        let mut dictionary = Dictionary::new();
+        dictionary.set("queries", i64::try_from(metrics.queries).unwrap_or(i64::MAX));
+        dictionary.set("rows", i64::try_from(metrics.rows).unwrap_or(i64::MAX));
+        dictionary.set("elapsed_ms", metrics.elapsed.as_secs_f64() * 1000.0);
+        dictionary.set("waited_ms", metrics.waited.as_secs_f64() * 1000.0);
+        dictionary.set(
+            "slow",
+            metrics
+                .slow
+                .iter()
+                .map(|slow| {
+                    let mut query = Dictionary::new();
+                    query.set("query", slow.query.as_str());
+                    query.set("rows", i64::try_from(slow.rows).unwrap_or(i64::MAX));
+                    query.set("elapsed_ms", slow.elapsed.as_secs_f64() * 1000.0);
+                    query.set("waited_ms", slow.waited.as_secs_f64() * 1000.0);
+                    query
+                })
+                .collect::<Array<Dictionary>>(),
+        );
+        dictionary
+    }
+
+    /// Drops the query metrics collected so far, e.g. when the game enters the screen to profile.
+    #[func]
+    fn reset_store_metrics(&self) {
+        if let Some(forge) = &self.forge {
+            forge.borrow().reset_metrics();
+        }
+    }
+
+    /// Unlocks the save with the passphrase, so the components of the `encrypted_kinds` are encrypted as they are
+    /// written and those already encrypted can be read. The passphrase sets the key the first time, kept in a `.key`
+    /// file next to the database, and must be the same afterwards, else the call fails with `LOCKED`. Call it once
+    /// started, before the campaign is used. Returns whether the save was unlocked.
+    #[func]
+    #[expect(clippy::needless_pass_by_value, reason = "Godot passes arguments to functions by value")]
+    fn unlock(&mut self, passphrase: GString) -> bool {
+        let unlocked = self.unlock_with(&passphrase.to_string());
+        self.record("failed to unlock save", unlocked).is_some()
+    }
+
+    /// Renders the marked up description, e.g. a lore entry, as a dictionary with its `bbcode` for a `RichTextLabel`,
+    /// whose `meta_clicked` signal hands back the id or slug the link clicked points to, its `plain` text, and the
+    /// targets of the links pointing to nothing the campaign holds as `dangling`. Returns an empty dictionary if the
+    /// text is not valid markup.
+    #[func]
+    #[expect(clippy::needless_pass_by_value, reason = "Godot passes arguments to functions by value")]
+    fn render_markup(&mut self, text: GString) -> Dictionary {
+        let rendered = self.forge().and_then(|forge| forge.borrow_mut().markup(&text.to_string()));
+        self.record("failed to render markup", rendered)
+            .map(|(markup, dangling)| {
+                dict! {
+                    "bbcode": markup.to_bbcode(),
+                    "plain": markup.to_plain(),
+                    "dangling": dangling.iter().map(|target| GString::from(target.to_string())).collect::<PackedStringArray>(),
+                }
+            })
+            .unwrap_or_default()
+    }
+
+    /// Sorts the lists of labels and names for the locale from now on, given as a language tag such as `de-AT`, e.g. as
+    /// the player switches language, or the same for every language if empty. Returns whether the locale is supported.
+    #[func]
+    fn collate(&mut self, locale: GString) -> bool {
+        let collated = self.forge().and_then(|forge| {
+            forge.borrow_mut().collate(collation(&locale)?);
+            Ok(())
+        });
+        if self.record("failed to switch locale", collated).is_none() {
+            return false;
+        }
+
+        self.locale = locale;
+        true
+    }
+
+    /// Archives the campaign of a save slot, as the GM, so it is no longer opened by default. Returns whether it was
+    /// archived.
+    #[func]
+    #[expect(clippy::needless_pass_by_value, reason = "Godot passes arguments to functions by value")]
+    fn archive_campaign(&mut self, id: GString) -> bool {
+        let archived = Uuid::parse_str(&id.to_string())
+            .map_err(Into::into)
+            .and_then(|id| self.forge()?.borrow_mut().run(async |campaign| campaign.archive(Actor::Gm, id).await));
+
+        self.record("failed to archive campaign", archived).is_some()
+    }
+}
+
+impl DarkForge {
+    fn start(&mut self) -> anyhow::Result<()> {
+        let packs = pack::packs(&self.packs)?;
+        let slow = u64::try_from(self.slow_query_ms).ok().map(Duration::from_millis);
+        let forge = Rc::new(RefCell::new(Forge::start(save::database(&self.db), &packs, slow)?));
+        forge.borrow_mut().collate(collation(&self.locale)?);
+
+        self.rolls = Some(RollService::attached(&forge));
+        self.clocks = Some(ClockService::attached(&forge));
+        self.counters = Some(CounterService::attached(&forge));
+        self.prompts = Some(ActionPrompts::attached(&forge));
+        self.characters = Some(CharacterSelection::attached(&forge));
+        self.setup = Some(SetupService::attached(&forge));
+        self.stats = Some(StatsService::attached(&forge));
+        self.forge = Some(forge);
+
+        Ok(())
+    }
+
+    /// Derives the key of the database from the passphrase and hands it to the forge.
+    #[cfg(feature = "encryption")]
+    fn unlock_with(&self, passphrase: &str) -> anyhow::Result<()> {
+        let key = Key::unlock(passphrase, save::database(&self.db).with_extension("key"))?;
+        let kinds = self.encrypted_kinds.as_slice().iter().map(ToString::to_string).collect();
+
+        self.forge()?.borrow_mut().unlock(key, kinds)
+    }
+
+    /// Fails, as the extension was built without encryption.
+    #[cfg(not(feature = "encryption"))]
+    #[expect(clippy::unused_self, reason = "Mirrors the extension built with encryption")]
+    fn unlock_with(&self, _passphrase: &str) -> anyhow::Result<()> {
+        anyhow::bail!("Dark Forge was built without the encryption feature")
+    }
+
+    /// Returns the forge of the singleton for nodes of the scene tree to share, once started.
+    pub(crate) fn shared(&self) -> Option<Shared> {
+        self.forge.clone()
+    }
+
+    /// Returns the forge of the singleton, once started.
+    fn forge(&self) -> anyhow::Result<Shared> {
+        self.forge.clone().context("Dark Forge has not started")
+    }
+
+    /// Closes the campaign, once its background tasks are flushed, and drops the services.
+    fn shutdown(&mut self) {
+        self.rolls = None;
+        self.clocks = None;
+        self.counters = None;
+        self.prompts = None;
+        self.characters = None;
+        self.setup = None;
+        self.stats = None;
+        let Some(forge) = self.forge.take() else {
+            return;
+        };
+
+        let closed = forge.borrow_mut().close();
+        self.record("failed to close campaign", closed);
+    }
+}
+
+impl Reporter for DarkForge {
+    fn last_error_mut(&mut self) -> &mut Dictionary {
+        &mut self.last_error
+    }
+}
+
+fn mismatch_to_dictionary(mismatch: &ContentMismatch) -> Dictionary {
+    let (kind, categories) = match mismatch {
+        ContentMismatch::Missing { .. } => ("missing", &[][..]),
+        ContentMismatch::Modded { categories, .. } => ("modded", categories.as_slice()),
+        ContentMismatch::Tampered { categories, .. } => ("tampered", categories.as_slice()),
+        ContentMismatch::Unexpected { .. } => ("unexpected", &[][..]),
+    };
+
+    dict! {
+        "kind": kind,
+        "pack": mismatch.pack(),
+        "categories": categories.iter().map(GString::from).collect::<PackedStringArray>(),
+    }
+}
+
+/// Returns the collation of the locale, or the root collation if none is given.
+fn collation(locale: &GString) -> anyhow::Result<Collation> {
+    if locale.is_empty() {
+        return Ok(Collation::root());
+    }
+
+    Ok(Collation::new(&locale.to_string())?)
+}
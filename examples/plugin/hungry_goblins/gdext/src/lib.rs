@@ -10,12 +10,48 @@
  * You should have received a copy of the GNU Affero General Public License along with this program.
  * If not, see https://www.gnu.org/licenses/.
  */
+#![allow(clippy::result_large_err, reason = "Triggered by code generated by the godot macros")]
 
 use godot::prelude::*;
 
 struct HungryGoblins;
 
+#[cfg(feature = "store")]
 mod character;
+#[cfg(feature = "store")]
+mod clock;
+#[cfg(feature = "store")]
+mod coalesce;
+#[cfg(feature = "store")]
+mod content;
+#[cfg(feature = "store")]
+mod counter;
+mod error;
+#[cfg(feature = "store")]
+mod forge;
+#[cfg(feature = "store")]
+mod map;
+mod pack;
+#[cfg(feature = "store")]
+mod prompt;
+mod random;
+#[cfg(feature = "store")]
+mod review;
+mod roll;
+mod rules;
+mod save;
+#[cfg(feature = "store")]
+mod schema;
+#[cfg(feature = "store")]
+mod selection;
+#[cfg(feature = "store")]
+mod setup;
+#[cfg(feature = "store")]
+mod sheet;
+#[cfg(feature = "store")]
+mod stats;
+#[cfg(feature = "store")]
+mod task;
 
 #[gdextension]
 unsafe impl ExtensionLibrary for HungryGoblins {}
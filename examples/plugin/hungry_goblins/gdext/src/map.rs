@@ -0,0 +1,294 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+use darkforge::{
+    campaign::{Campaign, Result},
+    faction::{Hold, Status},
+    map::{FactionMap, MapNode, Point},
+    worldgen::Relation,
+};
+use godot::{
+    classes::{Control, IControl, InputEvent, InputEventMouseButton},
+    global::MouseButton,
+    prelude::*,
+};
+
+use crate::{
+    error::Reporter,
+    forge::{self, DarkForge, Shared},
+    random,
+};
+
+/// The map of the factions of a campaign database and the relationships between them, for the GM's faction screen.
+///
+/// The graph draws itself: factions as circles sized by tier and colored by the crew's standing with them, allies
+/// linked in green and enemies in red. It shares the campaign of the Dark Forge singleton if there is one, and refreshes
+/// every `refresh_seconds` while it is in the tree, keeping factions in place as the campaign changes.
+#[derive(GodotClass)]
+#[class(base=Control)]
+pub struct FactionGraph {
+    base: Base<Control>,
+    /// Path to the campaign database, `user://campaign.db` if empty. Ignored when the singleton is found.
+    #[export]
+    db: GString,
+    /// Path to the Dark Forge singleton, whose campaign the graph shares.
+    #[export]
+    forge_path: NodePath,
+    /// How often the graph checks the campaign for changes, in seconds, or never if not positive.
+    #[export]
+    refresh_seconds: f64,
+    /// The error of the last call, see `DarkForgeError`, or an empty dictionary if it succeeded.
+    #[var(get)]
+    last_error: Dictionary,
+    /// The map as read from the campaign, before it is laid out.
+    read: FactionMap,
+    map: FactionMap,
+    elapsed: f64,
+    forge: Option<Shared>,
+}
+
+#[godot_api]
+impl IControl for FactionGraph {
+    fn init(base: Base<Control>) -> Self {
+        Self {
+            base,
+            db: GString::new(),
+            forge_path: NodePath::from("/root/Forge"),
+            refresh_seconds: 2.0,
+            last_error: Dictionary::new(),
+            read: FactionMap::default(),
+            map: FactionMap::default(),
+            elapsed: 0.0,
+            forge: None,
+        }
+    }
+
+    fn ready(&mut self) {
+        let path = self.forge_path.clone();
+        self.forge = self
+            .base()
+            .get_node_or_null(&path)
+            .and_then(|node| node.try_cast::<DarkForge>().ok())
+            .and_then(|forge| forge.bind().shared());
+        self.reload();
+    }
+
+    fn process(&mut self, delta: f64) {
+        if self.refresh_seconds <= 0.0 {
+            return;
+        }
+        self.elapsed += delta;
+        if self.elapsed >= self.refresh_seconds {
+            self.elapsed = 0.0;
+            self.reload();
+        }
+    }
+
+    fn draw(&mut self) {
+        let size = self.base().get_size();
+
+        let edges = self
+            .map
+            .edges
+            .iter()
+            .filter_map(|edge| {
+                let from = self.map.node(edge.from)?.position;
+                let to = self.map.node(edge.to)?.position;
+                Some((place(from, size), place(to, size), relation_color(edge.relation)))
+            })
+            .collect::<Vec<_>>();
+        let nodes = self
+            .map
+            .nodes
+            .iter()
+            .map(|node| {
+                (
+                    place(node.position, size),
+                    radius(node),
+                    status_color(node),
+                    node.faction.descriptor().label().to_owned(),
+                )
+            })
+            .collect::<Vec<_>>();
+        let font = self.base().get_theme_default_font();
+
+        let mut base = self.base_mut();
+        for (from, to, color) in edges {
+            base.draw_line_ex(from, to, color).width(2.0).done();
+        }
+        for (center, radius, color, label) in nodes {
+            base.draw_circle(center, radius, color);
+            if let Some(font) = &font {
+                base.draw_string(font, center + Vector2::new(radius + 4.0, 4.0), &label);
+            }
+        }
+    }
+
+    fn gui_input(&mut self, event: Gd<InputEvent>) {
+        let Ok(click) = event.try_cast::<InputEventMouseButton>() else {
+            return;
+        };
+        if !click.is_pressed() || click.get_button_index() != MouseButton::LEFT {
+            return;
+        }
+
+        let faction = self.faction_at(click.get_position());
+        if !faction.is_empty() {
+            self.base_mut().emit_signal("faction_selected", &[faction.to_variant()]);
+        }
+    }
+}
+
+#[godot_api]
+impl FactionGraph {
+    /// Emitted whenever the factions or their relationships change, once the graph is laid out again.
+    #[signal]
+    fn map_changed();
+
+    /// Emitted when a faction is clicked, with its id.
+    #[signal]
+    fn faction_selected(id: GString);
+
+    /// Emitted when a call fails, with the error as recorded in `last_error`.
+    #[signal]
+    fn failed(error: Dictionary);
+
+    /// Reads the factions and their relationships, laying the graph out again if they changed. Returns false if they
+    /// cannot be read.
+    #[func]
+    fn reload(&mut self) -> bool {
+        let Some(read) = self.run(async |campaign| campaign.faction_map().await) else {
+            return false;
+        };
+        if read == self.read {
+            return true;
+        }
+
+        let mut map = read.clone();
+        map.layout(Some(&self.map), &mut random::rng());
+        self.read = read;
+        self.map = map;
+        self.base_mut().queue_redraw();
+        self.base_mut().emit_signal("map_changed", &[]);
+
+        true
+    }
+
+    /// Returns the factions on the graph as dictionaries with an `id`, a `label`, a `tier`, a `hold`, the crew's
+    /// `status` with them and their `position` within the graph.
+    #[func]
+    fn factions(&self) -> Array<Dictionary> {
+        let size = self.base().get_size();
+
+        self.map
+            .nodes
+            .iter()
+            .map(|node| {
+                dict! {
+                    "id": node.entity.to_string(),
+                    "label": node.faction.descriptor().label(),
+                    "tier": i64::from(node.faction.tier()),
+                    "hold": hold_name(node.faction.hold()),
+                    "status": i64::from(node.status.value()),
+                    "position": place(node.position, size),
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the relationships on the graph as dictionaries with the ids of the factions they link `from` and `to`
+    /// and the `relation`, `ally` or `enemy`.
+    #[func]
+    fn relationships(&self) -> Array<Dictionary> {
+        self.map
+            .edges
+            .iter()
+            .map(|edge| {
+                dict! {
+                    "from": edge.from.to_string(),
+                    "to": edge.to.to_string(),
+                    "relation": relation_name(edge.relation),
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the id of the faction drawn at the position within the graph, or an empty string if there is none.
+    #[func]
+    fn faction_at(&self, position: Vector2) -> GString {
+        let size = self.base().get_size();
+
+        self.map
+            .nodes
+            .iter()
+            .rev()
+            .find(|node| place(node.position, size).distance_to(position) <= radius(node))
+            .map(|node| node.entity.to_string().into())
+            .unwrap_or_default()
+    }
+}
+
+impl FactionGraph {
+    /// Opens the campaign and runs `f`, reporting any error.
+    fn run<T>(&mut self, f: impl AsyncFnOnce(&mut Campaign) -> Result<T>) -> Option<T> {
+        let result = forge::run(self.forge.as_ref(), &self.db, f);
+        self.record("failed to read faction map", result)
+    }
+}
+
+impl Reporter for FactionGraph {
+    fn last_error_mut(&mut self) -> &mut Dictionary {
+        &mut self.last_error
+    }
+}
+
+/// Returns where the point of the map is drawn within a graph of the given size.
+#[expect(clippy::cast_possible_truncation, reason = "Points of the map are between 0 and 1")]
+fn place(point: Point, size: Vector2) -> Vector2 {
+    Vector2::new(point.x as f32 * size.x, point.y as f32 * size.y)
+}
+
+/// Returns the radius of the circle drawn for the faction, larger for higher tiers.
+fn radius(node: &MapNode) -> f32 {
+    8.0 + 4.0 * f32::from(node.faction.tier())
+}
+
+/// Returns the color of the faction, from red at war with the crew through grey to green for allies.
+fn status_color(node: &MapNode) -> Color {
+    let standing = f64::from(node.status.value()) / f64::from(Status::ALLY.value());
+    if standing < 0.0 {
+        Color::from_rgb(0.6, 0.6, 0.6).lerp(Color::from_rgb(0.85, 0.2, 0.2), -standing)
+    } else {
+        Color::from_rgb(0.6, 0.6, 0.6).lerp(Color::from_rgb(0.2, 0.75, 0.3), standing)
+    }
+}
+
+fn relation_color(relation: Relation) -> Color {
+    match relation {
+        Relation::Ally => Color::from_rgba(0.2, 0.75, 0.3, 0.8),
+        Relation::Enemy => Color::from_rgba(0.85, 0.2, 0.2, 0.8),
+    }
+}
+
+fn hold_name(hold: Hold) -> &'static str {
+    match hold {
+        Hold::Weak => "weak",
+        Hold::Strong => "strong",
+    }
+}
+
+fn relation_name(relation: Relation) -> &'static str {
+    match relation {
+        Relation::Ally => "ally",
+        Relation::Enemy => "enemy",
+    }
+}
@@ -0,0 +1,321 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+use std::{collections::BTreeMap, fs, path::Path};
+
+use anyhow::Context;
+use darkforge_data::pack::Pack;
+#[cfg(feature = "store")]
+use darkforge_data::{
+    pack::EntryChange,
+    store::{
+        Content, Migrator,
+        sql::sqlite::{MIGRATIONS, SqliteError, SqliteStore},
+    },
+};
+use godot::{
+    classes::{EditorImportPlugin, EditorPlugin, IEditorImportPlugin, IEditorPlugin, IResource, ResourceSaver},
+    global::Error,
+    prelude::*,
+};
+
+#[cfg(feature = "store")]
+use crate::error::Reporter;
+use crate::{random, save};
+
+/// A content pack imported into the project.
+///
+/// Packs are recognized by a `.dfpack` file placed at the root of their directory, whose contents are ignored. The
+/// pack's files are embedded in the imported resource so exported games can register it without reading the directory.
+#[derive(GodotClass)]
+#[class(tool, base=Resource)]
+pub struct ContentPack {
+    base: Base<Resource>,
+    /// Name of the pack, after its directory.
+    #[export]
+    pack_name: GString,
+    /// Contents of the pack's files, by category.
+    #[export]
+    files: Dictionary,
+}
+
+#[godot_api]
+impl IResource for ContentPack {
+    fn init(base: Base<Resource>) -> Self {
+        Self {
+            base,
+            pack_name: GString::new(),
+            files: Dictionary::new(),
+        }
+    }
+}
+
+#[godot_api]
+impl ContentPack {
+    /// Returns the SHA-256 digest of the pack's entries in hexadecimal, to list in the `shipped_content` of Dark Forge
+    /// when the game is exported. Returns an empty string if the pack does not parse.
+    #[func]
+    fn digest(&self) -> GString {
+        self.pack().map(|pack| pack.hash().digest.into()).unwrap_or_default()
+    }
+}
+
+impl ContentPack {
+    /// Parses the embedded pack.
+    fn pack(&self) -> anyhow::Result<Pack> {
+        let files = self
+            .files
+            .iter_shared()
+            .map(|(category, source)| (category.to_string(), source.to_string()));
+
+        Ok(Pack::parse(self.pack_name.to_string(), files)?)
+    }
+}
+
+/// Imports the content pack directories of the project as [`ContentPack`] resources, failing the import of packs that
+/// do not parse so mistakes show up in the editor rather than in game.
+#[derive(GodotClass)]
+#[class(tool, base=EditorImportPlugin)]
+pub struct PackImporter {
+    base: Base<EditorImportPlugin>,
+}
+
+#[godot_api]
+impl IEditorImportPlugin for PackImporter {
+    fn init(base: Base<EditorImportPlugin>) -> Self {
+        Self { base }
+    }
+
+    fn get_importer_name(&self) -> GString {
+        "darkforge.content_pack".into()
+    }
+
+    fn get_visible_name(&self) -> GString {
+        "Content Pack".into()
+    }
+
+    fn get_preset_count(&self) -> i32 {
+        1
+    }
+
+    fn get_preset_name(&self, _preset_index: i32) -> GString {
+        "Default".into()
+    }
+
+    fn get_recognized_extensions(&self) -> PackedStringArray {
+        PackedStringArray::from(&["dfpack".into()])
+    }
+
+    fn get_import_options(&self, _path: GString, _preset_index: i32) -> Array<Dictionary> {
+        Array::new()
+    }
+
+    fn get_save_extension(&self) -> GString {
+        "res".into()
+    }
+
+    fn get_resource_type(&self) -> GString {
+        "Resource".into()
+    }
+
+    fn get_priority(&self) -> f32 {
+        1.0
+    }
+
+    fn get_import_order(&self) -> i32 {
+        0
+    }
+
+    fn get_option_visibility(&self, _path: GString, _option_name: StringName, _options: Dictionary) -> bool {
+        true
+    }
+
+    fn import(
+        &self, source_file: GString, save_path: GString, _options: Dictionary, _platform_variants: Array<GString>, _gen_files: Array<GString>,
+    ) -> Error {
+        let pack = match import_pack(&save::globalize(&source_file)) {
+            Ok(pack) => pack,
+            Err(err) => {
+                godot_error!("failed to import content pack {source_file}: {err:#}");
+                return Error::ERR_PARSE_ERROR;
+            }
+        };
+
+        let path = format!("{save_path}.{}", self.get_save_extension());
+        ResourceSaver::singleton().save_ex(&pack).path(&path).done()
+    }
+}
+
+/// Adds the [`PackImporter`] to the editor while the extension is loaded.
+#[derive(GodotClass)]
+#[class(tool, base=EditorPlugin)]
+pub struct PackImportPlugin {
+    base: Base<EditorPlugin>,
+    importer: Option<Gd<PackImporter>>,
+}
+
+#[godot_api]
+impl IEditorPlugin for PackImportPlugin {
+    fn init(base: Base<EditorPlugin>) -> Self {
+        Self { base, importer: None }
+    }
+
+    fn enter_tree(&mut self) {
+        random::register_settings();
+        let importer = PackImporter::new_gd();
+        self.base_mut().add_import_plugin(&importer);
+        self.importer = Some(importer);
+    }
+
+    fn exit_tree(&mut self) {
+        if let Some(importer) = self.importer.take() {
+            self.base_mut().remove_import_plugin(&importer);
+        }
+    }
+}
+
+/// Registers content packs with the static store of the campaign database when the game starts.
+///
+/// Packs are dropped from the `FileSystem` dock onto the `packs` property. Entries that changed since the last start are
+/// applied, and entries removed from a pack are removed from the store.
+#[cfg(feature = "store")]
+#[derive(GodotClass)]
+#[class(base=Node)]
+pub struct PackRegistry {
+    base: Base<Node>,
+    /// Path to the campaign database, `user://campaign.db` if empty.
+    #[export]
+    db: GString,
+    /// The packs to register.
+    #[export]
+    packs: Array<Gd<ContentPack>>,
+    /// The error of the last call, see `DarkForgeError`, or an empty dictionary if it succeeded.
+    #[var(get)]
+    last_error: Dictionary,
+}
+
+#[cfg(feature = "store")]
+#[godot_api]
+impl INode for PackRegistry {
+    fn init(base: Base<Node>) -> Self {
+        Self {
+            base,
+            db: GString::new(),
+            packs: Array::new(),
+            last_error: Dictionary::new(),
+        }
+    }
+
+    fn ready(&mut self) {
+        self.register();
+    }
+}
+
+#[cfg(feature = "store")]
+#[godot_api]
+impl PackRegistry {
+    /// Emitted once the packs are registered, with the number of entries that changed.
+    #[signal]
+    fn packs_registered(changes: i64);
+
+    /// Emitted when a call fails, with the error as recorded in `last_error`.
+    #[signal]
+    fn failed(error: Dictionary);
+
+    /// Registers the packs with the campaign database. Returns the number of entries that changed, or -1 if a pack
+    /// cannot be registered.
+    #[func]
+    fn register(&mut self) -> i64 {
+        let changes = self.try_register();
+        let Some(changes) = self.record("failed to register content packs", changes) else {
+            return -1;
+        };
+
+        let count = i64::try_from(changes.len()).unwrap_or(i64::MAX);
+        self.base_mut().emit_signal("packs_registered", &[count.to_variant()]);
+
+        count
+    }
+}
+
+#[cfg(feature = "store")]
+impl PackRegistry {
+    fn try_register(&self) -> anyhow::Result<Vec<EntryChange>> {
+        let packs = packs(&self.packs)?;
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+
+        Ok(runtime.block_on(async {
+            let mut store = SqliteStore::open(save::database(&self.db)).await?;
+            store.apply(MIGRATIONS).await?;
+            register(&mut store, &packs).await
+        })?)
+    }
+}
+
+#[cfg(feature = "store")]
+impl Reporter for PackRegistry {
+    fn last_error_mut(&mut self) -> &mut Dictionary {
+        &mut self.last_error
+    }
+}
+
+/// Parses the embedded packs of the resources.
+pub(crate) fn packs(resources: &Array<Gd<ContentPack>>) -> anyhow::Result<Vec<Pack>> {
+    resources.iter_shared().map(|pack| pack.bind().pack()).collect()
+}
+
+/// Returns the names of the embedded packs, in order.
+pub(crate) fn names(resources: &Array<Gd<ContentPack>>) -> Vec<String> {
+    resources.iter_shared().map(|pack| pack.bind().pack_name.to_string()).collect()
+}
+
+#[cfg(feature = "store")]
+/// Registers the packs with the static store, returning the entries that changed since they were last registered.
+pub(crate) async fn register(store: &mut SqliteStore, packs: &[Pack]) -> Result<Vec<EntryChange>, SqliteError> {
+    let mut changes = Vec::new();
+    for pack in packs {
+        changes.extend(store.reload_pack(pack, |_| Ok(Vec::new())).await?);
+    }
+
+    Ok(changes)
+}
+
+/// Reads the pack whose `.dfpack` file is at the given path, and checks that it parses.
+fn import_pack(dfpack: &Path) -> anyhow::Result<Gd<ContentPack>> {
+    let dir = dfpack.parent().context("content pack has no directory")?;
+    let name = dir
+        .file_name()
+        .context("content pack directory has no name")?
+        .to_string_lossy()
+        .into_owned();
+
+    let mut files = BTreeMap::new();
+    for file in fs::read_dir(dir)? {
+        let file = file?.path();
+        let (Some(category), Some("json" | "jsonc")) = (file.file_stem(), file.extension().and_then(|e| e.to_str())) else {
+            continue;
+        };
+        files.insert(category.to_string_lossy().into_owned(), fs::read_to_string(&file)?);
+    }
+    Pack::parse(&name, &files)?;
+
+    let mut pack = ContentPack::new_gd();
+    {
+        let mut pack = pack.bind_mut();
+        pack.pack_name = name.into();
+        for (category, source) in &files {
+            pack.files.set(category.as_str(), source.as_str());
+        }
+    }
+
+    Ok(pack)
+}
@@ -0,0 +1,452 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+use std::{
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use darkforge::{
+    action::{Consequence, Delta, Modifier},
+    campaign::Campaign,
+    consequence::HarmConsequence,
+    context::Position,
+    effect::{Approach, Effect, Factor, Side, assess_effect},
+    permission::Actor,
+    prompt::{ActionFlow, Answer, Prompt, PromptError, PromptKind, PromptTimeouts, Timeout},
+};
+use godot::prelude::*;
+use uuid::Uuid;
+
+use crate::{
+    error::{InvalidArgument, Reporter},
+    forge::{self, Shared},
+    random,
+};
+
+/// Plays actions one decision at a time, so the roll prompts can be answered with a controller or the keyboard.
+///
+/// Each prompt is a dictionary with its `kind`, either `push`, `bargain` or `resist`, a `text` describing the bargain
+/// or the consequence, and its `options` in the order to present them. Options are dictionaries with the `answer`,
+/// either `accept`, `decline` or `resist`, and for resistance the `label` and `rating` of the attribute.
+///
+/// Tables playing apart can give each kind of prompt a timeout with `set_timeouts`, then call `time_out` regularly,
+/// e.g. from a `Timer`, so the prompt is answered for a player who does not answer in time.
+#[derive(GodotClass)]
+#[class(base=RefCounted)]
+pub struct ActionPrompts {
+    base: Base<RefCounted>,
+    /// Path to the campaign database, `user://campaign.db` if empty.
+    #[var]
+    db: GString,
+    /// Id of the player acting, or empty for the GM.
+    #[var]
+    player: GString,
+    /// The error of the last call, see `DarkForgeError`, or an empty dictionary if it succeeded.
+    #[var(get)]
+    last_error: Dictionary,
+    forge: Option<Shared>,
+    flow: Option<ActionFlow>,
+    /// When the pending prompt was emitted.
+    prompted_at: Option<Instant>,
+}
+
+#[godot_api]
+impl IRefCounted for ActionPrompts {
+    fn init(base: Base<RefCounted>) -> Self {
+        Self {
+            base,
+            db: GString::new(),
+            player: GString::new(),
+            last_error: Dictionary::new(),
+            forge: None,
+            flow: None,
+            prompted_at: None,
+        }
+    }
+}
+
+#[godot_api]
+impl ActionPrompts {
+    /// Emitted whenever a decision is pending, with the prompt.
+    #[signal]
+    fn prompted(prompt: Dictionary);
+
+    /// Emitted once the action is resolved, with the changes applied.
+    #[signal]
+    fn resolved(delta: Dictionary);
+
+    /// Emitted when a call fails, with the error as recorded in `last_error`.
+    #[signal]
+    fn failed(error: Dictionary);
+
+    /// Starts the action, given as a dictionary with the `character`, a `label`, the `rating` to roll, its `position`
+    /// and the `effect` of the approach, risky and standard if empty, the `bargain` offered if any and its
+    /// `consequences`. The effect is assessed against the opposition from the `actor` and `target` sides, dictionaries
+    /// with a `quality` and a `scale`, and the `potency` of the approach, either `advantage`, `disadvantage` or empty. Consequences are
+    /// dictionaries with a `kind`, either `harm` with a `level` and a `description`, `complication` with a `clock` and its
+    /// `ticks`, or `reduced_effect`. Returns the first prompt, or an empty dictionary if the action cannot start.
+    #[func]
+    #[expect(clippy::needless_pass_by_value, reason = "Godot passes arguments to functions by value")]
+    fn begin(&mut self, action: Dictionary) -> Dictionary {
+        let flow = flow_from_dictionary(&action);
+        let Some(flow) = self.record("failed to begin action", flow) else {
+            return Dictionary::new();
+        };
+
+        self.flow = Some(flow);
+        self.prompt()
+    }
+
+    /// Returns the pending prompt, or an empty dictionary if no decision is pending.
+    #[func]
+    fn pending(&self) -> Dictionary {
+        self.flow
+            .as_ref()
+            .and_then(ActionFlow::pending)
+            .map(prompt_to_dictionary)
+            .unwrap_or_default()
+    }
+
+    /// Answers the pending prompt with the option at the index, then plays the action as far as it can. Returns the
+    /// next prompt, or the changes applied as a dictionary with the `outcome`, the `effect`, the character's `stress`,
+    /// whether they suffered `trauma`, the levels of `harm` marked and the `breakdown` of the dice added to and taken
+    /// from the pool once the action is resolved. Returns an empty
+    /// dictionary if the option does not answer the prompt or the action cannot be played.
+    #[func]
+    fn answer(&mut self, option: i64) -> Dictionary {
+        let Some(mut flow) = self.flow.take() else {
+            self.record::<()>("failed to play action", Err(InvalidArgument("no action is being played".into()).into()));
+            return Dictionary::new();
+        };
+
+        let played = self.play(&mut flow, option);
+        self.flow = Some(flow);
+        self.played(played)
+    }
+
+    /// Answers the pending prompt with its default answer if it has waited for at least the timeout of its kind, then
+    /// plays the action on as `answer` does. Returns what `answer` does, or an empty dictionary if the prompt has not
+    /// timed out.
+    #[func]
+    fn time_out(&mut self) -> Dictionary {
+        let (Some(mut flow), Some(prompted_at)) = (self.flow.take(), self.prompted_at) else {
+            return Dictionary::new();
+        };
+
+        let answered = self.timeouts().and_then(|timeouts| Ok(flow.time_out(&timeouts, prompted_at.elapsed())?));
+        let played = match answered {
+            Ok(Some(_)) => self.advance(&mut flow).map(Some),
+            Ok(None) => Ok(None),
+            Err(err) => Err(err),
+        };
+        self.flow = Some(flow);
+        match self.record("failed to time out prompt", played) {
+            Some(Some(played)) => self.played(Ok(played)),
+            _ => Dictionary::new(),
+        }
+    }
+
+    /// Returns the seconds left to answer the pending prompt before it times out, or -1 if it waits for an answer.
+    #[func]
+    fn time_left(&mut self) -> f64 {
+        let timeout = self
+            .timeouts()
+            .map(|timeouts| self.flow.as_ref().and_then(|flow| flow.timeout(&timeouts)));
+        match (self.record("failed to read prompt timeouts", timeout).flatten(), self.prompted_at) {
+            (Some(timeout), Some(prompted_at)) => timeout.after.saturating_sub(prompted_at.elapsed()).as_secs_f64(),
+            _ => -1.0,
+        }
+    }
+
+    /// Returns the timeouts of the prompts of the campaign as a dictionary by kind of prompt, of dictionaries with the
+    /// seconds to wait `after` and the `default` answer, either `accept` or `decline`.
+    #[func]
+    fn timeouts_of_campaign(&mut self) -> Dictionary {
+        let timeouts = self.timeouts();
+        self.record("failed to read prompt timeouts", timeouts)
+            .map(|timeouts| {
+                PromptKind::ALL
+                    .into_iter()
+                    .filter_map(|kind| {
+                        let timeout = timeouts.get(kind)?;
+                        let default = if timeout.default == Answer::Accept { "accept" } else { "decline" };
+                        Some((kind_name(kind), dict! { "after": timeout.after.as_secs_f64(), "default": default }))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Replaces the timeouts of the prompts of the campaign, which only the GM may do, given as `timeouts_of_campaign` returns them.
+    /// Given `remote` instead, every prompt waits a minute before the character holds back and suffers consequences in
+    /// full. Returns whether the timeouts were set.
+    #[func]
+    #[expect(clippy::needless_pass_by_value, reason = "Godot passes arguments to functions by value")]
+    fn set_timeouts(&mut self, timeouts: Variant) -> bool {
+        let timeouts = if timeouts.try_to::<GString>().is_ok_and(|remote| remote == "remote".into()) {
+            Ok(PromptTimeouts::remote())
+        } else {
+            timeouts_from_dictionary(&timeouts.try_to::<Dictionary>().unwrap_or_default())
+        };
+        let set = timeouts.and_then(|timeouts| {
+            let actor = self.actor()?;
+            forge::run(self.forge.as_ref(), &self.db, async |campaign: &mut Campaign| {
+                campaign.set_prompt_timeouts(actor, &timeouts).await
+            })
+        });
+
+        self.record("failed to set prompt timeouts", set).is_some()
+    }
+
+    /// Abandons the action being played, if any.
+    #[func]
+    fn cancel(&mut self) {
+        self.flow = None;
+        self.prompted_at = None;
+    }
+}
+
+impl ActionPrompts {
+    /// Creates a service for the actions of the campaign of the singleton.
+    pub(crate) fn attached(forge: &Shared) -> Gd<ActionPrompts> {
+        Gd::from_init_fn(|base| Self {
+            base,
+            db: GString::new(),
+            player: GString::new(),
+            last_error: Dictionary::new(),
+            forge: Some(Rc::clone(forge)),
+            flow: None,
+            prompted_at: None,
+        })
+    }
+
+    /// Emits the pending prompt, if any, and returns it.
+    fn prompt(&mut self) -> Dictionary {
+        let prompt = self.pending();
+        self.prompted_at = None;
+        if !prompt.is_empty() {
+            self.prompted_at = Some(Instant::now());
+            self.base_mut().emit_signal("prompted", &[prompt.to_variant()]);
+        }
+
+        prompt
+    }
+
+    /// Emits the changes applied once the action is resolved, or else the next prompt, and returns them.
+    fn played(&mut self, played: anyhow::Result<Option<Delta>>) -> Dictionary {
+        match self.record("failed to play action", played) {
+            Some(Some(delta)) => {
+                self.prompted_at = None;
+                let breakdown = self.flow.as_ref().and_then(ActionFlow::roll).map(|roll| roll.modifiers.as_slice());
+                let delta = delta_to_dictionary(&delta, breakdown.unwrap_or_default());
+                self.base_mut().emit_signal("resolved", &[delta.to_variant()]);
+                delta
+            }
+            Some(None) => self.prompt(),
+            None => Dictionary::new(),
+        }
+    }
+
+    /// Returns the GM, or the player acting if one is set.
+    fn actor(&self) -> anyhow::Result<Actor> {
+        if self.player.is_empty() {
+            return Ok(Actor::Gm);
+        }
+
+        Ok(Actor::Player(Uuid::parse_str(&self.player.to_string())?))
+    }
+
+    fn timeouts(&self) -> anyhow::Result<PromptTimeouts> {
+        forge::run(self.forge.as_ref(), &self.db, async |campaign: &mut Campaign| {
+            campaign.prompt_timeouts().await
+        })
+    }
+
+    fn play(&self, flow: &mut ActionFlow, option: i64) -> anyhow::Result<Option<Delta>> {
+        let prompt = flow.pending().ok_or(PromptError::Settled)?;
+        let answer = usize::try_from(option)
+            .ok()
+            .and_then(|index| prompt.answers().get(index).copied())
+            .ok_or_else(|| InvalidArgument(format!("option {option} does not answer the prompt")))?;
+        flow.answer(answer)?;
+
+        self.advance(flow)
+    }
+
+    /// Plays the action on after a prompt was answered.
+    fn advance(&self, flow: &mut ActionFlow) -> anyhow::Result<Option<Delta>> {
+        let actor = self.actor()?;
+        forge::run(self.forge.as_ref(), &self.db, async |campaign: &mut Campaign| {
+            campaign.play_action(actor, flow, &random::d6()).await
+        })
+    }
+}
+
+impl Reporter for ActionPrompts {
+    fn last_error_mut(&mut self) -> &mut Dictionary {
+        &mut self.last_error
+    }
+}
+
+fn flow_from_dictionary(action: &Dictionary) -> anyhow::Result<ActionFlow> {
+    let field = |key: &str| action.get(key).unwrap_or_default();
+    let text = |key: &str| field(key).try_to::<GString>().unwrap_or_default().to_string();
+    let rating = u8::try_from(field("rating").try_to::<i64>().unwrap_or_default().clamp(0, u8::MAX.into()))?;
+
+    let mut flow = ActionFlow::new(Uuid::parse_str(&text("character"))?, text("label"), rating)
+        .position(match text("position").as_str() {
+            "controlled" => Position::Controlled,
+            "" | "risky" => Position::Risky,
+            "desperate" => Position::Desperate,
+            other => return Err(InvalidArgument(format!("unknown position {other}")).into()),
+        })
+        .assessment(&assess_effect(
+            side_from_dictionary(&field("actor").try_to::<Dictionary>().unwrap_or_default())?,
+            side_from_dictionary(&field("target").try_to::<Dictionary>().unwrap_or_default())?,
+            Approach::new(match text("effect").as_str() {
+                "zero" => Effect::Zero,
+                "limited" => Effect::Limited,
+                "" | "standard" => Effect::Standard,
+                "great" => Effect::Great,
+                "extreme" => Effect::Extreme,
+                other => return Err(InvalidArgument(format!("unknown effect {other}")).into()),
+            })
+            .potency(match text("potency").as_str() {
+                "disadvantage" => Factor::Disadvantage,
+                "" | "even" => Factor::Even,
+                "advantage" => Factor::Advantage,
+                other => return Err(InvalidArgument(format!("unknown potency {other}")).into()),
+            }),
+        ));
+    let bargain = text("bargain");
+    if !bargain.is_empty() {
+        flow = flow.bargain(bargain);
+    }
+    for consequence in field("consequences").try_to::<Array<Dictionary>>().unwrap_or_default().iter_shared() {
+        flow = flow.consequence(consequence_from_dictionary(&consequence)?);
+    }
+
+    Ok(flow)
+}
+
+fn timeouts_from_dictionary(timeouts: &Dictionary) -> anyhow::Result<PromptTimeouts> {
+    let mut parsed = PromptTimeouts::default();
+    for (kind, timeout) in timeouts.iter_shared() {
+        let kind = kind.try_to::<GString>().unwrap_or_default().to_string();
+        let kind = PromptKind::ALL
+            .into_iter()
+            .find(|candidate| kind_name(*candidate) == kind)
+            .ok_or_else(|| InvalidArgument(format!("unknown prompt {kind}")))?;
+        let timeout = timeout.try_to::<Dictionary>().unwrap_or_default();
+        let field = |key: &str| timeout.get(key).unwrap_or_default();
+        let after = Duration::try_from_secs_f64(field("after").try_to::<f64>().unwrap_or_default())
+            .map_err(|err| InvalidArgument(format!("invalid timeout: {err}")))?;
+        let default = match field("default").try_to::<GString>().unwrap_or_default().to_string().as_str() {
+            "accept" => Answer::Accept,
+            "decline" => Answer::Decline,
+            other => return Err(InvalidArgument(format!("unknown default answer {other}")).into()),
+        };
+        parsed = parsed.with(kind, Timeout { after, default })?;
+    }
+
+    Ok(parsed)
+}
+
+fn side_from_dictionary(side: &Dictionary) -> anyhow::Result<Side> {
+    let field = |key: &str| {
+        u8::try_from(
+            side.get(key)
+                .unwrap_or_default()
+                .try_to::<i64>()
+                .unwrap_or_default()
+                .clamp(0, u8::MAX.into()),
+        )
+    };
+
+    Ok(Side::new(field("quality")?, field("scale")?))
+}
+
+fn consequence_from_dictionary(consequence: &Dictionary) -> anyhow::Result<Consequence> {
+    let field = |key: &str| consequence.get(key).unwrap_or_default();
+    let count = |key: &str| u8::try_from(field(key).try_to::<i64>().unwrap_or_default().clamp(0, u8::MAX.into()));
+
+    Ok(match field("kind").try_to::<GString>().unwrap_or_default().to_string().as_str() {
+        "harm" => Consequence::Harm(HarmConsequence {
+            level: count("level")?,
+            description: field("description").try_to::<GString>().unwrap_or_default().to_string(),
+        }),
+        "complication" => Consequence::Complication {
+            clock: Uuid::parse_str(&field("clock").try_to::<GString>().unwrap_or_default().to_string())?,
+            ticks: count("ticks")?,
+        },
+        "reduced_effect" => Consequence::ReducedEffect,
+        other => return Err(InvalidArgument(format!("unknown consequence {other}")).into()),
+    })
+}
+
+fn prompt_to_dictionary(prompt: &Prompt) -> Dictionary {
+    let (text, attributes) = match prompt {
+        Prompt::Push => (String::new(), &[][..]),
+        Prompt::Bargain { offer } => (offer.clone(), &[][..]),
+        Prompt::Wounded { harm } | Prompt::NeedHelp { harm } => (harm.clone(), &[][..]),
+        Prompt::Resist { consequence, attributes } => (consequence_text(consequence), attributes.as_slice()),
+    };
+    let mut resistances = attributes.iter();
+    let options = prompt
+        .answers()
+        .iter()
+        .map(|answer| match answer {
+            Answer::Accept => dict! { "answer": "accept" },
+            Answer::Decline => dict! { "answer": "decline" },
+            Answer::Resist(rating) => {
+                let label = resistances.next().map(|attribute| attribute.label.clone()).unwrap_or_default();
+                dict! { "answer": "resist", "label": label, "rating": i64::from(*rating) }
+            }
+        })
+        .collect::<Array<Dictionary>>();
+
+    dict! {
+        "kind": kind_name(prompt.kind()),
+        "text": text,
+        "options": options,
+    }
+}
+
+fn kind_name(kind: PromptKind) -> &'static str {
+    match kind {
+        PromptKind::Push => "push",
+        PromptKind::Bargain => "bargain",
+        PromptKind::Wounded => "wounded",
+        PromptKind::NeedHelp => "need_help",
+        PromptKind::Resist => "resist",
+    }
+}
+
+fn consequence_text(consequence: &Consequence) -> String {
+    match consequence {
+        Consequence::Harm(harm) => harm.description.clone(),
+        Consequence::Complication { ticks, .. } => format!("+{ticks} clock"),
+        Consequence::ReducedEffect => "Reduced effect".into(),
+    }
+}
+
+fn delta_to_dictionary(delta: &Delta, modifiers: &[Modifier]) -> Dictionary {
+    dict! {
+        "outcome": format!("{:?}", delta.outcome).to_lowercase(),
+        "effect": format!("{:?}", delta.effect).to_lowercase(),
+        "stress": i64::from(delta.stress.value()),
+        "trauma": delta.trauma,
+        "harm": delta.harm.iter().map(|&level| i32::from(level)).collect::<PackedInt32Array>(),
+        "breakdown": modifiers.iter().map(|modifier| GString::from(modifier.to_string())).collect::<PackedStringArray>(),
+    }
+}
@@ -0,0 +1,171 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+use std::cell::OnceCell;
+
+use darkforge_rng::{
+    dice::{D6, Dice},
+    rng::{Random, UniformThreadRandom},
+};
+use godot::{
+    classes::{ProjectSettings, RandomNumberGenerator},
+    global::PropertyHint,
+    prelude::*,
+};
+use rand::{RngCore, rngs::ThreadRng};
+
+/// Project setting rolling dice and drawing random results with Godot's `RandomNumberGenerator` when true.
+const GODOT_RNG: &str = "darkforge/random/use_godot_rng";
+/// Project setting seeding Godot's `RandomNumberGenerator`, randomized when 0.
+const SEED: &str = "darkforge/random/seed";
+
+thread_local! {
+    /// The generator of the project, once configured.
+    static GENERATOR: OnceCell<Option<Gd<RandomNumberGenerator>>> = const { OnceCell::new() };
+}
+
+/// Values between two bounds drawn from Godot's `RandomNumberGenerator`.
+pub(crate) struct GodotRandom {
+    rng: Gd<RandomNumberGenerator>,
+    low: u8,
+    high: u8,
+}
+
+impl GodotRandom {
+    /// Creates a source of values between `low` and `high` inclusive drawn from the generator.
+    pub(crate) fn new(rng: Gd<RandomNumberGenerator>, low: u8, high: u8) -> GodotRandom {
+        GodotRandom { rng, low, high }
+    }
+}
+
+impl Random<u8> for GodotRandom {
+    fn next(&mut self) -> u8 {
+        let value = self.rng.randi_range(self.low.into(), self.high.into());
+        u8::try_from(value).unwrap_or(self.low)
+    }
+
+    fn take(&mut self, n: usize) -> Vec<u8> {
+        (0..n).map(|_| self.next()).collect()
+    }
+}
+
+/// A six-sided die rolled by the random source of the project.
+pub(crate) enum Die {
+    /// Rolled by the thread-local generator of Rust.
+    Rust(D6<UniformThreadRandom<u8>>),
+    /// Rolled by Godot's `RandomNumberGenerator`.
+    Godot(D6<GodotRandom>),
+}
+
+impl Dice for Die {
+    fn roll(&self) -> u8 {
+        match self {
+            Die::Rust(die) => die.roll(),
+            Die::Godot(die) => die.roll(),
+        }
+    }
+
+    fn roll_pool(&self, pool: usize) -> Vec<u8> {
+        match self {
+            Die::Rust(die) => die.roll_pool(pool),
+            Die::Godot(die) => die.roll_pool(pool),
+        }
+    }
+
+    fn sides(&self) -> u8 {
+        6
+    }
+}
+
+/// A random number generator drawing from the random source of the project, e.g. for the oracle.
+pub(crate) enum Rng {
+    /// The thread-local generator of Rust.
+    Rust(ThreadRng),
+    /// Godot's `RandomNumberGenerator`.
+    Godot(Gd<RandomNumberGenerator>),
+}
+
+impl RngCore for Rng {
+    fn next_u32(&mut self) -> u32 {
+        match self {
+            Rng::Rust(rng) => rng.next_u32(),
+            Rng::Godot(rng) => rng.randi(),
+        }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        (u64::from(self.next_u32()) << 32) | u64::from(self.next_u32())
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(4) {
+            let bytes = self.next_u32().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+}
+
+/// Returns a six-sided die rolled by the random source of the project, see [`register_settings`].
+pub(crate) fn d6() -> Die {
+    match generator() {
+        Some(rng) => Die::Godot(D6::new(GodotRandom::new(rng, 1, 6))),
+        None => Die::Rust(D6::default()),
+    }
+}
+
+/// Returns a random number generator drawing from the random source of the project, see [`register_settings`].
+pub(crate) fn rng() -> Rng {
+    generator().map_or_else(|| Rng::Rust(rand::rng()), Rng::Godot)
+}
+
+/// Adds the settings choosing the random source to the project settings, so designers can switch to Godot's
+/// `RandomNumberGenerator` and seed it from the editor.
+pub(crate) fn register_settings() {
+    let mut settings = ProjectSettings::singleton();
+    for (name, default, hint) in [
+        (GODOT_RNG, false.to_variant(), (VariantType::BOOL, PropertyHint::NONE)),
+        (SEED, 0.to_variant(), (VariantType::INT, PropertyHint::NONE)),
+    ] {
+        if !settings.has_setting(name) {
+            settings.set_setting(name, &default);
+        }
+        settings.set_initial_value(name, &default);
+        settings.set_as_basic(name, true);
+        settings.add_property_info(&dict! {
+            "name": name,
+            "type": hint.0.ord(),
+            "hint": hint.1.ord(),
+        });
+    }
+}
+
+/// Returns the generator of the project if it rolls with Godot's `RandomNumberGenerator`, configuring it from the
+/// project settings on first use.
+fn generator() -> Option<Gd<RandomNumberGenerator>> {
+    GENERATOR.with(|generator| {
+        generator
+            .get_or_init(|| {
+                let settings = ProjectSettings::singleton();
+                if !settings.get_setting(GODOT_RNG).try_to::<bool>().unwrap_or_default() {
+                    return None;
+                }
+
+                let mut rng = RandomNumberGenerator::new_gd();
+                match settings.get_setting(SEED).try_to::<i64>().unwrap_or_default() {
+                    0 => rng.randomize(),
+                    seed => rng.set_seed(u64::from_ne_bytes(seed.to_ne_bytes())),
+                }
+                Some(rng)
+            })
+            .clone()
+    })
+}
@@ -0,0 +1,168 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+use darkforge::{
+    campaign::{Campaign, Result},
+    changes::{ComponentEdit, EntityChange, EntityChanges, WorldChanges},
+};
+use darkforge_data::store::diff::Snapshot;
+use godot::prelude::*;
+
+use crate::{error::Reporter, forge};
+
+/// Shows a GM reviewing the log what changed in the campaign's world since a mark, e.g. over the session, for editor
+/// docks.
+///
+/// The dock marks the world as it is when the session starts, then lists the changes made since, grouped by the kind of
+/// the entities changed and named by their labels.
+#[derive(GodotClass)]
+#[class(tool, base=RefCounted)]
+pub struct WorldReview {
+    base: Base<RefCounted>,
+    /// Path to the campaign database, `user://campaign.db` if empty.
+    #[var]
+    db: GString,
+    /// The error of the last call, see `DarkForgeError`, or an empty dictionary if it succeeded.
+    #[var(get)]
+    last_error: Dictionary,
+    marked: Option<Snapshot>,
+}
+
+#[godot_api]
+impl IRefCounted for WorldReview {
+    fn init(base: Base<RefCounted>) -> Self {
+        Self {
+            base,
+            db: GString::new(),
+            last_error: Dictionary::new(),
+            marked: None,
+        }
+    }
+}
+
+#[godot_api]
+impl WorldReview {
+    /// Emitted when a call fails, with the error as recorded in `last_error`.
+    #[signal]
+    fn failed(error: Dictionary);
+
+    /// Marks the world as it is now, to review the changes made from now on. Returns false if the campaign cannot be
+    /// read, in which case the previous mark is kept.
+    #[func]
+    fn mark(&mut self) -> bool {
+        let Some(snapshot) = self.run(async |campaign| campaign.snapshot().await) else {
+            return false;
+        };
+
+        self.marked = Some(snapshot);
+        true
+    }
+
+    /// Returns whether the world was marked.
+    #[func]
+    fn is_marked(&self) -> bool {
+        self.marked.is_some()
+    }
+
+    /// Returns what changed since the mark, as a dictionary of:
+    /// - `kinds`: each a dictionary of the `kind` of entity, e.g. `faction`, and its changed `entities`, ordered by label,
+    ///   each a dictionary of its `id`, `label`, `change` (`spawned`, `despawned` or `changed`) and `components`, each a
+    ///   dictionary of its `kind` and its serialized value `before` and `after`, empty where it was added or removed,
+    /// - `relationships`: each a dictionary of its `source` and `target` ids and labels, as `source_label` and
+    ///   `target_label`, its `kind` and whether it was `related`, rather than removed.
+    ///
+    /// Returns an empty dictionary if the world was not marked or cannot be read.
+    #[func]
+    fn changes(&mut self) -> Dictionary {
+        let Some(marked) = self.marked.clone() else {
+            return Dictionary::new();
+        };
+
+        self.run(async |campaign| campaign.changes_since(&marked).await)
+            .map(|changes| changes_to_dictionary(&changes))
+            .unwrap_or_default()
+    }
+
+    /// Opens the campaign and runs `f`, reporting any error.
+    fn run<T>(&mut self, f: impl AsyncFnOnce(&mut Campaign) -> Result<T>) -> Option<T> {
+        let result = forge::run(None, &self.db, f);
+        self.record("failed to review world changes", result)
+    }
+}
+
+impl Reporter for WorldReview {
+    fn last_error_mut(&mut self) -> &mut Dictionary {
+        &mut self.last_error
+    }
+}
+
+/// Converts the changes into the dictionary returned by [`WorldReview::changes`].
+fn changes_to_dictionary(changes: &WorldChanges) -> Dictionary {
+    let kinds = changes
+        .kinds
+        .iter()
+        .map(|kind| {
+            let mut dictionary = Dictionary::new();
+            dictionary.set("kind", kind.kind.to_string());
+            dictionary.set("entities", kind.entities.iter().map(entity_to_dictionary).collect::<Array<_>>());
+            dictionary
+        })
+        .collect::<Array<Dictionary>>();
+
+    let relationships = changes
+        .relationships
+        .iter()
+        .map(|relationship| {
+            let mut dictionary = Dictionary::new();
+            dictionary.set("source", relationship.source.to_string());
+            dictionary.set("source_label", relationship.source_label.as_str());
+            dictionary.set("target", relationship.target.to_string());
+            dictionary.set("target_label", relationship.target_label.as_str());
+            dictionary.set("kind", relationship.kind.as_str());
+            dictionary.set("related", relationship.related);
+            dictionary
+        })
+        .collect::<Array<Dictionary>>();
+
+    let mut dictionary = Dictionary::new();
+    dictionary.set("kinds", kinds);
+    dictionary.set("relationships", relationships);
+    dictionary
+}
+
+/// Converts the changes of an entity into a dictionary of the `entities` of [`WorldReview::changes`].
+fn entity_to_dictionary(entity: &EntityChanges) -> Dictionary {
+    let components = entity
+        .components
+        .iter()
+        .map(|ComponentEdit { kind, before, after }| {
+            let mut dictionary = Dictionary::new();
+            dictionary.set("kind", kind.as_str());
+            dictionary.set("before", before.as_deref().unwrap_or_default());
+            dictionary.set("after", after.as_deref().unwrap_or_default());
+            dictionary
+        })
+        .collect::<Array<Dictionary>>();
+
+    let change = match entity.change {
+        EntityChange::Spawned => "spawned",
+        EntityChange::Despawned => "despawned",
+        EntityChange::Changed => "changed",
+    };
+
+    let mut dictionary = Dictionary::new();
+    dictionary.set("id", entity.entity.to_string());
+    dictionary.set("label", entity.label.as_str());
+    dictionary.set("change", change);
+    dictionary.set("components", components);
+    dictionary
+}
@@ -0,0 +1,386 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+#[cfg(feature = "store")]
+use std::rc::Rc;
+
+use darkforge::{action::Outcome, notation::Translate, oracle::Ruling, roll::Roll};
+#[cfg(feature = "store")]
+use darkforge::{
+    action::roll_pool,
+    campaign::{self, Campaign, Result},
+    context::RollContext,
+    downtime::{Activity, Applied},
+    effect::Effect,
+    gambling::{self, Cheat, Wager},
+    oracle::Likelihood,
+    permission::Actor,
+    roll::{RecordedRoll, RollRequest},
+    script::{Command, Context, Hook, Sandbox},
+};
+use godot::{classes::TranslationServer, prelude::*};
+#[cfg(feature = "store")]
+use uuid::Uuid;
+
+#[cfg(feature = "store")]
+use crate::{
+    error::Reporter,
+    forge::{self, Shared},
+    random,
+};
+
+/// Rolls dice in a campaign database and lists the roll history recorded in its journal.
+#[cfg(feature = "store")]
+#[derive(GodotClass)]
+#[class(base=RefCounted)]
+pub struct RollService {
+    base: Base<RefCounted>,
+    /// Path to the campaign database, `user://campaign.db` if empty.
+    #[var]
+    db: GString,
+    /// Id of the player rolling, or empty for the GM.
+    #[var]
+    player: GString,
+    /// The error of the last call, see `DarkForgeError`, or an empty dictionary if it succeeded.
+    #[var(get)]
+    last_error: Dictionary,
+    forge: Option<Shared>,
+}
+
+#[cfg(feature = "store")]
+#[godot_api]
+impl IRefCounted for RollService {
+    fn init(base: Base<RefCounted>) -> Self {
+        Self {
+            base,
+            db: GString::new(),
+            player: GString::new(),
+            last_error: Dictionary::new(),
+            forge: None,
+        }
+    }
+}
+
+#[cfg(feature = "store")]
+#[godot_api]
+impl RollService {
+    /// Emitted when a call fails, with the error as recorded in `last_error`.
+    #[signal]
+    fn failed(error: Dictionary);
+
+    /// Rolls a pool of dice for the rating, for the character if not empty. Returns the roll as a dictionary with a
+    /// `label`, a `rating`, the `dice` kept, the `outcome` and its dice `notation` translated with the project's
+    /// translations, or an empty dictionary if it cannot be recorded.
+    #[func]
+    #[expect(clippy::needless_pass_by_value, reason = "Godot passes arguments to functions by value")]
+    fn roll(&mut self, character: GString, label: GString, rating: i64) -> Dictionary {
+        let rating = u8::try_from(rating.clamp(0, u8::MAX.into())).unwrap_or_default();
+        let label = label.to_string();
+
+        self.run(&character, async |campaign, actor, character| {
+            campaign.roll(actor, character, label, rating, &random::d6()).await
+        })
+        .map(|roll| roll_to_dictionary(&roll))
+        .unwrap_or_default()
+    }
+
+    /// Records physical dice rolled at the table for the rating, for the character if not empty, in the order rolled.
+    /// Returns the roll as a dictionary as returned by `roll`, with `manual` set, or an empty dictionary if the dice do
+    /// not make a roll for the rating or it cannot be recorded.
+    #[func]
+    #[expect(clippy::needless_pass_by_value, reason = "Godot passes arguments to functions by value")]
+    fn record_manual(&mut self, character: GString, label: GString, rating: i64, dice: PackedInt32Array) -> Dictionary {
+        let rating = u8::try_from(rating.clamp(0, u8::MAX.into())).unwrap_or_default();
+        let label = label.to_string();
+        let dice = dice
+            .as_slice()
+            .iter()
+            .map(|&die| u8::try_from(die.clamp(0, u8::MAX.into())).unwrap_or_default())
+            .collect::<Vec<_>>();
+
+        self.run(&character, async |campaign, actor, character| {
+            campaign.record_manual_roll(RollContext::new(actor), character, label, rating, dice).await
+        })
+        .map(|roll| roll_to_dictionary(&roll))
+        .unwrap_or_default()
+    }
+
+    /// Rolls many pools at once, e.g. the fortune rolls of NPCs during a faction turn, recording them together. Each
+    /// request is a dictionary with a `label`, a `rating` and optionally a `character`. Returns the rolls in order, as
+    /// dictionaries as returned by `roll`, or an empty array if any of them cannot be made.
+    #[func]
+    #[expect(clippy::needless_pass_by_value, reason = "Godot passes arguments to functions by value")]
+    fn batch(&mut self, requests: Array<Dictionary>) -> Array<Dictionary> {
+        let requests = requests
+            .iter_shared()
+            .map(|request| request_from_dictionary(&request))
+            .collect::<anyhow::Result<Vec<_>>>();
+        let Some(requests) = self.record("invalid roll request", requests) else {
+            return Array::new();
+        };
+
+        self.run(&GString::new(), async |campaign, actor, _| {
+            campaign.roll_batch(actor, &requests, &random::d6()).await
+        })
+        .map(|rolls| rolls.iter().map(roll_to_dictionary).collect())
+        .unwrap_or_default()
+    }
+
+    /// Returns the special abilities scripted by the content pack for action rolls, as dictionaries with an `id`, a
+    /// `label` and a `description`.
+    #[func]
+    fn abilities(&mut self) -> Array<Dictionary> {
+        self.run(&GString::new(), async |campaign, _, _| campaign.scripts(Hook::Action).await)
+            .map(|scripts| {
+                scripts
+                    .iter()
+                    .map(|script| {
+                        dict! {
+                            "id": script.id.to_string(),
+                            "label": script.descriptor.label().to_string(),
+                            "description": script.descriptor.description().to_string(),
+                        }
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Rolls for the character with the scripted ability's adjustments, applying what else the ability does. Returns
+    /// the roll as `roll` does, along with the ability's `notes`, or an empty dictionary if it cannot be rolled.
+    #[func]
+    #[expect(clippy::needless_pass_by_value, reason = "Godot passes arguments to functions by value")]
+    fn roll_ability(&mut self, character: GString, ability: GString, label: GString, rating: i64) -> Dictionary {
+        let rating = u8::try_from(rating.clamp(0, u8::MAX.into())).unwrap_or_default();
+        let label = label.to_string();
+        let Some(ability) = self.record("invalid ability", Uuid::parse_str(&ability.to_string()).map_err(Into::into)) else {
+            return Dictionary::new();
+        };
+
+        self.run(&character, async |campaign, actor, character| {
+            let Some(character) = character else {
+                return Err(campaign::CampaignError::MissingCharacter(Uuid::nil()));
+            };
+            let context = Context::Action {
+                action: label.clone(),
+                rating,
+                effect: Effect::Standard,
+            };
+            let plan = campaign
+                .run_script(actor, character, ability, &context, &Sandbox::default(), &mut random::rng())
+                .await?;
+            let (rating, _) = plan.adjust(rating, Effect::Standard);
+            let roll = campaign
+                .roll_keeping(actor, Some(character), label, rating, &plan.keep_drop, &random::d6())
+                .await?;
+
+            Ok((roll, plan))
+        })
+        .map(|(roll, plan)| {
+            let mut dictionary = roll_to_dictionary(&roll);
+            let notes = plan
+                .commands
+                .iter()
+                .filter_map(|command| match command {
+                    Command::Note(note) => Some(GString::from(note.as_str())),
+                    _ => None,
+                })
+                .collect::<PackedStringArray>();
+            dictionary.set("notes", notes);
+            dictionary
+        })
+        .unwrap_or_default()
+    }
+
+    /// Asks the oracle a yes/no question, `likelihood` being one of `very_unlikely`, `unlikely`, `even`, `likely` or
+    /// `very_likely`. Returns the ruling as a dictionary with the `question`, the `answer` as displayed, whether it
+    /// `is_yes`, and the `complication` or an empty string, or an empty dictionary if it cannot be recorded.
+    #[func]
+    #[expect(clippy::needless_pass_by_value, reason = "Godot passes arguments to functions by value")]
+    fn ask_oracle(&mut self, question: GString, likelihood: GString) -> Dictionary {
+        let likelihood = likelihood.to_string().parse::<Likelihood>();
+        let Some(likelihood) = self.record("invalid likelihood", likelihood.map_err(Into::into)) else {
+            return Dictionary::new();
+        };
+        let question = question.to_string();
+
+        self.run(&GString::new(), async |campaign, actor, _| {
+            campaign.ask_oracle(actor, question, likelihood, &mut random::rng()).await
+        })
+        .map(ruling_to_dictionary)
+        .unwrap_or_default()
+    }
+
+    /// Gambles at bones in a den as the character's downtime vice, staking coin from their stash. The character cheats
+    /// if `cheat_rating` is not negative, making an action roll of that rating to palm loaded bones. Returns the game as
+    /// a dictionary with the `stress` cleared, whether they `overindulged`, how the `cheat` turned out (`unnoticed`,
+    /// `suspected`, `caught` or an empty string), the `player` and `house` dice and the coin won as `winnings`,
+    /// negative if lost, or an empty dictionary if it cannot be played.
+    #[func]
+    #[expect(clippy::needless_pass_by_value, reason = "Godot passes arguments to functions by value")]
+    fn gamble(&mut self, character: GString, stake: i64, cheat_rating: i64) -> Dictionary {
+        let stake = u8::try_from(stake.clamp(0, u8::MAX.into())).unwrap_or_default();
+        let cheat = u8::try_from(cheat_rating.min(u8::MAX.into()))
+            .ok()
+            .map(|rating| Outcome::from_dice(&roll_pool(&random::d6(), rating)));
+
+        self.run(&character, async |campaign, actor, character| {
+            let character = character.ok_or(campaign::CampaignError::MissingCharacter(Uuid::nil()))?;
+            let wager = Wager { stake, cheat };
+            campaign.downtime(actor, character, Activity::Gamble { wager }, &random::d6()).await
+        })
+        .and_then(|outcome| match outcome.applied {
+            Applied::Gambled { cleared, overindulged, game } => Some(dict! {
+                "stress": i64::from(cleared),
+                "overindulged": overindulged,
+                "cheat": match game.cheat {
+                    Some(Cheat::Unnoticed) => "unnoticed",
+                    Some(Cheat::Suspected) => "suspected",
+                    Some(Cheat::Caught) => "caught",
+                    None => "",
+                },
+                "player": game.player.iter().map(|&die| i32::from(die)).collect::<PackedInt32Array>(),
+                "house": game.house.iter().map(|&die| i32::from(die)).collect::<PackedInt32Array>(),
+                "winnings": i64::from(game.winnings),
+            }),
+            _ => None,
+        })
+        .unwrap_or_default()
+    }
+
+    /// Returns the chance of beating the house at bones, between 0 and 1, with a cheat that worked if `cheating`.
+    #[func]
+    fn gambling_odds(cheating: bool) -> f64 {
+        gambling::odds(cheating).to_f64()
+    }
+
+    /// Returns up to `limit` of the latest rolls, newest first, only those of the character if not empty. Each roll is
+    /// a dictionary as returned by `roll`, along with the `character` and the `actor` who rolled.
+    #[func]
+    #[expect(clippy::needless_pass_by_value, reason = "Godot passes arguments to functions by value")]
+    fn history(&mut self, character: GString, limit: i64) -> Array<Dictionary> {
+        let limit = u32::try_from(limit.max(0)).unwrap_or(u32::MAX);
+
+        self.run(&character, async |campaign, _, character| campaign.rolls(character, limit).await)
+            .map(|rolls| rolls.iter().map(recorded_to_dictionary).collect())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(feature = "store")]
+impl RollService {
+    /// Creates a service rolling in the campaign of the singleton, as the GM unless `player` is set.
+    pub(crate) fn attached(forge: &Shared) -> Gd<RollService> {
+        Gd::from_init_fn(|base| Self {
+            base,
+            db: GString::new(),
+            player: GString::new(),
+            last_error: Dictionary::new(),
+            forge: Some(Rc::clone(forge)),
+        })
+    }
+
+    /// Opens the campaign and runs `f` with the actor rolling and the character if not empty, reporting any error.
+    fn run<T>(&mut self, character: &GString, f: impl AsyncFnOnce(&mut Campaign, Actor, Option<Uuid>) -> Result<T>) -> Option<T> {
+        let result = self.try_run(character, f);
+        self.record("failed to access rolls", result)
+    }
+
+    fn try_run<T>(&self, character: &GString, f: impl AsyncFnOnce(&mut Campaign, Actor, Option<Uuid>) -> Result<T>) -> anyhow::Result<T> {
+        let character = if character.is_empty() {
+            None
+        } else {
+            Some(Uuid::parse_str(&character.to_string())?)
+        };
+        let actor = if self.player.is_empty() {
+            Actor::Gm
+        } else {
+            Actor::Player(Uuid::parse_str(&self.player.to_string())?)
+        };
+
+        forge::run(self.forge.as_ref(), &self.db, async |campaign| f(campaign, actor, character).await)
+    }
+}
+
+#[cfg(feature = "store")]
+impl Reporter for RollService {
+    fn last_error_mut(&mut self) -> &mut Dictionary {
+        &mut self.last_error
+    }
+}
+
+/// Returns the name of the outcome, used by the dice tray to pick its color.
+pub(crate) fn outcome_name(outcome: Outcome) -> &'static str {
+    match outcome {
+        Outcome::Failure => "failure",
+        Outcome::Partial => "partial",
+        Outcome::Success => "success",
+        Outcome::Critical => "critical",
+    }
+}
+
+#[cfg(feature = "store")]
+fn request_from_dictionary(request: &Dictionary) -> anyhow::Result<RollRequest> {
+    let field = |key: &str| request.get(key).unwrap_or_default();
+    let character = field("character").try_to::<GString>().unwrap_or_default();
+
+    Ok(RollRequest {
+        character: if character.is_empty() {
+            None
+        } else {
+            Some(Uuid::parse_str(&character.to_string())?)
+        },
+        label: field("label").try_to::<GString>().unwrap_or_default().to_string(),
+        rating: u8::try_from(field("rating").try_to::<i64>().unwrap_or_default().clamp(0, u8::MAX.into()))?,
+    })
+}
+
+/// Translates the text of rolls with the project's translations, see `TranslationServer`.
+struct Localized;
+
+impl Translate for Localized {
+    fn translate(&self, key: &str) -> Option<String> {
+        let translated = TranslationServer::singleton().translate(key).to_string();
+
+        (translated != key).then_some(translated)
+    }
+}
+
+pub(crate) fn roll_to_dictionary(roll: &Roll) -> Dictionary {
+    dict! {
+        "label": roll.label.clone(),
+        "rating": i64::from(roll.rating),
+        "dice": roll.dice.iter().map(|&die| i32::from(die)).collect::<PackedInt32Array>(),
+        "outcome": outcome_name(roll.outcome),
+        "notation": roll.notation(&Localized).to_string(),
+        "manual": roll.manual,
+    }
+}
+
+#[cfg(feature = "store")]
+fn recorded_to_dictionary(recorded: &RecordedRoll) -> Dictionary {
+    let mut dictionary = roll_to_dictionary(&recorded.roll);
+    dictionary.set("character", recorded.character.map(|character| character.to_string()).unwrap_or_default());
+    dictionary.set("actor", recorded.actor.to_string());
+    dictionary.set("recorded_at", recorded.recorded_at);
+
+    dictionary
+}
+
+pub(crate) fn ruling_to_dictionary(ruling: Ruling) -> Dictionary {
+    dict! {
+        "question": ruling.question,
+        "answer": ruling.answer.to_string(),
+        "is_yes": ruling.answer.is_yes(),
+        "complication": ruling.complication.unwrap_or_default(),
+    }
+}
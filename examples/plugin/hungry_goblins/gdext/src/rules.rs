@@ -0,0 +1,173 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+use darkforge::{
+    action::Outcome,
+    engine::{RuleSet, RulesRegistry},
+    gambling,
+    odds::{self, RollSpec},
+    oracle::{self, Complication, Likelihood},
+    table::{Row, Table},
+    template::{self, Bindings},
+};
+use godot::prelude::*;
+
+use crate::{
+    error::Reporter,
+    pack::{self, ContentPack},
+    random,
+    roll::{outcome_name, roll_to_dictionary, ruling_to_dictionary},
+};
+
+/// Applies the rules of the game without a campaign database, reading content from the packs in memory, so games
+/// exported without the store, e.g. to the web, can still roll dice and consult the oracle. Nothing is recorded.
+///
+/// The rules applied are those registered for the first of the packs that has some, see [`RuleBook::register_rules`],
+/// or the core rules of Blades in the Dark.
+#[derive(GodotClass)]
+#[class(base=RefCounted)]
+pub struct RuleBook {
+    base: Base<RefCounted>,
+    /// The content packs to read complications and other tables from.
+    #[export]
+    packs: Array<Gd<ContentPack>>,
+    /// The error of the last call, see `DarkForgeError`, or an empty dictionary if it succeeded.
+    #[var(get)]
+    last_error: Dictionary,
+    /// The rules of the packs that bring their own.
+    rules: RulesRegistry,
+}
+
+#[godot_api]
+impl IRefCounted for RuleBook {
+    fn init(base: Base<RefCounted>) -> Self {
+        Self {
+            base,
+            packs: Array::new(),
+            last_error: Dictionary::new(),
+            rules: RulesRegistry::default(),
+        }
+    }
+}
+
+#[godot_api]
+impl RuleBook {
+    /// Emitted when a call fails, with the error as recorded in `last_error`.
+    #[signal]
+    fn failed(error: Dictionary);
+
+    /// Rolls a pool of dice for the rating. Returns the roll as a dictionary, as `RollService.roll` does.
+    #[func]
+    #[expect(clippy::needless_pass_by_value, reason = "Godot passes arguments to functions by value")]
+    fn roll(&self, label: GString, rating: i64) -> Dictionary {
+        let rating = u8::try_from(rating.clamp(0, u8::MAX.into())).unwrap_or_default();
+
+        roll_to_dictionary(&self.rules().resolution.roll(&label.to_string(), rating, &[], &random::d6()))
+    }
+
+    /// Returns the chance of each outcome of a roll of the rating, in percent, as a dictionary by outcome name.
+    #[func]
+    fn odds(&self, rating: i64) -> Dictionary {
+        let spec = RollSpec::new(u8::try_from(rating.clamp(0, u8::MAX.into())).unwrap_or_default());
+        let odds = self.rules().resolution.odds(spec);
+
+        [Outcome::Failure, Outcome::Partial, Outcome::Success, Outcome::Critical]
+            .into_iter()
+            .map(|outcome| (outcome_name(outcome), odds.of(outcome)))
+            .collect()
+    }
+
+    /// Returns the chance of each outcome of pools of 0 to 10 dice under the core rules, in percent, as dictionaries by
+    /// outcome name indexed by pool. The odds are precomputed, for pool selectors to show them as the pool changes.
+    #[func]
+    fn odds_table() -> Array<Dictionary> {
+        odds::table()
+            .iter()
+            .map(|odds| {
+                let odds = odds.to_percent();
+                [Outcome::Failure, Outcome::Partial, Outcome::Success, Outcome::Critical]
+                    .into_iter()
+                    .map(|outcome| (outcome_name(outcome), odds.of(outcome)))
+                    .collect::<Dictionary>()
+            })
+            .collect()
+    }
+
+    /// Returns the chance of beating the house at bones, between 0 and 1, with a cheat that worked if `cheating`.
+    #[func]
+    fn gambling_odds(cheating: bool) -> f64 {
+        gambling::odds(cheating).to_f64()
+    }
+
+    /// Asks the oracle a yes/no question, drawing complications from the packs. Returns the ruling as a dictionary, as
+    /// `RollService.ask_oracle` does, or an empty dictionary if the likelihood is unknown or a pack cannot be read.
+    /// Without a campaign, placeholders in complications are replaced by their fallbacks.
+    #[func]
+    #[expect(clippy::needless_pass_by_value, reason = "Godot passes arguments to functions by value")]
+    fn ask_oracle(&mut self, question: GString, likelihood: GString) -> Dictionary {
+        let likelihood = likelihood.to_string().parse::<Likelihood>();
+        let Some(likelihood) = self.record("invalid likelihood", likelihood.map_err(Into::into)) else {
+            return Dictionary::new();
+        };
+        let complications = self.complications();
+        let Some(complications) = self.record("failed to read complications", complications) else {
+            return Dictionary::new();
+        };
+
+        let mut rng = random::rng();
+        let mut ruling = self.rules().oracle.ask(&question.to_string(), likelihood, &complications, &mut rng);
+        if let Some(complication) = ruling.complication.take() {
+            let rendered = template::render(&complication, &Bindings::default(), &mut rng);
+            let Some(rendered) = self.record("invalid complication", rendered.map_err(Into::into)) else {
+                return Dictionary::new();
+            };
+            ruling.complication = Some(rendered);
+        }
+
+        ruling_to_dictionary(ruling)
+    }
+}
+
+impl RuleBook {
+    /// Registers the rules of a hack under the name of its content pack, to apply whenever that pack is loaded.
+    /// Returns the rules it replaces if the pack had some.
+    #[expect(
+        dead_code,
+        reason = "Hungry Goblins plays by the core rules, hack modules built into the extension call it"
+    )]
+    pub fn register_rules(&mut self, pack: impl Into<String>, rules: RuleSet) -> Option<RuleSet> {
+        self.rules.register(pack, rules)
+    }
+
+    /// Returns the rules of the loaded packs.
+    fn rules(&self) -> &RuleSet {
+        let names = pack::names(&self.packs);
+
+        self.rules.resolve(names.iter().map(String::as_str))
+    }
+
+    /// Reads the complications of every pack.
+    fn complications(&self) -> anyhow::Result<Table<Complication>> {
+        let mut complications = Vec::new();
+        for pack in pack::packs(&self.packs)? {
+            complications.extend(pack.load::<Row<Complication>>(oracle::CATEGORY)?);
+        }
+
+        Ok(complications.into_iter().collect())
+    }
+}
+
+impl Reporter for RuleBook {
+    fn last_error_mut(&mut self) -> &mut Dictionary {
+        &mut self.last_error
+    }
+}
@@ -0,0 +1,35 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+use std::path::PathBuf;
+
+#[cfg(feature = "store")]
+use darkforge_data::paths::SavePaths;
+use godot::{classes::ProjectSettings, prelude::*};
+
+/// Returns the save paths of the game, under Godot's `user://` directory.
+#[cfg(feature = "store")]
+pub(crate) fn save_paths() -> SavePaths {
+    SavePaths::new(globalize(&"user://".into()))
+}
+
+/// Returns the path of the campaign database, resolving Godot paths such as `user://`, or the game's default database
+/// if `db` is empty.
+#[cfg(feature = "store")]
+pub(crate) fn database(db: &GString) -> PathBuf {
+    if db.is_empty() { save_paths().database() } else { globalize(db) }
+}
+
+/// Converts a Godot path into a path of the file system.
+pub(crate) fn globalize(path: &GString) -> PathBuf {
+    PathBuf::from(ProjectSettings::singleton().globalize_path(path).to_string())
+}
@@ -0,0 +1,128 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+use darkforge::schema;
+use darkforge_data::store::{
+    schema::{Reflect, Schema},
+    sql::sqlite::SqliteStore,
+};
+use godot::prelude::*;
+
+use crate::{error::Reporter, save};
+
+/// Lists the tables of the campaign database and the components saved in them, for editor docks.
+///
+/// The declared schema is the one the game expects, while the reflected schema is read back from a database, so a dock
+/// can point out what an older save lacks.
+#[derive(GodotClass)]
+#[class(tool, base=RefCounted)]
+pub struct DatabaseSchema {
+    base: Base<RefCounted>,
+    /// Path to the campaign database, `user://campaign.db` if empty.
+    #[var]
+    db: GString,
+    /// The error of the last call, see `DarkForgeError`, or an empty dictionary if it succeeded.
+    #[var(get)]
+    last_error: Dictionary,
+}
+
+#[godot_api]
+impl IRefCounted for DatabaseSchema {
+    fn init(base: Base<RefCounted>) -> Self {
+        Self {
+            base,
+            db: GString::new(),
+            last_error: Dictionary::new(),
+        }
+    }
+}
+
+#[godot_api]
+impl DatabaseSchema {
+    /// Emitted when a call fails, with the error as recorded in `last_error`.
+    #[signal]
+    fn failed(error: Dictionary);
+
+    /// Returns the schema the game expects, as a dictionary of `tables`, each with a `name` and its `columns`, each with
+    /// a `name`, a `type`, whether it is `nullable` and whether it is part of the primary `key`, along with the `kinds`
+    /// of components mapped to the name of the type saved under each.
+    #[func]
+    fn declared() -> Dictionary {
+        schema_to_dictionary(&schema::registry().schema())
+    }
+
+    /// Returns the schema of the campaign database as `declared` does, the types of components being empty as the
+    /// database does not know them. Returns an empty dictionary if the database cannot be read.
+    #[func]
+    fn reflected(&mut self) -> Dictionary {
+        let reflected = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(Into::into)
+            .and_then(|runtime| {
+                runtime.block_on(async {
+                    let mut store = SqliteStore::open(save::database(&self.db)).await?;
+                    let schema = store.schema().await?;
+                    store.close().await?;
+
+                    Ok(schema)
+                })
+            });
+
+        self.record("failed to read database schema", reflected)
+            .map(|schema| schema_to_dictionary(&schema))
+            .unwrap_or_default()
+    }
+}
+
+impl Reporter for DatabaseSchema {
+    fn last_error_mut(&mut self) -> &mut Dictionary {
+        &mut self.last_error
+    }
+}
+
+/// Converts a schema into the dictionary returned by [`DatabaseSchema::declared`].
+fn schema_to_dictionary(schema: &Schema) -> Dictionary {
+    let tables = schema
+        .tables
+        .iter()
+        .map(|table| {
+            let columns = table
+                .columns
+                .iter()
+                .map(|column| {
+                    let mut dictionary = Dictionary::new();
+                    dictionary.set("name", column.name.as_str());
+                    dictionary.set("type", column.ty.to_string());
+                    dictionary.set("nullable", column.nullable);
+                    dictionary.set("key", column.key);
+                    dictionary
+                })
+                .collect::<Array<Dictionary>>();
+
+            let mut dictionary = Dictionary::new();
+            dictionary.set("name", table.name.as_str());
+            dictionary.set("columns", columns);
+            dictionary
+        })
+        .collect::<Array<Dictionary>>();
+
+    let mut kinds = Dictionary::new();
+    for (kind, ty) in &schema.kinds {
+        kinds.set(kind.as_str(), ty.as_deref().unwrap_or_default());
+    }
+
+    let mut dictionary = Dictionary::new();
+    dictionary.set("tables", tables);
+    dictionary.set("kinds", kinds);
+    dictionary
+}
@@ -0,0 +1,107 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+
+use std::rc::Rc;
+
+use godot::prelude::*;
+use uuid::Uuid;
+
+use crate::{
+    character::Character,
+    error::Reporter,
+    forge::{self, Shared},
+};
+
+/// Lists the characters a player can pick from in a campaign database.
+#[derive(GodotClass)]
+#[class(base=RefCounted)]
+pub struct CharacterSelection {
+    base: Base<RefCounted>,
+    /// Path to the campaign database, `user://campaign.db` if empty.
+    #[var]
+    db: GString,
+    /// Id of the player choosing a character.
+    #[var]
+    player: GString,
+    /// The error of the last call, see `DarkForgeError`, or an empty dictionary if it succeeded.
+    #[var(get)]
+    last_error: Dictionary,
+    forge: Option<Shared>,
+}
+
+#[godot_api]
+impl IRefCounted for CharacterSelection {
+    fn init(base: Base<RefCounted>) -> Self {
+        Self {
+            base,
+            db: GString::new(),
+            player: GString::new(),
+            last_error: Dictionary::new(),
+            forge: None,
+        }
+    }
+}
+
+#[godot_api]
+impl CharacterSelection {
+    /// Emitted when a call fails, with the error as recorded in `last_error`.
+    #[signal]
+    fn failed(error: Dictionary);
+
+    /// Returns the player's characters that are still in play, or an empty array if they cannot be loaded.
+    #[func]
+    fn active_roster(&mut self) -> Array<Gd<Character>> {
+        let roster = self.load(true);
+        self.record("failed to load roster", roster).unwrap_or_default()
+    }
+
+    /// Returns every character the player has played, including retired ones, or an empty array if they cannot be
+    /// loaded.
+    #[func]
+    fn roster(&mut self) -> Array<Gd<Character>> {
+        let roster = self.load(false);
+        self.record("failed to load roster", roster).unwrap_or_default()
+    }
+}
+
+impl CharacterSelection {
+    /// Creates a selection of characters in the campaign of the singleton, for the player once `player` is set.
+    pub(crate) fn attached(forge: &Shared) -> Gd<CharacterSelection> {
+        Gd::from_init_fn(|base| Self {
+            base,
+            db: GString::new(),
+            player: GString::new(),
+            last_error: Dictionary::new(),
+            forge: Some(Rc::clone(forge)),
+        })
+    }
+
+    fn load(&self, active: bool) -> anyhow::Result<Array<Gd<Character>>> {
+        let player = Uuid::parse_str(&self.player.to_string())?;
+        let entries = forge::run(self.forge.as_ref(), &self.db, async |campaign| {
+            if active {
+                campaign.active_roster(Some(player)).await
+            } else {
+                campaign.roster(Some(player)).await
+            }
+        })?;
+
+        Ok(entries.iter().map(Character::from_roster).collect())
+    }
+}
+
+impl Reporter for CharacterSelection {
+    fn last_error_mut(&mut self) -> &mut Dictionary {
+        &mut self.last_error
+    }
+}
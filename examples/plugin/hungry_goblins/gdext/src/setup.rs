@@ -0,0 +1,169 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+use std::rc::Rc;
+
+use darkforge::{
+    campaign::{Campaign, Result},
+    permission::Actor,
+    setup::{Amendment, HouseRule, Setup},
+};
+use godot::prelude::*;
+use uuid::Uuid;
+
+use crate::{
+    error::Reporter,
+    forge::{self, Shared},
+};
+
+/// Displays and amends the decisions of session zero of a campaign database: its tone, lines and veils, and the house
+/// rules in play. Anyone may draw lines and veils, only the GM may make other amendments.
+#[derive(GodotClass)]
+#[class(base=RefCounted)]
+pub struct SetupService {
+    base: Base<RefCounted>,
+    /// Path to the campaign database, `user://campaign.db` if empty.
+    #[var]
+    db: GString,
+    /// Id of the player amending the setup, or empty for the GM.
+    #[var]
+    player: GString,
+    /// The error of the last call, see `DarkForgeError`, or an empty dictionary if it succeeded.
+    #[var(get)]
+    last_error: Dictionary,
+    forge: Option<Shared>,
+}
+
+#[godot_api]
+impl IRefCounted for SetupService {
+    fn init(base: Base<RefCounted>) -> Self {
+        Self {
+            base,
+            db: GString::new(),
+            player: GString::new(),
+            last_error: Dictionary::new(),
+            forge: None,
+        }
+    }
+}
+
+#[godot_api]
+impl SetupService {
+    /// Emitted when a call fails, with the error as recorded in `last_error`.
+    #[signal]
+    fn failed(error: Dictionary);
+
+    /// Returns the setup as a dictionary with the `tone`, the `lines` and `veils`, and the `house_rules` as a
+    /// dictionary of whether each is in play by name, or an empty dictionary if it cannot be read.
+    #[func]
+    fn setup(&mut self) -> Dictionary {
+        self.run(async |campaign, _| campaign.setup().await)
+            .map(|setup| setup_to_dictionary(&setup))
+            .unwrap_or_default()
+    }
+
+    /// Sets the tone, as the GM. Returns the setup as amended, as a dictionary as returned by `setup`, or an empty
+    /// dictionary if it cannot be amended.
+    #[func]
+    #[expect(clippy::needless_pass_by_value, reason = "Godot passes arguments to functions by value")]
+    fn set_tone(&mut self, tone: GString) -> Dictionary {
+        self.amend(Amendment::Tone(tone.to_string()))
+    }
+
+    /// Draws a line, content kept out of the game. Returns the setup as `set_tone` does.
+    #[func]
+    #[expect(clippy::needless_pass_by_value, reason = "Godot passes arguments to functions by value")]
+    fn draw_line(&mut self, line: GString) -> Dictionary {
+        self.amend(Amendment::Line(line.to_string()))
+    }
+
+    /// Draws a veil, content kept off screen. Returns the setup as `set_tone` does.
+    #[func]
+    #[expect(clippy::needless_pass_by_value, reason = "Godot passes arguments to functions by value")]
+    fn draw_veil(&mut self, veil: GString) -> Dictionary {
+        self.amend(Amendment::Veil(veil.to_string()))
+    }
+
+    /// Lifts a line or a veil, as the GM. Returns the setup as `set_tone` does.
+    #[func]
+    #[expect(clippy::needless_pass_by_value, reason = "Godot passes arguments to functions by value")]
+    fn lift(&mut self, content: GString) -> Dictionary {
+        self.amend(Amendment::Lift(content.to_string()))
+    }
+
+    /// Puts the house rule of the given name in or out of play, as the GM. Returns the setup as `set_tone` does, or an
+    /// empty dictionary if the house rule is unknown.
+    #[func]
+    #[expect(clippy::needless_pass_by_value, reason = "Godot passes arguments to functions by value")]
+    fn toggle_house_rule(&mut self, rule: GString, in_play: bool) -> Dictionary {
+        let rule = rule.to_string().parse::<HouseRule>();
+        let Some(rule) = self.record("invalid house rule", rule.map_err(Into::into)) else {
+            return Dictionary::new();
+        };
+
+        self.amend(Amendment::HouseRule { rule, in_play })
+    }
+}
+
+impl SetupService {
+    /// Creates a service for the setup of the campaign of the singleton.
+    pub(crate) fn attached(forge: &Shared) -> Gd<SetupService> {
+        Gd::from_init_fn(|base| Self {
+            base,
+            db: GString::new(),
+            player: GString::new(),
+            last_error: Dictionary::new(),
+            forge: Some(Rc::clone(forge)),
+        })
+    }
+
+    /// Amends the setup as the actor and returns it as a dictionary, or an empty dictionary if it cannot be amended.
+    fn amend(&mut self, amendment: Amendment) -> Dictionary {
+        self.run(async |campaign, actor| campaign.amend_setup(actor, amendment).await)
+            .map(|setup| setup_to_dictionary(&setup))
+            .unwrap_or_default()
+    }
+
+    /// Opens the campaign and runs `f` with the actor amending the setup, reporting any error.
+    fn run<T>(&mut self, f: impl AsyncFnOnce(&mut Campaign, Actor) -> Result<T>) -> Option<T> {
+        let result = self.try_run(f);
+        self.record("failed to access setup", result)
+    }
+
+    fn try_run<T>(&self, f: impl AsyncFnOnce(&mut Campaign, Actor) -> Result<T>) -> anyhow::Result<T> {
+        let actor = if self.player.is_empty() {
+            Actor::Gm
+        } else {
+            Actor::Player(Uuid::parse_str(&self.player.to_string())?)
+        };
+
+        forge::run(self.forge.as_ref(), &self.db, async |campaign| f(campaign, actor).await)
+    }
+}
+
+impl Reporter for SetupService {
+    fn last_error_mut(&mut self) -> &mut Dictionary {
+        &mut self.last_error
+    }
+}
+
+fn setup_to_dictionary(setup: &Setup) -> Dictionary {
+    dict! {
+        "tone": setup.tone.as_str(),
+        "lines": setup.lines.iter().map(GString::from).collect::<PackedStringArray>(),
+        "veils": setup.veils.iter().map(GString::from).collect::<PackedStringArray>(),
+        "house_rules": HouseRule::ALL
+            .into_iter()
+            .map(|rule| (rule.name(), setup.in_play(rule)))
+            .collect::<Dictionary>(),
+    }
+}
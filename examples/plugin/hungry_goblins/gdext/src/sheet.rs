@@ -0,0 +1,430 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+use std::time::{Duration, Instant};
+
+use darkforge::{
+    campaign::{Campaign, CampaignError, Result},
+    character::{Harm, Ratings, Sheet, Stress},
+    permission::Actor,
+};
+use darkforge_data::store::sql::sqlite::{RetryPolicy, SqliteError, SqliteStore, SqliteStoreBuilder};
+use godot::{
+    classes::{Control, IControl},
+    prelude::*,
+};
+use uuid::Uuid;
+
+use crate::{
+    coalesce::Coalescer,
+    counter::{self, Counter},
+    error::{InvalidArgument, Reporter},
+    save,
+};
+
+/// A field of a character the sheet writes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Field {
+    Stress,
+    Harm,
+    Rating(Uuid),
+}
+
+/// A change to a field of a character, waiting to be written.
+enum Edit {
+    Stress(Stress),
+    Harm(Harm),
+    Rating(Uuid, u8),
+}
+
+impl Edit {
+    /// Shows the change on the sheet before it is written.
+    fn show(&self, sheet: &mut Sheet) {
+        match self {
+            Edit::Stress(stress) => sheet.stress = *stress,
+            Edit::Harm(harm) => sheet.harm = harm.clone(),
+            Edit::Rating(action, dots) => {
+                sheet.ratings.set(*action, *dots);
+            }
+        }
+    }
+}
+
+/// A character sheet bound to a character in a campaign database.
+///
+/// The sheet is the root of the `character_sheet.tscn` scene shipped with the addon. Its script builds the stress
+/// track, harm boxes and action dots, and calls back into the sheet whenever a control changes.
+///
+/// Changes show on the sheet at once, but are only written once the control settles, see `debounce_ms`, so dragging
+/// a slider or holding a stepper down writes its final value rather than every value it went through. Writes still
+/// pending are flushed when the sheet leaves the tree. Changes that cannot be written as the save is held by another
+/// program are held again and tried later, reporting `DarkForgeError.UNAVAILABLE` once it keeps failing.
+#[derive(GodotClass)]
+#[class(base=Control)]
+pub struct CharacterSheet {
+    base: Base<Control>,
+    /// Path to the campaign database, `user://campaign.db` if empty.
+    #[export]
+    db: GString,
+    /// Id of the character entity shown on the sheet.
+    #[export]
+    character: GString,
+    /// Id of the player editing the sheet, or empty for the GM.
+    #[export]
+    player: GString,
+    /// Holds each change until no other change to the same field came for this many milliseconds, writing it at once if
+    /// not positive.
+    #[export]
+    debounce_ms: i64,
+    /// Writes a change held for this many milliseconds even if the field keeps changing, e.g. while a slider is dragged.
+    #[export]
+    max_delay_ms: i64,
+    /// The error of the last call, see `DarkForgeError`, or an empty dictionary if it succeeded.
+    #[var(get)]
+    last_error: Dictionary,
+    sheet: Option<Sheet>,
+    actions: Array<Dictionary>,
+    writes: Option<Coalescer<(Uuid, Field), Edit>>,
+    /// Opens the campaign database for each call, sharing the count of failed calls so the sheet stops hammering a save
+    /// that keeps failing.
+    store: Option<(GString, SqliteStoreBuilder)>,
+}
+
+#[godot_api]
+impl IControl for CharacterSheet {
+    fn init(base: Base<Control>) -> Self {
+        Self {
+            base,
+            db: GString::new(),
+            character: GString::new(),
+            player: GString::new(),
+            debounce_ms: 250,
+            max_delay_ms: 1000,
+            last_error: Dictionary::new(),
+            sheet: None,
+            actions: Array::new(),
+            writes: None,
+            store: None,
+        }
+    }
+
+    fn process(&mut self, _delta: f64) {
+        let due = self.writes.as_mut().map(|writes| writes.due(Instant::now())).unwrap_or_default();
+        if !due.is_empty() {
+            self.write(due);
+        }
+    }
+
+    fn exit_tree(&mut self) {
+        self.flush();
+    }
+}
+
+#[godot_api]
+impl CharacterSheet {
+    /// Emitted whenever the sheet is loaded or changed.
+    #[signal]
+    fn sheet_changed();
+
+    /// Emitted when the stress shown on the sheet moves, as `DarkForge.counters` emits it, for the stress track to
+    /// tween. The stress to settle on is still read with `stress` if the animation is skipped.
+    #[signal]
+    fn counter_changed(change: Dictionary);
+
+    /// Emitted when a call fails, with the error as recorded in `last_error`.
+    #[signal]
+    fn failed(error: Dictionary);
+
+    /// Loads the character and the actions of the campaign. Returns false if they cannot be loaded.
+    #[func]
+    fn reload(&mut self) -> bool {
+        let loaded = self.with_campaign(async |campaign, entity, _| {
+            let sheet = campaign.sheet(entity).await?;
+            let actions = campaign.actions().await?;
+            let attributes = campaign.attributes().await?;
+
+            let mut rows = Array::new();
+            for attribute in &attributes {
+                for action in actions.iter().filter(|action| attribute.actions.contains(&action.id)) {
+                    rows.push(&dict! {
+                        "id": action.id.to_string(),
+                        "label": action.descriptor.label().to_string(),
+                        "attribute": attribute.descriptor.label().to_string(),
+                    });
+                }
+            }
+
+            Ok((sheet, rows))
+        });
+
+        match loaded {
+            Some((mut sheet, actions)) => {
+                // Changes still held show over what the campaign holds, or they would flicker back until written.
+                for ((entity, _), edit) in self.writes.iter().flat_map(Coalescer::pending) {
+                    if *entity == sheet.entity {
+                        edit.show(&mut sheet);
+                    }
+                }
+                self.sheet = Some(sheet);
+                self.actions = actions;
+                self.base_mut().emit_signal("sheet_changed", &[]);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the character's name.
+    #[func]
+    fn character_name(&self) -> GString {
+        self.sheet.as_ref().map_or_else(GString::new, |sheet| sheet.character.name().into())
+    }
+
+    /// Returns the stress the character has taken.
+    #[func]
+    fn stress(&self) -> i64 {
+        self.sheet.as_ref().map_or(0, |sheet| sheet.stress.value().into())
+    }
+
+    /// Returns the number of boxes on the stress track.
+    #[func]
+    fn max_stress() -> i64 {
+        Stress::MAX.into()
+    }
+
+    /// Sets the stress the character has taken. Returns false if it cannot be saved.
+    #[func]
+    fn set_stress(&mut self, value: i64) -> bool {
+        let stress = Stress::new(u8::try_from(value.clamp(0, Stress::MAX.into())).unwrap_or_default());
+        self.save(Field::Stress, Edit::Stress(stress))
+    }
+
+    /// Returns the descriptions of the harm the character suffers from at the given level, from 1 to 4.
+    #[func]
+    fn harm(&self, level: i64) -> PackedStringArray {
+        let Some(harm) = self.sheet.as_ref().map(|sheet| &sheet.harm) else {
+            return PackedStringArray::new();
+        };
+
+        let marked = match level {
+            1 => harm.lesser.clone(),
+            2 => harm.moderate.clone(),
+            3 => harm.severe.iter().cloned().collect(),
+            4 => harm.fatal.iter().cloned().collect(),
+            _ => Vec::new(),
+        };
+        marked.iter().map(GString::from).collect()
+    }
+
+    /// Returns the number of harm boxes at the given level.
+    #[func]
+    fn harm_slots(level: i64) -> i64 {
+        match level {
+            1 | 2 => i64::try_from(Harm::SLOTS).unwrap_or_default(),
+            3 | 4 => 1,
+            _ => 0,
+        }
+    }
+
+    /// Replaces the harm the character suffers from at the given level. Returns false if it cannot be saved.
+    #[func]
+    #[expect(clippy::needless_pass_by_value, reason = "Godot passes arguments to functions by value")]
+    fn set_harm(&mut self, level: i64, descriptions: PackedStringArray) -> bool {
+        let mut harm = self.sheet.as_ref().map(|sheet| sheet.harm.clone()).unwrap_or_default();
+        let mut marked = descriptions
+            .as_slice()
+            .iter()
+            .map(GString::to_string)
+            .filter(|description| !description.trim().is_empty());
+
+        match level {
+            1 => harm.lesser = marked.by_ref().take(Harm::SLOTS).collect(),
+            2 => harm.moderate = marked.by_ref().take(Harm::SLOTS).collect(),
+            3 => harm.severe = marked.next(),
+            4 => harm.fatal = marked.next(),
+            _ => {
+                self.record::<()>("invalid harm level", Err(InvalidArgument(format!("no harm at level {level}")).into()));
+                return false;
+            }
+        }
+
+        self.save(Field::Harm, Edit::Harm(harm))
+    }
+
+    /// Returns the actions of the campaign as dictionaries with an `id`, a `label` and an `attribute`, grouped by
+    /// attribute.
+    #[func]
+    fn actions(&self) -> Array<Dictionary> {
+        self.actions.clone()
+    }
+
+    /// Returns the highest rating an action can have.
+    #[func]
+    fn max_rating() -> i64 {
+        Ratings::MAX.into()
+    }
+
+    /// Returns the character's rating in the action.
+    #[func]
+    fn rating(&self, action: GString) -> i64 {
+        let (Some(sheet), Ok(action)) = (&self.sheet, Uuid::parse_str(&String::from(action))) else {
+            return 0;
+        };
+
+        sheet.ratings.rating(action).into()
+    }
+
+    /// Sets the character's rating in the action. Returns false if it cannot be saved.
+    #[func]
+    fn set_rating(&mut self, action: GString, dots: i64) -> bool {
+        let Some(action) = self.record("invalid action id", Uuid::parse_str(&String::from(action)).map_err(Into::into)) else {
+            return false;
+        };
+        let dots = u8::try_from(dots.clamp(0, Ratings::MAX.into())).unwrap_or_default();
+
+        self.save(Field::Rating(action), Edit::Rating(action, dots))
+    }
+
+    /// Writes the changes still held, whether their controls settled or not, then reloads the sheet. Returns false if
+    /// they cannot be saved.
+    #[func]
+    fn flush(&mut self) -> bool {
+        let pending = self.writes.as_mut().map(Coalescer::flush).unwrap_or_default();
+        pending.is_empty() || self.write(pending)
+    }
+
+    /// Returns whether changes are held, waiting for their controls to settle.
+    #[func]
+    fn has_pending_writes(&self) -> bool {
+        self.writes.as_ref().is_some_and(|writes| !writes.is_empty())
+    }
+}
+
+impl CharacterSheet {
+    /// Holds a change to the character until its control settles, or writes it at once if the sheet does not debounce.
+    /// Returns false if it is written and cannot be saved.
+    fn save(&mut self, field: Field, edit: Edit) -> bool {
+        let Some(entity) = self.record("invalid character id", Uuid::parse_str(&self.character.to_string()).map_err(Into::into)) else {
+            return false;
+        };
+        if self.debounce_ms <= 0 {
+            return self.write(vec![((entity, field), edit)]);
+        }
+        let shown = self.sheet.as_mut().filter(|sheet| sheet.entity == entity).map(|sheet| {
+            let old = sheet.stress;
+            edit.show(sheet);
+            (old, sheet.stress)
+        });
+
+        self.writes().push((entity, field), edit, Instant::now());
+        self.base_mut().emit_signal("sheet_changed", &[]);
+        if let Some((old, new)) = shown.filter(|(old, new)| old != new) {
+            self.stress_changed(entity, old, new);
+        }
+
+        true
+    }
+
+    /// Writes the changes in a single opening of the campaign, then reloads the sheet. Changes that cannot be written
+    /// as the save is busy or unavailable are held again, to be tried once their controls settle.
+    fn write(&mut self, edits: Vec<((Uuid, Field), Edit)>) -> bool {
+        let result = self.run(async |campaign, _, actor| {
+            for ((entity, _), edit) in &edits {
+                match edit {
+                    Edit::Stress(stress) => campaign.update(actor, *entity, stress).await.map(|_| ())?,
+                    Edit::Harm(harm) => campaign.update(actor, *entity, harm).await.map(|_| ())?,
+                    Edit::Rating(action, dots) => campaign.rate(actor, *entity, *action, *dots).await.map(|_| ())?,
+                }
+            }
+
+            Ok(())
+        });
+
+        if result.as_ref().is_err_and(transient) {
+            let now = Instant::now();
+            let writes = self.writes();
+            for (key, edit) in edits {
+                writes.push(key, edit, now);
+            }
+        }
+
+        self.record("failed to update character sheet", result).is_some() && self.reload()
+    }
+
+    /// Emits `counter_changed` for the character's stress moving from `old` to `new`.
+    fn stress_changed(&mut self, entity: Uuid, old: Stress, new: Stress) {
+        let change = counter::change(
+            entity,
+            Counter::Stress,
+            old.value(),
+            new.value(),
+            counter::STEP_MS,
+            counter::MAX_DURATION_MS,
+        );
+        self.base_mut().emit_signal("counter_changed", &[change.to_variant()]);
+    }
+
+    /// Returns the changes held, created with the timings of the sheet.
+    fn writes(&mut self) -> &mut Coalescer<(Uuid, Field), Edit> {
+        let quiet = Duration::from_millis(self.debounce_ms.max(0).unsigned_abs());
+        let limit = Duration::from_millis(self.max_delay_ms.max(0).unsigned_abs());
+        self.writes.get_or_insert_with(|| Coalescer::new(quiet, limit))
+    }
+
+    /// Opens the campaign and runs `f` with the character entity and the actor editing the sheet, reporting any error.
+    fn with_campaign<T>(&mut self, f: impl AsyncFnOnce(&mut Campaign, Uuid, Actor) -> Result<T>) -> Option<T> {
+        let result = self.run(f);
+        self.record("failed to update character sheet", result)
+    }
+
+    fn run<T>(&mut self, f: impl AsyncFnOnce(&mut Campaign, Uuid, Actor) -> Result<T>) -> anyhow::Result<T> {
+        let entity = Uuid::parse_str(&self.character.to_string())?;
+        let actor = if self.player.is_empty() {
+            Actor::Gm
+        } else {
+            Actor::Player(Uuid::parse_str(&self.player.to_string())?)
+        };
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+        let builder = match &self.store {
+            Some((db, builder)) if *db == self.db => builder.clone(),
+            _ => {
+                let builder = SqliteStore::builder(save::database(&self.db)).retry(RetryPolicy::default());
+                self.store = Some((self.db.clone(), builder.clone()));
+                builder
+            }
+        };
+
+        Ok(runtime.block_on(async {
+            let store = builder.build().await?;
+            let mut campaign = Campaign::open(store).await?;
+            f(&mut campaign, entity, actor).await
+        })?)
+    }
+}
+
+impl Reporter for CharacterSheet {
+    fn last_error_mut(&mut self) -> &mut Dictionary {
+        &mut self.last_error
+    }
+}
+
+/// Returns whether the error is worth trying the write again later, as the save was busy or is unavailable for a while.
+fn transient(err: &anyhow::Error) -> bool {
+    err.chain()
+        .find_map(|err| {
+            err.downcast_ref::<SqliteError>().or(match err.downcast_ref::<CampaignError>() {
+                Some(CampaignError::Store(err)) => Some(err),
+                _ => None,
+            })
+        })
+        .is_some_and(|err| err.is_busy() || err.is_unavailable())
+}
@@ -0,0 +1,182 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+use std::{
+    rc::Rc,
+    sync::mpsc::{self, Receiver, TryRecvError},
+};
+
+use anyhow::Context;
+use darkforge::{
+    campaign::{Campaign, Result},
+    stats::{HeatReading, SessionTotal, Stats},
+};
+use godot::prelude::*;
+
+use crate::{
+    error::Reporter,
+    forge::{self, Shared},
+    task::Durability,
+};
+
+/// Reads the statistics of a campaign database for dashboards to chart: the scores, coin and trauma of each session,
+/// and the heat of the crews over time.
+#[derive(GodotClass)]
+#[class(base=RefCounted)]
+pub struct StatsService {
+    base: Base<RefCounted>,
+    /// Path to the campaign database, `user://campaign.db` if empty.
+    #[var]
+    db: GString,
+    /// The error of the last call, see `DarkForgeError`, or an empty dictionary if it succeeded.
+    #[var(get)]
+    last_error: Dictionary,
+    forge: Option<Shared>,
+    /// Receives the statistics requested in the background, until they are delivered.
+    requested: Option<Receiver<Stats>>,
+}
+
+#[godot_api]
+impl IRefCounted for StatsService {
+    fn init(base: Base<RefCounted>) -> Self {
+        Self {
+            base,
+            db: GString::new(),
+            last_error: Dictionary::new(),
+            forge: None,
+            requested: None,
+        }
+    }
+}
+
+#[godot_api]
+impl StatsService {
+    /// Emitted when a call fails, with the error as recorded in `last_error`.
+    #[signal]
+    fn failed(error: Dictionary);
+
+    /// Emitted once the statistics requested with `request_stats` are read, as `stats` returns them.
+    #[signal]
+    fn stats_read(stats: Dictionary);
+
+    /// Returns the statistics as a dictionary with the `scores` ended, the `coin` gained and the `trauma` suffered as
+    /// arrays of dictionaries with the `session` number and the `total` over it, session 0 standing for what happened
+    /// before the first session, and the `heat` as an array of dictionaries with the `crew`, the `seq` and
+    /// `recorded_at` of the journal entry and the `heat`, oldest first. Returns an empty dictionary if the statistics
+    /// cannot be read.
+    #[func]
+    fn stats(&mut self) -> Dictionary {
+        self.run(async |campaign| campaign.stats().await)
+            .map(|stats| stats_to_dictionary(&stats))
+            .unwrap_or_default()
+    }
+
+    /// Reads the statistics in the background, so a dashboard over a long campaign does not stall the frame, and emits
+    /// `stats_read` with them. The read is aborted if the singleton's `cancel_tasks` is called first, and fails through
+    /// the singleton. Requires the service of the singleton. Returns whether the read was started.
+    #[func]
+    fn request_stats(&mut self) -> bool {
+        let requested = self
+            .forge
+            .clone()
+            .context("statistics are read in the background by the service of Dark Forge");
+        let Some(forge) = self.record("failed to read statistics", requested) else {
+            return false;
+        };
+
+        let (sender, receiver) = mpsc::channel();
+        forge
+            .borrow_mut()
+            .spawn(Durability::Cancellable, "read statistics", move |mut campaign| async move {
+                let stats = campaign.stats().await;
+                campaign.close().await?;
+                // The service may be gone by now, leaving no one to deliver the statistics to.
+                let _ = sender.send(stats?);
+                Ok(())
+            });
+        self.requested = Some(receiver);
+        true
+    }
+}
+
+impl StatsService {
+    /// Creates a service for the statistics of the campaign of the singleton.
+    pub(crate) fn attached(forge: &Shared) -> Gd<StatsService> {
+        Gd::from_init_fn(|base| Self {
+            base,
+            db: GString::new(),
+            last_error: Dictionary::new(),
+            forge: Some(Rc::clone(forge)),
+            requested: None,
+        })
+    }
+
+    /// Emits `stats_read` with the statistics read in the background, if they are read by now, forgetting the request
+    /// if it was aborted or failed. Called every frame by the singleton.
+    pub(crate) fn deliver(&mut self) {
+        let Some(receiver) = &self.requested else {
+            return;
+        };
+
+        match receiver.try_recv() {
+            Ok(stats) => {
+                self.requested = None;
+                let stats = stats_to_dictionary(&stats);
+                self.base_mut().emit_signal("stats_read", &[stats.to_variant()]);
+            }
+            Err(TryRecvError::Disconnected) => self.requested = None,
+            Err(TryRecvError::Empty) => {}
+        }
+    }
+
+    /// Opens the campaign and runs `f`, reporting any error.
+    fn run<T>(&mut self, f: impl AsyncFnOnce(&mut Campaign) -> Result<T>) -> Option<T> {
+        let result = forge::run(self.forge.as_ref(), &self.db, f);
+        self.record("failed to read statistics", result)
+    }
+}
+
+impl Reporter for StatsService {
+    fn last_error_mut(&mut self) -> &mut Dictionary {
+        &mut self.last_error
+    }
+}
+
+fn stats_to_dictionary(stats: &Stats) -> Dictionary {
+    let totals = |series: &[SessionTotal]| {
+        series
+            .iter()
+            .map(|total| {
+                dict! {
+                    "session": i64::try_from(total.session.unwrap_or_default()).unwrap_or(i64::MAX),
+                    "total": total.total,
+                }
+            })
+            .collect::<Array<Dictionary>>()
+    };
+
+    dict! {
+        "scores": totals(&stats.scores),
+        "coin": totals(&stats.coin),
+        "trauma": totals(&stats.trauma),
+        "heat": stats.heat.iter().map(heat_to_dictionary).collect::<Array<Dictionary>>(),
+    }
+}
+
+fn heat_to_dictionary(reading: &HeatReading) -> Dictionary {
+    dict! {
+        "crew": reading.crew.to_string(),
+        "seq": reading.seq,
+        "recorded_at": reading.recorded_at,
+        "heat": reading.heat,
+    }
+}
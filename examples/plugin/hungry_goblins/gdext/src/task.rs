@@ -0,0 +1,120 @@
+/*
+ * Dark Forge is a library and extension for Godot engine that implements the Blades in the Dark SRD by One Seven Design.
+ * Copyright (C) 2025 Pierre Fouilloux, Hibiscus Collective
+ *
+ * This program is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License along with this program.
+ * If not, see https://www.gnu.org/licenses/.
+ */
+use std::{future::Future, time::Duration};
+
+use tokio::{
+    runtime::Runtime,
+    task::{AbortHandle, JoinSet},
+};
+
+/// How long closing the campaign waits for the tasks writing to it before aborting them.
+pub(crate) const FLUSH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// What becomes of a task when the game changes scene or quits.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Durability {
+    /// Aborted on scene change or quit, e.g. reading statistics for a screen that is going away.
+    Cancellable,
+    /// Awaited before the campaign is closed, so what it writes reaches the save.
+    Flushed,
+}
+
+/// A task that failed, with the label it was spawned with.
+pub(crate) struct Failure {
+    pub(crate) label: String,
+    pub(crate) error: anyhow::Error,
+}
+
+/// Tracks the tasks spawned on the runtime of the forge, so none is left running when the campaign is closed.
+///
+/// The runtime runs on the main thread, so tasks make progress whenever the forge blocks on it and when it is polled
+/// once a frame. Tasks write in transactions: one aborted mid-write rolls back rather than leaving half a write behind.
+#[derive(Default)]
+pub(crate) struct Supervisor {
+    tasks: JoinSet<(String, anyhow::Result<()>)>,
+    cancellable: Vec<AbortHandle>,
+}
+
+impl Supervisor {
+    /// Spawns the task on the runtime.
+    pub(crate) fn spawn(
+        &mut self, runtime: &Runtime, durability: Durability, label: impl Into<String>,
+        task: impl Future<Output = anyhow::Result<()>> + Send + 'static,
+    ) {
+        let label = label.into();
+        let handle = self.tasks.spawn_on(async move { (label, task.await) }, runtime.handle());
+        if durability == Durability::Cancellable {
+            self.cancellable.push(handle);
+        }
+    }
+
+    /// Returns the number of tasks not reaped yet.
+    pub(crate) fn pending(&self) -> usize {
+        self.tasks.len()
+    }
+
+    /// Aborts the cancellable tasks.
+    pub(crate) fn cancel(&mut self) {
+        for handle in self.cancellable.drain(..) {
+            handle.abort();
+        }
+    }
+
+    /// Lets the tasks run until they all wait, then returns those that failed since last polled.
+    pub(crate) fn poll(&mut self, runtime: &Runtime) -> Vec<Failure> {
+        runtime.block_on(tokio::task::yield_now());
+
+        let mut failures = Vec::new();
+        while let Some(joined) = self.tasks.try_join_next() {
+            failures.extend(failure(joined));
+        }
+        self.cancellable.retain(|handle| !handle.is_finished());
+
+        failures
+    }
+
+    /// Aborts the cancellable tasks and waits up to `timeout` for the others to finish, aborting those still running
+    /// after it. Returns the tasks that failed, those aborted for running late included.
+    pub(crate) fn flush(&mut self, runtime: &Runtime, timeout: Duration) -> Vec<Failure> {
+        self.cancel();
+
+        let mut failures = Vec::new();
+        let flushed = runtime.block_on(tokio::time::timeout(timeout, async {
+            while let Some(joined) = self.tasks.join_next().await {
+                failures.extend(failure(joined));
+            }
+        }));
+        if flushed.is_err() {
+            failures.push(Failure {
+                label: "flush".into(),
+                error: anyhow::anyhow!("{} tasks were aborted after {timeout:?}", self.tasks.len()),
+            });
+            runtime.block_on(self.tasks.shutdown());
+        }
+
+        failures
+    }
+}
+
+/// Returns the failure of a joined task, if it failed rather than succeeded or was cancelled.
+fn failure(joined: Result<(String, anyhow::Result<()>), tokio::task::JoinError>) -> Option<Failure> {
+    match joined {
+        Ok((_, Ok(()))) => None,
+        Ok((label, Err(error))) => Some(Failure { label, error }),
+        Err(err) if err.is_cancelled() => None,
+        Err(err) => Some(Failure {
+            label: "task".into(),
+            error: anyhow::anyhow!("task panicked: {err}"),
+        }),
+    }
+}